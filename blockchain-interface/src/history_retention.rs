@@ -0,0 +1,48 @@
+use std::{cmp, collections::BTreeSet};
+
+use nimiq_keys::Address;
+use nimiq_primitives::policy::Policy;
+
+/// The part of the transaction history that a node keeps if it stores history at all.
+/// Epochs are only pruned once they are finalized, and never before a node without history
+/// would prune them.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum HistoryRetention {
+    /// The full transaction history is kept.
+    #[default]
+    Full,
+    /// The full transaction history is kept for the given number of finalized epochs only.
+    LastEpochs(u32),
+    /// The full transaction history is kept for all epochs that contain the given block or
+    /// come after it.
+    SinceBlock(u32),
+    /// Only the historic transactions that involve one of the given addresses are kept for
+    /// finalized epochs. These transactions can still be looked up, but no longer be proven,
+    /// since the history trees of those epochs are removed.
+    Addresses(BTreeSet<Address>),
+}
+
+impl HistoryRetention {
+    /// Returns whether the full transaction history is kept.
+    pub fn is_full(&self) -> bool {
+        matches!(self, HistoryRetention::Full)
+    }
+
+    /// Returns the last epoch whose full history is removed once the given epoch is finalized.
+    /// Epochs up to and including the returned one are pruned.
+    pub fn last_pruned_epoch(&self, finalized_epoch: u32) -> Option<u32> {
+        match self {
+            HistoryRetention::Full => None,
+            HistoryRetention::LastEpochs(num_epochs) => {
+                finalized_epoch.checked_sub(cmp::max(*num_epochs, 1))
+            }
+            HistoryRetention::SinceBlock(block_number) => {
+                let first_kept_epoch = Policy::epoch_at(*block_number);
+                first_kept_epoch
+                    .checked_sub(1)
+                    .map(|epoch| cmp::min(epoch, finalized_epoch.saturating_sub(1)))
+            }
+            HistoryRetention::Addresses(_) => finalized_epoch.checked_sub(1),
+        }
+    }
+}
@@ -12,6 +12,7 @@ use nimiq_primitives::policy::Policy;
 use nimiq_transaction::{
     historic_transaction::HistoricTransaction, inherent::Inherent, Transaction,
 };
+use nimiq_vrf::VrfSeed;
 use rand::{CryptoRng, Rng, RngCore};
 
 use crate::{interface::HistoryInterface, Blockchain};
@@ -81,6 +82,57 @@ impl BlockProducer {
         skip_block_proof: Option<SkipBlockProof>,
         // The rng seed. We need this parameterized in order to have determinism when running unit tests.
         rng: &mut R,
+    ) -> MicroBlock {
+        // Calculate the seed for this block by signing the previous block seed with the validator
+        // key. The VRF seed of a skip block is carried over since a new VRF seed would require a
+        // new leader.
+        let prev_seed = blockchain.head().seed();
+        let seed = if skip_block_proof.is_some() {
+            prev_seed.clone()
+        } else {
+            prev_seed.sign_next_with_rng(&self.signing_key, rng)
+        };
+
+        let mut block = Self::next_unsigned_micro_block(
+            blockchain,
+            timestamp,
+            equivocation_proofs,
+            transactions,
+            extra_data,
+            skip_block_proof,
+            seed,
+        );
+
+        if block.justification.is_none() {
+            // Signs the block header using the signing key.
+            let hash = block.header.hash::<Blake2bHash>();
+            let signature = self.signing_key.sign(hash.as_slice());
+            block.justification = Some(MicroJustification::Micro(signature));
+        }
+
+        block
+    }
+
+    /// Creates the next micro block with the given VRF seed, without signing it.
+    ///
+    /// Skip blocks are complete since they are justified by the skip block proof. Other micro
+    /// blocks are returned without a justification, which needs to be added by signing the
+    /// header hash with the signing key of the block producer.
+    pub fn next_unsigned_micro_block(
+        // The (upgradable) read locked guard to the blockchain.
+        blockchain: &Blockchain,
+        // The timestamp for the block.
+        timestamp: u64,
+        // Proofs of any misbehavior by malicious validators.
+        equivocation_proofs: Vec<EquivocationProof>,
+        // The transactions to be included in the block body.
+        transactions: Vec<Transaction>,
+        // Extra data for this block.
+        extra_data: Vec<u8>,
+        // Skip block proof.
+        skip_block_proof: Option<SkipBlockProof>,
+        // The VRF seed of this block. For skip blocks, this is the seed of the previous block.
+        seed: VrfSeed,
     ) -> MicroBlock {
         // The network ID stays unchanged for the whole blockchain.
         let network = blockchain.head().network();
@@ -95,27 +147,15 @@ impl BlockProducer {
         // Get the hash of the latest block. It can be any block type.
         let parent_hash = blockchain.head_hash();
 
-        // Calculate the seed for this block by signing the previous block seed with the validator
-        // key.
-        let prev_seed = blockchain.head().seed().clone();
-
         let skip_block_info = if skip_block_proof.is_some() {
             Some(SkipBlockInfo {
                 block_number,
-                vrf_entropy: prev_seed.entropy(),
+                vrf_entropy: blockchain.head().seed().entropy(),
             })
         } else {
             None
         };
 
-        let seed = if skip_block_proof.is_some() {
-            // VRF seed of a skip block is carried over since a new VRF seed would require a new
-            // leader.
-            prev_seed
-        } else {
-            prev_seed.sign_next_with_rng(&self.signing_key, rng)
-        };
-
         // Create the inherents from the equivocation proofs or skip block info.
         let inherents = blockchain.create_punishment_inherents(
             block_number,
@@ -179,20 +219,11 @@ impl BlockProducer {
             history_root,
         };
 
-        let justification = if let Some(skip_block_proof) = skip_block_proof {
-            MicroJustification::Skip(skip_block_proof)
-        } else {
-            // Signs the block header using the signing key.
-            let hash = header.hash::<Blake2bHash>();
-            let signature = self.signing_key.sign(hash.as_slice());
-            MicroJustification::Micro(signature)
-        };
-
         // Returns the micro block.
         MicroBlock {
             header,
             body: Some(body),
-            justification: Some(justification),
+            justification: skip_block_proof.map(MicroJustification::Skip),
         }
     }
 
@@ -236,6 +267,31 @@ impl BlockProducer {
         extra_data: Vec<u8>,
         // The rng seed. We need this parameterized in order to have determinism when running unit tests.
         rng: &mut R,
+    ) -> MacroBlock {
+        // Calculate the seed for this block by signing the previous block seed with the validator
+        // key.
+        let seed = blockchain
+            .head()
+            .seed()
+            .sign_next_with_rng(&self.signing_key, rng);
+
+        Self::next_macro_block_proposal_with_seed(blockchain, timestamp, round, extra_data, seed)
+    }
+
+    /// Creates a proposal for the next macro block (checkpoint or election) with the given VRF
+    /// seed. This doesn't require the keys of the block producer.
+    // Note: Needs to be called with the Blockchain lock held.
+    pub fn next_macro_block_proposal_with_seed(
+        // The (upgradable) read locked guard to the blockchain.
+        blockchain: &Blockchain,
+        // The timestamp for the block proposal.
+        timestamp: u64,
+        // The round for the block proposal.
+        round: u32,
+        // Extra data for this block.
+        extra_data: Vec<u8>,
+        // The VRF seed of the block, i.e. the signed seed of the previous block.
+        seed: VrfSeed,
     ) -> MacroBlock {
         // The network ID stays unchanged for the whole blockchain.
         let network = blockchain.head().network();
@@ -259,13 +315,6 @@ impl BlockProducer {
             None
         };
 
-        // Calculate the seed for this block by signing the previous block seed with the validator
-        // key.
-        let seed = blockchain
-            .head()
-            .seed()
-            .sign_next_with_rng(&self.signing_key, rng);
-
         // If this is an election block, calculate the validator set for the next epoch.
         let validators = match Policy::is_election_block_at(block_number) {
             true => Some(blockchain.next_validators(&seed)),
@@ -2,7 +2,9 @@ use std::sync::Arc;
 
 use nimiq_account::{Accounts, BlockLog};
 use nimiq_block::Block;
-use nimiq_blockchain_interface::{BlockchainError, BlockchainEvent, ChainInfo, ForkEvent};
+use nimiq_blockchain_interface::{
+    BlockchainError, BlockchainEvent, ChainInfo, ForkEvent, HistoryRetention,
+};
 use nimiq_database::{
     mdbx::{MdbxDatabase, MdbxReadTransaction, MdbxWriteTransaction},
     traits::{Database, WriteTransaction},
@@ -72,6 +74,8 @@ pub struct BlockchainConfig {
     pub max_epochs_stored: u32,
     /// Enables/Disables indices in the history store.
     pub index_history: bool,
+    /// Which part of the history is retained if `keep_history` is set.
+    pub history_retention: HistoryRetention,
 }
 
 impl Default for BlockchainConfig {
@@ -80,6 +84,7 @@ impl Default for BlockchainConfig {
             keep_history: true,
             max_epochs_stored: Policy::MIN_EPOCHS_STORED,
             index_history: true,
+            history_retention: HistoryRetention::Full,
         }
     }
 }
@@ -393,6 +398,29 @@ impl Blockchain {
     pub fn write_transaction(&self) -> MdbxWriteTransaction {
         self.db.write_transaction()
     }
+
+    /// Prunes the history store according to the configured [`HistoryRetention`] after the
+    /// given epoch has been finalized.
+    pub(crate) fn prune_history(&self, txn: &mut MdbxWriteTransaction, finalized_epoch: u32) {
+        let retention = &self.config.history_retention;
+        let Some(last_pruned_epoch) = retention.last_pruned_epoch(finalized_epoch) else {
+            return;
+        };
+
+        // Also prune epochs that were kept under a previous configuration.
+        let (first_block, _) = self.history_store.history_store_range(Some(txn));
+        for epoch_number in Policy::epoch_at(first_block)..=last_pruned_epoch {
+            match retention {
+                HistoryRetention::Addresses(addresses) => {
+                    self.history_store
+                        .remove_history_except(txn, epoch_number, addresses);
+                }
+                _ => {
+                    self.history_store.remove_history(txn, epoch_number);
+                }
+            }
+        }
+    }
 }
 
 pub trait TransactionVerificationCache: Send + Sync {
@@ -412,6 +412,11 @@ impl Blockchain {
             return Err(PushError::InvalidBlock(BlockError::AccountsHashMismatch));
         }
 
+        // Prune the History Store according to the configured retention.
+        if macro_block.is_election() {
+            this.prune_history(&mut txn, block.epoch_number());
+        }
+
         // Give up database transactions and push lock before creating notifications.
         txn.commit();
 
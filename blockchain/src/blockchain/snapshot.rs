@@ -0,0 +1,90 @@
+use nimiq_account::BlockLogger;
+use nimiq_blockchain_interface::AbstractBlockchain;
+use nimiq_database::traits::WriteTransaction;
+use nimiq_primitives::{key_nibbles::KeyNibbles, trie::trie_chunk::TrieChunkWithStart};
+
+use crate::Blockchain;
+
+/// Implements methods to export the state of the blockchain into a snapshot.
+impl Blockchain {
+    /// Reads the accounts trie as it was at the current macro head, in chunks of at most
+    /// `chunk_size` items. Each chunk carries a proof against the state root of the macro head
+    /// and is passed to `f`. Stops at the first error returned by `f`.
+    ///
+    /// Micro blocks on top of the macro head are reverted within a write transaction that is
+    /// aborted afterwards, such that the database is left untouched. The accounts trie must be
+    /// complete.
+    pub fn export_macro_head_state<E>(
+        &self,
+        chunk_size: usize,
+        mut f: impl FnMut(TrieChunkWithStart) -> Result<(), E>,
+    ) -> Result<(), E> {
+        let mut txn = self.write_transaction();
+
+        assert!(
+            self.state.accounts.is_complete(Some(&txn)),
+            "Can't export an incomplete accounts trie"
+        );
+
+        // Revert all micro blocks of the current batch.
+        let mut current = self.state.main_chain.clone();
+        while !current.head.is_macro() {
+            let block = &current.head;
+
+            if let Some(ref prev_missing_range) = current.prev_missing_range {
+                self.state
+                    .accounts
+                    .revert_chunk(&mut (&mut txn).into(), prev_missing_range.start.clone())
+                    .expect("Failed to revert chunk");
+            }
+
+            let mut block_logger = BlockLogger::new_reverted(block.hash(), block.block_number());
+            self.revert_accounts(
+                &self.state.accounts,
+                &mut (&mut txn).into(),
+                block,
+                &mut block_logger,
+            )
+            .expect("Failed to revert block");
+
+            current = self
+                .chain_store
+                .get_chain_info(block.parent_hash(), true, Some(&txn))
+                .expect("Corrupted store: Failed to find main chain predecessor");
+        }
+
+        assert_eq!(
+            current.head.hash(),
+            self.macro_head_hash(),
+            "Reverted past the macro head"
+        );
+        assert_eq!(
+            current.head.state_root(),
+            &self.state.accounts.get_root_hash_assert(Some(&txn)),
+            "Inconsistent state after reverting to the macro head"
+        );
+
+        let mut start_key = Some(KeyNibbles::ROOT);
+        let result = loop {
+            let Some(key) = start_key.take() else {
+                break Ok(());
+            };
+
+            let chunk = self
+                .state
+                .accounts
+                .get_chunk(key.clone(), chunk_size, Some(&txn));
+            start_key = chunk.end_key.clone();
+
+            if let Err(error) = f(TrieChunkWithStart {
+                chunk,
+                start_key: key,
+            }) {
+                break Err(error);
+            }
+        };
+
+        txn.abort();
+        result
+    }
+}
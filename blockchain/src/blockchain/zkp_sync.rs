@@ -281,6 +281,9 @@ impl Blockchain {
                 // Prune the History Store.
                 this.history_store
                     .remove_history(&mut txn, Policy::epoch_at(block_number).saturating_sub(1));
+            } else {
+                // Prune the History Store according to the configured retention.
+                this.prune_history(&mut txn, Policy::epoch_at(block_number));
             }
         }
 
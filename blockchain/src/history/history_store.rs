@@ -1,4 +1,4 @@
-use std::{cmp, ops::Range};
+use std::{cmp, collections::BTreeSet, ops::Range};
 
 use nimiq_block::MicroBlock;
 use nimiq_database::{
@@ -11,6 +11,7 @@ use nimiq_database::{
 };
 use nimiq_genesis::NetworkId;
 use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_keys::Address;
 use nimiq_mmr::{
     error::Error as MMRError,
     mmr::{
@@ -223,6 +224,41 @@ impl HistoryStore {
         // Fast removal of all transactions.
         txn.remove(&self.hist_tx_table, &epoch_number);
 
+        self.remove_last_leaves_of_epoch(txn, epoch_number);
+    }
+
+    /// Removes the historic transactions of the given epoch that don't involve any of the given
+    /// addresses. Returns the removed transactions together with their leaf indices.
+    pub(crate) fn remove_txns_except(
+        &self,
+        txn: &mut MdbxWriteTransaction,
+        epoch_number: u32,
+        addresses: &BTreeSet<Address>,
+    ) -> Vec<(u32, HistoricTransaction)> {
+        let removed_txs: Vec<(u32, HistoricTransaction)> =
+            WriteTransaction::dup_cursor(txn, &self.hist_tx_table)
+                .into_iter_dup_of(&epoch_number)
+                .filter(|(_, hist_tx)| !involves_any(&hist_tx.value, addresses))
+                .map(|(_, hist_tx)| (hist_tx.index, hist_tx.value))
+                .collect();
+
+        let mut cursor = WriteTransaction::dup_cursor(txn, &self.hist_tx_table);
+        for (leaf_index, _) in removed_txs.iter() {
+            if cursor.set_subkey(&epoch_number, leaf_index).is_some() {
+                cursor.remove();
+            }
+        }
+
+        removed_txs
+    }
+
+    /// Removes the last leaf indexes of all blocks of the given epoch. Afterwards, the blocks of
+    /// this epoch are no longer part of the range of the history store.
+    pub(crate) fn remove_last_leaves_of_epoch(
+        &self,
+        txn: &mut MdbxWriteTransaction,
+        epoch_number: u32,
+    ) {
         let mut cursor = WriteTransaction::cursor(txn, &self.last_leaf_table);
 
         let Some(first_block) = Policy::first_block_of(epoch_number) else {
@@ -498,6 +534,19 @@ impl HistoryInterface for HistoryStore {
         Some(())
     }
 
+    /// Removes the history tree of a given epoch and all historic transactions that don't involve
+    /// any of the given addresses.
+    fn remove_history_except(
+        &self,
+        txn: &mut MdbxWriteTransaction,
+        epoch_number: u32,
+        addresses: &BTreeSet<Address>,
+    ) {
+        self.remove_leaves_from_history(txn, epoch_number, None);
+        self.remove_txns_except(txn, epoch_number, addresses);
+        self.remove_last_leaves_of_epoch(txn, epoch_number);
+    }
+
     /// Gets the history tree root for a given epoch.
     fn get_history_tree_root(
         &self,
@@ -866,6 +915,23 @@ impl HistoryInterface for HistoryStore {
     }
 }
 
+/// Returns whether the historic transaction involves one of the given addresses, either as
+/// sender or recipient, as reward address or as the address of a punished validator.
+fn involves_any(hist_tx: &HistoricTransaction, addresses: &BTreeSet<Address>) -> bool {
+    match &hist_tx.data {
+        HistoricTransactionData::Basic(tx) => {
+            let tx = tx.get_raw_transaction();
+            addresses.contains(&tx.sender) || addresses.contains(&tx.recipient)
+        }
+        HistoricTransactionData::Reward(ev) => {
+            addresses.contains(&ev.reward_address) || addresses.contains(&ev.validator_address)
+        }
+        HistoricTransactionData::Penalize(ev) => addresses.contains(&ev.validator_address),
+        HistoricTransactionData::Jail(ev) => addresses.contains(&ev.validator_address),
+        HistoricTransactionData::Equivocation(_) => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use nimiq_database::mdbx::MdbxDatabase;
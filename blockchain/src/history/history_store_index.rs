@@ -1,4 +1,7 @@
-use std::{collections::BTreeMap, ops::Range};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+};
 
 use nimiq_block::MicroBlock;
 use nimiq_database::{
@@ -97,27 +100,38 @@ impl HistoryStoreIndex {
 
             let Some(hist_tx) = tx_opt else { continue };
 
-            // Remove it from the transaction hash database.
-            let tx_hash = hist_tx.tx_hash();
-            txn.remove(&self.tx_hash_table, &tx_hash);
+            self.remove_historic_tx(txn, epoch_number, leaf_index, &hist_tx);
+        }
+    }
 
-            let ordered_hash = OrderedHash {
-                index: EpochBasedIndex::new(epoch_number, leaf_index),
-                value: tx_hash.into(),
-            };
-            match &hist_tx.data {
-                HistoricTransactionData::Basic(tx) => {
-                    let tx = tx.get_raw_transaction();
-                    txn.remove_item(&self.address_table, &tx.sender, &ordered_hash);
-                    txn.remove_item(&self.address_table, &tx.recipient, &ordered_hash);
-                }
-                HistoricTransactionData::Reward(ev) => {
-                    txn.remove_item(&self.address_table, &ev.reward_address, &ordered_hash);
-                }
-                HistoricTransactionData::Equivocation(_)
-                | HistoricTransactionData::Penalize(_)
-                | HistoricTransactionData::Jail(_) => {}
+    /// Removes a historic transaction from the transaction hash and address databases.
+    fn remove_historic_tx(
+        &self,
+        txn: &mut MdbxWriteTransaction,
+        epoch_number: u32,
+        leaf_index: u32,
+        hist_tx: &HistoricTransaction,
+    ) {
+        // Remove it from the transaction hash database.
+        let tx_hash = hist_tx.tx_hash();
+        txn.remove(&self.tx_hash_table, &tx_hash);
+
+        let ordered_hash = OrderedHash {
+            index: EpochBasedIndex::new(epoch_number, leaf_index),
+            value: tx_hash.into(),
+        };
+        match &hist_tx.data {
+            HistoricTransactionData::Basic(tx) => {
+                let tx = tx.get_raw_transaction();
+                txn.remove_item(&self.address_table, &tx.sender, &ordered_hash);
+                txn.remove_item(&self.address_table, &tx.recipient, &ordered_hash);
+            }
+            HistoricTransactionData::Reward(ev) => {
+                txn.remove_item(&self.address_table, &ev.reward_address, &ordered_hash);
             }
+            HistoricTransactionData::Equivocation(_)
+            | HistoricTransactionData::Penalize(_)
+            | HistoricTransactionData::Jail(_) => {}
         }
     }
 
@@ -513,6 +527,28 @@ impl HistoryInterface for HistoryStoreIndex {
 
         Some(())
     }
+
+    /// Removes the history tree of a given epoch and all historic transactions that don't involve
+    /// any of the given addresses, including their index entries.
+    fn remove_history_except(
+        &self,
+        txn: &mut MdbxWriteTransaction,
+        epoch_number: u32,
+        addresses: &BTreeSet<Address>,
+    ) {
+        self.history_store
+            .remove_leaves_from_history(txn, epoch_number, None);
+
+        let removed_txs = self
+            .history_store
+            .remove_txns_except(txn, epoch_number, addresses);
+        for (leaf_index, hist_tx) in removed_txs.iter() {
+            self.remove_historic_tx(txn, epoch_number, *leaf_index, hist_tx);
+        }
+
+        self.history_store
+            .remove_last_leaves_of_epoch(txn, epoch_number);
+    }
 }
 
 impl HistoryIndexInterface for HistoryStoreIndex {
@@ -1220,6 +1256,72 @@ mod tests {
         assert_eq!(query_4.len(), 0);
     }
 
+    #[test]
+    fn remove_history_except_works() {
+        // Initialize History Store.
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let history_store = HistoryStoreIndex::new(env.clone(), NetworkId::UnitAlbatross);
+
+        // Create historic transactions.
+        let hist_txs = gen_hist_txs();
+        let hashes: Vec<_> = hist_txs.iter().map(|hist_tx| hist_tx.tx_hash()).collect();
+
+        // Add historic transactions to History Store.
+        let block_number = Policy::genesis_block_number() + 2;
+        let epoch_number = Policy::epoch_at(block_number);
+        let mut txn = env.write_transaction();
+        history_store.add_to_history(&mut txn, block_number, &hist_txs[5..]);
+        let root = history_store.get_history_tree_root(block_number, Some(&txn));
+
+        // Only keep the transactions of the sender.
+        let sender =
+            Address::from_user_friendly_address("NQ09 VF5Y 1PKV MRM4 5LE1 55KV P6R2 GXYJ XYQF")
+                .unwrap();
+        history_store.remove_history_except(
+            &mut txn,
+            epoch_number,
+            &BTreeSet::from([sender.clone()]),
+        );
+
+        // The transactions of the sender can still be looked up.
+        assert_eq!(
+            history_store.get_hist_tx_by_hash(&hashes[5], Some(&txn)),
+            Some(hist_txs[5].clone())
+        );
+        assert_eq!(
+            history_store.get_hist_tx_by_hash(&hashes[6], Some(&txn)),
+            Some(hist_txs[6].clone())
+        );
+        assert_eq!(
+            history_store
+                .get_tx_hashes_by_address(&sender, 99, Some(&txn))
+                .len(),
+            2
+        );
+
+        // All other transactions are removed from the store and the index.
+        for hash in &hashes[7..] {
+            assert_eq!(history_store.get_hist_tx_by_hash(hash, Some(&txn)), None);
+        }
+        let reward_address =
+            Address::from_user_friendly_address("NQ04 B79B R4FF 4NGU A9H0 2PT9 9ART 5A88 J73T")
+                .unwrap();
+        assert!(history_store
+            .get_tx_hashes_by_address(&reward_address, 99, Some(&txn))
+            .is_empty());
+
+        // The history tree and the block is no longer part of the history store.
+        assert_ne!(
+            history_store.get_history_tree_root(block_number, Some(&txn)),
+            root
+        );
+        assert_eq!(history_store.length_at(block_number, Some(&txn)), 0);
+        assert!(history_store
+            .get_block_transactions(block_number, Some(&txn))
+            .is_empty());
+        assert_eq!(history_store.history_store_range(Some(&txn)), (0, 0));
+    }
+
     #[test]
     fn prove_works() {
         // Initialize History Store.
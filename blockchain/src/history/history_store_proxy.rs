@@ -1,6 +1,9 @@
+use std::collections::BTreeSet;
+
 use nimiq_block::{Block, MicroBlock};
 use nimiq_database::mdbx::{MdbxReadTransaction, MdbxWriteTransaction};
 use nimiq_hash::Blake2bHash;
+use nimiq_keys::Address;
 use nimiq_mmr::{
     error::Error as MMRError,
     mmr::proof::{RangeProof, SizeProof},
@@ -70,6 +73,23 @@ impl HistoryInterface for HistoryStoreProxy {
         }
     }
 
+    /// Removes the history associated with a given epoch, except for the given addresses.
+    fn remove_history_except(
+        &self,
+        txn: &mut MdbxWriteTransaction,
+        epoch_number: u32,
+        addresses: &BTreeSet<Address>,
+    ) {
+        match self {
+            HistoryStoreProxy::WithIndex(index) => {
+                index.remove_history_except(txn, epoch_number, addresses)
+            }
+            HistoryStoreProxy::WithoutIndex(store) => {
+                store.remove_history_except(txn, epoch_number, addresses)
+            }
+        }
+    }
+
     /// Obtains the current history root at the given block.
     fn get_history_tree_root(
         &self,
@@ -1,3 +1,5 @@
+use std::collections::BTreeSet;
+
 use nimiq_block::{Block, MicroBlock};
 use nimiq_database::mdbx::{MdbxReadTransaction, MdbxWriteTransaction};
 use nimiq_hash::Blake2bHash;
@@ -36,6 +38,16 @@ pub trait HistoryInterface {
     /// Removes the full history associated with a given epoch.
     fn remove_history(&self, txn: &mut MdbxWriteTransaction, epoch_number: u32) -> Option<()>;
 
+    /// Removes the history associated with a given epoch, except for the historic transactions
+    /// that involve one of the given addresses. The history tree of the epoch is removed, so the
+    /// remaining transactions can't be proven anymore.
+    fn remove_history_except(
+        &self,
+        txn: &mut MdbxWriteTransaction,
+        epoch_number: u32,
+        addresses: &BTreeSet<Address>,
+    );
+
     /// Obtains the current history root at the given block.
     fn get_history_tree_root(
         &self,
@@ -1,20 +1,27 @@
+use std::{collections::BTreeSet, ops::RangeInclusive, sync::Arc};
+
 use nimiq_block::{
     Block, DoubleProposalProof, DoubleVoteProof, EquivocationProof, ForkProof, MacroHeader,
     MicroHeader,
 };
-use nimiq_blockchain::interface::HistoryInterface;
-use nimiq_blockchain_interface::{AbstractBlockchain, PushResult};
+use nimiq_blockchain::{
+    interface::{HistoryIndexInterface, HistoryInterface},
+    BlockProducer, Blockchain, BlockchainConfig,
+};
+use nimiq_blockchain_interface::{AbstractBlockchain, HistoryRetention, PushResult};
 use nimiq_bls::AggregateSignature;
-use nimiq_database::traits::WriteTransaction;
+use nimiq_database::{mdbx::MdbxDatabase, traits::WriteTransaction};
 use nimiq_genesis::NetworkId;
 use nimiq_hash::{Blake2bHash, Blake2sHash, Hash, HashOutput};
-use nimiq_keys::{KeyPair, PrivateKey};
+use nimiq_keys::{Address, KeyPair, PrivateKey};
 use nimiq_primitives::{policy::Policy, TendermintIdentifier, TendermintStep, TendermintVote};
 use nimiq_serde::Deserialize;
 use nimiq_test_log::test;
 use nimiq_test_utils::{
     block_production::TemporaryBlockProducer,
-    blockchain::{generate_transactions, produce_macro_blocks, validator_address},
+    blockchain::{
+        generate_transactions, produce_macro_blocks, signing_key, validator_address, voting_key,
+    },
     test_custom_block::{next_micro_block, BlockConfig},
 };
 use nimiq_transaction::{
@@ -24,6 +31,8 @@ use nimiq_transaction::{
     },
     ExecutedTransaction, Transaction,
 };
+use nimiq_utils::time::OffsetTime;
+use parking_lot::RwLock;
 
 fn key_pair_with_funds() -> KeyPair {
     let priv_key = PrivateKey::deserialize_from_vec(
@@ -426,3 +435,127 @@ fn it_pushes_macro_block_with_rewards() {
         i += 1;
     }
 }
+
+fn blockchain_with_retention(
+    env: &MdbxDatabase,
+    history_retention: HistoryRetention,
+) -> Arc<RwLock<Blockchain>> {
+    let config = BlockchainConfig {
+        history_retention,
+        ..Default::default()
+    };
+    let blockchain = Blockchain::new(
+        env.clone(),
+        config,
+        NetworkId::UnitAlbatross,
+        Arc::new(OffsetTime::new()),
+    )
+    .unwrap();
+    Arc::new(RwLock::new(blockchain))
+}
+
+/// Produces the given epochs and returns the history of each epoch at the time it was finalized.
+fn produce_epochs(
+    blockchain: &Arc<RwLock<Blockchain>>,
+    epochs: RangeInclusive<u32>,
+) -> Vec<Vec<HistoricTransaction>> {
+    let producer = BlockProducer::new(signing_key(), voting_key());
+    epochs
+        .map(|epoch_number| {
+            produce_macro_blocks(&producer, blockchain, Policy::batches_per_epoch() as usize);
+            let history = blockchain
+                .read()
+                .history_store
+                .get_epoch_transactions(epoch_number, None);
+            assert!(!history.is_empty());
+            history
+        })
+        .collect()
+}
+
+/// Asserts that exactly the epochs in `kept_epochs` still have their full history.
+fn assert_kept_epochs(
+    blockchain: &Arc<RwLock<Blockchain>>,
+    histories: &[Vec<HistoricTransaction>],
+    kept_epochs: &[u32],
+) {
+    let blockchain = blockchain.read();
+    for (epoch_number, history) in (1..).zip(histories) {
+        let stored = blockchain
+            .history_store
+            .get_epoch_transactions(epoch_number, None);
+        if kept_epochs.contains(&epoch_number) {
+            assert_eq!(&stored, history, "epoch {epoch_number} should be kept");
+        } else {
+            assert!(stored.is_empty(), "epoch {epoch_number} should be pruned");
+        }
+    }
+
+    // Pruned epochs are no longer part of the history store range.
+    if let Some(first_kept_epoch) = kept_epochs.iter().min().filter(|epoch| **epoch > 1) {
+        let (first_block, _) = blockchain.history_store.history_store_range(None);
+        assert_eq!(Policy::epoch_at(first_block), *first_kept_epoch);
+    }
+}
+
+#[test]
+fn full_retention_keeps_all_epochs() {
+    let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+    let blockchain = blockchain_with_retention(&env, HistoryRetention::Full);
+    let histories = produce_epochs(&blockchain, 1..=3);
+
+    assert_kept_epochs(&blockchain, &histories, &[1, 2, 3]);
+}
+
+#[test]
+fn last_epochs_retention_prunes_older_epochs() {
+    let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+    let blockchain = blockchain_with_retention(&env, HistoryRetention::LastEpochs(1));
+    let histories = produce_epochs(&blockchain, 1..=3);
+
+    assert_kept_epochs(&blockchain, &histories, &[3]);
+}
+
+#[test]
+fn since_block_retention_prunes_epochs_before_block() {
+    let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+    let block_number = Policy::election_block_of(1).unwrap() + 1;
+    let blockchain = blockchain_with_retention(&env, HistoryRetention::SinceBlock(block_number));
+    let histories = produce_epochs(&blockchain, 1..=3);
+
+    assert_kept_epochs(&blockchain, &histories, &[2, 3]);
+}
+
+#[test]
+fn addresses_retention_keeps_transactions_of_addresses() {
+    let unrelated_address =
+        Address::from_user_friendly_address("NQ09 VF5Y 1PKV MRM4 5LE1 55KV P6R2 GXYJ XYQF")
+            .unwrap();
+
+    for (address, is_kept) in [(validator_address(), true), (unrelated_address, false)] {
+        // The node starts with the full history and is restarted with the address retention.
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let blockchain = blockchain_with_retention(&env, HistoryRetention::Full);
+        let mut histories = produce_epochs(&blockchain, 1..=2);
+        drop(blockchain);
+
+        let retention = HistoryRetention::Addresses(BTreeSet::from([address]));
+        let blockchain = blockchain_with_retention(&env, retention);
+        histories.extend(produce_epochs(&blockchain, 3..=3));
+
+        // Epochs kept under the previous configuration are pruned as well.
+        assert_kept_epochs(&blockchain, &histories, &[3]);
+
+        // The rewards of the validator can still be looked up if its address is kept.
+        let blockchain = blockchain.read();
+        let history_index = blockchain.history_store.history_index().unwrap();
+        for hist_tx in histories[..2].iter().flatten() {
+            let stored = history_index.get_hist_tx_by_hash(&hist_tx.tx_hash(), None);
+            if is_kept {
+                assert_eq!(stored.as_ref(), Some(hist_tx));
+            } else {
+                assert_eq!(stored, None);
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use nimiq_block::Block;
+use nimiq_blockchain::Blockchain;
+use nimiq_blockchain_interface::{AbstractBlockchain, ChunksPushResult, PushResult};
+use nimiq_genesis::NetworkId;
+use nimiq_keys::{KeyPair, PrivateKey};
+use nimiq_serde::Deserialize;
+use nimiq_test_log::test;
+use nimiq_test_utils::{
+    block_production::TemporaryBlockProducer,
+    blockchain::{generate_transactions, produce_macro_blocks},
+};
+
+fn key_pair_with_funds() -> KeyPair {
+    let priv_key = PrivateKey::deserialize_from_vec(
+        &hex::decode("6c9320ac201caf1f8eaa5b05f5d67a9e77826f3f6be266a0ecccc20416dc6587").unwrap(),
+    )
+    .unwrap();
+    priv_key.into()
+}
+
+#[test]
+fn it_exports_the_state_at_the_macro_head() {
+    let temp_producer1 = TemporaryBlockProducer::new();
+    produce_macro_blocks(&temp_producer1.producer, &temp_producer1.blockchain, 1);
+    let macro_block = temp_producer1.blockchain.read().macro_head();
+
+    // Change the state on top of the macro block.
+    let block_number = temp_producer1.blockchain.read().block_number();
+    let txs = generate_transactions(
+        &key_pair_with_funds(),
+        block_number,
+        NetworkId::UnitAlbatross,
+        5,
+        0,
+    );
+    temp_producer1.next_block_with_txs(vec![], false, txs);
+    let head_state_root = temp_producer1
+        .blockchain
+        .read()
+        .state
+        .accounts
+        .get_root_hash_assert(None);
+    assert_ne!(head_state_root, macro_block.header.state_root);
+
+    let mut chunks = vec![];
+    temp_producer1
+        .blockchain
+        .read()
+        .export_macro_head_state(2, |chunk| {
+            chunks.push(chunk);
+            Ok::<_, ()>(())
+        })
+        .unwrap();
+    assert!(chunks.len() > 1);
+    assert!(chunks.last().unwrap().chunk.end_key.is_none());
+
+    // The exporting blockchain is left untouched.
+    assert_eq!(
+        temp_producer1
+            .blockchain
+            .read()
+            .state
+            .accounts
+            .get_root_hash_assert(None),
+        head_state_root
+    );
+
+    // The chunks rebuild the state of the macro block.
+    let temp_producer2 = TemporaryBlockProducer::new_incomplete();
+    assert_eq!(
+        Blockchain::push_macro(
+            temp_producer2.blockchain.upgradable_read(),
+            Block::Macro(macro_block.clone()),
+        ),
+        Ok(PushResult::Extended)
+    );
+
+    let num_chunks = chunks.len();
+    assert_eq!(
+        temp_producer2
+            .blockchain
+            .read()
+            .commit_chunks(chunks, &macro_block.hash()),
+        Ok(ChunksPushResult::Chunks(num_chunks, 0))
+    );
+
+    let blockchain2 = temp_producer2.blockchain.read();
+    assert!(blockchain2.state.accounts.is_complete(None));
+    assert_eq!(
+        blockchain2.state.accounts.get_root_hash_assert(None),
+        macro_block.header.state_root
+    );
+}
+
+#[test]
+fn it_stops_at_the_first_error() {
+    let temp_producer = TemporaryBlockProducer::new();
+    produce_macro_blocks(&temp_producer.producer, &temp_producer.blockchain, 1);
+
+    let mut num_chunks = 0;
+    let result = temp_producer
+        .blockchain
+        .read()
+        .export_macro_head_state(1, |_| {
+            num_chunks += 1;
+            Err("sink failed")
+        });
+
+    assert_eq!(result, Err("sink failed"));
+    assert_eq!(num_chunks, 1);
+}
@@ -4,7 +4,11 @@ use log::info;
 use nimiq::prover::prover_main;
 pub use nimiq::{
     client::Client,
-    config::{command_line::CommandLine, config::ClientConfig, config_file::ConfigFile},
+    config::{
+        command_line::{Command, CommandLine},
+        config::ClientConfig,
+        config_file::ConfigFile,
+    },
     error::Error,
     extras::{
         logging::{initialize_logging, log_error_cause_chain},
@@ -59,6 +63,63 @@ async fn main_inner() -> Result<(), Error> {
     let config = builder.build()?;
     log::debug!("Final configuration: {:#?}", config);
 
+    // Run maintenance commands and exit.
+    match command_line.command {
+        Some(Command::ExportSnapshot { file }) => {
+            let snapshot = nimiq::snapshot::export_snapshot_to_file(&config, &file)?;
+            info!(
+                block_number = snapshot.block_number,
+                block_hash = %snapshot.block_hash,
+                num_chunks = snapshot.num_chunks,
+                num_items = snapshot.num_items,
+                "Exported snapshot to {}",
+                file.display(),
+            );
+            return Ok(());
+        }
+        Some(Command::ImportSnapshot { file }) => {
+            let snapshot = nimiq::snapshot::import_snapshot_from_file(&config, &file)?;
+            info!(
+                block_number = snapshot.block_number,
+                block_hash = %snapshot.block_hash,
+                num_chunks = snapshot.num_chunks,
+                num_items = snapshot.num_items,
+                "Imported snapshot from {}",
+                file.display(),
+            );
+            return Ok(());
+        }
+        Some(Command::ExportHistory {
+            dir,
+            from_epoch,
+            to_epoch,
+        }) => {
+            let archive = nimiq::history_archive::export_history_archive(
+                &config, &dir, from_epoch, to_epoch,
+            )?;
+            info!(
+                first_epoch = archive.first_epoch,
+                last_epoch = archive.last_epoch,
+                num_items = archive.num_items,
+                "Exported history archive to {}",
+                dir.display(),
+            );
+            return Ok(());
+        }
+        Some(Command::ImportHistory { dir }) => {
+            let archive = nimiq::history_archive::import_history_archive(&config, &dir)?;
+            info!(
+                first_epoch = archive.first_epoch,
+                last_epoch = archive.last_epoch,
+                num_items = archive.num_items,
+                "Imported history archive from {}",
+                dir.display(),
+            );
+            return Ok(());
+        }
+        None => {}
+    }
+
     // Clone config for RPC and metrics server
     let rpc_config = config.rpc_server.clone();
     let metrics_config = config.metrics_server.clone();
@@ -98,6 +159,16 @@ async fn main_inner() -> Result<(), Error> {
     let zkp_component = client.take_zkp_component().unwrap();
     spawn(zkp_component); //ITODO get metrics on this? ask JD
 
+    // Keep the validator uptime index up to date.
+    if let Some(uptime_index) = client.uptime_index() {
+        spawn(uptime_index.run());
+    }
+
+    // Keep track of equivocations and gossip the detected proofs.
+    if let Some(equivocation_monitor) = client.equivocation_monitor() {
+        spawn(equivocation_monitor.run(client.network()));
+    }
+
     // Start validator
     let val_metric_monitor = tokio_metrics::TaskMonitor::new();
     if let Some(validator) = client.take_validator() {
@@ -144,6 +215,7 @@ async fn main_inner() -> Result<(), Error> {
             metrics_config.addr,
             client.blockchain(),
             mempool,
+            client.validator_proxy(),
             client.consensus_proxy(),
             client.network(),
             &nimiq_task_metric,
@@ -0,0 +1,120 @@
+use std::collections::HashSet;
+
+use nimiq_keys::Address;
+use nimiq_network_interface::network::Network;
+
+use crate::messages::AddressNotification;
+
+/// The addresses we are subscribed to at remote peers, together with the peers serving the
+/// subscriptions and the last block we have seen notifications for.
+///
+/// This allows to resume the subscriptions at another peer without missing notifications when
+/// the serving peers disconnect.
+pub(crate) struct AddressSubscriptions<N: Network> {
+    /// The addresses we are subscribed to.
+    addresses: HashSet<Address>,
+    /// The peers that accepted our subscriptions.
+    serving_peers: HashSet<N::PeerId>,
+    /// The number of the last block we have seen notifications for.
+    last_seen_block: u32,
+}
+
+impl<N: Network> Default for AddressSubscriptions<N> {
+    fn default() -> Self {
+        Self {
+            addresses: HashSet::new(),
+            serving_peers: HashSet::new(),
+            last_seen_block: 0,
+        }
+    }
+}
+
+impl<N: Network> AddressSubscriptions<N> {
+    /// Adds addresses we subscribed to at the given block.
+    pub fn add_addresses(&mut self, addresses: &[Address], head_block_number: u32) {
+        if self.addresses.is_empty() {
+            self.last_seen_block = head_block_number;
+        }
+        self.addresses.extend(addresses.iter().cloned());
+    }
+
+    pub fn remove_addresses(&mut self, addresses: &[Address]) {
+        for address in addresses {
+            self.addresses.remove(address);
+        }
+    }
+
+    pub fn addresses(&self) -> Vec<Address> {
+        self.addresses.iter().cloned().collect()
+    }
+
+    pub fn last_seen_block(&self) -> u32 {
+        self.last_seen_block
+    }
+
+    pub fn add_serving_peer(&mut self, peer_id: N::PeerId) {
+        self.serving_peers.insert(peer_id);
+    }
+
+    /// Removes a serving peer. Returns true if the subscriptions need to be migrated to another
+    /// peer, i.e. if no serving peer is left.
+    pub fn remove_serving_peer(&mut self, peer_id: &N::PeerId) -> bool {
+        self.serving_peers.remove(peer_id) && self.needs_migration()
+    }
+
+    /// Returns true if we have subscriptions that no peer is serving.
+    pub fn needs_migration(&self) -> bool {
+        self.serving_peers.is_empty() && !self.addresses.is_empty()
+    }
+
+    /// Notes the blocks of a received notification.
+    pub fn on_notification(&mut self, notification: &AddressNotification) {
+        if let Some(block_number) = notification
+            .receipts
+            .iter()
+            .map(|(_, block_number)| *block_number)
+            .max()
+        {
+            self.last_seen_block = self.last_seen_block.max(block_number);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimiq_hash::Blake2bHash;
+    use nimiq_network_mock::{MockNetwork, MockPeerId};
+    use nimiq_test_log::test;
+
+    use super::*;
+    use crate::messages::NotificationEvent;
+
+    #[test]
+    fn it_tracks_subscriptions_for_migration() {
+        let mut subscriptions = AddressSubscriptions::<MockNetwork>::default();
+        let address = Address::burn_address();
+        let (peer1, peer2) = (MockPeerId(1), MockPeerId(2));
+
+        subscriptions.add_addresses(&[address.clone()], 10);
+        subscriptions.add_serving_peer(peer1);
+        subscriptions.add_serving_peer(peer2);
+        assert_eq!(subscriptions.last_seen_block(), 10);
+
+        subscriptions.on_notification(&AddressNotification {
+            event: NotificationEvent::BlockchainExtend,
+            receipts: vec![(Blake2bHash::default(), 12)],
+        });
+        assert_eq!(subscriptions.last_seen_block(), 12);
+
+        // Only once the last serving peer left, the subscriptions need to be migrated.
+        assert!(!subscriptions.remove_serving_peer(&peer1));
+        assert!(subscriptions.remove_serving_peer(&peer2));
+        assert!(subscriptions.needs_migration());
+
+        // Without addresses, there is nothing to migrate.
+        subscriptions.add_serving_peer(peer1);
+        subscriptions.remove_addresses(&[address]);
+        assert!(!subscriptions.remove_serving_peer(&peer1));
+        assert!(!subscriptions.needs_migration());
+    }
+}
@@ -6,9 +6,9 @@ use std::{
     },
 };
 
-use futures::stream::BoxStream;
+use futures::{stream::BoxStream, StreamExt};
 use nimiq_account::{Account, Staker, Validator};
-use nimiq_block::Block;
+use nimiq_block::{Block, MacroBlock};
 use nimiq_blockchain_interface::AbstractBlockchain;
 use nimiq_blockchain_proxy::BlockchainProxy;
 use nimiq_hash::Blake2bHash;
@@ -23,30 +23,64 @@ use nimiq_transaction::{
     historic_transaction::HistoricTransaction, ControlTransaction, ControlTransactionTopic,
     Transaction, TransactionTopic,
 };
+use parking_lot::RwLock;
 use tokio::sync::{
     broadcast::Sender as BroadcastSender, mpsc::Sender as MpscSender,
-    oneshot::channel as oneshot_channel,
+    oneshot::channel as oneshot_channel, watch::Sender as WatchSender,
 };
-use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::{BroadcastStream, WatchStream};
 
 use super::{ConsensusRequest, ResolveBlockError, ResolveBlockRequest};
+pub use crate::consensus::remote_data_store::ProvenTrieValues;
 use crate::{
-    consensus::remote_data_store::RemoteDataStore,
+    consensus::{address_subscriptions::AddressSubscriptions, remote_data_store::RemoteDataStore},
+    error::TrieProofError,
     messages::{
         AddressNotification, AddressSubscriptionOperation, AddressSubscriptionTopic,
-        RequestBlocksProof, RequestSubscribeToAddress, RequestTransactionReceiptsByAddress,
-        RequestTransactionsProof, ResponseBlocksProof,
+        RequestBlockByNumber, RequestBlocksProof, RequestHistoryRange,
+        RequestReplayAddressNotifications, RequestSubscribeToAddress,
+        RequestTransactionReceiptsByAddress, RequestTransactionsProof, ResponseBlockByNumber,
+        ResponseBlocksProof,
     },
+    sync::progress::SyncStatus,
     ConsensusEvent,
 };
 
+/// How final the inclusion of a transaction in the chain is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConfirmationLevel {
+    /// The transaction is included in a micro block that is not finalized yet.
+    Micro,
+    /// The transaction is included in a batch of the current epoch that is finalized by a
+    /// checkpoint block.
+    MacroFinalized,
+    /// The transaction is included in an epoch that is finalized by an election block.
+    EpochFinalized,
+}
+
+impl ConfirmationLevel {
+    /// Returns the confirmation level of a transaction at `block_number`, given the current
+    /// election and macro heads.
+    pub fn at(block_number: u32, election_head: u32, macro_head: u32) -> Self {
+        if block_number <= election_head {
+            ConfirmationLevel::EpochFinalized
+        } else if block_number <= macro_head {
+            ConfirmationLevel::MacroFinalized
+        } else {
+            ConfirmationLevel::Micro
+        }
+    }
+}
+
 pub struct ConsensusProxy<N: Network> {
     pub blockchain: BlockchainProxy,
     pub network: Arc<N>,
     pub(crate) established_flag: Arc<AtomicBool>,
     pub(crate) synced_validity_window_flag: Arc<AtomicBool>,
     pub(crate) events: BroadcastSender<ConsensusEvent>,
+    pub(crate) sync_status: Arc<WatchSender<SyncStatus<N::PeerId>>>,
     pub(crate) request: MpscSender<ConsensusRequest<N>>,
+    pub(crate) address_subscriptions: Arc<RwLock<AddressSubscriptions<N>>>,
 }
 
 impl<N: Network> Clone for ConsensusProxy<N> {
@@ -57,7 +91,9 @@ impl<N: Network> Clone for ConsensusProxy<N> {
             established_flag: Arc::clone(&self.established_flag),
             synced_validity_window_flag: Arc::clone(&self.synced_validity_window_flag),
             events: self.events.clone(),
+            sync_status: Arc::clone(&self.sync_status),
             request: self.request.clone(),
+            address_subscriptions: Arc::clone(&self.address_subscriptions),
         }
     }
 }
@@ -78,6 +114,17 @@ impl<N: Network> ConsensusProxy<N> {
         self.established_flag.load(Ordering::Acquire)
     }
 
+    /// Returns the current progress of the synchronization.
+    pub fn sync_status(&self) -> SyncStatus<N::PeerId> {
+        self.sync_status.borrow().clone()
+    }
+
+    /// Subscribes to changes of the synchronization progress. The stream yields the current
+    /// status first.
+    pub fn subscribe_sync_status(&self) -> WatchStream<SyncStatus<N::PeerId>> {
+        WatchStream::new(self.sync_status.subscribe())
+    }
+
     /// Returns true if the node is ready to start the validator/mempool.
     pub fn is_ready_for_validation(&self) -> bool {
         self.established_flag.load(Ordering::Acquire)
@@ -99,7 +146,15 @@ impl<N: Network> ConsensusProxy<N> {
             )
             .await;
 
-        txn_stream.unwrap()
+        // Keep track of the notifications we have seen, such that missed notifications can be
+        // replayed when our subscriptions are migrated to another peer.
+        let address_subscriptions = Arc::clone(&self.address_subscriptions);
+        txn_stream
+            .unwrap()
+            .inspect(move |(notification, _)| {
+                address_subscriptions.write().on_notification(notification)
+            })
+            .boxed()
     }
 
     pub async fn request_transaction_receipts_by_address(
@@ -187,6 +242,33 @@ impl<N: Network> ConsensusProxy<N> {
         }
     }
 
+    /// Verifies the inclusion of an arbitrary historic transaction given only its hash.
+    ///
+    /// The inclusion proof is requested from history nodes. If the transaction is in a finalized
+    /// epoch, the proving election block is verified against our election head with a block
+    /// proof. Returns the transaction and how final its inclusion is.
+    pub async fn verify_transaction_inclusion(
+        &self,
+        tx_hash: Blake2bHash,
+        min_peers: usize,
+    ) -> Result<(HistoricTransaction, ConfirmationLevel), RequestError> {
+        // The heads are read before the proof is verified, such that the confirmation level is
+        // never higher than what was actually proven.
+        let (election_head, macro_head) = {
+            let blockchain = self.blockchain.read();
+            (
+                blockchain.election_head().block_number(),
+                blockchain.macro_head().block_number(),
+            )
+        };
+
+        let hist_tx = self.request_transaction_by_hash(tx_hash, min_peers).await?;
+        let confirmation_level =
+            ConfirmationLevel::at(hist_tx.block_number, election_head, macro_head);
+
+        Ok((hist_tx, confirmation_level))
+    }
+
     async fn get_peers_for_service(
         &self,
         services: Services,
@@ -207,6 +289,56 @@ impl<N: Network> ConsensusProxy<N> {
             })
     }
 
+    /// Returns the connected peers with a transaction index that store the history of all blocks
+    /// since `first_block`. Peers that only provide partial history are asked for the range of
+    /// blocks they store and are skipped if it doesn't cover the requested blocks.
+    async fn get_peers_for_history(
+        &self,
+        first_block: u32,
+        min_peers: usize,
+    ) -> Result<Vec<<N as Network>::PeerId>, RequestError> {
+        let mut peers = vec![];
+        for peer_id in self
+            .get_peers_for_service(Services::TRANSACTION_INDEX, min_peers)
+            .await?
+        {
+            if self
+                .network
+                .peer_provides_services(peer_id, Services::HISTORY)
+            {
+                peers.push(peer_id);
+                continue;
+            }
+            if !self
+                .network
+                .peer_provides_services(peer_id, Services::PARTIAL_HISTORY)
+            {
+                continue;
+            }
+
+            match self
+                .network
+                .request::<RequestHistoryRange>(RequestHistoryRange {}, peer_id)
+                .await
+            {
+                Ok(range) if range.first_block <= first_block => peers.push(peer_id),
+                Ok(range) => {
+                    log::debug!(
+                        peer = %peer_id,
+                        first_block = range.first_block,
+                        requested_block = first_block,
+                        "Peer doesn't store the requested history"
+                    );
+                }
+                Err(error) => {
+                    log::debug!(peer = %peer_id, %error, "There was an error requesting the history range from peer");
+                }
+            }
+        }
+
+        Ok(peers)
+    }
+
     pub async fn prove_transactions_from_receipts(
         &self,
         receipts: Vec<(Blake2bHash, Option<u32>)>,
@@ -240,17 +372,20 @@ impl<N: Network> ConsensusProxy<N> {
         let can_query_full_nodes = receipts
             .iter()
             .all(|(_, block_number)| block_number.unwrap_or(0) > full_node_cutoff);
-        let peer_required_service = if can_query_full_nodes {
-            Services::FULL_BLOCKS
+        let peers = if can_query_full_nodes {
+            self.get_peers_for_service(Services::FULL_BLOCKS, min_peers)
+                .await?
         } else {
-            Services::TRANSACTION_INDEX
+            let first_block = receipts
+                .iter()
+                .map(|(_, block_number)| block_number.unwrap_or(0))
+                .min()
+                .unwrap_or(0);
+            self.get_peers_for_history(first_block, min_peers).await?
         };
 
         // We obtain a list of connected peers that could satisfy our request and perform the request to each one:
-        for peer_id in self
-            .get_peers_for_service(peer_required_service, min_peers)
-            .await?
-        {
+        for peer_id in peers {
             // This is the structure where we group transactions by their proving block number
             let mut hashes_by_block = HashMap::new();
 
@@ -466,6 +601,35 @@ impl<N: Network> ConsensusProxy<N> {
         Ok(accounts)
     }
 
+    /// Gets a set of accounts given their addresses, together with the block they were proven
+    /// against. If an account was not found, then `None` is returned in its corresponding entry.
+    ///
+    /// The accounts are taken from the first valid proof. Proofs are verified against the state
+    /// root of a block of our chain, so the accounts don't need to be confirmed by other peers.
+    pub async fn request_proven_accounts(
+        &self,
+        addresses: Vec<Address>,
+        min_peers: usize,
+    ) -> Result<ProvenTrieValues<Address, Account>, TrieProofError> {
+        let mut keys = HashMap::<KeyNibbles, Address>::from_iter(
+            addresses
+                .iter()
+                .map(|address| (KeyNibbles::from(address), address.clone())),
+        );
+        let proven: ProvenTrieValues<KeyNibbles, Account> = RemoteDataStore::get_proven_trie(
+            Arc::clone(&self.network),
+            self.blockchain.clone(),
+            &keys.keys().cloned().collect::<Vec<KeyNibbles>>(),
+            min_peers,
+        )
+        .await?;
+
+        Ok(proven.map_keys(|key| {
+            keys.remove(&key)
+                .expect("Key must be in the proven accounts")
+        }))
+    }
+
     /// Gets a set of validators given their addresses. The returned type is a
     /// BTreeMap of addresses to an optional `Validator`. If a validator was not
     /// found, then `None` is returned in its corresponding entry.
@@ -522,6 +686,83 @@ impl<N: Network> ConsensusProxy<N> {
                 .await?
         };
 
+        let head_block_number = self.blockchain.read().block_number();
+        self.address_subscriptions
+            .write()
+            .add_addresses(&addresses, head_block_number);
+
+        if self.subscribe_at_peers(addresses, peers).await {
+            Ok(())
+        } else {
+            Err(RequestError::OutboundRequest(
+                OutboundRequestError::NoReceiver,
+            ))
+        }
+    }
+
+    /// Migrates our address subscriptions to other peers, e.g. because the peers serving them
+    /// disconnected. The peers replay the notifications we missed since the last block we have
+    /// seen notifications for, which is why peers with a history index are preferred.
+    pub async fn resubscribe_to_addresses(&self) -> Result<(), RequestError> {
+        let (addresses, last_seen_block) = {
+            let address_subscriptions = self.address_subscriptions.read();
+            (
+                address_subscriptions.addresses(),
+                address_subscriptions.last_seen_block(),
+            )
+        };
+        if addresses.is_empty() {
+            return Ok(());
+        }
+
+        let mut peers = self.get_peers_for_service(Services::FULL_BLOCKS, 1).await?;
+        peers.sort_by_key(|peer_id| {
+            !self
+                .network
+                .peer_provides_services(*peer_id, Services::TRANSACTION_INDEX)
+        });
+
+        // A single peer serving the subscriptions is enough. If no peer accepts them, the
+        // consensus migrates them once another peer joins.
+        for peer_id in peers {
+            if self
+                .subscribe_at_peers(addresses.clone(), vec![peer_id])
+                .await
+            {
+                // Peers that don't support replays only serve notifications from now on.
+                let replay = self
+                    .network
+                    .request::<RequestReplayAddressNotifications>(
+                        RequestReplayAddressNotifications { last_seen_block },
+                        peer_id,
+                    )
+                    .await;
+                if !matches!(replay, Ok(Ok(()))) {
+                    log::debug!(
+                        %peer_id,
+                        last_seen_block,
+                        "Peer did not replay missed address notifications"
+                    );
+                }
+
+                log::debug!(
+                    %peer_id,
+                    last_seen_block,
+                    num_addresses = addresses.len(),
+                    "Migrated address subscriptions"
+                );
+                return Ok(());
+            }
+        }
+
+        Err(RequestError::OutboundRequest(
+            OutboundRequestError::NoReceiver,
+        ))
+    }
+
+    /// Subscribes to the addresses at all the given peers. Returns true if at least one peer
+    /// accepted the subscription.
+    async fn subscribe_at_peers(&self, addresses: Vec<Address>, peers: Vec<N::PeerId>) -> bool {
         let mut success = false;
 
         // Subscribe to all peers that could provide the necessary services
@@ -540,6 +781,7 @@ impl<N: Network> ConsensusProxy<N> {
             match response {
                 Ok(Ok(())) => {
                     // Done, we are subscribed at least to one peer, continue with the next one
+                    self.address_subscriptions.write().add_serving_peer(peer_id);
                     success = true;
                     continue;
                 }
@@ -554,13 +796,7 @@ impl<N: Network> ConsensusProxy<N> {
                 }
             }
         }
-        if success {
-            Ok(())
-        } else {
-            Err(RequestError::OutboundRequest(
-                OutboundRequestError::NoReceiver,
-            ))
-        }
+        success
     }
 
     pub async fn unsubscribe_from_addresses(
@@ -568,6 +804,10 @@ impl<N: Network> ConsensusProxy<N> {
         addresses: Vec<Address>,
         min_peers: usize,
     ) -> Result<(), RequestError> {
+        self.address_subscriptions
+            .write()
+            .remove_addresses(&addresses);
+
         // Unsubscribe given addresses from all peers
         // Note: this does not mean that we will fully unsubscribe  from a peer,
         // we will unsubscribe  only from the addresses that were supplied to this function
@@ -591,6 +831,193 @@ impl<N: Network> ConsensusProxy<N> {
         Ok(())
     }
 
+    /// Gets the block at the given height. If the block is not available in our blockchain, it
+    /// is requested from the network together with a proof that it is part of our chain.
+    ///
+    /// The block is proven by the hash chain up to the macro block that finalizes its batch. That
+    /// macro block is in turn proven against our election head, which allows to obtain blocks
+    /// that were already pruned from the light blockchain.
+    pub async fn request_block_at(
+        &self,
+        block_number: u32,
+        include_body: bool,
+        min_peers: usize,
+    ) -> Result<Block, RequestError> {
+        let blockchain = self.blockchain.read();
+        if let Ok(block) = blockchain.get_block_at(block_number, include_body) {
+            if !include_body || block.body().is_some() {
+                return Ok(block);
+            }
+        }
+        let election_head = blockchain.election_head();
+        let current_block_number = blockchain.block_number();
+
+        // We drop the blockchain lock because it's no longer needed while we request the block
+        drop(blockchain);
+
+        if block_number > current_block_number {
+            log::error!(
+                head = current_block_number,
+                block_number,
+                "Can't request a block from the future"
+            );
+            return Err(RequestError::OutboundRequest(OutboundRequestError::Other(
+                "Can't request a block from the future".to_string(),
+            )));
+        }
+
+        let full_node_cutoff = election_head.block_number() - Policy::blocks_per_epoch() + 1;
+        let peer_required_service = if block_number > full_node_cutoff {
+            Services::FULL_BLOCKS
+        } else {
+            Services::HISTORY
+        };
+
+        for peer_id in self
+            .get_peers_for_service(peer_required_service, min_peers)
+            .await?
+        {
+            let response = self
+                .network
+                .request::<RequestBlockByNumber>(
+                    RequestBlockByNumber {
+                        block_number,
+                        include_body,
+                        election_head: election_head.block_number(),
+                    },
+                    peer_id,
+                )
+                .await;
+
+            match response {
+                Ok(Ok(response)) => {
+                    if self.verify_block_by_number(
+                        &response,
+                        block_number,
+                        include_body,
+                        &election_head,
+                    ) {
+                        let block = response
+                            .blocks
+                            .into_iter()
+                            .next()
+                            .expect("The block was verified");
+                        return Ok(block);
+                    }
+                    log::warn!(peer = %peer_id, block_number, "The block proof from this peer did not verify");
+                }
+                Ok(Err(error)) => {
+                    log::debug!(peer = %peer_id, %error, "We requested a block by number but the peer couldn't provide it");
+                }
+                Err(error) => {
+                    log::debug!(peer = %peer_id, %error, "There was an error requesting a block by number from peer");
+                }
+            }
+        }
+
+        Err(RequestError::OutboundRequest(
+            OutboundRequestError::NoReceiver,
+        ))
+    }
+
+    /// Verifies that the first block of the response is the requested block and that it is part
+    /// of our chain.
+    fn verify_block_by_number(
+        &self,
+        response: &ResponseBlockByNumber,
+        block_number: u32,
+        include_body: bool,
+        election_head: &MacroBlock,
+    ) -> bool {
+        let blockchain = self.blockchain.read();
+        let network_id = blockchain.network_id();
+
+        // Check that we got the requested block.
+        let Some(block) = response.blocks.first() else {
+            return false;
+        };
+        if block.block_number() != block_number
+            || (include_body && block.body().is_none())
+            || block.verify(network_id).is_err()
+        {
+            return false;
+        }
+
+        // Check that the blocks form a chain.
+        for pair in response.blocks.windows(2) {
+            if pair[1].block_number() != pair[0].block_number() + 1
+                || *pair[1].parent_hash() != pair[0].hash()
+            {
+                return false;
+            }
+        }
+
+        // If any of the blocks is on our main chain, the requested block is proven. Our head is
+        // compared explicitly, since light clients don't necessarily store the blocks of the
+        // current batch that precede it.
+        let head_hash = blockchain.head_hash();
+        if response.blocks.iter().any(|block| {
+            let hash = block.hash();
+            hash == head_hash
+                || blockchain
+                    .get_block_at(block.block_number(), false)
+                    .map(|own_block| own_block.hash() == hash)
+                    .unwrap_or(false)
+        }) {
+            return true;
+        }
+
+        // Otherwise, the blocks must end in a macro block that we can prove.
+        let last_block = response
+            .blocks
+            .last()
+            .expect("The response contains blocks");
+        let Block::Macro(macro_block) = last_block else {
+            return false;
+        };
+        let election_block = if macro_block.is_election() {
+            macro_block
+        } else {
+            // A checkpoint block is proven by its justification from the validators of its epoch.
+            let Some(election_block) = &response.election_block else {
+                return false;
+            };
+            if !election_block.is_election()
+                || election_block.hash() != macro_block.header.parent_election_hash
+            {
+                return false;
+            }
+            let Some(validators) = election_block.get_validators() else {
+                return false;
+            };
+            if last_block.verify_validators(&validators).is_err() {
+                return false;
+            }
+            election_block
+        };
+
+        // Prove the election block against our election head.
+        if election_block.block_number() > election_head.block_number() {
+            return false;
+        }
+        let election_hash = election_block.hash();
+        if election_hash == election_head.hash()
+            || election_hash == election_head.header.parent_election_hash
+            || election_hash == blockchain.get_genesis_hash()
+            || blockchain
+                .get_block_at(election_block.block_number(), false)
+                .map(|own_block| own_block.hash() == election_hash)
+                .unwrap_or(false)
+        {
+            return true;
+        }
+        response
+            .proof
+            .as_ref()
+            .map(|proof| proof.is_block_proven(election_head, election_block))
+            .unwrap_or(false)
+    }
+
     /// Attempts to resolve a block with `block_hash` header hash at the given `block_height`.
     /// The first resolution attempt is performed with the peer specified by `first_peer_id`.
     ///
@@ -9,7 +9,7 @@ use std::{
     time::Duration,
 };
 
-use futures::{FutureExt, StreamExt};
+use futures::{future::BoxFuture, FutureExt, StreamExt};
 use instant::Instant;
 use nimiq_block::Block;
 use nimiq_blockchain_interface::AbstractBlockchain;
@@ -17,35 +17,45 @@ use nimiq_blockchain_proxy::BlockchainProxy;
 #[cfg(feature = "full")]
 use nimiq_blockchain_proxy::BlockchainReadProxy;
 use nimiq_hash::Blake2bHash;
-use nimiq_network_interface::{network::Network, request::request_handler};
+use nimiq_network_interface::{
+    network::{Network, NetworkEvent, SubscribeEvents},
+    request::{request_handler, RequestError},
+};
 use nimiq_utils::spawn;
 use nimiq_zkp_component::zkp_component::ZKPComponentProxy;
+use parking_lot::RwLock;
 use tokio::sync::{
     broadcast::{channel as broadcast, Sender as BroadcastSender},
     mpsc::{
         channel as mpsc_channel, error::SendError, Receiver as MpscReceiver, Sender as MpscSender,
     },
     oneshot::{error::RecvError, Sender as OneshotSender},
+    watch::{channel as watch, Sender as WatchSender},
 };
 use tokio_stream::wrappers::BroadcastStream;
 
-use self::consensus_proxy::ConsensusProxy;
 #[cfg(feature = "full")]
 use self::remote_event_dispatcher::RemoteEventDispatcher;
+use self::{address_subscriptions::AddressSubscriptions, consensus_proxy::ConsensusProxy};
 use crate::{
     consensus::head_requests::{HeadRequests, HeadRequestsResult},
     messages::{RequestBlock, RequestHead, RequestMacroChain, RequestMissingBlocks},
-    sync::{live::block_queue::BlockSource, syncer::LiveSyncPushEvent, syncer_proxy::SyncerProxy},
+    sync::{
+        live::block_queue::BlockSource, progress::SyncStatus, syncer::LiveSyncPushEvent,
+        syncer_proxy::SyncerProxy,
+    },
 };
 #[cfg(feature = "full")]
 use crate::{
     messages::{
-        RequestBatchSet, RequestBlocksProof, RequestHistoryChunk,
-        RequestTransactionReceiptsByAddress, RequestTransactionsProof, RequestTrieProof,
+        RequestBatchSet, RequestBlockByNumber, RequestBlocksProof, RequestHistoryChunk,
+        RequestHistoryRange, RequestTransactionReceiptsByAddress, RequestTransactionsProof,
+        RequestTrieProof,
     },
     sync::live::{diff_queue::RequestTrieDiff, state_queue::RequestChunk},
 };
 
+mod address_subscriptions;
 pub mod consensus_proxy;
 mod head_requests;
 mod remote_data_store;
@@ -131,6 +141,7 @@ pub struct Consensus<N: Network> {
 
     events: BroadcastSender<ConsensusEvent>,
     established_flag: Arc<AtomicBool>,
+    sync_status: Arc<WatchSender<SyncStatus<N::PeerId>>>,
     #[cfg(feature = "full")]
     last_batch_number: u32,
     synced_validity_window_flag: Arc<AtomicBool>,
@@ -155,6 +166,16 @@ pub struct Consensus<N: Network> {
     ),
 
     zkp_proxy: ZKPComponentProxy<N>,
+
+    /// Our address subscriptions at remote peers, shared with all consensus proxies.
+    address_subscriptions: Arc<RwLock<AddressSubscriptions<N>>>,
+    /// Network events used to migrate the address subscriptions when peers leave or join.
+    network_events: SubscribeEvents<N::PeerId>,
+    /// The ongoing migration of the address subscriptions, if any.
+    address_migration: Option<BoxFuture<'static, Result<(), RequestError>>>,
+    /// Whether a peer joined during the ongoing migration, which then needs to be retried if it
+    /// fails.
+    peer_joined_during_migration: bool,
 }
 
 impl<N: Network> Consensus<N> {
@@ -206,6 +227,7 @@ impl<N: Network> Consensus<N> {
             }
         }
         let synced_validity_window_flag = Arc::new(AtomicBool::new(synced_validity_window_flag));
+        let network_events = network.subscribe_events();
 
         Consensus {
             blockchain,
@@ -213,6 +235,7 @@ impl<N: Network> Consensus<N> {
             sync: syncer,
             events: tx,
             established_flag,
+            sync_status: Arc::new(watch(SyncStatus::default()).0),
             #[cfg(feature = "full")]
             last_batch_number: 0,
             synced_validity_window_flag,
@@ -222,6 +245,10 @@ impl<N: Network> Consensus<N> {
             // Choose a small buffer as having a lot of items buffered here indicates a bigger problem.
             requests: mpsc_channel(10),
             zkp_proxy,
+            address_subscriptions: Arc::new(RwLock::new(AddressSubscriptions::default())),
+            network_events,
+            address_migration: None,
+            peer_joined_during_migration: false,
         }
     }
 
@@ -264,6 +291,9 @@ impl<N: Network> Consensus<N> {
                 let stream = network.receive_requests::<RequestHistoryChunk>();
                 spawn(Box::pin(request_handler(network, stream, blockchain)));
 
+                let stream = network.receive_requests::<RequestHistoryRange>();
+                spawn(Box::pin(request_handler(network, stream, blockchain)));
+
                 let stream = network.receive_requests::<RequestTrieDiff>();
                 spawn(Box::pin(request_handler(network, stream, blockchain)));
 
@@ -286,6 +316,9 @@ impl<N: Network> Consensus<N> {
 
                 let stream = network.receive_requests::<RequestBlocksProof>();
                 spawn(Box::pin(request_handler(network, stream, blockchain)));
+
+                let stream = network.receive_requests::<RequestBlockByNumber>();
+                spawn(Box::pin(request_handler(network, stream, blockchain)));
             }
             BlockchainProxy::Light(_) => {}
         }
@@ -310,7 +343,9 @@ impl<N: Network> Consensus<N> {
             established_flag: Arc::clone(&self.established_flag),
             synced_validity_window_flag: Arc::clone(&self.synced_validity_window_flag),
             events: self.events.clone(),
+            sync_status: Arc::clone(&self.sync_status),
             request: self.requests.0.clone(),
+            address_subscriptions: Arc::clone(&self.address_subscriptions),
         }
     }
 
@@ -470,6 +505,45 @@ impl<N: Network> Consensus<N> {
     fn resolve_block(&mut self, request: ResolveBlockRequest<N>) {
         self.sync.resolve_block(request)
     }
+
+    /// Migrates our address subscriptions to another peer when the last peer serving them
+    /// left, or when a peer joins while no peer is serving them.
+    fn on_network_event(&mut self, event: NetworkEvent<N::PeerId>) {
+        let needs_migration = match event {
+            NetworkEvent::PeerLeft(peer_id) => self
+                .address_subscriptions
+                .write()
+                .remove_serving_peer(&peer_id),
+            NetworkEvent::PeerJoined(..) => {
+                self.peer_joined_during_migration = self.address_migration.is_some();
+                self.address_subscriptions.read().needs_migration()
+            }
+            NetworkEvent::DhtReady => false,
+        };
+
+        if needs_migration && self.address_migration.is_none() {
+            self.start_address_migration();
+        }
+    }
+
+    fn start_address_migration(&mut self) {
+        let proxy = self.proxy();
+        self.address_migration =
+            Some(async move { proxy.resubscribe_to_addresses().await }.boxed());
+        self.peer_joined_during_migration = false;
+    }
+
+    /// Publishes the current sync status if it changed.
+    fn update_sync_status(&mut self) {
+        let status = self.sync.sync_status(self.is_established());
+        self.sync_status.send_if_modified(|current| {
+            if *current != status {
+                *current = status;
+                return true;
+            }
+            false
+        });
+    }
 }
 
 impl<N: Network> Future for Consensus<N> {
@@ -546,9 +620,34 @@ impl<N: Network> Future for Consensus<N> {
             }
         }
 
+        // Migrate the address subscriptions if the peers serving them left.
+        while let Poll::Ready(Some(event)) = self.network_events.poll_next_unpin(cx) {
+            if let Ok(event) = event {
+                self.on_network_event(event);
+            }
+        }
+        while let Some(ref mut address_migration) = self.address_migration {
+            if let Poll::Ready(result) = address_migration.poll_unpin(cx) {
+                self.address_migration = None;
+                if let Err(error) = result {
+                    warn!(%error, "Failed to migrate address subscriptions");
+                }
+                // Peers that joined in the meantime might accept the subscriptions.
+                if self.peer_joined_during_migration
+                    && self.address_subscriptions.read().needs_migration()
+                {
+                    self.start_address_migration();
+                }
+            } else {
+                break;
+            }
+        }
+
         // Advance consensus and catch-up through head requests.
         self.request_heads();
 
+        self.update_sync_status();
+
         Poll::Pending
     }
 }
@@ -8,6 +8,7 @@ use nimiq_account::{
 };
 use nimiq_blockchain_interface::AbstractBlockchain;
 use nimiq_blockchain_proxy::BlockchainProxy;
+use nimiq_hash::Blake2bHash;
 use nimiq_keys::Address;
 use nimiq_network_interface::{
     network::{CloseReason, Network},
@@ -17,7 +18,32 @@ use nimiq_network_interface::{
 use nimiq_primitives::{key_nibbles::KeyNibbles, policy::Policy};
 use nimiq_serde::Deserialize;
 
-use crate::messages::RequestTrieProof;
+use crate::{error::TrieProofError, messages::RequestTrieProof};
+
+/// Values of the accounts trie, together with the block of our chain they were proven against.
+#[derive(Clone, Debug)]
+pub struct ProvenTrieValues<K, T> {
+    pub values: BTreeMap<K, Option<T>>,
+    /// The hash of the block the values were proven against.
+    pub block_hash: Blake2bHash,
+    /// The number of the block the values were proven against.
+    pub block_number: u32,
+}
+
+impl<K: Ord, T> ProvenTrieValues<K, T> {
+    /// Maps the keys of the proven values, keeping the block they were proven against.
+    pub fn map_keys<L: Ord, F: FnMut(K) -> L>(self, mut f: F) -> ProvenTrieValues<L, T> {
+        ProvenTrieValues {
+            values: self
+                .values
+                .into_iter()
+                .map(|(key, value)| (f(key), value))
+                .collect(),
+            block_hash: self.block_hash,
+            block_number: self.block_number,
+        }
+    }
+}
 
 /// The Remote Data Store is a component to remotely request data from the staking
 /// contract such as:
@@ -56,6 +82,28 @@ impl<N: Network> RemoteDataStore<N> {
         keys: &[KeyNibbles],
         min_peers: usize,
     ) -> Result<BTreeMap<KeyNibbles, Option<T>>, RequestError> {
+        match Self::get_proven_trie(network, blockchain, keys, min_peers).await {
+            Ok(proven) => Ok(proven.values),
+            Err(TrieProofError::Request(error)) => Err(error),
+            Err(TrieProofError::NoValidProof) => Err(RequestError::OutboundRequest(
+                OutboundRequestError::NoResponse,
+            )),
+        }
+    }
+
+    /// Gets a proof for deserializable items in a remote accounts trie and returns the items
+    /// together with the block they were proven against.
+    ///
+    /// Peers are asked one after another until one of them provides a valid proof. A proof is
+    /// only accepted if it verifies against the state root of a block of our chain, so a single
+    /// valid proof is sufficient: any other valid proof for the same block proves the same
+    /// values. Peers that send invalid proofs are disconnected.
+    pub(crate) async fn get_proven_trie<T: Deserialize>(
+        network: Arc<N>,
+        blockchain: BlockchainProxy,
+        keys: &[KeyNibbles],
+        min_peers: usize,
+    ) -> Result<ProvenTrieValues<KeyNibbles, T>, TrieProofError> {
         // First we tell the network to provide us with a vector that contains all the connected peers that support such services
         // Note: If the network could not provide enough peers that satisfies our requirement, then an error would be returned
         let peers = network
@@ -86,9 +134,11 @@ impl<N: Network> RemoteDataStore<N> {
 
             match response {
                 Ok(Ok(response)) => {
-                    let blockchain = blockchain.read();
                     // First try to obtain, from our chain store, the block that was used to generate the proof
-                    let block = blockchain.get_block(&response.block_hash, false).ok();
+                    let block = blockchain
+                        .read()
+                        .get_block(&response.block_hash, false)
+                        .ok();
 
                     if let Some(block) = block {
                         // Now we need to verify the proof
@@ -96,19 +146,23 @@ impl<N: Network> RemoteDataStore<N> {
                             .proof
                             .verify_values(block.state_root(), &keys.iter().collect::<Vec<_>>())
                         {
-                            return Ok(values
-                                .into_iter()
-                                .map(|(key, value)| {
-                                    (key, value.map(|v| T::deserialize_from_vec(&v).unwrap()))
-                                })
-                                .collect());
+                            log::trace!(%peer_id, block_hash = %response.block_hash, "Accepted accounts proof");
+                            return Ok(ProvenTrieValues {
+                                values: values
+                                    .into_iter()
+                                    .map(|(key, value)| {
+                                        (key, value.map(|v| T::deserialize_from_vec(&v).unwrap()))
+                                    })
+                                    .collect(),
+                                block_hash: response.block_hash,
+                                block_number: block.block_number(),
+                            });
                         } else {
                             // If the proof does not verify, we disconnect from the peer
                             log::warn!(%peer_id, "Banning peer because the accounts proof didn't verify");
                             network
                                 .disconnect_peer(peer_id, CloseReason::MaliciousPeer)
                                 .await;
-                            break;
                         }
                     } else {
                         // If we couldn't find the block, then we cannot verify the proof
@@ -127,9 +181,7 @@ impl<N: Network> RemoteDataStore<N> {
             }
         }
 
-        Err(RequestError::OutboundRequest(
-            OutboundRequestError::NoResponse,
-        ))
+        Err(TrieProofError::NoValidProof)
     }
 
     async fn get_staking_contract(&self) -> Result<StakingContract, RequestError> {
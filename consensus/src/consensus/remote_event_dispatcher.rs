@@ -3,27 +3,28 @@ use std::{
     future::Future,
     pin::Pin,
     sync::Arc,
-    task::{Context, Poll},
+    task::{Context, Poll, Waker},
 };
 
 use futures::{stream::BoxStream, StreamExt};
 use nimiq_blockchain::Blockchain;
-use nimiq_blockchain_interface::{AbstractBlockchain, BlockchainEvent};
+use nimiq_blockchain_interface::{AbstractBlockchain, BlockchainEvent, HistoryIndexInterface};
 use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_keys::Address;
 use nimiq_network_interface::{
-    network::{Network, NetworkEvent, SubscribeEvents},
+    network::{Network, NetworkEvent, SubscribeEvents, MIN_SUPPORTED_MSG_SIZE},
     request::{request_handler, Handle},
 };
 use nimiq_primitives::account::AccountType;
 use nimiq_transaction::account::staking_contract::IncomingStakingTransactionData;
-use nimiq_utils::spawn;
+use nimiq_utils::{spawn, WakerExt as _};
 use parking_lot::RwLock;
+use tokio::task::spawn_blocking;
 
 use crate::{
     messages::{
         AddressNotification, AddressSubscriptionOperation, AddressSubscriptionTopic,
-        NotificationEvent, RequestSubscribeToAddress,
+        NotificationEvent, RequestReplayAddressNotifications, RequestSubscribeToAddress,
     },
     SubscribeToAddressesError,
     SubscribeToAddressesError::*,
@@ -33,6 +34,14 @@ use crate::{
 pub const MAX_SUBSCRIBED_PEERS: usize = 50;
 /// The max number of addresses that can be subscribed, per peer.
 pub const MAX_SUBSCRIBED_PEERS_ADDRESSES: usize = 250;
+/// The max number of receipts that are replayed per address when a peer resubscribes.
+pub const MAX_REPLAYED_RECEIPTS: u16 = 500;
+/// The max number of transactions that are looked up in the history index per replay request,
+/// over all addresses. This also bounds the number of receipts that are replayed.
+pub const MAX_REPLAY_LOOKUPS: usize = 20_000;
+/// The max number of receipts per notification. A receipt takes at most 37 bytes, so a
+/// notification stays well below the message size every network supports.
+const MAX_RECEIPTS_PER_NOTIFICATION: usize = MIN_SUPPORTED_MSG_SIZE / (2 * Blake2bHash::SIZE);
 
 impl<N: Network> Handle<N, Arc<RwLock<RemoteEventDispatcherState<N>>>>
     for RequestSubscribeToAddress
@@ -76,6 +85,27 @@ impl<N: Network> Handle<N, Arc<RwLock<RemoteEventDispatcherState<N>>>>
     }
 }
 
+impl<N: Network> Handle<N, Arc<RwLock<RemoteEventDispatcherState<N>>>>
+    for RequestReplayAddressNotifications
+{
+    fn handle(
+        &self,
+        peer_id: N::PeerId,
+        state: &Arc<RwLock<RemoteEventDispatcherState<N>>>,
+    ) -> Result<(), SubscribeToAddressesError> {
+        let mut state = state.write();
+
+        // Only the notifications of addresses the peer is subscribed to are replayed.
+        let Some(addresses) = state.subscribed_peers.get(&peer_id) else {
+            return Err(InvalidOperation);
+        };
+        let addresses = addresses.iter().cloned().collect();
+        state.add_replay(peer_id, addresses, self.last_seen_block);
+
+        Ok(())
+    }
+}
+
 /// The state that is maintained by the remote event dispatcher:
 /// essentially the addresses and peers that are subscribed to us.
 pub struct RemoteEventDispatcherState<N: Network> {
@@ -84,6 +114,16 @@ pub struct RemoteEventDispatcherState<N: Network> {
 
     /// Maintains the current list of interesting addresses and the peers that are interested in those addresses
     pub subscriptions: HashMap<Address, HashSet<N::PeerId>>,
+
+    /// Notifications to replay: the peer, its addresses and the last block it has seen.
+    pending_replays: Vec<(N::PeerId, Vec<Address>, u32)>,
+
+    /// Peers whose notifications are currently being replayed. Each peer has at most one replay
+    /// in progress, further replay requests wait in `pending_replays`.
+    active_replays: HashSet<N::PeerId>,
+
+    /// Waker of the remote event dispatcher, used when a replay is requested.
+    waker: Option<Waker>,
 }
 
 impl<N: Network> RemoteEventDispatcherState<N> {
@@ -92,6 +132,9 @@ impl<N: Network> RemoteEventDispatcherState<N> {
         Self {
             subscribed_peers: HashMap::new(),
             subscriptions: HashMap::new(),
+            pending_replays: Vec::new(),
+            active_replays: HashSet::new(),
+            waker: None,
         }
     }
 
@@ -159,6 +202,21 @@ impl<N: Network> RemoteEventDispatcherState<N> {
         }
     }
 
+    /// Schedules the replay of the notifications for the given addresses after `last_seen_block`.
+    /// A replay that is still pending for the peer is replaced.
+    pub fn add_replay(
+        &mut self,
+        peer_id: N::PeerId,
+        addresses: Vec<Address>,
+        last_seen_block: u32,
+    ) {
+        self.pending_replays
+            .retain(|(replay_peer_id, _, _)| *replay_peer_id != peer_id);
+        self.pending_replays
+            .push((peer_id, addresses, last_seen_block));
+        self.waker.wake();
+    }
+
     /// Obtains the peers that are currently subscribed to us.
     pub fn get_peers(&self, address: &Address) -> Option<HashSet<N::PeerId>> {
         self.subscriptions.get(address).cloned()
@@ -200,6 +258,10 @@ impl<N: Network> RemoteEventDispatcher<N> {
 
         spawn(request_handler(&network, stream, &Arc::clone(&state)));
 
+        let stream = network.receive_requests::<RequestReplayAddressNotifications>();
+
+        spawn(request_handler(&network, stream, &Arc::clone(&state)));
+
         let blockchain_event_rx = blockchain.read().notifier_as_stream();
 
         let network_events = network.subscribe_events();
@@ -231,6 +293,113 @@ impl<N: Network> RemoteEventDispatcher<N> {
             }
         }
     }
+
+    /// Replays the receipts of the given addresses after `last_seen_block` to the peer. The
+    /// receipts are read from the history index on a blocking thread.
+    fn replay(&self, peer_id: N::PeerId, addresses: Vec<Address>, last_seen_block: u32) {
+        let blockchain = Arc::clone(&self.blockchain);
+        let network = Arc::clone(&self.network);
+        let state = Arc::clone(&self.state);
+        spawn(async move {
+            let result = spawn_blocking(move || {
+                replay_receipts(&blockchain.read(), &addresses, last_seen_block)
+            })
+            .await;
+
+            match result {
+                Ok(receipts) => {
+                    debug!(
+                        %peer_id,
+                        last_seen_block,
+                        num_receipts = receipts.len(),
+                        "Replaying address notifications"
+                    );
+                    publish_receipts(&network, peer_id, NotificationEvent::Replay, receipts).await;
+                }
+                Err(error) => {
+                    log::error!(%peer_id, %error, "Failed to collect the receipts to replay");
+                }
+            }
+
+            let mut state = state.write();
+            state.active_replays.remove(&peer_id);
+            state.waker.wake();
+        });
+    }
+
+    /// Publishes a notification to the given peer.
+    fn notify(
+        &self,
+        peer_id: N::PeerId,
+        event: NotificationEvent,
+        receipts: Vec<(Blake2bHash, u32)>,
+    ) {
+        let network = Arc::clone(&self.network);
+        spawn(async move {
+            publish_receipts(&network, peer_id, event, receipts).await;
+        });
+    }
+}
+
+/// Collects the receipts of the given addresses after `last_seen_block` from the history index.
+/// At most [`MAX_REPLAYED_RECEIPTS`] receipts are collected per address and at most
+/// [`MAX_REPLAY_LOOKUPS`] transactions are looked up in total, such that the receipts of the
+/// addresses listed last may be cut short. Returns an empty list if we don't have a history index.
+fn replay_receipts(
+    blockchain: &Blockchain,
+    addresses: &[Address],
+    last_seen_block: u32,
+) -> Vec<(Blake2bHash, u32)> {
+    let Some(history_index) = blockchain.history_store.history_index() else {
+        return vec![];
+    };
+
+    let mut receipts = vec![];
+    let mut num_lookups = 0;
+    'addresses: for address in addresses {
+        // The hashes are sorted from the most recent to the least recent transaction.
+        for hash in history_index.get_tx_hashes_by_address(address, MAX_REPLAYED_RECEIPTS, None) {
+            if num_lookups >= MAX_REPLAY_LOOKUPS {
+                break 'addresses;
+            }
+            num_lookups += 1;
+            match history_index.get_hist_tx_by_hash(&hash, None) {
+                Some(hist_tx) if hist_tx.block_number > last_seen_block => {
+                    receipts.push((hash, hist_tx.block_number));
+                }
+                Some(_) => break,
+                None => {}
+            }
+        }
+    }
+    receipts.sort_unstable_by_key(|(_, block_number)| *block_number);
+    receipts.dedup();
+    receipts
+}
+
+/// Publishes the receipts to the given peer, split into notifications of at most
+/// [`MAX_RECEIPTS_PER_NOTIFICATION`] receipts.
+async fn publish_receipts<N: Network>(
+    network: &N,
+    peer_id: N::PeerId,
+    event: NotificationEvent,
+    receipts: Vec<(Blake2bHash, u32)>,
+) {
+    for receipts in receipts.chunks(MAX_RECEIPTS_PER_NOTIFICATION) {
+        if let Err(error) = network
+            .publish_subtopic::<AddressSubscriptionTopic>(
+                peer_id.to_string(),
+                AddressNotification {
+                    receipts: receipts.to_vec(),
+                    event,
+                },
+            )
+            .await
+        {
+            log::debug!(%peer_id, %error, ?event, "Failed to publish address notification");
+            return;
+        }
+    }
 }
 
 impl<N: Network> Future for RemoteEventDispatcher<N> {
@@ -314,22 +483,24 @@ impl<N: Network> Future for RemoteEventDispatcher<N> {
             }
             // Notify all interested peers
             for (peer_id, receipts) in peer_receipts {
-                let network = Arc::clone(&self.network);
-                spawn({
-                    async move {
-                        let _ = network
-                            .publish_subtopic::<AddressSubscriptionTopic>(
-                                peer_id.to_string(),
-                                AddressNotification {
-                                    receipts: receipts.clone(),
-                                    event: NotificationEvent::BlockchainExtend,
-                                },
-                            )
-                            .await;
-                    }
-                });
+                self.notify(peer_id, NotificationEvent::BlockchainExtend, receipts);
+            }
+        }
+
+        // Replay the notifications that resubscribed peers missed
+        let mut state = self.state.write();
+        state.waker.store_waker(cx);
+        let pending_replays = std::mem::take(&mut state.pending_replays);
+        for (peer_id, addresses, last_seen_block) in pending_replays {
+            if !state.active_replays.insert(peer_id) {
+                state
+                    .pending_replays
+                    .push((peer_id, addresses, last_seen_block));
+                continue;
             }
+            self.replay(peer_id, addresses, last_seen_block);
         }
+        drop(state);
 
         // Listen and process network events
         while let Poll::Ready(Some(result)) = self.network_event_rx.poll_next_unpin(cx) {
@@ -337,6 +508,10 @@ impl<N: Network> Future for RemoteEventDispatcher<N> {
                 Ok(NetworkEvent::PeerLeft(peer_id)) => {
                     // Remove the peer from internal data structures.
                     self.state.write().remove_peer(&peer_id);
+                    self.state
+                        .write()
+                        .pending_replays
+                        .retain(|(replay_peer_id, _, _)| *replay_peer_id != peer_id);
                 }
                 Ok(_) => {}
                 Err(_) => return Poll::Pending,
@@ -346,3 +521,135 @@ impl<N: Network> Future for RemoteEventDispatcher<N> {
         Poll::Pending
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use nimiq_blockchain::{BlockProducer, BlockchainConfig};
+    use nimiq_database::mdbx::MdbxDatabase;
+    use nimiq_keys::{KeyPair, PrivateKey};
+    use nimiq_network_mock::{MockHub, MockNetwork, MockPeerId};
+    use nimiq_primitives::{networks::NetworkId, policy::Policy};
+    use nimiq_serde::Serialize as _;
+    use nimiq_test_log::test;
+    use nimiq_test_utils::blockchain::{
+        fill_micro_blocks_with_txns, produce_macro_blocks, signing_key, voting_key, REWARD_KEY,
+    };
+    use nimiq_utils::time::OffsetTime;
+
+    use super::*;
+
+    fn reward_address() -> Address {
+        Address::from(&KeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap()).public)
+    }
+
+    /// Creates a dispatcher on a chain of two batches with one transaction of the reward address
+    /// in each micro block.
+    fn dispatcher() -> RemoteEventDispatcher<MockNetwork> {
+        let blockchain = Arc::new(RwLock::new(
+            Blockchain::new(
+                MdbxDatabase::new_volatile(Default::default()).unwrap(),
+                BlockchainConfig::default(),
+                NetworkId::UnitAlbatross,
+                Arc::new(OffsetTime::new()),
+            )
+            .unwrap(),
+        ));
+        let producer = BlockProducer::new(signing_key(), voting_key());
+        for seed in 1..=2 {
+            fill_micro_blocks_with_txns(&producer, &blockchain, 1, seed);
+            produce_macro_blocks(&producer, &blockchain, 1);
+        }
+
+        let mut hub = MockHub::default();
+        RemoteEventDispatcher::new(Arc::new(hub.new_network()), blockchain)
+    }
+
+    #[test(tokio::test)]
+    async fn it_replays_receipts_after_the_last_seen_block() {
+        let dispatcher = dispatcher();
+        let last_seen_block = Policy::blocks_per_batch();
+
+        let receipts = replay_receipts(
+            &dispatcher.blockchain.read(),
+            &[reward_address()],
+            last_seen_block,
+        );
+        assert!(receipts
+            .iter()
+            .all(|(_, block_number)| *block_number > last_seen_block));
+        assert!(receipts.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+        // Each micro block of the second batch contains one transaction of the address.
+        assert_eq!(
+            receipts
+                .iter()
+                .filter(|(_, block_number)| !Policy::is_macro_block_at(*block_number))
+                .count() as u32,
+            Policy::blocks_per_batch() - 1
+        );
+
+        // Nothing is replayed for a peer that has seen the head, or for unrelated addresses.
+        let head = dispatcher.blockchain.read().block_number();
+        assert!(
+            replay_receipts(&dispatcher.blockchain.read(), &[reward_address()], head).is_empty()
+        );
+        assert!(
+            replay_receipts(&dispatcher.blockchain.read(), &[Address::burn_address()], 0)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn notifications_fit_into_a_message() {
+        let notification = AddressNotification {
+            event: NotificationEvent::Replay,
+            receipts: vec![(Blake2bHash::default(), u32::MAX); MAX_RECEIPTS_PER_NOTIFICATION],
+        };
+        assert!(notification.serialized_size() < MIN_SUPPORTED_MSG_SIZE);
+    }
+
+    #[test(tokio::test)]
+    async fn it_only_replays_for_subscribed_peers() {
+        let dispatcher = dispatcher();
+        let peer_id = MockPeerId(1);
+        let replay = RequestReplayAddressNotifications { last_seen_block: 0 };
+
+        assert_eq!(
+            Handle::<MockNetwork, _>::handle(&replay, peer_id, &dispatcher.state),
+            Err(InvalidOperation)
+        );
+        assert!(dispatcher.state.read().pending_replays.is_empty());
+
+        let subscribe = RequestSubscribeToAddress {
+            operation: AddressSubscriptionOperation::Subscribe,
+            addresses: vec![reward_address()],
+        };
+        assert_eq!(
+            Handle::<MockNetwork, _>::handle(&subscribe, peer_id, &dispatcher.state),
+            Ok(())
+        );
+        // Subscribing alone doesn't replay anything.
+        assert!(dispatcher.state.read().pending_replays.is_empty());
+
+        assert_eq!(
+            Handle::<MockNetwork, _>::handle(&replay, peer_id, &dispatcher.state),
+            Ok(())
+        );
+        assert_eq!(
+            dispatcher.state.read().pending_replays,
+            vec![(peer_id, vec![reward_address()], 0)]
+        );
+
+        // A new replay request replaces the pending one.
+        let replay = RequestReplayAddressNotifications { last_seen_block: 5 };
+        assert_eq!(
+            Handle::<MockNetwork, _>::handle(&replay, peer_id, &dispatcher.state),
+            Ok(())
+        );
+        assert_eq!(
+            dispatcher.state.read().pending_replays,
+            vec![(peer_id, vec![reward_address()], 5)]
+        );
+    }
+}
@@ -1,11 +1,16 @@
 use nimiq_blockchain_interface::BlockchainError;
+use nimiq_network_interface::request::RequestError;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::sync::checkpoint::CheckpointError;
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Blockchain error: {0}")]
     BlockchainError(#[from] BlockchainError),
+    #[error("Trusted checkpoint error: {0}")]
+    CheckpointError(#[from] CheckpointError),
 }
 
 #[derive(Debug, Error)]
@@ -16,6 +21,15 @@ pub enum SyncError {
     NoValidSyncTarget,
 }
 
+/// Errors that can occur when requesting proofs of the accounts trie from peers.
+#[derive(Debug, Error)]
+pub enum TrieProofError {
+    #[error("Request error: {0}")]
+    Request(#[from] RequestError),
+    #[error("No peer provided a valid proof")]
+    NoValidProof,
+}
+
 /// Different errors that can be obtained when subscribing to transaction addresses.
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, Error, Eq, PartialEq, Serialize, Deserialize)]
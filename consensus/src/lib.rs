@@ -2,7 +2,7 @@
 extern crate log;
 
 pub use consensus::{consensus_proxy::ConsensusProxy, Consensus, ConsensusEvent, RemoteEvent};
-pub use error::{Error, SubscribeToAddressesError};
+pub use error::{Error, SubscribeToAddressesError, TrieProofError};
 
 pub mod consensus;
 pub mod error;
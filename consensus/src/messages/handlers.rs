@@ -4,7 +4,7 @@ use std::{cmp, collections::HashSet};
 
 use nimiq_block::Block;
 #[cfg(feature = "full")]
-use nimiq_block::BlockInclusionProof;
+use nimiq_block::{BlockInclusionProof, MacroBlock};
 #[cfg(feature = "full")]
 use nimiq_blockchain::interface::{HistoryIndexInterface, HistoryInterface};
 #[cfg(feature = "full")]
@@ -175,6 +175,21 @@ impl<N: Network> Handle<N, Arc<RwLock<Blockchain>>> for RequestHistoryChunk {
     }
 }
 
+#[cfg(feature = "full")]
+impl<N: Network> Handle<N, Arc<RwLock<Blockchain>>> for RequestHistoryRange {
+    fn handle(
+        &self,
+        _peer_id: N::PeerId,
+        blockchain: &Arc<RwLock<Blockchain>>,
+    ) -> ResponseHistoryRange {
+        let (first_block, last_block) = blockchain.read().history_store.history_store_range(None);
+        ResponseHistoryRange {
+            first_block,
+            last_block,
+        }
+    }
+}
+
 impl<N: Network> Handle<N, BlockchainProxy> for RequestBlock {
     fn handle(
         &self,
@@ -536,8 +551,10 @@ fn prove_transaction(
         let block_number = hist_txn.block_number;
 
         let proving_block_number = if block_number <= election_head {
-            // If the txn is in a finalized epoch, we use the last election block
-            election_head
+            // If the txn is in a finalized epoch, we use the election block that finalized it,
+            // since the history root of an election block only covers its own epoch.
+            // This used to be the latest election block, which only works for the latest epoch.
+            Policy::election_block_of(Policy::epoch_at(block_number)).unwrap_or(election_head)
         } else if block_number <= macro_head {
             // If the txn is in a finalized batch in the current epoch, we use the last checkpoint block
             macro_head
@@ -662,6 +679,114 @@ impl<N: Network> Handle<N, Arc<RwLock<Blockchain>>> for RequestTrieProof {
     }
 }
 
+/// Collects the election blocks needed to prove `block_number` against `election_head`.
+#[cfg(feature = "full")]
+fn collect_block_proof(
+    blockchain: &Blockchain,
+    block_number: u32,
+    election_head: u32,
+    election_numbers: &mut Vec<u32>,
+) -> Vec<MacroBlock> {
+    let hops = BlockInclusionProof::get_interlink_hops(block_number, election_head);
+    let mut hop_blocks = Vec::new();
+    for &hop in &hops {
+        if !election_numbers.contains(&hop) {
+            if let Ok(Block::Macro(hop_block)) = blockchain.get_block_at(hop, false, None) {
+                hop_blocks.push(hop_block);
+            } else {
+                continue;
+            }
+        }
+    }
+    election_numbers.extend_from_slice(&hops);
+    hop_blocks
+}
+
+#[cfg(feature = "full")]
+impl<N: Network> Handle<N, Arc<RwLock<Blockchain>>> for RequestBlockByNumber {
+    fn handle(
+        &self,
+        _peer_id: N::PeerId,
+        blockchain: &Arc<RwLock<Blockchain>>,
+    ) -> Result<ResponseBlockByNumber, ResponseBlockByNumberError> {
+        let blockchain = blockchain.read();
+
+        if self.election_head > blockchain.election_head().block_number()
+            || !Policy::is_election_block_at(self.election_head)
+        {
+            return Err(ResponseBlockByNumberError::BadElectionHead(
+                self.election_head,
+            ));
+        }
+
+        let block = blockchain
+            .get_block_at(self.block_number, self.include_body, None)
+            .map_err(|_| ResponseBlockByNumberError::BlockNotFound)?;
+
+        // Add the blocks up to the macro block of the batch, or our head if the batch isn't finalized yet.
+        let last_block_number = if block.is_macro() {
+            self.block_number
+        } else {
+            cmp::min(
+                Policy::macro_block_after(self.block_number),
+                blockchain.block_number(),
+            )
+        };
+        let mut blocks = vec![block];
+        for block_number in self.block_number + 1..=last_block_number {
+            blocks.push(
+                blockchain
+                    .get_block_at(block_number, false, None)
+                    .map_err(|_| ResponseBlockByNumberError::BlockNotFound)?,
+            );
+        }
+
+        // Determine the election block that needs to be proven.
+        let (election_block_number, election_block) = match blocks.last() {
+            Some(Block::Macro(block)) if block.is_election() => (block.block_number(), None),
+            Some(Block::Macro(block)) => {
+                let election_block_number = Policy::election_block_before(block.block_number());
+                match blockchain.get_block_at(election_block_number, false, None) {
+                    Ok(Block::Macro(election_block)) => {
+                        (election_block_number, Some(election_block))
+                    }
+                    _ => return Err(ResponseBlockByNumberError::BlockNotFound),
+                }
+            }
+            // The batch is not finalized yet, the requester needs to know our head.
+            _ => {
+                return Ok(ResponseBlockByNumber {
+                    blocks,
+                    election_block: None,
+                    proof: None,
+                })
+            }
+        };
+
+        // The genesis block and the requester's election head are known to the requester.
+        let proof = if election_block_number < self.election_head
+            && election_block_number != Policy::genesis_block_number()
+        {
+            Some(BlockInclusionProof {
+                proof: collect_block_proof(
+                    &blockchain,
+                    election_block_number,
+                    self.election_head,
+                    &mut vec![],
+                ),
+            })
+        } else {
+            None
+        };
+
+        Ok(ResponseBlockByNumber {
+            blocks,
+            election_block,
+            proof,
+        })
+    }
+}
+
 #[cfg(feature = "full")]
 impl<N: Network> Handle<N, Arc<RwLock<Blockchain>>> for RequestBlocksProof {
     fn handle(
@@ -685,20 +810,13 @@ impl<N: Network> Handle<N, Arc<RwLock<Blockchain>>> for RequestBlocksProof {
         // Collect all election blocks needed for the proof
         let mut election_numbers = Vec::new();
         let mut block_proof = Vec::new();
-        for block_number in &self.blocks {
-            let hops = BlockInclusionProof::get_interlink_hops(*block_number, self.election_head);
-            let mut hop_blocks = Vec::new();
-            for &hop in &hops {
-                if !election_numbers.contains(&hop) {
-                    if let Ok(Block::Macro(hop_block)) = blockchain.get_block_at(hop, false, None) {
-                        hop_blocks.push(hop_block);
-                    } else {
-                        continue;
-                    }
-                }
-            }
-            election_numbers.extend_from_slice(&hops);
-            block_proof.append(&mut hop_blocks);
+        for &block_number in &self.blocks {
+            block_proof.append(&mut collect_block_proof(
+                &blockchain,
+                block_number,
+                self.election_head,
+                &mut election_numbers,
+            ));
         }
 
         Ok(ResponseBlocksProof {
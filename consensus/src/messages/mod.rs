@@ -171,6 +171,8 @@ pub const MAX_REQUEST_RESPONSE_BLOCK: u32 = 1000;
 pub const MAX_REQUEST_RESPONSE_MISSING_BLOCKS: u32 = 1000;
 /// The max number of RequestHead requests per peer.
 pub const MAX_REQUEST_RESPONSE_HEAD: u32 = 1000;
+/// The max number of RequestHistoryRange requests per peer.
+pub const MAX_REQUEST_RESPONSE_HISTORY_RANGE: u32 = 1000;
 /// The max number of Transactions proof requests per peer.
 pub const MAX_REQUEST_TRANSACTIONS_PROOF: u32 = 1000;
 /// The max number of Transactions proof requests per peer.
@@ -179,6 +181,8 @@ pub const MAX_REQUEST_TRANSACTIONS_BY_ADDRESS: u32 = 1000;
 pub const MAX_REQUEST_TRIE_PROOF: u32 = 1000;
 /// The max number of Block proof requests per peer.
 pub const MAX_REQUEST_BLOCKS_PROOF: u32 = 1000;
+/// The max number of Block by number requests per peer.
+pub const MAX_REQUEST_BLOCK_BY_NUMBER: u32 = 1000;
 /// The max number of Subscribe to address requests per peer.
 pub const MAX_REQUEST_SUBSCRIBE_BY_ADDRESS: u32 = 10;
 /// The max number of Address notifications per peer.
@@ -489,6 +493,39 @@ impl RequestCommon for RequestHead {
 }
 test_max_req_size!(RequestHead, request_head_req_size, request_head_resp_size);
 
+/// Request the range of blocks for which the peer stores the full transaction history.
+/// History nodes that prune their history report the range they still provide.
+#[derive(Clone, Debug, Deserialize, Serialize, SerializedMaxSize)]
+pub struct RequestHistoryRange {}
+
+#[derive(Clone, Debug, Deserialize, Serialize, SerializedMaxSize)]
+pub struct ResponseHistoryRange {
+    /// The first block for which the full transaction history is stored.
+    pub first_block: u32,
+    /// The last block for which the full transaction history is stored.
+    pub last_block: u32,
+}
+
+impl RequestCommon for RequestHistoryRange {
+    type Kind = RequestMarker;
+    const TYPE_ID: u16 = 219;
+    type Response = ResponseHistoryRange;
+    const MAX_REQUESTS: u32 = MAX_REQUEST_RESPONSE_HISTORY_RANGE;
+}
+test_max_req_size!(
+    RequestHistoryRange,
+    request_history_range_req_size,
+    request_history_range_resp_size
+);
+
+/// Response to [`RequestTransactionsProof`].
+///
+/// `block` is the block the history proof is generated against: the transaction's own block if
+/// it is in the current batch, the latest checkpoint block if it is in a finalized batch of the
+/// current epoch and the election block that finalized its epoch if it is in a finalized epoch.
+/// Responders used to return their latest election block for all finalized epochs, which only
+/// proves transactions of the latest finalized epoch. Requesters accept any election block that
+/// is linked to their election head, so they work with both kinds of responders.
 #[derive(Serialize, Deserialize)]
 pub struct ResponseTransactionsProof {
     pub proof: HistoryTreeProof,
@@ -607,6 +644,52 @@ impl RequestCommon for RequestBlocksProof {
     const MAX_REQUESTS: u32 = MAX_REQUEST_BLOCKS_PROOF;
 }
 
+/// Request a block by its number, together with a proof that it is part of the chain.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RequestBlockByNumber {
+    /// The number of the requested block.
+    pub block_number: u32,
+    /// Whether to include the body of the requested block.
+    pub include_body: bool,
+    /// The number of the latest election block known to the requester, which the proof is
+    /// generated against.
+    pub election_head: u32,
+}
+
+/// Response to [`RequestBlockByNumber`].
+///
+/// The requested block is proven by the hash chain of the following blocks up to the macro block
+/// of its batch (or the responder's head, for blocks of the current batch). A checkpoint block is
+/// proven by its justification, which is verified against the validators of the preceding
+/// election block. Election blocks are proven by a [`BlockInclusionProof`].
+#[derive(Serialize, Deserialize)]
+pub struct ResponseBlockByNumber {
+    /// The requested block followed by the blocks of its batch (without bodies).
+    pub blocks: Vec<Block>,
+    /// The election block preceding the last block of `blocks` if that is a checkpoint block.
+    pub election_block: Option<MacroBlock>,
+    /// Proof that the election block is part of the chain.
+    pub proof: Option<BlockInclusionProof>,
+}
+
+#[derive(Clone, Debug, Deserialize, Error, Serialize)]
+pub enum ResponseBlockByNumberError {
+    #[error("block not found")]
+    BlockNotFound,
+    #[error("bad election head {0}")]
+    BadElectionHead(u32),
+    #[error("unknown error")]
+    #[serde(other)]
+    Other,
+}
+
+impl RequestCommon for RequestBlockByNumber {
+    type Kind = RequestMarker;
+    const TYPE_ID: u16 = 220;
+    type Response = Result<ResponseBlockByNumber, ResponseBlockByNumberError>;
+    const MAX_REQUESTS: u32 = MAX_REQUEST_BLOCK_BY_NUMBER;
+}
+
 /// Operations supported for the transaction address subscription
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 #[repr(u8)]
@@ -633,12 +716,34 @@ impl RequestCommon for RequestSubscribeToAddress {
     const MAX_REQUESTS: u32 = MAX_REQUEST_SUBSCRIBE_BY_ADDRESS;
 }
 
+/// This request is used to replay the notifications a peer missed before it subscribed to us,
+/// e.g. because its subscriptions were migrated from another peer. The receipts of the addresses
+/// the peer is subscribed to are replayed from the history index, starting after the given block.
+///
+/// Peers that don't support this request just don't answer it, so the subscription itself is
+/// unaffected.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RequestReplayAddressNotifications {
+    /// The number of the last block the peer has seen notifications for.
+    pub last_seen_block: u32,
+}
+
+impl RequestCommon for RequestReplayAddressNotifications {
+    type Kind = RequestMarker;
+    const TYPE_ID: u16 = 221;
+    type Response = Result<(), SubscribeToAddressesError>;
+    const MAX_REQUESTS: u32 = MAX_REQUEST_SUBSCRIBE_BY_ADDRESS;
+}
+
 /// Different kind of events that could generate notifications
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
 #[repr(u8)]
 pub enum NotificationEvent {
     /// A new block was pushed into the chain.
     BlockchainExtend,
+    /// Receipts of blocks the peer missed before subscribing, replayed from the history index.
+    /// Only sent to peers that requested a replay with [`RequestReplayAddressNotifications`].
+    Replay,
 }
 
 /// Interesting Addresses Notifications:
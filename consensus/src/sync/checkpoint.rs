@@ -0,0 +1,233 @@
+use std::{fmt, num::ParseIntError, str::FromStr};
+
+use nimiq_block::MacroBlock;
+use nimiq_blockchain_interface::AbstractBlockchain;
+use nimiq_blockchain_proxy::BlockchainProxy;
+use nimiq_hash::Blake2bHash;
+use nimiq_macros::hex::FromHexError;
+use nimiq_primitives::policy::Policy;
+use thiserror::Error;
+
+/// An election block that is trusted to be part of the chain, in addition to the genesis block.
+/// The sync refuses any chain that doesn't contain this block, which protects nodes that start
+/// from scratch or have been offline for a long time against long-range attacks.
+///
+/// The block number is pinned together with the hash, such that a chain that skips over the
+/// checkpoint can be detected without knowing the checkpoint block itself.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TrustedCheckpoint {
+    /// The block number of the election block.
+    pub block_number: u32,
+    /// The hash of the election block.
+    pub hash: Blake2bHash,
+}
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum TrustedCheckpointParseError {
+    #[error("Trusted checkpoint is missing separator, expected <block_number>:<hash>: {0}")]
+    MissingColon(String),
+    #[error("Invalid block number: {0}")]
+    InvalidBlockNumber(#[from] ParseIntError),
+    #[error("Invalid block hash: {0}")]
+    InvalidHash(#[from] FromHexError),
+    #[error("Block #{0} is not an election block")]
+    NotElectionBlock(u32),
+}
+
+/// Errors that occur when checking a chain against the trusted checkpoint.
+#[derive(Clone, Debug, Error, Eq, PartialEq)]
+pub enum CheckpointError {
+    #[error("Chain doesn't contain the trusted checkpoint: block #{block_number} is {found}, expected {expected}")]
+    Mismatch {
+        block_number: u32,
+        expected: Blake2bHash,
+        found: Blake2bHash,
+    },
+    #[error("Trusted checkpoint {hash} is block #{found}, not #{expected}")]
+    WrongBlockNumber {
+        hash: Blake2bHash,
+        expected: u32,
+        found: u32,
+    },
+    #[error("Failed to retrieve election block {0}")]
+    MissingBlock(Blake2bHash),
+    #[error(
+        "Failed to prove that the chain of election block {0} contains the trusted checkpoint"
+    )]
+    Unproven(Blake2bHash),
+}
+
+impl TrustedCheckpoint {
+    pub fn new(block_number: u32, hash: Blake2bHash) -> Result<Self, TrustedCheckpointParseError> {
+        if !Policy::is_election_block_at(block_number) {
+            return Err(TrustedCheckpointParseError::NotElectionBlock(block_number));
+        }
+        Ok(Self { block_number, hash })
+    }
+
+    /// Returns the epoch of the checkpoint, which is the epoch that the election block concludes.
+    pub fn epoch_number(&self) -> u32 {
+        Policy::epoch_at(self.block_number)
+    }
+
+    /// Checks an election block of a chain. Only a block at the height of the checkpoint can
+    /// contradict it.
+    pub fn verify_block(
+        &self,
+        block_number: u32,
+        block_hash: &Blake2bHash,
+    ) -> Result<(), CheckpointError> {
+        if block_number == self.block_number && *block_hash != self.hash {
+            return Err(CheckpointError::Mismatch {
+                block_number,
+                expected: self.hash.clone(),
+                found: block_hash.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that `block` is the checkpoint block itself, e.g. when received from a peer.
+    pub fn verify_checkpoint_block(&self, block: &MacroBlock) -> Result<(), CheckpointError> {
+        if block.hash() != self.hash {
+            return Err(CheckpointError::MissingBlock(self.hash.clone()));
+        }
+        if block.block_number() != self.block_number {
+            return Err(CheckpointError::WrongBlockNumber {
+                hash: self.hash.clone(),
+                expected: self.block_number,
+                found: block.block_number(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks the election block hashes reported by a peer, where `ids[i]` is the hash of the
+    /// election block of epoch `first_epoch_number + i`.
+    pub fn verify_epoch_ids(
+        &self,
+        first_epoch_number: usize,
+        ids: &[Blake2bHash],
+    ) -> Result<(), CheckpointError> {
+        let Some(index) = (self.epoch_number() as usize).checked_sub(first_epoch_number) else {
+            return Ok(());
+        };
+        match ids.get(index) {
+            Some(block_hash) => self.verify_block(self.block_number, block_hash),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that the local chain contains the checkpoint if it already extends beyond it.
+    /// If the checkpoint block isn't stored locally, e.g. because the node synced via a
+    /// zero-knowledge proof, the chain has been checked when it was synced.
+    pub fn verify_chain(&self, blockchain: &BlockchainProxy) -> Result<(), CheckpointError> {
+        let blockchain = blockchain.read();
+        if blockchain.election_head().block_number() < self.block_number {
+            return Ok(());
+        }
+
+        match blockchain.get_block_at(self.block_number, false) {
+            Ok(block) => self.verify_block(block.block_number(), &block.hash()),
+            Err(error) => {
+                log::debug!(
+                    %error,
+                    checkpoint = %self,
+                    "Trusted checkpoint is not stored locally"
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns whether an election block at `block_number` would skip the checkpoint if it was
+    /// adopted by a chain whose latest election block is at `election_head`.
+    pub fn is_skipped_by(&self, election_head: u32, block_number: u32) -> bool {
+        election_head < self.block_number && block_number >= self.block_number
+    }
+}
+
+impl fmt::Display for TrustedCheckpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.block_number, self.hash)
+    }
+}
+
+impl FromStr for TrustedCheckpoint {
+    type Err = TrustedCheckpointParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (block_number, hash) = s
+            .split_once(':')
+            .ok_or_else(|| TrustedCheckpointParseError::MissingColon(s.to_string()))?;
+        Self::new(block_number.trim().parse()?, hash.trim().parse()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimiq_hash::Blake2bHash;
+    use nimiq_primitives::policy::Policy;
+    use nimiq_test_log::test;
+
+    use super::*;
+
+    fn checkpoint() -> TrustedCheckpoint {
+        TrustedCheckpoint::new(
+            Policy::election_block_of(2).unwrap(),
+            Blake2bHash::from([1u8; 32]),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn it_parses_block_number_and_hash() {
+        let checkpoint = checkpoint();
+        assert_eq!(checkpoint.to_string().parse(), Ok(checkpoint));
+
+        assert!(matches!(
+            "deadbeef".parse::<TrustedCheckpoint>(),
+            Err(TrustedCheckpointParseError::MissingColon(_))
+        ));
+        assert!(matches!(
+            format!(
+                "{}:{}",
+                Policy::election_block_of(1).unwrap() + 1,
+                Blake2bHash::default()
+            )
+            .parse::<TrustedCheckpoint>(),
+            Err(TrustedCheckpointParseError::NotElectionBlock(_))
+        ));
+    }
+
+    #[test]
+    fn it_only_rejects_other_blocks_at_the_checkpoint() {
+        let checkpoint = checkpoint();
+        let other_hash = Blake2bHash::from([2u8; 32]);
+
+        assert_eq!(
+            checkpoint.verify_block(checkpoint.block_number, &checkpoint.hash),
+            Ok(())
+        );
+        assert_eq!(
+            checkpoint.verify_block(Policy::election_block_of(1).unwrap(), &other_hash),
+            Ok(())
+        );
+        assert!(matches!(
+            checkpoint.verify_block(checkpoint.block_number, &other_hash),
+            Err(CheckpointError::Mismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn it_checks_the_epoch_ids_covering_the_checkpoint() {
+        let checkpoint = checkpoint();
+        let other_hash = Blake2bHash::from([2u8; 32]);
+
+        let ids = vec![other_hash.clone(), checkpoint.hash.clone()];
+        assert_eq!(checkpoint.verify_epoch_ids(1, &ids), Ok(()));
+        assert_eq!(checkpoint.verify_epoch_ids(3, &ids), Ok(()));
+        assert_eq!(checkpoint.verify_epoch_ids(1, &ids[..1]), Ok(()));
+        assert!(checkpoint.verify_epoch_ids(2, &ids).is_err());
+    }
+}
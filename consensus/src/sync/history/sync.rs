@@ -14,7 +14,9 @@ use parking_lot::RwLock;
 use crate::{
     messages::Checkpoint,
     sync::{
+        checkpoint::TrustedCheckpoint,
         history::cluster::{SyncCluster, SyncClusterResult},
+        progress::MacroSyncProgress,
         syncer::MacroSync,
     },
 };
@@ -38,6 +40,16 @@ impl<T> EpochIds<T> {
     pub(crate) fn last_epoch_number(&self) -> usize {
         self.checkpoint_epoch_number().saturating_sub(1)
     }
+
+    /// Updates the sync targets with the macro chain reported by the sender.
+    pub(crate) fn update_progress_target(&self, progress: &mut MacroSyncProgress) {
+        progress.update_target(
+            (!self.ids.is_empty()).then(|| self.last_epoch_number() as u32),
+            self.checkpoint
+                .as_ref()
+                .map(|checkpoint| checkpoint.block_number),
+        );
+    }
 }
 
 pub(crate) enum Job<TNetwork: Network> {
@@ -57,6 +69,8 @@ pub struct HistoryMacroSync<TNetwork: Network> {
     pub(crate) active_cluster: Option<SyncCluster<TNetwork>>,
     pub(crate) job_queue: VecDeque<Job<TNetwork>>,
     pub(crate) waker: Option<Waker>,
+    pub(crate) progress: MacroSyncProgress,
+    pub(crate) trusted_checkpoint: Option<TrustedCheckpoint>,
 }
 
 impl<TNetwork: Network> HistoryMacroSync<TNetwork> {
@@ -79,9 +93,16 @@ impl<TNetwork: Network> HistoryMacroSync<TNetwork> {
             active_cluster: None,
             job_queue: VecDeque::new(),
             waker: None,
+            progress: MacroSyncProgress::default(),
+            trusted_checkpoint: None,
         }
     }
 
+    /// Pins a trusted election block, such that chains that don't contain it are refused.
+    pub fn set_trusted_checkpoint(&mut self, checkpoint: TrustedCheckpoint) {
+        self.trusted_checkpoint = Some(checkpoint);
+    }
+
     pub fn remove_peer(&mut self, peer_id: TNetwork::PeerId) {
         for cluster in self.epoch_clusters.iter_mut() {
             cluster.remove_peer(&peer_id);
@@ -118,4 +139,12 @@ impl<TNetwork: Network> MacroSync<TNetwork::PeerId> for HistoryMacroSync<TNetwor
         .boxed();
         self.epoch_ids_stream.push(future);
     }
+
+    fn peers(&self) -> Vec<TNetwork::PeerId> {
+        self.peers.keys().copied().collect()
+    }
+
+    fn progress(&self) -> MacroSyncProgress {
+        self.progress
+    }
 }
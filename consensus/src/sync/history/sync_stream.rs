@@ -7,8 +7,9 @@ use std::{
 use futures::{FutureExt, Stream, StreamExt};
 use nimiq_block::Block;
 use nimiq_blockchain::Blockchain;
-use nimiq_network_interface::network::{Network, NetworkEvent};
-use nimiq_utils::WakerExt as _;
+use nimiq_network_interface::network::{CloseReason, Network, NetworkEvent};
+use nimiq_serde::Serialize as _;
+use nimiq_utils::{spawn, WakerExt as _};
 use tokio::task::spawn_blocking;
 
 use crate::sync::{
@@ -76,7 +77,29 @@ impl<TNetwork: Network> HistoryMacroSync<TNetwork> {
                         epoch_ids.sender
                     );
                     return Poll::Ready(Some(MacroSyncReturn::Outdated(epoch_ids.sender)));
-                } else if epoch_ids.ids.is_empty() && epoch_ids.checkpoint.is_none() {
+                }
+
+                if let Some(ref checkpoint) = self.trusted_checkpoint {
+                    if let Err(error) =
+                        checkpoint.verify_epoch_ids(epoch_ids.first_epoch_number, &epoch_ids.ids)
+                    {
+                        let peer_id = epoch_ids.sender;
+                        log::warn!(
+                            %error,
+                            %peer_id,
+                            "Disconnecting peer because its chain doesn't contain the trusted checkpoint"
+                        );
+                        let network = Arc::clone(&self.network);
+                        spawn(async move {
+                            network.disconnect_peer(peer_id, CloseReason::Other).await;
+                        });
+                        continue;
+                    }
+                }
+
+                epoch_ids.update_progress_target(&mut self.progress);
+
+                if epoch_ids.ids.is_empty() && epoch_ids.checkpoint.is_none() {
                     // We are synced with this peer.
                     debug!("Finished syncing with peer: {:?}", epoch_ids.sender);
                     return Poll::Ready(Some(MacroSyncReturn::Good(epoch_ids.sender)));
@@ -114,6 +137,14 @@ impl<TNetwork: Network> HistoryMacroSync<TNetwork> {
                 match result {
                     Some(Ok(batch_set)) => {
                         let hash = batch_set.block.hash();
+                        self.progress.add_bytes(
+                            batch_set.block.serialized_size()
+                                + batch_set
+                                    .history
+                                    .iter()
+                                    .map(|item| item.serialized_size())
+                                    .sum::<usize>(),
+                        );
                         let blockchain = Arc::clone(&self.blockchain);
 
                         // Note the fact that the future surrounding the spawn_blocking is created deliberately as
@@ -6,7 +6,8 @@ use std::{
 };
 
 use futures::{future::BoxFuture, FutureExt};
-use nimiq_block::Block;
+use nimiq_block::{Block, MacroBlock};
+use nimiq_blockchain_interface::AbstractBlockchain;
 use nimiq_blockchain_proxy::BlockchainProxy;
 use nimiq_hash::Blake2bHash;
 use nimiq_network_interface::{
@@ -15,7 +16,7 @@ use nimiq_network_interface::{
 };
 use nimiq_utils::{spawn, stream::FuturesUnordered};
 use nimiq_zkp_component::{
-    types::{Error, ZKPRequestEvent},
+    types::{Error, ZKPRequestEvent, ZKProof},
     zkp_component::ZKPComponentProxy,
 };
 #[cfg(feature = "full")]
@@ -23,7 +24,11 @@ use parking_lot::RwLock;
 
 use crate::{
     messages::{BlockError, Checkpoint},
-    sync::syncer::MacroSync,
+    sync::{
+        checkpoint::{CheckpointError, TrustedCheckpoint},
+        progress::MacroSyncProgress,
+        syncer::MacroSync,
+    },
 };
 #[cfg(feature = "full")]
 use crate::{
@@ -57,6 +62,16 @@ impl<T> EpochIds<T> {
     pub(crate) fn last_epoch_number(&self) -> usize {
         self.checkpoint_epoch_number().saturating_sub(1)
     }
+
+    /// Updates the sync targets with the macro chain reported by the sender.
+    pub(crate) fn update_progress_target(&self, progress: &mut MacroSyncProgress) {
+        progress.update_target(
+            (!self.ids.is_empty()).then(|| self.last_epoch_number() as u32),
+            self.checkpoint
+                .as_ref()
+                .map(|checkpoint| checkpoint.block_number),
+        );
+    }
 }
 
 #[cfg(feature = "full")]
@@ -156,6 +171,22 @@ pub struct LightMacroSync<TNetwork: Network> {
         FuturesUnordered<BoxFuture<'static, Option<EpochIds<TNetwork::PeerId>>>>,
     /// Reference to the ZKP proxy used to interact with the ZKP component
     pub(crate) zkp_component_proxy: ZKPComponentProxy<TNetwork>,
+    /// The sync targets and the number of bytes downloaded
+    pub(crate) progress: MacroSyncProgress,
+    /// The election block that the synced chain must contain (if any)
+    pub(crate) trusted_checkpoint: Option<TrustedCheckpoint>,
+    /// Whether to start from the trusted checkpoint if a peer can't provide a ZKP
+    pub(crate) sync_from_checkpoint: bool,
+    /// Election blocks that are checked against the trusted checkpoint before being applied
+    pub(crate) checkpoint_requests: FuturesUnordered<
+        BoxFuture<
+            'static,
+            (
+                Result<(MacroBlock, ZKProof), CheckpointError>,
+                TNetwork::PeerId,
+            ),
+        >,
+    >,
     /// ZKP related requests (proofs)
     pub(crate) zkp_requests:
         FuturesUnordered<BoxFuture<'static, (Result<ZKPRequestEvent, Error>, TNetwork::PeerId)>>,
@@ -238,6 +269,10 @@ impl<TNetwork: Network> LightMacroSync<TNetwork> {
             peer_requests: HashMap::new(),
             epoch_ids_stream: FuturesUnordered::new(),
             zkp_component_proxy,
+            progress: MacroSyncProgress::default(),
+            trusted_checkpoint: None,
+            sync_from_checkpoint: false,
+            checkpoint_requests: FuturesUnordered::new(),
             zkp_requests: FuturesUnordered::new(),
             #[cfg(feature = "full")]
             full_sync_threshold,
@@ -253,6 +288,37 @@ impl<TNetwork: Network> LightMacroSync<TNetwork> {
         }
     }
 
+    /// Pins a trusted election block, such that chains that don't contain it are refused. If
+    /// `sync_from_checkpoint` is set, the sync starts from the checkpoint when a peer can't
+    /// provide a ZKP.
+    pub fn set_trusted_checkpoint(
+        &mut self,
+        checkpoint: TrustedCheckpoint,
+        sync_from_checkpoint: bool,
+    ) {
+        self.trusted_checkpoint = Some(checkpoint);
+        self.sync_from_checkpoint = sync_from_checkpoint;
+    }
+
+    /// Returns the trusted checkpoint if adopting the election block at `block_number` would skip
+    /// it, i.e. if our chain doesn't reach the checkpoint yet.
+    pub(crate) fn checkpoint_skipped_by(&self, block_number: u32) -> Option<TrustedCheckpoint> {
+        let election_head = self.blockchain.read().election_head().block_number();
+        self.trusted_checkpoint
+            .as_ref()
+            .filter(|checkpoint| checkpoint.is_skipped_by(election_head, block_number))
+            .cloned()
+    }
+
+    /// Returns the trusted checkpoint if the sync should start from it.
+    pub(crate) fn checkpoint_to_sync_from(&self) -> Option<TrustedCheckpoint> {
+        let checkpoint = self.trusted_checkpoint.as_ref()?;
+        if !self.sync_from_checkpoint {
+            return None;
+        }
+        self.checkpoint_skipped_by(checkpoint.block_number)
+    }
+
     pub fn remove_peer_requests(&mut self, peer_id: TNetwork::PeerId) {
         self.peer_requests.remove(&peer_id);
     }
@@ -279,4 +345,20 @@ impl<TNetwork: Network> MacroSync<TNetwork::PeerId> for LightMacroSync<TNetwork>
         self.zkp_requests
             .push(Self::request_zkps(self.zkp_component_proxy.clone(), peer_id).boxed());
     }
+
+    fn peers(&self) -> Vec<TNetwork::PeerId> {
+        #[allow(unused_mut)]
+        let mut peers: Vec<_> = self.peer_requests.keys().copied().collect();
+        #[cfg(feature = "full")]
+        peers.extend(
+            self.syncing_peers
+                .iter()
+                .filter(|peer_id| !self.peer_requests.contains_key(peer_id)),
+        );
+        peers
+    }
+
+    fn progress(&self) -> MacroSyncProgress {
+        self.progress
+    }
 }
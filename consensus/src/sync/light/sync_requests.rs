@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use futures::FutureExt;
-use nimiq_block::Block;
+use nimiq_block::{Block, BlockInclusionProof, MacroBlock};
 use nimiq_blockchain_interface::AbstractBlockchain;
 use nimiq_blockchain_proxy::BlockchainProxy;
 use nimiq_hash::Blake2bHash;
@@ -13,13 +13,17 @@ use nimiq_network_interface::{
 };
 use nimiq_primitives::policy::Policy;
 use nimiq_zkp_component::{
-    types::{Error, ZKPRequestEvent},
+    types::{Error, ZKPRequestEvent, ZKProof},
     zkp_component::ZKPComponentProxy,
 };
 
 use crate::{
-    messages::{BlockError, MacroChain, MacroChainError, RequestBlock, RequestMacroChain},
+    messages::{
+        BlockError, MacroChain, MacroChainError, RequestBlock, RequestBlocksProof,
+        RequestMacroChain, ResponseBlocksProof,
+    },
     sync::{
+        checkpoint::{CheckpointError, TrustedCheckpoint},
         light::{
             sync::{EpochIds, PeerMacroRequests},
             LightMacroSync,
@@ -153,6 +157,110 @@ impl<TNetwork: Network> LightMacroSync<TNetwork> {
         }
     }
 
+    /// Checks that the chain of a ZKP's election block contains the trusted checkpoint. The
+    /// checkpoint block is requested together with a block inclusion proof, which links the
+    /// election block to the checkpoint through the interlinks of a logarithmic number of
+    /// election blocks. Returns the block and proof if the chain contains the checkpoint.
+    pub(crate) async fn verify_trusted_checkpoint(
+        network: Arc<TNetwork>,
+        peer_id: TNetwork::PeerId,
+        checkpoint: TrustedCheckpoint,
+        block: MacroBlock,
+        proof: ZKProof,
+    ) -> (
+        Result<(MacroBlock, ZKProof), CheckpointError>,
+        TNetwork::PeerId,
+    ) {
+        if block.block_number() == checkpoint.block_number {
+            let result = checkpoint
+                .verify_block(block.block_number(), &block.hash())
+                .map(|_| (block, proof));
+            return (result, peer_id);
+        }
+
+        // The election block might link to the checkpoint directly, otherwise we need the
+        // election blocks in between.
+        let hops =
+            BlockInclusionProof::get_interlink_hops(checkpoint.block_number, block.block_number());
+
+        let (checkpoint_block, inclusion_proof) = futures::join!(
+            Self::request_macro_block(Arc::clone(&network), peer_id, checkpoint.hash.clone()),
+            async {
+                if hops.is_empty() {
+                    return Ok(Ok(ResponseBlocksProof {
+                        proof: BlockInclusionProof { proof: vec![] },
+                    }));
+                }
+                network
+                    .request::<RequestBlocksProof>(
+                        RequestBlocksProof {
+                            election_head: block.block_number(),
+                            blocks: vec![checkpoint.block_number],
+                        },
+                        peer_id,
+                    )
+                    .await
+            },
+        );
+
+        let checkpoint_block = match checkpoint_block {
+            Ok(Ok(Block::Macro(checkpoint_block))) => checkpoint_block,
+            _ => return (Err(CheckpointError::MissingBlock(checkpoint.hash)), peer_id),
+        };
+        if let Err(error) = checkpoint.verify_checkpoint_block(&checkpoint_block) {
+            return (Err(error), peer_id);
+        }
+
+        let is_proven = match inclusion_proof {
+            // Without hops, the election block itself has to reference the checkpoint.
+            Ok(Ok(_)) if hops.is_empty() => {
+                block.header.parent_election_hash == checkpoint.hash
+                    || block
+                        .header
+                        .interlink
+                        .as_ref()
+                        .is_some_and(|interlink| interlink.contains(&checkpoint.hash))
+            }
+            Ok(Ok(ResponseBlocksProof { proof })) => {
+                proof.is_block_proven(&block, &checkpoint_block)
+            }
+            Ok(Err(error)) => {
+                log::debug!(%error, %peer_id, "Error on remote side while requesting block proof");
+                false
+            }
+            Err(error) => {
+                log::debug!(%error, %peer_id, "Error requesting block proof");
+                false
+            }
+        };
+
+        if !is_proven {
+            return (Err(CheckpointError::Unproven(block.hash())), peer_id);
+        }
+        (Ok((block, proof)), peer_id)
+    }
+
+    /// Requests the trusted checkpoint block from a peer in order to start the sync from it.
+    pub(crate) async fn request_trusted_checkpoint(
+        network: Arc<TNetwork>,
+        peer_id: TNetwork::PeerId,
+        checkpoint: TrustedCheckpoint,
+    ) -> (
+        Result<(MacroBlock, ZKProof), CheckpointError>,
+        TNetwork::PeerId,
+    ) {
+        let result = match Self::request_macro_block(network, peer_id, checkpoint.hash.clone())
+            .await
+        {
+            Ok(Ok(Block::Macro(block))) => checkpoint.verify_checkpoint_block(&block).map(|_| {
+                let proof = ZKProof::new(block.block_number(), None);
+                (block, proof)
+            }),
+            _ => Err(CheckpointError::MissingBlock(checkpoint.hash)),
+        };
+        (result, peer_id)
+    }
+
     #[cfg(feature = "full")]
     pub(crate) fn request_single_macro_block(
         &mut self,
@@ -5,7 +5,7 @@ use std::{
 };
 
 use futures::{FutureExt, Stream, StreamExt};
-use nimiq_block::Block;
+use nimiq_block::{Block, MacroBlock};
 #[cfg(feature = "full")]
 use nimiq_blockchain::Blockchain;
 use nimiq_blockchain_interface::AbstractBlockchain;
@@ -14,7 +14,11 @@ use nimiq_light_blockchain::LightBlockchain;
 use nimiq_network_interface::network::{CloseReason, Network, NetworkEvent};
 #[cfg(feature = "full")]
 use nimiq_primitives::policy::Policy;
-use nimiq_zkp_component::types::ZKPRequestEvent::{OutdatedProof, Proof};
+use nimiq_serde::Serialize as _;
+use nimiq_zkp_component::types::{
+    ZKPRequestEvent::{OutdatedProof, Proof},
+    ZKProof,
+};
 
 use crate::sync::{
     light::LightMacroSync,
@@ -69,70 +73,33 @@ impl<TNetwork: Network> LightMacroSync<TNetwork> {
             match zkp_request_result {
                 (Ok(zkp_event), peer_id) => match zkp_event {
                     Proof { proof, block } => {
-                        // Apply a newer proof to the blockchain
-                        let result = match self.blockchain {
-                            #[cfg(feature = "full")]
-                            BlockchainProxy::Full(ref full_blockchain) => {
-                                let blockchain_urg = full_blockchain.upgradable_read();
-                                if block
-                                    .block_number()
-                                    .saturating_sub(blockchain_urg.block_number())
-                                    <= self.full_sync_threshold
-                                {
-                                    // We deem this too close to do a macro sync, thus we are not pushing the zkp. This would
-                                    // clear the state and history store. Instead, we request the epoch ids from this peer.
-                                    log::debug!(
-                                        peer_id = %peer_id,
-                                        "Peer is sufficiently close not to apply the zkp."
-                                    );
-
-                                    let future = Self::request_epoch_ids(
-                                        self.blockchain.clone(),
-                                        Arc::clone(&self.network),
-                                        peer_id,
-                                    )
-                                    .boxed();
-                                    self.epoch_ids_stream.push(future);
-
-                                    continue;
-                                }
-                                Blockchain::push_zkp(
-                                    blockchain_urg,
-                                    Block::Macro(block),
-                                    proof.proof.expect("Expected a zkp proof"),
-                                    true,
-                                )
-                            }
-                            BlockchainProxy::Light(ref light_blockchain) => {
-                                LightBlockchain::push_zkp(
-                                    light_blockchain.upgradable_read(),
-                                    Block::Macro(block),
-                                    proof.proof.expect("Expected a zkp proof"),
-                                    true,
-                                )
-                            }
-                        };
-
-                        match result {
-                            Ok(result) => {
-                                log::debug!(result = ?result, "Applied ZKP proof to the blockchain");
-                                // Request epoch ids with our updated state from this peer
-                                let future = Self::request_epoch_ids(
-                                    self.blockchain.clone(),
-                                    Arc::clone(&self.network),
-                                    peer_id,
-                                )
-                                .boxed();
-                                self.epoch_ids_stream.push(future);
-                            }
-                            Err(result) => {
-                                log::warn!(?result, %peer_id, "Banning peer because failed applying ZKP proof to the blockchain",);
-
-                                // Since it failed applying the ZKP from this peer, we disconnect
-                                self.disconnect_peer(peer_id, CloseReason::MaliciousPeer);
+                        self.progress
+                            .update_target(Some(block.epoch_number()), None);
+                        self.progress.add_bytes(block.serialized_size());
+
+                        // A proof that skips the trusted checkpoint can only be applied once we
+                        // know that its chain passes through the checkpoint.
+                        if let Some(checkpoint) = self.checkpoint_skipped_by(block.block_number()) {
+                            log::debug!(
+                                block_number = block.block_number(),
+                                %checkpoint,
+                                %peer_id,
+                                "Verifying that the ZKP chain contains the trusted checkpoint"
+                            );
+                            let future = Self::verify_trusted_checkpoint(
+                                Arc::clone(&self.network),
+                                peer_id,
+                                checkpoint,
+                                block,
+                                proof,
+                            )
+                            .boxed();
+                            self.checkpoint_requests.push(future);
+                            continue;
+                        }
 
-                                return Poll::Ready(None);
-                            }
+                        if !self.push_zkp(peer_id, block, proof) {
+                            return Poll::Ready(None);
                         }
                     }
                     OutdatedProof { block_height: _ } => {
@@ -149,6 +116,24 @@ impl<TNetwork: Network> LightMacroSync<TNetwork> {
                     }
                 },
                 (Err(zkp_error), peer_id) => {
+                    // If configured, start from the trusted checkpoint instead of the proof.
+                    if let Some(checkpoint) = self.checkpoint_to_sync_from() {
+                        log::debug!(
+                            ?zkp_error,
+                            %checkpoint,
+                            %peer_id,
+                            "Error requesting zkp from peer, syncing from the trusted checkpoint instead",
+                        );
+                        let future = Self::request_trusted_checkpoint(
+                            Arc::clone(&self.network),
+                            peer_id,
+                            checkpoint,
+                        )
+                        .boxed();
+                        self.checkpoint_requests.push(future);
+                        continue;
+                    }
+
                     // There was an error requesting a proof from this peer, so we disconnect it
                     log::debug!(
                         ?zkp_error,
@@ -164,6 +149,103 @@ impl<TNetwork: Network> LightMacroSync<TNetwork> {
         Poll::Pending
     }
 
+    // Applies an election block and its proof to the blockchain and requests epoch ids from the
+    // peer with our updated state. Returns false if the block was rejected, in which case the
+    // peer is disconnected.
+    fn push_zkp(&mut self, peer_id: TNetwork::PeerId, block: MacroBlock, proof: ZKProof) -> bool {
+        // Proofs received from the ZKP component are already verified and the trusted
+        // checkpoint is adopted without a proof, so the proof is never verified here.
+        let proof = proof.proof.unwrap_or_default();
+
+        let result = match self.blockchain {
+            #[cfg(feature = "full")]
+            BlockchainProxy::Full(ref full_blockchain) => {
+                let blockchain_urg = full_blockchain.upgradable_read();
+                if block
+                    .block_number()
+                    .saturating_sub(blockchain_urg.block_number())
+                    <= self.full_sync_threshold
+                {
+                    // We deem this too close to do a macro sync, thus we are not pushing the zkp. This would
+                    // clear the state and history store. Instead, we request the epoch ids from this peer.
+                    log::debug!(
+                        peer_id = %peer_id,
+                        "Peer is sufficiently close not to apply the zkp."
+                    );
+
+                    let future = Self::request_epoch_ids(
+                        self.blockchain.clone(),
+                        Arc::clone(&self.network),
+                        peer_id,
+                    )
+                    .boxed();
+                    self.epoch_ids_stream.push(future);
+
+                    return true;
+                }
+                Blockchain::push_zkp(blockchain_urg, Block::Macro(block), proof, true)
+            }
+            BlockchainProxy::Light(ref light_blockchain) => LightBlockchain::push_zkp(
+                light_blockchain.upgradable_read(),
+                Block::Macro(block),
+                proof,
+                true,
+            ),
+        };
+
+        match result {
+            Ok(result) => {
+                log::debug!(result = ?result, "Applied ZKP proof to the blockchain");
+                // Request epoch ids with our updated state from this peer
+                let future = Self::request_epoch_ids(
+                    self.blockchain.clone(),
+                    Arc::clone(&self.network),
+                    peer_id,
+                )
+                .boxed();
+                self.epoch_ids_stream.push(future);
+                true
+            }
+            Err(result) => {
+                log::warn!(?result, %peer_id, "Banning peer because failed applying ZKP proof to the blockchain",);
+
+                // Since it failed applying the ZKP from this peer, we disconnect
+                self.disconnect_peer(peer_id, CloseReason::MaliciousPeer);
+                false
+            }
+        }
+    }
+
+    // Polls the election blocks that are checked against the trusted checkpoint. Blocks on a
+    // chain that contains the checkpoint are applied, peers on other chains are disconnected.
+    fn poll_checkpoint_requests(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<MacroSyncReturn<TNetwork::PeerId>>> {
+        while let Poll::Ready(Some((result, peer_id))) =
+            self.checkpoint_requests.poll_next_unpin(cx)
+        {
+            match result {
+                Ok((block, proof)) => {
+                    if !self.push_zkp(peer_id, block, proof) {
+                        return Poll::Ready(None);
+                    }
+                }
+                Err(error) => {
+                    log::warn!(
+                        %error,
+                        %peer_id,
+                        "Disconnecting peer because its chain doesn't contain the trusted checkpoint"
+                    );
+                    self.disconnect_peer(peer_id, CloseReason::Other);
+                    return Poll::Ready(None);
+                }
+            }
+        }
+
+        Poll::Pending
+    }
+
     fn poll_epoch_ids(
         &mut self,
         cx: &mut Context<'_>,
@@ -182,7 +264,25 @@ impl<TNetwork: Network> LightMacroSync<TNetwork> {
                 );
 
                 return Poll::Ready(Some(MacroSyncReturn::Outdated(epoch_ids.sender)));
-            } else if epoch_ids.ids.is_empty() && epoch_ids.checkpoint.is_none() {
+            }
+
+            if let Some(ref checkpoint) = self.trusted_checkpoint {
+                if let Err(error) =
+                    checkpoint.verify_epoch_ids(epoch_ids.first_epoch_number, &epoch_ids.ids)
+                {
+                    log::warn!(
+                        %error,
+                        peer_id = %epoch_ids.sender,
+                        "Disconnecting peer because its chain doesn't contain the trusted checkpoint"
+                    );
+                    self.disconnect_peer(epoch_ids.sender, CloseReason::Other);
+                    return Poll::Ready(None);
+                }
+            }
+
+            epoch_ids.update_progress_target(&mut self.progress);
+
+            if epoch_ids.ids.is_empty() && epoch_ids.checkpoint.is_none() {
                 match self.blockchain {
                     #[cfg(feature = "full")]
                     BlockchainProxy::Full(ref blockchain) => {
@@ -267,6 +367,8 @@ impl<TNetwork: Network> LightMacroSync<TNetwork> {
         while let Poll::Ready(Some(result)) = self.block_headers.poll_next_unpin(cx) {
             match result {
                 (Ok(Ok(block)), peer_id) => {
+                    self.progress.add_bytes(block.serialized_size());
+
                     if let Some(peer_requests) = self.peer_requests.get_mut(&peer_id) {
                         if !peer_requests.update_request(block) {
                             // We received a block we were not expecting from this peer
@@ -413,6 +515,10 @@ impl<TNetwork: Network> Stream for LightMacroSync<TNetwork> {
             return Poll::Ready(o);
         }
 
+        if let Poll::Ready(o) = self.poll_checkpoint_requests(cx) {
+            return Poll::Ready(o);
+        }
+
         if let Poll::Ready(o) = self.poll_epoch_ids(cx) {
             return Poll::Ready(o);
         }
@@ -444,6 +550,7 @@ mod tests {
     use nimiq_blockchain_interface::{AbstractBlockchain, PushResult};
     use nimiq_blockchain_proxy::BlockchainProxy;
     use nimiq_database::{mdbx::MdbxDatabase, traits::WriteTransaction};
+    use nimiq_hash::Blake2bHash;
     use nimiq_light_blockchain::LightBlockchain;
     use nimiq_network_interface::{network::Network, request::request_handler};
     use nimiq_network_mock::{MockHub, MockNetwork};
@@ -451,11 +558,16 @@ mod tests {
     use nimiq_test_log::test;
     use nimiq_test_utils::blockchain::{produce_macro_blocks_with_txns, signing_key, voting_key};
     use nimiq_utils::{spawn, time::OffsetTime};
+    use nimiq_zkp_component::types::ZKProof;
     use parking_lot::RwLock;
 
     use crate::{
-        messages::{RequestBlock, RequestHistoryChunk, RequestMacroChain},
-        sync::{light::LightMacroSync, syncer::MacroSyncReturn},
+        messages::{RequestBlock, RequestBlocksProof, RequestHistoryChunk, RequestMacroChain},
+        sync::{
+            checkpoint::{CheckpointError, TrustedCheckpoint},
+            light::LightMacroSync,
+            syncer::MacroSyncReturn,
+        },
     };
 
     fn blockchain() -> BlockchainProxy {
@@ -501,6 +613,11 @@ mod tests {
                     network.receive_requests::<RequestHistoryChunk>(),
                     full_blockchain,
                 ));
+                spawn(request_handler(
+                    network,
+                    network.receive_requests::<RequestBlocksProof>(),
+                    full_blockchain,
+                ));
             }
             BlockchainProxy::Light(_) => {}
         };
@@ -657,6 +774,135 @@ mod tests {
         test(blockchain()).await;
     }
 
+    #[test(tokio::test)]
+    async fn it_refuses_a_chain_without_the_trusted_checkpoint() {
+        async fn test(chain1: BlockchainProxy, checkpoint_hash: Option<Blake2bHash>) {
+            let mut hub = MockHub::default();
+            let net1 = Arc::new(hub.new_network());
+            let net2 = Arc::new(hub.new_network());
+
+            let chain2 = blockchain();
+
+            let producer = BlockProducer::new(signing_key(), voting_key());
+            if let BlockchainProxy::Full(ref chain2) = chain2 {
+                produce_macro_blocks_with_txns(
+                    &producer,
+                    chain2,
+                    Policy::batches_per_epoch() as usize,
+                    1,
+                    0,
+                );
+            }
+            let election_block = chain2.read().election_head();
+
+            let zkp_component =
+                nimiq_zkp_component::ZKPComponent::new(chain1.clone(), Arc::clone(&net1), None)
+                    .await;
+
+            let zkp_component_proxy = zkp_component.proxy();
+
+            spawn(zkp_component);
+
+            let mut sync = LightMacroSync::<MockNetwork>::new(
+                chain1.clone(),
+                Arc::clone(&net1),
+                net1.subscribe_events(),
+                zkp_component_proxy,
+                0,
+            );
+            let checkpoint = TrustedCheckpoint::new(
+                election_block.block_number(),
+                checkpoint_hash
+                    .clone()
+                    .unwrap_or_else(|| election_block.hash()),
+            )
+            .unwrap();
+            sync.set_trusted_checkpoint(checkpoint, false);
+
+            let zkp_component2 =
+                nimiq_zkp_component::ZKPComponent::new(chain2.clone(), Arc::clone(&net2), None)
+                    .await;
+
+            spawn(zkp_component2);
+
+            spawn_request_handlers(&net2, &chain2.clone());
+            net1.dial_mock(&net2);
+
+            match (sync.next().await, checkpoint_hash) {
+                // The chain contains the checkpoint.
+                (Some(MacroSyncReturn::Good(_)), None) => {
+                    assert_eq!(chain1.read().head_hash(), chain2.read().head_hash());
+                }
+                // The chain contains another block at the height of the checkpoint.
+                (None, Some(_)) => {
+                    assert_eq!(chain1.read().block_number(), Policy::genesis_block_number());
+                }
+                (res, _) => panic!("Unexpected MacroSyncReturn: {res:?}"),
+            }
+        }
+
+        test(light_blockchain(), None).await;
+        test(blockchain(), None).await;
+        test(light_blockchain(), Some(Blake2bHash::default())).await;
+        test(blockchain(), Some(Blake2bHash::default())).await;
+    }
+
+    #[test(tokio::test)]
+    async fn it_proves_the_trusted_checkpoint_with_an_inclusion_proof() {
+        let mut hub = MockHub::default();
+        let net1 = Arc::new(hub.new_network());
+        let net2 = Arc::new(hub.new_network());
+
+        let chain2 = blockchain();
+        let producer = BlockProducer::new(signing_key(), voting_key());
+        let mut election_blocks = vec![];
+        if let BlockchainProxy::Full(ref chain2) = chain2 {
+            for _ in 0..4 {
+                produce_macro_blocks_with_txns(
+                    &producer,
+                    chain2,
+                    Policy::batches_per_epoch() as usize,
+                    1,
+                    0,
+                );
+                election_blocks.push(chain2.read().election_head());
+            }
+        }
+        let block = election_blocks.pop().unwrap();
+
+        spawn_request_handlers(&net2, &chain2);
+        net1.dial_mock(&net2);
+
+        for checkpoint_block in &election_blocks {
+            let checkpoint =
+                TrustedCheckpoint::new(checkpoint_block.block_number(), checkpoint_block.hash())
+                    .unwrap();
+            let (result, _) = LightMacroSync::verify_trusted_checkpoint(
+                Arc::clone(&net1),
+                net2.get_local_peer_id(),
+                checkpoint,
+                block.clone(),
+                ZKProof::new(block.block_number(), None),
+            )
+            .await;
+            assert_eq!(result.unwrap().0, block);
+        }
+
+        // A chain that doesn't contain the checkpoint is refused.
+        let checkpoint =
+            TrustedCheckpoint::new(election_blocks[0].block_number(), Blake2bHash::default())
+                .unwrap();
+        let (result, _) = LightMacroSync::verify_trusted_checkpoint(
+            Arc::clone(&net1),
+            net2.get_local_peer_id(),
+            checkpoint,
+            block.clone(),
+            ZKProof::new(block.block_number(), None),
+        )
+        .await;
+        assert!(matches!(result, Err(CheckpointError::MissingBlock(_))));
+    }
+
     #[test(tokio::test)]
     async fn it_can_sync_a_single_finalized_epoch_and_batch() {
         async fn test(chain1: BlockchainProxy) {
@@ -13,6 +13,7 @@ use nimiq_network_interface::{
     request::RequestError,
 };
 use nimiq_primitives::policy::Policy;
+use nimiq_serde::Serialize as _;
 
 use super::LightMacroSync;
 use crate::{
@@ -213,6 +214,8 @@ impl<TNetwork: Network> LightMacroSync<TNetwork> {
 
             match result {
                 Ok(chunk) => {
+                    self.progress.add_bytes(chunk.serialized_size());
+
                     let peer_request = self.validity_requests.as_mut().unwrap();
                     let expected_root = peer_request.root_hash.clone();
                     let mut verifier_block_number = peer_request.verifier_block_number;
@@ -20,7 +20,7 @@ use thiserror::Error;
 
 use crate::{
     messages::{RequestMissingBlocks, ResponseBlocksError},
-    sync::{peer_list::PeerList, sync_queue::SyncQueue},
+    sync::{peer_list::PeerList, peer_stats::PeerStats, sync_queue::SyncQueue},
 };
 
 #[derive(Debug)]
@@ -73,6 +73,7 @@ pub enum MissingBlockError {
 ///
 /// - The sync queue which manages the requests and responses.
 /// - The peers list.
+/// - The response times and failure rates of the peers, used to select the peers to request from.
 /// - The network stream of events used to remove the peers that have left.  
 /// - Whether we include the body of a block.
 ///
@@ -81,6 +82,7 @@ pub enum MissingBlockError {
 pub struct BlockRequestComponent<N: Network> {
     sync_queue: SyncQueue<N, MissingBlockRequest, MissingBlockResponse<N>, MissingBlockError, ()>, // requesting missing blocks from peers
     peers: Arc<RwLock<PeerList<N>>>,
+    peer_stats: Arc<RwLock<PeerStats<N>>>,
     include_body: bool,
     /// Pending requests.
     pending_requests: BTreeSet<Blake2bHash>,
@@ -91,10 +93,12 @@ impl<N: Network> BlockRequestComponent<N> {
 
     pub fn new(network: Arc<N>, include_body: bool) -> Self {
         let peers = Arc::new(RwLock::new(PeerList::default()));
+        let peer_stats = Arc::new(RwLock::new(PeerStats::default()));
         let mut network_event_rx = network.subscribe_events();
 
         // Poll network events to remove peers.
         let peers_weak = Arc::downgrade(&peers);
+        let peer_stats_weak = Arc::downgrade(&peer_stats);
         spawn(async move {
             while let Some(result) = network_event_rx.next().await {
                 if let Ok(NetworkEvent::PeerLeft(peer_id)) = result {
@@ -106,6 +110,9 @@ impl<N: Network> BlockRequestComponent<N> {
 
                     debug!(%peer_id, "Removing peer from live sync");
                     peers.write().remove_peer(&peer_id);
+                    if let Some(peer_stats) = peer_stats_weak.upgrade() {
+                        peer_stats.write().remove_peer(&peer_id);
+                    }
                 }
             }
         });
@@ -240,8 +247,10 @@ impl<N: Network> BlockRequestComponent<N> {
                     true
                 },
                 (),
-            ),
+            )
+            .with_peer_stats(Arc::clone(&peer_stats)),
             peers,
+            peer_stats,
             include_body,
             pending_requests: BTreeSet::new(),
         }
@@ -302,6 +311,7 @@ impl<N: Network> BlockRequestComponent<N> {
 
     pub fn take_peer(&self, peer_id: &N::PeerId) -> Option<N::PeerId> {
         if self.peers.write().remove_peer(peer_id) {
+            self.peer_stats.write().release_peer(peer_id);
             return Some(*peer_id);
         }
         None
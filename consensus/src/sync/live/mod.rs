@@ -17,7 +17,10 @@ use tokio_stream::wrappers::ReceiverStream;
 #[cfg(feature = "full")]
 use self::state_queue::StateQueue;
 use self::{block_queue::BlockQueue, queue::LiveSyncQueue};
-use super::syncer::{LiveSync, LiveSyncEvent};
+use super::{
+    progress::LiveSyncProgress,
+    syncer::{LiveSync, LiveSyncEvent},
+};
 use crate::{
     consensus::ResolveBlockRequest,
     sync::live::block_queue::{BlockAndSource, BlockSource},
@@ -103,6 +106,10 @@ impl<N: Network, Q: LiveSyncQueue<N>> LiveSync<N> for LiveSyncer<N, Q> {
         self.queue.state_complete()
     }
 
+    fn progress(&self) -> LiveSyncProgress {
+        self.queue.progress()
+    }
+
     fn resolve_block(&mut self, request: ResolveBlockRequest<N>) {
         self.queue.resolve_block(request)
     }
@@ -30,6 +30,7 @@ use crate::{
     consensus::ResolveBlockRequest,
     sync::{
         live::block_queue::{BlockAndSource, BlockSource},
+        progress::LiveSyncProgress,
         syncer::LiveSyncEvent,
     },
 };
@@ -112,6 +113,10 @@ pub trait LiveSyncQueue<N: Network>: Stream<Item = Self::QueueResult> + Send + U
         true
     }
 
+    fn progress(&self) -> LiveSyncProgress {
+        LiveSyncProgress::default()
+    }
+
     /// Initiates an attempt to resolve a ResolveBlockRequest.
     fn resolve_block(&mut self, request: ResolveBlockRequest<N>);
 }
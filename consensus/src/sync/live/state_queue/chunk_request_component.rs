@@ -5,12 +5,16 @@ use std::{
 };
 
 use futures::{future::BoxFuture, FutureExt, Stream, StreamExt};
-use nimiq_network_interface::{network::Network, request::RequestError};
+use nimiq_network_interface::{
+    network::{Network, NetworkEvent},
+    request::RequestError,
+};
 use nimiq_primitives::key_nibbles::KeyNibbles;
+use nimiq_utils::spawn;
 use parking_lot::RwLock;
 
 use super::{RequestChunk, ResponseChunk};
-use crate::sync::{peer_list::PeerList, sync_queue::SyncQueue};
+use crate::sync::{peer_list::PeerList, peer_stats::PeerStats, sync_queue::SyncQueue};
 
 /// Peer Tracking & Chunk Request Component.
 /// This component returns only the responses that respect the size limit specified on
@@ -20,7 +24,8 @@ use crate::sync::{peer_list::PeerList, sync_queue::SyncQueue};
 ///
 /// - The sync queue which manages the requests and responses.
 /// - The peers list.
-/// - The network stream of events used to remove the peers that have left.  
+/// - The response times and failure rates of the peers, used to select the peers to request from.
+/// - The network stream of events used to remove the statistics of peers that have left.
 ///
 /// The public interface allows to request chunks, which are not immediately returned.
 /// The chunks instead are returned by polling the component.
@@ -29,12 +34,31 @@ pub struct ChunkRequestComponent<N: Network> {
         SyncQueue<N, RequestChunk, (ResponseChunk, RequestChunk, N::PeerId), RequestError, ()>,
     // These peers will be shared across the block request component and this component.
     peers: Arc<RwLock<PeerList<N>>>,
+    // The statistics are kept separately, as chunk requests take longer than block requests.
+    peer_stats: Arc<RwLock<PeerStats<N>>>,
 }
 
 impl<N: Network> ChunkRequestComponent<N> {
     const NUM_PENDING_CHUNKS: usize = 1;
 
     pub fn new(network: Arc<N>, peers: Arc<RwLock<PeerList<N>>>) -> Self {
+        let peer_stats = Arc::new(RwLock::new(PeerStats::default()));
+        let mut network_event_rx = network.subscribe_events();
+
+        // Poll network events to remove the statistics of peers that left. The peer list is
+        // shared with the block request component, which removes the peers themselves.
+        let peer_stats_weak = Arc::downgrade(&peer_stats);
+        spawn(async move {
+            while let Some(result) = network_event_rx.next().await {
+                if let Ok(NetworkEvent::PeerLeft(peer_id)) = result {
+                    let Some(peer_stats) = peer_stats_weak.upgrade() else {
+                        break;
+                    };
+                    peer_stats.write().remove_peer(&peer_id);
+                }
+            }
+        });
+
         let sync_queue = SyncQueue::with_verification(
             network,
             vec![],
@@ -65,13 +89,19 @@ impl<N: Network> ChunkRequestComponent<N> {
                 true
             },
             (),
-        );
+        )
+        .with_peer_stats(Arc::clone(&peer_stats));
 
-        ChunkRequestComponent { sync_queue, peers }
+        ChunkRequestComponent {
+            sync_queue,
+            peers,
+            peer_stats,
+        }
     }
 
     pub fn remove_peer(&mut self, peer_id: &N::PeerId) {
         self.peers.write().remove_peer(peer_id);
+        self.peer_stats.write().release_peer(peer_id);
     }
 
     pub fn has_pending_requests(&self) -> bool {
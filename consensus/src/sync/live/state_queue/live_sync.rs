@@ -22,6 +22,7 @@ use crate::{
             block_queue::{live_sync::PushOpResult as BlockPushOpResult, BlockAndSource},
             queue::{self, LiveSyncQueue},
         },
+        progress::LiveSyncProgress,
         syncer::{LiveSyncEvent, LiveSyncPeerEvent, LiveSyncPushEvent},
     },
 };
@@ -267,6 +268,10 @@ impl<N: Network> LiveSyncQueue<N> for StateQueue<N> {
         self.start_key.is_complete()
     }
 
+    fn progress(&self) -> LiveSyncProgress {
+        StateQueue::progress(self)
+    }
+
     fn resolve_block(&mut self, request: ResolveBlockRequest<N>) {
         self.diff_queue.resolve_block(request)
     }
@@ -23,6 +23,7 @@ use nimiq_primitives::{
     policy::Policy,
     trie::{trie_chunk::TrieChunk, trie_diff::TrieDiff},
 };
+use nimiq_serde::Serialize as _;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 
@@ -31,7 +32,10 @@ use super::{
     block_queue::BlockAndSource,
     queue::{ChunkAndSource, QueueConfig},
 };
-use crate::sync::live::diff_queue::{DiffQueue, QueuedDiff};
+use crate::sync::{
+    live::diff_queue::{DiffQueue, QueuedDiff},
+    progress::{LiveSyncProgress, SyncCounter},
+};
 
 /// The max number of chunk requests per peer.
 pub const MAX_REQUEST_RESPONSE_CHUNKS: u32 = 5000;
@@ -137,6 +141,49 @@ impl ChunkRequestState {
     }
 }
 
+/// Tracks the chunks received during the state sync to report its progress.
+#[derive(Default)]
+struct ChunkProgress {
+    /// The number of chunks received.
+    received: u64,
+    /// The number of bytes of chunks received.
+    bytes: u64,
+    /// The key space position of the chunk we started extrapolating from and the number of chunks
+    /// received since then.
+    first_position: Option<(f64, u64)>,
+    /// The estimated total number of chunks.
+    estimated_total: Option<u64>,
+}
+
+impl ChunkProgress {
+    fn note_chunk(&mut self, position: f64, bytes: usize) {
+        self.received += 1;
+        self.bytes = self.bytes.saturating_add(bytes as u64);
+
+        // Restart the extrapolation if the state sync restarted at an earlier key.
+        let (first_position, received) = match self.first_position {
+            Some((first_position, received)) if position >= first_position => {
+                (first_position, received + 1)
+            }
+            _ => (position, 1),
+        };
+        self.first_position = Some((first_position, received));
+
+        // Extrapolate the number of remaining chunks from the key space covered so far.
+        let covered = position - first_position;
+        if covered > 0.0 {
+            let remaining = ((received - 1) as f64 * (1.0 - position) / covered).ceil() as u64;
+            self.estimated_total = Some(self.received + remaining);
+        }
+    }
+}
+
+/// Returns the approximate position of a key in the key space as a fraction between 0 and 1.
+fn key_position(key: &KeyNibbles) -> f64 {
+    let prefix = u32::from_str_radix(&format!("{}00000000", key)[..8], 16).unwrap_or(0);
+    prefix as f64 / u32::MAX as f64
+}
+
 pub struct StateQueue<N: Network> {
     /// Configuration for the block queue.
     config: QueueConfig,
@@ -181,6 +228,9 @@ pub struct StateQueue<N: Network> {
     /// notification mechanism to wake us up once the list becomes nonempty if
     /// we find it empty.
     peers_became_nonempty: Option<BoxFuture<'static, ()>>,
+
+    /// The progress of the state sync.
+    chunk_progress: ChunkProgress,
 }
 
 impl<N: Network> StateQueue<N> {
@@ -220,6 +270,7 @@ impl<N: Network> StateQueue<N> {
             start_key,
             blockchain_rx,
             peers_became_nonempty: None,
+            chunk_progress: ChunkProgress::default(),
         }
     }
 
@@ -490,6 +541,20 @@ impl<N: Network> StateQueue<N> {
     pub fn chunk_request_state(&self) -> &ChunkRequestState {
         &self.start_key
     }
+
+    /// Returns the progress of the state sync.
+    pub fn progress(&self) -> LiveSyncProgress {
+        let progress = &self.chunk_progress;
+        let target = if self.start_key.is_complete() {
+            Some(progress.received)
+        } else {
+            progress.estimated_total
+        };
+        LiveSyncProgress {
+            chunks: SyncCounter::new(progress.received, target),
+            bytes_downloaded: progress.bytes,
+        }
+    }
 }
 
 impl<N: Network> Stream for StateQueue<N> {
@@ -558,10 +623,11 @@ impl<N: Network> Stream for StateQueue<N> {
                     // We throw away the remaining incoming chunks if we are complete.
                     // This avoids breaking the state sync invariant new block + no diffs, then no chunks.
                     if !self.start_key.is_complete() {
-                        let key = u32::from_str_radix(&format!("{}00000000", start_key)[..8], 16)
-                            .unwrap_or(0);
+                        let position = key_position(&start_key);
+                        self.chunk_progress
+                            .note_chunk(position, chunk.serialized_size());
 
-                        let percentage = (key as f32 / u32::MAX as f32) * 100.0;
+                        let percentage = position * 100.0;
                         log::info!(
                             ?start_key,
                             "Received state sync chunk, ~{:.2}% complete",
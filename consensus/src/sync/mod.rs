@@ -1,8 +1,11 @@
+pub mod checkpoint;
 #[cfg(feature = "full")]
 pub mod history;
 pub mod light;
 pub mod live;
 pub mod peer_list;
+pub mod peer_stats;
+pub mod progress;
 mod sync_queue;
 pub mod syncer;
 pub mod syncer_proxy;
@@ -0,0 +1,250 @@
+use std::{cmp, collections::HashMap, time::Duration};
+
+use instant::Instant;
+use nimiq_network_interface::network::Network;
+
+/// The response time assumed for peers we haven't heard from yet.
+const INITIAL_RESPONSE_TIME: Duration = Duration::from_millis(500);
+
+/// Statistics about the requests sent to a single peer.
+#[derive(Clone, Debug)]
+struct PeerStat {
+    /// Exponential moving average of the response times of successful requests.
+    avg_response_time: Duration,
+    /// Exponential moving average of the failure rate, between 0 and 1.
+    failure_rate: f64,
+    /// Number of completed requests, successful or not.
+    num_requests: u32,
+    /// Number of requests that are currently in flight.
+    num_pending: u32,
+    /// The time the peer was released from the sync component, if it hasn't been used since.
+    released_at: Option<Instant>,
+}
+
+impl Default for PeerStat {
+    fn default() -> Self {
+        Self {
+            avg_response_time: INITIAL_RESPONSE_TIME,
+            failure_rate: 0.0,
+            num_requests: 0,
+            num_pending: 0,
+            released_at: None,
+        }
+    }
+}
+
+/// Tracks the response times and failure rates of the peers that a sync component sends requests
+/// to, such that requests are sent to the fastest and most reliable peers first.
+///
+/// Peers without any history are assumed to respond within [`INITIAL_RESPONSE_TIME`], so new peers
+/// get a chance to prove themselves. Peers that fail the majority of their requests are only
+/// selected if no other peer is available.
+///
+/// The statistics of a peer that is released from a sync component, e.g. because it fell behind,
+/// are kept for [`PeerStats::RETENTION`], such that it can't clear a bad record by being released
+/// and added again. They are only dropped right away once the peer disconnects.
+#[derive(Debug)]
+pub struct PeerStats<N: Network> {
+    stats: HashMap<N::PeerId, PeerStat>,
+}
+
+impl<N: Network> Default for PeerStats<N> {
+    fn default() -> Self {
+        Self {
+            stats: HashMap::new(),
+        }
+    }
+}
+
+impl<N: Network> PeerStats<N> {
+    /// The weight of a new sample in the moving averages.
+    const SMOOTHING: f64 = 0.2;
+    /// The number of requests after which a peer's failure rate is considered meaningful.
+    const MIN_REQUESTS: u32 = 4;
+    /// The failure rate above which a peer is deprioritized.
+    const MAX_FAILURE_RATE: f64 = 0.5;
+    /// A request is hedged once it took this many times the peer's average response time.
+    const HEDGE_FACTOR: u32 = 3;
+    const MIN_HEDGE_DELAY: Duration = Duration::from_secs(1);
+    const MAX_HEDGE_DELAY: Duration = Duration::from_secs(10);
+    /// The time for which the statistics of a released peer are kept.
+    pub const RETENTION: Duration = Duration::from_secs(600);
+
+    /// Notes that a request to the peer has been sent.
+    pub fn start_request(&mut self, peer_id: N::PeerId) {
+        let stat = self.stats.entry(peer_id).or_default();
+        stat.num_pending += 1;
+        stat.released_at = None;
+    }
+
+    /// Notes that a request to the peer has been completed or dropped.
+    pub fn end_request(&mut self, peer_id: &N::PeerId) {
+        if let Some(stat) = self.stats.get_mut(peer_id) {
+            stat.num_pending = stat.num_pending.saturating_sub(1);
+        }
+    }
+
+    /// Records a successful response of the peer.
+    pub fn record_success(&mut self, peer_id: N::PeerId, response_time: Duration) {
+        let stat = self.stats.entry(peer_id).or_default();
+        stat.avg_response_time = stat
+            .avg_response_time
+            .mul_f64(1.0 - Self::SMOOTHING)
+            .saturating_add(response_time.mul_f64(Self::SMOOTHING));
+        stat.failure_rate *= 1.0 - Self::SMOOTHING;
+        stat.num_requests = stat.num_requests.saturating_add(1);
+    }
+
+    /// Records a failed request or an invalid response of the peer.
+    pub fn record_failure(&mut self, peer_id: N::PeerId) {
+        let stat = self.stats.entry(peer_id).or_default();
+        stat.failure_rate = stat.failure_rate * (1.0 - Self::SMOOTHING) + Self::SMOOTHING;
+        stat.num_requests = stat.num_requests.saturating_add(1);
+    }
+
+    /// Notes that the peer has been released from the sync component while it is still connected.
+    /// Its statistics are kept until [`PeerStats::RETENTION`] passed without it being used again.
+    /// Statistics of other peers whose retention expired are dropped.
+    pub fn release_peer(&mut self, peer_id: &N::PeerId) {
+        if let Some(stat) = self.stats.get_mut(peer_id) {
+            stat.released_at = Some(Instant::now());
+        }
+        self.stats.retain(|_, stat| {
+            stat.released_at
+                .map_or(true, |released_at| released_at.elapsed() < Self::RETENTION)
+        });
+    }
+
+    /// Drops the statistics of a peer that disconnected.
+    pub fn remove_peer(&mut self, peer_id: &N::PeerId) {
+        self.stats.remove(peer_id);
+    }
+
+    /// Returns whether the peer fails most of its requests.
+    pub fn is_unreliable(&self, peer_id: &N::PeerId) -> bool {
+        self.stats.get(peer_id).is_some_and(|stat| {
+            stat.num_requests >= Self::MIN_REQUESTS && stat.failure_rate > Self::MAX_FAILURE_RATE
+        })
+    }
+
+    /// Returns the time after which a request to the peer should be hedged by sending it to
+    /// another peer as well.
+    pub fn hedge_delay(&self, peer_id: &N::PeerId) -> Duration {
+        let avg_response_time = self
+            .stats
+            .get(peer_id)
+            .map(|stat| stat.avg_response_time)
+            .unwrap_or(INITIAL_RESPONSE_TIME);
+        (avg_response_time * Self::HEDGE_FACTOR).clamp(Self::MIN_HEDGE_DELAY, Self::MAX_HEDGE_DELAY)
+    }
+
+    /// The expected time until a new request to the peer is answered. Requests that are already
+    /// in flight and failures make a peer more expensive.
+    fn cost(&self, peer_id: &N::PeerId) -> f64 {
+        let stat = self.stats.get(peer_id).cloned().unwrap_or_default();
+        stat.avg_response_time.as_secs_f64()
+            * (1.0 + stat.num_pending as f64)
+            * (1.0 + stat.failure_rate)
+    }
+
+    /// Selects the peer to send the next request to, skipping `exclude`. Unreliable peers are only
+    /// selected if there is no other peer.
+    pub fn select(&self, peers: &[N::PeerId], exclude: Option<&N::PeerId>) -> Option<N::PeerId> {
+        let candidates = || peers.iter().filter(|peer_id| Some(*peer_id) != exclude);
+        let best = |peer_ids: &mut dyn Iterator<Item = &N::PeerId>| {
+            peer_ids
+                .min_by(|a, b| {
+                    self.cost(a)
+                        .partial_cmp(&self.cost(b))
+                        .unwrap_or(cmp::Ordering::Equal)
+                })
+                .copied()
+        };
+
+        best(&mut candidates().filter(|peer_id| !self.is_unreliable(peer_id)))
+            .or_else(|| best(&mut candidates()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use nimiq_network_mock::{MockNetwork, MockPeerId};
+    use nimiq_test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn it_prefers_fast_and_reliable_peers() {
+        let mut stats = PeerStats::<MockNetwork>::default();
+        let (fast, slow, failing) = (MockPeerId(1), MockPeerId(2), MockPeerId(3));
+        let peers = [fast, slow, failing];
+
+        // Without any statistics, all peers are equal.
+        assert!(stats.select(&peers, None).is_some());
+
+        for _ in 0..5 {
+            stats.record_success(fast, Duration::from_millis(50));
+            stats.record_success(slow, Duration::from_secs(3));
+            stats.record_failure(failing);
+        }
+        assert!(stats.is_unreliable(&failing));
+        assert!(!stats.is_unreliable(&slow));
+
+        assert_eq!(stats.select(&peers, None), Some(fast));
+        assert_eq!(stats.select(&peers, Some(&fast)), Some(slow));
+        // Unreliable peers are only used as a last resort.
+        assert_eq!(stats.select(&[fast, failing], Some(&fast)), Some(failing));
+
+        // Slow peers are hedged later than fast ones.
+        assert!(stats.hedge_delay(&slow) > stats.hedge_delay(&fast));
+    }
+
+    #[test]
+    fn it_spreads_requests_across_peers() {
+        let mut stats = PeerStats::<MockNetwork>::default();
+        let (peer1, peer2) = (MockPeerId(1), MockPeerId(2));
+        let peers = [peer1, peer2];
+
+        stats.record_success(peer1, Duration::from_millis(100));
+        stats.record_success(peer2, Duration::from_millis(150));
+        assert_eq!(stats.select(&peers, None), Some(peer1));
+
+        stats.start_request(peer1);
+        assert_eq!(stats.select(&peers, None), Some(peer2));
+
+        stats.end_request(&peer1);
+        assert_eq!(stats.select(&peers, None), Some(peer1));
+    }
+
+    #[test]
+    fn it_keeps_the_stats_of_released_peers() {
+        let mut stats = PeerStats::<MockNetwork>::default();
+        let (failing, other) = (MockPeerId(1), MockPeerId(2));
+
+        for _ in 0..5 {
+            stats.record_failure(failing);
+        }
+        stats.record_success(other, Duration::from_millis(100));
+
+        // Releasing the peer doesn't clear its record.
+        stats.release_peer(&failing);
+        assert!(stats.is_unreliable(&failing));
+
+        // Once the retention passed, the stats are dropped with the next release.
+        stats.stats.get_mut(&failing).unwrap().released_at =
+            Some(Instant::now() - PeerStats::<MockNetwork>::RETENTION);
+        stats.release_peer(&other);
+        assert!(!stats.is_unreliable(&failing));
+        assert!(stats.stats.contains_key(&other));
+
+        // Using a released peer again keeps its stats until it is released again.
+        stats.start_request(other);
+        assert!(stats.stats[&other].released_at.is_none());
+
+        // Disconnected peers are dropped right away.
+        stats.remove_peer(&other);
+        assert!(!stats.stats.contains_key(&other));
+    }
+}
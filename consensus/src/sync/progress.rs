@@ -0,0 +1,234 @@
+use std::{cmp::max, time::Duration};
+
+use instant::Instant;
+use nimiq_primitives::policy::Policy;
+
+/// The phase the synchronization is currently in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SyncPhase {
+    /// There are no peers to synchronize with.
+    #[default]
+    WaitingForPeers,
+    /// Synchronizing the macro chain up to the latest macro block of our peers. Depending on the
+    /// sync mode, this is the full history or the ZKP and the latest election/checkpoint blocks.
+    MacroSync,
+    /// Downloading the accounts trie in chunks.
+    StateSync,
+    /// Catching up with the blocks our peers are processing.
+    LiveSync,
+    /// Consensus is established.
+    Synced,
+}
+
+/// Progress towards a target, e.g. the number of the latest epoch of our peers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SyncCounter {
+    /// The number of items processed so far.
+    pub processed: u64,
+    /// The number of items to be processed in total, if known.
+    pub target: Option<u64>,
+}
+
+impl SyncCounter {
+    pub fn new(processed: u64, target: Option<u64>) -> Self {
+        Self {
+            processed,
+            target: target.map(|target| max(target, processed)),
+        }
+    }
+
+    /// Returns the number of items that are still left to be processed, if the target is known.
+    pub fn remaining(&self) -> Option<u64> {
+        self.target
+            .map(|target| target.saturating_sub(self.processed))
+    }
+}
+
+/// Progress reported by a `MacroSync`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MacroSyncProgress {
+    /// The latest epoch number announced by any of our peers.
+    pub target_epoch: Option<u32>,
+    /// The latest batch number announced by any of our peers.
+    pub target_batch: Option<u32>,
+    /// The number of bytes of blocks, proofs and history items downloaded.
+    pub bytes_downloaded: u64,
+}
+
+impl MacroSyncProgress {
+    /// Updates the targets with the macro chain a peer reported. `election_epoch` is the epoch of
+    /// the peer's latest election block and `checkpoint` the block number of its latest
+    /// checkpoint block, if any.
+    pub(crate) fn update_target(&mut self, election_epoch: Option<u32>, checkpoint: Option<u32>) {
+        if let Some(epoch) = election_epoch {
+            self.target_epoch = max(self.target_epoch, Some(epoch));
+            self.target_batch = max(
+                self.target_batch,
+                Some(epoch.saturating_mul(Policy::batches_per_epoch() as u32)),
+            );
+        }
+        if let Some(block_number) = checkpoint {
+            self.target_batch = max(self.target_batch, Some(Policy::batch_at(block_number)));
+        }
+    }
+
+    pub(crate) fn add_bytes(&mut self, bytes: usize) {
+        self.bytes_downloaded = self.bytes_downloaded.saturating_add(bytes as u64);
+    }
+}
+
+/// Progress reported by a `LiveSync`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LiveSyncProgress {
+    /// The accounts trie chunks received. The target is estimated from the position of the
+    /// latest chunk in the key space.
+    pub chunks: SyncCounter,
+    /// The number of bytes of chunks downloaded.
+    pub bytes_downloaded: u64,
+}
+
+/// Snapshot of the synchronization progress.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyncStatus<TPeerId> {
+    /// The current phase.
+    pub phase: SyncPhase,
+    /// Election blocks synchronized and the latest epoch of our peers.
+    pub epochs: SyncCounter,
+    /// Macro blocks synchronized and the latest batch of our peers.
+    pub batches: SyncCounter,
+    /// Accounts trie chunks received during the state sync.
+    pub chunks: SyncCounter,
+    /// Head block number and the highest block buffered during the live sync.
+    pub blocks: SyncCounter,
+    /// The number of bytes downloaded by the macro and state sync.
+    pub bytes_downloaded: u64,
+    /// The estimated time until the current phase is finished, if it can be estimated.
+    pub eta: Option<Duration>,
+    /// The peers used in the current phase.
+    pub peers: Vec<TPeerId>,
+}
+
+impl<TPeerId> Default for SyncStatus<TPeerId> {
+    fn default() -> Self {
+        Self {
+            phase: SyncPhase::default(),
+            epochs: SyncCounter::default(),
+            batches: SyncCounter::default(),
+            chunks: SyncCounter::default(),
+            blocks: SyncCounter::default(),
+            bytes_downloaded: 0,
+            eta: None,
+            peers: Vec::new(),
+        }
+    }
+}
+
+/// Estimates the remaining time of a sync phase from the rate at which its counter advanced
+/// since the phase started.
+#[derive(Default)]
+pub(crate) struct EtaEstimator {
+    phase: SyncPhase,
+    /// The time and counter value at which the estimation started.
+    start: Option<(Instant, u64)>,
+    last_processed: u64,
+    eta: Option<Duration>,
+}
+
+impl EtaEstimator {
+    /// Updates the estimate with the counter of the current phase. The estimate is only
+    /// recomputed when the counter advanced, such that it stays stable in between.
+    pub(crate) fn update(
+        &mut self,
+        phase: SyncPhase,
+        counter: Option<SyncCounter>,
+    ) -> Option<Duration> {
+        let Some(counter) = counter else {
+            self.phase = phase;
+            self.start = None;
+            self.eta = None;
+            return None;
+        };
+
+        match self.start {
+            Some((started_at, processed_at_start))
+                if phase == self.phase && counter.processed >= processed_at_start =>
+            {
+                if counter.processed != self.last_processed {
+                    self.last_processed = counter.processed;
+
+                    let processed = counter.processed - processed_at_start;
+                    self.eta = counter.remaining().and_then(|remaining| {
+                        (processed > 0).then(|| {
+                            started_at
+                                .elapsed()
+                                .mul_f64(remaining as f64 / processed as f64)
+                        })
+                    });
+                }
+            }
+            _ => {
+                self.phase = phase;
+                self.start = Some((Instant::now(), counter.processed));
+                self.last_processed = counter.processed;
+                self.eta = None;
+            }
+        }
+
+        self.eta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimiq_primitives::policy::Policy;
+    use nimiq_test_log::test;
+
+    use super::{EtaEstimator, MacroSyncProgress, SyncCounter, SyncPhase};
+
+    #[test]
+    fn it_tracks_the_highest_target() {
+        let mut progress = MacroSyncProgress::default();
+        let batches_per_epoch = Policy::batches_per_epoch() as u32;
+
+        progress.update_target(Some(3), None);
+        assert_eq!(progress.target_epoch, Some(3));
+        assert_eq!(progress.target_batch, Some(3 * batches_per_epoch));
+
+        let checkpoint = Policy::macro_block_of(3 * batches_per_epoch + 2).unwrap();
+        progress.update_target(Some(2), Some(checkpoint));
+        assert_eq!(progress.target_epoch, Some(3));
+        assert_eq!(progress.target_batch, Some(3 * batches_per_epoch + 2));
+    }
+
+    #[test]
+    fn it_estimates_only_with_progress() {
+        let mut estimator = EtaEstimator::default();
+
+        assert_eq!(estimator.update(SyncPhase::MacroSync, None), None);
+        assert_eq!(
+            estimator.update(SyncPhase::MacroSync, Some(SyncCounter::new(10, Some(20)))),
+            None
+        );
+        // Without progress, there is nothing to base an estimate on.
+        assert_eq!(
+            estimator.update(SyncPhase::MacroSync, Some(SyncCounter::new(10, Some(20)))),
+            None
+        );
+        assert!(estimator
+            .update(SyncPhase::MacroSync, Some(SyncCounter::new(15, Some(20))))
+            .is_some());
+        // A new phase starts over.
+        assert_eq!(
+            estimator.update(SyncPhase::LiveSync, Some(SyncCounter::new(100, Some(120)))),
+            None
+        );
+    }
+
+    #[test]
+    fn counter_target_is_never_below_processed() {
+        let counter = SyncCounter::new(10, Some(5));
+        assert_eq!(counter.target, Some(10));
+        assert_eq!(counter.remaining(), Some(0));
+        assert_eq!(SyncCounter::new(10, None).remaining(), None);
+    }
+}
@@ -9,13 +9,19 @@ use std::{
     task::{Context, Poll, Waker},
 };
 
-use futures::{future, future::BoxFuture, FutureExt, Stream, StreamExt};
+use futures::{
+    future,
+    future::{BoxFuture, Either},
+    FutureExt, Stream, StreamExt,
+};
+use instant::Instant;
 use nimiq_network_interface::network::Network;
+use nimiq_time::sleep;
 use nimiq_utils::{stream::FuturesUnordered, WakerExt as _};
 use parking_lot::RwLock;
 use pin_project::pin_project;
 
-use super::peer_list::PeerList;
+use super::{peer_list::PeerList, peer_stats::PeerStats};
 use crate::sync::peer_list::PeerListIndex;
 
 #[pin_project]
@@ -80,6 +86,42 @@ type RequestFn<TId, TNetwork, TOutput, TError> = fn(
     <TNetwork as Network>::PeerId,
 ) -> BoxFuture<'static, Result<TOutput, TError>>;
 type VerifyFn<TId, TOutput, TVerifyState> = fn(&TId, &TOutput, &mut TVerifyState) -> bool;
+/// The result of a request together with the peer that answered it.
+/// `None` if there was no peer to send the request to.
+type RequestResult<TNetwork, TOutput, TError> =
+    Option<(<TNetwork as Network>::PeerId, Result<TOutput, TError>)>;
+
+/// Keeps track of a request in flight in the [`PeerStats`], also if the request is dropped.
+struct PendingRequest<TNetwork: Network> {
+    peer_stats: Arc<RwLock<PeerStats<TNetwork>>>,
+    peer_id: TNetwork::PeerId,
+    start: Instant,
+}
+
+impl<TNetwork: Network> PendingRequest<TNetwork> {
+    fn new(peer_stats: Arc<RwLock<PeerStats<TNetwork>>>, peer_id: TNetwork::PeerId) -> Self {
+        peer_stats.write().start_request(peer_id);
+        Self {
+            peer_stats,
+            peer_id,
+            start: Instant::now(),
+        }
+    }
+
+    fn finish<TOutput, TError>(self, result: &Result<TOutput, TError>) {
+        let mut peer_stats = self.peer_stats.write();
+        match result {
+            Ok(_) => peer_stats.record_success(self.peer_id, self.start.elapsed()),
+            Err(_) => peer_stats.record_failure(self.peer_id),
+        }
+    }
+}
+
+impl<TNetwork: Network> Drop for PendingRequest<TNetwork> {
+    fn drop(&mut self) {
+        self.peer_stats.write().end_request(&self.peer_id);
+    }
+}
 
 /// The SyncQueue will request a list of ids from a set of peers
 /// and implements an ordered stream over the resulting objects.
@@ -95,21 +137,25 @@ pub struct SyncQueue<
     network: Arc<TNetwork>,
     desired_pending_size: usize,
     ids_to_request: VecDeque<(TId, Option<TNetwork::PeerId>)>,
-    pending_futures:
-        FuturesUnordered<OrderWrapper<TId, BoxFuture<'static, Option<Result<TOutput, TError>>>>>,
-    queued_outputs: BinaryHeap<OrderWrapper<TId, Option<TOutput>>>,
+    pending_futures: FuturesUnordered<
+        OrderWrapper<TId, BoxFuture<'static, RequestResult<TNetwork, TOutput, TError>>>,
+    >,
+    queued_outputs: BinaryHeap<OrderWrapper<TId, Option<(TNetwork::PeerId, TOutput)>>>,
     next_incoming_index: usize,
     next_outgoing_index: usize,
     current_peer_index: PeerListIndex,
     request_fn: RequestFn<TId, TNetwork, TOutput, TError>,
     verify_fn: VerifyFn<TId, TOutput, TVerifyState>,
     verify_state: TVerifyState,
+    /// If set, peers are selected based on their response times and failure rates instead of
+    /// round-robin, and slow requests are hedged by sending them to a second peer.
+    peer_stats: Option<Arc<RwLock<PeerStats<TNetwork>>>>,
     waker: Option<Waker>,
 }
 
 impl<TNetwork, TId, TOutput, TError> SyncQueue<TNetwork, TId, TOutput, TError, ()>
 where
-    TId: Clone + Debug,
+    TId: Clone + Debug + Send + 'static,
     TOutput: Send + Unpin + 'static,
     TError: Debug + Display + Send,
     TNetwork: Network,
@@ -137,7 +183,7 @@ where
 impl<TNetwork, TId, TOutput, TError, TVerifyState>
     SyncQueue<TNetwork, TId, TOutput, TError, TVerifyState>
 where
-    TId: Clone + Debug,
+    TId: Clone + Debug + Send + 'static,
     TOutput: Send + Unpin + 'static,
     TError: Debug + Display + Send,
     TNetwork: Network,
@@ -174,10 +220,110 @@ where
             request_fn,
             verify_fn,
             verify_state: initial_verify_state,
+            peer_stats: None,
             waker: None,
         }
     }
 
+    /// Enables the adaptive peer selection based on the given statistics, which are updated with
+    /// every request of this queue.
+    pub fn with_peer_stats(mut self, peer_stats: Arc<RwLock<PeerStats<TNetwork>>>) -> Self {
+        self.peer_stats = Some(peer_stats);
+        self
+    }
+
+    /// Selects the next peer to send a request to, skipping `exclude` if possible.
+    fn next_peer(
+        &self,
+        peer_index: &mut PeerListIndex,
+        exclude: Option<&TNetwork::PeerId>,
+    ) -> Option<TNetwork::PeerId> {
+        let peers = self.peers.read();
+        let Some(ref peer_stats) = self.peer_stats else {
+            return peers.increment_and_get(peer_index);
+        };
+
+        let peer_id = peer_stats
+            .read()
+            .select(peers.peers(), exclude)
+            .or_else(|| exclude.copied().filter(|peer_id| peers.has_peer(peer_id)))?;
+        *peer_index = peers.index_of(&peer_id)?;
+        Some(peer_id)
+    }
+
+    /// Creates the future for a request of `id` from `peer_id`.
+    ///
+    /// With adaptive peer selection, the request is also sent to a second peer if `peer_id`
+    /// didn't respond within its usual response time. The first successful response is used.
+    fn request(
+        &self,
+        id: TId,
+        peer_id: TNetwork::PeerId,
+    ) -> BoxFuture<'static, RequestResult<TNetwork, TOutput, TError>> {
+        let network = Arc::clone(&self.network);
+        let Some(ref peer_stats) = self.peer_stats else {
+            return (self.request_fn)(id, network, peer_id)
+                .map(move |result| Some((peer_id, result)))
+                .boxed();
+        };
+
+        let request_fn = self.request_fn;
+        let timed_request = move |id: TId, peer_id, peer_stats| {
+            let request = PendingRequest::new(peer_stats, peer_id);
+            request_fn(id, Arc::clone(&network), peer_id).map(move |result| {
+                request.finish(&result);
+                (peer_id, result)
+            })
+        };
+
+        let (hedge_peer_id, hedge_delay) = {
+            let peers = self.peers.read();
+            let peer_stats = peer_stats.read();
+            (
+                peer_stats.select(peers.peers(), Some(&peer_id)),
+                peer_stats.hedge_delay(&peer_id),
+            )
+        };
+
+        let primary = timed_request(id.clone(), peer_id, Arc::clone(peer_stats));
+        let Some(hedge_peer_id) = hedge_peer_id else {
+            return primary.map(Some).boxed();
+        };
+
+        let peer_stats = Arc::clone(peer_stats);
+        let hedge = async move {
+            sleep(hedge_delay).await;
+            log::debug!(
+                %peer_id,
+                %hedge_peer_id,
+                ?id,
+                ?hedge_delay,
+                "Hedging slow request"
+            );
+            timed_request(id, hedge_peer_id, peer_stats).await
+        };
+
+        async move {
+            match future::select(primary.boxed(), hedge.boxed()).await {
+                // A failed primary request is retried with another peer right away.
+                Either::Left((result, _)) => Some(result),
+                Either::Right(((hedge_peer_id, Ok(output)), _)) => {
+                    Some((hedge_peer_id, Ok(output)))
+                }
+                // If the hedged request failed, we still wait for the primary one.
+                Either::Right(((_, Err(_)), primary)) => Some(primary.await),
+            }
+        }
+        .boxed()
+    }
+
+    /// Notes an invalid response of the peer, such that it is deprioritized.
+    fn record_invalid_response(&self, peer_id: TNetwork::PeerId) {
+        if let Some(ref peer_stats) = self.peer_stats {
+            peer_stats.write().record_failure(peer_id);
+        }
+    }
+
     fn try_push_futures(&mut self) {
         // Determine number of new futures required to maintain desired_pending_size.
         let num_ids_to_request = cmp::min(
@@ -204,11 +350,12 @@ where
             // If we know the peer that sent us this block, we ask them first.
             let peer = match pubsub_peer {
                 Some(pubsub_peer) => Some(pubsub_peer),
-                None => self
-                    .peers
-                    .read()
-                    .increment_and_get(&mut self.current_peer_index)
-                    .map(|peer_id| (peer_id, self.current_peer_index.clone())),
+                None => {
+                    let mut peer_index = self.current_peer_index.clone();
+                    let peer_id = self.next_peer(&mut peer_index, None);
+                    self.current_peer_index = peer_index.clone();
+                    peer_id.map(|peer_id| (peer_id, peer_index))
+                }
             };
 
             let wrapper = match peer {
@@ -222,9 +369,7 @@ where
                     );
 
                     OrderWrapper {
-                        data: (self.request_fn)(id.clone(), Arc::clone(&self.network), peer_id)
-                            .map(Some)
-                            .boxed(),
+                        data: self.request(id.clone(), peer_id),
                         id,
                         index: self.next_incoming_index,
                         peer: peer_index,
@@ -282,7 +427,8 @@ where
         }
 
         // Re-request from different peer. Return an error if there are no more peers.
-        let peer = match self.peers.read().increment_and_get(&mut peer_index) {
+        let previous_peer = self.peers.read().get(&peer_index);
+        let peer = match self.next_peer(&mut peer_index, previous_peer.as_ref()) {
             Some(peer) => peer,
             None => return false,
         };
@@ -296,9 +442,7 @@ where
         );
 
         let wrapper = OrderWrapper {
-            data: (self.request_fn)(id.clone(), Arc::clone(&self.network), peer)
-                .map(Some)
-                .boxed(),
+            data: self.request(id.clone(), peer),
             id,
             index,
             peer: peer_index,
@@ -361,7 +505,7 @@ impl<TNetwork, TId, TOutput, TError, TVerifyState> Stream
     for SyncQueue<TNetwork, TId, TOutput, TError, TVerifyState>
 where
     TNetwork: Network,
-    TId: Clone + Unpin + Debug,
+    TId: Clone + Unpin + Debug + Send + 'static,
     TOutput: Send + Unpin,
     TError: Debug + Display + Send,
     TVerifyState: Unpin + 'static,
@@ -380,12 +524,13 @@ where
                 let request = self.queued_outputs.pop().unwrap();
 
                 match request.data {
-                    Some(data) => {
+                    Some((peer_id, data)) => {
                         if (self.verify_fn)(&request.id, &data, &mut self.verify_state) {
                             self.next_outgoing_index += 1;
                             return Poll::Ready(Some(Ok(data)));
                         } else {
-                            debug!(peer_id = %request.peer, id = ?request.id, "Verification failed");
+                            debug!(%peer_id, id = ?request.id, "Verification failed");
+                            self.record_invalid_response(peer_id);
                             let id = request.id.clone();
                             if !self.retry_request(
                                 request.id,
@@ -408,20 +553,28 @@ where
 
         loop {
             match self.pending_futures.poll_next_unpin(cx) {
-                Poll::Ready(Some(result)) => {
+                Poll::Ready(Some(mut result)) => {
+                    // If a hedged request answered first, a retry must skip the hedge peer.
+                    if let Some((ref peer_id, _)) = result.data {
+                        if let Some(peer_index) = self.peers.read().index_of(peer_id) {
+                            result.peer = peer_index;
+                        }
+                    }
+
                     match result.data {
-                        Some(Ok(output)) => {
+                        Some((peer_id, Ok(output))) => {
                             if result.index == self.next_outgoing_index {
                                 if (self.verify_fn)(&result.id, &output, &mut self.verify_state) {
                                     self.next_outgoing_index += 1;
                                     return Poll::Ready(Some(Ok(output)));
                                 } else {
-                                    debug!(peer_id = %result.peer, id = ?result.id, "Verification failed");
+                                    debug!(%peer_id, id = ?result.id, "Verification failed");
+                                    self.record_invalid_response(peer_id);
                                 }
                             } else {
                                 self.queued_outputs.push(OrderWrapper {
                                     id: result.id,
-                                    data: Some(output),
+                                    data: Some((peer_id, output)),
                                     index: result.index,
                                     peer: result.peer,
                                     num_tries: result.num_tries,
@@ -429,8 +582,8 @@ where
                                 continue;
                             }
                         }
-                        Some(Err(error)) => {
-                            debug!(peer_id = %result.peer, id = ?result.id, %error, "Request error");
+                        Some((peer_id, Err(error))) => {
+                            debug!(%peer_id, id = ?result.id, %error, "Request error");
                         }
                         None => {
                             debug!(id = ?result.id, "Request error: no peers available");
@@ -485,15 +638,19 @@ where
 #[cfg(test)]
 mod tests {
     use std::{
-        sync::Arc,
+        sync::{Arc, Mutex},
         task::{Context, Poll},
+        time::Duration,
     };
 
     use futures::{future, task::noop_waker_ref, FutureExt, StreamExt};
-    use nimiq_network_mock::MockHub;
+    use nimiq_network_mock::{MockHub, MockNetwork, MockPeerId};
+    use nimiq_test_log::test;
+    use nimiq_time::sleep;
+    use parking_lot::RwLock;
     use thiserror::Error;
 
-    use crate::sync::sync_queue::SyncQueue;
+    use crate::sync::{peer_list::PeerList, peer_stats::PeerStats, sync_queue::SyncQueue};
 
     #[test]
     fn it_can_handle_no_peers() {
@@ -517,4 +674,62 @@ mod tests {
             _ => panic!("Expected error"),
         };
     }
+
+    #[test(tokio::test)]
+    async fn it_hedges_slow_peers_and_skips_failing_peers() {
+        #[derive(Debug, Error)]
+        #[error("error")]
+        struct Error;
+
+        // The slow peer answers correctly, but only after the request has been hedged. The
+        // failing peer answers faster, but with data that doesn't verify.
+        const SLOW: MockPeerId = MockPeerId(1);
+        const FAILING: MockPeerId = MockPeerId(2);
+        const RELIABLE: MockPeerId = MockPeerId(3);
+        static REQUESTS: Mutex<Vec<MockPeerId>> = Mutex::new(Vec::new());
+
+        let mut hub = MockHub::new();
+        let network = Arc::new(hub.new_network());
+
+        let mut peers = PeerList::<MockNetwork>::default();
+        for peer_id in [SLOW, FAILING, RELIABLE] {
+            peers.add_peer(peer_id);
+        }
+
+        // The reliable peer has been slow in the past, so the failing peer is preferred to hedge
+        // the first request.
+        let mut peer_stats = PeerStats::<MockNetwork>::default();
+        peer_stats.record_success(RELIABLE, Duration::from_millis(1500));
+
+        let mut queue: SyncQueue<_, u32, u32, Error, _> = SyncQueue::with_verification(
+            network,
+            vec![(1, None)],
+            Arc::new(RwLock::new(peers)),
+            1,
+            |id, _, peer_id| {
+                REQUESTS.lock().unwrap().push(peer_id);
+                async move {
+                    if peer_id == SLOW {
+                        sleep(Duration::from_secs(3)).await;
+                        Ok(id)
+                    } else if peer_id == FAILING {
+                        sleep(Duration::from_secs(1)).await;
+                        Ok(0)
+                    } else {
+                        Ok(id)
+                    }
+                }
+                .boxed()
+            },
+            |id, output, _| id == output,
+            (),
+        )
+        .with_peer_stats(Arc::new(RwLock::new(peer_stats)));
+
+        assert_eq!(queue.next().await, Some(Ok(1)));
+
+        // The hedged request to the failing peer answered first and didn't verify. The retry
+        // is sent to another peer, not to the failing one again.
+        assert_eq!(*REQUESTS.lock().unwrap(), [SLOW, FAILING, SLOW, RELIABLE]);
+    }
 }
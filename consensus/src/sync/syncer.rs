@@ -17,7 +17,14 @@ use nimiq_time::{interval, Interval};
 use nimiq_utils::stream::FuturesUnordered;
 
 use crate::{
-    consensus::ResolveBlockRequest, messages::RequestHead, sync::live::block_queue::BlockSource,
+    consensus::ResolveBlockRequest,
+    messages::RequestHead,
+    sync::{
+        live::block_queue::BlockSource,
+        progress::{
+            EtaEstimator, LiveSyncProgress, MacroSyncProgress, SyncCounter, SyncPhase, SyncStatus,
+        },
+    },
 };
 
 /// Trait that defines how a node synchronizes macro blocks
@@ -31,6 +38,14 @@ pub trait MacroSync<TPeerId>: Stream<Item = MacroSyncReturn<TPeerId>> + Unpin +
     const MAX_REQUEST_EPOCHS: u16;
     /// Adds a peer to synchronize macro blocks
     fn add_peer(&mut self, peer_id: TPeerId);
+    /// Returns the list of peers that are being synchronized with
+    fn peers(&self) -> Vec<TPeerId> {
+        Vec::new()
+    }
+    /// Returns the targets and the number of bytes downloaded so far
+    fn progress(&self) -> MacroSyncProgress {
+        MacroSyncProgress::default()
+    }
 }
 
 /// Trait that defines how a node synchronizes receiving the blocks the peers are currently
@@ -53,6 +68,10 @@ pub trait LiveSync<N: Network>: Stream<Item = LiveSyncEvent<N::PeerId>> + Unpin
     fn state_complete(&self) -> bool {
         true
     }
+    /// Returns the progress of the state sync (or the default if there is no state sync)
+    fn progress(&self) -> LiveSyncProgress {
+        LiveSyncProgress::default()
+    }
     /// Initiates an attempt to resolve a ResolveBlockRequest.
     fn resolve_block(&mut self, request: ResolveBlockRequest<N>);
 }
@@ -144,6 +163,12 @@ pub struct Syncer<N: Network, M: MacroSync<N::PeerId>, L: LiveSync<N>> {
 
     /// The number of blockchain extensions triggered by block announcements
     accepted_announcements: usize,
+
+    /// The number of blocks remaining in the live sync buffer as of the last buffered block
+    blocks_remaining: usize,
+
+    /// Estimates the remaining time of the current sync phase
+    eta: EtaEstimator,
 }
 
 impl<N: Network, M: MacroSync<N::PeerId>, L: LiveSync<N>> Syncer<N, M, L> {
@@ -167,6 +192,8 @@ impl<N: Network, M: MacroSync<N::PeerId>, L: LiveSync<N>> Syncer<N, M, L> {
             check_interval: interval(Self::CHECK_INTERVAL),
             pending_checks: Default::default(),
             accepted_announcements: 0,
+            blocks_remaining: 0,
+            eta: EtaEstimator::default(),
         }
     }
 
@@ -200,6 +227,75 @@ impl<N: Network, M: MacroSync<N::PeerId>, L: LiveSync<N>> Syncer<N, M, L> {
         self.live_sync.state_complete()
     }
 
+    /// Returns the current sync status. `established` indicates whether consensus is established.
+    pub fn sync_status(&mut self, established: bool) -> SyncStatus<N::PeerId> {
+        let macro_progress = self.macro_sync.progress();
+        let live_progress = self.live_sync.progress();
+
+        let phase = if established {
+            SyncPhase::Synced
+        } else if self.live_sync.num_peers() > 0 {
+            if self.live_sync.state_complete() {
+                SyncPhase::LiveSync
+            } else {
+                SyncPhase::StateSync
+            }
+        } else if self.macro_sync.peers().is_empty() {
+            SyncPhase::WaitingForPeers
+        } else {
+            SyncPhase::MacroSync
+        };
+
+        let (epochs, batches, blocks) = {
+            let blockchain = self.blockchain.read();
+            let block_number = blockchain.block_number() as u64;
+            (
+                SyncCounter::new(
+                    blockchain.election_head().epoch_number() as u64,
+                    macro_progress.target_epoch.map(u64::from),
+                ),
+                SyncCounter::new(
+                    blockchain.macro_head().batch_number() as u64,
+                    macro_progress.target_batch.map(u64::from),
+                ),
+                SyncCounter::new(
+                    block_number,
+                    Some(block_number + self.blocks_remaining as u64),
+                ),
+            )
+        };
+        let chunks = live_progress.chunks;
+
+        let eta = self.eta.update(
+            phase,
+            match phase {
+                SyncPhase::MacroSync => Some(batches),
+                SyncPhase::StateSync => Some(chunks),
+                SyncPhase::LiveSync => Some(blocks),
+                SyncPhase::WaitingForPeers | SyncPhase::Synced => None,
+            },
+        );
+
+        let peers = match phase {
+            SyncPhase::WaitingForPeers => vec![],
+            SyncPhase::MacroSync => self.macro_sync.peers(),
+            _ => self.live_sync.peers(),
+        };
+
+        SyncStatus {
+            phase,
+            epochs,
+            batches,
+            chunks,
+            blocks,
+            bytes_downloaded: macro_progress
+                .bytes_downloaded
+                .saturating_add(live_progress.bytes_downloaded),
+            eta,
+            peers,
+        }
+    }
+
     /// Initiates an attempt to resolve a ResolveBlockRequest.
     pub fn resolve_block(&mut self, request: ResolveBlockRequest<N>) {
         self.live_sync.resolve_block(request)
@@ -343,8 +439,16 @@ impl<N: Network, M: MacroSync<N::PeerId>, L: LiveSync<N>> Stream for Syncer<N, M
         while let Poll::Ready(Some(result)) = self.live_sync.poll_next_unpin(cx) {
             match result {
                 LiveSyncEvent::PushEvent(push_event) => {
-                    if let LiveSyncPushEvent::AcceptedAnnouncedBlock(..) = push_event {
-                        self.accepted_announcements = self.accepted_announcements.saturating_add(1);
+                    match &push_event {
+                        LiveSyncPushEvent::AcceptedAnnouncedBlock(..) => {
+                            self.accepted_announcements =
+                                self.accepted_announcements.saturating_add(1);
+                            self.blocks_remaining = 0;
+                        }
+                        LiveSyncPushEvent::AcceptedBufferedBlock(_, remaining_in_buffer) => {
+                            self.blocks_remaining = *remaining_in_buffer;
+                        }
+                        _ => {}
                     }
                     return Poll::Ready(Some(push_event));
                 }
@@ -25,12 +25,14 @@ use crate::sync::{
 use crate::{
     consensus::ResolveBlockRequest,
     sync::{
+        checkpoint::TrustedCheckpoint,
         light::LightMacroSync,
         live::{
             block_queue::{BlockQueue, BlockSource},
             queue::QueueConfig,
             BlockLiveSync,
         },
+        progress::SyncStatus,
         syncer::{LiveSyncPushEvent, Syncer},
     },
 };
@@ -207,6 +209,27 @@ impl<N: Network> SyncerProxy<N> {
         ))
     }
 
+    /// Pins a trusted election block, such that the macro sync refuses chains that don't contain
+    /// it. If `sync_from_checkpoint` is set, full and light nodes start the macro sync from the
+    /// checkpoint when a peer can't provide a ZKP.
+    pub fn set_trusted_checkpoint(
+        &mut self,
+        checkpoint: TrustedCheckpoint,
+        sync_from_checkpoint: bool,
+    ) {
+        match self {
+            #[cfg(feature = "full")]
+            SyncerProxy::History(syncer) => syncer.macro_sync.set_trusted_checkpoint(checkpoint),
+            #[cfg(feature = "full")]
+            SyncerProxy::Full(syncer) => syncer
+                .macro_sync
+                .set_trusted_checkpoint(checkpoint, sync_from_checkpoint),
+            SyncerProxy::Light(syncer) => syncer
+                .macro_sync
+                .set_trusted_checkpoint(checkpoint, sync_from_checkpoint),
+        }
+    }
+
     /// Pushes a block for the live sync method
     pub fn push_block(&mut self, block: Block, block_source: BlockSource<N>) {
         gen_syncer_match!(self, push_block, block, block_source)
@@ -235,6 +258,11 @@ impl<N: Network> SyncerProxy<N> {
     pub fn resolve_block(&mut self, request: ResolveBlockRequest<N>) {
         gen_syncer_match!(self, resolve_block, request)
     }
+
+    /// Returns the current sync status
+    pub fn sync_status(&mut self, established: bool) -> SyncStatus<N::PeerId> {
+        gen_syncer_match!(self, sync_status, established)
+    }
 }
 
 impl<N: Network> Stream for SyncerProxy<N> {
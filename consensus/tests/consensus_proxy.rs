@@ -1,18 +1,29 @@
-use std::{str::FromStr, sync::Arc};
+use std::{str::FromStr, sync::Arc, time::Duration};
 
+use futures::StreamExt;
+
+use nimiq_block::Block;
 use nimiq_blockchain::{BlockProducer, Blockchain, BlockchainConfig};
+use nimiq_blockchain_interface::AbstractBlockchain;
 use nimiq_blockchain_proxy::BlockchainProxy;
 use nimiq_bls::cache::PublicKeyCache;
-use nimiq_consensus::{sync::syncer_proxy::SyncerProxy, Consensus};
+use nimiq_consensus::{
+    consensus::consensus_proxy::ConfirmationLevel,
+    messages::{AddressSubscriptionOperation, RequestBlockByNumber, RequestSubscribeToAddress},
+    sync::syncer_proxy::SyncerProxy,
+    Consensus, TrieProofError,
+};
 use nimiq_database::mdbx::MdbxDatabase;
 use nimiq_keys::{Address, KeyPair, PrivateKey};
-use nimiq_network_interface::network::Network;
-use nimiq_network_mock::MockHub;
+use nimiq_light_blockchain::LightBlockchain;
+use nimiq_network_interface::{network::Network, request::Handle};
+use nimiq_network_mock::{MockHub, MockNetwork};
 use nimiq_primitives::{networks::NetworkId, policy::Policy};
 use nimiq_test_log::test;
 use nimiq_test_utils::{
     blockchain::{
-        fill_micro_blocks_with_txns, produce_macro_blocks, signing_key, voting_key, REWARD_KEY,
+        fill_micro_blocks_with_txns, produce_macro_blocks, push_micro_block, signing_key,
+        voting_key, REWARD_KEY,
     },
     node::TESTING_BLS_CACHE_MAX_CAPACITY,
 };
@@ -22,6 +33,7 @@ use nimiq_transaction::{
 use nimiq_utils::time::OffsetTime;
 use nimiq_zkp_component::ZKPComponent;
 use parking_lot::{Mutex, RwLock};
+use tokio::{sync::mpsc, time::timeout};
 
 #[test(tokio::test)]
 async fn test_request_transactions_by_address() {
@@ -123,4 +135,415 @@ async fn test_request_transactions_by_address() {
         // There should be one basic transaction in each micro block of the first batch
         Policy::blocks_per_batch() - 1
     );
+
+    // The inclusion of a transaction of a finalized epoch can be verified given only its hash.
+    let tx = txs
+        .iter()
+        .find(|tx| matches!(tx.data, HistoricTransactionData::Basic(_)))
+        .unwrap();
+    let (verified_tx, confirmation_level) = consensus_proxy
+        .verify_transaction_inclusion(tx.tx_hash().into(), 1)
+        .await
+        .unwrap();
+    assert_eq!(verified_tx.tx_hash(), tx.tx_hash());
+    assert_eq!(verified_tx.block_number, tx.block_number);
+    assert_eq!(confirmation_level, ConfirmationLevel::EpochFinalized);
+}
+
+#[test]
+fn test_confirmation_level_boundaries() {
+    let election_head = Policy::election_block_of(2).unwrap();
+    let macro_head = election_head + Policy::blocks_per_batch();
+
+    // Blocks up to and including the election head are finalized by their epoch.
+    assert_eq!(
+        ConfirmationLevel::at(election_head - 1, election_head, macro_head),
+        ConfirmationLevel::EpochFinalized
+    );
+    assert_eq!(
+        ConfirmationLevel::at(election_head, election_head, macro_head),
+        ConfirmationLevel::EpochFinalized
+    );
+
+    // Blocks after the election head up to and including the macro head are finalized by a
+    // checkpoint block.
+    assert_eq!(
+        ConfirmationLevel::at(election_head + 1, election_head, macro_head),
+        ConfirmationLevel::MacroFinalized
+    );
+    assert_eq!(
+        ConfirmationLevel::at(macro_head, election_head, macro_head),
+        ConfirmationLevel::MacroFinalized
+    );
+
+    // Blocks after the macro head are only included in micro blocks.
+    assert_eq!(
+        ConfirmationLevel::at(macro_head + 1, election_head, macro_head),
+        ConfirmationLevel::Micro
+    );
+
+    // Without a checkpoint block in the current epoch, the macro head is the election head.
+    assert_eq!(
+        ConfirmationLevel::at(election_head, election_head, election_head),
+        ConfirmationLevel::EpochFinalized
+    );
+    assert_eq!(
+        ConfirmationLevel::at(election_head + 1, election_head, election_head),
+        ConfirmationLevel::Micro
+    );
+}
+
+#[test(tokio::test)]
+async fn test_request_proven_accounts() {
+    let mut hub = MockHub::default();
+
+    let blockchain = Arc::new(RwLock::new(
+        Blockchain::new(
+            MdbxDatabase::new_volatile(Default::default()).unwrap(),
+            BlockchainConfig::default(),
+            NetworkId::UnitAlbatross,
+            Arc::new(OffsetTime::new()),
+        )
+        .unwrap(),
+    ));
+    let producer = BlockProducer::new(signing_key(), voting_key());
+    produce_macro_blocks(&producer, &blockchain, 1);
+    let blockchain_proxy = BlockchainProxy::from(&blockchain);
+
+    // Create three nodes on the same chain. The last one requests the accounts from the others.
+    let mut nodes = vec![];
+    let mut networks = vec![];
+    for _ in 0..3 {
+        let net = Arc::new(hub.new_network());
+        let zkp_prover = ZKPComponent::new(blockchain_proxy.clone(), Arc::clone(&net), None)
+            .await
+            .proxy();
+        let syncer = SyncerProxy::new_history(
+            blockchain_proxy.clone(),
+            Arc::clone(&net),
+            Arc::new(Mutex::new(PublicKeyCache::new(
+                TESTING_BLS_CACHE_MAX_CAPACITY,
+            ))),
+            net.subscribe_events(),
+        )
+        .await;
+        nodes.push(Consensus::from_network(
+            blockchain_proxy.clone(),
+            Arc::clone(&net),
+            syncer,
+            zkp_prover,
+        ));
+        networks.push(net);
+    }
+    networks[2].dial_mock(&networks[0]);
+    networks[2].dial_mock(&networks[1]);
+    let consensus_proxy = nodes[2].proxy();
+
+    let address = Address::from(&KeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap()).public);
+    let proven = consensus_proxy
+        .request_proven_accounts(vec![address.clone()], 1)
+        .await
+        .unwrap();
+
+    assert_eq!(proven.block_hash, blockchain.read().head_hash());
+    assert_eq!(proven.block_number, blockchain.read().block_number());
+    assert_eq!(
+        proven.values.get(&address).unwrap(),
+        &blockchain.read().get_account_if_complete(&address)
+    );
+
+    // Without enough peers, the request fails.
+    let result = consensus_proxy
+        .request_proven_accounts(vec![address], 3)
+        .await;
+    assert!(matches!(result, Err(TrieProofError::Request(_))));
+}
+
+#[test(tokio::test)]
+async fn test_address_subscriptions_are_migrated() {
+    let mut hub = MockHub::default();
+
+    let blockchain = Arc::new(RwLock::new(
+        Blockchain::new(
+            MdbxDatabase::new_volatile(Default::default()).unwrap(),
+            BlockchainConfig::default(),
+            NetworkId::UnitAlbatross,
+            Arc::new(OffsetTime::new()),
+        )
+        .unwrap(),
+    ));
+    let blockchain_proxy = BlockchainProxy::from(&blockchain);
+
+    let net = Arc::new(hub.new_network());
+    let zkp_prover = ZKPComponent::new(blockchain_proxy.clone(), Arc::clone(&net), None)
+        .await
+        .proxy();
+    let syncer = SyncerProxy::new_history(
+        blockchain_proxy.clone(),
+        Arc::clone(&net),
+        Arc::new(Mutex::new(PublicKeyCache::new(
+            TESTING_BLS_CACHE_MAX_CAPACITY,
+        ))),
+        net.subscribe_events(),
+    )
+    .await;
+    let consensus = Consensus::from_network(blockchain_proxy, Arc::clone(&net), syncer, zkp_prover);
+    let consensus_proxy = consensus.proxy();
+    tokio::spawn(consensus);
+
+    // Two peers that accept all subscriptions and report the subscribed addresses.
+    let (subscriptions_tx, mut subscriptions_rx) = mpsc::unbounded_channel();
+    let mut peers = vec![];
+    for _ in 0..2 {
+        let peer = Arc::new(hub.new_network());
+        let mut requests = peer.receive_requests::<RequestSubscribeToAddress>();
+        let subscriptions_tx = subscriptions_tx.clone();
+        let responder = Arc::clone(&peer);
+        tokio::spawn(async move {
+            while let Some((request, request_id, _)) = requests.next().await {
+                if let AddressSubscriptionOperation::Subscribe = request.operation {
+                    subscriptions_tx
+                        .send((responder.peer_id(), request.addresses))
+                        .unwrap();
+                }
+                let _ = responder
+                    .respond::<RequestSubscribeToAddress>(request_id, Ok(()))
+                    .await;
+            }
+        });
+        peers.push(peer);
+    }
+
+    let address = Address::from(&KeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap()).public);
+    net.dial_mock(&peers[0]);
+    consensus_proxy
+        .subscribe_to_addresses(vec![address.clone()], 1, Some(peers[0].peer_id()))
+        .await
+        .unwrap();
+    assert_eq!(
+        subscriptions_rx.recv().await,
+        Some((peers[0].peer_id(), vec![address.clone()]))
+    );
+
+    // The serving peer leaves while no other peer is connected, so the subscriptions are
+    // migrated to the next peer that joins.
+    peers[0].disconnect();
+    net.dial_mock(&peers[1]);
+    let migrated = timeout(Duration::from_secs(5), subscriptions_rx.recv())
+        .await
+        .expect("Address subscriptions were not migrated");
+    assert_eq!(migrated, Some((peers[1].peer_id(), vec![address])));
+}
+
+/// Creates a chain of three epochs followed by a few micro blocks, which stores all its blocks.
+fn full_blockchain() -> Arc<RwLock<Blockchain>> {
+    let blockchain = Arc::new(RwLock::new(
+        Blockchain::new(
+            MdbxDatabase::new_volatile(Default::default()).unwrap(),
+            BlockchainConfig {
+                max_epochs_stored: u32::MAX,
+                ..Default::default()
+            },
+            NetworkId::UnitAlbatross,
+            Arc::new(OffsetTime::new()),
+        )
+        .unwrap(),
+    ));
+    let producer = BlockProducer::new(signing_key(), voting_key());
+    produce_macro_blocks(
+        &producer,
+        &blockchain,
+        3 * Policy::batches_per_epoch() as usize,
+    );
+    for _ in 0..3 {
+        push_micro_block(&producer, &blockchain);
+    }
+    blockchain
+}
+
+/// Creates a light blockchain that only knows the macro blocks of the given chain and the
+/// headers of its current batch.
+fn light_blockchain(blockchain: &Arc<RwLock<Blockchain>>) -> Arc<RwLock<LightBlockchain>> {
+    let light_blockchain = Arc::new(RwLock::new(LightBlockchain::new(NetworkId::UnitAlbatross)));
+    let blockchain = blockchain.read();
+    let macro_head = blockchain.macro_head().block_number();
+
+    let macro_blocks = (Policy::genesis_block_number() + Policy::blocks_per_batch()..=macro_head)
+        .step_by(Policy::blocks_per_batch() as usize);
+    for block_number in macro_blocks {
+        let block = blockchain.get_block_at(block_number, false, None).unwrap();
+        LightBlockchain::push_macro(light_blockchain.upgradable_read(), block).unwrap();
+    }
+    for block_number in macro_head + 1..=blockchain.block_number() {
+        let block = blockchain.get_block_at(block_number, true, None).unwrap();
+        LightBlockchain::push(light_blockchain.upgradable_read(), block).unwrap();
+    }
+    light_blockchain
+}
+
+async fn consensus(blockchain: BlockchainProxy, net: &Arc<MockNetwork>) -> Consensus<MockNetwork> {
+    let zkp_prover = ZKPComponent::new(blockchain.clone(), Arc::clone(net), None)
+        .await
+        .proxy();
+    let bls_cache = Arc::new(Mutex::new(PublicKeyCache::new(
+        TESTING_BLS_CACHE_MAX_CAPACITY,
+    )));
+    let syncer = match blockchain {
+        BlockchainProxy::Full(_) => {
+            SyncerProxy::new_history(
+                blockchain.clone(),
+                Arc::clone(net),
+                bls_cache,
+                net.subscribe_events(),
+            )
+            .await
+        }
+        BlockchainProxy::Light(_) => {
+            SyncerProxy::new_light(
+                blockchain.clone(),
+                Arc::clone(net),
+                bls_cache,
+                zkp_prover.clone(),
+                net.subscribe_events(),
+            )
+            .await
+        }
+    };
+    Consensus::from_network(blockchain, Arc::clone(net), syncer, zkp_prover)
+}
+
+/// Creates a peer that answers block requests with the response for the block `offset` blocks
+/// after the requested one, replacing the extra data of the first block if `forge` is set.
+fn dishonest_peer(
+    hub: &mut MockHub,
+    blockchain: &Arc<RwLock<Blockchain>>,
+    offset: u32,
+    forge: bool,
+) -> Arc<MockNetwork> {
+    let net = Arc::new(hub.new_network());
+    let mut requests = net.receive_requests::<RequestBlockByNumber>();
+    let responder = Arc::clone(&net);
+    let blockchain = Arc::clone(blockchain);
+    tokio::spawn(async move {
+        while let Some((mut request, request_id, peer_id)) = requests.next().await {
+            request.block_number += offset;
+            let mut response = Handle::<MockNetwork, _>::handle(&request, peer_id, &blockchain);
+            if let Ok(ref mut response) = response {
+                if let Some(Block::Micro(block)) = response.blocks.first_mut() {
+                    if forge {
+                        block.header.extra_data = b"forged".to_vec();
+                    }
+                }
+            }
+            let _ = responder
+                .respond::<RequestBlockByNumber>(request_id, response)
+                .await;
+        }
+    });
+    net
+}
+
+#[test(tokio::test)]
+async fn test_request_block_at_proves_finalized_blocks() {
+    let mut hub = MockHub::default();
+    let blockchain = full_blockchain();
+    let net1 = Arc::new(hub.new_network());
+    let _consensus1 = consensus(BlockchainProxy::from(&blockchain), &net1).await;
+
+    let light_blockchain = light_blockchain(&blockchain);
+    let net2 = Arc::new(hub.new_network());
+    let consensus2 = consensus(BlockchainProxy::from(&light_blockchain), &net2).await;
+    net2.dial_mock(&net1);
+    let consensus_proxy = consensus2.proxy();
+
+    // A micro block of the second epoch is proven by the checkpoint block of its batch, whose
+    // election block is proven by an inclusion proof against our election head.
+    let block_number = Policy::election_block_of(1).unwrap() + 1;
+    assert!(light_blockchain
+        .read()
+        .get_block_at(block_number, false)
+        .is_err());
+    let block = consensus_proxy
+        .request_block_at(block_number, true, 1)
+        .await
+        .unwrap();
+    assert_eq!(
+        block,
+        blockchain
+            .read()
+            .get_block_at(block_number, true, None)
+            .unwrap()
+    );
+
+    // The election block of a micro block of the last epoch is the parent of our election head.
+    let block_number = Policy::election_block_of(2).unwrap() + 1;
+    let block = consensus_proxy
+        .request_block_at(block_number, false, 1)
+        .await
+        .unwrap();
+    assert_eq!(
+        block.hash(),
+        blockchain
+            .read()
+            .get_block_at(block_number, false, None)
+            .unwrap()
+            .hash()
+    );
+}
+
+#[test(tokio::test)]
+async fn test_request_block_at_proves_blocks_of_current_batch() {
+    let mut hub = MockHub::default();
+    let blockchain = full_blockchain();
+    let net1 = Arc::new(hub.new_network());
+    let _consensus1 = consensus(BlockchainProxy::from(&blockchain), &net1).await;
+
+    let light_blockchain = light_blockchain(&blockchain);
+    let net2 = Arc::new(hub.new_network());
+    let consensus2 = consensus(BlockchainProxy::from(&light_blockchain), &net2).await;
+    net2.dial_mock(&net1);
+    let consensus_proxy = consensus2.proxy();
+
+    // The light client doesn't store the bodies, so the block is requested and proven by the hash
+    // chain up to our head.
+    let block_number = blockchain.read().block_number() - 1;
+    let block = consensus_proxy
+        .request_block_at(block_number, true, 1)
+        .await
+        .unwrap();
+    assert!(block.body().is_some());
+    assert_eq!(
+        block,
+        blockchain
+            .read()
+            .get_block_at(block_number, true, None)
+            .unwrap()
+    );
+}
+
+#[test(tokio::test)]
+async fn test_request_block_at_rejects_forged_and_mismatched_blocks() {
+    let mut hub = MockHub::default();
+    let blockchain = full_blockchain();
+    let light_blockchain = light_blockchain(&blockchain);
+    let finalized_block_number = Policy::election_block_of(1).unwrap() + 1;
+    let current_block_number = blockchain.read().block_number() - 1;
+
+    for (offset, forge) in [(0, true), (1, false)] {
+        let net1 = dishonest_peer(&mut hub, &blockchain, offset, forge);
+        let net2 = Arc::new(hub.new_network());
+        let consensus2 = consensus(BlockchainProxy::from(&light_blockchain), &net2).await;
+        net2.dial_mock(&net1);
+        let consensus_proxy = consensus2.proxy();
+
+        for block_number in [finalized_block_number, current_block_number] {
+            assert!(
+                consensus_proxy
+                    .request_block_at(block_number, true, 1)
+                    .await
+                    .is_err(),
+                "Accepted block {block_number} (offset {offset}, forged {forge})"
+            );
+        }
+    }
 }
@@ -16,18 +16,18 @@ use nimiq_consensus::{
     },
 };
 use nimiq_database::mdbx::MdbxDatabase;
-use nimiq_genesis::NetworkId;
+use nimiq_genesis::{NetworkId, NetworkInfo};
 use nimiq_network_interface::network::Network as NetworkInterface;
 use nimiq_network_libp2p::Network;
-use nimiq_network_mock::MockHub;
+use nimiq_network_mock::{LinkConditions, MockHub, MockNetwork};
 use nimiq_primitives::policy::Policy;
 use nimiq_test_log::test;
 use nimiq_test_utils::{
     blockchain::{produce_macro_blocks, signing_key, voting_key},
-    node::TESTING_BLS_CACHE_MAX_CAPACITY,
+    node::{Node, TESTING_BLS_CACHE_MAX_CAPACITY},
     test_network::TestNetwork,
 };
-use nimiq_time::sleep;
+use nimiq_time::{sleep, timeout};
 use nimiq_utils::time::OffsetTime;
 use nimiq_zkp_component::ZKPComponent;
 use parking_lot::{Mutex, RwLock};
@@ -167,6 +167,60 @@ async fn three_peers_can_sync() {
     //    );
 }
 
+#[test(tokio::test)]
+async fn peers_can_sync_over_slow_link() {
+    let mut hub = Some(MockHub::with_seed(3));
+    let network_info = NetworkInfo::from_network_id(NetworkId::UnitAlbatross);
+
+    let mut node1 = Node::<MockNetwork>::new_history(
+        1,
+        network_info.genesis_block(),
+        network_info.genesis_accounts(),
+        &mut hub,
+        false,
+    )
+    .await;
+    let mut node2 = Node::<MockNetwork>::new_history(
+        2,
+        network_info.genesis_block(),
+        network_info.genesis_accounts(),
+        &mut hub,
+        false,
+    )
+    .await;
+
+    let producer = BlockProducer::new(signing_key(), voting_key());
+    produce_macro_blocks(
+        &producer,
+        &node1.blockchain,
+        (Policy::batches_per_epoch() + 1) as usize,
+    );
+
+    // Both directions have a high latency with jitter and a limited bandwidth.
+    hub.as_ref()
+        .unwrap()
+        .set_default_link_conditions(LinkConditions {
+            latency: Duration::from_millis(100),
+            jitter: Duration::from_millis(100),
+            drop_rate: 0.0,
+            bandwidth: Some(100_000),
+        });
+
+    node1.consume();
+    node2.consume();
+    node2.network.dial_mock(&node1.network);
+
+    let head_hash = node1.blockchain.read().head_hash();
+    let blockchain2 = Arc::clone(&node2.blockchain);
+    timeout(Duration::from_secs(60), async move {
+        while blockchain2.read().head_hash() != head_hash {
+            sleep(Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("Node didn't sync over the slow link");
+}
+
 #[test(tokio::test)]
 async fn sync_ingredients() {
     let hub = MockHub::default();
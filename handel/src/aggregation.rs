@@ -1,18 +1,22 @@
 use std::{
     mem,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
+    time::Duration,
 };
 
 use futures::{
     future::{BoxFuture, Future, FutureExt},
     stream::{BoxStream, Stream, StreamExt},
 };
+use instant::Instant;
 use nimiq_time::{interval, Interval};
 
 use crate::{
     config::Config,
     contribution::AggregatableContribution,
+    evaluator::Evaluator,
     identity::IdentityRegistry,
     level::Level,
     network::{LevelUpdateSender, Network},
@@ -20,6 +24,7 @@ use crate::{
     pending_contributions::{PendingContribution, PendingContributionList},
     protocol::Protocol,
     store::ContributionStore,
+    telemetry::Telemetry,
     update::LevelUpdate,
     verifier::{VerificationResult, Verifier},
     Identifier,
@@ -42,6 +47,15 @@ where
     /// Handel configuration
     config: Config,
 
+    /// Statistics shared with the other aggregations of this kind
+    telemetry: Arc<Telemetry>,
+
+    /// Time at which the aggregation was started
+    started: Instant,
+
+    /// Whether the best aggregate reached the threshold of the evaluator
+    threshold_reached: bool,
+
     /// Levels
     levels: Vec<Level>,
 
@@ -60,6 +74,9 @@ where
     /// Interval for starting the next level regardless of previous levels completion
     start_level_interval: Interval,
 
+    /// Period of `start_level_interval`
+    level_timeout: Duration,
+
     /// Interval for sending level updates to the corresponding peers regardless of progression
     periodic_update_interval: Interval,
 
@@ -81,6 +98,7 @@ where
     pub fn new(
         protocol: P,
         config: Config,
+        telemetry: Arc<Telemetry>,
         own_contribution: P::Contribution,
         input_stream: LevelUpdateStream<P, TId>,
         sender: LevelUpdateSender<N>,
@@ -96,8 +114,10 @@ where
         pending_contributions.add_contribution(own_contribution.clone(), 0);
 
         // Regardless of level completion consecutive levels need to be activated at some point. Activate Levels every time this interval ticks,
-        // if the level has not already been activated due to level completion
-        let start_level_interval = interval(config.timeout);
+        // if the level has not already been activated due to level completion.
+        // The timeout is possibly adapted to the level completion times of previous aggregations.
+        let timeout = telemetry.start_aggregation(config.timeout, config.adaptive_timeout.as_ref());
+        let start_level_interval = interval(timeout);
 
         // Every `config.update_interval` send Level updates to corresponding peers no matter the aggregations progression
         // (makes sure other peers can catch up).
@@ -107,11 +127,15 @@ where
         Self {
             protocol,
             config,
+            telemetry,
+            started: Instant::now(),
+            threshold_reached: false,
             pending_contributions,
             levels,
             contribution: own_contribution,
             sender,
             start_level_interval,
+            level_timeout: timeout,
             periodic_update_interval,
             next_level_timeout: 0,
             current_verification: None,
@@ -164,19 +188,20 @@ where
 
     /// Check if a level was completed
     fn check_completed_level(&mut self, level_id: usize, store: &<P as Protocol<TId>>::Store) {
-        let num_peers = {
+        let (num_peers, started_at) = {
             let level = self
                 .levels
                 .get(level_id)
                 .expect("Attempted to check completeness of invalid level");
 
             // check if level already is completed
-            if level.state.read().receive_completed {
+            let state = level.state.read();
+            if state.receive_completed {
                 // The level was completed before so nothing more to do.
                 return;
             }
 
-            level.num_peers()
+            (level.num_peers(), state.started_at)
         };
 
         if num_peers == 0 {
@@ -207,6 +232,13 @@ where
                     .write()
                     .receive_completed = true;
             }
+            // The completion time is measured from the activation of the level, such that it
+            // doesn't depend on the timeout. Levels completed before their activation are skipped
+            // as they don't tell anything about the time a level takes.
+            if let Some(started_at) = started_at {
+                self.telemetry
+                    .level_completed(level_id, started_at.elapsed());
+            }
             // if there is a level with a higher id than the completed one it needs to be activated.
             if level_id + 1 < self.levels.len() {
                 // activate next level
@@ -316,6 +348,19 @@ where
             // next time the timeout triggers the next level needs activating
             self.next_level_timeout += 1;
 
+            // The previous level timed out if it was active but didn't complete. It took at least
+            // the timeout, so it is recorded as a sample at the timeout value.
+            if let Some(previous) = level.checked_sub(1).and_then(|id| self.levels.get(id)) {
+                let timed_out = {
+                    let state = previous.state.read();
+                    state.send_started && !state.receive_completed
+                };
+                if timed_out && !previous.is_empty() {
+                    self.telemetry
+                        .level_timed_out(previous.id, self.level_timeout);
+                }
+            }
+
             let store_rw = self.protocol.store();
             let store = store_rw.read();
 
@@ -324,6 +369,14 @@ where
         }
     }
 
+    /// Records the time to threshold once the best aggregate reaches the threshold.
+    fn check_threshold(&mut self, aggregate: &P::Contribution) {
+        if !self.threshold_reached && self.protocol.evaluator().is_final(aggregate) {
+            self.threshold_reached = true;
+            self.telemetry.threshold_reached(self.started.elapsed());
+        }
+    }
+
     fn into_inner(self) -> (LevelUpdateStream<P, TId>, LevelUpdateSender<N>) {
         (self.pending_contributions.into_stream(), self.sender)
     }
@@ -483,6 +536,7 @@ where
 
         // Return the aggregate if available.
         if let Some(contribution) = best_aggregate {
+            self.check_threshold(&contribution);
             return Poll::Ready(Some(contribution));
         }
 
@@ -589,6 +643,7 @@ where
     pub fn new(
         protocol: P,
         config: Config,
+        telemetry: Arc<Telemetry>,
         own_contribution: P::Contribution,
         input_stream: LevelUpdateStream<P, TId>,
         network: N,
@@ -600,6 +655,7 @@ where
         Self::Ongoing(OngoingAggregation::new(
             protocol,
             config,
+            telemetry,
             own_contribution,
             input_stream,
             sender,
@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 /// Handel configuration settings
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Config {
     /// Number of peers contacted during an update at each level
     pub update_count: usize,
@@ -14,6 +14,10 @@ pub struct Config {
 
     /// How many peers are contacted at each level
     pub peer_count: usize,
+
+    /// Adapt the level timeout to the level completion times observed in previous aggregations.
+    /// `timeout` is used as long as no level completions were observed.
+    pub adaptive_timeout: Option<AdaptiveTimeout>,
 }
 
 impl Default for Config {
@@ -23,6 +27,26 @@ impl Default for Config {
             update_interval: Duration::from_millis(500),
             timeout: Duration::from_millis(400),
             peer_count: 2,
+            adaptive_timeout: None,
+        }
+    }
+}
+
+/// Bounds for the adaptive level timeout
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AdaptiveTimeout {
+    /// The lowest timeout used
+    pub min_timeout: Duration,
+
+    /// The highest timeout used
+    pub max_timeout: Duration,
+}
+
+impl Default for AdaptiveTimeout {
+    fn default() -> Self {
+        AdaptiveTimeout {
+            min_timeout: Duration::from_millis(100),
+            max_timeout: Duration::from_millis(2000),
         }
     }
 }
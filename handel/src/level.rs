@@ -1,5 +1,6 @@
 use std::{cmp::min, sync::Arc};
 
+use instant::Instant;
 use parking_lot::RwLock;
 use rand::{seq::SliceRandom, thread_rng};
 
@@ -13,6 +14,8 @@ use crate::{
 pub struct LevelState {
     /// Send is already started
     pub send_started: bool,
+    /// Time at which send was started
+    pub started_at: Option<Instant>,
     /// Receive is already completed
     pub receive_completed: bool,
     /// The position of the peer where the next send must go to
@@ -23,6 +26,18 @@ pub struct LevelState {
     pub send_peers_count: usize,
 }
 
+impl LevelState {
+    /// Starts send if not already started. Returns whether it was started by this call.
+    fn start(&mut self) -> bool {
+        if self.send_started {
+            return false;
+        }
+        self.send_started = true;
+        self.started_at = Some(Instant::now());
+        true
+    }
+}
+
 /// Struct that defines an Aggregation Level
 #[derive(Debug)]
 pub struct Level {
@@ -47,6 +62,7 @@ impl Level {
             send_expected_full_size,
             state: RwLock::new(LevelState {
                 send_started: false,
+                started_at: None,
                 receive_completed: false,
                 send_peers_pos: 0,
                 send_signature_size: 0,
@@ -92,7 +108,7 @@ impl Level {
 
                     if !first_active {
                         first_active = true;
-                        level.state.write().send_started = true;
+                        level.state.write().start();
                     }
 
                     levels.push(level);
@@ -155,7 +171,7 @@ impl Level {
         state.send_peers_count = 0;
 
         if state.send_signature_size == self.send_expected_full_size {
-            state.send_started = true;
+            state.start();
             return true;
         }
 
@@ -166,13 +182,7 @@ impl Level {
     ///
     /// If the level was started before returns false, otherwise returns true.
     pub fn start(&self) -> bool {
-        let mut state = self.state.write();
-        if state.send_started {
-            false
-        } else {
-            state.send_started = true;
-            true
-        }
+        self.state.write().start()
     }
 }
 
@@ -214,8 +224,14 @@ mod test {
         assert_eq!(level.num_peers(), 0);
 
         // Start the level
-        level.start();
+        assert!(level.start());
         assert!(level.state.read().send_started);
+        let started_at = level.state.read().started_at;
+        assert!(started_at.is_some());
+
+        // Starting it again keeps the activation time
+        assert!(!level.start());
+        assert_eq!(level.state.read().started_at, started_at);
 
         // An empty level can't be active since there is no peer to send updates to
         assert!(!level.active());
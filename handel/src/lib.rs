@@ -18,6 +18,7 @@ pub mod partitioner;
 pub(crate) mod pending_contributions;
 pub mod protocol;
 pub mod store;
+pub mod telemetry;
 pub mod update;
 pub mod verifier;
 
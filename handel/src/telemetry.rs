@@ -0,0 +1,253 @@
+use std::time::Duration;
+
+use parking_lot::Mutex;
+
+use crate::config::AdaptiveTimeout;
+
+/// Statistics about the aggregations sharing a [`Telemetry`].
+#[derive(Clone, Debug, Default)]
+pub struct TelemetryStats {
+    /// Number of aggregations started
+    pub aggregations: u64,
+
+    /// Number of aggregations which reached their threshold
+    pub thresholds_reached: u64,
+
+    /// Moving average of the time from the start of an aggregation until it reached its threshold
+    pub time_to_threshold: Option<Duration>,
+
+    /// Statistics per level
+    pub levels: Vec<LevelStats>,
+
+    /// Level timeout used by the latest aggregation
+    pub timeout: Option<Duration>,
+}
+
+/// Statistics about a level of the aggregations sharing a [`Telemetry`].
+#[derive(Clone, Debug, Default)]
+pub struct LevelStats {
+    /// Number of aggregations in which the level was completed
+    pub completions: u64,
+
+    /// Number of aggregations in which the level timed out before it was completed
+    pub timeouts: u64,
+
+    /// Moving average of the time from the activation of the level until it was completed.
+    /// Timed out levels contribute the timeout as a lower bound of their completion time.
+    pub completion_time: Option<Duration>,
+}
+
+/// Collects statistics about aggregations of the same kind, e.g. all skip block aggregations.
+///
+/// The observed level completion times are also used to adapt the level timeout of subsequent
+/// aggregations if [`crate::config::Config::adaptive_timeout`] is set.
+#[derive(Debug, Default)]
+pub struct Telemetry {
+    stats: Mutex<TelemetryStats>,
+}
+
+impl Telemetry {
+    /// The latest observation is weighted with `1 / MOVING_AVERAGE_WEIGHT` in the moving averages.
+    const MOVING_AVERAGE_WEIGHT: u32 = 8;
+
+    /// The adaptive timeout is this percentage of the observed time per level, such that a level
+    /// usually completes before the timeout activates the next one.
+    const ADAPTIVE_TIMEOUT_PERCENT: u32 = 150;
+
+    /// Returns a snapshot of the statistics.
+    pub fn stats(&self) -> TelemetryStats {
+        self.stats.lock().clone()
+    }
+
+    fn update_average(average: &mut Option<Duration>, sample: Duration) {
+        *average = Some(match *average {
+            Some(average) => {
+                (average * (Self::MOVING_AVERAGE_WEIGHT - 1) + sample) / Self::MOVING_AVERAGE_WEIGHT
+            }
+            None => sample,
+        });
+    }
+
+    /// Records the start of an aggregation and returns its level timeout.
+    pub(crate) fn start_aggregation(
+        &self,
+        timeout: Duration,
+        adaptive_timeout: Option<&AdaptiveTimeout>,
+    ) -> Duration {
+        let mut stats = self.stats.lock();
+        stats.aggregations += 1;
+
+        let timeout = adaptive_timeout
+            .and_then(|adaptive_timeout| Self::adapted_timeout(&stats, adaptive_timeout))
+            .unwrap_or(timeout);
+        stats.timeout = Some(timeout);
+        timeout
+    }
+
+    /// The slowest level determines the timeout. The completion times are measured from the
+    /// activation of each level, so they don't grow with the timeout itself. Returns `None` if no
+    /// level completion was observed yet.
+    fn adapted_timeout(
+        stats: &TelemetryStats,
+        adaptive_timeout: &AdaptiveTimeout,
+    ) -> Option<Duration> {
+        let time_per_level = stats
+            .levels
+            .iter()
+            // Level 0 only contains our own contribution.
+            .skip(1)
+            .filter_map(|level_stats| level_stats.completion_time)
+            .max()?;
+
+        Some(
+            (time_per_level * Self::ADAPTIVE_TIMEOUT_PERCENT / 100)
+                .max(adaptive_timeout.min_timeout)
+                .min(adaptive_timeout.max_timeout),
+        )
+    }
+
+    fn level_stats(stats: &mut TelemetryStats, level: usize) -> &mut LevelStats {
+        if stats.levels.len() <= level {
+            stats.levels.resize_with(level + 1, Default::default);
+        }
+        &mut stats.levels[level]
+    }
+
+    /// Records that `level` was completed `elapsed` after its activation.
+    pub(crate) fn level_completed(&self, level: usize, elapsed: Duration) {
+        let mut stats = self.stats.lock();
+        let level_stats = Self::level_stats(&mut stats, level);
+        level_stats.completions += 1;
+        Self::update_average(&mut level_stats.completion_time, elapsed);
+    }
+
+    /// Records that `level` didn't complete before `timeout` activated the next level.
+    ///
+    /// The actual completion time is unknown but at least `timeout`, so the timeout is recorded
+    /// as a censored sample. Otherwise the adaptive timeout would only see the fast levels and
+    /// never grow for levels which regularly time out.
+    pub(crate) fn level_timed_out(&self, level: usize, timeout: Duration) {
+        let mut stats = self.stats.lock();
+        let level_stats = Self::level_stats(&mut stats, level);
+        level_stats.timeouts += 1;
+        Self::update_average(&mut level_stats.completion_time, timeout);
+    }
+
+    /// Records that an aggregation reached its threshold `elapsed` after its start.
+    pub(crate) fn threshold_reached(&self, elapsed: Duration) {
+        let mut stats = self.stats.lock();
+        stats.thresholds_reached += 1;
+        Self::update_average(&mut stats.time_to_threshold, elapsed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimiq_test_log::test;
+
+    use super::*;
+
+    #[test]
+    fn it_adapts_the_timeout_to_level_completions() {
+        let telemetry = Telemetry::default();
+        let adaptive_timeout = AdaptiveTimeout {
+            min_timeout: Duration::from_millis(100),
+            max_timeout: Duration::from_millis(1000),
+        };
+        let timeout = Duration::from_millis(400);
+
+        // Without observations the configured timeout is used.
+        assert_eq!(
+            telemetry.start_aggregation(timeout, Some(&adaptive_timeout)),
+            timeout
+        );
+
+        // Level 2 takes 200ms, level 1 only 100ms.
+        telemetry.level_completed(1, Duration::from_millis(100));
+        telemetry.level_completed(2, Duration::from_millis(200));
+        assert_eq!(
+            telemetry.start_aggregation(timeout, Some(&adaptive_timeout)),
+            Duration::from_millis(300)
+        );
+        // The adaptive timeout is only used if enabled.
+        assert_eq!(telemetry.start_aggregation(timeout, None), timeout);
+
+        // Slow levels are capped by the maximum timeout.
+        telemetry.level_completed(3, Duration::from_secs(30));
+        assert_eq!(
+            telemetry.start_aggregation(timeout, Some(&adaptive_timeout)),
+            adaptive_timeout.max_timeout
+        );
+
+        let stats = telemetry.stats();
+        assert_eq!(stats.aggregations, 4);
+        assert_eq!(stats.levels.len(), 4);
+        assert_eq!(stats.levels[0].completions, 0);
+        assert_eq!(stats.levels[1].completions, 1);
+        assert_eq!(stats.timeout, Some(adaptive_timeout.max_timeout));
+    }
+
+    #[test]
+    fn it_keeps_the_timeout_stable_for_stable_levels() {
+        let telemetry = Telemetry::default();
+        let adaptive_timeout = AdaptiveTimeout {
+            min_timeout: Duration::from_millis(100),
+            max_timeout: Duration::from_millis(1000),
+        };
+
+        // Every level completes 200ms after its activation, regardless of the timeout.
+        let mut timeout = Duration::from_millis(500);
+        for _ in 0..50 {
+            timeout = telemetry.start_aggregation(timeout, Some(&adaptive_timeout));
+            for level in 1..5 {
+                telemetry.level_completed(level, Duration::from_millis(200));
+            }
+        }
+        assert_eq!(timeout, Duration::from_millis(300));
+    }
+
+    #[test]
+    fn it_grows_the_timeout_for_timed_out_levels() {
+        let telemetry = Telemetry::default();
+        let adaptive_timeout = AdaptiveTimeout {
+            min_timeout: Duration::from_millis(100),
+            max_timeout: Duration::from_millis(1000),
+        };
+
+        // Level 1 completes quickly, level 2 never completes before the timeout.
+        let mut timeout = Duration::from_millis(200);
+        let mut timeouts = vec![];
+        for _ in 0..50 {
+            timeout = telemetry.start_aggregation(timeout, Some(&adaptive_timeout));
+            timeouts.push(timeout);
+            telemetry.level_completed(1, Duration::from_millis(50));
+            telemetry.level_timed_out(2, timeout);
+        }
+
+        // The timeout grows with every timed out aggregation until it reaches the maximum.
+        assert_eq!(timeouts[0], Duration::from_millis(200));
+        assert_eq!(timeouts[1], Duration::from_millis(300));
+        assert!(timeouts.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(timeout, adaptive_timeout.max_timeout);
+
+        let stats = telemetry.stats();
+        assert_eq!(stats.levels[1].timeouts, 0);
+        assert_eq!(stats.levels[2].completions, 0);
+        assert_eq!(stats.levels[2].timeouts, 50);
+    }
+
+    #[test]
+    fn it_averages_the_time_to_threshold() {
+        let telemetry = Telemetry::default();
+        telemetry.threshold_reached(Duration::from_millis(800));
+        assert_eq!(
+            telemetry.stats().time_to_threshold,
+            Some(Duration::from_millis(800))
+        );
+
+        telemetry.threshold_reached(Duration::from_millis(0));
+        let stats = telemetry.stats();
+        assert_eq!(stats.thresholds_reached, 2);
+        assert_eq!(stats.time_to_threshold, Some(Duration::from_millis(700)));
+    }
+}
@@ -18,6 +18,7 @@ use nimiq_handel::{
     partitioner::BinomialPartitioner,
     protocol,
     store::ReplaceStore,
+    telemetry::Telemetry,
     update::LevelUpdate,
     verifier::{VerificationResult, Verifier},
 };
@@ -211,6 +212,7 @@ async fn it_can_aggregate() {
         update_interval: Duration::from_millis(500),
         timeout: Duration::from_millis(500),
         peer_count: 1,
+        adaptive_timeout: None,
     };
 
     let stopped = Arc::new(RwLock::new(false));
@@ -251,6 +253,7 @@ async fn it_can_aggregate() {
         let mut aggregation = Aggregation::new(
             protocol,
             config.clone(),
+            Default::default(),
             contribution,
             Box::pin(
                 net.receive_messages::<Update<Contribution>>()
@@ -289,9 +292,11 @@ async fn it_can_aggregate() {
     }
 
     // instead of spawning the aggregation task await its result here.
+    let telemetry = Arc::new(Telemetry::default());
     let mut aggregation = Aggregation::new(
         protocol,
         config.clone(),
+        Arc::clone(&telemetry),
         contribution,
         Box::pin(
             net.receive_messages::<Update<Contribution>>()
@@ -337,6 +342,13 @@ async fn it_can_aggregate() {
     drop(aggregation);
     net.disconnect();
 
+    // The full aggregate reached the threshold.
+    let stats = telemetry.stats();
+    assert_eq!(stats.aggregations, 1);
+    assert_eq!(stats.thresholds_reached, 1);
+    assert!(stats.time_to_threshold.is_some());
+    assert_eq!(stats.timeout, Some(config.timeout));
+
     // give the other aggregations time to complete themselves
     let mut finished_count = 0usize;
     loop {
@@ -366,6 +378,7 @@ async fn it_can_aggregate() {
     let mut aggregation = Aggregation::new(
         protocol,
         config.clone(),
+        Default::default(),
         contribution,
         Box::pin(
             net.receive_messages::<Update<Contribution>>()
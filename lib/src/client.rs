@@ -3,7 +3,7 @@ use std::{fs, num::NonZeroU8, sync::Arc};
 use nimiq_block::Block;
 #[cfg(feature = "full-consensus")]
 use nimiq_blockchain::{Blockchain, BlockchainConfig};
-use nimiq_blockchain_interface::AbstractBlockchain;
+use nimiq_blockchain_interface::{AbstractBlockchain, HistoryRetention};
 use nimiq_blockchain_proxy::BlockchainProxy;
 use nimiq_bls::cache::PublicKeyCache;
 #[cfg(feature = "full-consensus")]
@@ -26,13 +26,21 @@ use nimiq_network_interface::{
     Multiaddr, Protocol,
 };
 use nimiq_network_libp2p::{
-    discovery::peer_contacts::PeerContact, Config as NetworkConfig, Network,
+    discovery::peer_contacts::PeerContact, Config as NetworkConfig, Network, PeerAllowlist,
     TlsConfig as NetworkTls,
 };
 use nimiq_primitives::policy::Policy;
 #[cfg(feature = "full-consensus")]
 use nimiq_utils::time::OffsetTime;
 #[cfg(feature = "validator")]
+use nimiq_validator::equivocation::EquivocationMonitor;
+#[cfg(feature = "validator")]
+use nimiq_validator::failover::{FailoverConfig, FileLeaseStore};
+#[cfg(feature = "validator")]
+use nimiq_validator::signer::{LocalSigner, RemoteSigner, ValidatorSigner};
+#[cfg(feature = "validator")]
+use nimiq_validator::uptime::UptimeIndex;
+#[cfg(feature = "validator")]
 use nimiq_validator::validator::Validator as AbstractValidator;
 #[cfg(feature = "validator")]
 use nimiq_validator::validator::ValidatorProxy as AbstractValidatorProxy;
@@ -102,6 +110,14 @@ pub(crate) struct ClientInner {
     #[cfg(feature = "validator")]
     validator: Option<ValidatorProxy>,
 
+    /// The index of the duty statistics of all validators, if enabled.
+    #[cfg(feature = "validator")]
+    uptime_index: Option<UptimeIndex>,
+
+    /// The monitor of the equivocations of all validators, if enabled.
+    #[cfg(feature = "validator")]
+    equivocation_monitor: Option<EquivocationMonitor>,
+
     /// Wallet that stores key pairs for transaction signing
     #[cfg(feature = "wallet")]
     wallet_store: Arc<WalletStore>,
@@ -110,12 +126,26 @@ pub(crate) struct ClientInner {
 }
 
 /// This function is used to generate the services flags (provided, needed) based upon the configured sync mode
-pub fn generate_service_flags(sync_mode: SyncMode, index_history: bool) -> (Services, Services) {
+/// History nodes that don't keep the full history advertise [`Services::PARTIAL_HISTORY`] instead of
+/// [`Services::HISTORY`], such that peers only request the history that they still store.
+pub fn generate_service_flags(
+    sync_mode: SyncMode,
+    index_history: bool,
+    history_retention: &HistoryRetention,
+) -> (Services, Services) {
     let provided_services = match sync_mode {
         // Services provided by history nodes
         SyncMode::History => {
-            log::info!("Client configured as a history node");
-            let mut services = Services::provided(NodeType::History);
+            let mut services = if history_retention.is_full() {
+                log::info!("Client configured as a history node");
+                Services::provided(NodeType::History)
+            } else {
+                log::info!(
+                    ?history_retention,
+                    "Client configured as a history node with partial history"
+                );
+                Services::provided(NodeType::Full) | Services::PARTIAL_HISTORY
+            };
             if index_history {
                 services |= Services::TRANSACTION_INDEX;
             }
@@ -225,8 +255,11 @@ impl ClientInner {
             identity_keypair.public().to_peer_id().to_base58()
         );
 
-        let (mut provided_services, required_services) =
-            generate_service_flags(config.consensus.sync_mode, config.consensus.index_history);
+        let (mut provided_services, required_services) = generate_service_flags(
+            config.consensus.sync_mode,
+            config.consensus.index_history,
+            &config.consensus.history_retention,
+        );
 
         // We update the services flags depending on our validator configuration
         #[cfg(feature = "validator")]
@@ -305,7 +338,7 @@ impl ClientInner {
         };
 
         // Setup libp2p network
-        let network_config = NetworkConfig::new(
+        let mut network_config = NetworkConfig::new(
             identity_keypair,
             peer_contact,
             seeds,
@@ -322,6 +355,19 @@ impl ClientInner {
                 .dht_quorum
                 .unwrap_or(NonZeroU8::new(3).unwrap()),
         );
+        if let Some(allowlist_file) = &config.network.peer_allowlist_file {
+            let allowlist = PeerAllowlist::from_file(allowlist_file).map_err(|e| {
+                Error::config_error(format!(
+                    "Failed to load peer allowlist from {}: {}",
+                    allowlist_file.display(),
+                    e
+                ))
+            })?;
+            network_config.allowlist = Some(allowlist);
+        }
+        network_config.disable_discovery = config.network.disable_discovery;
+        network_config.peer_bandwidth_limit = config.network.peer_bandwidth_limit;
+        network_config.relay_server = config.network.relay_server;
 
         log::debug!(
             addresses = ?config.network.listen_addresses,
@@ -360,7 +406,7 @@ impl ClientInner {
         #[cfg(not(feature = "database-storage"))]
         let zkp_storage = None;
 
-        let (blockchain_proxy, syncer_proxy, zkp_component) = match config.consensus.sync_mode {
+        let (blockchain_proxy, mut syncer_proxy, zkp_component) = match config.consensus.sync_mode {
             #[cfg(not(feature = "full-consensus"))]
             SyncMode::History => {
                 panic!("Can't build a history node without the full-consensus feature enabled")
@@ -373,6 +419,7 @@ impl ClientInner {
             SyncMode::History => {
                 blockchain_config.keep_history = true;
                 blockchain_config.index_history = config.consensus.index_history;
+                blockchain_config.history_retention = config.consensus.history_retention.clone();
                 let blockchain = match Blockchain::new(
                     environment.clone(),
                     blockchain_config,
@@ -481,6 +528,16 @@ impl ClientInner {
             }
         };
 
+        // Refuse to start on a chain that doesn't contain the trusted checkpoint.
+        if let Some(ref checkpoint) = config.consensus.trusted_checkpoint {
+            checkpoint
+                .verify_chain(&blockchain_proxy)
+                .map_err(|error| Error::Consensus(error.into()))?;
+            log::info!(%checkpoint, "Using trusted checkpoint");
+            syncer_proxy
+                .set_trusted_checkpoint(checkpoint.clone(), config.consensus.sync_from_checkpoint);
+        }
+
         // Open wallet
         #[cfg(feature = "wallet")]
         let wallet_store = Arc::new(WalletStore::new(environment.clone()));
@@ -494,6 +551,20 @@ impl ClientInner {
             zkp_component.proxy(),
         );
 
+        #[cfg(feature = "validator")]
+        let equivocation_monitor = match blockchain_proxy {
+            BlockchainProxy::Full(ref blockchain) if config.consensus.monitor_equivocations => {
+                log::info!("Equivocation monitoring enabled");
+                // Validators pass the Tendermint proposals they receive to the monitor themselves.
+                Some(EquivocationMonitor::new(
+                    environment.clone(),
+                    Arc::clone(blockchain),
+                    config.validator.is_none(),
+                ))
+            }
+            _ => None,
+        };
+
         #[cfg(feature = "validator")]
         let mut validator_or_mempool = None;
 
@@ -507,14 +578,59 @@ impl ClientInner {
                     // Load validator address
                     let automatic_reactivate = validator_config.automatic_reactivate;
 
-                    // Load signing key (before we give away ownership of the storage config)
-                    let signing_key = config.storage.signing_keypair()?;
-
-                    // Load validator key (before we give away ownership of the storage config)
-                    let voting_key = config.storage.voting_keypair()?;
-
-                    // Load fee key (before we give away ownership of the storage config)
-                    let fee_key = config.storage.fee_keypair()?;
+                    // Connect to the remote signer or load the keys (before we give away
+                    // ownership of the storage config)
+                    let signer: Arc<dyn ValidatorSigner> = match validator_config.remote_signer {
+                        Some(remote_signer) => {
+                            let address = remote_signer.address.parse().map_err(|e| {
+                                Error::config_error(format!("Invalid signer address: {}", e))
+                            })?;
+                            let signer = RemoteSigner::connect(
+                                address,
+                                remote_signer.auth_key.0,
+                                RemoteSigner::DEFAULT_TIMEOUT,
+                            )
+                            .await
+                            .map_err(|e| {
+                                Error::config_error(format!(
+                                    "Failed to connect to remote signer: {}",
+                                    e
+                                ))
+                            })?;
+                            log::info!(
+                                address = %remote_signer.address,
+                                "Using remote signer"
+                            );
+                            Arc::new(signer)
+                        }
+                        None => {
+                            let signer = LocalSigner::new(
+                                config.storage.signing_keypair()?,
+                                config.storage.voting_keypair()?,
+                                config.storage.fee_keypair()?,
+                            );
+                            // Rotated keys are written to the key files.
+                            match config.storage.key_store() {
+                                Some(key_store) => Arc::new(signer.with_key_store(key_store)),
+                                None => Arc::new(signer),
+                            }
+                        }
+                    };
+
+                    // Hosts sharing this validator are told apart by their peer ID by default.
+                    let failover = validator_config.failover.map(|failover| {
+                        log::info!(
+                            lease_file = %failover.lease_file.display(),
+                            "Using active/standby failover"
+                        );
+                        FailoverConfig {
+                            store: Arc::new(FileLeaseStore::new(failover.lease_file)),
+                            node_id: failover
+                                .node_id
+                                .unwrap_or_else(|| network.get_local_peer_id().to_string()),
+                            lease_duration: failover.lease_duration,
+                        }
+                    });
 
                     let validator_network =
                         Arc::new(ValidatorNetworkImpl::new(Arc::clone(&network)));
@@ -526,9 +642,12 @@ impl ClientInner {
                         validator_network,
                         validator_address,
                         automatic_reactivate,
-                        signing_key,
-                        voting_key,
-                        fee_key,
+                        signer,
+                        failover,
+                        validator_config.journal_tendermint,
+                        validator_config.aggregation,
+                        validator_config.transaction_selection,
+                        equivocation_monitor.clone(),
                         config.mempool.clone(),
                     );
 
@@ -563,6 +682,21 @@ impl ClientInner {
             }
         }
 
+        #[cfg(feature = "validator")]
+        let uptime_index = match blockchain_proxy {
+            BlockchainProxy::Full(ref blockchain)
+                if config.consensus.index_validator_uptime
+                    && config.consensus.sync_mode == SyncMode::History =>
+            {
+                log::info!("Validator uptime index enabled");
+                Some(UptimeIndex::new(
+                    environment.clone(),
+                    Arc::clone(blockchain),
+                ))
+            }
+            _ => None,
+        };
+
         // Start network.
         network.listen_on(config.network.listen_addresses).await;
         network.start_connecting().await;
@@ -574,6 +708,10 @@ impl ClientInner {
                 blockchain: blockchain_proxy,
                 #[cfg(feature = "validator")]
                 validator: validator_proxy,
+                #[cfg(feature = "validator")]
+                uptime_index,
+                #[cfg(feature = "validator")]
+                equivocation_monitor,
                 #[cfg(feature = "wallet")]
                 wallet_store,
                 zkp_component: zkp_component.proxy(),
@@ -693,6 +831,20 @@ impl Client {
         self.inner.validator.clone()
     }
 
+    /// Returns the *Validator uptime index* or `None` if it isn't enabled. The index needs to be
+    /// kept up to date by running [`UptimeIndex::run`].
+    #[cfg(feature = "validator")]
+    pub fn uptime_index(&self) -> Option<UptimeIndex> {
+        self.inner.uptime_index.clone()
+    }
+
+    /// Returns the *Equivocation monitor* or `None` if it isn't enabled. The monitor needs to be
+    /// run with [`EquivocationMonitor::run`].
+    #[cfg(feature = "validator")]
+    pub fn equivocation_monitor(&self) -> Option<EquivocationMonitor> {
+        self.inner.equivocation_monitor.clone()
+    }
+
     #[cfg(feature = "validator")]
     pub fn mempool(&self) -> Option<Arc<Mempool>> {
         match self.validator_or_mempool {
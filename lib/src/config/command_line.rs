@@ -1,7 +1,8 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use log::level_filters::{LevelFilter, ParseLevelFilterError};
+use nimiq_consensus::sync::checkpoint::TrustedCheckpoint;
 use nimiq_primitives::networks::NetworkId;
 use thiserror::Error;
 
@@ -64,9 +65,81 @@ pub struct CommandLine {
     #[clap(long)]
     pub network: Option<NetworkId>,
 
+    /// Pin an election block that the synced chain must contain, given as
+    /// `<block_number>:<hash>`
+    ///
+    /// # Examples
+    ///
+    /// * `nimiq-client --trusted-checkpoint 1728000:5a0f...`
+    ///
+    #[clap(long, value_parser)]
+    pub trusted_checkpoint: Option<TrustedCheckpoint>,
+
     /// Internally used flag to start a zero-knowledge prover process.
     #[clap(long, action)]
     pub prove: bool,
+
+    /// Run a maintenance command on the local database instead of starting the client.
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Export the accounts state at the latest macro block into a snapshot file.
+    ///
+    /// # Examples
+    ///
+    /// * `nimiq-client export-snapshot state.snapshot`
+    ///
+    ExportSnapshot {
+        /// The file to write the snapshot to.
+        file: PathBuf,
+    },
+
+    /// Bootstrap a full node from a snapshot file. The snapshot is verified against the
+    /// zero-knowledge proof and the macro chain it contains.
+    ///
+    /// # Examples
+    ///
+    /// * `nimiq-client --mode full import-snapshot state.snapshot`
+    ///
+    ImportSnapshot {
+        /// The file to read the snapshot from.
+        file: PathBuf,
+    },
+
+    /// Export the history of a range of epochs into an archive directory, one file per epoch.
+    ///
+    /// # Examples
+    ///
+    /// * `nimiq-client --mode history export-history archive/`
+    /// * `nimiq-client --mode history export-history --from-epoch 10 --to-epoch 20 archive/`
+    ///
+    ExportHistory {
+        /// The directory to write the epoch files to.
+        dir: PathBuf,
+
+        /// The first epoch to export. Defaults to the first epoch.
+        #[clap(long)]
+        from_epoch: Option<u32>,
+
+        /// The last epoch to export. Defaults to the latest completed epoch.
+        #[clap(long)]
+        to_epoch: Option<u32>,
+    },
+
+    /// Bootstrap a history node from an archive directory. Every epoch is verified like
+    /// during the history sync, which resumes after the last imported epoch.
+    ///
+    /// # Examples
+    ///
+    /// * `nimiq-client --mode history import-history archive/`
+    ///
+    ImportHistory {
+        /// The directory to read the epoch files from.
+        dir: PathBuf,
+    },
 }
 
 impl CommandLine {
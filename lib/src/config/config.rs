@@ -2,6 +2,8 @@
 use std::net::IpAddr;
 #[cfg(feature = "metrics-server")]
 use std::net::SocketAddr;
+#[cfg(feature = "validator")]
+use std::{collections::HashSet, fs::OpenOptions, sync::Arc, time::Duration};
 use std::{
     fmt,
     num::NonZeroU8,
@@ -10,13 +12,16 @@ use std::{
 };
 
 use derive_builder::Builder;
+use nimiq_blockchain_interface::HistoryRetention;
 #[cfg(feature = "validator")]
 use nimiq_bls::{KeyPair as BlsKeyPair, SecretKey as BlsSecretKey};
+use nimiq_consensus::sync::checkpoint::TrustedCheckpoint;
 #[cfg(feature = "database-storage")]
 use nimiq_database::mdbx::MdbxDatabase;
 use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_keys::Address;
 #[cfg(feature = "validator")]
-use nimiq_keys::{Address, KeyPair, PrivateKey};
+use nimiq_keys::{KeyPair, PrivateKey};
 #[cfg(feature = "nimiq-mempool")]
 use nimiq_mempool::{config::MempoolConfig, filter::MempoolRules};
 use nimiq_network_interface::Multiaddr;
@@ -26,6 +31,11 @@ use nimiq_serde::Deserialize;
 #[cfg(feature = "validator")]
 use nimiq_utils::key_rng::SecureGenerate;
 use nimiq_utils::{file_store::FileStore, Sensitive};
+#[cfg(feature = "validator")]
+use nimiq_validator::{
+    aggregation::AggregationConfig, failover::FailoverConfig, key_rotation::KeyStore,
+    signer::SignerError, transaction_selection::TransactionSelection,
+};
 use nimiq_zkp_circuits::DEFAULT_KEYS_PATH;
 use subtle::ConstantTimeEq;
 
@@ -92,6 +102,22 @@ pub struct ConsensusConfig {
     #[builder(default = "true")]
     /// History indices enabled. Only effective for history nodes (default: `true`)
     pub index_history: bool,
+    #[builder(default)]
+    /// Validator uptime index enabled. Only effective for history nodes (default: `false`)
+    pub index_validator_uptime: bool,
+    #[builder(default)]
+    /// Equivocation monitoring enabled. Only effective for full and history nodes (default: `false`)
+    pub monitor_equivocations: bool,
+    #[builder(default)]
+    /// Election block that the synced chain must contain
+    pub trusted_checkpoint: Option<TrustedCheckpoint>,
+    #[builder(default)]
+    /// Start the macro sync from the trusted checkpoint if no ZKP can be obtained.
+    /// Only effective for full and light nodes (default: `false`)
+    pub sync_from_checkpoint: bool,
+    #[builder(default)]
+    /// Part of the transaction history that is kept. Only effective for history nodes
+    pub history_retention: HistoryRetention,
 }
 
 impl Default for ConsensusConfig {
@@ -102,6 +128,11 @@ impl Default for ConsensusConfig {
             max_epochs_stored: Policy::MIN_EPOCHS_STORED,
             full_sync_threshold: 10800,
             index_history: true,
+            index_validator_uptime: false,
+            monitor_equivocations: false,
+            trusted_checkpoint: None,
+            sync_from_checkpoint: false,
+            history_retention: HistoryRetention::Full,
         }
     }
 }
@@ -155,6 +186,28 @@ pub struct NetworkConfig {
     /// Optional quorum value for the network DHT
     #[builder(default)]
     pub dht_quorum: Option<NonZeroU8>,
+
+    /// Optional path to a peer allowlist file. If set, the node only connects to peers
+    /// listed in this file (see `PeerAllowlist`). The file is reloaded when it changes.
+    /// Validator key entries only take effect once the validator's record was received through
+    /// the DHT, thus they have no effect if `disable_discovery` is set.
+    #[builder(default)]
+    pub peer_allowlist_file: Option<PathBuf>,
+
+    /// Optional bool to disable the DHT and the exchange of peer contacts.
+    /// Peers are then only found through the configured seeds.
+    #[builder(default)]
+    pub disable_discovery: bool,
+
+    /// Optional maximum number of bytes per second a peer may cause us to transfer through its
+    /// requests. Requests of peers exceeding this limit are rejected.
+    #[builder(default)]
+    pub peer_bandwidth_limit: Option<u64>,
+
+    /// Optional bool to act as a circuit relay for peers that are not publicly reachable.
+    /// This should only be enabled on publicly reachable nodes.
+    #[builder(default)]
+    pub relay_server: bool,
 }
 
 /// Configuration for setting TLS for secure WebSocket
@@ -176,6 +229,69 @@ impl From<TlsSettings> for TlsConfig {
     }
 }
 
+/// Writes rotated validator keys to the key files.
+#[cfg(feature = "validator")]
+struct FileKeyStore {
+    signing_key_path: Option<PathBuf>,
+    voting_key_path: Option<PathBuf>,
+}
+
+#[cfg(feature = "validator")]
+impl FileKeyStore {
+    fn signing_key_path(&self) -> Result<&Path, SignerError> {
+        self.signing_key_path
+            .as_deref()
+            .ok_or_else(|| SignerError::KeyStore("No path for warm key specified".to_string()))
+    }
+
+    fn voting_key_path(&self) -> Result<&Path, SignerError> {
+        self.voting_key_path
+            .as_deref()
+            .ok_or_else(|| SignerError::KeyStore("No path for validator key specified".to_string()))
+    }
+
+    /// Fails if the key file can't be written. The key files were created on startup, so they
+    /// are opened for appending, which doesn't modify them.
+    fn check_file(path: &Path) -> Result<(), SignerError> {
+        OpenOptions::new()
+            .append(true)
+            .open(path)
+            .map(drop)
+            .map_err(|error| {
+                SignerError::KeyStore(format!(
+                    "Key file {} is not writable: {}",
+                    path.display(),
+                    error
+                ))
+            })
+    }
+}
+
+#[cfg(feature = "validator")]
+impl KeyStore for FileKeyStore {
+    fn check_writable(&self, signing_key: bool, voting_key: bool) -> Result<(), SignerError> {
+        if signing_key {
+            Self::check_file(self.signing_key_path()?)?;
+        }
+        if voting_key {
+            Self::check_file(self.voting_key_path()?)?;
+        }
+        Ok(())
+    }
+
+    fn store_signing_key(&self, key: &KeyPair) -> Result<(), SignerError> {
+        FileStore::new(self.signing_key_path()?)
+            .store(key)
+            .map_err(|error| SignerError::KeyStore(error.to_string()))
+    }
+
+    fn store_voting_key(&self, key: &BlsKeyPair) -> Result<(), SignerError> {
+        FileStore::new(self.voting_key_path()?)
+            .store(key)
+            .map_err(|error| SignerError::KeyStore(error.to_string()))
+    }
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct FileStorageConfig {
     /// The parent directory where the database will be stored. The database directory name
@@ -473,6 +589,19 @@ impl StorageConfig {
         })
     }
 
+    /// Returns a store that writes rotated validator keys to the key files, such that they are
+    /// loaded after a restart. Volatile storage doesn't keep any keys.
+    #[cfg(feature = "validator")]
+    pub(crate) fn key_store(&self) -> Option<Arc<dyn KeyStore>> {
+        match self {
+            StorageConfig::Volatile => None,
+            StorageConfig::Filesystem(file_storage) => Some(Arc::new(FileKeyStore {
+                signing_key_path: file_storage.signing_key_path.clone(),
+                voting_key_path: file_storage.voting_key_path.clone(),
+            })),
+        }
+    }
+
     pub(crate) fn identity_keypair(&self) -> Result<IdentityKeypair, Error> {
         match self {
             StorageConfig::Volatile => Ok(IdentityKeypair::generate_ed25519()),
@@ -505,13 +634,52 @@ impl Default for StorageConfig {
 }
 
 #[cfg(feature = "validator")]
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ValidatorConfig {
     /// The validator address.
     pub validator_address: Address,
 
     /// Config if the validator automatically reactivates itself.
     pub automatic_reactivate: bool,
+
+    /// The remote signer holding the validator keys. If `None`, the keys are loaded from the
+    /// storage config.
+    pub remote_signer: Option<RemoteSignerConfig>,
+
+    /// The active/standby failover between several hosts of this validator, if enabled.
+    pub failover: Option<ValidatorFailoverConfig>,
+
+    /// Config if the events of the Tendermint instances are recorded in the journal.
+    pub journal_tendermint: bool,
+
+    /// The Handel settings of the signature aggregations.
+    pub aggregation: AggregationConfig,
+
+    /// The policies for the transactions included in the produced micro blocks.
+    pub transaction_selection: TransactionSelection,
+}
+
+#[cfg(feature = "validator")]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct RemoteSignerConfig {
+    /// The address of the signer, either `host:port` or `unix:<path>`.
+    pub address: String,
+
+    /// The key shared with the signer to authenticate the connection.
+    pub auth_key: Sensitive<Vec<u8>>,
+}
+
+#[cfg(feature = "validator")]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct ValidatorFailoverConfig {
+    /// The lease file shared by the hosts of the validator.
+    pub lease_file: PathBuf,
+
+    /// Identifies this host in the lease. Defaults to our peer ID.
+    pub node_id: Option<String>,
+
+    /// How long the lease is valid without being renewed.
+    pub lease_duration: Duration,
 }
 
 /// Credentials for JSON RPC server, metrics server or websocket RPC server
@@ -762,12 +930,22 @@ impl ClientConfigBuilder {
             only_secure_ws_connections: false,
             allow_loopback_addresses: config_file.network.allow_loopback_addresses,
             dht_quorum: config_file.network.dht_quorum,
+            peer_allowlist_file: config_file
+                .network
+                .peer_allowlist_file
+                .as_ref()
+                .map(PathBuf::from),
+            disable_discovery: config_file.network.disable_discovery,
+            peer_bandwidth_limit: config_file.network.peer_bandwidth_limit,
+            relay_server: config_file.network.relay_server,
         });
 
         // Configure consensus
         let mut consensus = ConsensusConfigBuilder::default()
             .sync_mode(config_file.consensus.sync_mode)
             .index_history(config_file.consensus.index_history)
+            .index_validator_uptime(config_file.consensus.index_validator_uptime)
+            .monitor_equivocations(config_file.consensus.monitor_equivocations)
             .build()
             .unwrap();
         if let Some(min_peers) = config_file.consensus.min_peers {
@@ -776,6 +954,32 @@ impl ClientConfigBuilder {
         if let Some(full_sync_threshold) = config_file.consensus.full_sync_threshold {
             consensus.full_sync_threshold = full_sync_threshold;
         }
+        if let Some(trusted_checkpoint) = &config_file.consensus.trusted_checkpoint {
+            consensus.trusted_checkpoint = Some(trusted_checkpoint.parse().map_err(|err| {
+                Error::config_error(format!("Invalid trusted checkpoint: {err}"))
+            })?);
+        }
+        consensus.sync_from_checkpoint = config_file.consensus.sync_from_checkpoint;
+        consensus.history_retention = match (
+            config_file.consensus.history_retention_epochs,
+            config_file.consensus.history_retention_since_block,
+            config_file.consensus.history_retention_addresses.as_slice(),
+        ) {
+            (None, None, []) => HistoryRetention::Full,
+            (Some(num_epochs), None, []) => HistoryRetention::LastEpochs(num_epochs),
+            (None, Some(block_number), []) => HistoryRetention::SinceBlock(block_number),
+            (None, None, addresses) => HistoryRetention::Addresses(
+                addresses
+                    .iter()
+                    .map(|address| Address::from_any_str(address))
+                    .collect::<Result<_, _>>()?,
+            ),
+            _ => {
+                return Err(Error::config_error(
+                    "Only one history retention mode can be configured",
+                ))
+            }
+        };
         self.consensus(consensus);
 
         // Configure network
@@ -798,9 +1002,78 @@ impl ClientConfigBuilder {
         }
         #[cfg(feature = "validator")]
         if let Some(validator_config) = config_file.validator.as_ref() {
+            let remote_signer = match &validator_config.signer_address {
+                Some(address) => {
+                    let auth_key = validator_config.signer_auth_key.as_ref().ok_or_else(|| {
+                        Error::config_error("A remote signer requires a signer_auth_key")
+                    })?;
+                    Some(RemoteSignerConfig {
+                        address: address.clone(),
+                        auth_key: Sensitive(hex::decode(&auth_key.0).map_err(|e| {
+                            Error::config_error(format!("Invalid signer_auth_key: {}", e))
+                        })?),
+                    })
+                }
+                None => None,
+            };
+
+            let failover = validator_config
+                .failover_lease_file
+                .as_ref()
+                .map(|lease_file| ValidatorFailoverConfig {
+                    lease_file: PathBuf::from(lease_file),
+                    node_id: validator_config.failover_node_id.clone(),
+                    lease_duration: validator_config
+                        .failover_lease_duration
+                        .map(Duration::from_secs)
+                        .unwrap_or(FailoverConfig::DEFAULT_LEASE_DURATION),
+                });
+
+            let validator_address = Address::from_any_str(&validator_config.validator_address)?;
+
+            let mut transaction_selection = TransactionSelection::default();
+            if let Some(settings) = &validator_config.transaction_selection {
+                let addresses = |addresses: &Vec<String>| {
+                    addresses
+                        .iter()
+                        .map(|address| Address::from_any_str(address))
+                        .collect::<Result<HashSet<_>, _>>()
+                };
+                transaction_selection = TransactionSelection {
+                    min_fee_per_byte: settings.min_fee_per_byte.unwrap_or_default(),
+                    reserved_control_bytes: settings.reserved_control_bytes,
+                    own_accounts: addresses(&settings.own_accounts)?,
+                    excluded_senders: addresses(&settings.excluded_senders)?,
+                    excluded_recipients: addresses(&settings.excluded_recipients)?,
+                    deprioritized_senders: addresses(&settings.deprioritized_senders)?,
+                    deprioritized_recipients: addresses(&settings.deprioritized_recipients)?,
+                };
+                if settings.prioritize_own_transactions {
+                    transaction_selection
+                        .own_accounts
+                        .insert(validator_address.clone());
+                }
+            }
+
             self.validator(ValidatorConfig {
-                validator_address: Address::from_any_str(&validator_config.validator_address)?,
+                validator_address,
                 automatic_reactivate: validator_config.automatic_reactivate,
+                remote_signer,
+                failover,
+                journal_tendermint: validator_config.journal_tendermint,
+                aggregation: AggregationConfig {
+                    tendermint: validator_config
+                        .tendermint_aggregation
+                        .clone()
+                        .map(Into::into)
+                        .unwrap_or_default(),
+                    skip_block: validator_config
+                        .skip_block_aggregation
+                        .clone()
+                        .map(Into::into)
+                        .unwrap_or_default(),
+                },
+                transaction_selection,
             });
 
             if let Some(key_path) = &validator_config.voting_key_file {
@@ -938,6 +1211,13 @@ impl ClientConfigBuilder {
                 .sync_mode = sync_mode.into()
         }
 
+        // Set trusted checkpoint
+        if let Some(trusted_checkpoint) = &command_line.trusted_checkpoint {
+            self.consensus
+                .get_or_insert_with(ConsensusConfig::default)
+                .trusted_checkpoint = Some(trusted_checkpoint.clone());
+        }
+
         // Set network ID
         if let Some(network_id) = command_line.network {
             self.network_id(network_id);
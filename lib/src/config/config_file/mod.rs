@@ -1,8 +1,12 @@
+#[cfg(feature = "validator")]
+use std::time::Duration;
 use std::{
     collections::HashMap, fmt::Debug, fs::read_to_string, num::NonZeroU8, path::Path, str::FromStr,
 };
 
 use log::level_filters::LevelFilter;
+#[cfg(feature = "validator")]
+use nimiq_handel::config::{AdaptiveTimeout, Config as HandelConfig};
 #[cfg(feature = "nimiq-mempool")]
 use nimiq_mempool::{
     config::MempoolConfig,
@@ -148,6 +152,12 @@ pub struct NetworkSettings {
     pub allow_loopback_addresses: bool,
     #[serde(default)]
     pub dht_quorum: Option<NonZeroU8>,
+    pub peer_allowlist_file: Option<String>,
+    #[serde(default)]
+    pub disable_discovery: bool,
+    pub peer_bandwidth_limit: Option<u64>,
+    #[serde(default)]
+    pub relay_server: bool,
 }
 
 impl NetworkSettings {
@@ -191,6 +201,28 @@ pub struct ConsensusSettings {
     /// History indices enabled. Only effective for history nodes (default: `true`)
     #[serde(default = "default_true")]
     pub index_history: bool,
+    #[serde(default)]
+    /// Validator uptime index enabled. Only effective for history nodes (default: `false`)
+    pub index_validator_uptime: bool,
+    #[serde(default)]
+    /// Equivocation monitoring enabled. Only effective for full and history nodes (default: `false`)
+    pub monitor_equivocations: bool,
+    /// Election block that the synced chain must contain, as `<block_number>:<hash>`
+    pub trusted_checkpoint: Option<String>,
+    #[serde(default)]
+    /// Start the macro sync from the trusted checkpoint if no ZKP can be obtained.
+    /// Only effective for full and light nodes (default: `false`)
+    pub sync_from_checkpoint: bool,
+    /// Keep the full transaction history only for this number of finalized epochs.
+    /// Only effective for history nodes
+    pub history_retention_epochs: Option<u32>,
+    /// Keep the full transaction history only for the epochs since this block.
+    /// Only effective for history nodes
+    pub history_retention_since_block: Option<u32>,
+    #[serde(default)]
+    /// Keep only the transaction history of these addresses for finalized epochs.
+    /// Only effective for history nodes
+    pub history_retention_addresses: Vec<String>,
 }
 
 impl Default for ConsensusSettings {
@@ -202,6 +234,13 @@ impl Default for ConsensusSettings {
             min_peers: None,
             full_sync_threshold: None,
             index_history: true,
+            index_validator_uptime: false,
+            monitor_equivocations: false,
+            trusted_checkpoint: None,
+            sync_from_checkpoint: false,
+            history_retention_epochs: None,
+            history_retention_since_block: None,
+            history_retention_addresses: vec![],
         }
     }
 }
@@ -434,6 +473,99 @@ pub struct ValidatorSettings {
     pub fee_key: Option<Sensitive<String>>,
     #[serde(default)]
     pub automatic_reactivate: bool,
+    /// Address of a remote signer holding the validator keys, either `host:port` or
+    /// `unix:<path>`. If set, the keys above are not used.
+    pub signer_address: Option<String>,
+    /// Hex encoded key shared with the remote signer to authenticate the connection.
+    pub signer_auth_key: Option<Sensitive<String>>,
+    /// Lease file shared by several hosts of this validator. If set, only the host holding the
+    /// lease signs, while the others stay synced and take over once the lease expires.
+    pub failover_lease_file: Option<String>,
+    /// Identifies this host in the lease. Defaults to the peer ID.
+    pub failover_node_id: Option<String>,
+    /// How long the lease is valid without being renewed, in seconds.
+    pub failover_lease_duration: Option<u64>,
+    /// Record the events of the Tendermint instances in the database, such that the production
+    /// of a macro block can be replayed.
+    #[serde(default)]
+    pub journal_tendermint: bool,
+    /// Handel settings of the Tendermint prevote and precommit aggregations.
+    pub tendermint_aggregation: Option<AggregationSettings>,
+    /// Handel settings of the skip block aggregations.
+    pub skip_block_aggregation: Option<AggregationSettings>,
+    /// Policies for the transactions included in the micro blocks produced by this validator.
+    pub transaction_selection: Option<TransactionSelectionSettings>,
+}
+
+/// Transaction selection policies of block production. Accounts are given as addresses in any
+/// format.
+#[derive(Clone, Debug, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct TransactionSelectionSettings {
+    /// Regular transactions paying less fee per byte are not included.
+    pub min_fee_per_byte: Option<f64>,
+    /// Bytes of each block reserved for control transactions.
+    pub reserved_control_bytes: Option<usize>,
+    /// Include transactions from or to the validator address and the `own_accounts` first.
+    /// Only transactions sent from them skip the `min_fee_per_byte`.
+    #[serde(default)]
+    pub prioritize_own_transactions: bool,
+    #[serde(default)]
+    pub own_accounts: Vec<String>,
+    #[serde(default)]
+    pub excluded_senders: Vec<String>,
+    #[serde(default)]
+    pub excluded_recipients: Vec<String>,
+    #[serde(default)]
+    pub deprioritized_senders: Vec<String>,
+    #[serde(default)]
+    pub deprioritized_recipients: Vec<String>,
+}
+
+/// Handel aggregation settings. All durations are in milliseconds.
+#[derive(Clone, Debug, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct AggregationSettings {
+    pub update_count: Option<usize>,
+    pub update_interval: Option<u64>,
+    pub timeout: Option<u64>,
+    pub peer_count: Option<usize>,
+    /// Adapt the level timeout to the level completion times of previous aggregations.
+    #[serde(default)]
+    pub adaptive_timeout: bool,
+    pub min_timeout: Option<u64>,
+    pub max_timeout: Option<u64>,
+}
+
+/// Convert aggregation settings
+#[cfg(feature = "validator")]
+impl From<AggregationSettings> for HandelConfig {
+    fn from(aggregation: AggregationSettings) -> Self {
+        let default = HandelConfig::default();
+        let default_adaptive_timeout = AdaptiveTimeout::default();
+        Self {
+            update_count: aggregation.update_count.unwrap_or(default.update_count),
+            update_interval: aggregation
+                .update_interval
+                .map(Duration::from_millis)
+                .unwrap_or(default.update_interval),
+            timeout: aggregation
+                .timeout
+                .map(Duration::from_millis)
+                .unwrap_or(default.timeout),
+            peer_count: aggregation.peer_count.unwrap_or(default.peer_count),
+            adaptive_timeout: aggregation.adaptive_timeout.then(|| AdaptiveTimeout {
+                min_timeout: aggregation
+                    .min_timeout
+                    .map(Duration::from_millis)
+                    .unwrap_or(default_adaptive_timeout.min_timeout),
+                max_timeout: aggregation
+                    .max_timeout
+                    .map(Duration::from_millis)
+                    .unwrap_or(default_adaptive_timeout.max_timeout),
+            }),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Default)]
@@ -52,6 +52,14 @@ pub enum Error {
 
     #[error("Nano ZKP Error: {0}")]
     NanoZKP(#[from] nimiq_zkp_primitives::NanoZKPError),
+
+    #[cfg(feature = "full-consensus")]
+    #[error("History archive error: {0}")]
+    HistoryArchive(#[from] crate::history_archive::HistoryArchiveError),
+
+    #[cfg(feature = "full-consensus")]
+    #[error("Snapshot error: {0}")]
+    Snapshot(#[from] crate::snapshot::SnapshotError),
 }
 
 impl Error {
@@ -6,21 +6,27 @@ use nimiq_consensus::ConsensusProxy;
 use nimiq_mempool::mempool::Mempool;
 pub use nimiq_metrics_server::NimiqTaskMonitor;
 use nimiq_network_interface::network::Network;
+#[cfg(feature = "validator")]
+use nimiq_validator::validator::ValidatorProxy;
 
 pub fn start_metrics_server<TNetwork: Network>(
     addr: SocketAddr,
     blockchain_proxy: BlockchainProxy,
     #[cfg(feature = "nimiq-mempool")] mempool: Option<Arc<Mempool>>,
+    #[cfg(feature = "validator")] validator: Option<ValidatorProxy>,
     consensus_proxy: ConsensusProxy<TNetwork>,
     network: Arc<nimiq_network_libp2p::Network>,
     task_monitors: &[NimiqTaskMonitor],
 ) {
     #[cfg(not(feature = "nimiq-mempool"))]
     let mempool = None;
+    #[cfg(not(feature = "validator"))]
+    let validator = None;
     nimiq_metrics_server::start_metrics_server(
         addr,
         blockchain_proxy,
         mempool,
+        validator,
         consensus_proxy,
         network,
         task_monitors,
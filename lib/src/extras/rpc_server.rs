@@ -41,11 +41,15 @@ pub fn initialize_rpc_server(
     let wallet_dispatcher = WalletDispatcher::new(wallet_store);
     let unlocked_wallets = Arc::clone(&wallet_dispatcher.unlocked_wallets);
 
-    dispatcher.add(BlockchainDispatcher::new(client.blockchain()));
+    dispatcher.add(BlockchainDispatcher::new(
+        client.blockchain(),
+        client.uptime_index(),
+        client.equivocation_monitor(),
+    ));
 
     dispatcher.add(ConsensusDispatcher::new(
         client.consensus_proxy(),
-        Some(unlocked_wallets),
+        Some(Arc::clone(&unlocked_wallets)),
     ));
     dispatcher.add(NetworkDispatcher::new(client.network()));
     if let Some(mempool) = client.mempool() {
@@ -56,6 +60,7 @@ pub fn initialize_rpc_server(
         dispatcher.add(ValidatorDispatcher::new(
             validator_proxy,
             client.consensus_proxy(),
+            Some(unlocked_wallets),
         ));
     }
     dispatcher.add(wallet_dispatcher);
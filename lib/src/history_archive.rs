@@ -0,0 +1,493 @@
+//! Offline archives of the chain history, to bootstrap history nodes without downloading every
+//! epoch from peers.
+//!
+//! An archive is a directory with one file per epoch. Each file starts with a magic number and
+//! a version, followed by length-prefixed records: first the header with all macro blocks of the
+//! epoch, then the history of the epoch in chunks of `CHUNK_SIZE` items. Every chunk carries a
+//! proof against the history root of the epoch's election block.
+//!
+//! Imported epochs are pushed like batch sets received by the history sync, which then resumes
+//! from the end of the archive.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use nimiq_block::{Block, MacroBlock};
+use nimiq_blockchain::{interface::HistoryInterface, Blockchain, HistoryTreeChunk, CHUNK_SIZE};
+use nimiq_blockchain_interface::{AbstractBlockchain, BlockchainError, PushError, PushResult};
+use nimiq_genesis::NetworkId;
+use nimiq_primitives::policy::Policy;
+use nimiq_serde::{Deserialize, DeserializeError, Serialize};
+use nimiq_utils::time::OffsetTime;
+use parking_lot::RwLock;
+use thiserror::Error;
+
+use crate::{
+    config::{config::ClientConfig, config_file::SyncMode},
+    error::Error,
+    snapshot::{read_record, write_record},
+};
+
+/// Magic number at the beginning of every epoch file.
+const ARCHIVE_MAGIC: [u8; 8] = *b"NIMQHIST";
+
+/// Version of the epoch file format.
+const ARCHIVE_VERSION: u16 = 1;
+
+/// Extension of epoch files.
+const EPOCH_FILE_EXTENSION: &str = "nimiqhist";
+
+#[derive(Debug, Error)]
+pub enum HistoryArchiveError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Malformed epoch file: {0}")]
+    Deserialize(#[from] DeserializeError),
+    #[error("Not an epoch file")]
+    InvalidMagic,
+    #[error("Unsupported epoch file version: {0}")]
+    UnsupportedVersion(u16),
+    #[error("Archive is for network {found}, but the node runs on {expected}")]
+    WrongNetwork {
+        expected: NetworkId,
+        found: NetworkId,
+    },
+    #[error("History archives can only be used with history nodes")]
+    UnsupportedSyncMode,
+    #[error("Invalid epoch range: {0}..={1}")]
+    InvalidRange(u32, u32),
+    #[error("Missing epoch {0}")]
+    MissingEpoch(u32),
+    #[error("Epoch file for epoch {0} is inconsistent")]
+    InconsistentEpoch(u32),
+    #[error("Invalid history chunk {chunk_index} of epoch {epoch_number}")]
+    InvalidChunk { epoch_number: u32, chunk_index: u64 },
+    #[error("Invalid macro block: {0}")]
+    InvalidBlock(#[from] PushError),
+    #[error("Blockchain error: {0}")]
+    Blockchain(#[from] BlockchainError),
+}
+
+/// Information about an exported or imported range of epochs.
+#[derive(Clone, Debug)]
+pub struct HistoryArchiveInfo {
+    pub first_epoch: u32,
+    pub last_epoch: u32,
+    /// The number of history items.
+    pub num_items: u64,
+}
+
+/// Information about an imported epoch file.
+#[derive(Clone, Debug)]
+pub struct EpochInfo {
+    pub epoch_number: u32,
+    pub num_items: u64,
+    /// The number of macro blocks that weren't known yet.
+    pub num_pushed: usize,
+}
+
+/// Macro blocks of an epoch, in ascending order. The last one is the election block.
+#[derive(Serialize, Deserialize)]
+struct EpochHeader {
+    network_id: NetworkId,
+    epoch_number: u32,
+    macro_blocks: Vec<MacroBlock>,
+    /// The number of history items in the epoch.
+    num_items: u64,
+}
+
+impl EpochHeader {
+    fn election_block(&self) -> Option<&MacroBlock> {
+        self.macro_blocks.last().filter(|block| block.is_election())
+    }
+}
+
+/// Returns the path of the file for the given epoch within the archive directory.
+pub fn epoch_file_path(dir: &Path, epoch_number: u32) -> PathBuf {
+    dir.join(format!("epoch-{epoch_number:08}.{EPOCH_FILE_EXTENSION}"))
+}
+
+/// Writes the macro blocks and history of a finished epoch.
+pub fn export_epoch<W: Write>(
+    blockchain: &Blockchain,
+    epoch_number: u32,
+    writer: &mut W,
+) -> Result<u64, HistoryArchiveError> {
+    let election_block_number = Policy::election_block_of(epoch_number)
+        .filter(|block_number| *block_number <= blockchain.election_head().block_number())
+        .ok_or(HistoryArchiveError::MissingEpoch(epoch_number))?;
+
+    let txn = blockchain.read_transaction();
+    let first_batch = Policy::batch_at(Policy::first_block_of(epoch_number).unwrap());
+    let last_batch = Policy::batch_at(election_block_number);
+    let macro_blocks = (first_batch..=last_batch)
+        .map(|batch| {
+            let block_number = Policy::macro_block_of(batch).unwrap();
+            match blockchain
+                .chain_store
+                .get_block_at(block_number, true, Some(&txn))?
+            {
+                Block::Macro(block) => Ok(block),
+                Block::Micro(_) => Err(HistoryArchiveError::Blockchain(
+                    BlockchainError::BlockIsNotMacro,
+                )),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let num_items = blockchain
+        .history_store
+        .num_epoch_transactions(epoch_number, Some(&txn)) as u64;
+    let header = EpochHeader {
+        network_id: blockchain.network_id(),
+        epoch_number,
+        macro_blocks,
+        num_items,
+    };
+
+    writer.write_all(&ARCHIVE_MAGIC)?;
+    writer.write_all(&ARCHIVE_VERSION.to_be_bytes())?;
+    write_record(writer, &header)?;
+
+    for chunk_index in 0..num_items.div_ceil(CHUNK_SIZE as u64) {
+        let chunk = blockchain
+            .history_store
+            .prove_chunk(
+                epoch_number,
+                election_block_number,
+                CHUNK_SIZE,
+                chunk_index as usize,
+                Some(&txn),
+            )
+            .ok_or(HistoryArchiveError::InvalidChunk {
+                epoch_number,
+                chunk_index,
+            })?;
+        write_record(writer, &chunk)?;
+    }
+    writer.flush()?;
+
+    Ok(num_items)
+}
+
+/// Reads an epoch file, verifies its history against the election block and pushes its macro
+/// blocks into `blockchain`. Blocks that are already known are skipped.
+pub fn import_epoch<R: Read>(
+    blockchain: &Arc<RwLock<Blockchain>>,
+    reader: &mut R,
+) -> Result<EpochInfo, HistoryArchiveError> {
+    let mut magic = [0u8; ARCHIVE_MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+    if magic != ARCHIVE_MAGIC {
+        return Err(HistoryArchiveError::InvalidMagic);
+    }
+    let mut version = [0u8; 2];
+    reader.read_exact(&mut version)?;
+    let version = u16::from_be_bytes(version);
+    if version != ARCHIVE_VERSION {
+        return Err(HistoryArchiveError::UnsupportedVersion(version));
+    }
+
+    let header: EpochHeader = read_record::<_, _, HistoryArchiveError>(reader)?;
+    let epoch_number = header.epoch_number;
+    let network_id = blockchain.read().network_id();
+    if header.network_id != network_id {
+        return Err(HistoryArchiveError::WrongNetwork {
+            expected: network_id,
+            found: header.network_id,
+        });
+    }
+    let election_block = header
+        .election_block()
+        .filter(|block| block.epoch_number() == epoch_number)
+        .ok_or(HistoryArchiveError::InconsistentEpoch(epoch_number))?;
+
+    // Verify the history against the election block, which commits to the whole epoch. The
+    // number of items in the header isn't verified yet, so it only bounds the preallocation.
+    let mut history = Vec::with_capacity(header.num_items.min(CHUNK_SIZE as u64) as usize);
+    for chunk_index in 0..header.num_items.div_ceil(CHUNK_SIZE as u64) {
+        let mut chunk: HistoryTreeChunk = read_record::<_, _, HistoryArchiveError>(reader)?;
+        let leaf_index = chunk_index as usize * CHUNK_SIZE;
+        if chunk.verify(&election_block.header.history_root, leaf_index) != Some(true) {
+            return Err(HistoryArchiveError::InvalidChunk {
+                epoch_number,
+                chunk_index,
+            });
+        }
+        history.append(&mut chunk.history);
+    }
+    if history.len() as u64 != header.num_items {
+        return Err(HistoryArchiveError::InconsistentEpoch(epoch_number));
+    }
+
+    // Push the macro blocks with the history up to each of them, like the history sync does
+    // for batch sets. The blockchain verifies each block and its history root.
+    let mut num_pushed = 0;
+    for macro_block in header.macro_blocks {
+        let block_number = macro_block.block_number();
+        let history_len = history.partition_point(|hist_tx| hist_tx.block_number <= block_number);
+
+        match Blockchain::push_history_sync(
+            blockchain.upgradable_read(),
+            Block::Macro(macro_block),
+            &history[..history_len],
+        )? {
+            PushResult::Extended | PushResult::Rebranched => num_pushed += 1,
+            PushResult::Known | PushResult::Ignored | PushResult::Forked => {}
+        }
+    }
+
+    Ok(EpochInfo {
+        epoch_number,
+        num_items: header.num_items,
+        num_pushed,
+    })
+}
+
+/// Opens the blockchain of the history node described by `config`.
+fn open_blockchain(config: &ClientConfig) -> Result<Blockchain, Error> {
+    if config.consensus.sync_mode != SyncMode::History {
+        return Err(HistoryArchiveError::UnsupportedSyncMode.into());
+    }
+
+    let environment = config.storage.database(
+        config.network_id,
+        config.consensus.sync_mode,
+        config.database.clone(),
+    )?;
+    let blockchain_config = nimiq_blockchain::BlockchainConfig {
+        max_epochs_stored: config.consensus.max_epochs_stored,
+        keep_history: true,
+        index_history: config.consensus.index_history,
+        ..Default::default()
+    };
+    Blockchain::new(
+        environment,
+        blockchain_config,
+        config.network_id,
+        Arc::new(OffsetTime::new()),
+    )
+    .map_err(|error| Error::Consensus(nimiq_consensus::Error::BlockchainError(error)))
+}
+
+/// Exports the epochs `first_epoch..=last_epoch` of the history node described by `config` into
+/// the directory `dir`. If not given, the range covers all finished epochs. The node must not be
+/// running.
+pub fn export_history_archive(
+    config: &ClientConfig,
+    dir: &Path,
+    first_epoch: Option<u32>,
+    last_epoch: Option<u32>,
+) -> Result<HistoryArchiveInfo, Error> {
+    let blockchain = open_blockchain(config)?;
+
+    let first_epoch = first_epoch.unwrap_or(1);
+    let last_epoch = last_epoch.unwrap_or_else(|| blockchain.election_head().epoch_number());
+    if first_epoch == 0 || first_epoch > last_epoch {
+        return Err(HistoryArchiveError::InvalidRange(first_epoch, last_epoch).into());
+    }
+
+    fs::create_dir_all(dir)?;
+
+    let mut num_items = 0;
+    for epoch_number in first_epoch..=last_epoch {
+        let mut writer = BufWriter::new(File::create(epoch_file_path(dir, epoch_number))?);
+        num_items += export_epoch(&blockchain, epoch_number, &mut writer)?;
+        log::info!(epoch_number, "Exported epoch");
+    }
+
+    Ok(HistoryArchiveInfo {
+        first_epoch,
+        last_epoch,
+        num_items,
+    })
+}
+
+/// Imports the epoch files in the directory `dir` into the history node described by `config`.
+/// The import starts at the epoch following the latest local election block and stops at the
+/// first missing epoch file. The node must not be running.
+pub fn import_history_archive(
+    config: &ClientConfig,
+    dir: &Path,
+) -> Result<HistoryArchiveInfo, Error> {
+    let blockchain = Arc::new(RwLock::new(open_blockchain(config)?));
+
+    // The current epoch may already be known partially.
+    let first_epoch = {
+        let blockchain = blockchain.read();
+        if blockchain.macro_head_hash() == blockchain.election_head_hash() {
+            blockchain.epoch_number() + 1
+        } else {
+            blockchain.epoch_number()
+        }
+    };
+
+    let mut epoch_number = first_epoch;
+    let mut num_items = 0;
+    loop {
+        let path = epoch_file_path(dir, epoch_number);
+        if !path.exists() {
+            break;
+        }
+
+        let mut reader = BufReader::new(File::open(&path)?);
+        let info = import_epoch(&blockchain, &mut reader).inspect_err(|_| {
+            log::error!(path = %path.display(), "Failed to import epoch file");
+        })?;
+        if info.epoch_number != epoch_number {
+            return Err(HistoryArchiveError::InconsistentEpoch(epoch_number).into());
+        }
+
+        log::info!(
+            epoch_number,
+            num_items = info.num_items,
+            num_blocks = info.num_pushed,
+            "Imported epoch"
+        );
+        num_items += info.num_items;
+        epoch_number += 1;
+    }
+
+    if epoch_number == first_epoch {
+        return Err(HistoryArchiveError::MissingEpoch(first_epoch).into());
+    }
+
+    Ok(HistoryArchiveInfo {
+        first_epoch,
+        last_epoch: epoch_number - 1,
+        num_items,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use nimiq_blockchain::{BlockProducer, Blockchain, BlockchainConfig};
+    use nimiq_blockchain_interface::AbstractBlockchain;
+    use nimiq_database::mdbx::MdbxDatabase;
+    use nimiq_genesis::NetworkId;
+    use nimiq_primitives::policy::Policy;
+    use nimiq_test_log::test;
+    use nimiq_test_utils::blockchain::{produce_macro_blocks, signing_key, voting_key};
+    use nimiq_utils::time::OffsetTime;
+    use parking_lot::RwLock;
+
+    use super::{
+        export_epoch, import_epoch, write_record, EpochHeader, HistoryArchiveError, ARCHIVE_MAGIC,
+        ARCHIVE_VERSION,
+    };
+    use crate::snapshot::read_record;
+
+    fn blockchain() -> Arc<RwLock<Blockchain>> {
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let blockchain = Blockchain::new(
+            env,
+            BlockchainConfig::default(),
+            NetworkId::UnitAlbatross,
+            Arc::new(OffsetTime::new()),
+        )
+        .unwrap();
+        Arc::new(RwLock::new(blockchain))
+    }
+
+    /// Returns the epoch file of the first epoch and the blockchain it was exported from.
+    fn epoch_file() -> (Vec<u8>, Arc<RwLock<Blockchain>>) {
+        let blockchain = blockchain();
+        let producer = BlockProducer::new(signing_key(), voting_key());
+        produce_macro_blocks(&producer, &blockchain, Policy::batches_per_epoch() as usize);
+
+        let mut file = Vec::new();
+        export_epoch(&blockchain.read(), 1, &mut file).unwrap();
+        (file, blockchain)
+    }
+
+    #[test]
+    fn exported_epochs_can_be_imported() {
+        let (file, source) = epoch_file();
+        let blockchain = blockchain();
+
+        let info = import_epoch(&blockchain, &mut &file[..]).unwrap();
+        assert_eq!(info.epoch_number, 1);
+        assert_eq!(info.num_pushed, Policy::batches_per_epoch() as usize);
+        assert_eq!(
+            blockchain.read().election_head_hash(),
+            source.read().election_head_hash()
+        );
+
+        let num_items = source.read().history_store.num_epoch_transactions(1, None);
+        assert_eq!(info.num_items, num_items as u64);
+        assert_eq!(
+            blockchain
+                .read()
+                .history_store
+                .num_epoch_transactions(1, None),
+            num_items
+        );
+
+        // Importing an epoch that is already known doesn't push any blocks.
+        let info = import_epoch(&source, &mut &file[..]).unwrap();
+        assert_eq!(info.num_pushed, 0);
+    }
+
+    #[test]
+    fn truncated_epoch_files_are_rejected() {
+        let (file, _) = epoch_file();
+        let blockchain = blockchain();
+
+        let truncated = &file[..file.len() - 10];
+        let result = import_epoch(&blockchain, &mut &truncated[..]);
+        assert!(
+            matches!(result, Err(HistoryArchiveError::Io(error)) if error.kind() == std::io::ErrorKind::UnexpectedEof)
+        );
+        assert_eq!(
+            blockchain.read().block_number(),
+            Policy::genesis_block_number()
+        );
+    }
+
+    #[test]
+    fn corrupt_epoch_files_are_rejected() {
+        let (file, _) = epoch_file();
+        let blockchain = blockchain();
+
+        // Wrong magic number.
+        let mut corrupt = file.clone();
+        corrupt[0] ^= 0xff;
+        let result = import_epoch(&blockchain, &mut &corrupt[..]);
+        assert!(matches!(result, Err(HistoryArchiveError::InvalidMagic)));
+
+        // A header announcing more items than the file contains must not preallocate them.
+        let mut chunks = &file[ARCHIVE_MAGIC.len() + 2..];
+        let mut header: EpochHeader =
+            read_record::<_, _, HistoryArchiveError>(&mut chunks).unwrap();
+        header.num_items = u64::MAX;
+        let mut corrupt = ARCHIVE_MAGIC.to_vec();
+        corrupt.extend_from_slice(&ARCHIVE_VERSION.to_be_bytes());
+        write_record(&mut corrupt, &header).unwrap();
+        corrupt.extend_from_slice(chunks);
+        let result = import_epoch(&blockchain, &mut &corrupt[..]);
+        assert!(matches!(result, Err(HistoryArchiveError::Io(_))));
+
+        // A manipulated history chunk fails verification.
+        if !chunks.is_empty() {
+            let mut corrupt = file.clone();
+            let last = corrupt.len() - 1;
+            corrupt[last] ^= 0x01;
+            let result = import_epoch(&blockchain, &mut &corrupt[..]);
+            assert!(matches!(
+                result,
+                Err(HistoryArchiveError::InvalidChunk { .. } | HistoryArchiveError::Deserialize(_))
+            ));
+        }
+
+        assert_eq!(
+            blockchain.read().block_number(),
+            Policy::genesis_block_number()
+        );
+    }
+}
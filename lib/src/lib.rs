@@ -2,6 +2,10 @@ pub mod client;
 pub mod config;
 pub mod error;
 pub mod extras;
+#[cfg(feature = "full-consensus")]
+pub mod history_archive;
+#[cfg(feature = "full-consensus")]
+pub mod snapshot;
 
 #[cfg(feature = "zkp-prover")]
 pub mod prover {
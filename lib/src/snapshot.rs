@@ -0,0 +1,594 @@
+//! State snapshots to bootstrap full nodes without downloading the accounts trie from peers.
+//!
+//! A snapshot contains the accounts trie (including the staking contract) at a macro block,
+//! together with the blocks needed to verify it: the election block covered by the ZKP, the
+//! proof itself and the macro chain from there to the snapshot block. The file starts with a
+//! magic number and a version, followed by length-prefixed records: first the header, then the
+//! trie chunks in key order. The last chunk is the one without an end key.
+//!
+//! The history within the validity window isn't part of the snapshot. A node that imported a
+//! snapshot fetches it from its peers when it starts.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use nimiq_block::Block;
+use nimiq_blockchain::{Blockchain, BlockchainConfig};
+use nimiq_blockchain_interface::{
+    AbstractBlockchain, BlockchainError, ChunksPushError, ChunksPushResult, Direction, PushError,
+};
+use nimiq_database::mdbx::MdbxDatabase;
+use nimiq_genesis::NetworkId;
+use nimiq_hash::Blake2bHash;
+use nimiq_primitives::{
+    key_nibbles::KeyNibbles,
+    policy::Policy,
+    trie::trie_chunk::{TrieChunk, TrieChunkWithStart},
+};
+use nimiq_serde::{Deserialize, DeserializeError, Serialize};
+use nimiq_utils::time::OffsetTime;
+use nimiq_zkp::ZKP_VERIFYING_DATA;
+use nimiq_zkp_component::{
+    proof_store::{DBProofStore, ProofStore},
+    types::ZKProof,
+};
+use parking_lot::RwLock;
+use thiserror::Error;
+
+use crate::{
+    config::{config::ClientConfig, config_file::SyncMode},
+    error::Error,
+};
+
+/// Magic number at the beginning of every snapshot file.
+const SNAPSHOT_MAGIC: [u8; 8] = *b"NIMQSNAP";
+
+/// Version of the snapshot file format.
+const SNAPSHOT_VERSION: u16 = 1;
+
+/// Maximum number of accounts trie items per chunk.
+const SNAPSHOT_CHUNK_SIZE: usize = 10_000;
+
+/// Maximum size of a single record in bytes. Larger records are rejected before any memory is
+/// allocated for them.
+const MAX_RECORD_SIZE: u32 = 256 * 1024 * 1024;
+
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Malformed snapshot: {0}")]
+    Deserialize(#[from] DeserializeError),
+    #[error("Not a snapshot file")]
+    InvalidMagic,
+    #[error("Unsupported snapshot version: {0}")]
+    UnsupportedVersion(u16),
+    #[error("Snapshot is for network {found}, but the node runs on {expected}")]
+    WrongNetwork {
+        expected: NetworkId,
+        found: NetworkId,
+    },
+    #[error("Snapshots can't be used with sync mode {0:?}")]
+    UnsupportedSyncMode(SyncMode),
+    #[error("The accounts trie is incomplete")]
+    IncompleteState,
+    #[error("Missing block: {0}")]
+    MissingBlock(String),
+    #[error(
+        "Snapshot at block {snapshot} is not newer than the local macro head at block {local}"
+    )]
+    NotNewer { local: u32, snapshot: u32 },
+    #[error("Snapshot has no proof for election block {0}")]
+    MissingProof(u32),
+    #[error("Invalid snapshot block: {0}")]
+    InvalidBlock(#[from] PushError),
+    #[error("Invalid snapshot chunk: {0}")]
+    InvalidChunk(#[from] ChunksPushError),
+    #[error("Snapshot chunk starting at {0} was not applied")]
+    ChunkIgnored(KeyNibbles),
+    #[error("Snapshot ended before the accounts trie was complete")]
+    Truncated,
+    #[error("Accounts trie doesn't match the state root of the snapshot block")]
+    StateRootMismatch,
+    #[error("Blockchain error: {0}")]
+    Blockchain(#[from] BlockchainError),
+}
+
+/// Information about an exported or imported snapshot.
+#[derive(Clone, Debug)]
+pub struct SnapshotInfo {
+    /// The macro block the state belongs to.
+    pub block_number: u32,
+    pub block_hash: Blake2bHash,
+    /// The number of accounts trie chunks.
+    pub num_chunks: usize,
+    /// The number of accounts trie items.
+    pub num_items: usize,
+}
+
+/// Blocks and proof needed to verify the state contained in a snapshot.
+#[derive(Serialize, Deserialize)]
+struct SnapshotHeader {
+    network_id: NetworkId,
+    /// The election block proven by the ZKP. This is the genesis block if there is no proof.
+    zkp_block: Block,
+    zkp: ZKProof,
+    /// The election block preceding the ZKP block, if known. It provides the previous slots.
+    previous_election_block: Option<Block>,
+    /// The election blocks following the ZKP block, up to the snapshot block.
+    election_blocks: Vec<Block>,
+    /// The checkpoint block the state belongs to. If not set, the state belongs to the last
+    /// election block.
+    checkpoint_block: Option<Block>,
+}
+
+impl SnapshotHeader {
+    /// The macro block the state belongs to.
+    fn block(&self) -> &Block {
+        self.checkpoint_block
+            .as_ref()
+            .or(self.election_blocks.last())
+            .unwrap_or(&self.zkp_block)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotChunk {
+    start_key: KeyNibbles,
+    chunk: TrieChunk,
+}
+
+/// Writes a length-prefixed record.
+pub(crate) fn write_record<W: Write, T: Serialize>(writer: &mut W, record: &T) -> io::Result<()> {
+    let bytes = record.serialize_to_vec();
+    writer.write_all(&(bytes.len() as u32).to_be_bytes())?;
+    writer.write_all(&bytes)
+}
+
+/// Reads a length-prefixed record. Records larger than `MAX_RECORD_SIZE` are rejected, and the
+/// buffer only grows with the data actually read, such that a corrupt length can't exhaust memory.
+pub(crate) fn read_record<R, T, E>(reader: &mut R) -> Result<T, E>
+where
+    R: Read,
+    T: Deserialize,
+    E: From<io::Error> + From<DeserializeError>,
+{
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len);
+    if len > MAX_RECORD_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Record of {len} bytes exceeds the maximum of {MAX_RECORD_SIZE} bytes"),
+        )
+        .into());
+    }
+
+    let mut bytes = Vec::new();
+    reader.take(u64::from(len)).read_to_end(&mut bytes)?;
+    if bytes.len() != len as usize {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(T::deserialize_all(&bytes)?)
+}
+
+/// Writes a snapshot of the state at the current macro head of `blockchain`. `proof_store`
+/// provides the latest ZKP, which must not be newer than the macro head.
+pub fn export_snapshot<W: Write>(
+    blockchain: &Blockchain,
+    proof_store: &dyn ProofStore,
+    writer: &mut W,
+) -> Result<SnapshotInfo, SnapshotError> {
+    if !blockchain.state.accounts.is_complete(None) {
+        return Err(SnapshotError::IncompleteState);
+    }
+
+    let block = Block::Macro(blockchain.macro_head());
+    let genesis_block_number = Policy::genesis_block_number();
+    let zkp = proof_store
+        .get_zkp()
+        .filter(|zkp| zkp.proof.is_some() && zkp.block_number <= block.block_number())
+        .unwrap_or_else(|| ZKProof::new(genesis_block_number, None));
+
+    let zkp_block = blockchain
+        .chain_store
+        .get_block_at(zkp.block_number, true, None)
+        .map_err(|_| SnapshotError::MissingBlock(format!("#{}", zkp.block_number)))?;
+    let previous_election_block = if zkp.block_number > genesis_block_number {
+        blockchain
+            .chain_store
+            .get_block(
+                &zkp_block.unwrap_macro_ref().header.parent_election_hash,
+                true,
+                None,
+            )
+            .ok()
+    } else {
+        None
+    };
+
+    let num_elections = Policy::epoch_at(block.block_number())
+        .saturating_sub(Policy::epoch_at(zkp.block_number))
+        .saturating_sub(u32::from(!block.is_election()));
+    let election_blocks = blockchain.chain_store.get_macro_blocks(
+        &zkp_block.hash(),
+        num_elections,
+        true,
+        Direction::Forward,
+        true,
+        None,
+    )?;
+    if election_blocks.len() != num_elections as usize {
+        return Err(SnapshotError::MissingBlock(format!(
+            "election blocks after #{}",
+            zkp.block_number
+        )));
+    }
+
+    let header = SnapshotHeader {
+        network_id: blockchain.network_id(),
+        zkp_block,
+        zkp,
+        previous_election_block,
+        election_blocks,
+        checkpoint_block: (!block.is_election()).then(|| block.clone()),
+    };
+    debug_assert_eq!(header.block().hash(), block.hash());
+
+    writer.write_all(&SNAPSHOT_MAGIC)?;
+    writer.write_all(&SNAPSHOT_VERSION.to_be_bytes())?;
+    write_record(writer, &header)?;
+
+    let mut num_chunks = 0;
+    let mut num_items = 0;
+    blockchain.export_macro_head_state(SNAPSHOT_CHUNK_SIZE, |chunk| {
+        num_chunks += 1;
+        num_items += chunk.chunk.items.len();
+        write_record(
+            writer,
+            &SnapshotChunk {
+                start_key: chunk.start_key,
+                chunk: chunk.chunk,
+            },
+        )
+    })?;
+    writer.flush()?;
+
+    Ok(SnapshotInfo {
+        block_number: block.block_number(),
+        block_hash: block.hash(),
+        num_chunks,
+        num_items,
+    })
+}
+
+/// Reads a snapshot and applies it to `blockchain`. The ZKP of the snapshot is stored in
+/// `proof_store`, unless it already holds a newer one.
+///
+/// The blocks are verified like blocks received during the macro sync, i.e. the ZKP is checked
+/// against the genesis block and the macro chain against the validators of each epoch. Each
+/// chunk is verified against the state root of the snapshot block.
+///
+/// Every chunk is committed on its own. If an import is interrupted, importing the same snapshot
+/// again resumes it: the blocks are already in place and the chunks that were applied before are
+/// skipped.
+pub fn import_snapshot<R: Read>(
+    blockchain: &Arc<RwLock<Blockchain>>,
+    proof_store: &dyn ProofStore,
+    reader: &mut R,
+) -> Result<SnapshotInfo, SnapshotError> {
+    let mut magic = [0u8; SNAPSHOT_MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+    if magic != SNAPSHOT_MAGIC {
+        return Err(SnapshotError::InvalidMagic);
+    }
+    let mut version = [0u8; 2];
+    reader.read_exact(&mut version)?;
+    let version = u16::from_be_bytes(version);
+    if version != SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+
+    let header: SnapshotHeader = read_record::<_, _, SnapshotError>(reader)?;
+    let block = header.block().clone();
+    let block_hash = block.hash();
+
+    let resume = {
+        let blockchain_rg = blockchain.read();
+        if header.network_id != blockchain_rg.network_id() {
+            return Err(SnapshotError::WrongNetwork {
+                expected: blockchain_rg.network_id(),
+                found: header.network_id,
+            });
+        }
+        // A previous import of this snapshot pushed the blocks, but didn't complete the state.
+        let resume = blockchain_rg.head_hash() == block_hash
+            && !blockchain_rg.state.accounts.is_complete(None);
+        let local = blockchain_rg.macro_head().block_number();
+        if !resume && block.block_number() <= local {
+            return Err(SnapshotError::NotNewer {
+                local,
+                snapshot: block.block_number(),
+            });
+        }
+        resume
+    };
+
+    if !resume {
+        push_snapshot_blocks(blockchain, proof_store, header)?;
+    }
+
+    if blockchain.read().head_hash() != block_hash {
+        return Err(SnapshotError::MissingBlock(block_hash.to_string()));
+    }
+
+    // Chunks before the missing range were applied by a previous, interrupted import.
+    let missing_start = blockchain
+        .read()
+        .get_missing_accounts_range(None)
+        .map(|range| range.start);
+
+    let mut num_chunks = 0;
+    let mut num_items = 0;
+    loop {
+        let chunk: SnapshotChunk =
+            read_record(reader).map_err(|error: SnapshotError| match error {
+                SnapshotError::Io(error) if error.kind() == io::ErrorKind::UnexpectedEof => {
+                    SnapshotError::Truncated
+                }
+                error => error,
+            })?;
+        let is_last = chunk.chunk.end_key.is_none();
+        let start_key = chunk.start_key.clone();
+        num_items += chunk.chunk.items.len();
+        num_chunks += 1;
+
+        let applied = missing_start
+            .as_ref()
+            .map_or(true, |missing_start| start_key < *missing_start);
+        if !applied {
+            let result = blockchain.read().commit_chunks(
+                vec![TrieChunkWithStart {
+                    chunk: chunk.chunk,
+                    start_key: chunk.start_key,
+                }],
+                &block_hash,
+            )?;
+            if result != ChunksPushResult::Chunks(1, 0) {
+                return Err(SnapshotError::ChunkIgnored(start_key));
+            }
+        }
+
+        if is_last {
+            break;
+        }
+    }
+
+    let blockchain_rg = blockchain.read();
+    if !blockchain_rg.state.accounts.is_complete(None) {
+        return Err(SnapshotError::Truncated);
+    }
+    if &blockchain_rg.state.accounts.get_root_hash_assert(None) != block.state_root() {
+        return Err(SnapshotError::StateRootMismatch);
+    }
+
+    Ok(SnapshotInfo {
+        block_number: block.block_number(),
+        block_hash,
+        num_chunks,
+        num_items,
+    })
+}
+
+/// Verifies and pushes the ZKP and the macro chain of a snapshot, moving the head of `blockchain`
+/// to the snapshot block.
+fn push_snapshot_blocks(
+    blockchain: &Arc<RwLock<Blockchain>>,
+    proof_store: &dyn ProofStore,
+    header: SnapshotHeader,
+) -> Result<(), SnapshotError> {
+    match header.zkp.proof.clone() {
+        Some(proof) => {
+            if header.zkp_block.block_number() != header.zkp.block_number
+                || !header.zkp_block.is_election()
+            {
+                return Err(SnapshotError::InvalidBlock(PushError::InvalidZKP));
+            }
+            Blockchain::push_zkp(blockchain.upgradable_read(), header.zkp_block, proof, false)?;
+
+            if !proof_store
+                .get_zkp()
+                .is_some_and(|zkp| zkp.block_number >= header.zkp.block_number)
+            {
+                proof_store.set_zkp(&header.zkp);
+            }
+        }
+        None => {
+            // Without a proof, the macro chain has to start at our genesis block.
+            let genesis_block = blockchain.read().chain_store.get_block_at(
+                Policy::genesis_block_number(),
+                false,
+                None,
+            )?;
+            if header.zkp_block.hash() != genesis_block.hash() {
+                return Err(SnapshotError::MissingProof(header.zkp_block.block_number()));
+            }
+        }
+    }
+
+    if let Some(previous_election_block) = header.previous_election_block {
+        Blockchain::update_previous_slots(blockchain.upgradable_read(), previous_election_block)?;
+    }
+
+    for macro_block in header
+        .election_blocks
+        .into_iter()
+        .chain(header.checkpoint_block)
+    {
+        Blockchain::push_macro(blockchain.upgradable_read(), macro_block)?;
+    }
+
+    Ok(())
+}
+
+/// Opens the database and the blockchain of the node described by `config`.
+fn open_blockchain(config: &ClientConfig) -> Result<(MdbxDatabase, Blockchain), Error> {
+    match config.consensus.sync_mode {
+        SyncMode::History | SyncMode::Full => {}
+        SyncMode::Light => {
+            return Err(Error::Snapshot(SnapshotError::UnsupportedSyncMode(
+                config.consensus.sync_mode,
+            )))
+        }
+    }
+
+    ZKP_VERIFYING_DATA.init_with_network_id(config.network_id);
+
+    let environment = config.storage.database(
+        config.network_id,
+        config.consensus.sync_mode,
+        config.database.clone(),
+    )?;
+    let blockchain_config = BlockchainConfig {
+        max_epochs_stored: config.consensus.max_epochs_stored,
+        keep_history: config.consensus.sync_mode == SyncMode::History,
+        index_history: config.consensus.sync_mode == SyncMode::History
+            && config.consensus.index_history,
+        ..Default::default()
+    };
+    let blockchain = Blockchain::new(
+        environment.clone(),
+        blockchain_config,
+        config.network_id,
+        Arc::new(OffsetTime::new()),
+    )
+    .map_err(|error| Error::Consensus(nimiq_consensus::Error::BlockchainError(error)))?;
+
+    Ok((environment, blockchain))
+}
+
+/// Exports a snapshot of the local state of the node described by `config` into the file at
+/// `path`. The node must not be running.
+pub fn export_snapshot_to_file(config: &ClientConfig, path: &Path) -> Result<SnapshotInfo, Error> {
+    let (environment, blockchain) = open_blockchain(config)?;
+    let proof_store = DBProofStore::new(environment);
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    Ok(export_snapshot(&blockchain, &proof_store, &mut writer)?)
+}
+
+/// Imports the snapshot in the file at `path` into the node described by `config`. Only full
+/// nodes can be bootstrapped from a snapshot. The node must not be running.
+pub fn import_snapshot_from_file(
+    config: &ClientConfig,
+    path: &Path,
+) -> Result<SnapshotInfo, Error> {
+    if config.consensus.sync_mode != SyncMode::Full {
+        return Err(Error::Snapshot(SnapshotError::UnsupportedSyncMode(
+            config.consensus.sync_mode,
+        )));
+    }
+
+    let (environment, blockchain) = open_blockchain(config)?;
+    let blockchain = Arc::new(RwLock::new(blockchain));
+    let proof_store = DBProofStore::new(environment);
+
+    let mut reader = BufReader::new(File::open(path)?);
+    Ok(import_snapshot(&blockchain, &proof_store, &mut reader)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use nimiq_blockchain::{BlockProducer, Blockchain, BlockchainConfig};
+    use nimiq_blockchain_interface::AbstractBlockchain;
+    use nimiq_database::mdbx::MdbxDatabase;
+    use nimiq_genesis::NetworkId;
+    use nimiq_primitives::policy::Policy;
+    use nimiq_test_log::test;
+    use nimiq_test_utils::blockchain::{produce_macro_blocks, signing_key, voting_key};
+    use nimiq_utils::time::OffsetTime;
+    use nimiq_zkp_component::proof_store::DBProofStore;
+    use parking_lot::RwLock;
+
+    use super::{
+        export_snapshot, import_snapshot, read_record, SnapshotError, SnapshotInfo, SNAPSHOT_MAGIC,
+        SNAPSHOT_VERSION,
+    };
+
+    fn blockchain() -> (MdbxDatabase, Arc<RwLock<Blockchain>>) {
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let blockchain = Blockchain::new(
+            env.clone(),
+            BlockchainConfig::default(),
+            NetworkId::UnitAlbatross,
+            Arc::new(OffsetTime::new()),
+        )
+        .unwrap();
+        (env, Arc::new(RwLock::new(blockchain)))
+    }
+
+    fn snapshot() -> (Vec<u8>, SnapshotInfo) {
+        let (env, blockchain) = blockchain();
+        let producer = BlockProducer::new(signing_key(), voting_key());
+        produce_macro_blocks(
+            &producer,
+            &blockchain,
+            Policy::batches_per_epoch() as usize + 1,
+        );
+
+        let mut snapshot = Vec::new();
+        let info =
+            export_snapshot(&blockchain.read(), &DBProofStore::new(env), &mut snapshot).unwrap();
+        (snapshot, info)
+    }
+
+    #[test]
+    fn interrupted_import_can_be_resumed() {
+        let (snapshot, info) = snapshot();
+        let (env, blockchain) = blockchain();
+        let proof_store = DBProofStore::new(env);
+
+        // The import is interrupted before the accounts trie is complete.
+        let truncated = &snapshot[..snapshot.len() - 10];
+        let result = import_snapshot(&blockchain, &proof_store, &mut &truncated[..]);
+        assert!(matches!(result, Err(SnapshotError::Truncated)));
+
+        // Importing the snapshot again completes the import.
+        let imported = import_snapshot(&blockchain, &proof_store, &mut &snapshot[..]).unwrap();
+        assert_eq!(imported.block_hash, info.block_hash);
+        assert_eq!(imported.num_items, info.num_items);
+        assert_eq!(blockchain.read().head_hash(), info.block_hash);
+        assert!(blockchain.read().state.accounts.is_complete(None));
+
+        // A completed import isn't repeated.
+        let result = import_snapshot(&blockchain, &proof_store, &mut &snapshot[..]);
+        assert!(matches!(result, Err(SnapshotError::NotNewer { .. })));
+    }
+
+    #[test]
+    fn oversized_records_are_rejected() {
+        let mut snapshot = SNAPSHOT_MAGIC.to_vec();
+        snapshot.extend_from_slice(&SNAPSHOT_VERSION.to_be_bytes());
+        snapshot.extend_from_slice(&u32::MAX.to_be_bytes());
+
+        let (env, blockchain) = blockchain();
+        let result = import_snapshot(&blockchain, &DBProofStore::new(env), &mut &snapshot[..]);
+        assert!(
+            matches!(result, Err(SnapshotError::Io(error)) if error.kind() == std::io::ErrorKind::InvalidData)
+        );
+
+        // A length that exceeds the remaining data is detected as well.
+        let mut record = 100u32.to_be_bytes().to_vec();
+        record.extend_from_slice(&[0; 10]);
+        let result = read_record::<_, u32, SnapshotError>(&mut &record[..]);
+        assert!(
+            matches!(result, Err(SnapshotError::Io(error)) if error.kind() == std::io::ErrorKind::UnexpectedEof)
+        );
+    }
+}
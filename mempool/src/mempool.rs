@@ -31,7 +31,7 @@ use crate::{
     executor::MempoolExecutor,
     filter::{MempoolFilter, MempoolRules},
     mempool_state::{EvictionReason, MempoolState},
-    mempool_transactions::{MempoolTransactions, TxPriority},
+    mempool_transactions::{BlockInclusion, MempoolTransactions, TxPriority},
     verify::{verify_tx, VerifyErr},
 };
 
@@ -519,6 +519,42 @@ impl Mempool {
         (txs, size)
     }
 
+    /// Returns a vector with accepted transactions from the mempool, selected by the given inclusion
+    /// function. If the caller already holds a blockchain lock, it can be passed to this function
+    /// to prevent double-locking the blockchain.
+    ///
+    /// Returns the transactions ordered by their [`BlockInclusion`] and then by fee per byte up to
+    /// max_bytes and removes them from the mempool. Excluded transactions are kept in the mempool.
+    /// It also return the sum of the serialized size of the returned transactions.
+    pub fn get_transactions_for_block_by_locked<F>(
+        &self,
+        blockchain: &Blockchain,
+        max_bytes: usize,
+        inclusion: F,
+    ) -> (Vec<Transaction>, usize)
+    where
+        F: Fn(&Transaction) -> BlockInclusion,
+    {
+        let mut state = self.state.write();
+        let (txs, size) = Self::get_transactions_for_block_by_impl(
+            &state.regular_transactions,
+            max_bytes,
+            inclusion,
+        );
+
+        for tx in &txs {
+            state.remove(blockchain, &tx.hash(), EvictionReason::BlockBuilding);
+        }
+
+        debug!(
+            returned_txs = txs.len(),
+            remaining_txs = state.regular_transactions.len(),
+            "Returned regular transactions from mempool"
+        );
+
+        (txs, size)
+    }
+
     /// Returns a vector with accepted control transactions from the mempool.
     /// Note that this takes a read lock on blockchain.
     ///
@@ -597,6 +633,48 @@ impl Mempool {
         (txs, size)
     }
 
+    fn get_transactions_for_block_by_impl<F>(
+        transactions: &MempoolTransactions,
+        max_bytes: usize,
+        inclusion: F,
+    ) -> (Vec<Transaction>, usize)
+    where
+        F: Fn(&Transaction) -> BlockInclusion,
+    {
+        let mut candidates: Vec<_> = transactions
+            .best_transactions
+            .iter()
+            .filter_map(|(tx_hash, order)| {
+                let tx = transactions.get(tx_hash).unwrap();
+                match inclusion(tx) {
+                    BlockInclusion::Excluded => None,
+                    tx_inclusion => Some((tx_inclusion, order, tx)),
+                }
+            })
+            .collect();
+
+        // The best transactions are the greatest ones, so they are sorted in reverse.
+        candidates.sort_by(|(inclusion_a, order_a, _), (inclusion_b, order_b, _)| {
+            inclusion_a.cmp(inclusion_b).then(order_b.cmp(order_a))
+        });
+
+        let mut txs = vec![];
+        let mut size = 0_usize;
+
+        for (_, _, tx) in candidates {
+            // Same as above, we stop at the first transaction that doesn't fit in the block.
+            let next_size = size + 1 + tx.serialized_size();
+            if next_size > max_bytes {
+                break;
+            }
+            size = next_size;
+
+            txs.push(tx.clone());
+        }
+
+        (txs, size)
+    }
+
     /// Adds a transaction to the Mempool.
     pub async fn add_transaction(
         &self,
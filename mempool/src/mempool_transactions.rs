@@ -20,6 +20,20 @@ pub enum TxPriority {
     High = 3,
 }
 
+/// How a block producer includes a transaction of the mempool in its block.
+/// Transactions are ordered by their inclusion first and by [`BestTxOrder`] second.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum BlockInclusion {
+    /// Included before all other transactions
+    Prioritized,
+    /// Included in the order of the mempool, this is the default
+    Regular,
+    /// Included only after all other transactions
+    Deprioritized,
+    /// Not included in the block, but kept in the mempool
+    Excluded,
+}
+
 /// Ordering in which transactions removed from the mempool to be included in blocks.
 /// This is stored on a max-heap, so the greater transaction comes first.
 /// Compares by fee per byte (higher first), then by insertion order (lower i.e. older first).
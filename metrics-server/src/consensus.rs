@@ -1,4 +1,7 @@
-use nimiq_consensus::ConsensusProxy;
+use nimiq_consensus::{
+    sync::progress::{SyncCounter, SyncPhase, SyncStatus},
+    ConsensusProxy,
+};
 use nimiq_network_interface::network::Network;
 use prometheus_client::registry::Registry;
 
@@ -13,12 +16,122 @@ impl ConsensusMetrics {
     ) {
         let sub_registry = registry.sub_registry_with_prefix("consensus");
 
-        let closure =
-            NumericClosureMetric::new_gauge(Box::new(move || consensus.is_established() as i64));
+        let consensus_proxy = consensus.clone();
+        let closure = NumericClosureMetric::new_gauge(Box::new(move || {
+            consensus_proxy.is_established() as i64
+        }));
         sub_registry.register(
             "is_established",
             "Whether consensus is established",
             closure,
         );
+
+        Self::register_sync_status(
+            sub_registry,
+            &consensus,
+            "sync_phase",
+            "Current sync phase (0: waiting for peers, 1: macro sync, 2: state sync, 3: live sync, 4: synced)",
+            |status| Self::phase_number(status.phase),
+        );
+        Self::register_sync_counter(
+            sub_registry,
+            &consensus,
+            "sync_epochs",
+            "epochs",
+            |status| status.epochs,
+        );
+        Self::register_sync_counter(
+            sub_registry,
+            &consensus,
+            "sync_batches",
+            "batches",
+            |status| status.batches,
+        );
+        Self::register_sync_counter(
+            sub_registry,
+            &consensus,
+            "sync_chunks",
+            "accounts trie chunks",
+            |status| status.chunks,
+        );
+        Self::register_sync_counter(
+            sub_registry,
+            &consensus,
+            "sync_blocks",
+            "blocks",
+            |status| status.blocks,
+        );
+        Self::register_sync_status(
+            sub_registry,
+            &consensus,
+            "sync_bytes_downloaded",
+            "Number of bytes downloaded by the macro and state sync",
+            |status| status.bytes_downloaded as i64,
+        );
+        Self::register_sync_status(
+            sub_registry,
+            &consensus,
+            "sync_eta_seconds",
+            "Estimated number of seconds until the current sync phase is finished (-1 if unknown)",
+            |status| status.eta.map(|eta| eta.as_secs() as i64).unwrap_or(-1),
+        );
+        Self::register_sync_status(
+            sub_registry,
+            &consensus,
+            "sync_peers",
+            "Number of peers used in the current sync phase",
+            |status| status.peers.len() as i64,
+        );
+    }
+
+    fn register_sync_counter<TNetwork: Network>(
+        registry: &mut Registry,
+        consensus: &ConsensusProxy<TNetwork>,
+        name: &str,
+        items: &str,
+        counter: fn(&SyncStatus<TNetwork::PeerId>) -> SyncCounter,
+    ) {
+        Self::register_sync_status(
+            registry,
+            consensus,
+            &format!("{name}_processed"),
+            &format!("Number of {items} processed by the sync"),
+            move |status| counter(status).processed as i64,
+        );
+        Self::register_sync_status(
+            registry,
+            consensus,
+            &format!("{name}_target"),
+            &format!("Number of {items} the sync is targeting (-1 if unknown)"),
+            move |status| {
+                counter(status)
+                    .target
+                    .map(|target| target as i64)
+                    .unwrap_or(-1)
+            },
+        );
+    }
+
+    fn register_sync_status<TNetwork: Network>(
+        registry: &mut Registry,
+        consensus: &ConsensusProxy<TNetwork>,
+        name: &str,
+        help: &str,
+        value: impl Fn(&SyncStatus<TNetwork::PeerId>) -> i64 + Send + Sync + 'static,
+    ) {
+        let consensus = consensus.clone();
+        let closure =
+            NumericClosureMetric::new_gauge(Box::new(move || value(&consensus.sync_status())));
+        registry.register(name, help, closure);
+    }
+
+    fn phase_number(phase: SyncPhase) -> i64 {
+        match phase {
+            SyncPhase::WaitingForPeers => 0,
+            SyncPhase::MacroSync => 1,
+            SyncPhase::StateSync => 2,
+            SyncPhase::LiveSync => 3,
+            SyncPhase::Synced => 4,
+        }
     }
 }
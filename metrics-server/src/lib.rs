@@ -5,10 +5,11 @@ use nimiq_consensus::ConsensusProxy;
 use nimiq_mempool::mempool::Mempool;
 use nimiq_network_interface::network::Network;
 use nimiq_utils::spawn;
+use nimiq_validator::validator::ValidatorProxy;
 use parking_lot::RwLock;
 use prometheus_client::{
     encoding::{EncodeGaugeValue, EncodeMetric, MetricEncoder},
-    metrics::MetricType,
+    metrics::{counter::ConstCounter, MetricType},
     registry::Registry,
 };
 #[cfg(tokio_unstable)]
@@ -20,6 +21,7 @@ use crate::tokio_runtime::TokioRuntimeMetrics;
 use crate::{
     chain::BlockMetrics, consensus::ConsensusMetrics, mempool::MempoolMetrics,
     network::NetworkMetrics, server::metrics_server, tokio_task::TokioTaskMetrics,
+    validator::ValidatorMetrics,
 };
 
 mod chain;
@@ -30,6 +32,7 @@ mod server;
 #[cfg(tokio_unstable)]
 mod tokio_runtime;
 mod tokio_task;
+mod validator;
 
 #[derive(Clone)]
 pub struct NimiqTaskMonitor {
@@ -39,13 +42,13 @@ pub struct NimiqTaskMonitor {
 
 struct NumericClosureMetric<T: EncodeGaugeValue + Sized + Debug> {
     metric_type: MetricType,
-    lambda: Box<dyn Fn() -> T + Sync + Send>,
+    lambda: Box<dyn Fn() -> Option<T> + Sync + Send>,
 }
 
-impl<T: EncodeGaugeValue + Sized + Debug> NumericClosureMetric<T> {
+impl<T: EncodeGaugeValue + Sized + Debug + 'static> NumericClosureMetric<T> {
     fn new(
         metric_type: MetricType,
-        lambda: Box<dyn Fn() -> T + Sync + Send>,
+        lambda: Box<dyn Fn() -> Option<T> + Sync + Send>,
     ) -> NumericClosureMetric<T> {
         NumericClosureMetric {
             metric_type,
@@ -54,13 +57,22 @@ impl<T: EncodeGaugeValue + Sized + Debug> NumericClosureMetric<T> {
     }
 
     pub fn new_gauge(lambda: Box<dyn Fn() -> T + Sync + Send>) -> NumericClosureMetric<T> {
+        NumericClosureMetric::new(MetricType::Gauge, Box::new(move || Some(lambda())))
+    }
+
+    /// A gauge without a sample while its value is unknown.
+    pub fn new_optional_gauge(
+        lambda: Box<dyn Fn() -> Option<T> + Sync + Send>,
+    ) -> NumericClosureMetric<T> {
         NumericClosureMetric::new(MetricType::Gauge, lambda)
     }
 }
 
 impl<T: EncodeGaugeValue + Sized + Debug> EncodeMetric for NumericClosureMetric<T> {
     fn encode(&self, mut encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
-        encoder.encode_gauge(&(self.lambda)())?;
+        if let Some(value) = (self.lambda)() {
+            encoder.encode_gauge(&value)?;
+        }
 
         Ok(())
     }
@@ -79,10 +91,40 @@ impl<T: EncodeGaugeValue + Sized + Debug> Debug for NumericClosureMetric<T> {
     }
 }
 
+/// A counter whose value is read from a closure, e.g. from statistics kept elsewhere.
+struct CounterClosureMetric {
+    lambda: Box<dyn Fn() -> u64 + Sync + Send>,
+}
+
+impl CounterClosureMetric {
+    pub fn new(lambda: Box<dyn Fn() -> u64 + Sync + Send>) -> CounterClosureMetric {
+        CounterClosureMetric { lambda }
+    }
+}
+
+impl EncodeMetric for CounterClosureMetric {
+    fn encode(&self, encoder: MetricEncoder) -> Result<(), std::fmt::Error> {
+        ConstCounter::new((self.lambda)()).encode(encoder)
+    }
+
+    fn metric_type(&self) -> MetricType {
+        MetricType::Counter
+    }
+}
+
+impl Debug for CounterClosureMetric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CounterClosureMetric")
+            .field("value", &(self.lambda)())
+            .finish()
+    }
+}
+
 pub fn start_metrics_server<TNetwork: Network>(
     addr: SocketAddr,
     blockchain_proxy: BlockchainProxy,
     mempool: Option<Arc<Mempool>>,
+    validator: Option<ValidatorProxy>,
     consensus_proxy: ConsensusProxy<TNetwork>,
     network: Arc<nimiq_network_libp2p::Network>,
     task_monitors: &[NimiqTaskMonitor],
@@ -90,7 +132,7 @@ pub fn start_metrics_server<TNetwork: Network>(
     let mut registry = Registry::default();
     let nimiq_registry = registry.sub_registry_with_prefix("nimiq");
 
-    BlockMetrics::register(nimiq_registry, blockchain_proxy);
+    BlockMetrics::register(nimiq_registry, blockchain_proxy.clone());
     ConsensusMetrics::register(nimiq_registry, consensus_proxy);
     NetworkMetrics::register(nimiq_registry, network);
 
@@ -98,6 +140,10 @@ pub fn start_metrics_server<TNetwork: Network>(
         MempoolMetrics::register(nimiq_registry, mempool);
     }
 
+    if let Some(validator) = validator {
+        ValidatorMetrics::register(nimiq_registry, validator, blockchain_proxy);
+    }
+
     // Setup the task metrics
     let task_metrics = Arc::new(RwLock::new(TokioTaskMetrics::new()));
     task_metrics.write().register(
@@ -0,0 +1,241 @@
+use std::{sync::Arc, time::Duration};
+
+use nimiq_blockchain::Blockchain;
+use nimiq_blockchain_proxy::BlockchainProxy;
+use nimiq_handel::{
+    partitioner::{BinomialPartitioner, Partitioner},
+    telemetry::{Telemetry, TelemetryStats},
+};
+use nimiq_primitives::policy::Policy;
+use nimiq_validator::{
+    failover::FailoverRole,
+    performance::{PerformanceTracker, ValidatorPerformance},
+    validator::ValidatorProxy,
+};
+use parking_lot::{Mutex, RwLock};
+use prometheus_client::registry::Registry;
+
+use crate::{CounterClosureMetric, NumericClosureMetric};
+
+pub struct ValidatorMetrics {}
+
+impl ValidatorMetrics {
+    pub fn register(
+        registry: &mut Registry,
+        validator: ValidatorProxy,
+        blockchain_proxy: BlockchainProxy,
+    ) {
+        let sub_registry = registry.sub_registry_with_prefix("validator");
+
+        if let BlockchainProxy::Full(blockchain) = blockchain_proxy {
+            Self::register_performance(sub_registry, &validator, blockchain);
+        }
+
+        Self::register_aggregation(
+            sub_registry,
+            "tendermint",
+            &validator.aggregation_telemetry.tendermint,
+        );
+        Self::register_aggregation(
+            sub_registry,
+            "skip_block",
+            &validator.aggregation_telemetry.skip_block,
+        );
+
+        // The failover metrics are only meaningful if failover is enabled.
+        if validator.failover_status.read().is_some() {
+            let failover_status = Arc::clone(&validator.failover_status);
+            let closure = NumericClosureMetric::new_gauge(Box::new(move || {
+                failover_status
+                    .read()
+                    .as_ref()
+                    .map(|status| (status.role == FailoverRole::Active) as i64)
+                    .unwrap_or_default()
+            }));
+            sub_registry.register(
+                "failover_active",
+                "Whether this host is the active host of the validator",
+                closure,
+            );
+
+            let failover_status = Arc::clone(&validator.failover_status);
+            let closure = NumericClosureMetric::new_gauge(Box::new(move || {
+                failover_status
+                    .read()
+                    .as_ref()
+                    .map(|status| status.transitions as i64)
+                    .unwrap_or_default()
+            }));
+            sub_registry.register(
+                "failover_transitions",
+                "Number of active/standby transitions since the validator was started",
+                closure,
+            );
+        }
+    }
+
+    fn register_aggregation(
+        registry: &mut Registry,
+        aggregation: &'static str,
+        telemetry: &Arc<Telemetry>,
+    ) {
+        let registry = registry.sub_registry_with_label(("aggregation".into(), aggregation.into()));
+
+        let counter = |value: fn(&TelemetryStats) -> u64| {
+            let telemetry = Arc::clone(telemetry);
+            CounterClosureMetric::new(Box::new(move || value(&telemetry.stats())))
+        };
+        // The durations have no sample until they were observed.
+        let millis = |value: Box<dyn Fn(&TelemetryStats) -> Option<Duration> + Sync + Send>| {
+            let telemetry = Arc::clone(telemetry);
+            NumericClosureMetric::new_optional_gauge(Box::new(move || {
+                value(&telemetry.stats()).map(|duration| duration.as_millis() as i64)
+            }))
+        };
+
+        registry.register(
+            "aggregations",
+            "Number of Handel aggregations started",
+            counter(|stats| stats.aggregations),
+        );
+        registry.register(
+            "aggregation_thresholds_reached",
+            "Number of Handel aggregations that reached their threshold",
+            counter(|stats| stats.thresholds_reached),
+        );
+        registry.register(
+            "aggregation_time_to_threshold_ms",
+            "Moving average of the time until a Handel aggregation reached its threshold",
+            millis(Box::new(|stats| stats.time_to_threshold)),
+        );
+        registry.register(
+            "aggregation_level_timeout_ms",
+            "Level timeout used by the latest Handel aggregation",
+            millis(Box::new(|stats| stats.timeout)),
+        );
+
+        // Level 0 only contains our own contribution, so it is skipped.
+        let num_levels = BinomialPartitioner::new(0, Policy::SLOTS as usize).levels();
+        for level in 1..num_levels {
+            let registry =
+                registry.sub_registry_with_label(("level".into(), level.to_string().into()));
+            let telemetry = Arc::clone(telemetry);
+            registry.register(
+                "aggregation_level_completions",
+                "Number of Handel aggregations in which the level was completed",
+                CounterClosureMetric::new(Box::new(move || {
+                    telemetry
+                        .stats()
+                        .levels
+                        .get(level)
+                        .map(|level| level.completions)
+                        .unwrap_or_default()
+                })),
+            );
+            registry.register(
+                "aggregation_level_completion_time_ms",
+                "Moving average of the time from the activation of the level until it was completed",
+                millis(Box::new(move |stats| {
+                    stats.levels.get(level).and_then(|level| level.completion_time)
+                })),
+            );
+        }
+    }
+
+    fn register_performance(
+        registry: &mut Registry,
+        validator: &ValidatorProxy,
+        blockchain: Arc<RwLock<Blockchain>>,
+    ) {
+        let tracker = Arc::new(Mutex::new(PerformanceTracker::new(
+            validator.validator_address.read().clone(),
+        )));
+
+        // The metrics have no sample while the statistics can't be computed.
+        let mut register =
+            |name: &str, help: &str, value: fn(&ValidatorPerformance) -> Option<i64>| {
+                let tracker = Arc::clone(&tracker);
+                let blockchain = Arc::clone(&blockchain);
+                let closure = NumericClosureMetric::new_optional_gauge(Box::new(move || {
+                    // The statistics are cached per head block, so this is cheap for all but the
+                    // first metric of a scrape.
+                    tracker
+                        .lock()
+                        .update(&blockchain)
+                        .ok()
+                        .and_then(|performance| value(&performance))
+                }));
+                registry.register(name, help, closure);
+            };
+
+        register(
+            "epoch_slots",
+            "Number of slots owned in the current epoch",
+            |performance| Some(performance.num_slots as i64),
+        );
+        register(
+            "epoch_micro_blocks_expected",
+            "Number of micro blocks we were selected to produce in the current epoch",
+            |performance| {
+                let micro_blocks = performance.micro_blocks.as_ref()?;
+                Some(micro_blocks.expected as i64)
+            },
+        );
+        register(
+            "epoch_micro_blocks_produced",
+            "Number of micro blocks produced in the current epoch",
+            |performance| {
+                let micro_blocks = performance.micro_blocks.as_ref()?;
+                Some(micro_blocks.produced as i64)
+            },
+        );
+        register(
+            "epoch_skip_blocks_signed",
+            "Number of skip block proofs including our signature in the current epoch",
+            |performance| {
+                let micro_blocks = performance.micro_blocks.as_ref()?;
+                Some(micro_blocks.skip_blocks_signed as i64)
+            },
+        );
+        register(
+            "epoch_skip_blocks_caused",
+            "Number of skip blocks caused in the current epoch",
+            |performance| Some(performance.skip_blocks_caused as i64),
+        );
+        register(
+            "epoch_macro_blocks_signed",
+            "Number of macro blocks whose justification includes our precommit in the current epoch",
+            |performance| Some(performance.macro_blocks_signed as i64),
+        );
+        register(
+            "epoch_macro_slots_signed",
+            "Number of our slots included in macro block justifications in the current epoch",
+            |performance| Some(performance.macro_slots_signed as i64),
+        );
+        register(
+            "epoch_tendermint_rounds",
+            "Number of Tendermint rounds needed to decide the macro blocks of the current epoch",
+            |performance| Some(performance.tendermint_rounds as i64),
+        );
+        register(
+            "epoch_tendermint_rounds_participated",
+            "Number of deciding Tendermint rounds including our precommit in the current epoch",
+            |performance| Some(performance.tendermint_rounds_participated as i64),
+        );
+        register(
+            "epoch_penalized_slots",
+            "Number of our slots penalized in the current epoch",
+            |performance| Some(performance.penalized_slots.len() as i64),
+        );
+        register(
+            "epoch_jailed",
+            "Whether we were jailed in the current epoch",
+            |performance| Some(performance.jailed as i64),
+        );
+        register(
+            "epoch_rewards",
+            "Rewards earned in the current epoch in Luna",
+            |performance| Some(u64::from(performance.rewards) as i64),
+        );
+    }
+}
@@ -41,6 +41,14 @@ bitflags! {
 
         /// This node is configured as a validator, so it is interested for other validator nodes.
         const VALIDATOR = 1 << 7;
+
+        /// The node acts as a circuit relay for nodes that are not publicly reachable.
+        const RELAY = 1 << 8;
+
+        /// The node provides the full transaction history of recent blocks only. The range of blocks it provides
+        /// the history for can be requested from the node.
+        /// Nodes that have this flag set don't have [`Services::HISTORY`] set.
+        const PARTIAL_HISTORY = 1 << 9;
     }
 }
 
@@ -0,0 +1,229 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::SystemTime,
+};
+
+use libp2p::PeerId;
+use nimiq_bls::CompressedPublicKey;
+use nimiq_serde::Deserialize;
+use thiserror::Error;
+
+/// Errors that can occur while loading a peer allowlist.
+#[derive(Debug, Error)]
+pub enum AllowlistError {
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Invalid allowlist entry on line {line}: {entry}")]
+    InvalidEntry { line: usize, entry: String },
+}
+
+/// Allowlist of peers used to run a private (permissioned) network.
+///
+/// A peer is allowed if either its `PeerId` is explicitly listed or if it published a valid
+/// `ValidatorRecord` in the DHT that was signed by one of the listed validator keys.
+///
+/// Note that validator key entries are only resolved to a `PeerId` once a verified
+/// `ValidatorRecord` for that key has been received through the DHT. Until then, and always when
+/// discovery is disabled, a validator key entry does not allow any peer. Every node therefore
+/// needs to be able to reach at least one peer that is listed by its `PeerId` (e.g. the seed
+/// nodes) to learn about the validator records in the first place.
+///
+/// The allowlist file contains one entry per line. Each entry is either a base58 encoded `PeerId`
+/// or a hex encoded compressed BLS public key of a validator. Empty lines and lines starting with
+/// `#` are ignored.
+#[derive(Clone, Debug, Default)]
+pub struct PeerAllowlist {
+    /// Explicitly allowed peer IDs.
+    peer_ids: HashSet<PeerId>,
+    /// Allowed validator keys.
+    validator_keys: HashSet<CompressedPublicKey>,
+    /// Peer IDs learned from verified validator records for allowed validator keys.
+    validator_peers: HashMap<CompressedPublicKey, PeerId>,
+    /// The file this allowlist was loaded from, if any.
+    path: Option<PathBuf>,
+    /// Modification time of the file when it was last loaded.
+    modified: Option<SystemTime>,
+}
+
+impl PeerAllowlist {
+    /// Creates an allowlist from the given peer IDs and validator keys.
+    pub fn new<P, K>(peer_ids: P, validator_keys: K) -> Self
+    where
+        P: IntoIterator<Item = PeerId>,
+        K: IntoIterator<Item = CompressedPublicKey>,
+    {
+        Self {
+            peer_ids: peer_ids.into_iter().collect(),
+            validator_keys: validator_keys.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Loads an allowlist from a file. The allowlist remembers the file such that it can be
+    /// reloaded later on using `reload_if_changed`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, AllowlistError> {
+        let path = path.as_ref();
+        let modified = fs::metadata(path)?.modified().ok();
+        let mut allowlist = Self::from_str(&fs::read_to_string(path)?)?;
+        allowlist.path = Some(path.to_path_buf());
+        allowlist.modified = modified;
+        Ok(allowlist)
+    }
+
+    /// Reloads the allowlist from its file if the file was modified since it was last loaded.
+    /// Returns `true` if the allowlist was reloaded.
+    ///
+    /// Peers learned from validator records are kept as long as their validator key is still
+    /// part of the allowlist.
+    pub fn reload_if_changed(&mut self) -> Result<bool, AllowlistError> {
+        let path = match &self.path {
+            Some(path) => path.clone(),
+            None => return Ok(false),
+        };
+
+        let modified = fs::metadata(&path)?.modified().ok();
+        if modified.is_some() && modified == self.modified {
+            return Ok(false);
+        }
+
+        let mut allowlist = Self::from_file(&path)?;
+        allowlist.validator_peers = std::mem::take(&mut self.validator_peers)
+            .into_iter()
+            .filter(|(key, _)| allowlist.validator_keys.contains(key))
+            .collect();
+        *self = allowlist;
+
+        Ok(true)
+    }
+
+    /// Returns whether a peer is allowed to connect to us and whether we are allowed to dial it.
+    pub fn is_allowed(&self, peer_id: &PeerId) -> bool {
+        self.peer_ids.contains(peer_id)
+            || self
+                .validator_peers
+                .values()
+                .any(|validator_peer_id| validator_peer_id == peer_id)
+    }
+
+    /// Returns whether the allowlist contains any validator key entries.
+    pub fn has_validator_keys(&self) -> bool {
+        !self.validator_keys.is_empty()
+    }
+
+    /// Returns whether a validator key is part of the allowlist.
+    pub fn is_validator_allowed(&self, validator_key: &CompressedPublicKey) -> bool {
+        self.validator_keys.contains(validator_key)
+    }
+
+    /// Associates a peer ID with an allowed validator key. This is called once a `ValidatorRecord`
+    /// signed by the validator key has been verified.
+    /// Returns `true` if the validator key is allowed and the peer ID was recorded.
+    pub fn add_validator_peer(
+        &mut self,
+        validator_key: CompressedPublicKey,
+        peer_id: PeerId,
+    ) -> bool {
+        if !self.validator_keys.contains(&validator_key) {
+            return false;
+        }
+        self.validator_peers.insert(validator_key, peer_id);
+        true
+    }
+}
+
+impl FromStr for PeerAllowlist {
+    type Err = AllowlistError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut allowlist = Self::default();
+
+        for (i, line) in s.lines().enumerate() {
+            let entry = line.trim();
+            if entry.is_empty() || entry.starts_with('#') {
+                continue;
+            }
+
+            if let Ok(peer_id) = PeerId::from_str(entry) {
+                allowlist.peer_ids.insert(peer_id);
+                continue;
+            }
+
+            let validator_key = hex::decode(entry)
+                .ok()
+                .and_then(|raw| CompressedPublicKey::deserialize_from_vec(&raw).ok())
+                .ok_or_else(|| AllowlistError::InvalidEntry {
+                    line: i + 1,
+                    entry: entry.to_string(),
+                })?;
+            allowlist.validator_keys.insert(validator_key);
+        }
+
+        Ok(allowlist)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use libp2p::PeerId;
+    use nimiq_bls::KeyPair;
+    use nimiq_test_log::test;
+    use nimiq_test_utils::test_rng::test_rng;
+    use nimiq_utils::key_rng::SecureGenerate;
+
+    use super::{AllowlistError, PeerAllowlist};
+
+    #[test]
+    fn parses_peer_ids_and_validator_keys() {
+        let peer_id = PeerId::random();
+        let other_peer_id = PeerId::random();
+        let validator_key = KeyPair::generate(&mut test_rng(false))
+            .public_key
+            .compress();
+
+        let allowlist = PeerAllowlist::from_str(&format!(
+            "# Consortium members\n{}\n\n  {}  \n",
+            peer_id,
+            validator_key.to_hex(),
+        ))
+        .unwrap();
+
+        assert!(allowlist.is_allowed(&peer_id));
+        assert!(!allowlist.is_allowed(&other_peer_id));
+        assert!(allowlist.is_validator_allowed(&validator_key));
+    }
+
+    #[test]
+    fn rejects_invalid_entries() {
+        let result = PeerAllowlist::from_str("not-a-peer-id");
+        assert!(matches!(
+            result,
+            Err(AllowlistError::InvalidEntry { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn validator_peers_are_allowed() {
+        let peer_id = PeerId::random();
+        let validator_key = KeyPair::generate(&mut test_rng(false))
+            .public_key
+            .compress();
+        let other_validator_key = KeyPair::generate(&mut test_rng(false))
+            .public_key
+            .compress();
+
+        let mut allowlist = PeerAllowlist::new([], [validator_key.clone()]);
+        assert!(!allowlist.is_allowed(&peer_id));
+
+        assert!(!allowlist.add_validator_peer(other_validator_key, peer_id));
+        assert!(!allowlist.is_allowed(&peer_id));
+
+        assert!(allowlist.add_validator_peer(validator_key, peer_id));
+        assert!(allowlist.is_allowed(&peer_id));
+    }
+}
@@ -1,10 +1,10 @@
 use std::{iter, sync::Arc};
 
 use libp2p::{
-    autonat, connection_limits, gossipsub,
+    autonat, connection_limits, dcutr, gossipsub,
     kad::{self, store::MemoryStore},
-    ping, request_response,
-    swarm::NetworkBehaviour,
+    ping, relay, request_response,
+    swarm::{behaviour::toggle::Toggle, NetworkBehaviour},
     Multiaddr, PeerId, StreamProtocol,
 };
 use parking_lot::RwLock;
@@ -12,8 +12,9 @@ use parking_lot::RwLock;
 use crate::{
     connection_pool,
     discovery::{self, peer_contacts::PeerContactBook},
-    dispatch::codecs::MessageCodec,
-    Config,
+    dispatch::codecs::{MessageCodec, RequestResponse},
+    traffic::TrafficStats,
+    Config, PeerAllowlist,
 };
 
 /// Maximum simultaneous libp2p connections per peer
@@ -35,30 +36,47 @@ pub struct Behaviour {
     pub dht: kad::Behaviour<MemoryStore>,
     pub gossipsub: gossipsub::Behaviour,
     pub autonat: autonat::Behaviour,
+    pub relay_client: relay::client::Behaviour,
+    pub relay: Toggle<relay::Behaviour>,
+    pub dcutr: dcutr::Behaviour,
     pub ping: ping::Behaviour,
-    pub request_response: request_response::Behaviour<MessageCodec>,
+    pub request_response: RequestResponse,
 }
 
 impl Behaviour {
     pub fn new(
         config: Config,
         contacts: Arc<RwLock<PeerContactBook>>,
+        allowlist: Option<Arc<RwLock<PeerAllowlist>>>,
+        relay_client: relay::client::Behaviour,
         peer_score_params: gossipsub::PeerScoreParams,
         force_dht_server_mode: bool,
+        traffic: Arc<TrafficStats>,
     ) -> Self {
         let public_key = config.keypair.public();
         let peer_id = public_key.to_peer_id();
 
         // DHT behaviour
+        // If discovery is disabled, peers are never added to the routing table, such that the DHT
+        // stays inactive.
+        let mut kademlia = config.kademlia;
+        if config.disable_discovery {
+            kademlia.set_kbucket_inserts(kad::BucketInserts::Manual);
+        }
         let store = MemoryStore::new(peer_id);
-        let mut dht = kad::Behaviour::with_config(peer_id, store, config.kademlia);
+        let mut dht = kad::Behaviour::with_config(peer_id, store, kademlia);
         if force_dht_server_mode {
             dht.set_mode(Some(kad::Mode::Server));
+        } else if config.disable_discovery {
+            dht.set_mode(Some(kad::Mode::Client));
         }
 
         // Discovery behaviour
+        let mut discovery_config = config.discovery.clone();
+        discovery_config.peer_exchange = !config.disable_discovery;
+        discovery_config.allowlist = allowlist.clone();
         let discovery = discovery::Behaviour::new(
-            config.discovery.clone(),
+            discovery_config,
             config.keypair.clone(),
             Arc::clone(&contacts),
         );
@@ -82,6 +100,7 @@ impl Behaviour {
         // Connection pool behaviour
         let pool = connection_pool::Behaviour::new(
             Arc::clone(&contacts),
+            allowlist,
             peer_id,
             config.seeds,
             config.discovery.required_services,
@@ -91,7 +110,13 @@ impl Behaviour {
         // Request Response behaviour
         let protocol = StreamProtocol::new("/nimiq/reqres/0.0.1");
         let req_res_config = request_response::Config::default();
-        let request_response = request_response::Behaviour::new(
+        let codec = if traffic.has_bandwidth_limit() {
+            MessageCodec::with_bandwidth_limit(traffic)
+        } else {
+            MessageCodec::default()
+        };
+        let request_response = RequestResponse::new(
+            codec,
             iter::once((protocol, request_response::ProtocolSupport::Full)),
             req_res_config,
         );
@@ -103,6 +128,16 @@ impl Behaviour {
         }
         let autonat = autonat::Behaviour::new(peer_id, autonat_config);
 
+        // Relay behaviour:
+        // Only act as a relay for other peers if configured to do so.
+        let relay = config
+            .relay_server
+            .then(|| relay::Behaviour::new(peer_id, relay::Config::default()))
+            .into();
+
+        // Direct connection upgrade through relay (hole punching) behaviour
+        let dcutr = dcutr::Behaviour::new(peer_id);
+
         // Connection limits behaviour
         let limits = connection_limits::ConnectionLimits::default()
             .with_max_pending_incoming(Some(16))
@@ -120,6 +155,9 @@ impl Behaviour {
             pool,
             request_response,
             autonat,
+            relay_client,
+            relay,
+            dcutr,
             connection_limits,
         }
     }
@@ -7,7 +7,7 @@ use sha2::{Digest, Sha256};
 
 use crate::{
     discovery::{self, peer_contacts::PeerContact},
-    DHT_PROTOCOL,
+    PeerAllowlist, DHT_PROTOCOL,
 };
 
 /// TLS settings for configuring a secure WebSocket
@@ -35,6 +35,20 @@ pub struct Config {
     pub only_secure_ws_connections: bool,
     pub allow_loopback_addresses: bool,
     pub dht_quorum: NonZeroU8,
+    /// Optional allowlist of peers. If set, we only accept connections from and dial peers that
+    /// are part of the allowlist. Validator key entries only take effect once the validator's
+    /// record was received through the DHT, so they have no effect if discovery is disabled.
+    pub allowlist: Option<PeerAllowlist>,
+    /// Disables the DHT and the exchange of peer contacts. Peers are then only found through the
+    /// configured seeds.
+    pub disable_discovery: bool,
+    /// Optional maximum number of bytes per second a peer may cause us to transfer through its
+    /// requests (i.e. its requests and our responses). Requests of peers exceeding this limit are
+    /// rejected.
+    pub peer_bandwidth_limit: Option<u64>,
+    /// Act as a circuit relay for peers that are not publicly reachable. This should only be
+    /// enabled on publicly reachable nodes.
+    pub relay_server: bool,
 }
 
 impl Config {
@@ -100,6 +114,10 @@ impl Config {
             only_secure_ws_connections,
             allow_loopback_addresses,
             dht_quorum,
+            allowlist: None,
+            disable_discovery: false,
+            peer_bandwidth_limit: None,
+            relay_server: false,
         }
     }
 }
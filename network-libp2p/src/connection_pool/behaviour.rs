@@ -28,7 +28,7 @@ use rand::{seq::IteratorRandom, thread_rng};
 use void::Void;
 
 use super::Error;
-use crate::discovery::peer_contacts::PeerContactBook;
+use crate::{discovery::peer_contacts::PeerContactBook, PeerAllowlist};
 
 /// Current state of connections and peers for connection limits
 #[derive(Clone, Debug)]
@@ -341,6 +341,10 @@ pub struct Behaviour {
     /// services of each of the peers.
     pub contacts: Arc<RwLock<PeerContactBook>>,
 
+    /// Optional allowlist of peers. If set, only peers that are part of the
+    /// allowlist are dialed and accepted.
+    allowlist: Option<Arc<RwLock<PeerAllowlist>>>,
+
     /// Local (own) peer ID
     own_peer_id: PeerId,
 
@@ -378,6 +382,7 @@ pub struct Behaviour {
 impl Behaviour {
     pub fn new(
         contacts: Arc<RwLock<PeerContactBook>>,
+        allowlist: Option<Arc<RwLock<PeerAllowlist>>>,
         own_peer_id: PeerId,
         seeds: Vec<Multiaddr>,
         required_services: Services,
@@ -396,6 +401,7 @@ impl Behaviour {
 
         Self {
             contacts,
+            allowlist,
             own_peer_id,
             seeds,
             required_services,
@@ -422,6 +428,14 @@ impl Behaviour {
         }
     }
 
+    /// Returns whether a peer is allowed according to the allowlist. If no
+    /// allowlist is configured, every peer is allowed.
+    fn is_allowed(&self, peer_id: &PeerId) -> bool {
+        self.allowlist
+            .as_ref()
+            .map_or(true, |allowlist| allowlist.read().is_allowed(peer_id))
+    }
+
     fn get_ip_info_from_multiaddr(&self, address: &Multiaddr) -> Option<IpInfo> {
         // Get IP from multiaddress if it exists.
         match address.iter().next() {
@@ -543,6 +557,7 @@ impl Behaviour {
                 let peer_id = contact.peer_id();
                 if peer_id != own_peer_id
                     && self.peer_ids.can_dial(peer_id)
+                    && self.is_allowed(peer_id)
                     && contact.addresses().count() > 0
                 {
                     Some(*peer_id)
@@ -571,6 +586,7 @@ impl Behaviour {
                 let peer_id = contact.peer_id();
                 if peer_id != own_peer_id
                     && self.peer_ids.can_dial(peer_id)
+                    && self.is_allowed(peer_id)
                     && contact.addresses().count() > 0
                 {
                     Some(*peer_id)
@@ -614,6 +630,26 @@ impl Behaviour {
         }
         drop(contacts);
 
+        // Reload the allowlist if it changed and disconnect peers that are no longer allowed.
+        if let Some(allowlist) = &self.allowlist {
+            match allowlist.write().reload_if_changed() {
+                Ok(true) => info!("Reloaded peer allowlist"),
+                Ok(false) => {}
+                Err(error) => warn!(%error, "Failed to reload peer allowlist"),
+            }
+            let disallowed_peers: Vec<PeerId> = self
+                .peer_ids
+                .connected
+                .keys()
+                .filter(|peer_id| !self.is_allowed(peer_id))
+                .copied()
+                .collect();
+            for peer_id in disallowed_peers {
+                info!(%peer_id, "Disconnecting peer that is no longer allowed");
+                self.close_connection(peer_id, CloseReason::Other);
+            }
+        }
+
         self.peer_ids.housekeeping();
         self.addresses.housekeeping();
 
@@ -956,17 +992,29 @@ impl NetworkBehaviour for Behaviour {
             return Err(ConnectionDenied::new(Error::BannedPeer));
         }
 
+        if !self.is_allowed(&peer) {
+            debug!(peer_id=%peer, "Peer is not part of the allowlist");
+            return Err(ConnectionDenied::new(Error::PeerNotAllowed));
+        }
+
         Ok(dummy::ConnectionHandler)
     }
 
     fn handle_established_outbound_connection(
         &mut self,
         _connection_id: ConnectionId,
-        _peer: PeerId,
+        peer: PeerId,
         _addr: &Multiaddr,
         _role_override: Endpoint,
         _port_use: PortUse,
     ) -> Result<THandler<Self>, ConnectionDenied> {
+        // Seeds are dialed without knowing their peer ID, thus we can only check
+        // the allowlist once the connection is established.
+        if !self.is_allowed(&peer) {
+            debug!(peer_id=%peer, "Peer is not part of the allowlist");
+            return Err(ConnectionDenied::new(Error::PeerNotAllowed));
+        }
+
         Ok(dummy::ConnectionHandler)
     }
 
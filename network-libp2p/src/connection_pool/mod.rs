@@ -24,4 +24,8 @@ pub enum Error {
     ///Maximum peers connections per IP has been reached
     #[error("Maximum peers connections per IP has been reached")]
     MaxPeerPerIPConnectionsReached,
+
+    /// Peer is not part of the allowlist
+    #[error("Peer is not part of the allowlist")]
+    PeerNotAllowed,
 }
@@ -24,6 +24,7 @@ use super::{
     handler::{Handler, HandlerOutEvent},
     peer_contacts::{PeerContact, PeerContactBook},
 };
+use crate::PeerAllowlist;
 
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -54,6 +55,14 @@ pub struct Config {
 
     /// Only secure websocket connections
     pub only_secure_ws_connections: bool,
+
+    /// Whether to exchange peer contacts with other peers. If disabled, we neither send our known
+    /// peer contacts to other peers nor learn new peer contacts from them.
+    pub peer_exchange: bool,
+
+    /// Optional allowlist of peers. Peers that are not part of it fail the handshake and their
+    /// peer contacts are not added to the peer contact book.
+    pub allowlist: Option<Arc<RwLock<PeerAllowlist>>>,
 }
 
 impl Config {
@@ -72,6 +81,8 @@ impl Config {
             house_keeping_interval: Duration::from_secs(60),
             keep_alive: true,
             only_secure_ws_connections,
+            peer_exchange: true,
+            allowlist: None,
         }
     }
 }
@@ -149,6 +160,18 @@ impl Behaviour {
             .add_own_addresses(addresses, &self.keypair)
     }
 
+    /// Removes addresses from our own contact within the peer contact book
+    pub fn remove_own_addresses(&self, addresses: Vec<Multiaddr>) {
+        self.peer_contact_book
+            .write()
+            .remove_own_addresses(addresses, &self.keypair)
+    }
+
+    /// Returns the dialable addresses of a peer known from its peer contact
+    pub fn get_peer_addresses(&self, peer_id: &PeerId) -> Option<Vec<Multiaddr>> {
+        self.peer_contact_book.read().get_addresses(peer_id)
+    }
+
     /// Returns whether an address in `Multiaddr` format is a dialable websocket address
     pub fn is_address_dialable(&self, address: &Multiaddr) -> bool {
         self.peer_contact_book.read().is_address_dialable(address)
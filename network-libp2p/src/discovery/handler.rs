@@ -87,6 +87,9 @@ pub enum Error {
 
     #[error("Received update with too many peer contacts: {num_peer_contacts}")]
     UpdateLimitExceeded { num_peer_contacts: usize },
+
+    #[error("Peer is not part of the allowlist: {peer_id}")]
+    PeerNotAllowed { peer_id: PeerId },
 }
 
 impl Error {
@@ -215,6 +218,10 @@ impl Handler {
         peer_contact_book: &PeerContactBook,
         limit: usize,
     ) -> Vec<SignedPeerContact> {
+        if !self.config.peer_exchange {
+            return vec![];
+        }
+
         let mut rng = thread_rng();
 
         peer_contact_book
@@ -225,6 +232,31 @@ impl Handler {
             .collect()
     }
 
+    /// Returns whether the peer is allowed according to our allowlist. If no allowlist is
+    /// configured every peer is allowed.
+    fn is_allowed(&self, peer_id: &PeerId) -> bool {
+        self.config
+            .allowlist
+            .as_ref()
+            .map_or(true, |allowlist| allowlist.read().is_allowed(peer_id))
+    }
+
+    /// Filters the peer contacts received from the other peer. If peer exchange is disabled, all
+    /// contacts are dropped. Otherwise only the contacts of allowed peers are kept.
+    fn filter_received_contacts(
+        &self,
+        peer_contacts: Vec<SignedPeerContact>,
+    ) -> Vec<SignedPeerContact> {
+        if !self.config.peer_exchange {
+            return vec![];
+        }
+
+        peer_contacts
+            .into_iter()
+            .filter(|peer_contact| self.is_allowed(&peer_contact.peer_id()))
+            .collect()
+    }
+
     /// Checks if the handler is ready to start the discovery protocol.
     /// This basically checks that:
     /// - Both inbound and outbound are available
@@ -517,6 +549,17 @@ impl ConnectionHandler for Handler {
                                         );
                                     }
 
+                                    // Check that the peer is part of our allowlist
+                                    if !self.is_allowed(&self.peer_id) {
+                                        return Poll::Ready(
+                                            ConnectionHandlerEvent::NotifyBehaviour(
+                                                HandlerOutEvent::Error(Error::PeerNotAllowed {
+                                                    peer_id: self.peer_id,
+                                                }),
+                                            ),
+                                        );
+                                    }
+
                                     // Check and verify the peer contacts received
                                     if peer_contacts.len() > self.config.update_limit as usize {
                                         return Poll::Ready(
@@ -543,6 +586,8 @@ impl ConnectionHandler for Handler {
                                         }
                                     }
 
+                                    let peer_contacts =
+                                        self.filter_received_contacts(peer_contacts);
                                     let mut peer_contact_book = self.peer_contact_book.write();
 
                                     // Insert the peer into the peer contact book.
@@ -655,6 +700,8 @@ impl ConnectionHandler for Handler {
                                     }
 
                                     // Insert the new peer contacts into the peer contact book.
+                                    let peer_contacts =
+                                        self.filter_received_contacts(peer_contacts);
                                     self.peer_contact_book.write().insert_all_filtered(
                                         peer_contacts,
                                         self.config.required_services,
@@ -569,6 +569,20 @@ impl PeerContactBook {
         if self.memory_transport {
             return true;
         }
+        // Relayed addresses are dialable if the address of the relay is dialable
+        if address
+            .iter()
+            .any(|protocol| matches!(protocol, Protocol::P2pCircuit))
+        {
+            return self.is_relayed_address_dialable(address);
+        }
+        // QUIC addresses are only dialable if we support the QUIC transport
+        if address
+            .iter()
+            .any(|protocol| matches!(protocol, Protocol::QuicV1))
+        {
+            return cfg!(feature = "tokio-quic") && self.is_quic_address_dialable(address);
+        }
         // Otherwise check for an appropriate WS address
         let mut protocols = address.iter();
         let mut ip = protocols.next();
@@ -618,6 +632,56 @@ impl PeerContactBook {
             }
         }
     }
+
+    /// Returns true if an address is a valid relayed address, i.e. a dialable address of a relay
+    /// including its `P2p` protocol, followed by `/p2p-circuit` and an optional trailing `P2p`
+    /// protocol. Only a single relay hop is supported.
+    fn is_relayed_address_dialable(&self, address: &Multiaddr) -> bool {
+        let mut protocols = address.iter();
+        let relay_address: Multiaddr = protocols
+            .by_ref()
+            .take_while(|protocol| !matches!(protocol, Protocol::P2pCircuit))
+            .collect();
+
+        matches!(relay_address.iter().last(), Some(Protocol::P2p(_)))
+            && matches!(
+                (protocols.next(), protocols.next()),
+                (None, None) | (Some(Protocol::P2p(_)), None)
+            )
+            && self.is_address_dialable(&relay_address)
+    }
+
+    /// Returns true if an address is a valid QUIC address, i.e. an IP or DNS address followed by
+    /// `/udp/<port>/quic-v1` and an optional trailing `P2p` protocol.
+    fn is_quic_address_dialable(&self, address: &Multiaddr) -> bool {
+        let mut protocols = address.iter();
+        match protocols.next() {
+            Some(Protocol::Ip4(ip)) => {
+                if !self.allow_loopback_addresses && ip.is_loopback() {
+                    return false;
+                }
+            }
+            Some(Protocol::Ip6(ip)) => {
+                if !self.allow_loopback_addresses && ip.is_loopback() {
+                    return false;
+                }
+            }
+            Some(Protocol::Dns(_))
+            | Some(Protocol::Dns4(_))
+            | Some(Protocol::Dns6(_))
+            | Some(Protocol::Dnsaddr(_)) => {}
+            _ => return false,
+        }
+
+        matches!(
+            (protocols.next(), protocols.next(), protocols.next()),
+            (
+                Some(Protocol::Udp(_)),
+                Some(Protocol::QuicV1),
+                None | Some(Protocol::P2p(_))
+            )
+        ) && protocols.next().is_none()
+    }
 }
 
 mod serde_public_key {
@@ -5,12 +5,30 @@
 //!
 //! Note that this doesn't actually serialize/deserialize the message content, but
 //! only handles reading/writing the message.
+//!
+//! If a per-peer bandwidth limit is configured, the codec also enforces it: requests of
+//! peers that exceeded their limit are refused before they are dispatched.
 
-use std::{io, mem};
+use std::{
+    io, mem,
+    sync::Arc,
+    task::{Context, Poll},
+};
 
 use futures::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
-use libp2p::{request_response, StreamProtocol};
+use libp2p::{
+    core::{transport::PortUse, Endpoint},
+    request_response,
+    swarm::{
+        ConnectionDenied, ConnectionId, FromSwarm, NetworkBehaviour, THandler, THandlerInEvent,
+        THandlerOutEvent, ToSwarm,
+    },
+    Multiaddr, PeerId, StreamProtocol,
+};
 use nimiq_network_interface::network;
+use parking_lot::Mutex;
+
+use crate::traffic::TrafficStats;
 
 /// Size of a u64
 #[allow(unused_qualifications)] // Remove with a MSVR >= 1.80
@@ -18,8 +36,80 @@ const U64_LENGTH: usize = mem::size_of::<u64>();
 const MAX_REQUEST_SIZE: u64 = network::MIN_SUPPORTED_REQ_SIZE as u64 + U64_LENGTH as u64;
 const MAX_RESPONSE_SIZE: u64 = network::MIN_SUPPORTED_RESP_SIZE as u64 + U64_LENGTH as u64;
 
-#[derive(Default, Debug, Clone)]
-pub struct MessageCodec;
+/// Shared state used to enforce the per-peer bandwidth limit.
+#[derive(Clone)]
+struct BandwidthLimit {
+    traffic: Arc<TrafficStats>,
+    /// The peer of the connection that is currently being established. The request-response
+    /// behaviour clones its codec for every new connection, which binds the clone to this peer.
+    connecting_peer: Arc<Mutex<Option<PeerId>>>,
+}
+
+#[derive(Default)]
+pub struct MessageCodec {
+    /// The peer this codec is used for. This is `None` for the codec held by the behaviour.
+    peer_id: Option<PeerId>,
+    bandwidth_limit: Option<BandwidthLimit>,
+}
+
+impl MessageCodec {
+    /// Creates a codec that enforces the bandwidth limit of the given traffic stats. It needs to
+    /// be used through a `RequestResponse` behaviour which binds the codec to the connected peers.
+    pub(crate) fn with_bandwidth_limit(traffic: Arc<TrafficStats>) -> Self {
+        Self {
+            peer_id: None,
+            bandwidth_limit: Some(BandwidthLimit {
+                traffic,
+                connecting_peer: Default::default(),
+            }),
+        }
+    }
+
+    /// Returns an error if the peer of this codec exceeded its bandwidth limit.
+    fn check_bandwidth_limit(&self) -> io::Result<()> {
+        if let (Some(peer_id), Some(limit)) = (&self.peer_id, &self.bandwidth_limit) {
+            if limit.traffic.exceeds_bandwidth_limit(peer_id) {
+                return Err(io::Error::new(
+                    io::ErrorKind::Other,
+                    format!("Peer {peer_id} exceeded its bandwidth limit"),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Accounts bytes served to the peer of this codec towards its bandwidth limit.
+    fn note_served_bytes(&self, bytes: usize) {
+        if let (Some(peer_id), Some(limit)) = (&self.peer_id, &self.bandwidth_limit) {
+            limit.traffic.note_served_bytes(*peer_id, bytes);
+        }
+    }
+}
+
+impl Clone for MessageCodec {
+    fn clone(&self) -> Self {
+        // The behaviour clones its codec for every new connection, so bind these clones to the
+        // peer of the connection that is being established.
+        let peer_id = self.peer_id.or_else(|| {
+            self.bandwidth_limit
+                .as_ref()
+                .and_then(|limit| *limit.connecting_peer.lock())
+        });
+        Self {
+            peer_id,
+            bandwidth_limit: self.bandwidth_limit.clone(),
+        }
+    }
+}
+
+impl std::fmt::Debug for MessageCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MessageCodec")
+            .field("peer_id", &self.peer_id)
+            .field("bandwidth_limit", &self.bandwidth_limit.is_some())
+            .finish()
+    }
+}
 
 pub type IncomingRequest = Vec<u8>;
 pub type OutgoingResponse = Vec<u8>;
@@ -34,8 +124,11 @@ impl request_response::Codec for MessageCodec {
     where
         T: AsyncRead + Unpin + Send,
     {
+        self.check_bandwidth_limit()?;
+
         let mut vec = Vec::new();
         io.take(MAX_REQUEST_SIZE).read_to_end(&mut vec).await?;
+        self.note_served_bytes(vec.len());
         if vec.len() < U64_LENGTH {
             return Ok(None);
         }
@@ -121,8 +214,208 @@ impl request_response::Codec for MessageCodec {
         T: AsyncWrite + Unpin + Send,
     {
         let src = res.expect("No data to write");
+        self.note_served_bytes(U64_LENGTH + src.len());
         io.write_all(&(src.len() as u64).to_be_bytes()).await?;
         io.write_all(&src).await?;
         Ok(())
     }
 }
+
+/// Request-response behaviour that binds the `MessageCodec` of every connection to its peer,
+/// such that the codec can enforce the per-peer bandwidth limit.
+pub struct RequestResponse {
+    inner: request_response::Behaviour<MessageCodec>,
+    connecting_peer: Option<Arc<Mutex<Option<PeerId>>>>,
+}
+
+impl RequestResponse {
+    pub fn new<I>(codec: MessageCodec, protocols: I, config: request_response::Config) -> Self
+    where
+        I: IntoIterator<Item = (StreamProtocol, request_response::ProtocolSupport)>,
+    {
+        let connecting_peer = codec
+            .bandwidth_limit
+            .as_ref()
+            .map(|limit| Arc::clone(&limit.connecting_peer));
+        Self {
+            inner: request_response::Behaviour::with_codec(codec, protocols, config),
+            connecting_peer,
+        }
+    }
+
+    /// Runs `f` while the codec clones created by the inner behaviour are bound to `peer_id`.
+    fn with_connecting_peer<R>(&mut self, peer_id: PeerId, f: impl FnOnce(&mut Self) -> R) -> R {
+        if let Some(connecting_peer) = &self.connecting_peer {
+            *connecting_peer.lock() = Some(peer_id);
+        }
+        let result = f(self);
+        if let Some(connecting_peer) = &self.connecting_peer {
+            *connecting_peer.lock() = None;
+        }
+        result
+    }
+}
+
+impl std::ops::Deref for RequestResponse {
+    type Target = request_response::Behaviour<MessageCodec>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl std::ops::DerefMut for RequestResponse {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl NetworkBehaviour for RequestResponse {
+    type ConnectionHandler =
+        <request_response::Behaviour<MessageCodec> as NetworkBehaviour>::ConnectionHandler;
+    type ToSwarm = <request_response::Behaviour<MessageCodec> as NetworkBehaviour>::ToSwarm;
+
+    fn handle_pending_inbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        local_addr: &Multiaddr,
+        remote_addr: &Multiaddr,
+    ) -> Result<(), ConnectionDenied> {
+        self.inner
+            .handle_pending_inbound_connection(connection_id, local_addr, remote_addr)
+    }
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        local_addr: &Multiaddr,
+        remote_addr: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.with_connecting_peer(peer, |this| {
+            this.inner.handle_established_inbound_connection(
+                connection_id,
+                peer,
+                local_addr,
+                remote_addr,
+            )
+        })
+    }
+
+    fn handle_pending_outbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        maybe_peer: Option<PeerId>,
+        addresses: &[Multiaddr],
+        effective_role: Endpoint,
+    ) -> Result<Vec<Multiaddr>, ConnectionDenied> {
+        self.inner.handle_pending_outbound_connection(
+            connection_id,
+            maybe_peer,
+            addresses,
+            effective_role,
+        )
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        peer: PeerId,
+        addr: &Multiaddr,
+        role_override: Endpoint,
+        port_use: PortUse,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        self.with_connecting_peer(peer, |this| {
+            this.inner.handle_established_outbound_connection(
+                connection_id,
+                peer,
+                addr,
+                role_override,
+                port_use,
+            )
+        })
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm) {
+        self.inner.on_swarm_event(event)
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        peer_id: PeerId,
+        connection_id: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        self.inner
+            .on_connection_handler_event(peer_id, connection_id, event)
+    }
+
+    fn poll(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        self.inner.poll(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use futures::io::Cursor;
+    use libp2p::{request_response::Codec, PeerId, StreamProtocol};
+    use nimiq_test_log::test;
+
+    use super::MessageCodec;
+    use crate::traffic::TrafficStats;
+
+    fn encode(data: &[u8]) -> Vec<u8> {
+        let mut buf = (data.len() as u64).to_be_bytes().to_vec();
+        buf.extend_from_slice(data);
+        buf
+    }
+
+    #[test(tokio::test)]
+    async fn refuses_requests_of_peers_exceeding_bandwidth_limit() {
+        let traffic = Arc::new(TrafficStats::with_peer_bandwidth_limit(Some(1)));
+        let protocol = StreamProtocol::new("/test");
+        let peer_id = PeerId::random();
+        let other_peer_id = PeerId::random();
+
+        // Bind codecs to peers like the behaviour does when establishing connections.
+        let codec = MessageCodec::with_bandwidth_limit(Arc::clone(&traffic));
+        let bandwidth_limit = codec.bandwidth_limit.as_ref().unwrap();
+        *bandwidth_limit.connecting_peer.lock() = Some(peer_id);
+        let mut peer_codec = codec.clone();
+        *bandwidth_limit.connecting_peer.lock() = Some(other_peer_id);
+        let mut other_peer_codec = codec.clone();
+        *bandwidth_limit.connecting_peer.lock() = None;
+
+        // The per-stream clones of the connection handler stay bound to the peer.
+        let mut stream_codec = peer_codec.clone();
+        let request = stream_codec
+            .read_request(&protocol, &mut Cursor::new(encode(&[1; 10])))
+            .await
+            .unwrap();
+        assert_eq!(request, Some(vec![1; 10]));
+
+        let mut response = Cursor::new(Vec::new());
+        peer_codec
+            .write_response(&protocol, &mut response, Some(vec![2; 100]))
+            .await
+            .unwrap();
+        assert!(traffic.exceeds_bandwidth_limit(&peer_id));
+
+        assert!(peer_codec
+            .read_request(&protocol, &mut Cursor::new(encode(&[1; 10])))
+            .await
+            .is_err());
+
+        // Other peers are not affected.
+        assert!(other_peer_codec
+            .read_request(&protocol, &mut Cursor::new(encode(&[1; 10])))
+            .await
+            .unwrap()
+            .is_some());
+    }
+}
@@ -53,6 +53,9 @@ pub enum NetworkError {
 
     #[error("Peer contact error: {0}")]
     PeerContactError(#[from] PeerContactError),
+
+    #[error("DHT is disabled")]
+    DhtDisabled,
 }
 
 impl<T> From<tokio::sync::mpsc::error::SendError<T>> for NetworkError {
@@ -1,6 +1,7 @@
 #[macro_use]
 extern crate log;
 
+mod allowlist;
 mod behaviour;
 mod config;
 mod connection_pool;
@@ -13,10 +14,12 @@ mod network_metrics;
 mod network_types;
 mod rate_limiting;
 mod swarm;
+mod traffic;
 
 pub const DISCOVERY_PROTOCOL: &str = "/nimiq/discovery/0.0.1";
 pub const DHT_PROTOCOL: &str = "/nimiq/kad/0.0.1";
 
+pub use allowlist::{AllowlistError, PeerAllowlist};
 pub use config::{Config, TlsConfig};
 pub use error::NetworkError;
 pub use libp2p::{
@@ -29,6 +32,7 @@ pub use network::Network;
 use serde::{
     de::Error, ser::Error as SerializationError, Deserialize, Deserializer, Serialize, Serializer,
 };
+pub use traffic::{NetworkStats, TrafficCounters};
 
 /// Wrapper to libp2p Keypair identity that implements SerDe Serialize/Deserialize
 #[derive(Clone, Debug)]
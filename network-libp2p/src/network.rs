@@ -40,6 +40,7 @@ use crate::{
     network_types::{GossipsubId, NetworkAction, ValidateMessage},
     rate_limiting::RequestRateLimitData,
     swarm::{new_swarm, swarm_task},
+    traffic::{NetworkStats, TrafficStats},
     Config, NetworkError,
 };
 
@@ -59,6 +60,8 @@ pub struct Network {
     /// Metrics used for data analysis
     #[cfg(feature = "metrics")]
     metrics: Arc<NetworkMetrics>,
+    /// Traffic accounting per request type, gossipsub topic and peer
+    traffic: Arc<TrafficStats>,
     /// Required services from other peers. This is defined on init, based on our client type
     required_services: Services,
     /// Reference to PeerContactBook, used to satisfy rpc requests for it.
@@ -72,8 +75,12 @@ impl Network {
     ///
     ///  - `config`: The network configuration, containing key pair, and other behavior-specific configuration.
     ///
-    pub async fn new(config: Config) -> Self {
+    pub async fn new(mut config: Config) -> Self {
         let required_services = config.required_services;
+        // Advertise the relay service if we act as a relay for other peers
+        if config.relay_server {
+            config.peer_contact.services |= Services::RELAY;
+        }
         // TODO: persist to disk
         let own_peer_contact = config.peer_contact.clone();
         let contacts = Arc::new(RwLock::new(PeerContactBook::new(
@@ -87,16 +94,42 @@ impl Network {
             ..Default::default()
         };
         let dht_quorum = config.dht_quorum;
+        let dht_disabled = config.disable_discovery;
+        let peer_bandwidth_limit = config.peer_bandwidth_limit;
+        if dht_disabled
+            && config
+                .allowlist
+                .as_ref()
+                .is_some_and(|allowlist| allowlist.has_validator_keys())
+        {
+            warn!("Validator keys in the peer allowlist have no effect when discovery is disabled, list their peer IDs instead");
+        }
+        let allowlist = config
+            .allowlist
+            .take()
+            .map(|allowlist| Arc::new(RwLock::new(allowlist)));
         // Only force the server mode if we are doing a memory transport.
         // Otherwise expect the regular flow: DHT will get in server mode once a confirmed address is obtained using Autonat.
         // In memory transport we don't have a mechanism that sets the DHT in server mode such as confirming an address
         // with Autonat. This is because Autonat v1 only works with IP addresses.
         let force_dht_server_mode = config.memory_transport;
+
+        #[cfg(feature = "metrics")]
+        let metrics = Arc::new(NetworkMetrics::default());
+
+        let traffic = Arc::new(TrafficStats::new(
+            peer_bandwidth_limit,
+            #[cfg(feature = "metrics")]
+            metrics.clone(),
+        ));
+
         let swarm = new_swarm(
             config,
             Arc::clone(&contacts),
+            allowlist.clone(),
             params.clone(),
             force_dht_server_mode,
+            Arc::clone(&traffic),
         );
 
         let local_peer_id = *Swarm::local_peer_id(&swarm);
@@ -108,9 +141,6 @@ impl Network {
 
         let update_scores = interval(params.decay_interval);
 
-        #[cfg(feature = "metrics")]
-        let metrics = Arc::new(NetworkMetrics::default());
-
         spawn(Box::pin(swarm_task(
             swarm,
             events_tx.clone(),
@@ -119,8 +149,11 @@ impl Network {
             Arc::clone(&connected_peers),
             update_scores,
             Arc::clone(&contacts),
+            allowlist,
             force_dht_server_mode,
             dht_quorum,
+            dht_disabled,
+            Arc::clone(&traffic),
             #[cfg(feature = "metrics")]
             metrics.clone(),
         )));
@@ -134,6 +167,7 @@ impl Network {
             validate_tx,
             #[cfg(feature = "metrics")]
             metrics,
+            traffic,
             required_services,
         }
     }
@@ -159,6 +193,18 @@ impl Network {
         Ok(output_rx.await?)
     }
 
+    /// Gets the addresses the network is listening on. Addresses with port 0 are reported with the
+    /// port assigned by the operating system once the network listens on them.
+    pub async fn listen_addresses(&self) -> Result<Vec<Multiaddr>, NetworkError> {
+        let (output_tx, output_rx) = oneshot::channel();
+
+        self.action_tx
+            .clone()
+            .send(NetworkAction::ListenAddresses { output: output_tx })
+            .await?;
+        Ok(output_rx.await?)
+    }
+
     /// Tells the network to listen on a specific address received in a
     /// `Multiaddr` format.
     pub async fn listen_on(&self, listen_addresses: Vec<Multiaddr>) {
@@ -366,6 +412,11 @@ impl Network {
         }
     }
 
+    /// Gets a snapshot of the traffic accounted per request type, gossipsub topic and peer
+    pub fn network_stats(&self) -> NetworkStats {
+        self.traffic.stats()
+    }
+
     #[cfg(feature = "metrics")]
     /// Gets the network metrics
     pub fn metrics(&self) -> Arc<NetworkMetrics> {
@@ -1,16 +1,30 @@
-use std::time::Duration;
+use std::{collections::HashSet, time::Duration};
 
-use libp2p::gossipsub::TopicHash;
+use libp2p::{gossipsub::TopicHash, PeerId};
+use nimiq_network_interface::request::RequestType;
+use parking_lot::Mutex;
 use prometheus_client::{
     encoding::EncodeLabelSet,
     metrics::{counter::Counter, family::Family, histogram::Histogram},
     registry::Registry,
 };
 
+use crate::traffic::Direction;
+
+/// Maximum number of peers that get their own `peer_id` label in the per-peer traffic metrics.
+/// The traffic of any further peers is aggregated under the label `other`.
+const MAX_LABELED_PEERS: usize = 32;
+
 pub struct NetworkMetrics {
     gossipsub_messages_received: Family<TopicLabels, Counter>,
     gossipsub_messages_published: Family<TopicLabels, Counter>,
     response_times: Histogram,
+    bytes: Family<DirectionLabels, Counter>,
+    request_bytes: Family<RequestTrafficLabels, Counter>,
+    request_messages: Family<RequestTrafficLabels, Counter>,
+    gossipsub_bytes: Family<TopicTrafficLabels, Counter>,
+    peer_bytes: Family<PeerTrafficLabels, Counter>,
+    labeled_peers: Mutex<HashSet<PeerId>>,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
@@ -18,12 +32,41 @@ struct TopicLabels {
     topic: String,
 }
 
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct DirectionLabels {
+    direction: Direction,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct RequestTrafficLabels {
+    type_id: u16,
+    direction: Direction,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct TopicTrafficLabels {
+    topic: String,
+    direction: Direction,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct PeerTrafficLabels {
+    peer_id: String,
+    direction: Direction,
+}
+
 impl Default for NetworkMetrics {
     fn default() -> Self {
         NetworkMetrics {
             gossipsub_messages_received: Default::default(),
             gossipsub_messages_published: Default::default(),
             response_times: Histogram::new([0.01, 0.02, 0.05, 0.1, 0.2, 0.5, 1.0, 2.0].into_iter()),
+            bytes: Default::default(),
+            request_bytes: Default::default(),
+            request_messages: Default::default(),
+            gossipsub_bytes: Default::default(),
+            peer_bytes: Default::default(),
+            labeled_peers: Default::default(),
         }
     }
 }
@@ -47,24 +90,54 @@ impl NetworkMetrics {
             "Time between requests and responses",
             self.response_times.clone(),
         );
+
+        registry.register(
+            "bytes",
+            "Number of accounted bytes per direction",
+            self.bytes.clone(),
+        );
+
+        registry.register(
+            "request_bytes",
+            "Number of request-response bytes per request type and direction",
+            self.request_bytes.clone(),
+        );
+
+        registry.register(
+            "request_messages",
+            "Number of request-response messages per request type and direction",
+            self.request_messages.clone(),
+        );
+
+        registry.register(
+            "gossipsub_bytes",
+            "Number of gossipsub bytes per topic and direction",
+            self.gossipsub_bytes.clone(),
+        );
+
+        registry.register(
+            "peer_bytes",
+            "Number of bytes per connected peer and direction, peers beyond the first ones are aggregated as 'other'",
+            self.peer_bytes.clone(),
+        );
     }
 
-    pub(crate) fn note_received_pubsub_message(&self, topic: &TopicHash) {
-        if ["blocks", "transactions", "tendermint-proposal"].contains(&&*topic.to_string()) {
-            self.gossipsub_messages_received
-                .get_or_create(&TopicLabels {
-                    topic: topic.to_string(),
-                })
-                .inc();
+    fn topic_label(topic: &str) -> String {
+        if ["blocks", "transactions", "tendermint-proposal"].contains(&topic) {
+            topic.to_string()
         } else {
-            self.gossipsub_messages_received
-                .get_or_create(&TopicLabels {
-                    topic: "unknown".into(),
-                })
-                .inc();
+            "unknown".into()
         }
     }
 
+    pub(crate) fn note_received_pubsub_message(&self, topic: &TopicHash) {
+        self.gossipsub_messages_received
+            .get_or_create(&TopicLabels {
+                topic: Self::topic_label(topic.as_str()),
+            })
+            .inc();
+    }
+
     pub(crate) fn note_published_pubsub_message(&self, topic_str: &str) {
         self.gossipsub_messages_published
             .get_or_create(&TopicLabels {
@@ -76,4 +149,77 @@ impl NetworkMetrics {
     pub(crate) fn note_response_time(&self, duration: Duration) {
         self.response_times.observe(duration.as_secs_f64());
     }
+
+    pub(crate) fn note_request_traffic(
+        &self,
+        type_id: RequestType,
+        peer_id: PeerId,
+        direction: Direction,
+        bytes: usize,
+    ) {
+        let labels = RequestTrafficLabels {
+            type_id: type_id.type_id(),
+            direction,
+        };
+        self.request_bytes
+            .get_or_create(&labels)
+            .inc_by(bytes as u64);
+        self.request_messages.get_or_create(&labels).inc();
+        self.note_traffic(Some(peer_id), direction, bytes);
+    }
+
+    pub(crate) fn note_gossipsub_traffic(
+        &self,
+        topic: &str,
+        peer_id: Option<PeerId>,
+        direction: Direction,
+        bytes: usize,
+    ) {
+        self.gossipsub_bytes
+            .get_or_create(&TopicTrafficLabels {
+                topic: Self::topic_label(topic),
+                direction,
+            })
+            .inc_by(bytes as u64);
+        self.note_traffic(peer_id, direction, bytes);
+    }
+
+    fn note_traffic(&self, peer_id: Option<PeerId>, direction: Direction, bytes: usize) {
+        self.bytes
+            .get_or_create(&DirectionLabels { direction })
+            .inc_by(bytes as u64);
+
+        if let Some(peer_id) = peer_id {
+            self.peer_bytes
+                .get_or_create(&PeerTrafficLabels {
+                    peer_id: self.peer_label(peer_id),
+                    direction,
+                })
+                .inc_by(bytes as u64);
+        }
+    }
+
+    /// Returns the label for the given peer, limiting the number of labeled peers to
+    /// `MAX_LABELED_PEERS` to bound the cardinality of the metric.
+    fn peer_label(&self, peer_id: PeerId) -> String {
+        let mut labeled_peers = self.labeled_peers.lock();
+        if labeled_peers.contains(&peer_id) || labeled_peers.len() < MAX_LABELED_PEERS {
+            labeled_peers.insert(peer_id);
+            peer_id.to_string()
+        } else {
+            "other".into()
+        }
+    }
+
+    pub(crate) fn remove_peer_traffic(&self, peer_id: &PeerId) {
+        if !self.labeled_peers.lock().remove(peer_id) {
+            return;
+        }
+        for direction in [Direction::Inbound, Direction::Outbound] {
+            self.peer_bytes.remove(&PeerTrafficLabels {
+                peer_id: peer_id.to_string(),
+                direction,
+            });
+        }
+    }
 }
@@ -1,9 +1,13 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use bytes::Bytes;
 #[cfg(feature = "metrics")]
 use instant::Instant;
 use libp2p::{
+    core::transport::ListenerId,
     gossipsub,
     kad::{QueryId, Record},
     request_response::{InboundRequestId, OutboundRequestId, ResponseChannel},
@@ -19,13 +23,15 @@ use nimiq_network_interface::{
 use nimiq_serde::{Deserialize, DeserializeError};
 use nimiq_utils::tagged_signing::{TaggedSignable, TaggedSigned};
 use nimiq_validator_network::validator_record::ValidatorRecord;
+use parking_lot::RwLock;
 use thiserror::Error;
 use tokio::sync::{mpsc, oneshot};
 
 use crate::{
     dispatch::codecs::{IncomingRequest, OutgoingResponse},
     rate_limiting::RequestRateLimitData,
-    NetworkError,
+    traffic::TrafficStats,
+    NetworkError, PeerAllowlist,
 };
 
 #[derive(Debug)]
@@ -70,6 +76,9 @@ pub(crate) enum NetworkAction {
     NetworkInfo {
         output: oneshot::Sender<NetworkInfo>,
     },
+    ListenAddresses {
+        output: oneshot::Sender<Vec<Multiaddr>>,
+    },
     ReceiveRequests {
         type_id: RequestType,
         output: mpsc::Sender<(Bytes, InboundRequestId, PeerId)>,
@@ -241,12 +250,16 @@ pub(crate) struct TaskState {
     pub(crate) dht_server_mode: bool,
     /// Senders per `OutboundRequestId` for request-response
     pub(crate) requests: HashMap<OutboundRequestId, oneshot::Sender<Result<Bytes, RequestError>>>,
+    /// Request types per `OutboundRequestId` for request-response traffic accounting
+    pub(crate) request_types: HashMap<OutboundRequestId, RequestType>,
     /// Time spent per `OutboundRequestId` for request-response
     #[cfg(feature = "metrics")]
     pub(crate) requests_initiated: HashMap<OutboundRequestId, Instant>,
     /// Senders for receiving responses per `InboundRequestId` for request-response
     pub(crate) response_channels:
         HashMap<InboundRequestId, ResponseChannel<Option<OutgoingResponse>>>,
+    /// Requesting peer and request type per `InboundRequestId` for request-response traffic accounting
+    pub(crate) response_types: HashMap<InboundRequestId, (PeerId, RequestType)>,
     /// Senders and respective rate limiting constants for replying to requests per `RequestType` for request-response
     pub(crate) receive_requests: HashMap<
         RequestType,
@@ -257,6 +270,18 @@ pub(crate) struct TaskState {
     >,
     /// DHT quorum value
     pub(crate) dht_quorum: u8,
+    /// DHT (kad) is disabled, such that no records can be stored or retrieved
+    pub(crate) dht_disabled: bool,
+    /// Optional allowlist of peers
+    pub(crate) allowlist: Option<Arc<RwLock<PeerAllowlist>>>,
+    /// Traffic accounting
+    pub(crate) traffic: Arc<TrafficStats>,
+    /// We are not publicly reachable according to autonat
+    pub(crate) nat_private: bool,
+    /// Listeners on relayed addresses per relay peer
+    pub(crate) relay_listeners: HashMap<PeerId, ListenerId>,
+    /// Connected relays whose relayed listener was closed, e.g. because they denied our reservation
+    pub(crate) failed_relays: HashSet<PeerId>,
 }
 
 #[derive(Clone, Debug)]
@@ -1,9 +1,11 @@
 use std::{collections::HashMap, num::NonZeroU8, sync::Arc};
 
 use base64::Engine;
-use futures::StreamExt;
+use futures::{future::Either, StreamExt};
 #[cfg(feature = "metrics")]
 use instant::Instant;
+#[cfg(feature = "tokio-quic")]
+use libp2p::quic;
 #[cfg(all(target_family = "wasm", not(feature = "tokio-websocket")))]
 use libp2p::websocket_websys;
 use libp2p::{
@@ -16,13 +18,14 @@ use libp2p::{
     gossipsub,
     identity::Keypair,
     kad::{self, store::RecordStore, GetRecordOk, InboundRequest, QueryResult, Quorum, Record},
-    noise,
+    multiaddr::Protocol,
+    noise, relay,
     request_response::{self},
     swarm::{
         dial_opts::{DialOpts, PeerCondition},
         SwarmEvent,
     },
-    yamux, PeerId, Swarm, SwarmBuilder, Transport,
+    yamux, Multiaddr, PeerId, Swarm, SwarmBuilder, Transport,
 };
 #[cfg(feature = "tokio-websocket")]
 use libp2p::{dns, tcp, websocket};
@@ -30,7 +33,7 @@ use log::Instrument;
 use nimiq_bls::{CompressedPublicKey, KeyPair};
 use nimiq_network_interface::{
     network::{CloseReason, NetworkEvent},
-    peer_info::PeerInfo,
+    peer_info::{PeerInfo, Services},
     request::{peek_type, InboundRequestError, OutboundRequestError, RequestError},
 };
 use nimiq_serde::{Deserialize, Serialize};
@@ -49,22 +52,37 @@ use crate::{
         DhtBootStrapState, DhtRecord, DhtResults, NetworkAction, TaskState, ValidateMessage,
     },
     rate_limiting::RateLimits,
-    Config, NetworkError, TlsConfig,
+    traffic::{Direction, TrafficStats},
+    Config, NetworkError, PeerAllowlist, TlsConfig,
 };
 
 type NimiqSwarm = Swarm<behaviour::Behaviour>;
 
+/// Maximum number of relays we hold a reservation with while we are not publicly reachable.
+const MAX_RELAY_RESERVATIONS: usize = 2;
+
 pub(crate) fn new_swarm(
     config: Config,
     contacts: Arc<RwLock<PeerContactBook>>,
+    allowlist: Option<Arc<RwLock<PeerAllowlist>>>,
     peer_score_params: gossipsub::PeerScoreParams,
     force_dht_server_mode: bool,
+    traffic: Arc<TrafficStats>,
 ) -> Swarm<behaviour::Behaviour> {
     let keypair = config.keypair.clone();
     let transport = new_transport(&keypair, config.memory_transport, config.tls.as_ref()).unwrap();
+    let (relay_transport, relay_client) = relay::client::new(keypair.public().to_peer_id());
+    let transport = with_relay_transport(&keypair, transport, relay_transport);
 
-    let behaviour =
-        behaviour::Behaviour::new(config, contacts, peer_score_params, force_dht_server_mode);
+    let behaviour = behaviour::Behaviour::new(
+        config,
+        contacts,
+        allowlist,
+        relay_client,
+        peer_score_params,
+        force_dht_server_mode,
+        traffic,
+    );
 
     // TODO add proper config
     #[cfg(not(target_family = "wasm"))]
@@ -94,13 +112,19 @@ pub(crate) async fn swarm_task(
     connected_peers: Arc<RwLock<HashMap<PeerId, PeerInfo>>>,
     mut update_scores: Interval,
     contacts: Arc<RwLock<PeerContactBook>>,
+    allowlist: Option<Arc<RwLock<PeerAllowlist>>>,
     force_dht_server_mode: bool,
     dht_quorum: NonZeroU8,
+    dht_disabled: bool,
+    traffic: Arc<TrafficStats>,
     #[cfg(feature = "metrics")] metrics: Arc<NetworkMetrics>,
 ) {
     let mut task_state = TaskState {
         dht_server_mode: force_dht_server_mode,
         dht_quorum: dht_quorum.into(),
+        dht_disabled,
+        allowlist,
+        traffic,
         ..Default::default()
     };
     let mut rate_limiting = RateLimits::default();
@@ -192,12 +216,17 @@ fn new_transport(
 
         let yamux = yamux::Config::default();
 
-        Ok(transport
+        let transport = transport
             .upgrade(core::upgrade::Version::V1)
             .authenticate(noise::Config::new(keypair).unwrap())
             .multiplex(yamux)
             .timeout(std::time::Duration::from_secs(20))
-            .boxed())
+            .boxed();
+
+        #[cfg(feature = "tokio-quic")]
+        let transport = with_quic_transport(keypair, transport);
+
+        Ok(transport)
     } else {
         #[cfg(feature = "tokio-websocket")]
         let mut transport = websocket::WsConfig::new(dns::tokio::Transport::system(
@@ -225,15 +254,64 @@ fn new_transport(
 
         let yamux = yamux::Config::default();
 
-        Ok(transport
+        let transport = transport
             .upgrade(core::upgrade::Version::V1)
             .authenticate(noise::Config::new(keypair).unwrap())
             .multiplex(yamux)
             .timeout(std::time::Duration::from_secs(20))
-            .boxed())
+            .boxed();
+
+        #[cfg(feature = "tokio-quic")]
+        let transport = with_quic_transport(keypair, transport);
+
+        Ok(transport)
     }
 }
 
+/// Combines the QUIC transport with the given transport.
+/// QUIC addresses (`/udp/<port>/quic-v1`) are handled by the QUIC transport, which comes with its
+/// own encryption and multiplexing. All other addresses fall back to the given transport.
+#[cfg(feature = "tokio-quic")]
+fn with_quic_transport(
+    keypair: &Keypair,
+    transport: Boxed<(PeerId, StreamMuxerBox)>,
+) -> Boxed<(PeerId, StreamMuxerBox)> {
+    let quic = quic::tokio::Transport::new(quic::Config::new(keypair))
+        .map(|(peer_id, muxer), _| (peer_id, StreamMuxerBox::new(muxer)));
+
+    quic.or_transport(transport)
+        .map(|output, _| match output {
+            Either::Left(output) => output,
+            Either::Right(output) => output,
+        })
+        .boxed()
+}
+
+/// Combines the circuit relay transport with the given transport.
+/// Relayed addresses (`/p2p-circuit`) are handled by the relay transport, which tunnels the
+/// connection through the relay using the given transport. As the relayed connection is not
+/// secured by the underlying transport, it is upgraded the same way as the given transport.
+fn with_relay_transport(
+    keypair: &Keypair,
+    transport: Boxed<(PeerId, StreamMuxerBox)>,
+    relay_transport: relay::client::Transport,
+) -> Boxed<(PeerId, StreamMuxerBox)> {
+    let relay_transport = relay_transport
+        .upgrade(core::upgrade::Version::V1)
+        .authenticate(noise::Config::new(keypair).unwrap())
+        .multiplex(yamux::Config::default())
+        .timeout(std::time::Duration::from_secs(20))
+        .boxed();
+
+    relay_transport
+        .or_transport(transport)
+        .map(|output, _| match output {
+            Either::Left(output) => output,
+            Either::Right(output) => output,
+        })
+        .boxed()
+}
+
 fn handle_event(
     event: SwarmEvent<behaviour::BehaviourEvent>,
     events_tx: &broadcast::Sender<NetworkEvent<PeerId>>,
@@ -275,7 +353,7 @@ fn handle_event(
             }
 
             // Save dialed peer addresses
-            if endpoint.is_dialer() {
+            if endpoint.is_dialer() && !state.dht_disabled {
                 let listen_addr = endpoint.get_remote_address();
 
                 if swarm.behaviour().is_address_dialable(listen_addr) {
@@ -324,6 +402,14 @@ fn handle_event(
                 // Removes or marks to remove the respective rate limits.
                 // Also cleans up the expired rate limits pending to delete.
                 rate_limiting.remove_rate_limits(peer_id);
+                state.traffic.remove_peer(&peer_id);
+
+                // If the peer was one of our relays, try to find another one.
+                state.failed_relays.remove(&peer_id);
+                if let Some(listener_id) = state.relay_listeners.remove(&peer_id) {
+                    swarm.remove_listener(listener_id);
+                    listen_on_relays(swarm, state, connected_peers);
+                }
 
                 let _ = events_tx.send(NetworkEvent::PeerLeft(peer_id));
             }
@@ -375,6 +461,49 @@ fn handle_event(
                 .add_own_addresses([address].to_vec());
         }
 
+        SwarmEvent::ExpiredListenAddr {
+            listener_id: _,
+            address,
+        } => {
+            debug!(%address, "Expired listen address");
+            // Stop advertising relayed addresses that are no longer valid.
+            if is_relayed_address(&address) {
+                swarm
+                    .behaviour_mut()
+                    .discovery
+                    .remove_own_addresses([address].to_vec());
+            }
+        }
+
+        SwarmEvent::ListenerClosed {
+            listener_id,
+            addresses,
+            reason,
+        } => {
+            debug!(?listener_id, ?addresses, ?reason, "Listener closed");
+            let relayed_addresses: Vec<_> =
+                addresses.into_iter().filter(is_relayed_address).collect();
+            if !relayed_addresses.is_empty() {
+                swarm
+                    .behaviour_mut()
+                    .discovery
+                    .remove_own_addresses(relayed_addresses);
+            }
+
+            // If the listener of a relay was closed (e.g. because the reservation was denied),
+            // don't use this relay again while we are connected to it and try to find another one.
+            let relay_peer_id = state
+                .relay_listeners
+                .iter()
+                .find(|(_, relay_listener_id)| **relay_listener_id == listener_id)
+                .map(|(relay_peer_id, _)| *relay_peer_id);
+            if let Some(relay_peer_id) = relay_peer_id {
+                state.relay_listeners.remove(&relay_peer_id);
+                state.failed_relays.insert(relay_peer_id);
+                listen_on_relays(swarm, state, connected_peers);
+            }
+        }
+
         SwarmEvent::Behaviour(event) => {
             match event {
                 behaviour::BehaviourEvent::Autonat(event) => match event {
@@ -386,12 +515,39 @@ fn handle_event(
                     }
                     autonat::Event::StatusChanged { old, new } => {
                         log::debug!(?old, ?new, "Autonat status changed");
-                        if new == autonat::NatStatus::Private {
-                            log::warn!("Couldn't detect a public reachable address. Validator network operations won't be possible");
-                            log::warn!("You may need to find a relay to enable validator network operations");
+                        match new {
+                            autonat::NatStatus::Private => {
+                                log::warn!("Couldn't detect a public reachable address. Trying to become reachable through relays");
+                                state.nat_private = true;
+                                listen_on_relays(swarm, state, connected_peers);
+                            }
+                            autonat::NatStatus::Public(_) => {
+                                state.nat_private = false;
+                                stop_listening_on_relays(swarm, state);
+                            }
+                            autonat::NatStatus::Unknown => {}
                         }
                     }
                 },
+                behaviour::BehaviourEvent::RelayClient(event) => match event {
+                    relay::client::Event::ReservationReqAccepted { relay_peer_id, .. } => {
+                        log::debug!(%relay_peer_id, "Relay accepted our reservation");
+                    }
+                    event => {
+                        log::trace!(?event, "Relay client event");
+                    }
+                },
+                behaviour::BehaviourEvent::Relay(event) => {
+                    log::trace!(?event, "Relay event");
+                }
+                behaviour::BehaviourEvent::Dcutr(event) => match event.result {
+                    Ok(connection_id) => {
+                        log::debug!(peer_id = %event.remote_peer_id, %connection_id, "Hole punching succeeded");
+                    }
+                    Err(error) => {
+                        log::debug!(peer_id = %event.remote_peer_id, %error, "Hole punching failed");
+                    }
+                },
                 behaviour::BehaviourEvent::ConnectionLimits(_) => {}
                 behaviour::BehaviourEvent::Dht(event) => {
                     match event {
@@ -404,6 +560,7 @@ fn handle_event(
                             match result {
                                 QueryResult::GetRecord(Ok(GetRecordOk::FoundRecord(record))) => {
                                     if let Some(dht_record) = verify_record(&record.record) {
+                                        allow_validator_peer(state, &record.record, &dht_record);
                                         if step.count.get() == 1_usize {
                                             // This is our first record
                                             let results = DhtResults {
@@ -540,6 +697,7 @@ fn handle_event(
                         } => {
                             // Verify incoming record
                             if let Some(dht_record) = verify_record(&record) {
+                                allow_validator_peer(state, &record, &dht_record);
                                 // Now verify that we should overwrite it because it's better than the one we have
                                 let mut overwrite = true;
                                 let store = swarm.behaviour_mut().dht.store_mut();
@@ -592,7 +750,11 @@ fn handle_event(
                                 let _ =
                                     events_tx.send(NetworkEvent::PeerJoined(peer_id, peer_info));
 
-                                if swarm.behaviour().is_address_dialable(&peer_address) {
+                                listen_on_relays(swarm, state, connected_peers);
+
+                                if !state.dht_disabled
+                                    && swarm.behaviour().is_address_dialable(&peer_address)
+                                {
                                     swarm
                                         .behaviour_mut()
                                         .add_peer_address(peer_id, peer_address);
@@ -621,6 +783,13 @@ fn handle_event(
                     } => {
                         let topic = message.topic.clone();
                         if let Some(topic_info) = state.gossip_topics.get_mut(&topic) {
+                            state.traffic.note_gossipsub_message(
+                                Some(propagation_source),
+                                topic.as_str(),
+                                Direction::Inbound,
+                                message.data.len(),
+                            );
+
                             let (output, validate) = topic_info;
                             if !&*validate {
                                 if let Err(error) = swarm
@@ -647,6 +816,12 @@ fn handle_event(
                             }
                         } else {
                             warn!(topic = %message.topic, "unknown topic hash");
+                            state.traffic.note_gossipsub_message(
+                                Some(propagation_source),
+                                "unknown",
+                                Direction::Inbound,
+                                message.data.len(),
+                            );
                         }
                         #[cfg(feature = "metrics")]
                         metrics.note_received_pubsub_message(&topic);
@@ -696,6 +871,12 @@ fn handle_event(
                                         content = &*base64::prelude::BASE64_STANDARD.encode(&request),
                                         "Incoming request from peer",
                                     );
+                                    state.traffic.note_inbound_request(
+                                        peer_id,
+                                        type_id,
+                                        request.len(),
+                                    );
+
                                     // Check if we have a receiver registered for this message type
 
                                     // Filter off sender if not alive.
@@ -721,13 +902,16 @@ fn handle_event(
                                             );
                                             let response: Result<(), InboundRequestError> =
                                                 Err(InboundRequestError::ExceedsRateLimit);
+                                            let response = response.serialize_to_vec();
+                                            state.traffic.note_outbound_response(
+                                                peer_id,
+                                                type_id,
+                                                response.len(),
+                                            );
                                             if swarm
                                                 .behaviour_mut()
                                                 .request_response
-                                                .send_response(
-                                                    channel,
-                                                    Some(response.serialize_to_vec()),
-                                                )
+                                                .send_response(channel, Some(response))
                                                 .is_err()
                                             {
                                                 error!(
@@ -740,17 +924,23 @@ fn handle_event(
                                         } else {
                                             if type_id.requires_response() {
                                                 state.response_channels.insert(request_id, channel);
+                                                state
+                                                    .response_types
+                                                    .insert(request_id, (peer_id, type_id));
                                             } else {
                                                 // Respond on behalf of the actual receiver because the actual receiver isn't interested in responding.
                                                 let response: Result<(), InboundRequestError> =
                                                     Ok(());
+                                                let response = response.serialize_to_vec();
+                                                state.traffic.note_outbound_response(
+                                                    peer_id,
+                                                    type_id,
+                                                    response.len(),
+                                                );
                                                 if swarm
                                                     .behaviour_mut()
                                                     .request_response
-                                                    .send_response(
-                                                        channel,
-                                                        Some(response.serialize_to_vec()),
-                                                    )
+                                                    .send_response(channel, Some(response))
                                                     .is_err()
                                                 {
                                                     error!(
@@ -784,10 +974,16 @@ fn handle_event(
                                         );
                                         let err: Result<(), InboundRequestError> =
                                             Err(InboundRequestError::NoReceiver);
+                                        let err = err.serialize_to_vec();
+                                        state.traffic.note_outbound_response(
+                                            peer_id,
+                                            type_id,
+                                            err.len(),
+                                        );
                                         if swarm
                                             .behaviour_mut()
                                             .request_response
-                                            .send_response(channel, Some(err.serialize_to_vec()))
+                                            .send_response(channel, Some(err))
                                             .is_err()
                                         {
                                             error!(
@@ -820,6 +1016,15 @@ fn handle_event(
                                 %peer_id,
                                 "Incoming response from peer",
                             );
+                            if let (Some(type_id), Some(response)) =
+                                (state.request_types.remove(&request_id), &response)
+                            {
+                                state.traffic.note_inbound_response(
+                                    peer_id,
+                                    type_id,
+                                    response.len(),
+                                );
+                            }
                             if let Some(channel) = state.requests.remove(&request_id) {
                                 // We might get empty responses (None) because of the implementation of our codecs.
                                 if channel
@@ -859,6 +1064,7 @@ fn handle_event(
                             %error,
                             "Failed to send request to peer",
                         );
+                        state.request_types.remove(&request_id);
                         if let Some(channel) = state.requests.remove(&request_id) {
                             if channel.send(Err(to_response_error(error))).is_err() {
                                 error!(%request_id, %peer_id, error = "receiver hung up", "could not send outbound failure to channel");
@@ -882,6 +1088,7 @@ fn handle_event(
                             %error,
                             "Response to request sent from peer failed",
                         );
+                        state.response_types.remove(&request_id);
                     }
                     request_response::Event::ResponseSent { peer, request_id } => {
                         trace!(
@@ -897,6 +1104,75 @@ fn handle_event(
     }
 }
 
+/// Listens on relayed addresses through connected relays while we are not publicly reachable,
+/// until we hold a reservation with `MAX_RELAY_RESERVATIONS` relays.
+/// The relayed addresses are advertised in our peer contact once the relays accepted our
+/// reservations (see `SwarmEvent::NewListenAddr`).
+fn listen_on_relays(
+    swarm: &mut NimiqSwarm,
+    state: &mut TaskState,
+    connected_peers: &RwLock<HashMap<PeerId, PeerInfo>>,
+) {
+    let num_missing = MAX_RELAY_RESERVATIONS.saturating_sub(state.relay_listeners.len());
+    if !state.nat_private || num_missing == 0 {
+        return;
+    }
+
+    let relays: Vec<(PeerId, Multiaddr)> = connected_peers
+        .read()
+        .iter()
+        .filter(|(peer_id, peer_info)| {
+            peer_info.get_services().contains(Services::RELAY)
+                && !state.relay_listeners.contains_key(peer_id)
+                && !state.failed_relays.contains(peer_id)
+        })
+        .filter_map(|(peer_id, _)| {
+            // Only use direct addresses of the relay, we don't support multiple hops.
+            let mut address = swarm
+                .behaviour()
+                .discovery
+                .get_peer_addresses(peer_id)?
+                .into_iter()
+                .find(|address| !is_relayed_address(address))?;
+            if let Some(Protocol::P2p(_)) = address.iter().last() {
+                address.pop();
+            }
+            Some((*peer_id, address))
+        })
+        .take(num_missing)
+        .collect();
+
+    for (relay_peer_id, relay_address) in relays {
+        let address = relay_address
+            .with(Protocol::P2p(relay_peer_id))
+            .with(Protocol::P2pCircuit);
+        match swarm.listen_on(address.clone()) {
+            Ok(listener_id) => {
+                debug!(%relay_peer_id, %address, "Listening on relayed address");
+                state.relay_listeners.insert(relay_peer_id, listener_id);
+            }
+            Err(error) => {
+                warn!(%relay_peer_id, %address, %error, "Failed to listen on relayed address");
+            }
+        }
+    }
+}
+
+/// Stops listening on relayed addresses once we are publicly reachable.
+fn stop_listening_on_relays(swarm: &mut NimiqSwarm, state: &mut TaskState) {
+    for (relay_peer_id, listener_id) in state.relay_listeners.drain() {
+        debug!(%relay_peer_id, "Stop listening on relayed address");
+        swarm.remove_listener(listener_id);
+    }
+}
+
+/// Returns whether an address is a relayed (`/p2p-circuit`) address.
+fn is_relayed_address(address: &Multiaddr) -> bool {
+    address
+        .iter()
+        .any(|protocol| matches!(protocol, Protocol::P2pCircuit))
+}
+
 fn perform_action(action: NetworkAction, swarm: &mut NimiqSwarm, state: &mut TaskState) {
     // FIXME implement compact debug format for NetworkAction
     // trace!(?action, "performing action");
@@ -930,10 +1206,28 @@ fn perform_action(action: NetworkAction, swarm: &mut NimiqSwarm, state: &mut Tas
             }
         }
         NetworkAction::DhtGet { key, output } => {
+            if state.dht_disabled {
+                if output.send(Err(NetworkError::DhtDisabled)).is_err() {
+                    error!(
+                        error = "receiver hung up",
+                        "could not send dht get error to channel",
+                    );
+                }
+                return;
+            }
             let query_id = swarm.behaviour_mut().dht.get_record(key.into());
             state.dht_gets.insert(query_id, output);
         }
         NetworkAction::DhtPut { key, value, output } => {
+            if state.dht_disabled {
+                if output.send(Err(NetworkError::DhtDisabled)).is_err() {
+                    error!(
+                        error = "receiver hung up",
+                        "could not send dht put error to channel",
+                    );
+                }
+                return;
+            }
             let local_peer_id = Swarm::local_peer_id(swarm);
 
             let record = Record {
@@ -1068,6 +1362,12 @@ fn perform_action(action: NetworkAction, swarm: &mut NimiqSwarm, state: &mut Tas
             output,
         } => {
             let topic = gossipsub::IdentTopic::new(topic_name.clone());
+            state.traffic.note_gossipsub_message(
+                None,
+                &topic_name,
+                Direction::Outbound,
+                data.len(),
+            );
 
             if output
                 .send(
@@ -1095,6 +1395,14 @@ fn perform_action(action: NetworkAction, swarm: &mut NimiqSwarm, state: &mut Tas
                 );
             }
         }
+        NetworkAction::ListenAddresses { output } => {
+            if output.send(swarm.listeners().cloned().collect()).is_err() {
+                error!(
+                    error = "receiver hung up",
+                    "could not send listen addresses to channel",
+                );
+            }
+        }
         NetworkAction::ReceiveRequests {
             type_id,
             output,
@@ -1111,6 +1419,9 @@ fn perform_action(action: NetworkAction, swarm: &mut NimiqSwarm, state: &mut Tas
             response_channel,
             output,
         } => {
+            state
+                .traffic
+                .note_outbound_request(peer_id, request_type_id, request.len());
             let request_id = swarm
                 .behaviour_mut()
                 .request_response
@@ -1122,6 +1433,7 @@ fn perform_action(action: NetworkAction, swarm: &mut NimiqSwarm, state: &mut Tas
                 "Request was sent to peer",
             );
             state.requests.insert(request_id, response_channel);
+            state.request_types.insert(request_id, request_type_id);
             #[cfg(feature = "metrics")]
             state.requests_initiated.insert(request_id, Instant::now());
             if output.send(request_id).is_err() {
@@ -1134,6 +1446,11 @@ fn perform_action(action: NetworkAction, swarm: &mut NimiqSwarm, state: &mut Tas
             output,
         } => {
             if let Some(response_channel) = state.response_channels.remove(&request_id) {
+                if let Some((peer_id, type_id)) = state.response_types.remove(&request_id) {
+                    state
+                        .traffic
+                        .note_outbound_response(peer_id, type_id, response.len());
+                }
                 if output
                     .send(
                         swarm
@@ -1200,6 +1517,29 @@ fn perform_action(action: NetworkAction, swarm: &mut NimiqSwarm, state: &mut Tas
     }
 }
 
+/// If an allowlist is configured and the verified record was signed by one of the allowed validator
+/// keys, the peer ID contained in the record is added to the allowed peers.
+fn allow_validator_peer(state: &TaskState, record: &Record, dht_record: &DhtRecord) {
+    let Some(allowlist) = &state.allowlist else {
+        return;
+    };
+
+    match dht_record {
+        DhtRecord::Validator(_, validator_record, _) => {
+            if let Ok(validator_key) =
+                CompressedPublicKey::deserialize_from_vec(record.key.as_ref())
+            {
+                if allowlist
+                    .write()
+                    .add_validator_peer(validator_key, validator_record.peer_id)
+                {
+                    debug!(peer_id = %validator_record.peer_id, "Allowing peer of allowlisted validator");
+                }
+            }
+        }
+    }
+}
+
 /// Returns a DHT record if the record decoding and verification was successful, None otherwise
 pub(crate) fn verify_record(record: &Record) -> Option<DhtRecord> {
     if let Some(tag) = TaggedSigned::<ValidatorRecord<PeerId>, KeyPair>::peek_tag(&record.value) {
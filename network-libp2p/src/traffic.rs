@@ -0,0 +1,381 @@
+use std::{collections::HashMap, time::Duration};
+
+#[cfg(feature = "metrics")]
+use std::sync::Arc;
+
+use instant::Instant;
+use libp2p::PeerId;
+use nimiq_network_interface::request::RequestType;
+use parking_lot::Mutex;
+
+#[cfg(feature = "metrics")]
+use crate::network_metrics::NetworkMetrics;
+
+/// The time window over which the per-peer bandwidth limit is enforced.
+const BANDWIDTH_WINDOW: Duration = Duration::from_secs(60);
+
+/// Direction of the accounted traffic, seen from the local peer.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "metrics",
+    derive(prometheus_client::encoding::EncodeLabelValue)
+)]
+pub(crate) enum Direction {
+    Inbound,
+    Outbound,
+}
+
+/// Byte and message counters in both directions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TrafficCounters {
+    /// Number of bytes received.
+    pub bytes_received: u64,
+    /// Number of bytes sent.
+    pub bytes_sent: u64,
+    /// Number of messages received.
+    pub messages_received: u64,
+    /// Number of messages sent.
+    pub messages_sent: u64,
+}
+
+impl TrafficCounters {
+    fn add(&mut self, direction: Direction, bytes: usize) {
+        match direction {
+            Direction::Inbound => {
+                self.bytes_received += bytes as u64;
+                self.messages_received += 1;
+            }
+            Direction::Outbound => {
+                self.bytes_sent += bytes as u64;
+                self.messages_sent += 1;
+            }
+        }
+    }
+}
+
+/// Snapshot of the traffic accounted by the network.
+///
+/// Request-response traffic is accounted per request type ID (`RequestCommon::TYPE_ID`), where
+/// responses are accounted under the type ID of their request. Gossipsub traffic is accounted
+/// per topic. Per-peer counters only cover currently connected peers.
+#[derive(Clone, Debug, Default)]
+pub struct NetworkStats {
+    /// Counters over all accounted traffic.
+    pub total: TrafficCounters,
+    /// Counters per request type ID.
+    pub request_types: HashMap<u16, TrafficCounters>,
+    /// Counters per gossipsub topic.
+    pub topics: HashMap<String, TrafficCounters>,
+    /// Counters per connected peer.
+    pub peers: HashMap<PeerId, TrafficCounters>,
+}
+
+/// Bytes served to a peer within the current bandwidth window.
+#[derive(Debug)]
+struct PeerBandwidth {
+    window_start: Instant,
+    bytes: u64,
+}
+
+#[derive(Debug, Default)]
+struct TrafficState {
+    stats: NetworkStats,
+    bandwidth: HashMap<PeerId, PeerBandwidth>,
+}
+
+/// Traffic accounting shared between the `Network`, the swarm task and the request-response codecs.
+///
+/// Besides the counters, this keeps track of the bytes each peer caused us to transfer by sending
+/// requests to us (i.e. its requests and our responses) in order to enforce an optional per-peer
+/// bandwidth limit. The limit is enforced by the `MessageCodec`, which also accounts these bytes.
+#[derive(Default)]
+pub(crate) struct TrafficStats {
+    state: Mutex<TrafficState>,
+    /// Maximum number of bytes per second a peer may cause us to transfer through its requests.
+    peer_bandwidth_limit: Option<u64>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Arc<NetworkMetrics>>,
+}
+
+impl TrafficStats {
+    pub(crate) fn new(
+        peer_bandwidth_limit: Option<u64>,
+        #[cfg(feature = "metrics")] metrics: Arc<NetworkMetrics>,
+    ) -> Self {
+        Self {
+            state: Default::default(),
+            peer_bandwidth_limit,
+            #[cfg(feature = "metrics")]
+            metrics: Some(metrics),
+        }
+    }
+
+    /// Accounts a request received from a peer.
+    pub(crate) fn note_inbound_request(&self, peer_id: PeerId, type_id: RequestType, bytes: usize) {
+        self.note_request(peer_id, type_id, Direction::Inbound, bytes);
+    }
+
+    /// Accounts a response sent to a peer.
+    pub(crate) fn note_outbound_response(
+        &self,
+        peer_id: PeerId,
+        type_id: RequestType,
+        bytes: usize,
+    ) {
+        self.note_request(peer_id, type_id, Direction::Outbound, bytes);
+    }
+
+    /// Accounts a request sent to a peer.
+    pub(crate) fn note_outbound_request(
+        &self,
+        peer_id: PeerId,
+        type_id: RequestType,
+        bytes: usize,
+    ) {
+        self.note_request(peer_id, type_id, Direction::Outbound, bytes);
+    }
+
+    /// Accounts a response received from a peer.
+    pub(crate) fn note_inbound_response(
+        &self,
+        peer_id: PeerId,
+        type_id: RequestType,
+        bytes: usize,
+    ) {
+        self.note_request(peer_id, type_id, Direction::Inbound, bytes);
+    }
+
+    fn note_request(
+        &self,
+        peer_id: PeerId,
+        type_id: RequestType,
+        direction: Direction,
+        bytes: usize,
+    ) {
+        let mut state = self.state.lock();
+        state.stats.total.add(direction, bytes);
+        state
+            .stats
+            .request_types
+            .entry(type_id.type_id())
+            .or_default()
+            .add(direction, bytes);
+        state
+            .stats
+            .peers
+            .entry(peer_id)
+            .or_default()
+            .add(direction, bytes);
+        drop(state);
+
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.note_request_traffic(type_id, peer_id, direction, bytes);
+        }
+    }
+
+    /// Accounts a gossipsub message. Published messages are not attributed to a peer since
+    /// gossipsub decides which peers they are sent to.
+    pub(crate) fn note_gossipsub_message(
+        &self,
+        peer_id: Option<PeerId>,
+        topic: &str,
+        direction: Direction,
+        bytes: usize,
+    ) {
+        let mut state = self.state.lock();
+        state.stats.total.add(direction, bytes);
+        state
+            .stats
+            .topics
+            .entry(topic.to_string())
+            .or_default()
+            .add(direction, bytes);
+        if let Some(peer_id) = peer_id {
+            state
+                .stats
+                .peers
+                .entry(peer_id)
+                .or_default()
+                .add(direction, bytes);
+        }
+        drop(state);
+
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.note_gossipsub_traffic(topic, peer_id, direction, bytes);
+        }
+    }
+
+    /// Accounts bytes a peer caused us to transfer through its requests towards its bandwidth
+    /// limit, i.e. its requests and our responses.
+    pub(crate) fn note_served_bytes(&self, peer_id: PeerId, bytes: usize) {
+        if self.peer_bandwidth_limit.is_none() {
+            return;
+        }
+
+        let now = Instant::now();
+        let mut state = self.state.lock();
+        let bandwidth = state
+            .bandwidth
+            .entry(peer_id)
+            .or_insert_with(|| PeerBandwidth {
+                window_start: now,
+                bytes: 0,
+            });
+        if now.duration_since(bandwidth.window_start) >= BANDWIDTH_WINDOW {
+            bandwidth.window_start = now;
+            bandwidth.bytes = 0;
+        }
+        bandwidth.bytes += bytes as u64;
+    }
+
+    /// Returns whether a peer bandwidth limit is configured.
+    pub(crate) fn has_bandwidth_limit(&self) -> bool {
+        self.peer_bandwidth_limit.is_some()
+    }
+
+    /// Returns whether the peer exceeded its bandwidth limit within the current window.
+    pub(crate) fn exceeds_bandwidth_limit(&self, peer_id: &PeerId) -> bool {
+        let Some(limit) = self.peer_bandwidth_limit else {
+            return false;
+        };
+
+        let state = self.state.lock();
+        state.bandwidth.get(peer_id).is_some_and(|bandwidth| {
+            bandwidth.window_start.elapsed() < BANDWIDTH_WINDOW
+                && bandwidth.bytes > limit.saturating_mul(BANDWIDTH_WINDOW.as_secs())
+        })
+    }
+
+    /// Removes the per-peer counters of a disconnected peer.
+    ///
+    /// The bandwidth window of the peer is kept until it expires, such that a peer can't reset
+    /// its limit by reconnecting. Expired windows of all peers are dropped here as well.
+    pub(crate) fn remove_peer(&self, peer_id: &PeerId) {
+        let mut state = self.state.lock();
+        state.stats.peers.remove(peer_id);
+        state
+            .bandwidth
+            .retain(|_, bandwidth| bandwidth.window_start.elapsed() < BANDWIDTH_WINDOW);
+        drop(state);
+
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            metrics.remove_peer_traffic(peer_id);
+        }
+    }
+
+    /// Returns a snapshot of the accounted traffic.
+    pub(crate) fn stats(&self) -> NetworkStats {
+        self.state.lock().stats.clone()
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_peer_bandwidth_limit(peer_bandwidth_limit: Option<u64>) -> Self {
+        Self {
+            peer_bandwidth_limit,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libp2p::PeerId;
+    use nimiq_network_interface::request::RequestType;
+    use nimiq_test_log::test;
+
+    use super::{Direction, TrafficStats, BANDWIDTH_WINDOW};
+
+    fn traffic_stats(peer_bandwidth_limit: Option<u64>) -> TrafficStats {
+        TrafficStats::with_peer_bandwidth_limit(peer_bandwidth_limit)
+    }
+
+    #[test]
+    fn accounts_requests_and_gossip() {
+        let traffic = traffic_stats(None);
+        let peer_id = PeerId::random();
+        let type_id = RequestType::request(42);
+
+        traffic.note_inbound_request(peer_id, type_id, 10);
+        traffic.note_outbound_response(peer_id, type_id, 100);
+        traffic.note_outbound_request(peer_id, RequestType::message(7), 5);
+        traffic.note_gossipsub_message(Some(peer_id), "blocks", Direction::Inbound, 1000);
+        traffic.note_gossipsub_message(None, "blocks", Direction::Outbound, 500);
+
+        let stats = traffic.stats();
+        assert_eq!(stats.total.bytes_received, 1010);
+        assert_eq!(stats.total.bytes_sent, 605);
+        assert_eq!(stats.total.messages_received, 2);
+        assert_eq!(stats.total.messages_sent, 3);
+
+        let request = stats.request_types[&42];
+        assert_eq!(request.bytes_received, 10);
+        assert_eq!(request.bytes_sent, 100);
+        assert_eq!(stats.request_types[&7].messages_sent, 1);
+
+        let topic = &stats.topics["blocks"];
+        assert_eq!(topic.bytes_received, 1000);
+        assert_eq!(topic.bytes_sent, 500);
+
+        let peer = stats.peers[&peer_id];
+        assert_eq!(peer.bytes_received, 1010);
+        assert_eq!(peer.bytes_sent, 105);
+
+        traffic.remove_peer(&peer_id);
+        assert!(traffic.stats().peers.is_empty());
+    }
+
+    #[test]
+    fn enforces_peer_bandwidth_limit() {
+        let traffic = traffic_stats(Some(1));
+        let peer_id = PeerId::random();
+        let other_peer_id = PeerId::random();
+
+        traffic.note_served_bytes(peer_id, 10);
+        assert!(!traffic.exceeds_bandwidth_limit(&peer_id));
+
+        // Plain traffic accounting doesn't count towards the limit.
+        traffic.note_inbound_response(other_peer_id, RequestType::request(1), 1000);
+        assert!(!traffic.exceeds_bandwidth_limit(&other_peer_id));
+
+        traffic.note_served_bytes(peer_id, 100);
+        assert!(traffic.exceeds_bandwidth_limit(&peer_id));
+
+        // Disconnecting doesn't reset the bandwidth window.
+        traffic.remove_peer(&peer_id);
+        assert!(traffic.exceeds_bandwidth_limit(&peer_id));
+    }
+
+    #[test]
+    fn keeps_bandwidth_window_across_reconnects() {
+        let traffic = traffic_stats(Some(1));
+        let peer_id = PeerId::random();
+        let other_peer_id = PeerId::random();
+
+        traffic.note_served_bytes(peer_id, 100);
+        traffic.note_served_bytes(other_peer_id, 10);
+        assert!(traffic.exceeds_bandwidth_limit(&peer_id));
+
+        // The capped peer disconnects and reconnects within the window.
+        traffic.remove_peer(&peer_id);
+        traffic.note_served_bytes(peer_id, 1);
+        assert!(traffic.exceeds_bandwidth_limit(&peer_id));
+
+        // Once the window expired, the next disconnect drops it.
+        traffic
+            .state
+            .lock()
+            .bandwidth
+            .get_mut(&peer_id)
+            .unwrap()
+            .window_start -= BANDWIDTH_WINDOW;
+        assert!(!traffic.exceeds_bandwidth_limit(&peer_id));
+
+        traffic.remove_peer(&other_peer_id);
+        let state = traffic.state.lock();
+        assert!(!state.bandwidth.contains_key(&peer_id));
+        assert!(state.bandwidth.contains_key(&other_peer_id));
+    }
+}
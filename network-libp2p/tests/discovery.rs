@@ -60,6 +60,8 @@ impl TestNode {
             house_keeping_interval: Duration::from_secs(1),
             keep_alive: true,
             only_secure_ws_connections: false,
+            peer_exchange: true,
+            allowlist: None,
         };
 
         let peer_contact = PeerContact {
@@ -298,3 +300,47 @@ fn test_housekeeping() {
         .get(&old_contact.public_key().clone().to_peer_id())
         .is_none());
 }
+
+#[test]
+fn test_relayed_addresses_are_dialable() {
+    let peer_contact_book = PeerContactBook::new(
+        random_peer_contact(1, Services::FULL_BLOCKS),
+        false,
+        false,
+        false,
+    );
+    let relay_peer_id = PeerId::random();
+    let peer_id = PeerId::random();
+
+    let relayed: Multiaddr =
+        format!("/dns/relay.local/tcp/443/wss/p2p/{relay_peer_id}/p2p-circuit")
+            .parse()
+            .unwrap();
+    assert!(peer_contact_book.is_address_dialable(&relayed));
+
+    let relayed_with_peer_id: Multiaddr =
+        format!("/dns/relay.local/tcp/443/wss/p2p/{relay_peer_id}/p2p-circuit/p2p/{peer_id}")
+            .parse()
+            .unwrap();
+    assert!(peer_contact_book.is_address_dialable(&relayed_with_peer_id));
+
+    // The relay must be identified by its peer ID
+    let without_relay_peer_id: Multiaddr =
+        "/dns/relay.local/tcp/443/wss/p2p-circuit".parse().unwrap();
+    assert!(!peer_contact_book.is_address_dialable(&without_relay_peer_id));
+
+    // The address of the relay must be dialable
+    let loopback_relay: Multiaddr =
+        format!("/ip4/127.0.0.1/tcp/8443/ws/p2p/{relay_peer_id}/p2p-circuit")
+            .parse()
+            .unwrap();
+    assert!(!peer_contact_book.is_address_dialable(&loopback_relay));
+
+    // Multiple relay hops are not supported
+    let multi_hop: Multiaddr = format!(
+        "/dns/relay.local/tcp/443/wss/p2p/{relay_peer_id}/p2p-circuit/p2p/{peer_id}/p2p-circuit"
+    )
+    .parse()
+    .unwrap();
+    assert!(!peer_contact_book.is_address_dialable(&multi_hop));
+}
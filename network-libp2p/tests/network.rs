@@ -4,7 +4,7 @@ use futures::{Stream, StreamExt};
 use libp2p::{
     gossipsub,
     identity::Keypair,
-    multiaddr::{multiaddr, Multiaddr},
+    multiaddr::{multiaddr, Multiaddr, Protocol},
     PeerId,
 };
 use nimiq_bls::KeyPair;
@@ -14,7 +14,7 @@ use nimiq_network_interface::{
 };
 use nimiq_network_libp2p::{
     discovery::{self, peer_contacts::PeerContact},
-    Config, Network,
+    Config, Network, PeerAllowlist,
 };
 use nimiq_test_log::test;
 use nimiq_test_utils::test_rng::test_rng;
@@ -56,6 +56,8 @@ fn network_config(address: Multiaddr) -> Config {
             house_keeping_interval: Duration::from_secs(60),
             keep_alive: false,
             only_secure_ws_connections: false,
+            peer_exchange: true,
+            allowlist: None,
         },
         kademlia: Default::default(),
         gossipsub,
@@ -67,6 +69,10 @@ fn network_config(address: Multiaddr) -> Config {
         only_secure_ws_connections: false,
         allow_loopback_addresses: true,
         dht_quorum: NonZeroU8::new(1).unwrap(),
+        allowlist: None,
+        disable_discovery: false,
+        peer_bandwidth_limit: None,
+        relay_server: false,
     }
 }
 
@@ -111,16 +117,41 @@ impl TestNetwork {
 }
 
 async fn create_connected_networks() -> (Network, Network) {
-    log::debug!("creating connected test networks");
     let mut rng = thread_rng();
     let addr1 = multiaddr![Memory(rng.gen::<u64>())];
     let addr2 = multiaddr![Memory(rng.gen::<u64>())];
 
+    create_connected_networks_with_addresses(addr1, addr2).await
+}
+
+/// Waits for the network to listen and returns its first listen address, with the port assigned
+/// by the operating system if it listens on port 0.
+async fn listen_address(net: &Network) -> Multiaddr {
+    timeout(Duration::from_secs(5), async {
+        loop {
+            if let Some(address) = net.listen_addresses().await.unwrap().into_iter().next() {
+                break address;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("Network is not listening")
+}
+
+async fn create_connected_networks_with_addresses(
+    addr1: Multiaddr,
+    addr2: Multiaddr,
+) -> (Network, Network) {
+    log::debug!("creating connected test networks");
+
     let net1 = Network::new(network_config(addr1.clone())).await;
-    net1.listen_on(vec![addr1.clone()]).await;
+    net1.listen_on(vec![addr1]).await;
+    let addr1 = listen_address(&net1).await;
 
     let net2 = Network::new(network_config(addr2.clone())).await;
-    net2.listen_on(vec![addr2.clone()]).await;
+    net2.listen_on(vec![addr2]).await;
+    let addr2 = listen_address(&net2).await;
 
     log::debug!(address = %addr1, peer_id = %net1.get_local_peer_id(), "Network 1");
     log::debug!(address = %addr2, peer_id = %net2.get_local_peer_id(), "Network 2");
@@ -336,6 +367,23 @@ async fn two_networks_can_connect() {
     assert_eq!(peer1, net1.get_local_peer_id());
 }
 
+#[cfg(feature = "tokio-quic")]
+#[test(tokio::test)]
+async fn two_networks_can_connect_over_quic() {
+    // Let the operating system assign the ports, such that they don't collide.
+    let addr1 = multiaddr![Ip4([127, 0, 0, 1]), Udp(0u16), QuicV1];
+    let addr2 = multiaddr![Ip4([127, 0, 0, 1]), Udp(0u16), QuicV1];
+
+    let (net1, net2) = create_connected_networks_with_addresses(addr1, addr2).await;
+    assert_eq!(net1.get_peers().len(), 1);
+    assert_eq!(net2.get_peers().len(), 1);
+
+    let peer2 = net1.get_peers()[0];
+    let peer1 = net2.get_peers()[0];
+    assert_eq!(peer2, net2.get_local_peer_id());
+    assert_eq!(peer1, net1.get_local_peer_id());
+}
+
 #[test(tokio::test(flavor = "multi_thread", worker_threads = 2))]
 async fn two_networks_can_connect_double_dial() {
     let (net1, net2) = create_double_connected_networks().await;
@@ -392,6 +440,133 @@ async fn connections_are_properly_closed_peers() {
     assert_eq!(net2.get_peers(), &[]);
 }
 
+/// Creates a network that only allows the given peer and one that dials it.
+/// Returns both networks and the address of the first one.
+async fn create_allowlisted_networks(allowed: Option<PeerId>) -> (Network, Network, Multiaddr) {
+    let mut rng = thread_rng();
+    let addr1 = multiaddr![Memory(rng.gen::<u64>())];
+    let addr2 = multiaddr![Memory(rng.gen::<u64>())];
+
+    let net2 = Network::new(network_config(addr2.clone())).await;
+    net2.listen_on(vec![addr2]).await;
+
+    let allowed = allowed.unwrap_or_else(|| net2.get_local_peer_id());
+    let mut config = network_config(addr1.clone());
+    config.allowlist = Some(PeerAllowlist::new([allowed], []));
+    let net1 = Network::new(config).await;
+    net1.listen_on(vec![addr1.clone()]).await;
+
+    (net1, net2, addr1)
+}
+
+#[test(tokio::test)]
+async fn allowlisted_peer_can_connect() {
+    let (net1, net2, addr1) = create_allowlisted_networks(None).await;
+    let mut events1 = net1.subscribe_events();
+
+    net2.dial_address(addr1).await.unwrap();
+
+    let event1 = helper::get_next_peer_event(&mut events1).await;
+    helper::assert_peer_joined(&event1, &net2.get_local_peer_id());
+    assert_eq!(net1.get_peers(), &[net2.get_local_peer_id()]);
+}
+
+#[test(tokio::test)]
+async fn peer_not_on_allowlist_is_denied() {
+    let (net1, net2, addr1) = create_allowlisted_networks(Some(PeerId::random())).await;
+    let mut events1 = net1.subscribe_events();
+
+    // The dial itself may or may not report an error, depending on when the connection is denied.
+    let _ = net2.dial_address(addr1).await;
+
+    // No peer must join within a reasonable time.
+    assert!(timeout(
+        Duration::from_secs(2),
+        helper::get_next_peer_event(&mut events1)
+    )
+    .await
+    .is_err());
+    assert_eq!(net1.get_peers(), &[]);
+
+    // A validator key entry without a known validator record does not allow the peer either.
+    let validator_key = KeyPair::generate(&mut test_rng(false))
+        .public_key
+        .compress();
+    let mut config = network_config(multiaddr![Memory(thread_rng().gen::<u64>())]);
+    config.allowlist = Some(PeerAllowlist::new([], [validator_key]));
+    config.disable_discovery = true;
+    let addr3 = config.peer_contact.addresses[0].clone();
+    let net3 = Network::new(config).await;
+    net3.listen_on(vec![addr3.clone()]).await;
+    let mut events3 = net3.subscribe_events();
+
+    let _ = net2.dial_address(addr3).await;
+    assert!(timeout(
+        Duration::from_secs(2),
+        helper::get_next_peer_event(&mut events3)
+    )
+    .await
+    .is_err());
+    assert_eq!(net3.get_peers(), &[]);
+}
+
+#[test(tokio::test)]
+async fn peers_connect_through_relay() {
+    let mut rng = thread_rng();
+
+    let relay_address = multiaddr![Memory(rng.gen::<u64>())];
+    let mut relay_config = network_config(relay_address.clone());
+    relay_config.relay_server = true;
+    let relay = Network::new(relay_config).await;
+    relay.listen_on(vec![relay_address.clone()]).await;
+    let relay_peer_id = relay.get_local_peer_id();
+
+    // The listener is not directly reachable, it only listens through the relay.
+    let listener = Network::new(network_config(multiaddr![Memory(rng.gen::<u64>())])).await;
+    let listener_peer_id = listener.get_local_peer_id();
+    let mut listener_events = listener.subscribe_events();
+    listener.dial_address(relay_address.clone()).await.unwrap();
+    let event = helper::get_next_peer_event(&mut listener_events).await;
+    helper::assert_peer_joined(&event, &relay_peer_id);
+
+    let relayed_address = relay_address
+        .with(Protocol::P2p(relay_peer_id))
+        .with(Protocol::P2pCircuit);
+    listener.listen_on(vec![relayed_address.clone()]).await;
+
+    // Give the relay some time to accept the reservation of the listener.
+    sleep(Duration::from_secs(1)).await;
+
+    let dialer = Network::new(network_config(multiaddr![Memory(rng.gen::<u64>())])).await;
+    let mut dialer_events = dialer.subscribe_events();
+    dialer
+        .dial_address(relayed_address.with(Protocol::P2p(listener_peer_id)))
+        .await
+        .unwrap();
+
+    // The dialer connects to the relay first and then to the listener through the relay.
+    // As hole punching (DCUtR) can't succeed on the memory transport, the connection must
+    // stay open over the relay.
+    let joined = async {
+        loop {
+            if let NetworkEvent::PeerJoined(peer_id, _) =
+                helper::get_next_peer_event(&mut dialer_events).await
+            {
+                if peer_id == listener_peer_id {
+                    break;
+                }
+            }
+        }
+    };
+    timeout(Duration::from_secs(10), joined)
+        .await
+        .expect("Dialer didn't connect to the listener through the relay");
+
+    sleep(Duration::from_secs(1)).await;
+    assert!(dialer.has_peer(listener_peer_id));
+    assert!(listener.has_peer(dialer.get_local_peer_id()));
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq, PartialOrd)]
 pub struct TestRecord {
     x: i32,
@@ -237,6 +237,8 @@ fn network_config(address: Multiaddr) -> Config {
             house_keeping_interval: Duration::from_secs(60),
             keep_alive: true,
             only_secure_ws_connections: false,
+            peer_exchange: true,
+            allowlist: None,
         },
         kademlia: Default::default(),
         gossipsub,
@@ -248,6 +250,10 @@ fn network_config(address: Multiaddr) -> Config {
         only_secure_ws_connections: false,
         allow_loopback_addresses: true,
         dht_quorum: NonZeroU8::new(1).unwrap(),
+        allowlist: None,
+        disable_discovery: false,
+        peer_bandwidth_limit: None,
+        relay_server: false,
     }
 }
 
@@ -0,0 +1,146 @@
+use std::{collections::HashMap, time::Duration};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::MockAddress;
+
+/// Seed of the random number generator if none is given explicitly.
+const DEFAULT_SEED: u64 = 0;
+
+/// Simulated conditions of a directed link between two mock networks.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LinkConditions {
+    /// Fixed delay added to every message sent over the link.
+    pub latency: Duration,
+    /// Maximum random delay added on top of the latency. The actual jitter is uniformly
+    /// distributed between zero and this value.
+    pub jitter: Duration,
+    /// Probability in the range `[0, 1]` that a message sent over the link is lost.
+    pub drop_rate: f64,
+    /// Bandwidth of the link in bytes per second. Messages are delayed by the time it takes to
+    /// transmit them. `None` means unlimited bandwidth.
+    pub bandwidth: Option<u64>,
+}
+
+/// A change to the simulated network conditions.
+#[derive(Clone, Debug)]
+pub enum NetworkCondition {
+    /// Sets the conditions of the link from the first to the second network.
+    Link(MockAddress, MockAddress, LinkConditions),
+    /// Sets the conditions of all links that don't have explicit conditions set.
+    DefaultLink(LinkConditions),
+    /// Splits the networks into the given groups. Networks can only communicate with networks
+    /// in the same group. All networks that aren't part of any group form an additional group.
+    Partition(Vec<Vec<MockAddress>>),
+    /// Removes the partition, such that all networks can communicate again.
+    Heal,
+}
+
+/// Outcome of sending a message over a link.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Delivery {
+    /// The message arrives after the given delay.
+    Delayed(Duration),
+    /// The message is lost.
+    Dropped,
+}
+
+/// Simulated network conditions of a hub.
+///
+/// Every directed link draws its randomness from its own random number generator, which is
+/// derived from the seed and the addresses of the link. The same number of values is drawn for
+/// every message, regardless of the current conditions. Thus, the fate of the n-th message sent
+/// over a link only depends on the seed and the conditions at the time, and not on the order in
+/// which messages of different links are processed.
+#[derive(Debug)]
+pub(crate) struct NetworkConditions {
+    default_link: LinkConditions,
+    links: HashMap<(MockAddress, MockAddress), LinkConditions>,
+    /// Group index of each partitioned network, if the network is partitioned.
+    partition: Option<HashMap<MockAddress, usize>>,
+    seed: u64,
+    rngs: HashMap<(MockAddress, MockAddress), StdRng>,
+}
+
+impl Default for NetworkConditions {
+    fn default() -> Self {
+        Self::new(DEFAULT_SEED)
+    }
+}
+
+impl NetworkConditions {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            default_link: LinkConditions::default(),
+            links: HashMap::new(),
+            partition: None,
+            seed,
+            rngs: HashMap::new(),
+        }
+    }
+
+    /// Returns the random number generator of the link from `from` to `to`.
+    fn link_rng(&mut self, from: MockAddress, to: MockAddress) -> &mut StdRng {
+        let seed = self.seed;
+        self.rngs.entry((from, to)).or_insert_with(|| {
+            let link = (u64::from(from) << 32) ^ u64::from(to);
+            StdRng::seed_from_u64(seed ^ link.wrapping_mul(0x9e37_79b9_7f4a_7c15))
+        })
+    }
+
+    pub fn apply(&mut self, condition: NetworkCondition) {
+        match condition {
+            NetworkCondition::Link(from, to, conditions) => {
+                self.links.insert((from, to), conditions);
+            }
+            NetworkCondition::DefaultLink(conditions) => self.default_link = conditions,
+            NetworkCondition::Partition(groups) => {
+                let partition = groups
+                    .into_iter()
+                    .enumerate()
+                    .flat_map(|(group, addresses)| {
+                        addresses.into_iter().map(move |address| (address, group))
+                    })
+                    .collect();
+                self.partition = Some(partition);
+            }
+            NetworkCondition::Heal => self.partition = None,
+        }
+    }
+
+    /// Returns whether the two networks are separated by a partition.
+    pub fn is_partitioned(&self, from: MockAddress, to: MockAddress) -> bool {
+        self.partition
+            .as_ref()
+            .is_some_and(|partition| partition.get(&from) != partition.get(&to))
+    }
+
+    /// Determines the fate of a message of `size` bytes sent from `from` to `to`.
+    pub fn delivery(&mut self, from: MockAddress, to: MockAddress, size: usize) -> Delivery {
+        if from == to {
+            return Delivery::Delayed(Duration::ZERO);
+        }
+
+        let rng = self.link_rng(from, to);
+        let drop_sample: f64 = rng.gen();
+        let jitter_sample: f64 = rng.gen();
+
+        if self.is_partitioned(from, to) {
+            return Delivery::Dropped;
+        }
+
+        let link = self.links.get(&(from, to)).unwrap_or(&self.default_link);
+        if drop_sample < link.drop_rate {
+            return Delivery::Dropped;
+        }
+
+        let mut delay = link.latency;
+        if !link.jitter.is_zero() {
+            delay += link.jitter.mul_f64(jitter_sample);
+        }
+        if let Some(bandwidth) = link.bandwidth {
+            delay += Duration::from_secs_f64(size as f64 / bandwidth.max(1) as f64);
+        }
+        Delivery::Delayed(delay)
+    }
+}
@@ -2,13 +2,18 @@ use std::{
     collections::{HashMap, HashSet},
     hash::Hash,
     sync::{atomic::AtomicBool, Arc},
+    time::Duration,
 };
 
+use instant::Instant;
 use nimiq_network_interface::{peer_info::PeerInfo, request::RequestType};
+use nimiq_time::sleep_until;
+use nimiq_utils::spawn;
 use parking_lot::{Mutex, RwLock};
 use tokio::sync::{broadcast, mpsc, oneshot};
 
 use crate::{
+    conditions::{LinkConditions, NetworkCondition, NetworkConditions},
     network::{MockNetwork, MockRequestId},
     MockAddress, MockPeerId, ObservableHashMap,
 };
@@ -37,8 +42,8 @@ pub(crate) struct MockTopic {
     /// Subscribed peer list
     peers: HashSet<MockAddress>,
 
-    /// Sender channel for the topic
-    pub sender: broadcast::Sender<(Arc<Vec<u8>>, MockPeerId)>,
+    /// Sender channel for the topic, carrying the data, the publisher and the time of publishing
+    pub sender: broadcast::Sender<(Arc<Vec<u8>>, MockPeerId, Instant)>,
 }
 
 #[derive(Debug, Default)]
@@ -68,6 +73,9 @@ pub(crate) struct MockHubInner {
 
     /// Arcs to `AtomicBool`s for each network if they're connected.
     pub is_connected: HashMap<MockAddress, Arc<AtomicBool>>,
+
+    /// Simulated link conditions and partitions.
+    pub conditions: Arc<Mutex<NetworkConditions>>,
 }
 
 impl MockHubInner {
@@ -133,6 +141,19 @@ impl MockHub {
         Self::default()
     }
 
+    /// Creates a hub whose simulated network conditions are driven by a random number generator
+    /// seeded with `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        let inner = MockHubInner {
+            conditions: Arc::new(Mutex::new(NetworkConditions::new(seed))),
+            ..Default::default()
+        };
+        Self {
+            last_address: 0,
+            inner: Arc::new(Mutex::new(inner)),
+        }
+    }
+
     pub fn new_address(&mut self) -> MockAddress {
         self.last_address += 1;
         MockAddress(self.last_address)
@@ -148,4 +169,57 @@ impl MockHub {
         log::debug!("New mock network with address={}", address);
         MockNetwork::new(address, Arc::clone(&self.inner))
     }
+
+    /// Applies a change to the simulated network conditions.
+    pub fn apply_condition(&self, condition: NetworkCondition) {
+        log::debug!("Applying network condition: {:?}", condition);
+        let conditions = Arc::clone(&self.inner.lock().conditions);
+        conditions.lock().apply(condition);
+    }
+
+    /// Sets the conditions of the link from `from` to `to`.
+    pub fn set_link_conditions(
+        &self,
+        from: MockAddress,
+        to: MockAddress,
+        conditions: LinkConditions,
+    ) {
+        self.apply_condition(NetworkCondition::Link(from, to, conditions));
+    }
+
+    /// Sets the conditions of all links that don't have explicit conditions set.
+    pub fn set_default_link_conditions(&self, conditions: LinkConditions) {
+        self.apply_condition(NetworkCondition::DefaultLink(conditions));
+    }
+
+    /// Splits the networks into groups that can't communicate with each other. Messages between
+    /// networks of different groups are lost, but the networks stay connected.
+    pub fn partition(&self, groups: Vec<Vec<MockAddress>>) {
+        self.apply_condition(NetworkCondition::Partition(groups));
+    }
+
+    /// Removes the partition, such that all networks can communicate again.
+    pub fn heal(&self) {
+        self.apply_condition(NetworkCondition::Heal);
+    }
+
+    /// Applies the given changes to the simulated network conditions in the background, each after
+    /// its delay relative to the time of this call has elapsed.
+    pub fn schedule_conditions<I>(&self, script: I)
+    where
+        I: IntoIterator<Item = (Duration, NetworkCondition)>,
+    {
+        let start = Instant::now();
+        let mut script: Vec<_> = script.into_iter().collect();
+        script.sort_by_key(|(delay, _)| *delay);
+
+        let conditions = Arc::clone(&self.inner.lock().conditions);
+        spawn(async move {
+            for (delay, condition) in script {
+                sleep_until(start + delay).await;
+                log::debug!("Applying scheduled network condition: {:?}", condition);
+                conditions.lock().apply(condition);
+            }
+        });
+    }
 }
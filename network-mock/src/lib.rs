@@ -1,7 +1,9 @@
+mod conditions;
 mod hub;
 mod network;
 mod observable_hash_map;
 
+pub use conditions::{LinkConditions, NetworkCondition};
 use derive_more::{Display, From, Into};
 pub use hub::MockHub;
 pub use network::{MockId, MockNetwork};
@@ -70,7 +72,10 @@ pub async fn create_mock_validator_network(n: usize, dial: bool) -> Vec<MockNetw
 
 #[cfg(test)]
 pub mod tests {
+    use std::time::Duration;
+
     use futures::{Stream, StreamExt};
+    use instant::Instant;
     use nimiq_keys::{KeyPair, SecureGenerate};
     use nimiq_network_interface::network::{Network, NetworkEvent, SubscribeEvents, Topic};
     use nimiq_test_log::test;
@@ -78,7 +83,11 @@ pub mod tests {
     use nimiq_utils::{spawn, tagged_signing::TaggedSignable};
     use serde::{Deserialize, Serialize};
 
-    use super::{network::MockNetworkError, MockHub, MockPeerId};
+    use super::{
+        conditions::{Delivery, NetworkConditions},
+        network::MockNetworkError,
+        LinkConditions, MockAddress, MockHub, MockPeerId, NetworkCondition,
+    };
 
     pub async fn assert_peer_joined(
         events: &mut SubscribeEvents<MockPeerId>,
@@ -218,4 +227,155 @@ pub mod tests {
             net1.unsubscribe::<TestTopic>().await
         );
     }
+
+    #[test(tokio::test)]
+    async fn test_gossipsub_link_latency() {
+        let mut hub = MockHub::new();
+        let net1 = hub.new_network();
+        let net2 = hub.new_network();
+        net1.dial_mock(&net2);
+
+        let latency = Duration::from_millis(100);
+        hub.set_link_conditions(
+            net2.address(),
+            net1.address(),
+            LinkConditions {
+                latency,
+                ..Default::default()
+            },
+        );
+
+        let mut messages = net1.subscribe::<TestTopic>().await.unwrap();
+        consume_stream(net2.subscribe::<TestTopic>().await.unwrap());
+
+        let start = Instant::now();
+        net2.publish::<TestTopic>(TestRecord { x: 42 })
+            .await
+            .unwrap();
+
+        let (received_message, _peer) = messages.next().await.unwrap();
+        assert_eq!(received_message, TestRecord { x: 42 });
+        assert!(start.elapsed() >= latency);
+    }
+
+    #[test(tokio::test)]
+    async fn test_gossipsub_partition_and_heal() {
+        let mut hub = MockHub::new();
+        let net1 = hub.new_network();
+        let net2 = hub.new_network();
+        let net3 = hub.new_network();
+        net2.dial_mock(&net1);
+        net3.dial_mock(&net1);
+
+        let mut messages2 = net2.subscribe::<TestTopic>().await.unwrap();
+        let mut messages3 = net3.subscribe::<TestTopic>().await.unwrap();
+        consume_stream(net1.subscribe::<TestTopic>().await.unwrap());
+
+        // Network 1 is cut off from the networks 2 and 3.
+        hub.partition(vec![vec![net1.address()]]);
+        net1.publish::<TestTopic>(TestRecord { x: 1 })
+            .await
+            .unwrap();
+        net2.publish::<TestTopic>(TestRecord { x: 2 })
+            .await
+            .unwrap();
+
+        let (received_message, _peer) = messages3.next().await.unwrap();
+        assert_eq!(received_message, TestRecord { x: 2 });
+
+        hub.heal();
+        net1.publish::<TestTopic>(TestRecord { x: 3 })
+            .await
+            .unwrap();
+
+        // Network 2 receives its own message and the one published after healing.
+        let (received_message, _peer) = messages2.next().await.unwrap();
+        assert_eq!(received_message, TestRecord { x: 2 });
+        let (received_message, _peer) = messages2.next().await.unwrap();
+        assert_eq!(received_message, TestRecord { x: 3 });
+        let (received_message, _peer) = messages3.next().await.unwrap();
+        assert_eq!(received_message, TestRecord { x: 3 });
+    }
+
+    #[test]
+    fn network_conditions_are_deterministic() {
+        let from = MockAddress(1);
+        let to = MockAddress(2);
+        let link = LinkConditions {
+            latency: Duration::from_millis(50),
+            jitter: Duration::from_millis(20),
+            drop_rate: 0.3,
+            bandwidth: Some(1000),
+        };
+
+        let deliveries = |seed| {
+            let mut conditions = NetworkConditions::new(seed);
+            conditions.apply(NetworkCondition::Link(from, to, link.clone()));
+            (0..100)
+                .map(|_| conditions.delivery(from, to, 100))
+                .collect::<Vec<_>>()
+        };
+
+        let first = deliveries(42);
+        assert_eq!(first, deliveries(42));
+        assert!(first.contains(&Delivery::Dropped));
+        assert!(first.iter().all(|delivery| match delivery {
+            Delivery::Delayed(delay) => *delay >= Duration::from_millis(150),
+            Delivery::Dropped => true,
+        }));
+
+        // The reverse direction is unaffected.
+        let mut conditions = NetworkConditions::new(42);
+        conditions.apply(NetworkCondition::Link(from, to, link));
+        assert_eq!(
+            conditions.delivery(to, from, 100),
+            Delivery::Delayed(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn network_conditions_are_independent_per_link() {
+        let a = MockAddress(1);
+        let b = MockAddress(2);
+        let c = MockAddress(3);
+        let link = LinkConditions {
+            latency: Duration::from_millis(50),
+            jitter: Duration::from_millis(20),
+            drop_rate: 0.3,
+            bandwidth: None,
+        };
+
+        let conditions = || {
+            let mut conditions = NetworkConditions::new(42);
+            conditions.apply(NetworkCondition::DefaultLink(link.clone()));
+            conditions
+        };
+
+        // Messages over a single link.
+        let mut sequential = conditions();
+        let expected: Vec<_> = (0..50).map(|_| sequential.delivery(a, b, 100)).collect();
+
+        // The same messages interleaved with messages over other links.
+        let mut interleaved = conditions();
+        let actual: Vec<_> = (0..50)
+            .map(|i| {
+                for _ in 0..i % 3 {
+                    interleaved.delivery(a, c, 100);
+                    interleaved.delivery(c, b, 100);
+                }
+                interleaved.delivery(a, b, 100)
+            })
+            .collect();
+        assert_eq!(expected, actual);
+
+        // A partition doesn't shift the outcomes of later messages.
+        let mut partitioned = conditions();
+        partitioned.apply(NetworkCondition::Partition(vec![vec![a]]));
+        for _ in 0..10 {
+            assert_eq!(partitioned.delivery(a, b, 100), Delivery::Dropped);
+        }
+        partitioned.apply(NetworkCondition::Heal);
+        let actual: Vec<_> = (10..50).map(|_| partitioned.delivery(a, b, 100)).collect();
+        assert_eq!(expected[10..], actual[..]);
+    }
 }
@@ -8,6 +8,7 @@ use std::{
 
 use async_trait::async_trait;
 use futures::{stream::BoxStream, StreamExt};
+use instant::Instant;
 use nimiq_network_interface::{
     network::{
         CloseReason, MsgAcceptance, Network, NetworkEvent, PubsubId, SubscribeEvents, Topic,
@@ -19,14 +20,18 @@ use nimiq_network_interface::{
     },
 };
 use nimiq_serde::{Deserialize, DeserializeError, Serialize};
-use nimiq_time::timeout;
-use nimiq_utils::tagged_signing::{TaggedKeyPair, TaggedSignable};
+use nimiq_time::{sleep, sleep_until, timeout};
+use nimiq_utils::{
+    spawn,
+    tagged_signing::{TaggedKeyPair, TaggedSignable},
+};
 use parking_lot::{Mutex, RwLock};
 use thiserror::Error;
 use tokio::sync::{broadcast, mpsc, oneshot};
 use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream, ReceiverStream};
 
 use crate::{
+    conditions::Delivery,
     hub::{MockHubInner, RequestKey, ResponseSender},
     observable_hash_map, MockAddress, MockPeerId, ObservableHashMap,
 };
@@ -189,8 +194,9 @@ impl MockNetwork {
 
         let sender_id = MockPeerId::from(self.address);
         let (tx, rx) = oneshot::channel::<Vec<u8>>();
+        let data = request.serialize_request();
 
-        let (sender, request_id) = {
+        let (sender, request_id, delivery) = {
             let mut hub = self.hub.lock();
 
             let key = RequestKey {
@@ -221,24 +227,43 @@ impl MockNetwork {
             }
             hub.next_request_id += 1;
 
-            (sender, request_id)
+            let delivery = hub
+                .conditions
+                .lock()
+                .delivery(self.address, peer_id.into(), data.len());
+
+            (sender, request_id, delivery)
         };
 
-        let data = request.serialize_request();
+        // A lost request is never dispatched to the peer, such that we run into the timeout if we
+        // expect a response.
+        match delivery {
+            Delivery::Delayed(delay) => {
+                if !delay.is_zero() {
+                    sleep(delay).await;
+                }
 
-        let request = (data, request_id, sender_id);
-        if let Err(e) = sender.send(request).await {
-            log::warn!(
-                "Cannot send request {} from {} to {} - {:?}",
+                let request = (data, request_id, sender_id);
+                if let Err(e) = sender.send(request).await {
+                    log::warn!(
+                        "Cannot send request {} from {} to {} - {:?}",
+                        std::any::type_name::<Req>(),
+                        self.address,
+                        peer_id,
+                        e
+                    );
+                    self.hub.lock().response_senders.remove(&request_id);
+                    return Err(RequestError::OutboundRequest(
+                        OutboundRequestError::SendError,
+                    ));
+                }
+            }
+            Delivery::Dropped => log::debug!(
+                "Dropping request {} from {} to {}",
                 std::any::type_name::<Req>(),
                 self.address,
                 peer_id,
-                e
-            );
-            self.hub.lock().response_senders.remove(&request_id);
-            return Err(RequestError::OutboundRequest(
-                OutboundRequestError::SendError,
-            ));
+            ),
         }
 
         let result = timeout(MockNetwork::REQUEST_TIMEOUT, rx).await;
@@ -326,6 +351,8 @@ impl MockNetwork {
     {
         let mut hub = self.hub.lock();
         let is_connected = Arc::clone(&self.is_connected);
+        let conditions = Arc::clone(&hub.conditions);
+        let address = self.address;
 
         log::debug!(
             "Peer {} subscribing to topic '{}'",
@@ -334,7 +361,7 @@ impl MockNetwork {
         );
 
         // Add this peer to the topic list
-        let sender: &broadcast::Sender<(Arc<Vec<u8>>, MockPeerId)> =
+        let sender: &broadcast::Sender<(Arc<Vec<u8>>, MockPeerId, Instant)> =
             if let Some(topic) = hub.subscribe(topic_name.clone(), self.address) {
                 &topic.sender
             } else {
@@ -343,16 +370,37 @@ impl MockNetwork {
 
         let stream = BroadcastStream::new(sender.subscribe()).filter_map(move |r| {
             let is_connected = Arc::clone(&is_connected);
+            let conditions = Arc::clone(&conditions);
 
             async move {
                 if is_connected.load(Ordering::SeqCst) {
                     match r {
-                        Ok((data, peer_id)) => match T::Item::deserialize_from_vec(&data) {
-                            Ok(item) => return Some((item, peer_id)),
-                            Err(e) => {
-                                log::warn!("Dropped item because deserialization failed: {}", e)
+                        Ok((data, peer_id, published_at)) => {
+                            let delivery =
+                                conditions
+                                    .lock()
+                                    .delivery(peer_id.into(), address, data.len());
+                            match delivery {
+                                Delivery::Delayed(delay) => {
+                                    sleep_until(published_at + delay).await;
+                                }
+                                Delivery::Dropped => {
+                                    log::debug!(
+                                        "Dropping gossipsub message from {} to {}",
+                                        peer_id,
+                                        address
+                                    );
+                                    return None;
+                                }
                             }
-                        },
+
+                            match T::Item::deserialize_from_vec(&data) {
+                                Ok(item) => return Some((item, peer_id)),
+                                Err(e) => {
+                                    log::warn!("Dropped item because deserialization failed: {}", e)
+                                }
+                            }
+                        }
                         Err(BroadcastStreamRecvError::Lagged(_)) => {
                             log::warn!("Mock gossipsub channel is lagging")
                         }
@@ -416,7 +464,7 @@ impl MockNetwork {
             if let Some(topic) = hub.get_topic(&topic_name) {
                 topic
                     .sender
-                    .send((Arc::new(data), self.address.into()))
+                    .send((Arc::new(data), self.address.into(), Instant::now()))
                     .unwrap();
                 Ok(())
             } else {
@@ -624,10 +672,33 @@ impl Network for MockNetwork {
             let mut data = Vec::with_capacity(response.serialized_size());
             response.serialize(&mut data).unwrap();
 
-            responder
-                .sender
-                .send(data)
-                .map_err(|_| MockNetworkError::CantRespond(request_id))
+            let delivery =
+                hub.conditions
+                    .lock()
+                    .delivery(self.address, responder.peer.into(), data.len());
+            match delivery {
+                Delivery::Delayed(delay) if delay.is_zero() => responder
+                    .sender
+                    .send(data)
+                    .map_err(|_| MockNetworkError::CantRespond(request_id)),
+                Delivery::Delayed(delay) => {
+                    spawn(async move {
+                        sleep(delay).await;
+                        let _ = responder.sender.send(data);
+                    });
+                    Ok(())
+                }
+                // A lost response lets the request time out on the requesting side.
+                Delivery::Dropped => {
+                    log::debug!(
+                        "Dropping response to request {} from {} to {}",
+                        request_id,
+                        self.address,
+                        responder.peer
+                    );
+                    Ok(())
+                }
+            }
         } else {
             Err(MockNetworkError::CantRespond(request_id))
         }
@@ -4,7 +4,9 @@ use clap::{ArgGroup, Parser};
 use futures::StreamExt;
 use nimiq_hash::Blake2bHash;
 use nimiq_keys::Address;
-use nimiq_rpc_interface::{blockchain::BlockchainInterface, types::LogType};
+use nimiq_rpc_interface::{
+    blockchain::BlockchainInterface, consensus::ConsensusInterface, types::LogType,
+};
 
 use super::accounts_subcommands::HandleSubcommand;
 use crate::Client;
@@ -143,6 +145,13 @@ pub enum BlockchainCommand {
     /// Lists the current stakes from the staking contract.
     Stakes {},
 
+    /// Returns the current phase and progress of the synchronization with the network.
+    SyncStatus {
+        /// Keep printing the sync status whenever it changes.
+        #[clap(short, long)]
+        follow: bool,
+    },
+
     /// Follow the head of the blockchain.
     FollowHead {
         /// Show the full block instead of only the hash.
@@ -324,6 +333,17 @@ impl HandleSubcommand for BlockchainCommand {
                 println!("{:#?}", client.blockchain.get_active_validators().await?);
             }
 
+            BlockchainCommand::SyncStatus { follow } => {
+                if follow {
+                    let mut stream = client.consensus.subscribe_for_sync_status().await?;
+
+                    while let Some(status) = stream.next().await {
+                        println!("{status:#?}");
+                    }
+                } else {
+                    println!("{:#?}", client.consensus.get_sync_status().await?);
+                }
+            }
             BlockchainCommand::FollowHead { block: show_block } => {
                 if show_block {
                     let mut stream = client
@@ -17,6 +17,9 @@ pub enum NetworkCommand {
         #[clap(short, long)]
         count: bool,
     },
+
+    /// Returns the bytes and messages sent and received per request type, gossipsub topic and peer.
+    Stats {},
 }
 
 #[async_trait]
@@ -33,6 +36,9 @@ impl HandleSubcommand for NetworkCommand {
                     println!("{:#?}", client.network.get_peer_list().await?);
                 }
             }
+            NetworkCommand::Stats {} => {
+                println!("{:#?}", client.network.get_network_stats().await?);
+            }
         }
         Ok(client)
     }
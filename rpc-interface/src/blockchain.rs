@@ -4,8 +4,8 @@ use nimiq_hash::Blake2bHash;
 use nimiq_keys::Address;
 
 use crate::types::{
-    Account, Block, BlockLog, BlockchainState, ExecutedTransaction, Inherent, LogType,
-    PenalizedSlots, RPCData, RPCResult, Slot, Staker, Validator,
+    Account, Block, BlockLog, BlockchainState, Equivocation, ExecutedTransaction, Inherent,
+    LogType, PenalizedSlots, RPCData, RPCResult, Slot, Staker, Validator, ValidatorUptime,
 };
 
 #[nimiq_jsonrpc_derive::proxy(name = "BlockchainProxy", rename_all = "camelCase")]
@@ -163,6 +163,27 @@ pub trait BlockchainInterface {
         address: Address,
     ) -> RPCResult<Staker, BlockchainState, Self::Error>;
 
+    /// Returns the duty statistics of the validators in the epochs from `from_epoch` to
+    /// `to_epoch` (inclusive), optionally only those of the validator with the given address.
+    /// The statistics are taken from the uptime index of the node, which only contains the epochs
+    /// since it was enabled. The statistics of the current epoch cover the blocks up to the latest
+    /// macro block.
+    async fn get_validator_uptime(
+        &mut self,
+        from_epoch: u32,
+        to_epoch: u32,
+        address: Option<Address>,
+    ) -> RPCResult<Vec<ValidatorUptime>, (), Self::Error>;
+
+    /// Returns the equivocations known to the equivocation monitor of the node, optionally only
+    /// those of the validator with the given address. These are the equivocations detected by
+    /// the node, received from other nodes or included in blocks, until the end of their
+    /// reporting window.
+    async fn get_equivocations(
+        &mut self,
+        address: Option<Address>,
+    ) -> RPCResult<Vec<Equivocation>, (), Self::Error>;
+
     /// Subscribes to new block events (retrieves the full block).
     #[stream]
     async fn subscribe_for_head_block(
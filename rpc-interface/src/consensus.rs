@@ -1,10 +1,11 @@
 use async_trait::async_trait;
+use futures::stream::BoxStream;
 use nimiq_hash::Blake2bHash;
 use nimiq_keys::Address;
 use nimiq_primitives::coin::Coin;
 use nimiq_transaction::account::htlc_contract::{AnyHash, PreImage};
 
-use crate::types::{RPCResult, Transaction, ValidityStartHeight};
+use crate::types::{RPCData, RPCResult, SyncStatus, Transaction, ValidityStartHeight};
 
 #[nimiq_jsonrpc_derive::proxy(name = "ConsensusProxy", rename_all = "camelCase")]
 #[async_trait]
@@ -16,6 +17,15 @@ pub trait ConsensusInterface {
     #[allow(clippy::wrong_self_convention)]
     async fn is_consensus_established(&mut self) -> RPCResult<bool, (), Self::Error>;
 
+    /// Returns the current phase and progress of the synchronization with the network.
+    async fn get_sync_status(&mut self) -> RPCResult<SyncStatus, (), Self::Error>;
+
+    /// Subscribes to changes of the synchronization progress.
+    #[stream]
+    async fn subscribe_for_sync_status(
+        &mut self,
+    ) -> Result<BoxStream<'static, RPCData<SyncStatus, ()>>, Self::Error>;
+
     /// Given a serialized transaction, it will return the corresponding transaction struct.
     async fn get_raw_transaction_info(
         &mut self,
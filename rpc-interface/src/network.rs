@@ -1,6 +1,6 @@
 use async_trait::async_trait;
 
-use crate::types::RPCResult;
+use crate::types::{NetworkStats, RPCResult};
 
 #[nimiq_jsonrpc_derive::proxy(name = "NetworkProxy", rename_all = "camelCase")]
 #[async_trait]
@@ -15,4 +15,8 @@ pub trait NetworkInterface {
 
     /// Returns a list with the IDs of all our peers.
     async fn get_peer_list(&mut self) -> RPCResult<Vec<String>, (), Self::Error>;
+
+    /// Returns the number of bytes and messages sent and received, in total, per request type,
+    /// per gossipsub topic and per connected peer.
+    async fn get_network_stats(&mut self) -> RPCResult<NetworkStats, (), Self::Error>;
 }
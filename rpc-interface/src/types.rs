@@ -505,6 +505,37 @@ impl From<nimiq_block::DoubleVoteProof> for DoubleVoteProof {
     }
 }
 
+/// The evidence of an equivocation known to the equivocation monitor of the node. Besides the
+/// proofs that can be included in a block, it can be a pair of conflicting proposals.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum EquivocationEvidence {
+    Fork(ForkProof),
+    DoubleProposal(DoubleProposalProof),
+    DoubleVote(DoubleVoteProof),
+    ConflictingProposals(ConflictingProposals),
+}
+
+impl From<nimiq_block::EquivocationProof> for EquivocationEvidence {
+    fn from(proof: nimiq_block::EquivocationProof) -> Self {
+        match proof.into() {
+            EquivocationProof::Fork(proof) => EquivocationEvidence::Fork(proof),
+            EquivocationProof::DoubleProposal(proof) => EquivocationEvidence::DoubleProposal(proof),
+            EquivocationProof::DoubleVote(proof) => EquivocationEvidence::DoubleVote(proof),
+        }
+    }
+}
+
+/// Two different Tendermint proposals signed by the same validator at the same height, in the
+/// same round. Unlike a double proposal proof, they can't be included in a block.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictingProposals {
+    pub block_number: u32,
+    pub round: u32,
+    pub hashes: [Blake2bHash; 2],
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ExecutedTransaction {
@@ -1356,3 +1387,226 @@ impl MempoolInfo {
         info
     }
 }
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrafficCounters {
+    pub bytes_received: u64,
+    pub bytes_sent: u64,
+    pub messages_received: u64,
+    pub messages_sent: u64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestTypeTraffic {
+    pub type_id: u16,
+    #[serde(flatten)]
+    pub traffic: TrafficCounters,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicTraffic {
+    pub topic: String,
+    #[serde(flatten)]
+    pub traffic: TrafficCounters,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerTraffic {
+    pub peer_id: String,
+    #[serde(flatten)]
+    pub traffic: TrafficCounters,
+}
+
+/// Traffic accounted by the network since startup. Per-peer traffic only covers currently
+/// connected peers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkStats {
+    pub total: TrafficCounters,
+    pub request_types: Vec<RequestTypeTraffic>,
+    pub topics: Vec<TopicTraffic>,
+    pub peers: Vec<PeerTraffic>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SyncPhase {
+    WaitingForPeers,
+    MacroSync,
+    StateSync,
+    LiveSync,
+    Synced,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncCounter {
+    pub processed: u64,
+    /// The number of items to be processed in total, if known.
+    pub target: Option<u64>,
+}
+
+/// Progress of the synchronization with the network.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncStatus {
+    pub phase: SyncPhase,
+    pub epochs: SyncCounter,
+    pub batches: SyncCounter,
+    pub chunks: SyncCounter,
+    pub blocks: SyncCounter,
+    pub bytes_downloaded: u64,
+    /// The estimated number of seconds until the current phase is finished, if known.
+    pub eta: Option<u64>,
+    pub peers: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FailoverRole {
+    Active,
+    Standby,
+}
+
+/// The status of the active/standby failover between several hosts of a validator.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailoverStatus {
+    /// Identifies this host in the lease.
+    pub node_id: String,
+    pub role: FailoverRole,
+    /// The number of role changes since the validator was started.
+    pub transitions: u64,
+    /// The unix timestamp in milliseconds of the last role change, if any.
+    pub last_transition: Option<u64>,
+}
+
+/// Duty statistics of a validator in an epoch, computed from the chain.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorPerformance {
+    pub epoch_number: u32,
+    /// The last block taken into account. This is the head of the chain for the current epoch.
+    pub block_number: u32,
+    /// The number of slots the validator owns in the epoch.
+    pub num_slots: u16,
+    /// The statistics about micro blocks. Not available for past epochs on history nodes, since
+    /// they don't store old micro blocks.
+    pub micro_blocks: Option<MicroBlockPerformance>,
+    /// The number of macro blocks in the epoch.
+    pub macro_blocks: u32,
+    /// The number of macro blocks whose Tendermint justification includes the validator's
+    /// precommit.
+    pub macro_blocks_signed: u32,
+    /// The number of the validator's slots included in the macro block justifications.
+    pub macro_slots_signed: u64,
+    /// The number of Tendermint rounds it took to decide the macro blocks of the epoch.
+    pub tendermint_rounds: u32,
+    /// The number of Tendermint rounds the validator participated in. Only the deciding round of
+    /// a macro block is recorded on the chain, so this is the number of deciding rounds whose
+    /// justification includes the validator's precommit.
+    pub tendermint_rounds_participated: u32,
+    /// The number of skip blocks caused by the validator.
+    pub skip_blocks_caused: u32,
+    /// The slots of the validator that were penalized.
+    pub penalized_slots: Vec<u16>,
+    /// Whether the validator was jailed.
+    pub jailed: bool,
+    /// The rewards paid to the validator.
+    pub rewards: Coin,
+    /// The changes of the validator's settings.
+    pub updates: Vec<ValidatorUpdate>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MicroBlockPerformance {
+    /// The number of micro blocks for which one of the validator's slots was selected as
+    /// producer.
+    pub expected: u32,
+    /// The number of those micro blocks that the validator produced.
+    pub produced: u32,
+    /// The number of skip blocks whose proof includes the validator's signature.
+    pub skip_blocks_signed: u32,
+}
+
+/// A successful `UpdateValidator` transaction. Validators have no commission, the rewards are
+/// paid to the reward address.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorUpdate {
+    pub block_number: u32,
+    pub new_reward_address: Option<Address>,
+    pub new_signing_key: bool,
+    pub new_voting_key: bool,
+    pub new_signal_data: bool,
+}
+
+/// Duty statistics of a validator in an epoch, from the uptime index.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidatorUptime {
+    pub validator_address: Address,
+    #[serde(flatten)]
+    pub performance: ValidatorPerformance,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EquivocationStatus {
+    /// The evidence can't be included in a block.
+    Detected,
+    /// The proof wasn't included in a block yet.
+    Pending,
+    /// The proof was included in a block.
+    Included,
+}
+
+/// An equivocation known to the equivocation monitor of the node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Equivocation {
+    pub validator_address: Address,
+    pub status: EquivocationStatus,
+    /// The block that included the proof, if it is included.
+    pub included_in: Option<u32>,
+    /// Whether the evidence was received from other nodes or seen in a block, rather than
+    /// detected by the node.
+    pub received: bool,
+    /// The time at which the node learned about the equivocation, in milliseconds since the Unix
+    /// epoch.
+    pub detected_at: u64,
+    pub evidence: EquivocationEvidence,
+    /// The serialized evidence, in hex.
+    pub serialized_evidence: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyRotationState {
+    /// The `UpdateValidator` transaction was not included in the chain yet.
+    Submitted,
+    /// The staking contract contains the new keys. They are used from the next epoch on.
+    Confirmed,
+    /// The transaction wasn't included within its validity window.
+    Expired,
+}
+
+/// The status of a pending rotation of our validator keys.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyRotationStatus {
+    /// The new signing key, if it is rotated.
+    pub signing_key: Option<Ed25519PublicKey>,
+    /// The new voting key, if it is rotated.
+    pub voting_key: Option<CompressedPublicKey>,
+    pub transaction_hash: Blake2bHash,
+    pub validity_start_height: u32,
+    pub state: KeyRotationState,
+    /// The first block signed with the new keys, if the rotation is confirmed.
+    pub effective_from: Option<u32>,
+}
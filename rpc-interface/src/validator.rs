@@ -1,7 +1,9 @@
 use async_trait::async_trait;
+use nimiq_hash::Blake2bHash;
 use nimiq_keys::Address;
+use nimiq_primitives::coin::Coin;
 
-use crate::types::RPCResult;
+use crate::types::{FailoverStatus, KeyRotationStatus, RPCResult, ValidatorPerformance};
 
 #[nimiq_jsonrpc_derive::proxy(name = "ValidatorProxy", rename_all = "camelCase")]
 #[async_trait]
@@ -11,10 +13,10 @@ pub trait ValidatorInterface {
     /// Returns our validator address.
     async fn get_address(&mut self) -> RPCResult<Address, (), Self::Error>;
 
-    /// Returns our validator signing key.
+    /// Returns our validator signing key. Fails if the keys are held by a remote signer.
     async fn get_signing_key(&mut self) -> RPCResult<String, (), Self::Error>;
 
-    /// Returns our validator voting key.
+    /// Returns our validator voting key. Fails if the keys are held by a remote signer.
     async fn get_voting_key(&mut self) -> RPCResult<String, (), Self::Error>;
 
     /// Updates the configuration setting to automatically reactivate our validator.
@@ -28,4 +30,44 @@ pub trait ValidatorInterface {
 
     /// Returns if our validator is currently synced.
     async fn is_validator_synced(&mut self) -> RPCResult<bool, (), Self::Error>;
+
+    /// Returns whether this host is the active or a standby host of our validator. Fails if
+    /// active/standby failover is not enabled.
+    async fn get_failover_status(&mut self) -> RPCResult<FailoverStatus, (), Self::Error>;
+
+    /// Returns the duty statistics of our validator in the given epoch, or in the current epoch
+    /// if none is given. They are computed from the chain, so they are also available for past
+    /// epochs on history nodes.
+    async fn get_validator_performance(
+        &mut self,
+        epoch_number: Option<u32>,
+    ) -> RPCResult<ValidatorPerformance, (), Self::Error>;
+
+    /// Returns the hex encoded signing history of our validator, which records the micro blocks
+    /// and skip blocks we signed. It can be imported on another host with the same keys.
+    async fn export_signing_history(&mut self) -> RPCResult<String, (), Self::Error>;
+
+    /// Imports a hex encoded signing history exported from another host. Nothing is imported if
+    /// it conflicts with what our validator already signed. Returns the number of imported records.
+    async fn import_signing_history(
+        &mut self,
+        signing_history: String,
+    ) -> RPCResult<usize, (), Self::Error>;
+
+    /// Starts rotating our validator keys to the given hex encoded secret keys. At least one of
+    /// them must be given. Sends an `UpdateValidator` transaction signed with the validator key,
+    /// whose wallet must be unlocked, and pays the fee with our fee key. The validator keeps
+    /// signing with its current keys until the next election block and switches to the new ones
+    /// afterwards. Returns the hash of the transaction.
+    async fn rotate_keys(
+        &mut self,
+        new_signing_secret_key: Option<String>,
+        new_voting_secret_key: Option<String>,
+        fee: Coin,
+    ) -> RPCResult<Blake2bHash, (), Self::Error>;
+
+    /// Returns the status of the pending rotation of our validator keys, if any.
+    async fn get_key_rotation_status(
+        &mut self,
+    ) -> RPCResult<Option<KeyRotationStatus>, (), Self::Error>;
 }
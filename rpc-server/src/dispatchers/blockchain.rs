@@ -11,21 +11,75 @@ use nimiq_rpc_interface::{
     blockchain::BlockchainInterface,
     types::{
         is_of_log_type_and_related_to_addresses, Account, Block, BlockLog, BlockchainState,
+        ConflictingProposals, Equivocation, EquivocationEvidence, EquivocationStatus,
         ExecutedTransaction, Inherent, LogType, PenalizedSlots, RPCData, RPCResult, Slot, Staker,
-        Validator,
+        Validator, ValidatorUptime,
     },
 };
+use nimiq_serde::Serialize;
+use nimiq_validator::{
+    equivocation::{
+        EquivocationMonitor, EquivocationRecord, EquivocationStatus as BEquivocationStatus,
+        Evidence,
+    },
+    uptime::UptimeIndex,
+};
 use tokio_stream::wrappers::BroadcastStream;
 
+use super::validator::to_validator_performance;
 use crate::error::Error;
 
 pub struct BlockchainDispatcher {
     blockchain: BlockchainProxy,
+    uptime_index: Option<UptimeIndex>,
+    equivocation_monitor: Option<EquivocationMonitor>,
 }
 
 impl BlockchainDispatcher {
-    pub fn new(blockchain: BlockchainProxy) -> Self {
-        Self { blockchain }
+    pub fn new(
+        blockchain: BlockchainProxy,
+        uptime_index: Option<UptimeIndex>,
+        equivocation_monitor: Option<EquivocationMonitor>,
+    ) -> Self {
+        Self {
+            blockchain,
+            uptime_index,
+            equivocation_monitor,
+        }
+    }
+}
+
+fn to_equivocation(record: EquivocationRecord) -> Equivocation {
+    let status = match record.status() {
+        BEquivocationStatus::Detected => EquivocationStatus::Detected,
+        BEquivocationStatus::Pending => EquivocationStatus::Pending,
+        BEquivocationStatus::Included(_) => EquivocationStatus::Included,
+    };
+    let validator_address = record.evidence.validator_address().clone();
+    let (evidence, serialized_evidence) = match record.evidence {
+        Evidence::Proof(proof) => {
+            let serialized_evidence = proof.serialize_to_vec();
+            (proof.into(), serialized_evidence)
+        }
+        Evidence::DoubleProposal(proposals) => {
+            let (hash1, hash2) = proposals.header_hashes();
+            let evidence = EquivocationEvidence::ConflictingProposals(ConflictingProposals {
+                block_number: proposals.block_number(),
+                round: proposals.round(),
+                hashes: [hash1, hash2],
+            });
+            (evidence, proposals.serialize_to_vec())
+        }
+    };
+
+    Equivocation {
+        validator_address,
+        status,
+        included_in: record.included_in,
+        received: record.received,
+        detected_at: record.detected_at,
+        evidence,
+        serialized_evidence: hex::encode(serialized_evidence),
     }
 }
 
@@ -549,6 +603,52 @@ impl BlockchainInterface for BlockchainDispatcher {
         }
     }
 
+    async fn get_validator_uptime(
+        &mut self,
+        from_epoch: u32,
+        to_epoch: u32,
+        address: Option<Address>,
+    ) -> RPCResult<Vec<ValidatorUptime>, (), Self::Error> {
+        let uptime_index = self
+            .uptime_index
+            .as_ref()
+            .ok_or(Error::UptimeIndexDisabled)?;
+        if from_epoch > to_epoch {
+            return Err(Error::InvalidArgument(format!(
+                "from_epoch {} is after to_epoch {}",
+                from_epoch, to_epoch
+            )));
+        }
+
+        let uptime = uptime_index
+            .get_epochs(from_epoch, to_epoch, address.as_ref())
+            .into_iter()
+            .flat_map(|(_, uptime)| uptime.validators)
+            .map(|(validator_address, performance)| ValidatorUptime {
+                validator_address,
+                performance: to_validator_performance(performance),
+            })
+            .collect::<Vec<_>>();
+        Ok(uptime.into())
+    }
+
+    async fn get_equivocations(
+        &mut self,
+        address: Option<Address>,
+    ) -> RPCResult<Vec<Equivocation>, (), Self::Error> {
+        let equivocation_monitor = self
+            .equivocation_monitor
+            .as_ref()
+            .ok_or(Error::EquivocationMonitorDisabled)?;
+
+        let equivocations = equivocation_monitor
+            .get(address.as_ref())
+            .into_iter()
+            .map(to_equivocation)
+            .collect::<Vec<_>>();
+        Ok(equivocations.into())
+    }
+
     #[stream]
     async fn subscribe_for_head_block(
         &mut self,
@@ -742,3 +842,88 @@ impl BlockchainInterface for BlockchainDispatcher {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use nimiq_block::MacroHeader;
+    use nimiq_database::mdbx::MdbxDatabase;
+    use nimiq_hash::Hash;
+    use nimiq_tendermint::{ProposalMessage, SignedProposalMessage};
+    use nimiq_test_log::test;
+    use nimiq_test_utils::{
+        block_production::TemporaryBlockProducer, blockchain::fill_micro_blocks,
+    };
+    use nimiq_validator::aggregation::tendermint::proposal::{Header, SignedProposal};
+
+    use super::*;
+
+    /// Returns a proposal for the next macro block along with its header.
+    fn sign_proposal(
+        producer: &TemporaryBlockProducer,
+        extra_data: Vec<u8>,
+    ) -> (MacroHeader, SignedProposal) {
+        let blockchain = producer.blockchain.read();
+        let header = producer
+            .producer
+            .next_macro_block_proposal(
+                &blockchain,
+                blockchain.timestamp() + Policy::BLOCK_SEPARATION_TIME,
+                0,
+                extra_data,
+            )
+            .header;
+        let data = SignedProposal::hash(&header, 0, None).serialize_to_vec();
+        let proposal = SignedProposalMessage {
+            message: ProposalMessage {
+                proposal: Header::<()>(header.clone(), None),
+                round: 0,
+                valid_round: None,
+            },
+            signature: (producer.producer.signing_key.sign(&data), 0),
+        };
+        (header, proposal.into())
+    }
+
+    #[test(tokio::test)]
+    async fn it_returns_conflicting_proposals_as_separate_evidence() {
+        let producer = TemporaryBlockProducer::new();
+        let blockchain = BlockchainProxy::from(Arc::clone(&producer.blockchain));
+
+        let mut dispatcher = BlockchainDispatcher::new(blockchain.clone(), None, None);
+        assert!(matches!(
+            dispatcher.get_equivocations(None).await,
+            Err(Error::EquivocationMonitorDisabled)
+        ));
+
+        let monitor = EquivocationMonitor::new(
+            MdbxDatabase::new_volatile(Default::default()).unwrap(),
+            Arc::clone(&producer.blockchain),
+            false,
+        );
+        fill_micro_blocks(&producer.producer, &producer.blockchain);
+        let (header1, proposal1) = sign_proposal(&producer, vec![1]);
+        let (header2, proposal2) = sign_proposal(&producer, vec![2]);
+        monitor.observe_proposal(&proposal1);
+        monitor.observe_proposal(&proposal2);
+
+        let mut dispatcher = BlockchainDispatcher::new(blockchain, None, Some(monitor));
+        let equivocations = dispatcher.get_equivocations(None).await.unwrap().data;
+        assert_eq!(equivocations.len(), 1);
+
+        let equivocation = &equivocations[0];
+        assert_eq!(equivocation.status, EquivocationStatus::Detected);
+        assert!(!equivocation.received);
+        assert_eq!(equivocation.included_in, None);
+        let EquivocationEvidence::ConflictingProposals(proposals) = &equivocation.evidence else {
+            panic!("Unexpected evidence: {:?}", equivocation.evidence);
+        };
+        assert_eq!(proposals.block_number, header1.block_number);
+        assert_eq!(proposals.round, 0);
+        assert_eq!(
+            proposals.hashes,
+            [header1.hash::<Blake2bHash>(), header2.hash::<Blake2bHash>()]
+        );
+    }
+}
@@ -1,17 +1,21 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
+use futures::{stream::BoxStream, StreamExt};
 use nimiq_blockchain_interface::AbstractBlockchain;
 use nimiq_blockchain_proxy::BlockchainReadProxy;
 use nimiq_bls::{KeyPair as BlsKeyPair, SecretKey as BlsSecretKey};
-use nimiq_consensus::ConsensusProxy;
+use nimiq_consensus::{sync::progress, ConsensusProxy};
 use nimiq_hash::{Blake2bHash, Hash};
 use nimiq_keys::{Address, Ed25519PublicKey, KeyPair, PrivateKey};
-use nimiq_network_libp2p::Network;
+use nimiq_network_libp2p::{Network, PeerId};
 use nimiq_primitives::{coin::Coin, networks::NetworkId};
 use nimiq_rpc_interface::{
     consensus::ConsensusInterface,
-    types::{RPCResult, Transaction as RPCTransaction, ValidityStartHeight},
+    types::{
+        RPCData, RPCResult, SyncCounter, SyncPhase, SyncStatus, Transaction as RPCTransaction,
+        ValidityStartHeight,
+    },
 };
 use nimiq_serde::{Deserialize, Serialize};
 use nimiq_transaction::{
@@ -64,6 +68,36 @@ impl ConsensusDispatcher {
     }
 }
 
+fn to_sync_counter(counter: progress::SyncCounter) -> SyncCounter {
+    SyncCounter {
+        processed: counter.processed,
+        target: counter.target,
+    }
+}
+
+fn to_sync_status(status: progress::SyncStatus<PeerId>) -> SyncStatus {
+    SyncStatus {
+        phase: match status.phase {
+            progress::SyncPhase::WaitingForPeers => SyncPhase::WaitingForPeers,
+            progress::SyncPhase::MacroSync => SyncPhase::MacroSync,
+            progress::SyncPhase::StateSync => SyncPhase::StateSync,
+            progress::SyncPhase::LiveSync => SyncPhase::LiveSync,
+            progress::SyncPhase::Synced => SyncPhase::Synced,
+        },
+        epochs: to_sync_counter(status.epochs),
+        batches: to_sync_counter(status.batches),
+        chunks: to_sync_counter(status.chunks),
+        blocks: to_sync_counter(status.blocks),
+        bytes_downloaded: status.bytes_downloaded,
+        eta: status.eta.map(|eta| eta.as_secs()),
+        peers: status
+            .peers
+            .into_iter()
+            .map(|peer_id| peer_id.to_string())
+            .collect(),
+    }
+}
+
 fn transaction_to_hex_string(transaction: &Transaction) -> String {
     hex::encode(transaction.serialize_to_vec())
 }
@@ -77,6 +111,21 @@ impl ConsensusInterface for ConsensusDispatcher {
         Ok(self.consensus.is_established().into())
     }
 
+    async fn get_sync_status(&mut self) -> RPCResult<SyncStatus, (), Self::Error> {
+        Ok(to_sync_status(self.consensus.sync_status()).into())
+    }
+
+    #[stream]
+    async fn subscribe_for_sync_status(
+        &mut self,
+    ) -> Result<BoxStream<'static, RPCData<SyncStatus, ()>>, Self::Error> {
+        Ok(self
+            .consensus
+            .subscribe_sync_status()
+            .map(|status| to_sync_status(status).into())
+            .boxed())
+    }
+
     async fn get_raw_transaction_info(
         &mut self,
         raw_tx: String,
@@ -3,7 +3,12 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use nimiq_network_interface::network::Network as InterfaceNetwork;
 use nimiq_network_libp2p::Network;
-use nimiq_rpc_interface::{network::NetworkInterface, types::RPCResult};
+use nimiq_rpc_interface::{
+    network::NetworkInterface,
+    types::{
+        NetworkStats, PeerTraffic, RPCResult, RequestTypeTraffic, TopicTraffic, TrafficCounters,
+    },
+};
 
 use crate::error::Error;
 
@@ -39,4 +44,54 @@ impl NetworkInterface for NetworkDispatcher {
             .collect::<Vec<_>>()
             .into())
     }
+
+    async fn get_network_stats(&mut self) -> RPCResult<NetworkStats, (), Self::Error> {
+        let stats = self.network.network_stats();
+
+        let mut request_types: Vec<_> = stats
+            .request_types
+            .into_iter()
+            .map(|(type_id, traffic)| RequestTypeTraffic {
+                type_id,
+                traffic: to_traffic_counters(traffic),
+            })
+            .collect();
+        request_types.sort_by_key(|traffic| traffic.type_id);
+
+        let mut topics: Vec<_> = stats
+            .topics
+            .into_iter()
+            .map(|(topic, traffic)| TopicTraffic {
+                topic,
+                traffic: to_traffic_counters(traffic),
+            })
+            .collect();
+        topics.sort_by(|a, b| a.topic.cmp(&b.topic));
+
+        let peers = stats
+            .peers
+            .into_iter()
+            .map(|(peer_id, traffic)| PeerTraffic {
+                peer_id: peer_id.to_string(),
+                traffic: to_traffic_counters(traffic),
+            })
+            .collect();
+
+        Ok(NetworkStats {
+            total: to_traffic_counters(stats.total),
+            request_types,
+            topics,
+            peers,
+        }
+        .into())
+    }
+}
+
+fn to_traffic_counters(traffic: nimiq_network_libp2p::TrafficCounters) -> TrafficCounters {
+    TrafficCounters {
+        bytes_received: traffic.bytes_received,
+        bytes_sent: traffic.bytes_sent,
+        messages_received: traffic.messages_received,
+        messages_sent: traffic.messages_sent,
+    }
 }
@@ -1,27 +1,102 @@
-use std::sync::atomic::Ordering;
+use std::{
+    io,
+    sync::{atomic::Ordering, Arc},
+};
 
 use async_trait::async_trait;
+use nimiq_blockchain_interface::AbstractBlockchain;
+use nimiq_blockchain_proxy::BlockchainProxy;
+use nimiq_bls::{KeyPair as BlsKeyPair, SecretKey as BlsSecretKey};
 use nimiq_consensus::ConsensusProxy;
-use nimiq_keys::Address;
+use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_keys::{Address, KeyPair, PrivateKey};
 use nimiq_network_libp2p::Network;
-use nimiq_rpc_interface::{types::RPCResult, validator::ValidatorInterface};
-use nimiq_serde::Serialize;
-use nimiq_validator::validator::ValidatorProxy;
+use nimiq_primitives::{coin::Coin, policy::Policy};
+use nimiq_rpc_interface::{
+    types::{
+        FailoverRole, FailoverStatus, KeyRotationState, KeyRotationStatus, MicroBlockPerformance,
+        RPCResult, ValidatorPerformance, ValidatorUpdate,
+    },
+    validator::ValidatorInterface,
+};
+use nimiq_serde::{Deserialize, Serialize};
+use nimiq_validator::{
+    failover, key_rotation, performance, signing_history::SigningHistoryExport,
+    validator::ValidatorProxy,
+};
+use parking_lot::RwLock;
+use tokio::task::spawn_blocking;
 
-use crate::error::Error;
+use crate::{error::Error, wallets::UnlockedWallets};
+
+pub(crate) fn to_validator_performance(
+    performance: performance::ValidatorPerformance,
+) -> ValidatorPerformance {
+    ValidatorPerformance {
+        epoch_number: performance.epoch_number,
+        block_number: performance.block_number,
+        num_slots: performance.num_slots,
+        micro_blocks: performance
+            .micro_blocks
+            .map(|micro_blocks| MicroBlockPerformance {
+                expected: micro_blocks.expected,
+                produced: micro_blocks.produced,
+                skip_blocks_signed: micro_blocks.skip_blocks_signed,
+            }),
+        macro_blocks: performance.macro_blocks,
+        macro_blocks_signed: performance.macro_blocks_signed,
+        macro_slots_signed: performance.macro_slots_signed,
+        tendermint_rounds: performance.tendermint_rounds,
+        tendermint_rounds_participated: performance.tendermint_rounds_participated,
+        skip_blocks_caused: performance.skip_blocks_caused,
+        penalized_slots: performance.penalized_slots.into_iter().collect(),
+        jailed: performance.jailed,
+        rewards: performance.rewards,
+        updates: performance
+            .updates
+            .into_iter()
+            .map(|update| ValidatorUpdate {
+                block_number: update.block_number,
+                new_reward_address: update.new_reward_address,
+                new_signing_key: update.new_signing_key,
+                new_voting_key: update.new_voting_key,
+                new_signal_data: update.new_signal_data,
+            })
+            .collect(),
+    }
+}
 
 pub struct ValidatorDispatcher {
     validator: ValidatorProxy,
     consensus: ConsensusProxy<Network>,
+    unlocked_wallets: Option<Arc<RwLock<UnlockedWallets>>>,
 }
 
 impl ValidatorDispatcher {
-    pub fn new(validator: ValidatorProxy, consensus: ConsensusProxy<Network>) -> Self {
+    pub fn new(
+        validator: ValidatorProxy,
+        consensus: ConsensusProxy<Network>,
+        unlocked_wallets: Option<Arc<RwLock<UnlockedWallets>>>,
+    ) -> Self {
         ValidatorDispatcher {
             validator,
             consensus,
+            unlocked_wallets,
         }
     }
+
+    /// Tries to fetch the key pair for the wallet with the given address.
+    fn get_wallet_keypair(&self, address: &Address) -> Result<KeyPair, Error> {
+        Ok(self
+            .unlocked_wallets
+            .as_ref()
+            .ok_or_else(|| Error::UnlockedWalletNotFound(address.clone()))?
+            .read()
+            .get(address)
+            .ok_or_else(|| Error::UnlockedWalletNotFound(address.clone()))?
+            .key_pair
+            .clone())
+    }
 }
 
 #[nimiq_jsonrpc_derive::service(rename_all = "camelCase")]
@@ -34,18 +109,21 @@ impl ValidatorInterface for ValidatorDispatcher {
     }
 
     async fn get_signing_key(&mut self) -> RPCResult<String, (), Self::Error> {
-        Ok(hex::encode(self.validator.signing_key.read().private.serialize_to_vec()).into())
+        let keys = self
+            .validator
+            .signer
+            .local_keys()
+            .ok_or(Error::RemoteSigner)?;
+        Ok(hex::encode(keys.signing_key().private.serialize_to_vec()).into())
     }
 
     async fn get_voting_key(&mut self) -> RPCResult<String, (), Self::Error> {
-        Ok(hex::encode(
-            self.validator
-                .voting_key
-                .read()
-                .secret_key
-                .serialize_to_vec(),
-        )
-        .into())
+        let keys = self
+            .validator
+            .signer
+            .local_keys()
+            .ok_or(Error::RemoteSigner)?;
+        Ok(hex::encode(keys.voting_key().secret_key.serialize_to_vec()).into())
     }
 
     async fn set_automatic_reactivation(
@@ -69,4 +147,133 @@ impl ValidatorInterface for ValidatorDispatcher {
         let is_synced = self.consensus.is_ready_for_validation();
         Ok(is_synced.into())
     }
+
+    async fn get_failover_status(&mut self) -> RPCResult<FailoverStatus, (), Self::Error> {
+        let status = self
+            .validator
+            .failover_status
+            .read()
+            .clone()
+            .ok_or(Error::FailoverDisabled)?;
+        Ok(FailoverStatus {
+            node_id: status.node_id,
+            role: match status.role {
+                failover::FailoverRole::Active => FailoverRole::Active,
+                failover::FailoverRole::Standby => FailoverRole::Standby,
+            },
+            transitions: status.transitions,
+            last_transition: status.last_transition,
+        }
+        .into())
+    }
+
+    async fn get_validator_performance(
+        &mut self,
+        epoch_number: Option<u32>,
+    ) -> RPCResult<ValidatorPerformance, (), Self::Error> {
+        let BlockchainProxy::Full(blockchain) = &self.consensus.blockchain else {
+            return Err(Error::NotSupportedForLightBlockchain);
+        };
+        let blockchain = Arc::clone(blockchain);
+        let epoch_number =
+            epoch_number.unwrap_or_else(|| Policy::epoch_at(blockchain.read().block_number()));
+        let validator_address = self.validator.validator_address.read().clone();
+
+        // Walking an epoch takes a while, so it is done on the blocking thread pool.
+        let performance = spawn_blocking(move || {
+            performance::validator_performance(&blockchain, &validator_address, epoch_number)
+        })
+        .await
+        .map_err(|error| Error::Io(io::Error::new(io::ErrorKind::Other, error)))?
+        .map_err(Error::ValidatorPerformance)?;
+        Ok(to_validator_performance(performance).into())
+    }
+
+    async fn export_signing_history(&mut self) -> RPCResult<String, (), Self::Error> {
+        let export = self.validator.signing_history.export();
+        Ok(hex::encode(export.serialize_to_vec()).into())
+    }
+
+    async fn import_signing_history(
+        &mut self,
+        signing_history: String,
+    ) -> RPCResult<usize, (), Self::Error> {
+        let export = SigningHistoryExport::deserialize_from_vec(&hex::decode(signing_history)?)?;
+        let num_records = self.validator.signing_history.import(&export)?;
+
+        log::info!(num_records, "Imported signing history.");
+        Ok(num_records.into())
+    }
+
+    async fn rotate_keys(
+        &mut self,
+        new_signing_secret_key: Option<String>,
+        new_voting_secret_key: Option<String>,
+        fee: Coin,
+    ) -> RPCResult<Blake2bHash, (), Self::Error> {
+        let signing_key = new_signing_secret_key
+            .map(|key| {
+                PrivateKey::deserialize_from_vec(&hex::decode(key)?)
+                    .map(KeyPair::from)
+                    .map_err(|_| Error::InvalidArgument("Signing Key".to_string()))
+            })
+            .transpose()?;
+        let voting_key = new_voting_secret_key
+            .map(|key| {
+                BlsSecretKey::deserialize_from_vec(&hex::decode(key)?)
+                    .map(BlsKeyPair::from)
+                    .map_err(|_| Error::InvalidArgument("Voting Key".to_string()))
+            })
+            .transpose()?;
+
+        let validator_address = self.validator.validator_address.read().clone();
+        let cold_key = self.get_wallet_keypair(&validator_address)?;
+
+        let BlockchainProxy::Full(blockchain) = &self.consensus.blockchain else {
+            return Err(Error::NotSupportedForLightBlockchain);
+        };
+        let transaction = self.validator.key_rotation.start(
+            &blockchain.read(),
+            &validator_address,
+            self.validator.signer.as_ref(),
+            &cold_key,
+            signing_key,
+            voting_key,
+            fee,
+        )?;
+        let transaction_hash = transaction.hash::<Blake2bHash>();
+
+        self.consensus
+            .send_transaction(transaction)
+            .await
+            .map_err(Error::NetworkError)?;
+        Ok(transaction_hash.into())
+    }
+
+    async fn get_key_rotation_status(
+        &mut self,
+    ) -> RPCResult<Option<KeyRotationStatus>, (), Self::Error> {
+        let BlockchainProxy::Full(blockchain) = &self.consensus.blockchain else {
+            return Err(Error::NotSupportedForLightBlockchain);
+        };
+        let validator_address = self.validator.validator_address.read().clone();
+
+        let status = self
+            .validator
+            .key_rotation
+            .status(&blockchain.read(), &validator_address)
+            .map(|status| KeyRotationStatus {
+                signing_key: status.signing_key,
+                voting_key: status.voting_key.map(|key| key.compress()),
+                transaction_hash: status.transaction_hash,
+                validity_start_height: status.validity_start_height,
+                state: match status.state {
+                    key_rotation::KeyRotationState::Submitted => KeyRotationState::Submitted,
+                    key_rotation::KeyRotationState::Confirmed => KeyRotationState::Confirmed,
+                    key_rotation::KeyRotationState::Expired => KeyRotationState::Expired,
+                },
+                effective_from: status.effective_from,
+            });
+        Ok(status.into())
+    }
 }
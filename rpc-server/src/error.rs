@@ -89,6 +89,27 @@ pub enum Error {
 
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+
+    #[error("Signing history error: {0}")]
+    SigningHistory(#[from] nimiq_validator::signing_history::SigningHistoryError),
+
+    #[error("The validator keys are held by a remote signer")]
+    RemoteSigner,
+
+    #[error("Active/standby failover is not enabled")]
+    FailoverDisabled,
+
+    #[error("Failed to compute the validator performance: {0}")]
+    ValidatorPerformance(nimiq_blockchain_interface::BlockchainError),
+
+    #[error("The validator uptime index is not enabled")]
+    UptimeIndexDisabled,
+
+    #[error("The equivocation monitor is not enabled")]
+    EquivocationMonitorDisabled,
+
+    #[error("Key rotation error: {0}")]
+    KeyRotation(#[from] nimiq_validator::key_rotation::KeyRotationError),
 }
 
 impl From<Error> for RpcError {
@@ -142,7 +142,9 @@ async fn main_inner() -> Result<(), Error> {
         passive: false,
         sync_mode: None,
         network: None,
+        trusted_checkpoint: None,
         prove: false,
+        command: None,
     };
 
     // Parse config file - this will obey the `--config` command line option.
@@ -237,6 +239,7 @@ async fn main_inner() -> Result<(), Error> {
             metrics_config.addr,
             client.blockchain(),
             client.mempool(),
+            client.validator_proxy(),
             client.consensus_proxy(),
             client.network(),
             &[],
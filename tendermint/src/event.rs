@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+use crate::utils::Step;
+
+/// Structured events emitted by the state machine through [`crate::Protocol::on_event`].
+///
+/// They describe why a height needed the rounds it needed. A sequence of events recorded for a
+/// height can be replayed with [`crate::replay::replay`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Event<ProposalHash> {
+    /// A new round was started.
+    RoundStarted {
+        round: u32,
+        /// Whether f+1 contributions for the round were seen such that the rounds in between
+        /// were skipped, rather than the previous round concluding.
+        skipped_ahead: bool,
+    },
+    /// This node set the proposal it proposes in `round`.
+    Proposed {
+        round: u32,
+        valid_round: Option<u32>,
+        proposal_hash: ProposalHash,
+    },
+    /// A proposal for `round` was received and verified.
+    ProposalVerified {
+        round: u32,
+        valid_round: Option<u32>,
+        proposal_hash: ProposalHash,
+        valid: bool,
+    },
+    /// The timeout of `step` in `round` elapsed.
+    Timeout { round: u32, step: Step },
+    /// The aggregation for `round` and `step` was acted upon after reaching 2f+1 votes, either in
+    /// total or for a single proposal. Contains the number of votes for each proposal hash, where
+    /// `None` is a vote against all proposals.
+    Votes {
+        round: u32,
+        step: Step,
+        votes: Vec<(Option<ProposalHash>, usize)>,
+    },
+}
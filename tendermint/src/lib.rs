@@ -1,11 +1,14 @@
 extern crate log;
 
+pub(crate) mod event;
 pub(crate) mod protocol;
+pub mod replay;
 pub(crate) mod state;
 mod states;
 pub(crate) mod tendermint;
 pub(crate) mod utils;
 
+pub use event::Event;
 pub use protocol::*;
 pub use state::*;
 pub use tendermint::*;
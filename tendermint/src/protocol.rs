@@ -1,8 +1,14 @@
-use futures::{future::BoxFuture, stream::BoxStream};
+use std::time::Duration;
+
+use futures::{
+    future::{BoxFuture, FutureExt},
+    stream::BoxStream,
+};
 use nimiq_collections::BitSet;
+use nimiq_time::sleep;
 use serde::{Deserialize, Serialize};
 
-use crate::utils::Step;
+use crate::{event::Event, utils::Step};
 
 /// Error for proposal verification. Currently not really used, but in place to allow for potential
 /// punishment of misbehaving contributors to the tendermint protocol.
@@ -163,4 +169,12 @@ pub trait Protocol: Clone + Send + Sync + Unpin + Sized + 'static {
         step: Step,
         message: Self::AggregationMessage,
     ) -> BoxFuture<'static, Result<(), ()>>;
+
+    /// Creates the future of a timeout with the given `duration`. Sleeps for `duration` by default.
+    fn create_timeout(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        sleep(duration).boxed()
+    }
+
+    /// Called for every event of the state machine, e.g. to record them. Does nothing by default.
+    fn on_event(&self, _event: Event<Self::ProposalHash>) {}
 }
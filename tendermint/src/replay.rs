@@ -0,0 +1,553 @@
+//! Deterministic replay of recorded [`Event`]s through [`Tendermint`], for debugging consensus
+//! incidents.
+//!
+//! All inputs of the replayed instance are taken from the journal of a height: This node proposes
+//! in the rounds it proposed in, proposals are received with their recorded validity and
+//! aggregations yield the recorded votes. Every input is only released once all events preceding
+//! it in the journal were emitted again, such that the inputs arrive in the recorded order.
+//! Timeouts don't depend on the clock but elapse whenever the instance is idle, i.e. it can't make
+//! progress otherwise.
+//!
+//! The replay succeeds if the instance emits the recorded events in the recorded order. Replaying
+//! a journal that does not start at round 0, e.g. because the node was restarted in between, fails.
+
+use std::{
+    collections::BTreeMap,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::{
+    future::{self, BoxFuture, Future, FutureExt},
+    stream::{self, BoxStream, Stream, StreamExt},
+    task::{waker_ref, ArcWake, AtomicWaker},
+};
+use nimiq_collections::BitSet;
+use tokio::sync::watch;
+
+use crate::{
+    event::Event,
+    protocol::{
+        Aggregation, AggregationMessage, Inherent, Proposal, ProposalError, ProposalMessage,
+        Protocol, ProtocolError, SignedProposalMessage, TaggedAggregationMessage,
+    },
+    tendermint::Tendermint,
+    utils::{Return, Step},
+};
+
+/// The parameters of the protocol a journal was recorded with.
+pub trait ReplayParameters: Send + Sync + Unpin + 'static {
+    type ProposalHash: Unpin + Clone + Send + Sync + std::fmt::Debug + Ord + 'static;
+
+    const TIMEOUT_DELTA: u64;
+    const TIMEOUT_INIT: u64;
+    const TWO_F_PLUS_ONE: usize;
+    const F_PLUS_ONE: usize;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplayError<ProposalHash> {
+    /// The event at `index` was expected, but `actual` was emitted instead.
+    Diverged {
+        index: usize,
+        expected: Event<ProposalHash>,
+        actual: Event<ProposalHash>,
+    },
+    /// The event at `index` was expected, but the instance did not make any progress.
+    Stalled {
+        index: usize,
+        expected: Event<ProposalHash>,
+    },
+    /// The instance terminated before the event at `index` was emitted.
+    Terminated {
+        index: usize,
+        expected: Event<ProposalHash>,
+    },
+}
+
+/// Replays the recorded `events` of a height. Returns the round and proposal hash of the decision,
+/// if the replay reached one.
+pub async fn replay<TParameters: ReplayParameters>(
+    events: Vec<Event<TParameters::ProposalHash>>,
+) -> Result<Option<(u32, TParameters::ProposalHash)>, ReplayError<TParameters::ProposalHash>> {
+    let journal = Arc::new(Journal::new(events));
+    let protocol = ReplayProtocol::<TParameters> {
+        journal: Arc::clone(&journal),
+        _parameters: PhantomData,
+    };
+    let proposal_stream = protocol.proposal_stream();
+    let level_update_stream = protocol.level_update_stream();
+    let mut tendermint = Tendermint::new(protocol, None, proposal_stream, level_update_stream);
+
+    loop {
+        let item = next_unless_idle(&mut tendermint).await;
+
+        let index = journal.cursor();
+        if let Some(divergence) = journal.divergence() {
+            return Err(divergence);
+        }
+        let Some(expected) = journal.events.get(index).cloned() else {
+            return Ok(match item {
+                Ok(Some(Return::Decision(decision))) => Some(decision),
+                _ => None,
+            });
+        };
+
+        match item {
+            Some(Some(Return::Decision(_)) | None) => {
+                return Err(ReplayError::Terminated { index, expected })
+            }
+            Some(Some(_)) => {}
+            None => {
+                // The instance is idle, which lets its pending timeouts elapse. Without any, the
+                // replay is stuck.
+                if !journal.elapse_timeouts() {
+                    return Err(ReplayError::Stalled { index, expected });
+                }
+            }
+        }
+    }
+}
+
+/// Notes whether the task polling the replayed instance was woken, and wakes it.
+#[derive(Default)]
+struct WakeFlag {
+    woken: AtomicBool,
+    waker: AtomicWaker,
+}
+
+impl ArcWake for WakeFlag {
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.woken.store(true, Ordering::SeqCst);
+        arc_self.waker.wake();
+    }
+}
+
+/// Resolves to the next item of `stream`, or to `None` if the stream is idle, i.e. it is pending
+/// without having been woken while it was polled. All inputs of the replayed instance are released
+/// by its own events or by elapsing its timeouts, so nothing else can wake it.
+fn next_unless_idle<S: Stream + Unpin>(
+    stream: &mut S,
+) -> impl Future<Output = Option<Option<S::Item>>> + '_ {
+    let flag = Arc::new(WakeFlag::default());
+    future::poll_fn(move |cx| {
+        flag.waker.register(cx.waker());
+        flag.woken.store(false, Ordering::SeqCst);
+
+        let waker = waker_ref(&flag);
+        match stream.poll_next_unpin(&mut Context::from_waker(&waker)) {
+            Poll::Ready(item) => Poll::Ready(Some(item)),
+            // The waker was already passed on, so this task is polled again.
+            Poll::Pending if flag.woken.load(Ordering::SeqCst) => Poll::Pending,
+            Poll::Pending => Poll::Ready(None),
+        }
+    })
+}
+
+/// The recorded events and the progress of the replay.
+struct Journal<ProposalHash> {
+    events: Vec<Event<ProposalHash>>,
+    /// The number of recorded events that were emitted again.
+    cursor: watch::Sender<usize>,
+    /// The first event that did not match the recorded one, with its index.
+    divergence: Mutex<Option<(usize, Event<ProposalHash>)>>,
+    /// Incremented whenever the instance is idle, which elapses its pending timeouts.
+    idle: watch::Sender<u64>,
+}
+
+impl<ProposalHash: Clone + PartialEq + Send + Sync + 'static> Journal<ProposalHash> {
+    fn new(events: Vec<Event<ProposalHash>>) -> Self {
+        Self {
+            events,
+            cursor: watch::Sender::new(0),
+            divergence: Mutex::new(None),
+            idle: watch::Sender::new(0),
+        }
+    }
+
+    fn cursor(&self) -> usize {
+        *self.cursor.borrow()
+    }
+
+    fn divergence(&self) -> Option<ReplayError<ProposalHash>> {
+        let (index, actual) = self.divergence.lock().unwrap().clone()?;
+        Some(ReplayError::Diverged {
+            index,
+            expected: self.events[index].clone(),
+            actual,
+        })
+    }
+
+    /// Compares an emitted event to the next recorded one. Events after the end of the journal or
+    /// after a divergence are ignored.
+    fn emitted(&self, event: Event<ProposalHash>) {
+        let mut divergence = self.divergence.lock().unwrap();
+        let index = self.cursor();
+        if divergence.is_some() || index >= self.events.len() {
+            return;
+        }
+
+        if self.events[index] == event {
+            self.cursor.send_replace(index + 1);
+        } else {
+            *divergence = Some((index, event));
+        }
+    }
+
+    /// Creates a timeout which elapses the next time the instance is idle.
+    fn timeout(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut idle = self.idle.subscribe();
+        async move {
+            // The sender outlives the instance and thus all timeouts.
+            let _ = idle.changed().await;
+        }
+    }
+
+    /// Elapses the pending timeouts. Returns false if there are none.
+    fn elapse_timeouts(&self) -> bool {
+        if self.idle.receiver_count() == 0 {
+            return false;
+        }
+        self.idle.send_modify(|idle| *idle += 1);
+        true
+    }
+
+    /// Resolves once all events before `index` were emitted again. Resolves to false if the event at
+    /// `index` was emitted as well, i.e. its input was already released by other means.
+    fn released(&self, index: usize) -> impl Future<Output = bool> + Send + 'static {
+        let mut cursor = self.cursor.subscribe();
+        async move {
+            match cursor.wait_for(|cursor| *cursor >= index).await {
+                Ok(cursor) => *cursor == index,
+                Err(_) => false,
+            }
+        }
+    }
+
+    /// Creates a stream of the given inputs, each released at the index of its event.
+    fn inputs<T: Send + 'static>(
+        journal: &Arc<Self>,
+        inputs: Vec<(usize, T)>,
+    ) -> BoxStream<'static, T> {
+        let journal = Arc::clone(journal);
+        stream::iter(inputs)
+            .filter_map(move |(index, input)| {
+                journal
+                    .released(index)
+                    .map(move |released| released.then_some(input))
+            })
+            .chain(stream::pending())
+            .boxed()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ReplayProposal<ProposalHash>(ProposalHash);
+
+impl<ProposalHash: Clone> Proposal<ProposalHash, ProposalHash> for ReplayProposal<ProposalHash> {
+    fn hash(&self) -> ProposalHash {
+        self.0.clone()
+    }
+
+    fn inherent_hash(&self) -> ProposalHash {
+        self.0.clone()
+    }
+}
+
+impl<ProposalHash: Clone> Inherent<ProposalHash> for ReplayProposal<ProposalHash> {
+    fn hash(&self) -> ProposalHash {
+        self.0.clone()
+    }
+}
+
+/// An aggregate with the recorded number of votes, cast by made up contributors.
+#[derive(Clone, Debug)]
+struct ReplayAggregate<ProposalHash> {
+    contributions: BTreeMap<Option<ProposalHash>, BitSet>,
+}
+
+impl<ProposalHash: Clone + Ord> ReplayAggregate<ProposalHash> {
+    fn new(votes: &[(Option<ProposalHash>, usize)]) -> Self {
+        let mut contributions = BTreeMap::new();
+        let mut next_contributor = 0;
+        for (vote, count) in votes {
+            let contributors: &mut BitSet = contributions.entry(vote.clone()).or_default();
+            for contributor in next_contributor..next_contributor + count {
+                contributors.insert(contributor);
+            }
+            next_contributor += count;
+        }
+        Self { contributions }
+    }
+}
+
+impl<ProposalHash: Send + Sync + Clone + std::fmt::Debug + Ord + Unpin + 'static>
+    Aggregation<ProposalHash> for ReplayAggregate<ProposalHash>
+{
+    fn proposals(&self) -> Vec<(ProposalHash, usize)> {
+        self.contributions
+            .iter()
+            .filter_map(|(vote, contributors)| {
+                vote.as_ref()
+                    .map(|proposal_hash| (proposal_hash.clone(), contributors.len()))
+            })
+            .collect()
+    }
+
+    fn contributors_for(&self, vote: Option<&ProposalHash>) -> BitSet {
+        self.contributions
+            .get(&vote.cloned())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn all_contributors(&self) -> BitSet {
+        let mut all_contributors = BitSet::default();
+        for contributors in self.contributions.values() {
+            all_contributors |= contributors.clone();
+        }
+        all_contributors
+    }
+}
+
+impl<ProposalHash: Send + Sync + Clone + std::fmt::Debug + Ord + Unpin + 'static>
+    AggregationMessage<ProposalHash> for ReplayAggregate<ProposalHash>
+{
+    fn sender(&self) -> u16 {
+        0
+    }
+}
+
+/// Provides the inputs recorded in the journal to the replayed instance.
+struct ReplayProtocol<TParameters: ReplayParameters> {
+    journal: Arc<Journal<TParameters::ProposalHash>>,
+    _parameters: PhantomData<TParameters>,
+}
+
+impl<TParameters: ReplayParameters> Clone for ReplayProtocol<TParameters> {
+    fn clone(&self) -> Self {
+        Self {
+            journal: Arc::clone(&self.journal),
+            _parameters: PhantomData,
+        }
+    }
+}
+
+type ReplaySignedProposal<ProposalHash> = SignedProposalMessage<ReplayProposal<ProposalHash>, bool>;
+
+impl<TParameters: ReplayParameters> ReplayProtocol<TParameters> {
+    /// Returns the recorded proposals received by this node with the index of their event.
+    /// The signature of a proposal is whether it is valid.
+    fn received_proposals(
+        &self,
+    ) -> impl Iterator<Item = (usize, ReplaySignedProposal<TParameters::ProposalHash>)> + '_ {
+        self.journal
+            .events
+            .iter()
+            .enumerate()
+            .filter_map(|(index, event)| match event {
+                Event::ProposalVerified {
+                    round,
+                    valid_round,
+                    proposal_hash,
+                    valid,
+                } => Some((
+                    index,
+                    SignedProposalMessage {
+                        message: ProposalMessage {
+                            round: *round,
+                            valid_round: *valid_round,
+                            proposal: ReplayProposal(proposal_hash.clone()),
+                        },
+                        signature: *valid,
+                    },
+                )),
+                _ => None,
+            })
+    }
+
+    fn proposal_stream(
+        &self,
+    ) -> BoxStream<'static, ReplaySignedProposal<TParameters::ProposalHash>> {
+        Journal::inputs(&self.journal, self.received_proposals().collect())
+    }
+
+    /// Creates the level updates which make the instance skip ahead to the recorded rounds.
+    fn level_update_stream(
+        &self,
+    ) -> BoxStream<'static, TaggedAggregationMessage<ReplayAggregate<TParameters::ProposalHash>>>
+    {
+        let skips = self
+            .journal
+            .events
+            .iter()
+            .enumerate()
+            .filter_map(|(index, event)| match event {
+                Event::RoundStarted {
+                    round,
+                    skipped_ahead: true,
+                } => Some((
+                    index,
+                    TaggedAggregationMessage {
+                        tag: (*round, Step::Prevote),
+                        aggregation: ReplayAggregate::new(&[(None, TParameters::F_PLUS_ONE)]),
+                    },
+                )),
+                _ => None,
+            })
+            .collect();
+        Journal::inputs(&self.journal, skips)
+    }
+
+    fn own_proposal(&self, round: u32) -> Option<&TParameters::ProposalHash> {
+        self.journal.events.iter().find_map(|event| match event {
+            Event::Proposed {
+                round: proposed_round,
+                valid_round: None,
+                proposal_hash,
+            } if *proposed_round == round => Some(proposal_hash),
+            _ => None,
+        })
+    }
+}
+
+impl<TParameters: ReplayParameters> Protocol for ReplayProtocol<TParameters> {
+    type Proposal = ReplayProposal<TParameters::ProposalHash>;
+    type ProposalHash = TParameters::ProposalHash;
+    type InherentHash = TParameters::ProposalHash;
+    type ProposalSignature = bool;
+    type Inherent = ReplayProposal<TParameters::ProposalHash>;
+    type AggregationMessage = ReplayAggregate<TParameters::ProposalHash>;
+    type Aggregation = ReplayAggregate<TParameters::ProposalHash>;
+    type Decision = (u32, TParameters::ProposalHash);
+
+    const TIMEOUT_DELTA: u64 = TParameters::TIMEOUT_DELTA;
+    const TIMEOUT_INIT: u64 = TParameters::TIMEOUT_INIT;
+    const TWO_F_PLUS_ONE: usize = TParameters::TWO_F_PLUS_ONE;
+    const F_PLUS_ONE: usize = TParameters::F_PLUS_ONE;
+
+    fn is_proposer(&self, round: u32) -> Result<bool, ProtocolError> {
+        Ok(self.journal.events.iter().any(|event| {
+            matches!(event, Event::Proposed { round: proposed_round, .. } if *proposed_round == round)
+        }))
+    }
+
+    fn create_proposal(
+        &self,
+        round: u32,
+    ) -> Result<(ProposalMessage<Self::Proposal>, Self::Inherent), ProtocolError> {
+        let proposal_hash = self.own_proposal(round).ok_or(ProtocolError::Abort)?;
+        Ok((
+            ProposalMessage {
+                round,
+                valid_round: None,
+                proposal: ReplayProposal(proposal_hash.clone()),
+            },
+            ReplayProposal(proposal_hash.clone()),
+        ))
+    }
+
+    fn sign_proposal(&self, _proposal_message: &ProposalMessage<Self::Proposal>) -> bool {
+        true
+    }
+
+    fn verify_proposal(
+        &self,
+        proposal: &SignedProposalMessage<Self::Proposal, Self::ProposalSignature>,
+        precalculated_inherent: Option<Self::Inherent>,
+    ) -> Result<Self::Inherent, ProposalError> {
+        if proposal.signature {
+            Ok(precalculated_inherent.unwrap_or_else(|| proposal.message.proposal.clone()))
+        } else {
+            Err(ProposalError::InvalidProposal)
+        }
+    }
+
+    fn broadcast_proposal(
+        &self,
+        _proposal: SignedProposalMessage<Self::Proposal, Self::ProposalSignature>,
+    ) {
+    }
+
+    fn request_proposal(
+        &self,
+        proposal_hash: Self::ProposalHash,
+        round: u32,
+        _candidates: BitSet,
+    ) -> BoxFuture<'static, Option<SignedProposalMessage<Self::Proposal, Self::ProposalSignature>>>
+    {
+        let cursor = self.journal.cursor();
+        let response = self.received_proposals().find(|(index, proposal)| {
+            *index >= cursor
+                && proposal.message.round == round
+                && proposal.message.proposal.0 == proposal_hash
+        });
+
+        match response {
+            Some((index, proposal)) => self
+                .journal
+                .released(index)
+                .map(move |released| released.then_some(proposal))
+                .boxed(),
+            None => future::ready(None).boxed(),
+        }
+    }
+
+    fn create_decision(
+        &self,
+        proposal: Self::Proposal,
+        _inherent: Self::Inherent,
+        _aggregation: Self::Aggregation,
+        round: u32,
+    ) -> Self::Decision {
+        (round, proposal.0)
+    }
+
+    fn create_aggregation(
+        &self,
+        round: u32,
+        step: Step,
+        _vote: Option<Self::ProposalHash>,
+        _update_stream: BoxStream<'static, Self::AggregationMessage>,
+    ) -> BoxStream<'static, Self::Aggregation> {
+        let aggregates = self
+            .journal
+            .events
+            .iter()
+            .enumerate()
+            .filter_map(|(index, event)| match event {
+                Event::Votes {
+                    round: votes_round,
+                    step: votes_step,
+                    votes,
+                } if *votes_round == round && *votes_step == step => {
+                    Some((index, ReplayAggregate::new(votes)))
+                }
+                _ => None,
+            })
+            .collect();
+        Journal::inputs(&self.journal, aggregates)
+    }
+
+    fn verify_aggregation_message(
+        &self,
+        _round: u32,
+        _step: Step,
+        _message: Self::AggregationMessage,
+    ) -> BoxFuture<'static, Result<(), ()>> {
+        future::ready(Ok(())).boxed()
+    }
+
+    fn create_timeout(&self, _duration: Duration) -> BoxFuture<'static, ()> {
+        self.journal.timeout().boxed()
+    }
+
+    fn on_event(&self, event: Event<Self::ProposalHash>) {
+        self.journal.emitted(event);
+    }
+}
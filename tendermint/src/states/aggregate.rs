@@ -3,6 +3,7 @@ use std::task::Context;
 use futures::future::FutureExt;
 
 use crate::{
+    event::Event,
     protocol::{Aggregation, Protocol},
     utils::{Return, Step},
     Tendermint,
@@ -67,6 +68,7 @@ impl<TProtocol: Protocol> Tendermint<TProtocol> {
                 proposal = ?proposal_hash,
                 "Aggregation resulted in Block polka",
             );
+            self.emit_votes(round_and_step);
             self.on_polka(proposal_hash);
 
             // Reset timeout.
@@ -83,6 +85,7 @@ impl<TProtocol: Protocol> Tendermint<TProtocol> {
         if none_contributor_count >= TProtocol::TWO_F_PLUS_ONE {
             // Vote against all proposals, as None has 2f+1 votes.
             log::debug!(?round_and_step, "Aggregation resulted in None polka",);
+            self.emit_votes(round_and_step);
             self.on_none_polka();
 
             // Reset timeout.
@@ -103,6 +106,7 @@ impl<TProtocol: Protocol> Tendermint<TProtocol> {
         {
             // Vote against all proposals, as None has 2f+1 votes.
             log::debug!(?round_and_step, "Aggregation resulted in None polka",);
+            self.emit_votes(round_and_step);
             self.on_none_polka();
 
             // Reset timeout.
@@ -118,6 +122,11 @@ impl<TProtocol: Protocol> Tendermint<TProtocol> {
         // Check if the timeout elapsed. If so the result must be returned, even though it might still improve.
         if self.timeout.as_mut().unwrap().poll_unpin(cx).is_ready() {
             log::debug!("Aggregation timed out without final result.");
+            self.emit_votes(round_and_step);
+            self.protocol.on_event(Event::Timeout {
+                round: round_and_step.0,
+                step: round_and_step.1,
+            });
             self.on_none_polka();
 
             // Reset timeout.
@@ -130,6 +139,17 @@ impl<TProtocol: Protocol> Tendermint<TProtocol> {
         None
     }
 
+    /// Emits the votes of the aggregation for `round_and_step` which the state machine is acting upon.
+    fn emit_votes(&self, (round, step): (u32, Step)) {
+        if let Some(aggregate) = self.state.best_votes.get(&(round, step)) {
+            self.protocol.on_event(Event::Votes {
+                round,
+                step,
+                votes: Self::votes_of(aggregate),
+            });
+        }
+    }
+
     /// For the current round and step as denoted within `self.state` this will perform all necessary
     /// action to advance to the next state while having seen 2f+1 votes for the known proposal with `proposal_hash`
     /// as its hash.
@@ -193,6 +213,10 @@ impl<TProtocol: Protocol> Tendermint<TProtocol> {
                 // Remove all future contributions for the round that is about to start.
                 self.future_contributions
                     .retain(|round, _contributors| round > &self.state.current_round);
+                self.protocol.on_event(Event::RoundStarted {
+                    round: self.state.current_round,
+                    skipped_ahead: false,
+                });
             }
             Step::Propose => unreachable!(),
         }
@@ -2,7 +2,7 @@ use std::task::Context;
 
 use futures::future::FutureExt;
 
-use crate::{Protocol, Return, Step, Tendermint};
+use crate::{Event, Protocol, Return, Step, Tendermint};
 
 impl<TProtocol: Protocol> Tendermint<TProtocol> {
     /// Waits for a proposal to arrive.
@@ -130,6 +130,10 @@ impl<TProtocol: Protocol> Tendermint<TProtocol> {
         // Check if the timeout elapsed.
         if self.timeout.as_mut().unwrap().poll_unpin(cx).is_ready() {
             // The timeout elapsed, vote nil as the proposal did not arrive in time.
+            self.protocol.on_event(Event::Timeout {
+                round: self.state.current_round,
+                step: Step::Propose,
+            });
             self.state
                 .votes
                 .insert((self.state.current_round, Step::Prevote), None);
@@ -1,4 +1,5 @@
 use crate::{
+    event::Event,
     protocol::{Inherent, Proposal, ProposalMessage, Protocol, SignedProposalMessage},
     utils::{Return, Step},
     ProtocolError, Tendermint,
@@ -122,6 +123,11 @@ impl<TProtocol: Protocol> Tendermint<TProtocol> {
 
             // Store the proposal for the current round.
             proposals.insert(proposal_hash.clone(), (Some(*valid_round), signature));
+            self.protocol.on_event(Event::Proposed {
+                round: self.state.current_round,
+                valid_round: Some(*valid_round),
+                proposal_hash: proposal_hash.clone(),
+            });
 
             // Yield the state as it has changed.
             Ok(Return::Update(self.state.clone()))
@@ -153,7 +159,12 @@ impl<TProtocol: Protocol> Tendermint<TProtocol> {
                 .insert(proposal_hash.clone(), message.proposal);
 
             // Store the proposal for the current round.
-            proposals.insert(proposal_hash, (None, signature));
+            proposals.insert(proposal_hash.clone(), (None, signature));
+            self.protocol.on_event(Event::Proposed {
+                round: self.state.current_round,
+                valid_round: None,
+                proposal_hash,
+            });
 
             // Yield the state as it has changed.
             Ok(Return::Update(self.state.clone()))
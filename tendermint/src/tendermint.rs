@@ -12,13 +12,13 @@ use futures::{
     stream::{BoxStream, SelectAll, Stream, StreamExt},
 };
 use nimiq_collections::BitSet;
-use nimiq_time::sleep;
 use nimiq_utils::stream::FuturesUnordered;
 use rand::{thread_rng, Rng};
 use tokio::{sync::mpsc, time::Duration};
 use tokio_stream::wrappers::ReceiverStream;
 
 use crate::{
+    event::Event,
     protocol::{Aggregation, Protocol, SignedProposalMessage, TaggedAggregationMessage},
     state::State,
     utils::{Return, Step},
@@ -169,7 +169,7 @@ impl<TProtocol: Protocol> Tendermint<TProtocol> {
                 TProtocol::TIMEOUT_INIT
                     + self.state.current_round as u64 * TProtocol::TIMEOUT_DELTA,
             );
-            self.timeout = Some(sleep(duration).boxed());
+            self.timeout = Some(self.protocol.create_timeout(duration));
         }
     }
 
@@ -215,6 +215,24 @@ impl<TProtocol: Protocol> Tendermint<TProtocol> {
             .is_some()
     }
 
+    /// Returns the number of votes for each proposal hash and `None` in `aggregate`, as reported
+    /// in [`Event::Votes`].
+    pub(crate) fn votes_of(
+        aggregate: &TProtocol::Aggregation,
+    ) -> Vec<(Option<TProtocol::ProposalHash>, usize)> {
+        let mut votes: Vec<_> = aggregate
+            .proposals()
+            .into_iter()
+            .map(|(proposal_hash, count)| (Some(proposal_hash), count))
+            .collect();
+        let none_count = aggregate.contributors_for(None).len();
+        if none_count > 0 {
+            votes.push((None, none_count));
+        }
+        votes.sort();
+        votes
+    }
+
     /// Creates an aggregation for a given `id`. If that aggregation does already exist,
     /// as indicated by the presence of `id` in `self.aggregation_senders` it has no effect.
     ///
@@ -305,6 +323,37 @@ impl<TProtocol: Protocol> Tendermint<TProtocol> {
     fn process_proposal(
         &mut self,
         proposal: SignedProposalMessage<TProtocol::Proposal, TProtocol::ProposalSignature>,
+    ) -> Option<TProtocol::ProposalHash> {
+        let proposal_hash = self.verify_proposal(&proposal);
+
+        self.protocol.on_event(Event::ProposalVerified {
+            round: proposal.message.round,
+            valid_round: proposal.message.valid_round,
+            proposal_hash: proposal.message.proposal.hash(),
+            valid: proposal_hash.is_some(),
+        });
+
+        // The proposal with `proposal_hash` was successfully verified. Add it to the set of proposals for its round.
+        let proposal_hash = proposal_hash?;
+        self.state
+            .round_proposals
+            .entry(proposal.message.round)
+            .or_default()
+            .insert(
+                proposal_hash.clone(),
+                (proposal.message.valid_round, proposal.signature),
+            );
+
+        // make sure the next poll will return a state update
+        Some(proposal_hash)
+    }
+
+    /// Verifies a signed proposal, unless it is already known, and adds it and its inherent to the known ones.
+    ///
+    /// Returns Some(proposal_hash) in case the proposal is valid, None otherwise.
+    fn verify_proposal(
+        &mut self,
+        proposal: &SignedProposalMessage<TProtocol::Proposal, TProtocol::ProposalSignature>,
     ) -> Option<TProtocol::ProposalHash> {
         let inherent_entry = self
             .state
@@ -316,7 +365,7 @@ impl<TProtocol: Protocol> Tendermint<TProtocol> {
             Entry::Vacant(inherent_entry) => {
                 // The inherent is unknown. The proposal itself cannot be known either.
                 // Do full verification while also creating the inherent.
-                match self.protocol.verify_proposal(&proposal, None) {
+                match self.protocol.verify_proposal(proposal, None) {
                     Ok(inherent) => {
                         // Use proposal and created inherent to produce the hash
                         let proposal_hash = proposal.message.proposal.hash();
@@ -356,7 +405,7 @@ impl<TProtocol: Protocol> Tendermint<TProtocol> {
                     // The proposal is not known, but the inherent is. Simplify verification as the inherent does not need to be produced.
                     match self
                         .protocol
-                        .verify_proposal(&proposal, Some(inherent_entry.get().clone()))
+                        .verify_proposal(proposal, Some(inherent_entry.get().clone()))
                     {
                         Ok(_) => {
                             // The proposal is valid.
@@ -383,17 +432,6 @@ impl<TProtocol: Protocol> Tendermint<TProtocol> {
             }
         };
 
-        // The proposal with `proposal_hash` was successfully verified. Add it to the set of proposals for its round.
-        self.state
-            .round_proposals
-            .entry(proposal.message.round)
-            .or_default()
-            .insert(
-                proposal_hash.clone(),
-                (proposal.message.valid_round, proposal.signature),
-            );
-
-        // make sure the next poll will return a state update
         Some(proposal_hash)
     }
 
@@ -456,6 +494,10 @@ impl<TProtocol: Protocol> Tendermint<TProtocol> {
                                 // Skip to that round
                                 self.state.current_round = round;
                                 self.state.current_step = Step::Propose;
+                                self.protocol.on_event(Event::RoundStarted {
+                                    round,
+                                    skipped_ahead: true,
+                                });
                                 self.future_contributions.retain(|&round, _contributors| {
                                     round > self.state.current_round
                                 });
@@ -607,11 +649,21 @@ impl<TProtocol: Protocol> Tendermint<TProtocol> {
                                 {
                                     self.state.valid =
                                         Some((round_and_step.0, proposal_hash.clone()));
+                                    self.protocol.on_event(Event::Votes {
+                                        round: round_and_step.0,
+                                        step: round_and_step.1,
+                                        votes: Self::votes_of(best_vote),
+                                    });
                                 }
                             }
                             Step::Precommit => {
                                 // The proposal exists and is valid. 2f+1 precommits have been seen.
                                 log::debug!(?round_and_step, "Aggregation produced decision value",);
+                                self.protocol.on_event(Event::Votes {
+                                    round: round_and_step.0,
+                                    step: round_and_step.1,
+                                    votes: Self::votes_of(best_vote),
+                                });
 
                                 // Get the inherent
                                 let inherent = self
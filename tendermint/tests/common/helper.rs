@@ -165,6 +165,32 @@ pub async fn run_tendermint(
         >,
     )>,
 ) -> Option<<Validator as Protocol>::Decision> {
+    run_tendermint_recorded(proposals, prevotes, precommits, proposal_responses)
+        .await
+        .0
+}
+
+/// Same as [`run_tendermint`], additionally returning all events emitted by the instance.
+pub async fn run_tendermint_recorded(
+    proposals: Vec<(
+        bool,
+        Option<
+            SignedProposalMessage<
+                <Validator as Protocol>::Proposal,
+                <Validator as Protocol>::ProposalSignature,
+            >,
+        >,
+    )>,
+    prevotes: Vec<Vec<(Option<u32>, Range<usize>)>>,
+    precommits: Vec<Vec<(Option<u32>, Range<usize>)>>,
+    proposal_responses: Vec<(
+        (u32, u32),
+        SignedProposalMessage<
+            <Validator as Protocol>::Proposal,
+            <Validator as Protocol>::ProposalSignature,
+        >,
+    )>,
+) -> (Option<<Validator as Protocol>::Decision>, Vec<Event<u32>>) {
     let mut last_state = State::<Validator>::default();
 
     let (observe_sender, mut receiver) = mpsc::channel::<Observe>(5);
@@ -179,6 +205,7 @@ pub async fn run_tendermint(
         observe_sender,
         known_proposals,
         aggregate_senders: Arc::new(Mutex::new(BTreeMap::default())),
+        events: Arc::new(Mutex::new(vec![])),
     };
 
     let (proposal_sender, proposal_receiver) = mpsc::channel(10);
@@ -256,7 +283,10 @@ pub async fn run_tendermint(
 
         match stream_item {
             None => panic!(""),
-            Some(Return::Decision(d)) => return Some(d),
+            Some(Return::Decision(d)) => {
+                let events = validator.events.lock().expect("").clone();
+                return (Some(d), events);
+            }
             Some(Return::Update(state)) => {
                 last_state = state;
             }
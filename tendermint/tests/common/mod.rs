@@ -91,6 +91,8 @@ pub struct Validator {
     /// (round, hash) => SignedProposal
     known_proposals: BTreeMap<(u32, u32), SignedProposalMessage<TestProposal, bool>>,
     observe_sender: mpsc::Sender<Observe>,
+    /// All events emitted by the instance.
+    pub events: Arc<Mutex<Vec<Event<u32>>>>,
 }
 
 // Dummy PartialEq implementation such that State<Validator> implements PartialEq.
@@ -117,6 +119,7 @@ pub fn create_validator(
         observe_sender,
         known_proposals,
         aggregate_senders: Arc::new(Mutex::new(BTreeMap::default())),
+        events: Arc::new(Mutex::new(vec![])),
     };
 
     (validator, receiver)
@@ -260,4 +263,20 @@ impl Protocol for Validator {
             .try_send(Observe::Proposal(proposal))
             .expect("Failed to send proposal to observer");
     }
+
+    fn on_event(&self, event: Event<u32>) {
+        self.events.lock().expect("").push(event);
+    }
+}
+
+/// Replays journals recorded by [`Validator`].
+pub struct TestParameters;
+
+impl replay::ReplayParameters for TestParameters {
+    type ProposalHash = u32;
+
+    const TIMEOUT_DELTA: u64 = Validator::TIMEOUT_DELTA;
+    const TIMEOUT_INIT: u64 = Validator::TIMEOUT_INIT;
+    const TWO_F_PLUS_ONE: usize = Validator::TWO_F_PLUS_ONE;
+    const F_PLUS_ONE: usize = Validator::F_PLUS_ONE;
 }
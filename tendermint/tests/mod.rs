@@ -1,8 +1,14 @@
 pub mod common;
 
-use nimiq_tendermint::{ProposalMessage, Protocol, SignedProposalMessage};
+use nimiq_tendermint::{
+    replay::{replay, ReplayError},
+    Event, ProposalMessage, Protocol, SignedProposalMessage, Step,
+};
 
-use self::common::{helper::run_tendermint, TestInherent, TestProposal, Validator};
+use self::common::{
+    helper::{run_tendermint, run_tendermint_recorded},
+    TestInherent, TestParameters, TestProposal, Validator,
+};
 
 #[tokio::test]
 /// This instance proposes/receives a proposal for round 0 and also sees 2f+1 prevotes and precommits
@@ -394,3 +400,90 @@ async fn it_can_deal_with_insufficient_precommits() {
     assert_eq!(decision.inherents, TestInherent(7));
     assert!(decision.sig.len() >= Validator::TWO_F_PLUS_ONE)
 }
+
+#[tokio::test]
+/// The events recorded while deciding in round 2 are emitted in the same order when replaying them.
+/// A journal the instance does not behave according to is detected.
+async fn it_replays_recorded_events() {
+    let (decision, events) = run_tendermint_recorded(
+        vec![
+            // Do not propose in round 0 but receive valid proposal
+            (
+                false,
+                Some(SignedProposalMessage {
+                    signature: true,
+                    message: ProposalMessage {
+                        proposal: TestProposal(3),
+                        round: 0,
+                        valid_round: None,
+                    },
+                }),
+            ),
+            // Do not propose in round 1 and also do not receive any proposal.
+            (false, None),
+            // Do propose in round 2
+            (true, None),
+        ],
+        vec![
+            vec![(Some(3), 0..Validator::TWO_F_PLUS_ONE)],
+            vec![(Some(5), 0..Validator::TWO_F_PLUS_ONE)],
+            vec![(Some(3), 0..Validator::TWO_F_PLUS_ONE)],
+        ],
+        vec![
+            vec![
+                (Some(3), 0..Validator::F_PLUS_ONE),
+                (None, Validator::F_PLUS_ONE..(2 * Validator::F_PLUS_ONE)),
+            ],
+            vec![
+                (Some(5), 0..Validator::F_PLUS_ONE),
+                (None, Validator::F_PLUS_ONE..(2 * Validator::F_PLUS_ONE)),
+            ],
+            vec![(Some(3), 0..Validator::TWO_F_PLUS_ONE)],
+        ],
+        vec![],
+    )
+    .await;
+    assert_eq!(decision.expect("").round, 2);
+
+    assert!(events.contains(&Event::Timeout {
+        round: 1,
+        step: Step::Propose
+    }));
+    assert!(events.contains(&Event::RoundStarted {
+        round: 2,
+        skipped_ahead: false
+    }));
+    assert!(events.contains(&Event::Proposed {
+        round: 2,
+        valid_round: Some(0),
+        proposal_hash: 3
+    }));
+
+    // The replay does not depend on the clock, so the recorded timeouts elapse immediately.
+    assert_eq!(
+        replay::<TestParameters>(events.clone()).await,
+        Ok(Some((2, 3)))
+    );
+
+    // With the proposal of round 0 being invalid, the instance times out waiting for a proposal
+    // instead of seeing the prevotes for it.
+    let mut modified_events = events;
+    for event in modified_events.iter_mut() {
+        if let Event::ProposalVerified {
+            round: 0, valid, ..
+        } = event
+        {
+            *valid = false;
+        }
+    }
+    assert!(matches!(
+        replay::<TestParameters>(modified_events).await,
+        Err(ReplayError::Diverged {
+            actual: Event::Timeout {
+                round: 0,
+                step: Step::Propose
+            },
+            ..
+        })
+    ));
+}
@@ -0,0 +1,158 @@
+use std::{
+    fs,
+    io::{Read, Write},
+    net::TcpListener,
+    process::exit,
+    str::FromStr,
+    sync::Arc,
+    thread,
+};
+
+use anyhow::Error;
+use clap::{crate_authors, crate_description, crate_version, Arg, Command};
+use nimiq_bls::{KeyPair as BlsKeyPair, SecretKey as BlsSecretKey};
+use nimiq_database::mdbx::MdbxDatabase;
+use nimiq_keys::{KeyPair, PrivateKey};
+use nimiq_serde::{Deserialize, Serialize};
+use nimiq_validator::{
+    signer::{LocalSigner, SignerAddress, SignerService, ValidatorSigner},
+    signing_history::SigningHistory,
+};
+use thiserror::Error;
+
+/// Reads a hex encoded value from the file at the path given by the argument `name`.
+fn read_hex_file(matches: &clap::ArgMatches, name: &str) -> Result<Vec<u8>, Error> {
+    let path = matches
+        .get_one::<String>(name)
+        .ok_or(AppError::MissingArgument(name.to_string()))?;
+    Ok(hex::decode(fs::read_to_string(path)?.trim())?)
+}
+
+fn serve<S: Read + Write>(service: &SignerService, stream: S) {
+    if let Err(e) = service.serve(stream) {
+        eprintln!("Connection closed: {e}");
+    }
+}
+
+fn run_app() -> Result<(), Error> {
+    let matches = Command::new("Validator signer")
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about(crate_description!())
+        .arg(
+            Arg::new("listen")
+                .short('l')
+                .long("listen")
+                .value_name("ADDRESS")
+                .required(true)
+                .help("Listen on ADDRESS, either `host:port` or `unix:<path>`."),
+        )
+        .arg(
+            Arg::new("database")
+                .short('d')
+                .long("database")
+                .value_name("PATH")
+                .required(true)
+                .help("Store the signing history in the database at PATH."),
+        )
+        .arg(
+            Arg::new("auth_key_file")
+                .long("auth-key-file")
+                .value_name("FILE")
+                .required(true)
+                .help("Read the hex encoded key shared with the validator from FILE."),
+        )
+        .arg(
+            Arg::new("signing_key_file")
+                .long("signing-key-file")
+                .value_name("FILE")
+                .required(true)
+                .help("Read the hex encoded Schnorr signing key from FILE."),
+        )
+        .arg(
+            Arg::new("voting_key_file")
+                .long("voting-key-file")
+                .value_name("FILE")
+                .required(true)
+                .help("Read the hex encoded BLS voting key from FILE."),
+        )
+        .arg(
+            Arg::new("fee_key_file")
+                .long("fee-key-file")
+                .value_name("FILE")
+                .required(true)
+                .help("Read the hex encoded Schnorr fee key from FILE."),
+        )
+        .get_matches();
+
+    let signing_key: KeyPair =
+        PrivateKey::deserialize_from_vec(&read_hex_file(&matches, "signing_key_file")?)?.into();
+    let voting_key = BlsKeyPair::from(BlsSecretKey::deserialize_from_vec(&read_hex_file(
+        &matches,
+        "voting_key_file",
+    )?)?);
+    let fee_key: KeyPair =
+        PrivateKey::deserialize_from_vec(&read_hex_file(&matches, "fee_key_file")?)?.into();
+    let auth_key = read_hex_file(&matches, "auth_key_file")?;
+    if auth_key.len() < 32 {
+        return Err(AppError::AuthKeyTooShort.into());
+    }
+
+    let database = matches
+        .get_one::<String>("database")
+        .ok_or(AppError::MissingArgument("database".to_string()))?;
+    let history = SigningHistory::new(MdbxDatabase::new(database, Default::default())?);
+
+    let signer = LocalSigner::new(signing_key, voting_key, fee_key);
+    println!(
+        "Signing for signing key {} and voting key {}",
+        signer.signing_public_key(),
+        hex::encode(signer.voting_public_key().compress().serialize_to_vec()),
+    );
+    let service = Arc::new(SignerService::new(signer, history, auth_key));
+
+    let listen = matches
+        .get_one::<String>("listen")
+        .ok_or(AppError::MissingArgument("listen".to_string()))?;
+    match SignerAddress::from_str(listen)? {
+        SignerAddress::Tcp(address) => {
+            let listener = TcpListener::bind(&address)?;
+            println!("Listening on {address}");
+            for stream in listener.incoming() {
+                let stream = stream?;
+                stream.set_nodelay(true)?;
+                let service = Arc::clone(&service);
+                thread::spawn(move || serve(&service, stream));
+            }
+        }
+        #[cfg(unix)]
+        SignerAddress::Unix(path) => {
+            let listener = std::os::unix::net::UnixListener::bind(&path)?;
+            println!("Listening on {}", path.display());
+            for stream in listener.incoming() {
+                let stream = stream?;
+                let service = Arc::clone(&service);
+                thread::spawn(move || serve(&service, stream));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    exit(match run_app() {
+        Ok(_) => 0,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            1
+        }
+    });
+}
+
+#[derive(Debug, Error)]
+enum AppError {
+    #[error("Argument is missing: {0}")]
+    MissingArgument(String),
+    #[error("The authentication key must be at least 32 bytes long")]
+    AuthKeyTooShort,
+}
@@ -0,0 +1,100 @@
+use std::process::exit;
+
+use anyhow::Error;
+use clap::{crate_authors, crate_version, Arg, Command};
+use nimiq_database::mdbx::MdbxDatabase;
+use nimiq_tendermint::replay::{replay, ReplayError};
+use nimiq_validator::tendermint_journal::{JournalParameters, TendermintJournal};
+use thiserror::Error;
+
+fn run_app() -> Result<(), Error> {
+    let matches = Command::new("Tendermint replay")
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about("Replays the Tendermint journal of a macro block recorded by a validator.")
+        .arg(
+            Arg::new("database")
+                .short('d')
+                .long("database")
+                .value_name("PATH")
+                .required(true)
+                .help("Read the journal from the validator database at PATH."),
+        )
+        .arg(
+            Arg::new("block_number")
+                .short('b')
+                .long("block-number")
+                .value_name("BLOCK_NUMBER")
+                .value_parser(clap::value_parser!(u32))
+                .help("Replay the journal of the macro block at BLOCK_NUMBER. Defaults to the latest journal."),
+        )
+        .get_matches();
+
+    let database = matches
+        .get_one::<String>("database")
+        .ok_or(AppError::MissingArgument("database".to_string()))?;
+    let journal = TendermintJournal::new(MdbxDatabase::new(database, Default::default())?);
+
+    let block_number = match matches.get_one::<u32>("block_number") {
+        Some(block_number) => *block_number,
+        None => journal.last_block_number().ok_or(AppError::NoJournal)?,
+    };
+    let entries = journal.get(block_number);
+    if entries.is_empty() {
+        return Err(AppError::NoJournalAt(block_number).into());
+    }
+
+    println!("Journal of block #{block_number}:");
+    let start = entries[0].timestamp;
+    for (index, entry) in entries.iter().enumerate() {
+        println!(
+            "{index:>4} +{:>6}ms {:?}",
+            entry.timestamp.saturating_sub(start),
+            entry.event
+        );
+    }
+
+    // Timeouts elapse as soon as the replayed instance is idle, so no timer is needed.
+    let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+    let events = entries.into_iter().map(|entry| entry.event).collect();
+    match runtime.block_on(replay::<JournalParameters>(events)) {
+        Ok(Some((round, proposal_hash))) => {
+            println!("Replay reproduced the journal, deciding on {proposal_hash} in round {round}.")
+        }
+        Ok(None) => println!("Replay reproduced the journal, which ends without a decision."),
+        Err(ReplayError::Diverged {
+            index,
+            expected,
+            actual,
+        }) => {
+            println!("Replay diverged at event {index}: expected {expected:?}, emitted {actual:?}.")
+        }
+        Err(ReplayError::Stalled { index, expected }) => {
+            println!("Replay stalled at event {index}: expected {expected:?}.")
+        }
+        Err(ReplayError::Terminated { index, expected }) => {
+            println!("Replay terminated at event {index}: expected {expected:?}.")
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    exit(match run_app() {
+        Ok(_) => 0,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            1
+        }
+    });
+}
+
+#[derive(Debug, Error)]
+enum AppError {
+    #[error("Argument is missing: {0}")]
+    MissingArgument(String),
+    #[error("The database does not contain any Tendermint journal")]
+    NoJournal,
+    #[error("The database does not contain a Tendermint journal for block #{0}")]
+    NoJournalAt(u32),
+}
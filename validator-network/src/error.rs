@@ -37,4 +37,8 @@ where
 
     #[error("Request error: {0}")]
     Request(RequestError),
+
+    /// Our validator record could not be signed.
+    #[error("Failed to sign the validator record")]
+    RecordSigning,
 }
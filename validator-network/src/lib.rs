@@ -5,7 +5,7 @@ pub mod validator_record;
 
 use async_trait::async_trait;
 use futures::stream::BoxStream;
-use nimiq_bls::{lazy::LazyPublicKey, CompressedPublicKey, SecretKey};
+use nimiq_bls::{lazy::LazyPublicKey, CompressedPublicKey};
 use nimiq_network_interface::{
     network::{CloseReason, MsgAcceptance, Network, SubscribeEvents, Topic},
     request::{Message, Request, RequestCommon},
@@ -13,6 +13,9 @@ use nimiq_network_interface::{
 
 pub use crate::error::NetworkError;
 
+/// Signs the data of our validator record with our voting key.
+pub type RecordSigner = Box<dyn FnOnce(&[u8]) -> Option<Vec<u8>> + Send>;
+
 pub type MessageStream<TMessage> = BoxStream<'static, (TMessage, usize)>;
 pub type PubsubId<TValidatorNetwork> =
     <<TValidatorNetwork as ValidatorNetwork>::NetworkType as Network>::PubsubId;
@@ -60,14 +63,20 @@ pub trait ValidatorNetwork: Send + Sync {
         &self,
     ) -> Result<BoxStream<'a, (TTopic::Item, PubsubId<Self>)>, Self::Error>;
 
+    /// Unsubscribes from a specific Gossipsub topic. Ends the stream returned by
+    /// [`Self::subscribe`].
+    async fn unsubscribe<TTopic: Topic + Sync>(&self) -> Result<(), Self::Error>;
+
     /// Subscribes to network events
     fn subscribe_events(&self) -> SubscribeEvents<<Self::NetworkType as Network>::PeerId>;
 
-    /// Sets this node peer ID using its secret key and public key.
+    /// Sets this node peer ID using its public key. The validator record is signed by `sign`,
+    /// which receives the tagged record data and returns the compressed signature of the secret
+    /// key matching `public_key`, or `None` if the record could not be signed.
     async fn set_public_key(
         &self,
         public_key: &CompressedPublicKey,
-        secret_key: &SecretKey,
+        sign: RecordSigner,
     ) -> Result<(), Self::Error>;
 
     /// Closes the connection to the peer with `peer_id` with the given `close_reason`.
@@ -3,17 +3,20 @@ use std::{collections::BTreeMap, error::Error, fmt::Debug, sync::Arc};
 use async_trait::async_trait;
 use futures::{stream::BoxStream, StreamExt, TryFutureExt};
 use log::warn;
-use nimiq_bls::{lazy::LazyPublicKey, CompressedPublicKey, KeyPair, SecretKey};
+use nimiq_bls::{lazy::LazyPublicKey, CompressedPublicKey, KeyPair, PublicKey};
 use nimiq_network_interface::{
     network::{CloseReason, MsgAcceptance, Network, SubscribeEvents, Topic},
     request::{InboundRequestError, Message, Request, RequestCommon, RequestError},
 };
 use nimiq_serde::{Deserialize, Serialize};
-use nimiq_utils::spawn;
+use nimiq_utils::{
+    spawn,
+    tagged_signing::{TaggedKeyPair, TaggedSignable},
+};
 use parking_lot::RwLock;
 use time::OffsetDateTime;
 
-use super::{MessageStream, NetworkError, PubsubId, ValidatorNetwork};
+use super::{MessageStream, NetworkError, PubsubId, RecordSigner, ValidatorNetwork};
 use crate::validator_record::ValidatorRecord;
 
 /// Validator `PeerId` cache state
@@ -280,6 +283,19 @@ impl<M: RequestCommon> RequestCommon for ValidatorMessage<M> {
     const MAX_REQUESTS: u32 = M::MAX_REQUESTS;
 }
 
+/// A key pair stand-in holding a signature that was already created for the validator record,
+/// e.g. by a remote signer. This way, the secret key doesn't need to be known to the network.
+#[derive(Deserialize, Serialize)]
+struct PresignedKeyPair(Vec<u8>);
+
+impl TaggedKeyPair for PresignedKeyPair {
+    type PublicKey = PublicKey;
+
+    fn sign(&self, _message: &[u8]) -> Vec<u8> {
+        self.0.clone()
+    }
+}
+
 // Proposal - gossip
 // LevelUpdate - multicast
 // StateEx - request/response
@@ -433,6 +449,14 @@ where
         Ok(self.network.subscribe::<TTopic>().await?)
     }
 
+    async fn unsubscribe<TTopic>(&self) -> Result<(), Self::Error>
+    where
+        TTopic: Topic + Sync,
+    {
+        self.network.unsubscribe::<TTopic>().await?;
+        Ok(())
+    }
+
     fn subscribe_events(&self) -> SubscribeEvents<<Self::NetworkType as Network>::PeerId> {
         self.network.subscribe_events()
     }
@@ -440,15 +464,16 @@ where
     async fn set_public_key(
         &self,
         public_key: &CompressedPublicKey,
-        secret_key: &SecretKey,
+        sign: RecordSigner,
     ) -> Result<(), Self::Error> {
         let peer_id = self.network.get_local_peer_id();
         let record = ValidatorRecord::new(
             peer_id,
             (OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000) as u64,
         );
+        let signature = sign(&record.message_data()).ok_or(NetworkError::RecordSigning)?;
         self.network
-            .dht_put(public_key, &record, &KeyPair::from(*secret_key))
+            .dht_put(public_key, &record, &PresignedKeyPair(signature))
             .await?;
 
         Ok(())
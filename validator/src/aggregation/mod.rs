@@ -4,3 +4,23 @@ pub mod tendermint;
 /// Implementation of signature aggregation protocols (skip block and pBFT prepare/commit) using
 /// the Handel protocol. The Handel protocol itself is implemented in the nimiq-handel crate.
 mod verifier;
+
+use std::sync::Arc;
+
+use nimiq_handel::{config::Config, telemetry::Telemetry};
+
+/// The Handel configuration per kind of aggregation.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AggregationConfig {
+    /// Used for the prevote and precommit aggregations of Tendermint.
+    pub tendermint: Config,
+    /// Used for the skip block aggregations.
+    pub skip_block: Config,
+}
+
+/// The Handel telemetry per kind of aggregation, see [`AggregationConfig`].
+#[derive(Clone, Debug, Default)]
+pub struct AggregationTelemetry {
+    pub tendermint: Arc<Telemetry>,
+    pub skip_block: Arc<Telemetry>,
+}
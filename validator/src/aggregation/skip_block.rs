@@ -11,8 +11,8 @@ use futures::{
     ready,
     stream::{select, BoxStream, Stream, StreamExt},
 };
-use nimiq_block::{MultiSignature, SignedSkipBlockInfo, SkipBlockInfo, SkipBlockProof};
-use nimiq_bls::{AggregateSignature, KeyPair};
+use nimiq_block::{MultiSignature, SkipBlockInfo, SkipBlockProof};
+use nimiq_bls::{AggregateSignature, Signature};
 use nimiq_collections::BitSet;
 use nimiq_handel::{
     aggregation::Aggregation,
@@ -23,6 +23,7 @@ use nimiq_handel::{
     partitioner::BinomialPartitioner,
     protocol::Protocol,
     store::ReplaceStore,
+    telemetry::Telemetry,
     update::LevelUpdate,
 };
 use nimiq_hash::Blake2sHash;
@@ -251,11 +252,14 @@ pub struct SkipBlockAggregation {}
 impl SkipBlockAggregation {
     pub async fn start<N: ValidatorNetwork + 'static>(
         skip_block_info: SkipBlockInfo,
-        voting_key: KeyPair,
+        // Our signature of the skip block info.
+        signature: Signature,
         // TODO: This seems to be a SlotBand. Change this to a proper Validator ID.
         validator_id: u16,
         active_validators: Validators,
         network: Arc<N>,
+        config: Config,
+        telemetry: Arc<Telemetry>,
     ) -> (SkipBlockInfo, SkipBlockProof) {
         // TODO expose this somewehere else so we don't need to clone here.
         let weights = Arc::new(ValidatorRegistry::new(active_validators.clone()));
@@ -271,15 +275,8 @@ impl SkipBlockAggregation {
                 &skip_block_info,
                 message_hash
             );
-            let signed_skip_block_info = SignedSkipBlockInfo::from_message(
-                skip_block_info.clone(),
-                &voting_key.secret_key,
-                validator_id,
-            );
-
-            let signature = AggregateSignature::from_signatures(&[signed_skip_block_info
-                .signature
-                .multiply(slots.len() as u16)]);
+            let aggregate_signature =
+                AggregateSignature::from_signatures(&[signature.multiply(slots.len() as u16)]);
 
             let mut signers = BitSet::new();
             for slot in slots.clone() {
@@ -287,7 +284,7 @@ impl SkipBlockAggregation {
             }
 
             let own_contribution = SignedSkipBlockMessage {
-                proof: MultiSignature::new(signature, signers),
+                proof: MultiSignature::new(aggregate_signature, signers),
             };
 
             warn!(
@@ -310,7 +307,8 @@ impl SkipBlockAggregation {
 
             let aggregation = Aggregation::new(
                 protocol,
-                Config::default(),
+                config.clone(),
+                Arc::clone(&telemetry),
                 own_contribution,
                 Box::pin(input_switch),
                 NetworkWrapper::new(skip_block_info.clone(), Arc::clone(&network)),
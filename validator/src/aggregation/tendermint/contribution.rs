@@ -1,7 +1,7 @@
 use std::{collections::BTreeMap, ops};
 
 use nimiq_block::MultiSignature;
-use nimiq_bls::{AggregateSignature, SecretKey};
+use nimiq_bls::{AggregateSignature, Signature};
 use nimiq_collections::bitset::BitSet;
 use nimiq_handel::{
     contribution::{AggregatableContribution, ContributionError},
@@ -26,16 +26,17 @@ impl std::fmt::Debug for TendermintContribution {
 }
 
 impl TendermintContribution {
+    /// Creates our own contribution from our signature of the given vote.
     pub(crate) fn from_vote(
         vote: TendermintVote,
-        secret_key: &SecretKey,
+        signature: Signature,
         validator_slots: ops::Range<u16>,
     ) -> Self {
         assert!(!validator_slots.is_empty());
-        // sign the hash
-        let signature = AggregateSignature::from_signatures(&[secret_key
-            .sign(&vote)
-            .multiply(validator_slots.len() as u16)]);
+        let signature =
+            AggregateSignature::from_signatures(
+                &[signature.multiply(validator_slots.len() as u16)],
+            );
 
         // get the slots of the validator and insert them into the bitset
         let mut signers = BitSet::new();
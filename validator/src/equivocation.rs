@@ -0,0 +1,896 @@
+//! Detection, gossip and persistence of equivocations.
+//!
+//! The [`EquivocationMonitor`] runs on any full node. It detects forks in the blocks it receives
+//! and, unless the node is a validator, double proposals in the Tendermint proposals it observes.
+//! Proofs detected by other nodes are received on the [`EquivocationProofTopic`], and the proofs
+//! detected locally are published there. Validators additionally detect double votes in the
+//! Tendermint aggregations they take part in with a [`DoubleVoteDetector`] and include the
+//! pending proofs in the micro blocks they produce.
+//!
+//! Proposals are signed over [`SignedProposal::hash`] rather than over the hash of their header,
+//! so two conflicting proposals can't be turned into a
+//! [`DoubleProposalProof`](nimiq_block::DoubleProposalProof). They are recorded as evidence, but
+//! neither gossiped nor included in blocks.
+//!
+//! All evidence is stored until the end of its reporting window, along with the block that
+//! included it.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use futures::{
+    future,
+    stream::{self, StreamExt},
+};
+use nimiq_block::{Block, DoubleVoteProof, EquivocationProof, MicroBlock, MultiSignature};
+use nimiq_blockchain::{interface::HistoryInterface, Blockchain};
+use nimiq_blockchain_interface::{AbstractBlockchain, BlockchainEvent, ForkEvent};
+use nimiq_database::{
+    declare_table,
+    mdbx::MdbxDatabase,
+    traits::{Database, ReadCursor, ReadTransaction, WriteCursor, WriteTransaction},
+};
+use nimiq_database_value_derive::DbSerializable;
+use nimiq_hash::{Blake2bHash, Blake2sHash, Hash};
+use nimiq_keys::Address;
+use nimiq_network_interface::network::{MsgAcceptance, Network, Topic};
+use nimiq_primitives::{
+    networks::NetworkId, policy::Policy, slots_allocation::Validators, TendermintIdentifier,
+    TendermintStep,
+};
+use nimiq_serde::{Deserialize, Serialize};
+use nimiq_transaction::{DoubleProposalLocator, DoubleVoteLocator, EquivocationLocator};
+use nimiq_utils::time::systemtime_to_timestamp;
+use parking_lot::{Mutex, RwLock};
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::BroadcastStream;
+
+use crate::aggregation::tendermint::{
+    contribution::TendermintContribution, proposal::SignedProposal,
+};
+
+/// The topic on which equivocation proofs are gossiped.
+#[derive(Clone, Debug, Default)]
+pub struct EquivocationProofTopic;
+
+impl Topic for EquivocationProofTopic {
+    type Item = EquivocationProof;
+
+    const BUFFER_SIZE: usize = 16;
+    const NAME: &'static str = "equivocation-proofs";
+    const VALIDATE: bool = true;
+}
+
+/// The Tendermint proposal topic, as observed by nodes that are not validators.
+/// See [`ProposalTopic`](crate::r#macro::ProposalTopic).
+#[derive(Clone, Debug, Default)]
+struct ObservedProposalTopic;
+
+impl Topic for ObservedProposalTopic {
+    type Item = SignedProposal;
+
+    const BUFFER_SIZE: usize = 8;
+    const NAME: &'static str = "tendermint-proposal";
+    const VALIDATE: bool = true;
+}
+
+/// Two different proposals signed by the same validator for the same height and round.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ConflictingProposals {
+    pub validator_address: Address,
+    pub proposal1: SignedProposal,
+    pub proposal2: SignedProposal,
+}
+
+impl ConflictingProposals {
+    pub fn block_number(&self) -> u32 {
+        self.proposal1.proposal.block_number
+    }
+
+    pub fn round(&self) -> u32 {
+        self.proposal1.round
+    }
+
+    /// Returns the hashes of the conflicting headers.
+    pub fn header_hashes(&self) -> (Blake2bHash, Blake2bHash) {
+        (
+            self.proposal1.proposal.hash(),
+            self.proposal2.proposal.hash(),
+        )
+    }
+}
+
+/// The evidence of an equivocation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Evidence {
+    /// A proof that can be included in a block.
+    Proof(EquivocationProof),
+    /// Conflicting proposals, which can't be included in a block.
+    DoubleProposal(ConflictingProposals),
+}
+
+impl Evidence {
+    pub fn locator(&self) -> EquivocationLocator {
+        match self {
+            Evidence::Proof(proof) => proof.locator(),
+            Evidence::DoubleProposal(proposals) => {
+                EquivocationLocator::DoubleProposal(DoubleProposalLocator {
+                    validator_address: proposals.validator_address.clone(),
+                    block_number: proposals.block_number(),
+                    round: proposals.round(),
+                })
+            }
+        }
+    }
+
+    pub fn validator_address(&self) -> &Address {
+        match self {
+            Evidence::Proof(proof) => proof.validator_address(),
+            Evidence::DoubleProposal(proposals) => &proposals.validator_address,
+        }
+    }
+
+    pub fn block_number(&self) -> u32 {
+        match self {
+            Evidence::Proof(proof) => proof.block_number(),
+            Evidence::DoubleProposal(proposals) => proposals.block_number(),
+        }
+    }
+
+    /// Returns whether the equivocation is still within its reporting window at the given block
+    /// number, see [`EquivocationProof::is_valid_at`].
+    pub fn is_valid_at(&self, block_number: u32) -> bool {
+        block_number <= Policy::last_block_of_reporting_window(self.block_number())
+            && Policy::batch_at(block_number) >= Policy::batch_at(self.block_number())
+    }
+}
+
+/// The status of an equivocation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EquivocationStatus {
+    /// The evidence can't be included in a block.
+    Detected,
+    /// The proof wasn't included in a block yet.
+    Pending,
+    /// The proof was included in the block with the given number.
+    Included(u32),
+}
+
+/// An equivocation known to this node.
+#[derive(Clone, Debug, Serialize, Deserialize, DbSerializable)]
+pub struct EquivocationRecord {
+    pub evidence: Evidence,
+    /// The Unix time in milliseconds at which this node learned about the equivocation.
+    pub detected_at: u64,
+    /// Whether the evidence was received from other nodes or seen in a block, rather than
+    /// detected by this node.
+    pub received: bool,
+    /// The number of the block that included the proof.
+    pub included_in: Option<u32>,
+}
+
+impl EquivocationRecord {
+    fn new(evidence: Evidence, received: bool) -> Self {
+        Self {
+            evidence,
+            detected_at: systemtime_to_timestamp(SystemTime::now()),
+            received,
+            included_in: None,
+        }
+    }
+
+    pub fn status(&self) -> EquivocationStatus {
+        match (&self.evidence, self.included_in) {
+            (_, Some(block_number)) => EquivocationStatus::Included(block_number),
+            (Evidence::Proof(_), None) => EquivocationStatus::Pending,
+            (Evidence::DoubleProposal(_), None) => EquivocationStatus::Detected,
+        }
+    }
+}
+
+declare_table!(EquivocationTable, "Equivocations", Blake2bHash => EquivocationRecord);
+
+enum MonitorEvent<Id> {
+    Proof(EquivocationProof, Id),
+    Proposal(SignedProposal, Id),
+    Fork(ForkEvent),
+    Blockchain(BlockchainEvent),
+    Pending(EquivocationRecord),
+}
+
+#[derive(Clone)]
+pub struct EquivocationMonitor {
+    env: MdbxDatabase,
+    blockchain: Arc<RwLock<Blockchain>>,
+    /// Whether to subscribe to the Tendermint proposals. Validators pass their proposals to
+    /// [`Self::observe_proposal`] themselves.
+    observe_proposals: bool,
+    /// The first proposal seen for each validator, height and round.
+    proposals: Arc<Mutex<HashMap<DoubleProposalLocator, SignedProposal>>>,
+    /// Notifies about new pending proofs.
+    pending: broadcast::Sender<EquivocationRecord>,
+}
+
+impl EquivocationMonitor {
+    /// The number of rounds of a macro block for which proposals are observed.
+    pub const MAX_ROUNDS: u32 = 32;
+
+    pub fn new(
+        env: MdbxDatabase,
+        blockchain: Arc<RwLock<Blockchain>>,
+        observe_proposals: bool,
+    ) -> Self {
+        env.create_regular_table(&EquivocationTable);
+
+        Self {
+            env,
+            blockchain,
+            observe_proposals,
+            proposals: Arc::new(Mutex::new(HashMap::new())),
+            pending: broadcast::channel(64).0,
+        }
+    }
+
+    /// Subscribes to the proofs that become pending, whether detected or received.
+    pub fn subscribe(&self) -> broadcast::Receiver<EquivocationRecord> {
+        self.pending.subscribe()
+    }
+
+    /// Returns the known equivocations, optionally only those of the given validator, ordered by
+    /// block number.
+    pub fn get(&self, validator_address: Option<&Address>) -> Vec<EquivocationRecord> {
+        let txn = self.env.read_transaction();
+        let cursor = ReadTransaction::cursor(&txn, &EquivocationTable);
+
+        let mut records: Vec<_> = cursor
+            .into_iter_start()
+            .map(|(_, record)| record)
+            .filter(|record: &EquivocationRecord| {
+                validator_address.map_or(true, |address| {
+                    record.evidence.validator_address() == address
+                })
+            })
+            .collect();
+        records.sort_by_key(|record| (record.evidence.block_number(), record.detected_at));
+        records
+    }
+
+    /// Records the given evidence, unless it is already known, outdated or already included in
+    /// the chain. Returns whether it was recorded.
+    pub fn report(&self, evidence: Evidence, received: bool) -> bool {
+        let key = evidence.locator().hash::<Blake2bHash>();
+
+        // Keep the lock until the evidence is stored, such that it is not included concurrently.
+        let blockchain = self.blockchain.read();
+        if !evidence.is_valid_at(blockchain.block_number() + 1) {
+            return false;
+        }
+        if let Evidence::Proof(proof) = &evidence {
+            if blockchain
+                .history_store
+                .has_equivocation_proof(proof.locator(), None)
+            {
+                return false;
+            }
+        }
+
+        let mut txn = self.env.write_transaction();
+        if txn.get(&EquivocationTable, &key).is_some() {
+            return false;
+        }
+        let record = EquivocationRecord::new(evidence, received);
+        txn.put(&EquivocationTable, &key, &record);
+        txn.commit();
+        drop(blockchain);
+
+        info!(
+            validator_address = %record.evidence.validator_address(),
+            block_number = record.evidence.block_number(),
+            received,
+            "Equivocation detected"
+        );
+        if matches!(record.evidence, Evidence::Proof(_)) {
+            // Nobody might be listening.
+            _ = self.pending.send(record);
+        }
+        true
+    }
+
+    /// Verifies a gossiped proof against the validators of its epoch.
+    fn verify_proof(&self, proof: &EquivocationProof) -> MsgAcceptance {
+        let blockchain = self.blockchain.read();
+        if !proof.is_valid_at(blockchain.block_number() + 1) {
+            return MsgAcceptance::Ignore;
+        }
+        let validators = match blockchain
+            .get_validators_for_epoch(Policy::epoch_at(proof.block_number()), None)
+        {
+            Ok(validators) => validators,
+            Err(_) => return MsgAcceptance::Ignore,
+        };
+        match proof.verify(blockchain.network_id(), &validators) {
+            Ok(()) => MsgAcceptance::Accept,
+            Err(error) => {
+                debug!(%error, ?proof, "Received invalid equivocation proof");
+                MsgAcceptance::Reject
+            }
+        }
+    }
+
+    /// Checks a Tendermint proposal and records a double proposal if its proposer already signed
+    /// a different proposal for the same height and round. Only proposals for the next macro block
+    /// signed by the proposer of their round are accepted, up to [`Self::MAX_ROUNDS`] rounds.
+    pub fn observe_proposal(&self, proposal: &SignedProposal) -> MsgAcceptance {
+        let block_number = proposal.proposal.block_number;
+        if proposal.round >= Self::MAX_ROUNDS {
+            return MsgAcceptance::Ignore;
+        }
+
+        let blockchain = self.blockchain.read();
+        if block_number != Policy::macro_block_after(blockchain.block_number()) {
+            return MsgAcceptance::Ignore;
+        }
+
+        // The proposer of the round is determined by the seed of the preceding micro block.
+        let vrf_seed = match blockchain.get_block(&proposal.proposal.parent_hash, false, None) {
+            Ok(Block::Micro(block)) if block.header.block_number + 1 == block_number => {
+                block.header.seed
+            }
+            _ => return MsgAcceptance::Ignore,
+        };
+        let proposer =
+            match blockchain.get_proposer(block_number, proposal.round, vrf_seed.entropy(), None) {
+                Ok(proposer) => proposer,
+                Err(_) => return MsgAcceptance::Ignore,
+            };
+        drop(blockchain);
+
+        if proposal.signer != proposer.band {
+            return MsgAcceptance::Reject;
+        }
+        let proposer = proposer.validator;
+        let data = SignedProposal::hash(&proposal.proposal, proposal.round, proposal.valid_round)
+            .serialize_to_vec();
+        if !proposer.signing_key.verify(&proposal.signature, &data) {
+            return MsgAcceptance::Reject;
+        }
+
+        let locator = DoubleProposalLocator {
+            validator_address: proposer.address.clone(),
+            block_number,
+            round: proposal.round,
+        };
+        let mut proposals = self.proposals.lock();
+        let Some(first) = proposals.get(&locator) else {
+            proposals.insert(locator, proposal.clone());
+            return MsgAcceptance::Accept;
+        };
+        if first.proposal.hash::<Blake2bHash>() != proposal.proposal.hash::<Blake2bHash>() {
+            let evidence = Evidence::DoubleProposal(ConflictingProposals {
+                validator_address: locator.validator_address,
+                proposal1: first.clone(),
+                proposal2: proposal.clone(),
+            });
+            drop(proposals);
+            self.report(evidence, false);
+        }
+        MsgAcceptance::Accept
+    }
+
+    fn on_blockchain_event(&self, event: BlockchainEvent) {
+        match event {
+            BlockchainEvent::Extended(hash)
+            | BlockchainEvent::Finalized(hash)
+            | BlockchainEvent::EpochFinalized(hash) => {
+                let block = self.blockchain.read().get_block(&hash, true);
+                match block {
+                    Ok(block) => self.apply_block(&block),
+                    Err(error) => warn!(%error, %hash, "Failed to get block"),
+                }
+            }
+            BlockchainEvent::Rebranched(old_chain, new_chain) => {
+                for (_hash, block) in old_chain.iter() {
+                    self.revert_block(block);
+                }
+                for (_hash, block) in new_chain.iter() {
+                    self.apply_block(block);
+                }
+            }
+            BlockchainEvent::HistoryAdopted(_) | BlockchainEvent::Stored(_) => {}
+        }
+    }
+
+    /// Marks the proofs included in a micro block. After a macro block, the evidence whose
+    /// reporting window ended is removed.
+    fn apply_block(&self, block: &Block) {
+        match block {
+            Block::Micro(MicroBlock {
+                body: Some(body), ..
+            }) => {
+                let mut txn = self.env.write_transaction();
+                for proof in &body.equivocation_proofs {
+                    let key = proof.locator().hash::<Blake2bHash>();
+                    let mut record = txn.get(&EquivocationTable, &key).unwrap_or_else(|| {
+                        EquivocationRecord::new(Evidence::Proof(proof.clone()), true)
+                    });
+                    record.included_in = Some(block.block_number());
+                    txn.put(&EquivocationTable, &key, &record);
+                }
+                txn.commit();
+            }
+            Block::Macro(_) => {
+                let next_block_number = block.block_number() + 1;
+                self.proposals
+                    .lock()
+                    .retain(|locator, _| locator.block_number >= next_block_number);
+
+                let txn = self.env.write_transaction();
+                let mut cursor = WriteTransaction::cursor(&txn, &EquivocationTable);
+                let mut pos = cursor.first();
+                while let Some((_, record)) = pos {
+                    if !record.evidence.is_valid_at(next_block_number) {
+                        cursor.remove();
+                    }
+                    pos = cursor.next();
+                }
+                drop(cursor);
+                txn.commit();
+            }
+            _ => {}
+        }
+    }
+
+    /// Unmarks the proofs included in a reverted micro block.
+    fn revert_block(&self, block: &Block) {
+        if let Block::Micro(MicroBlock {
+            body: Some(body), ..
+        }) = block
+        {
+            let mut txn = self.env.write_transaction();
+            for proof in &body.equivocation_proofs {
+                let key = proof.locator().hash::<Blake2bHash>();
+                if let Some(mut record) = txn.get(&EquivocationTable, &key) {
+                    record.included_in = None;
+                    txn.put(&EquivocationTable, &key, &record);
+                }
+            }
+            txn.commit();
+        }
+    }
+
+    /// Keeps track of the equivocations and gossips the proofs detected by this node. This
+    /// future doesn't terminate.
+    pub async fn run<N: Network>(self, network: Arc<N>) {
+        let (fork_events, blockchain_events) = {
+            let blockchain = self.blockchain.read();
+            (
+                BroadcastStream::new(blockchain.fork_notifier.subscribe()),
+                blockchain.notifier_as_stream(),
+            )
+        };
+        let fork_events =
+            fork_events.filter_map(|event| future::ready(event.ok().map(MonitorEvent::Fork)));
+        let blockchain_events = blockchain_events.map(MonitorEvent::Blockchain);
+        let pending = BroadcastStream::new(self.subscribe())
+            .filter_map(|record| future::ready(record.ok().map(MonitorEvent::Pending)));
+
+        let proofs = network
+            .subscribe::<EquivocationProofTopic>()
+            .await
+            .expect("Failed to subscribe to equivocation proof topic")
+            .map(|(proof, id)| MonitorEvent::Proof(proof, id));
+
+        let mut streams = vec![
+            fork_events.boxed(),
+            blockchain_events.boxed(),
+            pending.boxed(),
+            proofs.boxed(),
+        ];
+        if self.observe_proposals {
+            let proposals = network
+                .subscribe::<ObservedProposalTopic>()
+                .await
+                .expect("Failed to subscribe to proposal topic")
+                .map(|(proposal, id)| MonitorEvent::Proposal(proposal, id));
+            streams.push(proposals.boxed());
+        }
+
+        let mut events = stream::select_all(streams);
+        while let Some(event) = events.next().await {
+            match event {
+                MonitorEvent::Proof(proof, id) => {
+                    let acceptance = self.verify_proof(&proof);
+                    if matches!(acceptance, MsgAcceptance::Accept) {
+                        self.report(Evidence::Proof(proof), true);
+                    }
+                    network.validate_message::<EquivocationProofTopic>(id, acceptance);
+                }
+                MonitorEvent::Proposal(proposal, id) => {
+                    let acceptance = self.observe_proposal(&proposal);
+                    network.validate_message::<ObservedProposalTopic>(id, acceptance);
+                }
+                MonitorEvent::Fork(ForkEvent::Detected(fork_proof)) => {
+                    self.report(Evidence::Proof(fork_proof.into()), false);
+                }
+                MonitorEvent::Blockchain(event) => self.on_blockchain_event(event),
+                MonitorEvent::Pending(EquivocationRecord {
+                    evidence: Evidence::Proof(proof),
+                    received: false,
+                    ..
+                }) => {
+                    if let Err(error) = network.publish::<EquivocationProofTopic>(proof).await {
+                        warn!(%error, "Failed to publish equivocation proof");
+                    }
+                }
+                MonitorEvent::Pending(_) => {}
+            }
+        }
+    }
+}
+
+/// Detects double votes in the Tendermint aggregations of a macro block.
+///
+/// A validator votes at most once per round and step. The verified aggregates of each round and
+/// step are kept per proposal hash, and a validator whose slots signed aggregates for two
+/// different proposal hashes voted twice.
+pub(crate) struct DoubleVoteDetector {
+    network_id: NetworkId,
+    block_number: u32,
+    validators: Validators,
+    /// The verified aggregates of each round and step, by proposal hash.
+    aggregates: HashMap<(u32, TendermintStep), BTreeMap<Option<Blake2sHash>, Vec<MultiSignature>>>,
+    reported: HashSet<DoubleVoteLocator>,
+    proof_tx: mpsc::UnboundedSender<EquivocationProof>,
+}
+
+impl DoubleVoteDetector {
+    /// The maximum number of aggregates kept per round, step and proposal hash.
+    const MAX_AGGREGATES: usize = 16;
+
+    pub fn new(
+        network_id: NetworkId,
+        block_number: u32,
+        validators: Validators,
+        proof_tx: mpsc::UnboundedSender<EquivocationProof>,
+    ) -> Self {
+        Self {
+            network_id,
+            block_number,
+            validators,
+            aggregates: HashMap::new(),
+            reported: HashSet::new(),
+            proof_tx,
+        }
+    }
+
+    /// Checks a verified aggregate of the given round and step against the ones seen before and
+    /// sends a proof for every validator that voted twice.
+    pub fn observe(
+        &mut self,
+        round: u32,
+        step: TendermintStep,
+        aggregate: &TendermintContribution,
+    ) {
+        let id = TendermintIdentifier {
+            network: self.network_id,
+            block_number: self.block_number,
+            round_number: round,
+            step,
+        };
+        let votes = self.aggregates.entry((round, step)).or_default();
+
+        for (proposal_hash, signature) in &aggregate.contributions {
+            for (other_hash, other_signatures) in votes.iter() {
+                if other_hash == proposal_hash {
+                    continue;
+                }
+                for other_signature in other_signatures {
+                    let overlap = &signature.signers & &other_signature.signers;
+                    for slot in overlap.iter() {
+                        let validator = self.validators.get_validator_by_slot_number(slot as u16);
+                        let locator = DoubleVoteLocator {
+                            validator_address: validator.address.clone(),
+                            block_number: self.block_number,
+                            round,
+                            step,
+                        };
+                        if !self.reported.insert(locator) {
+                            continue;
+                        }
+                        let proof = DoubleVoteProof::new(
+                            id.clone(),
+                            validator.address.clone(),
+                            proposal_hash.clone(),
+                            signature.signature.clone(),
+                            signature.signers.clone(),
+                            other_hash.clone(),
+                            other_signature.signature.clone(),
+                            other_signature.signers.clone(),
+                        );
+                        // The validator might be shutting down.
+                        _ = self.proof_tx.send(proof.into());
+                    }
+                }
+            }
+
+            let signatures = votes.entry(proposal_hash.clone()).or_default();
+            if signatures.len() < Self::MAX_AGGREGATES && !signatures.contains(signature) {
+                signatures.push(signature.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use nimiq_block::ForkProof;
+    use nimiq_network_mock::MockHub;
+    use nimiq_primitives::TendermintVote;
+    use nimiq_test_log::test;
+    use nimiq_test_utils::{
+        block_production::TemporaryBlockProducer, blockchain::fill_micro_blocks,
+    };
+    use nimiq_time::{sleep, timeout};
+    use nimiq_utils::spawn;
+
+    use super::*;
+
+    fn monitor(producer: &TemporaryBlockProducer) -> EquivocationMonitor {
+        EquivocationMonitor::new(
+            MdbxDatabase::new_volatile(Default::default()).unwrap(),
+            Arc::clone(&producer.blockchain),
+            true,
+        )
+    }
+
+    fn validator_address(producer: &TemporaryBlockProducer) -> Address {
+        producer
+            .blockchain
+            .read()
+            .current_validators()
+            .unwrap()
+            .iter()
+            .next()
+            .unwrap()
+            .address
+            .clone()
+    }
+
+    /// Returns a fork proof of two micro blocks produced at the next height.
+    fn fork_proof(producer: &TemporaryBlockProducer) -> EquivocationProof {
+        let block1 = producer.next_block_no_push(vec![1], false).unwrap_micro();
+        let block2 = producer.next_block_no_push(vec![2], false).unwrap_micro();
+        ForkProof::new(
+            validator_address(producer),
+            block1.header,
+            block1.justification.unwrap().unwrap_micro(),
+            block2.header,
+            block2.justification.unwrap().unwrap_micro(),
+        )
+        .into()
+    }
+
+    #[test]
+    fn it_records_fork_proofs_until_included() {
+        let producer = TemporaryBlockProducer::new();
+        let monitor = monitor(&producer);
+        producer.next_block(vec![], false);
+        let proof = fork_proof(&producer);
+
+        assert!(monitor.report(Evidence::Proof(proof.clone()), false));
+        assert!(!monitor.report(Evidence::Proof(proof.clone()), true));
+        let records = monitor.get(None);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].status(), EquivocationStatus::Pending);
+        assert!(!records[0].received);
+
+        let block = {
+            let blockchain = producer.blockchain.read();
+            Block::Micro(producer.producer.next_micro_block(
+                &blockchain,
+                blockchain.timestamp() + Policy::BLOCK_SEPARATION_TIME,
+                vec![proof.clone()],
+                vec![],
+                vec![],
+                None,
+            ))
+        };
+        producer.push(block.clone()).unwrap();
+        monitor.on_blockchain_event(BlockchainEvent::Extended(block.hash()));
+
+        let block_number = block.block_number();
+        let records = monitor.get(Some(&validator_address(&producer)));
+        assert_eq!(
+            records[0].status(),
+            EquivocationStatus::Included(block_number)
+        );
+        assert!(monitor.get(Some(&Address::from([1; 20]))).is_empty());
+
+        // Included proofs are not reported again.
+        assert!(!monitor.report(Evidence::Proof(proof), false));
+
+        monitor.revert_block(&block);
+        assert_eq!(monitor.get(None)[0].status(), EquivocationStatus::Pending);
+    }
+
+    #[test(tokio::test)]
+    async fn it_gossips_detected_proofs() {
+        let producer = TemporaryBlockProducer::new();
+        producer.next_block(vec![], false);
+        let monitor1 = monitor(&producer);
+        let monitor2 = monitor(&producer);
+
+        let mut hub = MockHub::default();
+        let network1 = Arc::new(hub.new_network());
+        let network2 = Arc::new(hub.new_network());
+        network1.dial_mock(&network2);
+        spawn(monitor1.clone().run(network1));
+        spawn(monitor2.clone().run(network2));
+
+        // Wait for both monitors to subscribe to the topics.
+        sleep(Duration::from_millis(100)).await;
+
+        let proof = fork_proof(&producer);
+        assert!(monitor1.report(Evidence::Proof(proof), false));
+
+        timeout(Duration::from_secs(1), async {
+            while monitor2.get(None).is_empty() {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("Proof wasn't gossiped");
+
+        // The proof is only recorded as received by the other node.
+        assert!(!monitor1.get(None)[0].received);
+        let records = monitor2.get(None);
+        assert_eq!(records.len(), 1);
+        assert!(records[0].received);
+        assert_eq!(records[0].status(), EquivocationStatus::Pending);
+    }
+
+    /// Signs a proposal for the next macro block as the given slot band.
+    fn sign_proposal(
+        producer: &TemporaryBlockProducer,
+        round: u32,
+        signer: u16,
+        extra_data: Vec<u8>,
+    ) -> SignedProposal {
+        let blockchain = producer.blockchain.read();
+        let header = producer
+            .producer
+            .next_macro_block_proposal(
+                &blockchain,
+                blockchain.timestamp() + Policy::BLOCK_SEPARATION_TIME,
+                round,
+                extra_data,
+            )
+            .header;
+        let data = SignedProposal::hash(&header, round, None).serialize_to_vec();
+        SignedProposal {
+            signature: producer.producer.signing_key.sign(&data),
+            proposal: header,
+            round,
+            valid_round: None,
+            signer,
+        }
+    }
+
+    #[test]
+    fn it_detects_double_proposals() {
+        let producer = TemporaryBlockProducer::new();
+        let monitor = monitor(&producer);
+
+        fill_micro_blocks(&producer.producer, &producer.blockchain);
+        let proposal1 = sign_proposal(&producer, 0, 0, vec![1]);
+        let proposal2 = sign_proposal(&producer, 0, 0, vec![2]);
+
+        // Only proposals for the next macro block are observed.
+        let mut later = proposal1.clone();
+        later.proposal.block_number += Policy::blocks_per_batch();
+        assert!(matches!(
+            monitor.observe_proposal(&later),
+            MsgAcceptance::Ignore
+        ));
+
+        let mut forged = proposal1.clone();
+        forged.round = 1;
+        assert!(matches!(
+            monitor.observe_proposal(&forged),
+            MsgAcceptance::Reject
+        ));
+        let mut wrong_signer = proposal1.clone();
+        wrong_signer.signer = 1;
+        assert!(matches!(
+            monitor.observe_proposal(&wrong_signer),
+            MsgAcceptance::Reject
+        ));
+        let late_round = sign_proposal(&producer, EquivocationMonitor::MAX_ROUNDS, 0, vec![]);
+        assert!(matches!(
+            monitor.observe_proposal(&late_round),
+            MsgAcceptance::Ignore
+        ));
+
+        assert!(matches!(
+            monitor.observe_proposal(&proposal1),
+            MsgAcceptance::Accept
+        ));
+        assert!(matches!(
+            monitor.observe_proposal(&proposal1),
+            MsgAcceptance::Accept
+        ));
+        assert!(monitor.get(None).is_empty());
+
+        monitor.observe_proposal(&proposal2);
+        let records = monitor.get(None);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].status(), EquivocationStatus::Detected);
+        assert_eq!(
+            records[0].evidence.validator_address(),
+            &validator_address(&producer)
+        );
+    }
+
+    #[test]
+    fn it_detects_double_votes() {
+        let producer = TemporaryBlockProducer::new();
+        let validators = producer.blockchain.read().current_validators().unwrap();
+        let block_number = Policy::macro_block_after(producer.blockchain.read().block_number());
+        let (proof_tx, mut proof_rx) = mpsc::unbounded_channel();
+        let mut detector = DoubleVoteDetector::new(
+            NetworkId::UnitAlbatross,
+            block_number,
+            validators.clone(),
+            proof_tx,
+        );
+
+        let vote = |step, proposal_hash: Option<Blake2sHash>| {
+            let vote = TendermintVote {
+                proposal_hash,
+                id: TendermintIdentifier {
+                    network: NetworkId::UnitAlbatross,
+                    block_number,
+                    round_number: 0,
+                    step,
+                },
+            };
+            let signature = producer.producer.voting_key.sign(&vote);
+            TendermintContribution::from_vote(
+                vote,
+                signature,
+                validators.validators[0].slots.clone(),
+            )
+        };
+
+        let prevote = |hash| vote(TendermintStep::PreVote, hash);
+        detector.observe(
+            0,
+            TendermintStep::PreVote,
+            &prevote(Some(Blake2sHash::from([1; 32]))),
+        );
+        detector.observe(
+            0,
+            TendermintStep::PreCommit,
+            &vote(TendermintStep::PreCommit, None),
+        );
+        assert!(proof_rx.try_recv().is_err());
+
+        detector.observe(0, TendermintStep::PreVote, &prevote(None));
+        let proof = proof_rx.try_recv().unwrap();
+        assert_eq!(proof.validator_address(), &validators.validators[0].address);
+        proof.verify(NetworkId::UnitAlbatross, &validators).unwrap();
+
+        // Each double vote is reported once.
+        detector.observe(
+            0,
+            TendermintStep::PreVote,
+            &prevote(Some(Blake2sHash::from([2; 32]))),
+        );
+        assert!(proof_rx.try_recv().is_err());
+    }
+}
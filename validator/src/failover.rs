@@ -0,0 +1,568 @@
+//! Active/standby failover between several hosts running the same validator.
+//!
+//! Only one host may sign at a time. The hosts compete for a lease in a [`LeaseStore`] they
+//! share. The holder of the lease is active and renews it periodically. All other hosts stay
+//! synced, but paused, and take over once the lease expires. An active host that fails to renew
+//! its lease stops signing before the lease expires, so that two hosts never sign at the same time
+//! as long as their clocks are roughly in sync.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, ErrorKind, Write},
+    path::PathBuf,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt};
+use nimiq_time::{sleep, timeout};
+use parking_lot::Mutex;
+use thiserror::Error;
+use tokio::{task::spawn_blocking, time::Instant};
+
+#[derive(Debug, Error)]
+pub enum LeaseError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("Malformed lease: {0}")]
+    Malformed(String),
+    #[error("Lease is locked by another host")]
+    Contended,
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time is before the unix epoch")
+        .as_millis() as u64
+}
+
+/// A lease shared by the hosts of a validator.
+#[async_trait]
+pub trait LeaseStore: Send + Sync {
+    /// Acquires the lease for `holder` for `duration` if it is free, expired or already held by
+    /// `holder`, in which case it is renewed. Returns whether `holder` holds the lease afterwards,
+    /// or [`LeaseError::Contended`] if the lease couldn't be checked because another host is
+    /// updating it.
+    async fn acquire(&self, holder: &str, duration: Duration) -> Result<bool, LeaseError>;
+
+    /// Releases the lease if it is held by `holder`.
+    async fn release(&self, holder: &str) -> Result<(), LeaseError>;
+}
+
+/// A lease stored in a file, e.g. on a file system shared by the hosts.
+///
+/// The file contains the holder and the expiry as a unix timestamp in milliseconds. Updates are
+/// serialized with a lock file next to it. All file operations run on the blocking thread pool.
+#[derive(Clone)]
+pub struct FileLeaseStore {
+    path: PathBuf,
+}
+
+impl FileLeaseStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    fn lock_path(&self) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(".lock");
+        path.into()
+    }
+
+    fn read(&self) -> Result<Option<(String, u64)>, LeaseError> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+
+        let (holder, expiry) = content
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| LeaseError::Malformed(content.clone()))?;
+        let expiry = expiry
+            .parse()
+            .map_err(|_| LeaseError::Malformed(content.clone()))?;
+        Ok(Some((holder.to_string(), expiry)))
+    }
+
+    fn write(&self, holder: &str, expiry: u64) -> Result<(), LeaseError> {
+        // Write to a temporary file first so that the lease is replaced atomically.
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        fs::write(&tmp_path, format!("{} {}\n", holder, expiry))?;
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// Runs `f` while holding the lock file. Fails with [`LeaseError::Contended`] if the lock is
+    /// held by another host.
+    ///
+    /// Each lock file contains a random token, such that a host only removes the lock it created
+    /// and a stale lock is only removed if it is still the one that was found to be stale.
+    fn locked<T>(
+        &self,
+        stale_after: Duration,
+        f: impl FnOnce() -> Result<T, LeaseError>,
+    ) -> Result<T, LeaseError> {
+        let lock_path = self.lock_path();
+        let token = format!("{:016x}\n", rand::random::<u64>());
+        let mut lock = match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock_path)
+        {
+            Ok(lock) => lock,
+            Err(error) if error.kind() == ErrorKind::AlreadyExists => {
+                self.remove_stale_lock(stale_after)?;
+                return Err(LeaseError::Contended);
+            }
+            Err(error) => return Err(error.into()),
+        };
+        lock.write_all(token.as_bytes())?;
+
+        let result = f();
+        // Only remove the lock if it is still ours, it might have been considered stale.
+        match fs::read_to_string(&lock_path) {
+            Ok(content) if content == token => fs::remove_file(&lock_path)?,
+            Ok(_) => {
+                warn!(path = %lock_path.display(), "Lease lock was taken over by another host")
+            }
+            Err(error) if error.kind() == ErrorKind::NotFound => {}
+            Err(error) => return Err(error.into()),
+        }
+        result
+    }
+
+    /// Removes the lock file if it is older than `stale_after`, e.g. because a host crashed while
+    /// holding it.
+    ///
+    /// The lock is moved out of the way atomically before it is removed. If another host replaced
+    /// the stale lock in the meantime, the lock that was moved is that host's and is put back.
+    fn remove_stale_lock(&self, stale_after: Duration) -> Result<(), LeaseError> {
+        let lock_path = self.lock_path();
+        let (content, modified) = match fs::read_to_string(&lock_path)
+            .and_then(|content| Ok((content, fs::metadata(&lock_path)?.modified()?)))
+        {
+            Ok(lock) => lock,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(error.into()),
+        };
+        if modified.elapsed().unwrap_or_default() <= stale_after {
+            return Ok(());
+        }
+
+        let mut stale_path = lock_path.clone().into_os_string();
+        stale_path.push(format!(".{:016x}.stale", rand::random::<u64>()));
+        match fs::rename(&lock_path, &stale_path) {
+            Ok(()) => {}
+            // Another host removed the stale lock already.
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(error.into()),
+        }
+
+        if fs::read_to_string(&stale_path)? == content {
+            warn!(path = %lock_path.display(), "Removed stale lease lock");
+        } else {
+            // Put the lock back unless yet another host created a new one.
+            match fs::hard_link(&stale_path, &lock_path) {
+                Ok(()) => {}
+                Err(error) if error.kind() == ErrorKind::AlreadyExists => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+        fs::remove_file(&stale_path)?;
+        Ok(())
+    }
+
+    fn acquire_blocking(&self, holder: &str, duration: Duration) -> Result<bool, LeaseError> {
+        self.locked(duration, || {
+            let now = unix_millis();
+            match self.read()? {
+                Some((current, expiry)) if current != holder && expiry > now => Ok(false),
+                _ => {
+                    self.write(holder, now + duration.as_millis() as u64)?;
+                    Ok(true)
+                }
+            }
+        })
+    }
+
+    fn release_blocking(&self, holder: &str) -> Result<(), LeaseError> {
+        self.locked(Duration::from_secs(60), || match self.read()? {
+            Some((current, _)) if current == holder => Ok(fs::remove_file(&self.path)?),
+            _ => Ok(()),
+        })
+    }
+
+    /// Runs `f` on the blocking thread pool, since the lease may be on a slow network file system.
+    async fn blocking<T: Send + 'static>(
+        &self,
+        f: impl FnOnce(Self) -> Result<T, LeaseError> + Send + 'static,
+    ) -> Result<T, LeaseError> {
+        let store = self.clone();
+        spawn_blocking(move || f(store))
+            .await
+            .map_err(|error| LeaseError::Io(io::Error::new(ErrorKind::Other, error)))?
+    }
+}
+
+#[async_trait]
+impl LeaseStore for FileLeaseStore {
+    async fn acquire(&self, holder: &str, duration: Duration) -> Result<bool, LeaseError> {
+        let holder = holder.to_string();
+        self.blocking(move |store| store.acquire_blocking(&holder, duration))
+            .await
+    }
+
+    async fn release(&self, holder: &str) -> Result<(), LeaseError> {
+        let holder = holder.to_string();
+        self.blocking(move |store| store.release_blocking(&holder))
+            .await
+    }
+}
+
+/// A lease held in memory, which can be shared by validators running in the same process.
+#[derive(Clone, Default)]
+pub struct MemoryLeaseStore {
+    lease: Arc<Mutex<Option<(String, Instant)>>>,
+}
+
+impl MemoryLeaseStore {
+    /// Returns the current holder of the lease, if any.
+    pub fn holder(&self) -> Option<String> {
+        self.lease
+            .lock()
+            .as_ref()
+            .filter(|(_, expiry)| *expiry > Instant::now())
+            .map(|(holder, _)| holder.clone())
+    }
+}
+
+#[async_trait]
+impl LeaseStore for MemoryLeaseStore {
+    async fn acquire(&self, holder: &str, duration: Duration) -> Result<bool, LeaseError> {
+        let mut lease = self.lease.lock();
+        let now = Instant::now();
+        match lease.as_ref() {
+            Some((current, expiry)) if current != holder && *expiry > now => Ok(false),
+            _ => {
+                *lease = Some((holder.to_string(), now + duration));
+                Ok(true)
+            }
+        }
+    }
+
+    async fn release(&self, holder: &str) -> Result<(), LeaseError> {
+        let mut lease = self.lease.lock();
+        if lease.as_ref().is_some_and(|(current, _)| current == holder) {
+            *lease = None;
+        }
+        Ok(())
+    }
+}
+
+/// Configures the active/standby failover of a validator.
+#[derive(Clone)]
+pub struct FailoverConfig {
+    /// The lease shared by the hosts of the validator.
+    pub store: Arc<dyn LeaseStore>,
+    /// Identifies this host in the lease.
+    pub node_id: String,
+    /// How long the lease is valid without being renewed.
+    pub lease_duration: Duration,
+}
+
+impl FailoverConfig {
+    pub const DEFAULT_LEASE_DURATION: Duration = Duration::from_secs(30);
+
+    /// The lease is renewed four times per lease duration, such that a single failed renewal
+    /// doesn't make the active host step down.
+    fn renew_interval(&self) -> Duration {
+        self.lease_duration / 4
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FailoverRole {
+    /// This host holds the lease and participates in block production.
+    Active,
+    /// Another host holds the lease. This host stays synced, but doesn't sign anything.
+    Standby,
+}
+
+#[derive(Clone, Debug)]
+pub struct FailoverStatus {
+    pub node_id: String,
+    pub role: FailoverRole,
+    /// The number of role changes since the validator was started.
+    pub transitions: u64,
+    /// The unix timestamp in milliseconds of the last role change.
+    pub last_transition: Option<u64>,
+}
+
+impl FailoverStatus {
+    pub(crate) fn new(node_id: String) -> Self {
+        Self {
+            node_id,
+            role: FailoverRole::Standby,
+            transitions: 0,
+            last_transition: None,
+        }
+    }
+
+    pub(crate) fn transition(&mut self, role: FailoverRole) {
+        self.role = role;
+        self.transitions += 1;
+        self.last_transition = Some(unix_millis());
+    }
+}
+
+/// Competes for the lease and keeps track of our role.
+struct LeaseKeeper {
+    config: FailoverConfig,
+    role: FailoverRole,
+    valid_until: Option<Instant>,
+    started: bool,
+}
+
+impl LeaseKeeper {
+    async fn next_role(mut self) -> Option<(FailoverRole, Self)> {
+        loop {
+            if self.started {
+                sleep(self.config.renew_interval()).await;
+            }
+            self.started = true;
+
+            let role = self.check().await;
+            if role != self.role {
+                self.role = role;
+                return Some((role, self));
+            }
+        }
+    }
+
+    async fn check(&mut self) -> FailoverRole {
+        let renew_interval = self.config.renew_interval();
+        let start = Instant::now();
+        let result = timeout(
+            renew_interval,
+            self.config
+                .store
+                .acquire(&self.config.node_id, self.config.lease_duration),
+        )
+        .await;
+
+        let error = match result {
+            Ok(Ok(true)) => {
+                self.valid_until = Some(start + self.config.lease_duration);
+                return FailoverRole::Active;
+            }
+            Ok(Ok(false)) => {
+                self.valid_until = None;
+                return FailoverRole::Standby;
+            }
+            Ok(Err(LeaseError::Contended)) => {
+                debug!("Validator lease is locked by another host");
+                None
+            }
+            Ok(Err(error)) => Some(error.to_string()),
+            Err(_) => Some("timed out".to_string()),
+        };
+        if let Some(error) = error {
+            warn!(%error, "Failed to renew validator lease");
+        }
+
+        // We don't know whether we still hold the lease. Keep our role as long as our lease is
+        // still valid once the next renewal completed or timed out, which happens at most two
+        // renewal intervals from now. Otherwise, step down before the lease expires so that a
+        // standby can take over safely.
+        match self.valid_until {
+            Some(valid_until) if Instant::now() + 2 * renew_interval < valid_until => self.role,
+            _ => {
+                self.valid_until = None;
+                FailoverRole::Standby
+            }
+        }
+    }
+}
+
+/// Returns a stream of the changes of our role. The initial role is [`FailoverRole::Standby`].
+pub(crate) fn role_changes(config: FailoverConfig) -> BoxStream<'static, FailoverRole> {
+    let keeper = LeaseKeeper {
+        config,
+        role: FailoverRole::Standby,
+        valid_until: None,
+        started: false,
+    };
+    stream::unfold(keeper, LeaseKeeper::next_role).boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use nimiq_test_log::test;
+
+    use super::*;
+
+    fn config(store: &MemoryLeaseStore, node_id: &str) -> FailoverConfig {
+        FailoverConfig {
+            store: Arc::new(store.clone()),
+            node_id: node_id.to_string(),
+            lease_duration: Duration::from_secs(3),
+        }
+    }
+
+    #[test(tokio::test(start_paused = true))]
+    async fn standby_takes_over_after_lease_expiry() {
+        let store = MemoryLeaseStore::default();
+
+        let mut first = role_changes(config(&store, "first"));
+        assert_eq!(first.next().await, Some(FailoverRole::Active));
+        // Keep renewing the lease in the background.
+        let first = tokio::spawn(async move { first.next().await });
+
+        let mut second = role_changes(config(&store, "second"));
+        let next = timeout(Duration::from_secs(10), second.next()).await;
+        assert!(next.is_err(), "Second host must stay on standby");
+        assert_eq!(store.holder().as_deref(), Some("first"));
+
+        // The first host stops renewing its lease.
+        first.abort();
+        assert_eq!(second.next().await, Some(FailoverRole::Active));
+        assert_eq!(store.holder().as_deref(), Some("second"));
+    }
+
+    /// A lease store whose renewals can be made to fail.
+    #[derive(Default)]
+    struct FlakyLeaseStore {
+        failing: Mutex<bool>,
+    }
+
+    #[async_trait]
+    impl LeaseStore for FlakyLeaseStore {
+        async fn acquire(&self, _holder: &str, _duration: Duration) -> Result<bool, LeaseError> {
+            if *self.failing.lock() {
+                return Err(LeaseError::Contended);
+            }
+            Ok(true)
+        }
+
+        async fn release(&self, _holder: &str) -> Result<(), LeaseError> {
+            Ok(())
+        }
+    }
+
+    #[test(tokio::test(start_paused = true))]
+    async fn active_host_steps_down_before_lease_expiry() {
+        let store = Arc::new(FlakyLeaseStore::default());
+        let config = FailoverConfig {
+            store: Arc::clone(&store) as Arc<dyn LeaseStore>,
+            node_id: "first".to_string(),
+            lease_duration: Duration::from_secs(4),
+        };
+
+        let mut roles = role_changes(config);
+        assert_eq!(roles.next().await, Some(FailoverRole::Active));
+        let valid_until = Instant::now() + Duration::from_secs(4);
+
+        // A single failed renewal doesn't make the host step down.
+        *store.failing.lock() = true;
+        let next = timeout(Duration::from_millis(1500), roles.next()).await;
+        assert!(next.is_err(), "Host must stay active");
+
+        // Once the renewals keep failing, the host steps down before its lease expires.
+        assert_eq!(roles.next().await, Some(FailoverRole::Standby));
+        assert!(Instant::now() < valid_until);
+    }
+
+    #[test(tokio::test)]
+    async fn file_lease_is_exclusive() {
+        let path = std::env::temp_dir().join(format!("nimiq-lease-{}", rand::random::<u64>()));
+        let store = FileLeaseStore::new(path.clone());
+        let duration = Duration::from_secs(30);
+
+        assert!(store.acquire("first", duration).await.unwrap());
+        assert!(store.acquire("first", duration).await.unwrap());
+        assert!(!store.acquire("second", duration).await.unwrap());
+
+        store.release("second").await.unwrap();
+        assert!(!store.acquire("second", duration).await.unwrap());
+
+        store.release("first").await.unwrap();
+        assert!(store.acquire("second", duration).await.unwrap());
+
+        store.release("second").await.unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test(tokio::test)]
+    async fn stale_file_lock_is_removed() {
+        let path = std::env::temp_dir().join(format!("nimiq-lease-{}", rand::random::<u64>()));
+        let store = FileLeaseStore::new(path.clone());
+        let lock_path = store.lock_path();
+        let duration = Duration::from_secs(30);
+
+        // A fresh lock of another host is left in place.
+        fs::write(&lock_path, "other\n").unwrap();
+        assert!(matches!(
+            store.acquire("first", duration).await,
+            Err(LeaseError::Contended)
+        ));
+        assert_eq!(fs::read_to_string(&lock_path).unwrap(), "other\n");
+
+        // A lock that is older than the lease duration is removed.
+        fs::File::options()
+            .write(true)
+            .open(&lock_path)
+            .unwrap()
+            .set_modified(SystemTime::now() - 2 * duration)
+            .unwrap();
+        assert!(matches!(
+            store.acquire("first", duration).await,
+            Err(LeaseError::Contended)
+        ));
+        assert!(!lock_path.exists());
+        assert!(store.acquire("first", duration).await.unwrap());
+
+        store.release("first").await.unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test(tokio::test)]
+    async fn contended_file_lease_keeps_role() {
+        let path = std::env::temp_dir().join(format!("nimiq-lease-{}", rand::random::<u64>()));
+        let store = FileLeaseStore::new(path.clone());
+        let lock_path = store.lock_path();
+        let config = |node_id: &str| FailoverConfig {
+            store: Arc::new(store.clone()),
+            node_id: node_id.to_string(),
+            lease_duration: Duration::from_millis(1500),
+        };
+
+        let mut first = role_changes(config("first"));
+        assert_eq!(first.next().await, Some(FailoverRole::Active));
+        let mut second = role_changes(config("second"));
+        let second = tokio::spawn(async move { second.next().await });
+
+        // Another host holds the lock during one renewal of the first host, which must neither
+        // step down nor lose the lease to the second host.
+        fs::write(&lock_path, "locked\n").unwrap();
+        let next = timeout(Duration::from_millis(700), first.next()).await;
+        assert!(next.is_err(), "First host must stay active");
+        fs::remove_file(&lock_path).unwrap();
+        let next = timeout(Duration::from_secs(3), first.next()).await;
+        assert!(next.is_err(), "First host must stay active");
+        assert!(!second.is_finished(), "Second host must stay on standby");
+
+        // The first host steps down before its lease expires if the lock stays contended.
+        fs::write(&lock_path, "locked\n").unwrap();
+        let next = timeout(Duration::from_millis(1500), first.next()).await;
+        assert_eq!(next.unwrap(), Some(FailoverRole::Standby));
+
+        second.abort();
+        let _ = fs::remove_file(&lock_path);
+        let _ = fs::remove_file(&path);
+    }
+}
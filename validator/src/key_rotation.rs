@@ -0,0 +1,540 @@
+//! Rotation of the signing and voting keys of a validator.
+//!
+//! The new keys are announced with an `UpdateValidator` transaction, which is signed with the
+//! validator's cold key and includes a proof of knowledge of the new voting key. The staking
+//! contract adopts the new keys as soon as the transaction is included, but the keys used in an
+//! epoch are fixed by the election block preceding it. The validator thus keeps signing with its
+//! old keys until the next election block and switches to the new ones right after it. The pending
+//! keys are stored in the validator database, such that the switch also happens if the validator
+//! is restarted in between.
+//!
+//! The pending secret keys are stored unencrypted in the `KeyRotation` table, just like the keys
+//! in the key file. The validator database must thus be protected like the key file until the
+//! rotation is complete, at which point the pending keys are removed from it.
+
+use nimiq_blockchain::Blockchain;
+use nimiq_blockchain_interface::AbstractBlockchain;
+use nimiq_bls::{KeyPair as BlsKeyPair, PublicKey as BlsPublicKey, SecretKey as BlsSecretKey};
+use nimiq_database::{
+    declare_table,
+    mdbx::MdbxDatabase,
+    traits::{Database, ReadTransaction, WriteTransaction},
+};
+use nimiq_database_value_derive::DbSerializable;
+use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_keys::{
+    Address, Ed25519PublicKey as SchnorrPublicKey, KeyPair as SchnorrKeyPair, PrivateKey,
+};
+use nimiq_primitives::{coin::Coin, policy::Policy};
+use nimiq_serde::{Deserialize, Serialize};
+use nimiq_transaction::Transaction;
+use nimiq_transaction_builder::TransactionBuilder;
+use parking_lot::RwLock;
+use thiserror::Error;
+
+use crate::signer::{LocalSigner, SignerError, ValidatorSigner};
+
+/// Persists the keys of a [`LocalSigner`] once they were replaced, such that a restarted
+/// validator uses the new keys.
+pub trait KeyStore: Send + Sync {
+    /// Fails if the given keys can't be stored, e.g. because there is no key file to write them
+    /// to. This is checked before a key rotation is started.
+    fn check_writable(&self, signing_key: bool, voting_key: bool) -> Result<(), SignerError>;
+
+    fn store_signing_key(&self, key: &SchnorrKeyPair) -> Result<(), SignerError>;
+
+    fn store_voting_key(&self, key: &BlsKeyPair) -> Result<(), SignerError>;
+}
+
+/// The keys announced by an `UpdateValidator` transaction that are not in use yet. The secret keys
+/// are serialized unencrypted.
+#[derive(Clone, Serialize, Deserialize, DbSerializable)]
+struct PendingKeys {
+    signing_key: Option<PrivateKey>,
+    voting_key: Option<BlsSecretKey>,
+    transaction: Transaction,
+}
+
+impl PendingKeys {
+    fn signing_public_key(&self) -> Option<SchnorrPublicKey> {
+        self.signing_key.as_ref().map(SchnorrPublicKey::from)
+    }
+
+    fn voting_public_key(&self) -> Option<BlsPublicKey> {
+        self.voting_key.as_ref().map(BlsPublicKey::from_secret)
+    }
+}
+
+declare_table!(KeyRotationTable, "KeyRotation", () => PendingKeys);
+
+#[derive(Debug, Error)]
+pub enum KeyRotationError {
+    #[error("The validator keys are held by a remote signer")]
+    RemoteSigner,
+    #[error("Neither a new signing key nor a new voting key was given")]
+    NoNewKeys,
+    #[error(
+        "The previous key rotation is confirmed, it becomes effective with the next election block"
+    )]
+    AlreadyConfirmed,
+    #[error("The new keys can't be persisted: {0}")]
+    KeyStore(SignerError),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyRotationState {
+    /// The `UpdateValidator` transaction was not included in the chain yet.
+    Submitted,
+    /// The staking contract contains the new keys. They are used from the next epoch on.
+    Confirmed,
+    /// The transaction wasn't included within its validity window. The rotation needs to be
+    /// started again.
+    Expired,
+}
+
+#[derive(Clone, Debug)]
+pub struct KeyRotationStatus {
+    pub signing_key: Option<SchnorrPublicKey>,
+    pub voting_key: Option<BlsPublicKey>,
+    pub transaction_hash: Blake2bHash,
+    pub validity_start_height: u32,
+    pub state: KeyRotationState,
+    /// The first block signed with the new keys, if the rotation is confirmed.
+    pub effective_from: Option<u32>,
+}
+
+/// Keeps track of a pending key rotation of our validator.
+pub struct KeyRotation {
+    env: MdbxDatabase,
+    pending: RwLock<Option<PendingKeys>>,
+}
+
+impl KeyRotation {
+    pub fn new(env: MdbxDatabase) -> Self {
+        env.create_regular_table(&KeyRotationTable);
+        let pending = env.read_transaction().get(&KeyRotationTable, &());
+
+        Self {
+            env,
+            pending: RwLock::new(pending),
+        }
+    }
+
+    fn store(&self, pending: Option<PendingKeys>) {
+        let mut txn = self.env.write_transaction();
+        match &pending {
+            Some(pending) => txn.put(&KeyRotationTable, &(), pending),
+            None => txn.remove(&KeyRotationTable, &()),
+        }
+        txn.commit();
+        *self.pending.write() = pending;
+    }
+
+    /// Starts rotating to the given keys. Returns the `UpdateValidator` transaction, which the
+    /// caller needs to send to the network. Its fee is paid with the fee key of our validator.
+    ///
+    /// A previous rotation is replaced unless it is already confirmed.
+    pub fn start(
+        &self,
+        blockchain: &Blockchain,
+        validator_address: &Address,
+        signer: &dyn ValidatorSigner,
+        cold_key: &SchnorrKeyPair,
+        signing_key: Option<SchnorrKeyPair>,
+        voting_key: Option<BlsKeyPair>,
+        fee: Coin,
+    ) -> Result<Transaction, KeyRotationError> {
+        let local_keys = signer.local_keys().ok_or(KeyRotationError::RemoteSigner)?;
+        if signing_key.is_none() && voting_key.is_none() {
+            return Err(KeyRotationError::NoNewKeys);
+        }
+        // A restarted validator would go back to the old keys.
+        local_keys
+            .check_key_store(signing_key.is_some(), voting_key.is_some())
+            .map_err(KeyRotationError::KeyStore)?;
+        if self
+            .status(blockchain, validator_address)
+            .is_some_and(|status| status.state == KeyRotationState::Confirmed)
+        {
+            return Err(KeyRotationError::AlreadyConfirmed);
+        }
+
+        let transaction = TransactionBuilder::new_update_validator(
+            &local_keys.fee_key(),
+            cold_key,
+            signing_key.as_ref().map(|key| key.public),
+            voting_key.as_ref(),
+            None,
+            None,
+            fee,
+            blockchain.block_number(),
+            blockchain.network_id(),
+        );
+
+        info!(
+            transaction_hash = %transaction.hash::<Blake2bHash>(),
+            new_signing_key = signing_key.is_some(),
+            new_voting_key = voting_key.is_some(),
+            "Starting validator key rotation"
+        );
+        self.store(Some(PendingKeys {
+            signing_key: signing_key.map(|key| key.private),
+            voting_key: voting_key.map(|key| key.secret_key),
+            transaction: transaction.clone(),
+        }));
+        Ok(transaction)
+    }
+
+    /// Returns the status of the pending key rotation, if any.
+    pub fn status(
+        &self,
+        blockchain: &Blockchain,
+        validator_address: &Address,
+    ) -> Option<KeyRotationStatus> {
+        let pending = self.pending.read().clone()?;
+        let validity_start_height = pending.transaction.validity_start_height;
+
+        let state = if Self::is_in_staking_contract(blockchain, validator_address, &pending) {
+            KeyRotationState::Confirmed
+        } else if blockchain.block_number()
+            >= validity_start_height + Policy::transaction_validity_window_blocks()
+        {
+            KeyRotationState::Expired
+        } else {
+            KeyRotationState::Submitted
+        };
+
+        Some(KeyRotationStatus {
+            signing_key: pending.signing_public_key(),
+            voting_key: pending.voting_public_key(),
+            transaction_hash: pending.transaction.hash(),
+            validity_start_height,
+            state,
+            effective_from: (state == KeyRotationState::Confirmed)
+                .then(|| Policy::election_block_after(blockchain.block_number()) + 1),
+        })
+    }
+
+    /// Whether the staking contract contains all keys of the pending rotation.
+    fn is_in_staking_contract(
+        blockchain: &Blockchain,
+        validator_address: &Address,
+        pending: &PendingKeys,
+    ) -> bool {
+        let Some(staking_contract) = blockchain.get_staking_contract_if_complete(None) else {
+            return false;
+        };
+        let data_store = blockchain.get_staking_contract_store();
+        let txn = blockchain.read_transaction();
+        let Some(validator) =
+            staking_contract.get_validator(&data_store.read(&txn), validator_address)
+        else {
+            return false;
+        };
+
+        pending
+            .signing_public_key()
+            .map_or(true, |key| key == validator.signing_key)
+            && pending
+                .voting_public_key()
+                .map_or(true, |key| key == validator.voting_key)
+    }
+
+    /// Whether the validators of the current epoch contain all keys of the pending rotation.
+    fn is_in_current_validators(
+        blockchain: &Blockchain,
+        validator_address: &Address,
+        pending: &PendingKeys,
+    ) -> bool {
+        let Some(validators) = blockchain.current_validators() else {
+            return false;
+        };
+        let Some(validator) = validators.get_validator_by_address(validator_address) else {
+            return false;
+        };
+
+        pending
+            .signing_public_key()
+            .map_or(true, |key| key == validator.signing_key)
+            && pending.voting_public_key().map_or(true, |key| {
+                key.compress() == *validator.voting_key.compressed()
+            })
+    }
+
+    /// Switches the signer to the pending keys if they are in effect. This is the case right
+    /// after an election block if the staking contract contains them, or if the validators of the
+    /// current epoch were elected with them. Returns whether the keys were switched.
+    ///
+    /// This is checked when the validator starts and after each election block. The pending
+    /// rotation is kept until the new keys are persisted, such that persisting them is retried
+    /// after the next election block and a restarted validator switches to them again.
+    pub(crate) fn switch_if_effective(
+        &self,
+        blockchain: &Blockchain,
+        validator_address: &Address,
+        signer: &dyn ValidatorSigner,
+    ) -> bool {
+        let Some(pending) = self.pending.read().clone() else {
+            return false;
+        };
+
+        let head = blockchain.block_number();
+        let effective = (Policy::is_election_block_at(head)
+            && Self::is_in_staking_contract(blockchain, validator_address, &pending))
+            || Self::is_in_current_validators(blockchain, validator_address, &pending);
+        if !effective {
+            return false;
+        }
+
+        let Some(local_keys) = signer.local_keys() else {
+            error!("Cannot switch to the new validator keys held by a remote signer");
+            return false;
+        };
+        let switched = pending
+            .signing_public_key()
+            .is_some_and(|key| key != local_keys.signing_public_key())
+            || pending
+                .voting_public_key()
+                .is_some_and(|key| key != local_keys.voting_public_key());
+
+        // The new keys are used even if persisting them fails.
+        match local_keys.replace_keys(
+            pending.signing_key.clone().map(SchnorrKeyPair::from),
+            pending.voting_key.map(BlsKeyPair::from),
+        ) {
+            Ok(()) => self.store(None),
+            Err(error) => error!(%error, "Failed to persist the new validator keys"),
+        }
+
+        if switched {
+            info!(
+                epoch_number = Policy::epoch_at(head + 1),
+                "Switched to the new validator keys"
+            );
+        }
+        switched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        str::FromStr,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+    };
+
+    use nimiq_keys::SecureGenerate;
+    use nimiq_test_log::test;
+    use nimiq_test_utils::{
+        block_production::TemporaryBlockProducer,
+        blockchain::{signing_key, validator_address, validator_key, voting_key, REWARD_KEY},
+    };
+    use parking_lot::Mutex;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct TestKeyStore {
+        read_only: bool,
+        unavailable: AtomicBool,
+        signing_key: Mutex<Option<SchnorrKeyPair>>,
+        voting_key: Mutex<Option<BlsKeyPair>>,
+    }
+
+    impl TestKeyStore {
+        fn check(&self) -> Result<(), SignerError> {
+            if self.read_only || self.unavailable.load(Ordering::Relaxed) {
+                return Err(SignerError::KeyStore(
+                    "Key store is not writable".to_string(),
+                ));
+            }
+            Ok(())
+        }
+    }
+
+    impl KeyStore for TestKeyStore {
+        fn check_writable(&self, _signing_key: bool, _voting_key: bool) -> Result<(), SignerError> {
+            self.check()
+        }
+
+        fn store_signing_key(&self, key: &SchnorrKeyPair) -> Result<(), SignerError> {
+            self.check()?;
+            *self.signing_key.lock() = Some(key.clone());
+            Ok(())
+        }
+
+        fn store_voting_key(&self, key: &BlsKeyPair) -> Result<(), SignerError> {
+            self.check()?;
+            *self.voting_key.lock() = Some(key.clone());
+            Ok(())
+        }
+    }
+
+    fn genesis_signer(key_store: Arc<TestKeyStore>) -> LocalSigner {
+        let fee_key = SchnorrKeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap());
+        LocalSigner::new(signing_key(), voting_key(), fee_key).with_key_store(key_store)
+    }
+
+    #[test]
+    fn it_keeps_the_pending_keys_across_restarts() {
+        let producer = TemporaryBlockProducer::new();
+        let blockchain = producer.blockchain.read();
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let signer = LocalSigner::new(
+            SchnorrKeyPair::generate_default_csprng(),
+            BlsKeyPair::generate_default_csprng(),
+            SchnorrKeyPair::generate_default_csprng(),
+        );
+        let validator_address = Address::from(&SchnorrKeyPair::generate_default_csprng());
+        let new_voting_key = BlsKeyPair::generate_default_csprng();
+
+        let key_rotation = KeyRotation::new(env.clone());
+        assert!(matches!(
+            key_rotation.start(
+                &blockchain,
+                &validator_address,
+                &signer,
+                &SchnorrKeyPair::generate_default_csprng(),
+                None,
+                None,
+                Coin::ZERO,
+            ),
+            Err(KeyRotationError::NoNewKeys)
+        ));
+
+        let transaction = key_rotation
+            .start(
+                &blockchain,
+                &validator_address,
+                &signer,
+                &SchnorrKeyPair::generate_default_csprng(),
+                None,
+                Some(new_voting_key.clone()),
+                Coin::ZERO,
+            )
+            .unwrap();
+        assert!(transaction.verify(blockchain.network_id()).is_ok());
+
+        let key_rotation = KeyRotation::new(env);
+        let status = key_rotation
+            .status(&blockchain, &validator_address)
+            .unwrap();
+        assert_eq!(status.state, KeyRotationState::Submitted);
+        assert_eq!(status.voting_key, Some(new_voting_key.public_key));
+        assert_eq!(status.signing_key, None);
+        assert_eq!(status.transaction_hash, transaction.hash());
+
+        // The keys are not in effect, since the transaction was never included.
+        assert!(!key_rotation.switch_if_effective(&blockchain, &validator_address, &signer));
+        assert_ne!(signer.voting_public_key(), new_voting_key.public_key);
+    }
+
+    #[test]
+    fn it_refuses_keys_that_cannot_be_persisted() {
+        let producer = TemporaryBlockProducer::new();
+        let key_store = Arc::new(TestKeyStore {
+            read_only: true,
+            ..Default::default()
+        });
+        let signer = genesis_signer(key_store);
+
+        let key_rotation =
+            KeyRotation::new(MdbxDatabase::new_volatile(Default::default()).unwrap());
+        assert!(matches!(
+            key_rotation.start(
+                &producer.blockchain.read(),
+                &validator_address(),
+                &signer,
+                &validator_key(),
+                None,
+                Some(BlsKeyPair::generate_default_csprng()),
+                Coin::ZERO,
+            ),
+            Err(KeyRotationError::KeyStore(_))
+        ));
+        assert!(key_rotation
+            .status(&producer.blockchain.read(), &validator_address())
+            .is_none());
+    }
+
+    #[test]
+    fn it_switches_to_the_new_keys_after_the_election_block() {
+        let producer = TemporaryBlockProducer::new();
+        let key_store = Arc::new(TestKeyStore::default());
+        let signer = genesis_signer(Arc::clone(&key_store));
+        let validator_address = validator_address();
+        let new_signing_key = SchnorrKeyPair::generate_default_csprng();
+        let new_voting_key = BlsKeyPair::generate_default_csprng();
+
+        let key_rotation =
+            KeyRotation::new(MdbxDatabase::new_volatile(Default::default()).unwrap());
+        let transaction = key_rotation
+            .start(
+                &producer.blockchain.read(),
+                &validator_address,
+                &signer,
+                &validator_key(),
+                Some(new_signing_key.clone()),
+                Some(new_voting_key.clone()),
+                Coin::from_u64_unchecked(100),
+            )
+            .unwrap();
+        producer.next_block_with_txs(vec![], false, vec![transaction]);
+        let status = key_rotation
+            .status(&producer.blockchain.read(), &validator_address)
+            .unwrap();
+        assert_eq!(status.state, KeyRotationState::Confirmed);
+
+        // The old keys are used until the end of the epoch.
+        while !Policy::is_election_block_at(producer.blockchain.read().block_number() + 1) {
+            producer.next_block(vec![], false);
+            assert!(!key_rotation.switch_if_effective(
+                &producer.blockchain.read(),
+                &validator_address,
+                &signer
+            ));
+        }
+        assert_eq!(signer.signing_public_key(), signing_key().public);
+        assert_eq!(signer.voting_public_key(), voting_key().public_key);
+
+        // The new keys are used from the next epoch on, even if persisting them fails.
+        key_store.unavailable.store(true, Ordering::Relaxed);
+        producer.next_block(vec![], false);
+        let blockchain = producer.blockchain.read();
+        let validators = blockchain.current_validators().unwrap();
+        let validator = validators
+            .get_validator_by_address(&validator_address)
+            .unwrap();
+        assert_eq!(validator.signing_key, new_signing_key.public);
+
+        assert!(key_rotation.switch_if_effective(&blockchain, &validator_address, &signer));
+        assert_eq!(signer.signing_public_key(), new_signing_key.public);
+        assert_eq!(signer.voting_public_key(), new_voting_key.public_key);
+        assert!(key_store.signing_key.lock().is_none());
+        assert!(key_rotation
+            .status(&blockchain, &validator_address)
+            .is_some());
+
+        // The pending keys are kept until they are persisted.
+        key_store.unavailable.store(false, Ordering::Relaxed);
+        assert!(!key_rotation.switch_if_effective(&blockchain, &validator_address, &signer));
+        assert!(key_rotation
+            .status(&blockchain, &validator_address)
+            .is_none());
+        assert_eq!(
+            key_store.signing_key.lock().as_ref().map(|key| key.public),
+            Some(new_signing_key.public)
+        );
+        assert_eq!(
+            key_store
+                .voting_key
+                .lock()
+                .as_ref()
+                .map(|key| key.public_key),
+            Some(new_voting_key.public_key)
+        );
+    }
+}
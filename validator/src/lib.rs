@@ -2,9 +2,18 @@
 extern crate log;
 
 pub mod aggregation;
+pub mod equivocation;
+pub mod failover;
 mod jail;
+pub mod key_rotation;
 mod r#macro;
 mod micro;
+pub mod performance;
 mod proposal_buffer;
+pub mod signer;
+pub mod signing_history;
 pub mod tendermint;
+pub mod tendermint_journal;
+pub mod transaction_selection;
+pub mod uptime;
 pub mod validator;
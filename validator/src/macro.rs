@@ -9,14 +9,16 @@ use futures::{
     future,
     stream::{BoxStream, Stream, StreamExt},
 };
-use nimiq_block::MacroBlock;
-use nimiq_blockchain::{BlockProducer, Blockchain};
+use nimiq_block::{EquivocationProof, MacroBlock};
+use nimiq_blockchain::Blockchain;
+use nimiq_handel::{config::Config, telemetry::Telemetry};
 use nimiq_keys::Ed25519Signature as SchnorrSignature;
 use nimiq_network_interface::network::Topic;
 use nimiq_primitives::{networks::NetworkId, slots_allocation::Validators};
 use nimiq_tendermint::{Return as TendermintReturn, SignedProposalMessage, Tendermint};
 use nimiq_validator_network::{PubsubId, ValidatorNetwork};
 use parking_lot::RwLock;
+use tokio::sync::mpsc;
 
 use crate::{
     aggregation::tendermint::{
@@ -24,7 +26,9 @@ use crate::{
         state::MacroState,
         update_message::TendermintUpdate,
     },
+    signer::ValidatorSigner,
     tendermint::TendermintProtocol,
+    tendermint_journal::JournalRecorder,
 };
 
 pub(crate) enum MappedReturn<TValidatorNetwork: ValidatorNetwork + 'static>
@@ -74,7 +78,7 @@ where
     pub fn new(
         blockchain: Arc<RwLock<Blockchain>>,
         network: Arc<TValidatorNetwork>,
-        block_producer: BlockProducer,
+        signer: Arc<dyn ValidatorSigner>,
         validator_slot_band: u16,
         current_validators: Validators,
         network_id: NetworkId,
@@ -84,6 +88,10 @@ where
             'static,
             SignedProposalMessage<Header<PubsubId<TValidatorNetwork>>, (SchnorrSignature, u16)>,
         >,
+        handel_config: Config,
+        handel_telemetry: Arc<Telemetry>,
+        journal: Option<JournalRecorder>,
+        double_vote_tx: mpsc::UnboundedSender<EquivocationProof>,
     ) -> Self {
         let input = network
             .receive::<TendermintUpdate>()
@@ -103,34 +111,41 @@ where
         let dependencies = TendermintProtocol::new(
             blockchain,
             network,
-            block_producer,
+            signer,
             current_validators,
             validator_slot_band,
             network_id,
             block_height,
+            handel_config,
+            handel_telemetry,
+            journal.clone(),
+            double_vote_tx,
         );
 
+        // A fresh instance starts a new journal, such that it can be replayed.
+        let state_opt = state_opt.and_then(|s| s.into_tendermint_state(block_height));
+        if let (None, Some(journal)) = (&state_opt, &journal) {
+            journal.clear(block_height);
+        }
+
         // create the Tendermint instance, which implements Stream
-        let tendermint = Tendermint::new(
-            dependencies,
-            state_opt.and_then(|s| s.into_tendermint_state(block_height)),
-            proposal_stream,
-            input,
-        )
-        // and map the return value such that a state update can be persisted.
-        .map(move |item| match item {
-            TendermintReturn::Decision(decision) => MappedReturn::Decision(decision),
-            TendermintReturn::Update(state) => {
-                MappedReturn::Update(MacroState::from_tendermint_state(block_height, state))
-            }
-            TendermintReturn::ProposalAccepted(proposal) => {
-                MappedReturn::ProposalAccepted(proposal)
-            }
-            TendermintReturn::ProposalIgnored(proposal) => MappedReturn::ProposalIgnored(proposal),
-            TendermintReturn::ProposalRejected(proposal) => {
-                MappedReturn::ProposalRejected(proposal)
-            }
-        });
+        let tendermint = Tendermint::new(dependencies, state_opt, proposal_stream, input)
+            // and map the return value such that a state update can be persisted.
+            .map(move |item| match item {
+                TendermintReturn::Decision(decision) => MappedReturn::Decision(decision),
+                TendermintReturn::Update(state) => {
+                    MappedReturn::Update(MacroState::from_tendermint_state(block_height, state))
+                }
+                TendermintReturn::ProposalAccepted(proposal) => {
+                    MappedReturn::ProposalAccepted(proposal)
+                }
+                TendermintReturn::ProposalIgnored(proposal) => {
+                    MappedReturn::ProposalIgnored(proposal)
+                }
+                TendermintReturn::ProposalRejected(proposal) => {
+                    MappedReturn::ProposalRejected(proposal)
+                }
+            });
 
         // Create the instance and return it.
         Self {
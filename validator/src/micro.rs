@@ -7,9 +7,10 @@ use std::{
 };
 
 use futures::{future::BoxFuture, ready, FutureExt, Stream};
-use nimiq_block::{Block, EquivocationProof, MicroBlock, SkipBlockInfo};
+use nimiq_block::{Block, EquivocationProof, MicroBlock, MicroJustification, SkipBlockInfo};
 use nimiq_blockchain::{BlockProducer, Blockchain};
 use nimiq_blockchain_interface::{AbstractBlockchain, PushResult};
+use nimiq_handel::{config::Config, telemetry::Telemetry};
 use nimiq_mempool::mempool::Mempool;
 use nimiq_time::sleep;
 use nimiq_utils::time::systemtime_to_timestamp;
@@ -17,7 +18,10 @@ use nimiq_validator_network::ValidatorNetwork;
 use nimiq_vrf::VrfSeed;
 use parking_lot::RwLock;
 
-use crate::aggregation::skip_block::SkipBlockAggregation;
+use crate::{
+    aggregation::skip_block::SkipBlockAggregation, signer::ValidatorSigner,
+    signing_history::SigningHistory, transaction_selection::TransactionSelection,
+};
 
 // Ignoring this clippy warning since size difference is not that much (320
 // bytes) and we probably don't want the performance penalty of the allocation.
@@ -30,14 +34,18 @@ pub(crate) enum ProduceMicroBlockEvent {
 struct NextProduceMicroBlockEvent<TValidatorNetwork> {
     blockchain: Arc<RwLock<Blockchain>>,
     mempool: Arc<Mempool>,
+    transaction_selection: Arc<TransactionSelection>,
     network: Arc<TValidatorNetwork>,
-    block_producer: BlockProducer,
+    signer: Arc<dyn ValidatorSigner>,
+    signing_history: Arc<SigningHistory>,
     validator_slot_band: u16,
     equivocation_proofs: Vec<EquivocationProof>,
     prev_seed: VrfSeed,
     block_number: u32,
     producer_timeout: Duration,
     block_separation_time: Duration,
+    skip_block_config: Config,
+    skip_block_telemetry: Arc<Telemetry>,
 }
 
 impl<TValidatorNetwork: ValidatorNetwork + 'static> NextProduceMicroBlockEvent<TValidatorNetwork> {
@@ -47,26 +55,34 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> NextProduceMicroBlockEvent<T
     fn new(
         blockchain: Arc<RwLock<Blockchain>>,
         mempool: Arc<Mempool>,
+        transaction_selection: Arc<TransactionSelection>,
         network: Arc<TValidatorNetwork>,
-        block_producer: BlockProducer,
+        signer: Arc<dyn ValidatorSigner>,
+        signing_history: Arc<SigningHistory>,
         validator_slot_band: u16,
         equivocation_proofs: Vec<EquivocationProof>,
         prev_seed: VrfSeed,
         block_number: u32,
         producer_timeout: Duration,
         block_separation_time: Duration,
+        skip_block_config: Config,
+        skip_block_telemetry: Arc<Telemetry>,
     ) -> Self {
         Self {
             blockchain,
             mempool,
+            transaction_selection,
             network,
-            block_producer,
+            signer,
+            signing_history,
             validator_slot_band,
             equivocation_proofs,
             prev_seed,
             block_number,
             producer_timeout,
             block_separation_time,
+            skip_block_config,
+            skip_block_telemetry,
         }
     }
 
@@ -76,23 +92,19 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> NextProduceMicroBlockEvent<T
         Option<ProduceMicroBlockEvent>,
         NextProduceMicroBlockEvent<TValidatorNetwork>,
     ) {
-        let in_current_state = |head: &Block| {
-            self.prev_seed == *head.seed() && self.block_number == head.block_number() + 1
-        };
-
         let mut delay = Duration::default();
         let mut expected_next_ts;
 
         let return_value = loop {
-            // Acquire blockchain.upgradable_read() to prevent further changes to the blockchain while
-            // we're constructing the block. Check if we're still in the correct state, abort otherwise.
-            {
-                let blockchain = self.blockchain.upgradable_read();
+            // Acquire blockchain.read() to check if we're still in the correct state and whether
+            // it is our turn, abort otherwise.
+            let produce_now = {
+                let blockchain = self.blockchain.read();
 
                 // Calculate the expected block time as expected by the reward function.
                 expected_next_ts = self.expected_next_timestamp(&blockchain);
 
-                if !in_current_state(blockchain.head()) {
+                if !self.in_current_state(blockchain.head()) {
                     break Some(None);
                 } else if self.is_our_turn(&blockchain) {
                     // We want to produce a block at the expected timestamp for this block in this batch
@@ -103,53 +115,26 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> NextProduceMicroBlockEvent<T
                     // If the timestamp hasn't passed, wait until the expected block timestamp
                     // to produce the block.
                     if expected_next_ts <= now {
-                        info!(
-                            block_number = self.block_number,
-                            slot_band = self.validator_slot_band,
-                            "Our turn, producing micro block #{}",
-                            self.block_number,
-                        );
-
-                        let block = self.produce_micro_block(&blockchain);
-                        let num_transactions = block
-                            .body
-                            .as_ref()
-                            .map(|body| body.transactions.len())
-                            .unwrap_or(0);
-
-                        debug!(
-                            block_number = block.header.block_number,
-                            num_transactions,
-                            ?delay,
-                            "Produced micro block {} with {} transactions",
-                            block,
-                            num_transactions
-                        );
-
-                        let block1 = block.clone();
-
-                        // Use a trusted push since these blocks were generated by this validator
-                        let result = if cfg!(feature = "trusted_push") {
-                            Blockchain::trusted_push(blockchain, Block::Micro(block))
-                        } else {
-                            Blockchain::push(blockchain, Block::Micro(block))
-                        };
-
-                        if let Err(e) = &result {
-                            error!("Failed to push our own block onto the chain: {:?}", e);
-                        }
-
-                        let event = result
-                            .map(move |result| ProduceMicroBlockEvent::MicroBlock(block1, result))
-                            .ok();
-                        break Some(event);
+                        true
                     } else {
                         delay = Duration::from_millis(expected_next_ts - now);
-                    };
+                        false
+                    }
                 } else {
                     break None;
                 }
+            };
+
+            if produce_now {
+                info!(
+                    block_number = self.block_number,
+                    slot_band = self.validator_slot_band,
+                    "Our turn, producing micro block #{}",
+                    self.block_number,
+                );
+                break Some(self.produce_and_push_micro_block(delay));
             }
+
             // We have dropped the blockchain lock.
             // Wait for the expected timestamp to arrive before actually producing the block
             sleep(delay).await;
@@ -183,7 +168,7 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> NextProduceMicroBlockEvent<T
         // Acquire a blockchain read lock and check if the state still matches to fetch active validators.
         let active_validators = {
             let blockchain = self.blockchain.read();
-            if in_current_state(blockchain.head()) {
+            if self.in_current_state(blockchain.head()) {
                 Some(blockchain.current_validators().unwrap())
             } else {
                 None
@@ -198,12 +183,27 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> NextProduceMicroBlockEvent<T
             vrf_entropy: self.prev_seed.entropy(),
         };
 
+        if let Err(error) = self.signing_history.record_skip_block(&skip_block_info) {
+            error!(%error, "Refusing to contribute to skip block");
+            return (None, self);
+        }
+
+        let signature = match self.signer.sign_skip_block(&skip_block_info) {
+            Ok(signature) => signature,
+            Err(error) => {
+                error!(%error, "Failed to sign skip block");
+                return (None, self);
+            }
+        };
+
         let (_, skip_block_proof) = SkipBlockAggregation::start(
             skip_block_info.clone(),
-            self.block_producer.voting_key.clone(),
+            signature,
             self.validator_slot_band,
             active_validators.unwrap(),
             Arc::clone(&self.network),
+            self.skip_block_config.clone(),
+            Arc::clone(&self.skip_block_telemetry),
         )
         .await;
 
@@ -213,18 +213,20 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> NextProduceMicroBlockEvent<T
             let blockchain = self.blockchain.upgradable_read();
             let head = blockchain.head();
 
-            if !in_current_state(head) {
+            if !self.in_current_state(head) {
                 None
             } else {
                 let timestamp = head.timestamp() + self.producer_timeout.as_millis() as u64;
 
-                let block = self.block_producer.next_micro_block(
+                // The VRF seed of a skip block is carried over from the previous block.
+                let block = BlockProducer::next_unsigned_micro_block(
                     &blockchain,
                     timestamp,
                     vec![],
                     vec![],
                     vec![], // TODO: Allow validators to set extra data field.
                     Some(skip_block_proof),
+                    head.seed().clone(),
                 );
 
                 let block1 = block.clone();
@@ -255,6 +257,84 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> NextProduceMicroBlockEvent<T
         }
     }
 
+    fn in_current_state(&self, head: &Block) -> bool {
+        self.prev_seed == *head.seed() && self.block_number == head.block_number() + 1
+    }
+
+    /// Produces, signs and pushes our micro block. The blockchain lock is not held while the
+    /// signer computes the VRF seed and the signature.
+    fn produce_and_push_micro_block(&self, delay: Duration) -> Option<ProduceMicroBlockEvent> {
+        let seed = match self.signer.vrf_seed(&self.prev_seed) {
+            Ok(seed) => seed,
+            Err(error) => {
+                error!(%error, "Failed to compute the VRF seed of our micro block");
+                return None;
+            }
+        };
+
+        let mut block = {
+            let blockchain = self.blockchain.read();
+            if !self.in_current_state(blockchain.head()) {
+                return None;
+            }
+            self.produce_micro_block(&blockchain, seed)
+        };
+
+        // Record the block before it leaves this validator, such that we never sign a conflicting
+        // block at the same height, not even after a restart.
+        if let Err(error) = self.signing_history.record_micro_block(&block.header) {
+            error!(%error, "Refusing to produce micro block");
+            return None;
+        }
+
+        match self.signer.sign_micro_header(&block.header) {
+            Ok(signature) => block.justification = Some(MicroJustification::Micro(signature)),
+            Err(error) => {
+                error!(%error, "Failed to sign micro block");
+                return None;
+            }
+        }
+
+        let num_transactions = block
+            .body
+            .as_ref()
+            .map(|body| body.transactions.len())
+            .unwrap_or(0);
+
+        debug!(
+            block_number = block.header.block_number,
+            num_transactions,
+            ?delay,
+            "Produced micro block {} with {} transactions",
+            block,
+            num_transactions
+        );
+
+        // Acquire blockchain.upgradable_read() to prevent further changes to the blockchain while
+        // we're pushing the block. Check if we're still in the correct state, abort otherwise.
+        let blockchain = self.blockchain.upgradable_read();
+        if !self.in_current_state(blockchain.head()) {
+            return None;
+        }
+
+        let block1 = block.clone();
+
+        // Use a trusted push since these blocks were generated by this validator
+        let result = if cfg!(feature = "trusted_push") {
+            Blockchain::trusted_push(blockchain, Block::Micro(block))
+        } else {
+            Blockchain::push(blockchain, Block::Micro(block))
+        };
+
+        if let Err(e) = &result {
+            error!("Failed to push our own block onto the chain: {:?}", e);
+        }
+
+        result
+            .map(move |result| ProduceMicroBlockEvent::MicroBlock(block1, result))
+            .ok()
+    }
+
     fn is_our_turn(&self, blockchain: &Blockchain) -> bool {
         let proposer_slot = blockchain.get_proposer(
             self.block_number,
@@ -274,35 +354,28 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> NextProduceMicroBlockEvent<T
         }
     }
 
-    fn produce_micro_block(&self, blockchain: &Blockchain) -> MicroBlock {
+    /// Creates our next micro block with the given VRF seed. The block still needs to be signed.
+    fn produce_micro_block(&self, blockchain: &Blockchain, seed: VrfSeed) -> MicroBlock {
         let timestamp = u64::max(
             blockchain.timestamp(),
             systemtime_to_timestamp(SystemTime::now()),
         );
 
-        // First we try to fill the block with control transactions
-        let mut block_available_bytes =
-            MicroBlock::get_available_bytes(self.equivocation_proofs.len());
-
-        let (mut transactions, txn_size) = self
-            .mempool
-            .get_control_transactions_for_block_locked(blockchain, block_available_bytes);
-
-        block_available_bytes = block_available_bytes.saturating_sub(txn_size);
-
-        let (mut regular_transactions, _) = self
-            .mempool
-            .get_transactions_for_block_locked(blockchain, block_available_bytes);
-
-        transactions.append(&mut regular_transactions);
+        let block_available_bytes = MicroBlock::get_available_bytes(self.equivocation_proofs.len());
+        let transactions = self.transaction_selection.select_transactions(
+            &self.mempool,
+            blockchain,
+            block_available_bytes,
+        );
 
-        self.block_producer.next_micro_block(
+        BlockProducer::next_unsigned_micro_block(
             blockchain,
             timestamp,
             self.equivocation_proofs.clone(),
             transactions,
             vec![], // TODO: Allow validators to set extra data field.
             None,
+            seed,
         )
     }
 
@@ -334,26 +407,34 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> ProduceMicroBlock<TValidator
     pub fn new(
         blockchain: Arc<RwLock<Blockchain>>,
         mempool: Arc<Mempool>,
+        transaction_selection: Arc<TransactionSelection>,
         network: Arc<TValidatorNetwork>,
-        block_producer: BlockProducer,
+        signer: Arc<dyn ValidatorSigner>,
+        signing_history: Arc<SigningHistory>,
         validator_slot_band: u16,
         equivocation_proofs: Vec<EquivocationProof>,
         prev_seed: VrfSeed,
         block_number: u32,
         producer_timeout: Duration,
         block_separation_time: Duration,
+        skip_block_config: Config,
+        skip_block_telemetry: Arc<Telemetry>,
     ) -> Self {
         let next_event = NextProduceMicroBlockEvent::new(
             blockchain,
             mempool,
+            transaction_selection,
             network,
-            block_producer,
+            signer,
+            signing_history,
             validator_slot_band,
             equivocation_proofs,
             prev_seed,
             block_number,
             producer_timeout,
             block_separation_time,
+            skip_block_config,
+            skip_block_telemetry,
         )
         .next()
         .boxed();
@@ -389,3 +470,115 @@ impl<TValidatorNetwork: ValidatorNetwork + 'static> Stream
         Poll::Ready(Some(event))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, str::FromStr};
+
+    use futures::StreamExt;
+    use nimiq_database::mdbx::MdbxDatabase;
+    use nimiq_keys::{Address, KeyPair, PrivateKey};
+    use nimiq_mempool::config::MempoolConfig;
+    use nimiq_network_mock::MockHub;
+    use nimiq_primitives::{coin::Coin, networks::NetworkId, policy::Policy};
+    use nimiq_test_log::test;
+    use nimiq_test_utils::{block_production::TemporaryBlockProducer, blockchain::REWARD_KEY};
+    use nimiq_transaction::ExecutedTransaction;
+    use nimiq_transaction_builder::TransactionBuilder;
+    use nimiq_validator_network::network_impl::ValidatorNetworkImpl;
+
+    use super::*;
+    use crate::signer::LocalSigner;
+
+    #[test(tokio::test)]
+    async fn it_produces_micro_blocks_with_the_selected_transactions() {
+        let producer = TemporaryBlockProducer::new();
+        let blockchain = Arc::clone(&producer.blockchain);
+        let mempool = Arc::new(Mempool::new(
+            Arc::clone(&blockchain),
+            MempoolConfig::default(),
+        ));
+        let key_pair = KeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap());
+        let recipients: Vec<_> = (1..=3u8).map(|i| Address::from([i; 20])).collect();
+
+        let mut transactions = vec![];
+        for (fee, recipient) in (1..=3).zip(&recipients) {
+            let transaction = TransactionBuilder::new_basic(
+                &key_pair,
+                recipient.clone(),
+                Coin::from_u64_unchecked(1),
+                Coin::from_u64_unchecked(fee * 1000),
+                1 + Policy::genesis_block_number(),
+                NetworkId::UnitAlbatross,
+            )
+            .unwrap();
+            mempool
+                .add_transaction(transaction.clone(), None)
+                .await
+                .unwrap();
+            transactions.push(transaction);
+        }
+
+        let transaction_selection = TransactionSelection {
+            excluded_recipients: HashSet::from([recipients[1].clone()]),
+            ..Default::default()
+        };
+
+        let (prev_seed, block_number, validator_slot_band) = {
+            let blockchain = blockchain.read();
+            let head = blockchain.head();
+            let block_number = head.block_number() + 1;
+            let proposer = blockchain
+                .get_proposer(block_number, block_number, head.seed().entropy(), None)
+                .unwrap();
+            (head.seed().clone(), block_number, proposer.band)
+        };
+
+        let mut hub = MockHub::default();
+        let network = Arc::new(ValidatorNetworkImpl::new(Arc::new(hub.new_network())));
+        let signer = Arc::new(LocalSigner::new(
+            producer.producer.signing_key.clone(),
+            producer.producer.voting_key.clone(),
+            producer.producer.signing_key.clone(),
+        ));
+        let signing_history = Arc::new(SigningHistory::new(
+            MdbxDatabase::new_volatile(Default::default()).unwrap(),
+        ));
+
+        let mut produce_micro_block = ProduceMicroBlock::new(
+            Arc::clone(&blockchain),
+            Arc::clone(&mempool),
+            Arc::new(transaction_selection),
+            network,
+            signer,
+            signing_history,
+            validator_slot_band,
+            vec![],
+            prev_seed,
+            block_number,
+            Duration::from_millis(Policy::BLOCK_PRODUCER_TIMEOUT),
+            Duration::from_millis(Policy::BLOCK_SEPARATION_TIME),
+            Default::default(),
+            Default::default(),
+        );
+
+        let Some(ProduceMicroBlockEvent::MicroBlock(block, result)) =
+            produce_micro_block.next().await
+        else {
+            panic!("No micro block produced");
+        };
+
+        // The excluded transaction stays in the mempool, the others are included by fee.
+        assert_eq!(result, PushResult::Extended);
+        assert_eq!(blockchain.read().block_number(), block_number);
+        assert_eq!(
+            block.body.unwrap().transactions,
+            vec![
+                ExecutedTransaction::Ok(transactions[2].clone()),
+                ExecutedTransaction::Ok(transactions[0].clone()),
+            ]
+        );
+        assert_eq!(mempool.num_transactions(), 1);
+        assert!(produce_micro_block.next().await.is_none());
+    }
+}
@@ -0,0 +1,505 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use nimiq_block::{Block, MicroJustification, MultiSignature};
+use nimiq_blockchain::{interface::HistoryInterface, Blockchain};
+use nimiq_blockchain_interface::{AbstractBlockchain, BlockchainError};
+use nimiq_collections::BitSet;
+use nimiq_database::mdbx::MdbxReadTransaction;
+use nimiq_hash::Blake2bHash;
+use nimiq_keys::Address;
+use nimiq_primitives::{coin::Coin, policy::Policy, slots_allocation::Validators};
+use nimiq_serde::{Deserialize, Serialize};
+use nimiq_transaction::{
+    account::staking_contract::IncomingStakingTransactionData,
+    historic_transaction::HistoricTransactionData, ExecutedTransaction,
+};
+use nimiq_vrf::VrfEntropy;
+use parking_lot::RwLock;
+
+/// Duty statistics of a validator in an epoch, computed from the chain.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorPerformance {
+    pub epoch_number: u32,
+    /// The last block taken into account. This is the head of the chain for the current epoch.
+    pub block_number: u32,
+    /// The number of slots the validator owns in the epoch.
+    pub num_slots: u16,
+    /// The statistics about micro blocks, if the micro blocks of the epoch are available. They
+    /// are not available for past epochs on history nodes.
+    pub micro_blocks: Option<MicroBlockPerformance>,
+    /// The number of macro blocks in the epoch.
+    pub macro_blocks: u32,
+    /// The number of macro blocks whose Tendermint justification includes our precommit.
+    pub macro_blocks_signed: u32,
+    /// The number of our slots included in the aggregated signatures of the macro block
+    /// justifications.
+    pub macro_slots_signed: u64,
+    /// The number of Tendermint rounds it took to decide the macro blocks of the epoch.
+    pub tendermint_rounds: u32,
+    /// The number of Tendermint rounds we participated in. Earlier rounds of a macro block leave
+    /// no trace on the chain, so only the deciding rounds whose justification includes our
+    /// precommit are counted.
+    pub tendermint_rounds_participated: u32,
+    /// The number of times one of our slots was penalized for not producing a block, i.e. the
+    /// number of skip blocks we caused.
+    pub skip_blocks_caused: u32,
+    /// The slots that were penalized.
+    pub penalized_slots: BTreeSet<u16>,
+    /// Whether the validator was jailed.
+    pub jailed: bool,
+    /// The rewards paid to the validator.
+    pub rewards: Coin,
+    /// The changes of the validator's settings.
+    pub updates: Vec<ValidatorUpdate>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MicroBlockPerformance {
+    /// The number of micro blocks for which one of our slots was selected as producer.
+    pub expected: u32,
+    /// The number of those micro blocks that we produced.
+    pub produced: u32,
+    /// The number of skip blocks whose proof includes our signature.
+    pub skip_blocks_signed: u32,
+}
+
+/// A successful `UpdateValidator` transaction.
+///
+/// The staking contract has no notion of a commission. Rewards are paid to the reward address
+/// and shared with the stakers off-chain, so a change of the reward address is what delegators
+/// need to watch for.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorUpdate {
+    pub block_number: u32,
+    pub new_reward_address: Option<Address>,
+    pub new_signing_key: bool,
+    pub new_voting_key: bool,
+    pub new_signal_data: bool,
+}
+
+/// Walks the blocks of an epoch and accumulates the statistics of all validators of the epoch.
+#[derive(Clone)]
+pub(crate) struct PerformanceAccumulator {
+    epoch_number: u32,
+    validators: Validators,
+    /// The statistics of the validators, indexed by slot band.
+    performances: Vec<ValidatorPerformance>,
+    micro_blocks_available: bool,
+    last_block_number: u32,
+    next_block_number: u32,
+    disabled_slots: BitSet,
+    prev_entropy: VrfEntropy,
+}
+
+impl PerformanceAccumulator {
+    pub(crate) fn new(
+        blockchain: &Blockchain,
+        epoch_number: u32,
+        txn: &MdbxReadTransaction,
+    ) -> Result<Self, BlockchainError> {
+        let first_block_number =
+            Policy::first_block_of(epoch_number).ok_or(BlockchainError::InvalidEpoch)?;
+        let last_block_number =
+            Policy::election_block_of(epoch_number).ok_or(BlockchainError::InvalidEpoch)?;
+        if first_block_number > blockchain.block_number() {
+            return Err(BlockchainError::InvalidEpoch);
+        }
+
+        let validators = blockchain.get_validators_for_epoch(epoch_number, Some(txn))?;
+        let performances = validators
+            .iter()
+            .map(|validator| ValidatorPerformance {
+                epoch_number,
+                num_slots: validator.num_slots(),
+                micro_blocks: Some(MicroBlockPerformance::default()),
+                ..Default::default()
+            })
+            .collect();
+
+        // The slot selection of the first batch depends on the preceding election block.
+        let election_block = blockchain
+            .get_block_at(first_block_number - 1, false, Some(txn))?
+            .unwrap_macro();
+
+        Ok(Self {
+            epoch_number,
+            validators,
+            performances,
+            micro_blocks_available: true,
+            last_block_number,
+            next_block_number: first_block_number,
+            disabled_slots: election_block.header.next_batch_initial_punished_set,
+            prev_entropy: election_block.header.seed.entropy(),
+        })
+    }
+
+    pub(crate) fn epoch_number(&self) -> u32 {
+        self.epoch_number
+    }
+
+    /// The last block that was processed.
+    pub(crate) fn block_number(&self) -> u32 {
+        self.next_block_number - 1
+    }
+
+    /// Whether all blocks of the epoch were processed.
+    pub(crate) fn is_complete(&self) -> bool {
+        self.next_block_number > self.last_block_number
+    }
+
+    /// Returns the statistics of the validator with the given address. A validator without
+    /// slots in the epoch gets empty statistics.
+    pub(crate) fn performance(&self, validator_address: &Address) -> ValidatorPerformance {
+        match self.validators.validator_map.get(validator_address) {
+            Some(band) => self.finish(&self.performances[*band as usize]),
+            None => self.finish(&ValidatorPerformance {
+                epoch_number: self.epoch_number,
+                micro_blocks: Some(MicroBlockPerformance::default()),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Returns the statistics of all validators of the epoch.
+    pub(crate) fn performances(&self) -> BTreeMap<Address, ValidatorPerformance> {
+        self.validators
+            .iter()
+            .zip(&self.performances)
+            .map(|(validator, performance)| (validator.address.clone(), self.finish(performance)))
+            .collect()
+    }
+
+    fn finish(&self, performance: &ValidatorPerformance) -> ValidatorPerformance {
+        let mut performance = performance.clone();
+        performance.block_number = self.block_number();
+        if !self.micro_blocks_available {
+            performance.micro_blocks = None;
+        }
+        performance
+    }
+
+    fn performance_mut(
+        &mut self,
+        validator_address: &Address,
+    ) -> Option<&mut ValidatorPerformance> {
+        let band = *self.validators.validator_map.get(validator_address)?;
+        Some(&mut self.performances[band as usize])
+    }
+
+    /// Returns the number of signed slots per slot band.
+    fn signed_slots(&self, signature: &MultiSignature) -> BTreeMap<usize, u64> {
+        let mut signed_slots = BTreeMap::new();
+        for slot in signature.signers.iter() {
+            let band = self.validators.get_band_from_slot(slot as u16) as usize;
+            *signed_slots.entry(band).or_default() += 1;
+        }
+        signed_slots
+    }
+
+    /// Processes the blocks of the epoch up to (and including) `block_number`.
+    pub(crate) fn process(
+        &mut self,
+        blockchain: &Blockchain,
+        block_number: u32,
+        txn: &MdbxReadTransaction,
+    ) -> Result<(), BlockchainError> {
+        let block_number = block_number.min(self.last_block_number);
+        while self.next_block_number <= block_number {
+            self.process_block(blockchain, self.next_block_number, txn)?;
+            self.next_block_number += 1;
+        }
+        Ok(())
+    }
+
+    /// Processes the finalized blocks of the epoch up to (and including) `block_number`, which
+    /// must not be after the latest macro block. The blockchain is only locked for one batch at
+    /// a time, so that walking an epoch doesn't block the chain. This is safe since finalized
+    /// blocks can't be reverted.
+    pub(crate) fn process_finalized(
+        &mut self,
+        blockchain: &RwLock<Blockchain>,
+        block_number: u32,
+    ) -> Result<(), BlockchainError> {
+        let block_number = block_number.min(self.last_block_number);
+        while self.next_block_number <= block_number {
+            let blockchain = blockchain.read();
+            let txn = blockchain.read_transaction();
+            let batch_end = block_number.min(Policy::macro_block_after(self.next_block_number - 1));
+            self.process(&blockchain, batch_end, &txn)?;
+        }
+        Ok(())
+    }
+
+    fn process_events(
+        &mut self,
+        blockchain: &Blockchain,
+        block_number: u32,
+        txn: &MdbxReadTransaction,
+    ) {
+        for transaction in blockchain
+            .history_store
+            .get_block_transactions(block_number, Some(txn))
+        {
+            match transaction.data {
+                HistoricTransactionData::Reward(event) => {
+                    if let Some(performance) = self.performance_mut(&event.validator_address) {
+                        performance.rewards += event.value;
+                    }
+                }
+                HistoricTransactionData::Penalize(event) => {
+                    if let Some(performance) = self.performance_mut(&event.validator_address) {
+                        performance.skip_blocks_caused += 1;
+                        performance.penalized_slots.insert(event.slot);
+                    }
+                }
+                HistoricTransactionData::Jail(event) => {
+                    if let Some(performance) = self.performance_mut(&event.validator_address) {
+                        performance.jailed = true;
+                    }
+                }
+                HistoricTransactionData::Basic(ExecutedTransaction::Ok(transaction))
+                    if transaction.recipient == Policy::STAKING_CONTRACT_ADDRESS =>
+                {
+                    if let Ok(IncomingStakingTransactionData::UpdateValidator {
+                        new_signing_key,
+                        new_voting_key,
+                        new_reward_address,
+                        new_signal_data,
+                        proof,
+                        ..
+                    }) = IncomingStakingTransactionData::parse(&transaction)
+                    {
+                        // The update is signed with the validator's cold key.
+                        if let Some(performance) = self.performance_mut(&proof.compute_signer()) {
+                            performance.updates.push(ValidatorUpdate {
+                                block_number,
+                                new_reward_address,
+                                new_signing_key: new_signing_key.is_some(),
+                                new_voting_key: new_voting_key.is_some(),
+                                new_signal_data: new_signal_data.is_some(),
+                            });
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn process_block(
+        &mut self,
+        blockchain: &Blockchain,
+        block_number: u32,
+        txn: &MdbxReadTransaction,
+    ) -> Result<(), BlockchainError> {
+        self.process_events(blockchain, block_number, txn);
+
+        if Policy::is_macro_block_at(block_number) {
+            let block = blockchain
+                .get_block_at(block_number, false, Some(txn))?
+                .unwrap_macro();
+
+            let (rounds, signed_slots) = match &block.justification {
+                Some(justification) => (
+                    justification.round + 1,
+                    self.signed_slots(&justification.sig),
+                ),
+                None => (0, BTreeMap::new()),
+            };
+            for performance in &mut self.performances {
+                performance.macro_blocks += 1;
+                performance.tendermint_rounds += rounds;
+            }
+            for (band, num_slots) in signed_slots {
+                let performance = &mut self.performances[band];
+                performance.macro_blocks_signed += 1;
+                performance.macro_slots_signed += num_slots;
+                performance.tendermint_rounds_participated += 1;
+            }
+
+            self.disabled_slots = block.header.next_batch_initial_punished_set;
+            self.prev_entropy = block.header.seed.entropy();
+            return Ok(());
+        }
+
+        // Once a micro block is missing, we can't determine the producers anymore.
+        if !self.micro_blocks_available {
+            return Ok(());
+        }
+        let block = match blockchain.get_block_at(block_number, false, Some(txn)) {
+            Ok(Block::Micro(block)) => block,
+            Ok(Block::Macro(_)) => unreachable!("Expected a micro block"),
+            Err(BlockchainError::BlockNotFound) => {
+                self.micro_blocks_available = false;
+                return Ok(());
+            }
+            Err(error) => return Err(error),
+        };
+        let prev_entropy = std::mem::replace(&mut self.prev_entropy, block.header.seed.entropy());
+
+        let slot = <Blockchain as AbstractBlockchain>::compute_slot_number(
+            block_number,
+            prev_entropy,
+            self.disabled_slots.clone(),
+        );
+        let producer = self.validators.get_band_from_slot(slot) as usize;
+        let skip_block_signers = match &block.justification {
+            Some(MicroJustification::Skip(proof)) => self.signed_slots(&proof.sig),
+            _ => BTreeMap::new(),
+        };
+
+        let micro_blocks = self.performances[producer]
+            .micro_blocks
+            .as_mut()
+            .expect("Micro block statistics must be available");
+        micro_blocks.expected += 1;
+        if !block.is_skip_block() {
+            micro_blocks.produced += 1;
+        }
+        for band in skip_block_signers.into_keys() {
+            self.performances[band]
+                .micro_blocks
+                .as_mut()
+                .expect("Micro block statistics must be available")
+                .skip_blocks_signed += 1;
+        }
+
+        Ok(())
+    }
+}
+
+/// Computes the duty statistics of the validator with the given address in the given epoch.
+///
+/// For the current epoch, the statistics cover the blocks up to the head of the chain. The
+/// blockchain is locked for one batch at a time, but walking a whole epoch is still slow, so this
+/// should not be called from an async context.
+pub fn validator_performance(
+    blockchain: &RwLock<Blockchain>,
+    validator_address: &Address,
+    epoch_number: u32,
+) -> Result<ValidatorPerformance, BlockchainError> {
+    let mut accumulator = {
+        let blockchain = blockchain.read();
+        let txn = blockchain.read_transaction();
+        PerformanceAccumulator::new(&blockchain, epoch_number, &txn)?
+    };
+
+    let head = blockchain.read().block_number();
+    accumulator.process_finalized(blockchain, Policy::last_macro_block(head))?;
+
+    let blockchain = blockchain.read();
+    let txn = blockchain.read_transaction();
+    accumulator.process(&blockchain, blockchain.block_number(), &txn)?;
+    Ok(accumulator.performance(validator_address))
+}
+
+/// Keeps track of the duty statistics of a validator in the current epoch.
+///
+/// The statistics up to the latest macro block are cached, since macro blocks are final. Every
+/// update thus only processes the blocks of the current batch.
+pub struct PerformanceTracker {
+    validator_address: Address,
+    finalized: Option<PerformanceAccumulator>,
+    current: Option<(Blake2bHash, ValidatorPerformance)>,
+}
+
+impl PerformanceTracker {
+    pub fn new(validator_address: Address) -> Self {
+        Self {
+            validator_address,
+            finalized: None,
+            current: None,
+        }
+    }
+
+    /// Returns the statistics of the current epoch up to the head of the chain.
+    pub fn update(
+        &mut self,
+        blockchain: &RwLock<Blockchain>,
+    ) -> Result<ValidatorPerformance, BlockchainError> {
+        let (head_hash, head) = {
+            let blockchain = blockchain.read();
+            (blockchain.head_hash(), blockchain.block_number())
+        };
+        if let Some((hash, performance)) = &self.current {
+            if *hash == head_hash {
+                return Ok(performance.clone());
+            }
+        }
+
+        let epoch_number = Policy::epoch_at(head);
+        let mut finalized = match self.finalized.take() {
+            Some(finalized) if finalized.epoch_number() == epoch_number => finalized,
+            _ => {
+                let blockchain = blockchain.read();
+                let txn = blockchain.read_transaction();
+                PerformanceAccumulator::new(&blockchain, epoch_number, &txn)?
+            }
+        };
+        finalized.process_finalized(blockchain, Policy::last_macro_block(head))?;
+
+        // The chain might have moved on in the meantime.
+        let blockchain = blockchain.read();
+        let txn = blockchain.read_transaction();
+        let head_hash = blockchain.head_hash();
+        let head = blockchain.block_number();
+        finalized.process(&blockchain, Policy::last_macro_block(head), &txn)?;
+
+        let mut current = finalized.clone();
+        self.finalized = Some(finalized);
+        current.process(&blockchain, head, &txn)?;
+        let performance = current.performance(&self.validator_address);
+        self.current = Some((head_hash, performance.clone()));
+        Ok(performance)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimiq_primitives::policy::Policy;
+    use nimiq_test_log::test;
+    use nimiq_test_utils::block_production::TemporaryBlockProducer;
+
+    use super::*;
+
+    #[test]
+    fn it_computes_duty_statistics() {
+        let producer = TemporaryBlockProducer::new();
+        let num_blocks = Policy::blocks_per_batch() + 2;
+        for i in 0..num_blocks {
+            producer.next_block(vec![], i == 1);
+        }
+
+        let (validator_address, head) = {
+            let blockchain = producer.blockchain.read();
+            let validators = blockchain.current_validators().unwrap();
+            let validator = validators.iter().next().unwrap();
+            (validator.address.clone(), blockchain.block_number())
+        };
+        let epoch_number = Policy::epoch_at(head);
+
+        let performance =
+            validator_performance(&producer.blockchain, &validator_address, epoch_number).unwrap();
+        assert_eq!(performance.block_number, head);
+        assert_eq!(performance.num_slots, Policy::SLOTS);
+        assert_eq!(
+            performance.micro_blocks,
+            Some(MicroBlockPerformance {
+                expected: num_blocks - 1,
+                produced: num_blocks - 2,
+                skip_blocks_signed: 1,
+            })
+        );
+        assert_eq!(performance.macro_blocks, 1);
+        assert_eq!(performance.macro_blocks_signed, 1);
+        assert_eq!(performance.macro_slots_signed, Policy::SLOTS as u64);
+        assert_eq!(performance.tendermint_rounds, 1);
+        assert_eq!(performance.tendermint_rounds_participated, 1);
+        assert_eq!(performance.skip_blocks_caused, 1);
+        assert_eq!(performance.penalized_slots.len(), 1);
+        assert!(!performance.jailed);
+
+        let mut tracker = PerformanceTracker::new(validator_address);
+        assert_eq!(tracker.update(&producer.blockchain).unwrap(), performance);
+    }
+}
@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use nimiq_block::{MacroHeader, MicroHeader, SkipBlockInfo};
+use nimiq_bls::{KeyPair as BlsKeyPair, PublicKey as BlsPublicKey, Signature as BlsSignature};
+use nimiq_hash::{Blake2bHash, Hash};
+use nimiq_keys::{
+    Address, Ed25519PublicKey as SchnorrPublicKey, Ed25519Signature, KeyPair as SchnorrKeyPair,
+};
+use nimiq_primitives::{coin::Coin, networks::NetworkId, signed::Message, TendermintVote};
+use nimiq_serde::Serialize;
+use nimiq_transaction::Transaction;
+use nimiq_transaction_builder::TransactionBuilder;
+use nimiq_vrf::VrfSeed;
+use parking_lot::RwLock;
+
+use super::{SignerError, ValidatorSigner};
+use crate::{aggregation::tendermint::proposal::SignedProposal, key_rotation::KeyStore};
+
+#[derive(Clone)]
+struct Keys {
+    signing_key: SchnorrKeyPair,
+    voting_key: BlsKeyPair,
+    fee_key: SchnorrKeyPair,
+}
+
+/// A signer holding the validator keys in memory.
+///
+/// The signing and voting keys can be replaced while the validator is running, see
+/// [`crate::key_rotation`].
+pub struct LocalSigner {
+    keys: RwLock<Keys>,
+    key_store: Option<Arc<dyn KeyStore>>,
+}
+
+impl LocalSigner {
+    pub fn new(
+        signing_key: SchnorrKeyPair,
+        voting_key: BlsKeyPair,
+        fee_key: SchnorrKeyPair,
+    ) -> Self {
+        Self {
+            keys: RwLock::new(Keys {
+                signing_key,
+                voting_key,
+                fee_key,
+            }),
+            key_store: None,
+        }
+    }
+
+    /// Persists replaced keys in the given store, such that they are used after a restart.
+    pub fn with_key_store(mut self, key_store: Arc<dyn KeyStore>) -> Self {
+        self.key_store = Some(key_store);
+        self
+    }
+
+    pub fn signing_key(&self) -> SchnorrKeyPair {
+        self.keys.read().signing_key.clone()
+    }
+
+    pub fn voting_key(&self) -> BlsKeyPair {
+        self.keys.read().voting_key.clone()
+    }
+
+    pub fn fee_key(&self) -> SchnorrKeyPair {
+        self.keys.read().fee_key.clone()
+    }
+
+    /// Fails if the given keys couldn't be persisted after replacing them. Without a key store,
+    /// replaced keys are only kept in memory.
+    pub fn check_key_store(&self, signing_key: bool, voting_key: bool) -> Result<(), SignerError> {
+        match &self.key_store {
+            Some(key_store) => key_store.check_writable(signing_key, voting_key),
+            None => Ok(()),
+        }
+    }
+
+    /// Replaces the signing and/or voting key. The new keys are used for all subsequent
+    /// signatures, even if persisting them fails.
+    pub fn replace_keys(
+        &self,
+        signing_key: Option<SchnorrKeyPair>,
+        voting_key: Option<BlsKeyPair>,
+    ) -> Result<(), SignerError> {
+        let mut keys = self.keys.write();
+        if let Some(signing_key) = signing_key.clone() {
+            keys.signing_key = signing_key;
+        }
+        if let Some(voting_key) = voting_key.clone() {
+            keys.voting_key = voting_key;
+        }
+        drop(keys);
+
+        if let Some(key_store) = &self.key_store {
+            if let Some(signing_key) = &signing_key {
+                key_store.store_signing_key(signing_key)?;
+            }
+            if let Some(voting_key) = &voting_key {
+                key_store.store_voting_key(voting_key)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Clone for LocalSigner {
+    fn clone(&self) -> Self {
+        Self {
+            keys: RwLock::new(self.keys.read().clone()),
+            key_store: self.key_store.clone(),
+        }
+    }
+}
+
+impl ValidatorSigner for LocalSigner {
+    fn signing_public_key(&self) -> SchnorrPublicKey {
+        self.keys.read().signing_key.public
+    }
+
+    fn voting_public_key(&self) -> BlsPublicKey {
+        self.keys.read().voting_key.public_key
+    }
+
+    fn vrf_seed(&self, prev_seed: &VrfSeed) -> Result<VrfSeed, SignerError> {
+        Ok(prev_seed.sign_next(&self.keys.read().signing_key))
+    }
+
+    fn sign_micro_header(&self, header: &MicroHeader) -> Result<Ed25519Signature, SignerError> {
+        let hash = header.hash::<Blake2bHash>();
+        Ok(self.keys.read().signing_key.sign(hash.as_slice()))
+    }
+
+    fn sign_proposal(
+        &self,
+        header: &MacroHeader,
+        round: u32,
+        valid_round: Option<u32>,
+    ) -> Result<Ed25519Signature, SignerError> {
+        let data = SignedProposal::hash(header, round, valid_round).serialize_to_vec();
+        Ok(self.keys.read().signing_key.sign(&data))
+    }
+
+    fn sign_tendermint_vote(&self, vote: &TendermintVote) -> Result<BlsSignature, SignerError> {
+        Ok(self.keys.read().voting_key.sign(vote))
+    }
+
+    fn sign_skip_block(&self, info: &SkipBlockInfo) -> Result<BlsSignature, SignerError> {
+        Ok(info.sign(&self.keys.read().voting_key.secret_key))
+    }
+
+    fn sign_validator_record(&self, data: &[u8]) -> Result<Vec<u8>, SignerError> {
+        Ok(self
+            .keys
+            .read()
+            .voting_key
+            .sign(&data)
+            .compress()
+            .as_ref()
+            .to_vec())
+    }
+
+    fn reactivate_transaction(
+        &self,
+        validator_address: Address,
+        validity_start_height: u32,
+        network_id: NetworkId,
+    ) -> Result<Transaction, SignerError> {
+        let keys = self.keys.read();
+        Ok(TransactionBuilder::new_reactivate_validator(
+            &keys.fee_key,
+            validator_address,
+            &keys.signing_key,
+            Coin::ZERO,
+            validity_start_height,
+            network_id,
+        ))
+    }
+
+    fn local_keys(&self) -> Option<&LocalSigner> {
+        Some(self)
+    }
+}
@@ -0,0 +1,93 @@
+//! Abstraction over the keys of a validator.
+//!
+//! The validator never touches its secret keys directly, it asks a [`ValidatorSigner`] for every
+//! signature instead. The [`LocalSigner`] holds the keys in memory, while the [`RemoteSigner`]
+//! forwards all requests to a separate signer process (see [`SignerService`]), such that the
+//! keys never need to be present on the validator host.
+
+use nimiq_block::{MacroHeader, MicroHeader, SkipBlockInfo};
+use nimiq_bls::{PublicKey as BlsPublicKey, Signature as BlsSignature};
+use nimiq_keys::{Address, Ed25519PublicKey as SchnorrPublicKey, Ed25519Signature};
+use nimiq_primitives::{networks::NetworkId, TendermintVote};
+use nimiq_serde::DeserializeError;
+use nimiq_transaction::Transaction;
+use nimiq_vrf::VrfSeed;
+use thiserror::Error;
+
+pub use self::{
+    local::LocalSigner,
+    remote::{RemoteSigner, SignerAddress},
+    service::SignerService,
+};
+
+mod local;
+pub mod protocol;
+mod remote;
+mod service;
+
+#[derive(Debug, Error)]
+pub enum SignerError {
+    #[error("Signer refused to sign: {0}")]
+    Refused(String),
+    #[error("Signer connection failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid message from signer: {0}")]
+    Deserialize(#[from] DeserializeError),
+    #[error("Signer message too large: {0} bytes")]
+    MessageTooLarge(usize),
+    #[error("Signer authentication failed")]
+    Authentication,
+    #[error("Unexpected response from signer")]
+    UnexpectedResponse,
+    #[error("Failed to store keys: {0}")]
+    KeyStore(String),
+}
+
+/// Creates all signatures on behalf of a validator.
+///
+/// The methods are blocking. Callers must not hold the blockchain lock while waiting for a
+/// signature, since a remote signer might take a while to answer.
+pub trait ValidatorSigner: Send + Sync {
+    /// The public key corresponding to the signing key, which signs blocks and proposals.
+    fn signing_public_key(&self) -> SchnorrPublicKey;
+
+    /// The public key corresponding to the voting key, which signs votes and the validator record.
+    fn voting_public_key(&self) -> BlsPublicKey;
+
+    /// Computes the VRF seed following `prev_seed` with the signing key.
+    fn vrf_seed(&self, prev_seed: &VrfSeed) -> Result<VrfSeed, SignerError>;
+
+    /// Signs the hash of a micro block header we produced.
+    fn sign_micro_header(&self, header: &MicroHeader) -> Result<Ed25519Signature, SignerError>;
+
+    /// Signs a macro block proposal for the given Tendermint round.
+    fn sign_proposal(
+        &self,
+        header: &MacroHeader,
+        round: u32,
+        valid_round: Option<u32>,
+    ) -> Result<Ed25519Signature, SignerError>;
+
+    /// Signs a Tendermint prevote or precommit.
+    fn sign_tendermint_vote(&self, vote: &TendermintVote) -> Result<BlsSignature, SignerError>;
+
+    /// Signs our contribution to a skip block.
+    fn sign_skip_block(&self, info: &SkipBlockInfo) -> Result<BlsSignature, SignerError>;
+
+    /// Signs the tagged data of our validator record in the DHT, returning the compressed
+    /// signature.
+    fn sign_validator_record(&self, data: &[u8]) -> Result<Vec<u8>, SignerError>;
+
+    /// Creates a signed transaction reactivating our validator, paying the fee with the fee key.
+    fn reactivate_transaction(
+        &self,
+        validator_address: Address,
+        validity_start_height: u32,
+        network_id: NetworkId,
+    ) -> Result<Transaction, SignerError>;
+
+    /// Returns the local keys if this signer holds them in memory.
+    fn local_keys(&self) -> Option<&LocalSigner> {
+        None
+    }
+}
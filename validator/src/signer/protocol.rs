@@ -0,0 +1,345 @@
+//! The protocol spoken between a [`RemoteSigner`](super::RemoteSigner) and a
+//! [`SignerService`](super::SignerService).
+//!
+//! Every message is sent as a frame, which is a big-endian `u32` length followed by the
+//! serialized message. When a connection is established, the signer sends a random nonce. Every
+//! request and response afterwards is wrapped in an [`Envelope`] that carries a counter and an
+//! HMAC over the nonce, the direction, the counter and the payload, keyed with a secret shared by
+//! the validator and the signer. The counter must strictly increase on every request and the nonce
+//! is fresh for every connection, which prevents replaying requests. Messages are not encrypted,
+//! since they don't contain secrets.
+
+use std::io::{Read, Write};
+
+use nimiq_block::{MacroHeader, MicroHeader, SkipBlockInfo};
+use nimiq_bls::{PublicKey as BlsPublicKey, Signature as BlsSignature};
+use nimiq_hash::{hmac::compute_hmac_sha512, Blake2sHash};
+use nimiq_keys::{Address, Ed25519PublicKey as SchnorrPublicKey, Ed25519Signature};
+use nimiq_primitives::{networks::NetworkId, TendermintIdentifier};
+use nimiq_serde::{Deserialize, Serialize};
+use nimiq_transaction::Transaction;
+use nimiq_vrf::VrfSeed;
+use rand::RngCore;
+
+use super::SignerError;
+
+/// The maximum size of a single frame.
+pub const MAX_FRAME_SIZE: usize = 64 * 1024;
+
+/// The size of the nonce sent by the signer when a connection is established.
+pub const NONCE_SIZE: usize = 32;
+
+// The requests are dominated by the size of a macro block header.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SignerRequest {
+    PublicKeys,
+    VrfSeed(VrfSeed),
+    MicroHeader(MicroHeader),
+    Proposal {
+        header: MacroHeader,
+        round: u32,
+        valid_round: Option<u32>,
+    },
+    TendermintVote {
+        proposal_hash: Option<Blake2sHash>,
+        id: TendermintIdentifier,
+    },
+    SkipBlock(SkipBlockInfo),
+    ValidatorRecord(Vec<u8>),
+    ReactivateTransaction {
+        validator_address: Address,
+        validity_start_height: u32,
+        network_id: NetworkId,
+    },
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum SignerResponse {
+    PublicKeys {
+        signing_key: SchnorrPublicKey,
+        voting_key: BlsPublicKey,
+    },
+    VrfSeed(VrfSeed),
+    Signature(Ed25519Signature),
+    VotingSignature(BlsSignature),
+    RecordSignature(Vec<u8>),
+    Transaction(Transaction),
+    /// The signer refused the request, e.g. because it would be a double sign.
+    Refused(String),
+}
+
+/// An authenticated message.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Envelope {
+    pub counter: u64,
+    pub payload: Vec<u8>,
+    pub mac: Vec<u8>,
+}
+
+#[derive(Clone, Copy)]
+#[repr(u8)]
+enum Direction {
+    Request = 0x00,
+    Response = 0x01,
+}
+
+/// One end of an authenticated connection between a validator and its signer.
+pub struct SignerChannel<S> {
+    stream: S,
+    key: Vec<u8>,
+    nonce: [u8; NONCE_SIZE],
+    counter: u64,
+}
+
+impl<S: Read + Write> SignerChannel<S> {
+    /// Opens the validator end of a connection.
+    pub fn connect(mut stream: S, key: &[u8]) -> Result<Self, SignerError> {
+        let nonce = read_frame(&mut stream)?
+            .try_into()
+            .map_err(|_| SignerError::Authentication)?;
+
+        Ok(Self {
+            stream,
+            key: key.to_vec(),
+            nonce,
+            counter: 0,
+        })
+    }
+
+    /// Opens the signer end of a connection.
+    pub fn accept(mut stream: S, key: &[u8]) -> Result<Self, SignerError> {
+        let mut nonce = [0u8; NONCE_SIZE];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        write_frame(&mut stream, &nonce)?;
+
+        Ok(Self {
+            stream,
+            key: key.to_vec(),
+            nonce,
+            counter: 0,
+        })
+    }
+
+    /// Sends a request to the signer and waits for its response.
+    pub fn request(&mut self, request: &SignerRequest) -> Result<SignerResponse, SignerError> {
+        self.counter += 1;
+        self.send(Direction::Request, request)?;
+
+        let (counter, response) = self.receive(Direction::Response)?;
+        if counter != self.counter {
+            return Err(SignerError::Authentication);
+        }
+        Ok(response)
+    }
+
+    /// Waits for the next request from the validator.
+    pub fn next_request(&mut self) -> Result<SignerRequest, SignerError> {
+        let (counter, request) = self.receive(Direction::Request)?;
+        if counter <= self.counter {
+            return Err(SignerError::Authentication);
+        }
+        self.counter = counter;
+        Ok(request)
+    }
+
+    /// Responds to the last request received from the validator.
+    pub fn respond(&mut self, response: &SignerResponse) -> Result<(), SignerError> {
+        self.send(Direction::Response, response)
+    }
+
+    fn send<T: Serialize>(&mut self, direction: Direction, message: &T) -> Result<(), SignerError> {
+        let payload = message.serialize_to_vec();
+        let envelope = Envelope {
+            counter: self.counter,
+            mac: self.mac(direction, self.counter, &payload),
+            payload,
+        };
+        write_frame(&mut self.stream, &envelope.serialize_to_vec())
+    }
+
+    fn receive<T: Deserialize>(&mut self, direction: Direction) -> Result<(u64, T), SignerError> {
+        let envelope = Envelope::deserialize_from_vec(&read_frame(&mut self.stream)?)?;
+
+        let mac = self.mac(direction, envelope.counter, &envelope.payload);
+        if !constant_time_eq(&mac, &envelope.mac) {
+            return Err(SignerError::Authentication);
+        }
+
+        Ok((
+            envelope.counter,
+            T::deserialize_from_vec(&envelope.payload)?,
+        ))
+    }
+
+    fn mac(&self, direction: Direction, counter: u64, payload: &[u8]) -> Vec<u8> {
+        let mut data = Vec::with_capacity(NONCE_SIZE + 9 + payload.len());
+        data.extend_from_slice(&self.nonce);
+        data.push(direction as u8);
+        data.extend_from_slice(&counter.to_be_bytes());
+        data.extend_from_slice(payload);
+
+        compute_hmac_sha512(&self.key, &data).as_slice().to_vec()
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+fn write_frame<W: Write>(writer: &mut W, data: &[u8]) -> Result<(), SignerError> {
+    if data.len() > MAX_FRAME_SIZE {
+        return Err(SignerError::MessageTooLarge(data.len()));
+    }
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(data)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn read_frame<R: Read>(reader: &mut R) -> Result<Vec<u8>, SignerError> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(SignerError::MessageTooLarge(len));
+    }
+
+    let mut data = vec![0u8; len];
+    reader.read_exact(&mut data)?;
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{TcpListener, TcpStream};
+
+    use nimiq_test_log::test;
+
+    use super::*;
+
+    const KEY: &[u8] = b"shared secret";
+
+    /// Opens the validator and the signer end of a loopback connection with the given keys.
+    fn channels(
+        validator_key: &[u8],
+        signer_key: &[u8],
+    ) -> (SignerChannel<TcpStream>, SignerChannel<TcpStream>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let validator = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (signer, _) = listener.accept().unwrap();
+
+        let signer = SignerChannel::accept(signer, signer_key).unwrap();
+        let validator = SignerChannel::connect(validator, validator_key).unwrap();
+        (validator, signer)
+    }
+
+    /// Sends a request with the given counter.
+    fn send_request(channel: &mut SignerChannel<TcpStream>, counter: u64) {
+        channel.counter = counter;
+        channel
+            .send(Direction::Request, &SignerRequest::PublicKeys)
+            .unwrap();
+    }
+
+    #[test]
+    fn it_authenticates_requests() {
+        let (mut validator, mut signer) = channels(KEY, KEY);
+        send_request(&mut validator, 1);
+        assert!(matches!(
+            signer.next_request(),
+            Ok(SignerRequest::PublicKeys)
+        ));
+
+        // A request with a different key is rejected.
+        let (mut validator, mut signer) = channels(b"other secret", KEY);
+        send_request(&mut validator, 1);
+        assert!(matches!(
+            signer.next_request(),
+            Err(SignerError::Authentication)
+        ));
+
+        // A request with a tampered payload is rejected.
+        let (mut validator, mut signer) = channels(KEY, KEY);
+        let payload = SignerRequest::PublicKeys.serialize_to_vec();
+        let envelope = Envelope {
+            counter: 1,
+            mac: validator.mac(Direction::Request, 1, &payload),
+            payload: SignerRequest::ValidatorRecord(vec![]).serialize_to_vec(),
+        };
+        write_frame(&mut validator.stream, &envelope.serialize_to_vec()).unwrap();
+        assert!(matches!(
+            signer.next_request(),
+            Err(SignerError::Authentication)
+        ));
+
+        // A request recorded on another connection is rejected, since the nonce differs.
+        let (other_validator, _other_signer) = channels(KEY, KEY);
+        let payload = SignerRequest::PublicKeys.serialize_to_vec();
+        let envelope = Envelope {
+            counter: 1,
+            mac: other_validator.mac(Direction::Request, 1, &payload),
+            payload,
+        };
+        write_frame(&mut validator.stream, &envelope.serialize_to_vec()).unwrap();
+        assert!(matches!(
+            signer.next_request(),
+            Err(SignerError::Authentication)
+        ));
+    }
+
+    #[test]
+    fn it_rejects_replayed_and_reordered_requests() {
+        let (mut validator, mut signer) = channels(KEY, KEY);
+
+        send_request(&mut validator, 1);
+        assert!(signer.next_request().is_ok());
+        send_request(&mut validator, 1);
+        assert!(matches!(
+            signer.next_request(),
+            Err(SignerError::Authentication)
+        ));
+
+        send_request(&mut validator, 3);
+        assert!(signer.next_request().is_ok());
+        send_request(&mut validator, 2);
+        assert!(matches!(
+            signer.next_request(),
+            Err(SignerError::Authentication)
+        ));
+    }
+
+    #[test]
+    fn it_rejects_responses_to_other_requests() {
+        let response = SignerResponse::Refused("test".to_string());
+
+        // The response carries the counter of the request it answers. It is sent ahead of the
+        // request here, which the loopback connection buffers.
+        let (mut validator, mut signer) = channels(KEY, KEY);
+        signer.counter = 1;
+        signer.respond(&response).unwrap();
+        assert!(matches!(
+            validator.request(&SignerRequest::PublicKeys),
+            Ok(SignerResponse::Refused(_))
+        ));
+
+        let (mut validator, mut signer) = channels(KEY, KEY);
+        validator.counter = 1;
+        signer.counter = 1;
+        signer.respond(&response).unwrap();
+        assert!(matches!(
+            validator.request(&SignerRequest::PublicKeys),
+            Err(SignerError::Authentication)
+        ));
+
+        // A request reflected as a response doesn't authenticate either.
+        let (mut validator, mut signer) = channels(KEY, KEY);
+        signer.counter = 1;
+        signer.send(Direction::Request, &response).unwrap();
+        assert!(matches!(
+            validator.request(&SignerRequest::PublicKeys),
+            Err(SignerError::Authentication)
+        ));
+    }
+}
@@ -0,0 +1,305 @@
+use std::{
+    fmt,
+    io::{self, Read, Write},
+    net::TcpStream,
+    str::FromStr,
+    time::Duration,
+};
+#[cfg(unix)]
+use std::{os::unix::net::UnixStream, path::PathBuf};
+
+use nimiq_block::{MacroHeader, MicroHeader, SkipBlockInfo};
+use nimiq_bls::{PublicKey as BlsPublicKey, Signature as BlsSignature};
+use nimiq_keys::{Address, Ed25519PublicKey as SchnorrPublicKey, Ed25519Signature};
+use nimiq_primitives::{networks::NetworkId, TendermintVote};
+use nimiq_transaction::Transaction;
+use nimiq_vrf::VrfSeed;
+use parking_lot::Mutex;
+use tokio::{
+    runtime::{Handle, RuntimeFlavor},
+    task::{block_in_place, spawn_blocking},
+};
+
+use super::{
+    protocol::{SignerChannel, SignerRequest, SignerResponse},
+    SignerError, ValidatorSigner,
+};
+
+/// The address of a signer process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignerAddress {
+    /// A TCP socket address, e.g. `127.0.0.1:8649`.
+    Tcp(String),
+    /// The path of a Unix domain socket, written as `unix:<path>`.
+    #[cfg(unix)]
+    Unix(PathBuf),
+}
+
+impl FromStr for SignerAddress {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = s.strip_prefix("unix:") {
+            #[cfg(unix)]
+            return Ok(SignerAddress::Unix(PathBuf::from(path)));
+            #[cfg(not(unix))]
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("Unix sockets are not supported on this platform: {}", path),
+            ));
+        }
+        Ok(SignerAddress::Tcp(s.to_string()))
+    }
+}
+
+impl fmt::Display for SignerAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignerAddress::Tcp(address) => write!(f, "{}", address),
+            #[cfg(unix)]
+            SignerAddress::Unix(path) => write!(f, "unix:{}", path.display()),
+        }
+    }
+}
+
+/// A TCP or Unix socket connection.
+pub enum SignerStream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl SignerStream {
+    /// Connects to the given address. Reads and writes time out after `timeout`.
+    pub fn connect(address: &SignerAddress, timeout: Duration) -> io::Result<Self> {
+        match address {
+            SignerAddress::Tcp(address) => {
+                let stream = TcpStream::connect(address)?;
+                stream.set_nodelay(true)?;
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                Ok(SignerStream::Tcp(stream))
+            }
+            #[cfg(unix)]
+            SignerAddress::Unix(path) => {
+                let stream = UnixStream::connect(path)?;
+                stream.set_read_timeout(Some(timeout))?;
+                stream.set_write_timeout(Some(timeout))?;
+                Ok(SignerStream::Unix(stream))
+            }
+        }
+    }
+}
+
+impl Read for SignerStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            SignerStream::Tcp(stream) => stream.read(buf),
+            #[cfg(unix)]
+            SignerStream::Unix(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for SignerStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            SignerStream::Tcp(stream) => stream.write(buf),
+            #[cfg(unix)]
+            SignerStream::Unix(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            SignerStream::Tcp(stream) => stream.flush(),
+            #[cfg(unix)]
+            SignerStream::Unix(stream) => stream.flush(),
+        }
+    }
+}
+
+/// A signer forwarding all requests to a separate signer process, which holds the keys.
+///
+/// The connection is established lazily and re-established after errors. Since the requests
+/// perform blocking I/O, they are run with [`block_in_place`] on a multi-threaded runtime, such
+/// that other tasks can proceed on the worker thread in the meantime. Callers must not hold the
+/// blockchain lock while waiting for a signature.
+pub struct RemoteSigner {
+    address: SignerAddress,
+    auth_key: Vec<u8>,
+    timeout: Duration,
+    channel: Mutex<Option<SignerChannel<SignerStream>>>,
+    signing_public_key: SchnorrPublicKey,
+    voting_public_key: BlsPublicKey,
+}
+
+impl RemoteSigner {
+    /// The default time to wait for the signer to respond.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// Connects to the signer at `address`, authenticating with the shared `auth_key`, and
+    /// fetches the public keys of the validator.
+    pub async fn connect(
+        address: SignerAddress,
+        auth_key: Vec<u8>,
+        timeout: Duration,
+    ) -> Result<Self, SignerError> {
+        spawn_blocking(move || Self::connect_blocking(address, auth_key, timeout))
+            .await
+            .map_err(|error| SignerError::Io(io::Error::new(io::ErrorKind::Other, error)))?
+    }
+
+    fn connect_blocking(
+        address: SignerAddress,
+        auth_key: Vec<u8>,
+        timeout: Duration,
+    ) -> Result<Self, SignerError> {
+        let mut channel =
+            SignerChannel::connect(SignerStream::connect(&address, timeout)?, &auth_key)?;
+        let (signing_public_key, voting_public_key) =
+            match channel.request(&SignerRequest::PublicKeys)? {
+                SignerResponse::PublicKeys {
+                    signing_key,
+                    voting_key,
+                } => (signing_key, voting_key),
+                response => return Err(Self::unexpected(response)),
+            };
+
+        Ok(Self {
+            address,
+            auth_key,
+            timeout,
+            channel: Mutex::new(Some(channel)),
+            signing_public_key,
+            voting_public_key,
+        })
+    }
+
+    fn request(&self, request: SignerRequest) -> Result<SignerResponse, SignerError> {
+        // Outside of a multi-threaded runtime, e.g. in tests, we can only block.
+        match Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                block_in_place(|| self.request_blocking(request))
+            }
+            _ => self.request_blocking(request),
+        }
+    }
+
+    fn request_blocking(&self, request: SignerRequest) -> Result<SignerResponse, SignerError> {
+        let mut channel = self.channel.lock();
+
+        // A connection that has been idle for a while might have been closed by the other side,
+        // so we retry once with a fresh connection.
+        let mut retry = channel.is_some();
+        loop {
+            if channel.is_none() {
+                let stream = SignerStream::connect(&self.address, self.timeout)?;
+                *channel = Some(SignerChannel::connect(stream, &self.auth_key)?);
+            }
+
+            match channel.as_mut().unwrap().request(&request) {
+                Ok(SignerResponse::Refused(reason)) => return Err(SignerError::Refused(reason)),
+                Ok(response) => return Ok(response),
+                Err(error) => {
+                    warn!(%error, address = %self.address, "Request to signer failed");
+                    *channel = None;
+                    if !retry || !matches!(error, SignerError::Io(_)) {
+                        return Err(error);
+                    }
+                    retry = false;
+                }
+            }
+        }
+    }
+
+    fn unexpected(response: SignerResponse) -> SignerError {
+        match response {
+            SignerResponse::Refused(reason) => SignerError::Refused(reason),
+            _ => SignerError::UnexpectedResponse,
+        }
+    }
+}
+
+impl ValidatorSigner for RemoteSigner {
+    fn signing_public_key(&self) -> SchnorrPublicKey {
+        self.signing_public_key
+    }
+
+    fn voting_public_key(&self) -> BlsPublicKey {
+        self.voting_public_key
+    }
+
+    fn vrf_seed(&self, prev_seed: &VrfSeed) -> Result<VrfSeed, SignerError> {
+        match self.request(SignerRequest::VrfSeed(prev_seed.clone()))? {
+            SignerResponse::VrfSeed(seed) => Ok(seed),
+            response => Err(Self::unexpected(response)),
+        }
+    }
+
+    fn sign_micro_header(&self, header: &MicroHeader) -> Result<Ed25519Signature, SignerError> {
+        match self.request(SignerRequest::MicroHeader(header.clone()))? {
+            SignerResponse::Signature(signature) => Ok(signature),
+            response => Err(Self::unexpected(response)),
+        }
+    }
+
+    fn sign_proposal(
+        &self,
+        header: &MacroHeader,
+        round: u32,
+        valid_round: Option<u32>,
+    ) -> Result<Ed25519Signature, SignerError> {
+        let request = SignerRequest::Proposal {
+            header: header.clone(),
+            round,
+            valid_round,
+        };
+        match self.request(request)? {
+            SignerResponse::Signature(signature) => Ok(signature),
+            response => Err(Self::unexpected(response)),
+        }
+    }
+
+    fn sign_tendermint_vote(&self, vote: &TendermintVote) -> Result<BlsSignature, SignerError> {
+        let request = SignerRequest::TendermintVote {
+            proposal_hash: vote.proposal_hash.clone(),
+            id: vote.id.clone(),
+        };
+        match self.request(request)? {
+            SignerResponse::VotingSignature(signature) => Ok(signature),
+            response => Err(Self::unexpected(response)),
+        }
+    }
+
+    fn sign_skip_block(&self, info: &SkipBlockInfo) -> Result<BlsSignature, SignerError> {
+        match self.request(SignerRequest::SkipBlock(info.clone()))? {
+            SignerResponse::VotingSignature(signature) => Ok(signature),
+            response => Err(Self::unexpected(response)),
+        }
+    }
+
+    fn sign_validator_record(&self, data: &[u8]) -> Result<Vec<u8>, SignerError> {
+        match self.request(SignerRequest::ValidatorRecord(data.to_vec()))? {
+            SignerResponse::RecordSignature(signature) => Ok(signature),
+            response => Err(Self::unexpected(response)),
+        }
+    }
+
+    fn reactivate_transaction(
+        &self,
+        validator_address: Address,
+        validity_start_height: u32,
+        network_id: NetworkId,
+    ) -> Result<Transaction, SignerError> {
+        let request = SignerRequest::ReactivateTransaction {
+            validator_address,
+            validity_start_height,
+            network_id,
+        };
+        match self.request(request)? {
+            SignerResponse::Transaction(transaction) => Ok(transaction),
+            response => Err(Self::unexpected(response)),
+        }
+    }
+}
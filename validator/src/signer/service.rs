@@ -0,0 +1,307 @@
+use std::{
+    cmp,
+    io::{Read, Write},
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use nimiq_hash::{Blake2sHash, Hash};
+use nimiq_primitives::{policy::Policy, TendermintVote};
+use nimiq_utils::tagged_signing::TaggedSignable;
+use nimiq_validator_network::validator_record::ValidatorRecord;
+
+use super::{
+    protocol::{SignerChannel, SignerRequest, SignerResponse},
+    LocalSigner, SignerError, ValidatorSigner,
+};
+use crate::signing_history::SigningHistory;
+
+/// The signer side of the remote signer protocol, used by the reference signer.
+///
+/// It holds the validator keys and refuses to sign anything that conflicts with what it signed
+/// before: a second micro block or skip block at the same height, a second proposal in the same
+/// Tendermint round or a second vote in the same round and step.
+///
+/// Requests at or below the height the history was pruned to are refused, since the records
+/// needed to detect conflicts are gone.
+pub struct SignerService {
+    signer: LocalSigner,
+    history: SigningHistory,
+    auth_key: Vec<u8>,
+    /// The records below this height have been removed from the history.
+    pruned_until: AtomicU32,
+    /// The highest height we signed anything at.
+    highest_signed: AtomicU32,
+}
+
+impl SignerService {
+    pub fn new(signer: LocalSigner, history: SigningHistory, auth_key: Vec<u8>) -> Self {
+        // Records below the lowest one left might have been pruned before a restart.
+        let (pruned_until, highest_signed) = history
+            .height_range()
+            .map(|(first, last)| (first.saturating_sub(1), last))
+            .unwrap_or_default();
+
+        Self {
+            signer,
+            history,
+            auth_key,
+            pruned_until: AtomicU32::new(pruned_until),
+            highest_signed: AtomicU32::new(highest_signed),
+        }
+    }
+
+    /// Serves the requests of a validator on the given connection until it is closed or an error
+    /// occurs.
+    pub fn serve<S: Read + Write>(&self, stream: S) -> Result<(), SignerError> {
+        let mut channel = SignerChannel::accept(stream, &self.auth_key)?;
+        loop {
+            let request = match channel.next_request() {
+                Ok(request) => request,
+                Err(SignerError::Io(error))
+                    if error.kind() == std::io::ErrorKind::UnexpectedEof =>
+                {
+                    return Ok(())
+                }
+                Err(error) => return Err(error),
+            };
+            channel.respond(&self.handle(request))?;
+        }
+    }
+
+    /// Handles a single request, enforcing the double-sign rules.
+    pub fn handle(&self, request: SignerRequest) -> SignerResponse {
+        match self.try_handle(request) {
+            Ok(response) => response,
+            Err(error) => {
+                warn!(%error, "Refusing signing request");
+                SignerResponse::Refused(error.to_string())
+            }
+        }
+    }
+
+    fn try_handle(&self, request: SignerRequest) -> Result<SignerResponse, SignerError> {
+        let refuse = |error: &dyn std::fmt::Display| SignerError::Refused(error.to_string());
+
+        let response = match request {
+            SignerRequest::PublicKeys => SignerResponse::PublicKeys {
+                signing_key: self.signer.signing_public_key(),
+                voting_key: self.signer.voting_public_key(),
+            },
+            SignerRequest::VrfSeed(prev_seed) => {
+                SignerResponse::VrfSeed(self.signer.vrf_seed(&prev_seed)?)
+            }
+            SignerRequest::MicroHeader(header) => {
+                self.check_height(header.block_number)?;
+                self.history
+                    .record_micro_block(&header)
+                    .map_err(|e| refuse(&e))?;
+                self.signed(header.block_number);
+                SignerResponse::Signature(self.signer.sign_micro_header(&header)?)
+            }
+            SignerRequest::Proposal {
+                header,
+                round,
+                valid_round,
+            } => {
+                self.check_height(header.block_number)?;
+                self.history
+                    .record_proposal(header.block_number, round, header.hash::<Blake2sHash>())
+                    .map_err(|e| refuse(&e))?;
+                self.signed(header.block_number);
+                SignerResponse::Signature(self.signer.sign_proposal(&header, round, valid_round)?)
+            }
+            SignerRequest::TendermintVote { proposal_hash, id } => {
+                let vote = TendermintVote { proposal_hash, id };
+                self.check_height(vote.id.block_number)?;
+                self.history.record_vote(&vote).map_err(|e| refuse(&e))?;
+                self.signed(vote.id.block_number);
+                SignerResponse::VotingSignature(self.signer.sign_tendermint_vote(&vote)?)
+            }
+            SignerRequest::SkipBlock(info) => {
+                self.check_height(info.block_number)?;
+                self.history
+                    .record_skip_block(&info)
+                    .map_err(|e| refuse(&e))?;
+                self.signed(info.block_number);
+                SignerResponse::VotingSignature(self.signer.sign_skip_block(&info)?)
+            }
+            SignerRequest::ValidatorRecord(data) => {
+                // The voting key must only be used for validator records here, everything else
+                // would bypass the rules above.
+                if data.first() != Some(&ValidatorRecord::<()>::TAG) {
+                    return Err(refuse(&"not a validator record"));
+                }
+                SignerResponse::RecordSignature(self.signer.sign_validator_record(&data)?)
+            }
+            SignerRequest::ReactivateTransaction {
+                validator_address,
+                validity_start_height,
+                network_id,
+            } => SignerResponse::Transaction(self.signer.reactivate_transaction(
+                validator_address,
+                validity_start_height,
+                network_id,
+            )?),
+        };
+        Ok(response)
+    }
+
+    /// Refuses requests at heights whose records were pruned and prunes the records of finalized
+    /// batches.
+    ///
+    /// Being asked to sign at `block_number` implies that the macro block preceding it is final.
+    /// Since the validator might be compromised, the pruning never goes beyond the highest height
+    /// we actually signed at, such that a single request at a bogus height can't wipe records
+    /// that are still needed.
+    fn check_height(&self, block_number: u32) -> Result<(), SignerError> {
+        let pruned_until = self.pruned_until.load(Ordering::Acquire);
+        if block_number <= pruned_until {
+            return Err(SignerError::Refused(format!(
+                "block {} precedes the pruned signing history ({})",
+                block_number, pruned_until
+            )));
+        }
+
+        if block_number <= Policy::genesis_block_number() {
+            return Ok(());
+        }
+        let prune_until = cmp::min(
+            Policy::macro_block_before(block_number),
+            self.highest_signed.load(Ordering::Acquire),
+        );
+        if self.pruned_until.fetch_max(prune_until, Ordering::AcqRel) < prune_until {
+            self.history.prune(prune_until);
+        }
+        Ok(())
+    }
+
+    /// Notes that we signed something at the given height.
+    fn signed(&self, block_number: u32) {
+        self.highest_signed
+            .fetch_max(block_number, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, sync::Arc, thread};
+
+    use nimiq_block::{MacroHeader, MicroHeader, SkipBlockInfo};
+    use nimiq_database::mdbx::MdbxDatabase;
+    use nimiq_hash::Blake2bHash;
+    use nimiq_primitives::{networks::NetworkId, TendermintIdentifier, TendermintStep};
+    use nimiq_test_log::test;
+    use nimiq_test_utils::blockchain::{signing_key, voting_key};
+    use nimiq_vrf::{VrfEntropy, VrfSeed};
+
+    use super::*;
+    use crate::signer::{RemoteSigner, SignerAddress};
+
+    const KEY: &[u8] = b"shared secret";
+
+    /// Starts a signer service on a loopback port and connects a remote signer to it.
+    async fn remote_signer() -> RemoteSigner {
+        let signer = LocalSigner::new(signing_key(), voting_key(), signing_key());
+        let history = SigningHistory::new(MdbxDatabase::new_volatile(Default::default()).unwrap());
+        let service = Arc::new(SignerService::new(signer, history, KEY.to_vec()));
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = SignerAddress::Tcp(listener.local_addr().unwrap().to_string());
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let _ = service.serve(stream.unwrap());
+            }
+        });
+
+        RemoteSigner::connect(address, KEY.to_vec(), RemoteSigner::DEFAULT_TIMEOUT)
+            .await
+            .unwrap()
+    }
+
+    fn micro_header(block_number: u32, extra_data: u8) -> MicroHeader {
+        MicroHeader {
+            network: NetworkId::UnitAlbatross,
+            version: Policy::VERSION,
+            block_number,
+            timestamp: 0,
+            parent_hash: Blake2bHash::default(),
+            seed: VrfSeed::default(),
+            extra_data: vec![extra_data],
+            state_root: Blake2bHash::default(),
+            body_root: Blake2sHash::default(),
+            diff_root: Blake2bHash::default(),
+            history_root: Blake2bHash::default(),
+        }
+    }
+
+    fn skip_block_info(block_number: u32, entropy: u8) -> SkipBlockInfo {
+        SkipBlockInfo {
+            block_number,
+            vrf_entropy: VrfEntropy([entropy; 32]),
+        }
+    }
+
+    fn vote(block_number: u32, proposal_hash: Option<Blake2sHash>) -> TendermintVote {
+        TendermintVote {
+            proposal_hash,
+            id: TendermintIdentifier {
+                network: NetworkId::UnitAlbatross,
+                block_number,
+                round_number: 0,
+                step: TendermintStep::PreVote,
+            },
+        }
+    }
+
+    fn is_refused<T>(result: Result<T, SignerError>) -> bool {
+        matches!(result, Err(SignerError::Refused(_)))
+    }
+
+    #[test(tokio::test)]
+    async fn it_refuses_conflicting_signatures() {
+        let signer = remote_signer().await;
+        assert_eq!(signer.signing_public_key(), signing_key().public);
+
+        signer.sign_micro_header(&micro_header(10, 1)).unwrap();
+        // Signing the same block again is fine.
+        signer.sign_micro_header(&micro_header(10, 1)).unwrap();
+        assert!(is_refused(signer.sign_micro_header(&micro_header(10, 2))));
+
+        signer.sign_skip_block(&skip_block_info(11, 1)).unwrap();
+        assert!(is_refused(signer.sign_skip_block(&skip_block_info(11, 2))));
+
+        let proposal = MacroHeader {
+            block_number: 32,
+            ..Default::default()
+        };
+        let other_proposal = MacroHeader {
+            timestamp: 1,
+            ..proposal.clone()
+        };
+        signer.sign_proposal(&proposal, 0, None).unwrap();
+        signer.sign_proposal(&other_proposal, 1, None).unwrap();
+        assert!(is_refused(signer.sign_proposal(&other_proposal, 0, None)));
+
+        let proposal_hash = proposal.hash::<Blake2sHash>();
+        signer
+            .sign_tendermint_vote(&vote(32, Some(proposal_hash)))
+            .unwrap();
+        assert!(is_refused(signer.sign_tendermint_vote(&vote(32, None))));
+    }
+
+    #[test(tokio::test)]
+    async fn it_refuses_requests_below_the_pruned_history() {
+        let signer = remote_signer().await;
+        signer.sign_micro_header(&micro_header(10, 1)).unwrap();
+        signer.sign_skip_block(&skip_block_info(11, 1)).unwrap();
+
+        // A request far ahead doesn't prune beyond what we signed, and everything at or below
+        // the pruned height is refused, such that the pruned records can't be signed again.
+        signer
+            .sign_micro_header(&micro_header(100 * Policy::blocks_per_batch(), 1))
+            .unwrap();
+        assert!(is_refused(signer.sign_micro_header(&micro_header(10, 2))));
+        assert!(is_refused(signer.sign_skip_block(&skip_block_info(11, 2))));
+        assert!(is_refused(signer.sign_micro_header(&micro_header(10, 1))));
+    }
+}
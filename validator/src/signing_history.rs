@@ -0,0 +1,356 @@
+use std::collections::BTreeMap;
+
+use nimiq_block::{MicroHeader, SkipBlockInfo};
+use nimiq_database::{
+    declare_table,
+    mdbx::MdbxDatabase,
+    traits::{Database, ReadCursor, ReadTransaction, WriteCursor, WriteTransaction},
+};
+use nimiq_database_value_derive::DbSerializable;
+use nimiq_hash::{Blake2bHash, Blake2sHash, Hash};
+use nimiq_primitives::{TendermintStep, TendermintVote};
+use nimiq_serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// The hashes of everything we signed at a given block height.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, DbSerializable)]
+pub struct SigningRecord {
+    /// The hash of the micro block we produced.
+    pub micro_block: Option<Blake2bHash>,
+    /// The hash of the skip block info we contributed to.
+    pub skip_block: Option<Blake2bHash>,
+    /// The hashes of the macro block proposals we signed, by round.
+    pub proposals: BTreeMap<u32, Blake2sHash>,
+    /// The proposal hashes of the Tendermint votes we signed, by round and step.
+    pub votes: BTreeMap<(u32, TendermintStep), Option<Blake2sHash>>,
+}
+
+impl SigningRecord {
+    /// Merges another record into this one. Fails if both records contain a different hash for
+    /// the same kind of signature.
+    fn merge(
+        &mut self,
+        other: &SigningRecord,
+        block_number: u32,
+    ) -> Result<(), SigningHistoryError> {
+        Self::merge_hash(&mut self.micro_block, &other.micro_block)
+            .map_err(|_| SigningHistoryError::ConflictingMicroBlock(block_number))?;
+        Self::merge_hash(&mut self.skip_block, &other.skip_block)
+            .map_err(|_| SigningHistoryError::ConflictingSkipBlock(block_number))?;
+        Self::merge_map(&mut self.proposals, &other.proposals)
+            .map_err(|round| SigningHistoryError::ConflictingProposal(block_number, round))?;
+        Self::merge_map(&mut self.votes, &other.votes).map_err(|(round, step)| {
+            SigningHistoryError::ConflictingVote(block_number, round, step)
+        })
+    }
+
+    fn merge_hash(own: &mut Option<Blake2bHash>, other: &Option<Blake2bHash>) -> Result<(), ()> {
+        match (own.as_ref(), other) {
+            (Some(own), Some(other)) if own != other => Err(()),
+            (None, Some(other)) => {
+                *own = Some(other.clone());
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Merges the entries of `other` into `own`. Fails with the first key that maps to different
+    /// values in both maps.
+    fn merge_map<K: Copy + Ord, V: Clone + PartialEq>(
+        own: &mut BTreeMap<K, V>,
+        other: &BTreeMap<K, V>,
+    ) -> Result<(), K> {
+        if let Some((key, _)) = other
+            .iter()
+            .find(|(key, value)| own.get(key).is_some_and(|own| own != *value))
+        {
+            return Err(*key);
+        }
+        own.extend(other.iter().map(|(key, value)| (*key, value.clone())));
+        Ok(())
+    }
+}
+
+declare_table!(SigningHistoryTable, "SigningHistory", u32 => SigningRecord);
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum SigningHistoryError {
+    #[error("A different micro block was already signed at block {0}")]
+    ConflictingMicroBlock(u32),
+    #[error("A different skip block was already signed at block {0}")]
+    ConflictingSkipBlock(u32),
+    #[error("A different proposal was already signed at block {0} in round {1}")]
+    ConflictingProposal(u32, u32),
+    #[error("A different vote was already signed at block {0} in round {1} ({2:?})")]
+    ConflictingVote(u32, u32, TendermintStep),
+}
+
+/// A snapshot of the signing history, used to migrate it to another host.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningHistoryExport {
+    pub records: Vec<(u32, SigningRecord)>,
+}
+
+/// Persistent record of the micro blocks and skip block contributions signed by this validator.
+/// The reference signer additionally records the macro block proposals and Tendermint votes.
+///
+/// Every signature is recorded before it leaves the validator, so that a restarted node (or a
+/// standby node using the same keys and an imported history) never signs a second, conflicting
+/// block at the same height.
+#[derive(Debug)]
+pub struct SigningHistory {
+    env: MdbxDatabase,
+}
+
+impl SigningHistory {
+    pub fn new(env: MdbxDatabase) -> Self {
+        env.create_regular_table(&SigningHistoryTable);
+
+        Self { env }
+    }
+
+    /// Returns the signatures recorded at the given block height.
+    pub fn get(&self, block_number: u32) -> Option<SigningRecord> {
+        self.env
+            .read_transaction()
+            .get(&SigningHistoryTable, &block_number)
+    }
+
+    /// Records that we are about to sign the given micro block header. Signing the same block
+    /// again is allowed, signing a different block at the same height is refused.
+    pub fn record_micro_block(&self, header: &MicroHeader) -> Result<(), SigningHistoryError> {
+        self.record(
+            header.block_number,
+            SigningRecord {
+                micro_block: Some(header.hash()),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Records that we are about to sign a contribution for the given skip block. Signing the same
+    /// skip block again is allowed, signing a different one at the same height is refused.
+    pub fn record_skip_block(&self, info: &SkipBlockInfo) -> Result<(), SigningHistoryError> {
+        self.record(
+            info.block_number,
+            SigningRecord {
+                skip_block: Some(info.hash()),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Records that we are about to sign the macro block proposal with the given hash in the
+    /// given round. Signing a different proposal in the same round is refused.
+    pub fn record_proposal(
+        &self,
+        block_number: u32,
+        round: u32,
+        proposal_hash: Blake2sHash,
+    ) -> Result<(), SigningHistoryError> {
+        self.record(
+            block_number,
+            SigningRecord {
+                proposals: BTreeMap::from([(round, proposal_hash)]),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Records that we are about to sign the given Tendermint vote. Voting for something else in
+    /// the same round and step is refused.
+    pub fn record_vote(&self, vote: &TendermintVote) -> Result<(), SigningHistoryError> {
+        self.record(
+            vote.id.block_number,
+            SigningRecord {
+                votes: BTreeMap::from([(
+                    (vote.id.round_number, vote.id.step),
+                    vote.proposal_hash.clone(),
+                )]),
+                ..Default::default()
+            },
+        )
+    }
+
+    fn record(&self, block_number: u32, record: SigningRecord) -> Result<(), SigningHistoryError> {
+        let mut txn = self.env.write_transaction();
+        let mut stored = txn
+            .get(&SigningHistoryTable, &block_number)
+            .unwrap_or_default();
+        stored.merge(&record, block_number)?;
+        txn.put(&SigningHistoryTable, &block_number, &stored);
+        txn.commit();
+        Ok(())
+    }
+
+    /// Returns the lowest and the highest block height with a record, if any.
+    pub fn height_range(&self) -> Option<(u32, u32)> {
+        let txn = self.env.read_transaction();
+        let mut cursor = ReadTransaction::cursor(&txn, &SigningHistoryTable);
+        let (first, _) = cursor.first()?;
+        let (last, _) = cursor.last()?;
+        Some((first, last))
+    }
+
+    /// Removes all records below the given block height. This is safe once the corresponding
+    /// blocks are finalized, since we will never be asked to sign at those heights again.
+    pub fn prune(&self, block_number: u32) {
+        let txn = self.env.write_transaction();
+        let mut cursor = WriteTransaction::cursor(&txn, &SigningHistoryTable);
+        let mut pos = cursor.first();
+
+        while let Some((height, _)) = pos {
+            if height >= block_number {
+                break;
+            }
+            cursor.remove();
+            pos = cursor.next();
+        }
+        drop(cursor);
+        txn.commit();
+    }
+
+    /// Exports all records of the signing history.
+    pub fn export(&self) -> SigningHistoryExport {
+        let txn = self.env.read_transaction();
+        let cursor = ReadTransaction::cursor(&txn, &SigningHistoryTable);
+
+        SigningHistoryExport {
+            records: cursor.into_iter_start().collect(),
+        }
+    }
+
+    /// Imports the records of an exported signing history, merging them with the existing ones.
+    /// Nothing is imported if any record conflicts with what we already signed.
+    ///
+    /// Returns the number of imported records.
+    pub fn import(&self, export: &SigningHistoryExport) -> Result<usize, SigningHistoryError> {
+        let mut txn = self.env.write_transaction();
+        for (block_number, record) in &export.records {
+            let mut stored = txn
+                .get(&SigningHistoryTable, block_number)
+                .unwrap_or_default();
+            stored.merge(record, *block_number)?;
+            txn.put(&SigningHistoryTable, block_number, &stored);
+        }
+        txn.commit();
+        Ok(export.records.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimiq_database::mdbx::MdbxDatabase;
+    use nimiq_primitives::{networks::NetworkId, TendermintIdentifier};
+    use nimiq_test_log::test;
+    use nimiq_vrf::VrfEntropy;
+
+    use super::*;
+
+    fn skip_block_info(block_number: u32, entropy: u8) -> SkipBlockInfo {
+        SkipBlockInfo {
+            block_number,
+            vrf_entropy: VrfEntropy([entropy; 32]),
+        }
+    }
+
+    #[test]
+    fn it_refuses_conflicting_signatures() {
+        let history = SigningHistory::new(MdbxDatabase::new_volatile(Default::default()).unwrap());
+
+        history.record_skip_block(&skip_block_info(5, 1)).unwrap();
+        // Signing the same skip block again is fine.
+        history.record_skip_block(&skip_block_info(5, 1)).unwrap();
+        assert_eq!(
+            history.record_skip_block(&skip_block_info(5, 2)),
+            Err(SigningHistoryError::ConflictingSkipBlock(5))
+        );
+        history.record_skip_block(&skip_block_info(6, 2)).unwrap();
+
+        assert_eq!(history.height_range(), Some((5, 6)));
+
+        history.prune(6);
+        assert_eq!(history.get(5), None);
+        assert!(history.get(6).is_some());
+        assert_eq!(history.height_range(), Some((6, 6)));
+    }
+
+    #[test]
+    fn it_refuses_conflicting_tendermint_signatures() {
+        let history = SigningHistory::new(MdbxDatabase::new_volatile(Default::default()).unwrap());
+        let vote = |round_number, step, proposal_hash| TendermintVote {
+            proposal_hash,
+            id: TendermintIdentifier {
+                network: NetworkId::UnitAlbatross,
+                block_number: 32,
+                round_number,
+                step,
+            },
+        };
+        let hash = |byte| Blake2sHash::from([byte; 32]);
+
+        history.record_proposal(32, 0, hash(1)).unwrap();
+        history.record_proposal(32, 0, hash(1)).unwrap();
+        history.record_proposal(32, 1, hash(2)).unwrap();
+        assert_eq!(
+            history.record_proposal(32, 0, hash(2)),
+            Err(SigningHistoryError::ConflictingProposal(32, 0))
+        );
+
+        history
+            .record_vote(&vote(0, TendermintStep::PreVote, Some(hash(1))))
+            .unwrap();
+        history
+            .record_vote(&vote(0, TendermintStep::PreCommit, None))
+            .unwrap();
+        history
+            .record_vote(&vote(1, TendermintStep::PreVote, None))
+            .unwrap();
+        assert_eq!(
+            history.record_vote(&vote(0, TendermintStep::PreVote, None)),
+            Err(SigningHistoryError::ConflictingVote(
+                32,
+                0,
+                TendermintStep::PreVote
+            ))
+        );
+        assert_eq!(
+            history.record_vote(&vote(0, TendermintStep::PreCommit, Some(hash(1)))),
+            Err(SigningHistoryError::ConflictingVote(
+                32,
+                0,
+                TendermintStep::PreCommit
+            ))
+        );
+    }
+
+    #[test]
+    fn it_exports_and_imports_the_history() {
+        let history = SigningHistory::new(MdbxDatabase::new_volatile(Default::default()).unwrap());
+        history.record_skip_block(&skip_block_info(5, 1)).unwrap();
+        history.record_skip_block(&skip_block_info(6, 1)).unwrap();
+        let export = history.export();
+        assert_eq!(export.records.len(), 2);
+
+        let other = SigningHistory::new(MdbxDatabase::new_volatile(Default::default()).unwrap());
+        other.record_skip_block(&skip_block_info(7, 1)).unwrap();
+        assert_eq!(other.import(&export), Ok(2));
+        assert_eq!(
+            other.record_skip_block(&skip_block_info(6, 2)),
+            Err(SigningHistoryError::ConflictingSkipBlock(6))
+        );
+
+        // A conflicting import is rejected as a whole.
+        let conflicting =
+            SigningHistory::new(MdbxDatabase::new_volatile(Default::default()).unwrap());
+        conflicting
+            .record_skip_block(&skip_block_info(5, 2))
+            .unwrap();
+        assert_eq!(
+            conflicting.import(&export),
+            Err(SigningHistoryError::ConflictingSkipBlock(5))
+        );
+        assert_eq!(conflicting.get(6), None);
+    }
+}
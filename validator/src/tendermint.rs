@@ -2,16 +2,18 @@ use std::sync::Arc;
 
 use futures::{
     future::{self, BoxFuture, FutureExt},
-    stream::{BoxStream, StreamExt},
+    stream::{self, BoxStream, StreamExt},
 };
-use nimiq_block::{Block, MacroBlock, TendermintProof};
+use nimiq_block::{Block, EquivocationProof, MacroBlock, TendermintProof};
 use nimiq_blockchain::{BlockProducer, Blockchain};
 use nimiq_blockchain_interface::AbstractBlockchain;
 use nimiq_collections::BitSet;
 use nimiq_handel::{
     aggregation::Aggregation,
+    config::Config,
     identity::IdentityRegistry,
     protocol::Protocol as _,
+    telemetry::Telemetry,
     verifier::{VerificationResult, Verifier},
 };
 use nimiq_hash::{Blake2sHash, Hash};
@@ -22,14 +24,15 @@ use nimiq_primitives::{
 };
 use nimiq_serde::Serialize;
 use nimiq_tendermint::{
-    Proposal, ProposalError, ProposalMessage, Protocol, ProtocolError, SignedProposalMessage, Step,
-    TaggedAggregationMessage,
+    Event, Proposal, ProposalError, ProposalMessage, Protocol, ProtocolError,
+    SignedProposalMessage, Step, TaggedAggregationMessage,
 };
 use nimiq_utils::spawn;
 use nimiq_validator_network::{
     single_response_requester::SingleResponseRequester, PubsubId, ValidatorNetwork,
 };
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use tokio::sync::mpsc;
 
 use crate::{
     aggregation::{
@@ -41,7 +44,10 @@ use crate::{
             update_message::TendermintUpdate,
         },
     },
+    equivocation::DoubleVoteDetector,
     r#macro::ProposalTopic,
+    signer::ValidatorSigner,
+    tendermint_journal::JournalRecorder,
 };
 
 // A note for the signing of the proposal:
@@ -110,14 +116,22 @@ pub struct TendermintProtocol<TValidatorNetwork: ValidatorNetwork> {
     pub network_id: NetworkId,
     // The block number of the macro block to produce.
     pub block_height: u32,
-    // Information relative to our validator that is necessary to produce blocks.
-    pub block_producer: BlockProducer,
+    // Creates the signatures of our validator.
+    pub signer: Arc<dyn ValidatorSigner>,
     // The validators for the current epoch.
     pub current_validators: Validators,
     // The main blockchain struct. Contains all of this validator information about the current chain.
     pub blockchain: Arc<RwLock<Blockchain>>,
     // Validator registry on the heap for easy cloning into handel protocol.
     validator_registry: Arc<ValidatorRegistry>,
+    // The Handel configuration of the aggregations.
+    handel_config: Config,
+    // The telemetry shared by the aggregations of all Tendermint instances.
+    handel_telemetry: Arc<Telemetry>,
+    // Records the events of the Tendermint instance, if enabled.
+    journal: Option<JournalRecorder>,
+    // Detects double votes in the aggregations of the Tendermint instance.
+    double_votes: Arc<Mutex<DoubleVoteDetector>>,
 }
 
 impl<TValidatorNetwork: ValidatorNetwork> Clone for TendermintProtocol<TValidatorNetwork> {
@@ -127,10 +141,14 @@ impl<TValidatorNetwork: ValidatorNetwork> Clone for TendermintProtocol<TValidato
             validator_slot_band: self.validator_slot_band,
             network_id: self.network_id,
             block_height: self.block_height,
-            block_producer: self.block_producer.clone(),
+            signer: Arc::clone(&self.signer),
             current_validators: self.current_validators.clone(),
             blockchain: Arc::clone(&self.blockchain),
             validator_registry: Arc::clone(&self.validator_registry),
+            handel_config: self.handel_config.clone(),
+            handel_telemetry: Arc::clone(&self.handel_telemetry),
+            journal: self.journal.clone(),
+            double_votes: Arc::clone(&self.double_votes),
         }
     }
 }
@@ -142,14 +160,25 @@ where
     pub fn new(
         blockchain: Arc<RwLock<Blockchain>>,
         network: Arc<TValidatorNetwork>,
-        block_producer: BlockProducer,
+        signer: Arc<dyn ValidatorSigner>,
         current_validators: Validators,
         validator_slot_band: u16,
         network_id: NetworkId,
         block_height: u32,
+        handel_config: Config,
+        handel_telemetry: Arc<Telemetry>,
+        journal: Option<JournalRecorder>,
+        double_vote_tx: mpsc::UnboundedSender<EquivocationProof>,
     ) -> Self {
+        let double_votes = DoubleVoteDetector::new(
+            network_id,
+            block_height,
+            current_validators.clone(),
+            double_vote_tx,
+        );
+
         Self {
-            block_producer,
+            signer,
             blockchain,
             network_id,
             block_height,
@@ -157,6 +186,10 @@ where
             validator_registry: Arc::new(ValidatorRegistry::new(current_validators.clone())),
             current_validators,
             network,
+            handel_config,
+            handel_telemetry,
+            journal,
+            double_votes: Arc::new(Mutex::new(double_votes)),
         }
     }
 }
@@ -210,18 +243,37 @@ where
         &self,
         round: u32,
     ) -> Result<(ProposalMessage<Self::Proposal>, Self::Inherent), ProtocolError> {
-        let blockchain = self.blockchain.read();
-
         // Abort if the blockchain state has changed.
-        if blockchain.block_number() != self.block_height - 1 {
+        let prev_seed = {
+            let blockchain = self.blockchain.read();
+            if blockchain.block_number() != self.block_height - 1 {
+                return Err(ProtocolError::Abort);
+            }
+            blockchain.head().seed().clone()
+        };
+
+        // The blockchain lock is not held while the signer computes the VRF seed.
+        let seed = self.signer.vrf_seed(&prev_seed).map_err(|error| {
+            log::error!(%error, round, "Failed to compute the VRF seed of our proposal");
+            ProtocolError::Abort
+        })?;
+
+        let blockchain = self.blockchain.read();
+        if blockchain.block_number() != self.block_height - 1
+            || *blockchain.head().seed() != prev_seed
+        {
             return Err(ProtocolError::Abort);
         }
 
         // Create the proposal.
         let time = blockchain.time.now();
-        let block = self
-            .block_producer
-            .next_macro_block_proposal(&blockchain, time, round, vec![]);
+        let block = BlockProducer::next_macro_block_proposal_with_seed(
+            &blockchain,
+            time,
+            round,
+            vec![],
+            seed,
+        );
 
         // Always `Some(…)` because the above function always sets it to `Some(…)`.
         let body = block.body.expect("produced blocks always have a body");
@@ -353,16 +405,20 @@ where
         &self,
         proposal_message: &ProposalMessage<Self::Proposal>,
     ) -> Self::ProposalSignature {
-        let data = SignedProposal::hash(
-            &proposal_message.proposal.0,
-            proposal_message.round,
-            proposal_message.valid_round,
-        )
-        .serialize_to_vec();
-        (
-            self.block_producer.signing_key.sign(&data),
-            self.validator_slot_band,
-        )
+        let signature = self
+            .signer
+            .sign_proposal(
+                &proposal_message.proposal.0,
+                proposal_message.round,
+                proposal_message.valid_round,
+            )
+            .unwrap_or_else(|error| {
+                // The signature can't be omitted here. An invalid one makes the other validators
+                // reject our proposal, such that the round times out.
+                log::error!(%error, round = proposal_message.round, "Failed to sign our proposal");
+                SchnorrSignature::default()
+            });
+        (signature, self.validator_slot_band)
     }
 
     fn create_aggregation(
@@ -394,22 +450,32 @@ where
             id: id.clone(),
         };
 
+        let signature = match self.signer.sign_tendermint_vote(&tendermint_vote) {
+            Ok(signature) => signature,
+            Err(error) => {
+                // Without our own contribution we can't take part in this aggregation.
+                log::error!(%error, round, ?step, "Failed to sign our vote");
+                return stream::empty().boxed();
+            }
+        };
+
         let own_contribution = TendermintContribution::from_vote(
             tendermint_vote,
-            &self.block_producer.voting_key.secret_key,
+            signature,
             self.validator_registry.get_slots(self.validator_slot_band),
         );
 
         let protocol = TendermintAggregationProtocol::new(
             Arc::clone(&self.validator_registry),
             self.validator_slot_band as usize,
-            1, // to be removed
+            Policy::TWO_F_PLUS_ONE as usize,
             id,
         );
 
         Aggregation::new(
             protocol,
-            nimiq_handel::config::Config::default(),
+            self.handel_config.clone(),
+            Arc::clone(&self.handel_telemetry),
             own_contribution,
             update_stream.map(|item| item.0).boxed(),
             network,
@@ -439,15 +505,20 @@ where
         let protocol = TendermintAggregationProtocol::new(
             Arc::clone(&self.validator_registry),
             self.validator_slot_band as usize,
-            1,
+            Policy::TWO_F_PLUS_ONE as usize,
             id,
         );
 
+        let double_votes = Arc::clone(&self.double_votes);
+
         async move {
             if matches!(
                 protocol.verifier().verify(&message.0.aggregate).await,
                 VerificationResult::Ok
             ) {
+                double_votes
+                    .lock()
+                    .observe(round, step, &message.0.aggregate);
                 Ok(())
             } else {
                 Err(())
@@ -488,4 +559,11 @@ where
             }
         }
     }
+
+    fn on_event(&self, event: Event<Self::ProposalHash>) {
+        log::debug!(block_number = self.block_height, ?event, "Tendermint event");
+        if let Some(journal) = &self.journal {
+            journal.record(self.block_height, event);
+        }
+    }
 }
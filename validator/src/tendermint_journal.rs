@@ -0,0 +1,280 @@
+//! A local journal of the events emitted by Tendermint while producing macro blocks. It shows why
+//! a macro block needed several rounds, and the journal of a height can be replayed with
+//! [`nimiq_tendermint::replay::replay`] using [`JournalParameters`].
+//!
+//! Events are recorded through a [`JournalRecorder`], which writes them to the database in batches
+//! on a blocking thread, such that recording doesn't slow down the Tendermint instance.
+
+use std::{mem, sync::Arc, time::SystemTime};
+
+use nimiq_database::{
+    declare_table,
+    mdbx::MdbxDatabase,
+    traits::{
+        Database, DupReadCursor, DupWriteCursor, ReadCursor, ReadTransaction, WriteTransaction,
+    },
+    utils::IndexedValue,
+};
+use nimiq_database_value_derive::DbSerializable;
+use nimiq_hash::Blake2sHash;
+use nimiq_primitives::policy::Policy;
+use nimiq_serde::{Deserialize, Serialize};
+use nimiq_tendermint::{replay::ReplayParameters, Event};
+use nimiq_utils::{spawn, time::systemtime_to_timestamp};
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::spawn_blocking,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, DbSerializable)]
+pub struct JournalEntry {
+    /// The Unix time in milliseconds at which the event was emitted.
+    pub timestamp: u64,
+    pub event: Event<Blake2sHash>,
+}
+
+// `u32` (block height) -> `IndexedValue` (`event index || JournalEntry`)
+declare_table!(TendermintJournalTable, "TendermintJournalEvents", u32 => u32 => JournalEntry);
+
+/// The parameters of the Tendermint protocol the journal is recorded with.
+pub struct JournalParameters;
+
+impl ReplayParameters for JournalParameters {
+    type ProposalHash = Blake2sHash;
+
+    const TIMEOUT_DELTA: u64 = Policy::TENDERMINT_TIMEOUT_DELTA;
+    const TIMEOUT_INIT: u64 = Policy::TENDERMINT_TIMEOUT_INIT;
+    const TWO_F_PLUS_ONE: usize = Policy::TWO_F_PLUS_ONE as usize;
+    const F_PLUS_ONE: usize = Policy::F_PLUS_ONE as usize;
+}
+
+#[derive(Debug)]
+pub struct TendermintJournal {
+    env: MdbxDatabase,
+}
+
+impl TendermintJournal {
+    /// The number of batches for which the journals of their macro blocks are kept.
+    pub const RETAINED_BATCHES: u32 = 720;
+
+    pub fn new(env: MdbxDatabase) -> Self {
+        env.create_dup_table(&TendermintJournalTable);
+
+        Self { env }
+    }
+
+    /// Returns the events recorded at the given block height.
+    pub fn get(&self, block_number: u32) -> Vec<JournalEntry> {
+        let txn = self.env.read_transaction();
+        txn.dup_cursor(&TendermintJournalTable)
+            .into_iter_dup_of(&block_number)
+            .map(|(_, entry)| entry.value)
+            .collect()
+    }
+
+    /// Returns the highest block height with recorded events.
+    pub fn last_block_number(&self) -> Option<u32> {
+        let txn = self.env.read_transaction();
+        let mut cursor = txn.dup_cursor(&TendermintJournalTable);
+        cursor.last().map(|(block_number, _)| block_number)
+    }
+
+    /// Applies the given writes in order within a single transaction. Each event is stored under
+    /// its own index, so recording doesn't rewrite the events recorded before.
+    fn write(&self, writes: Vec<JournalWrite>) {
+        let mut txn = self.env.write_transaction();
+        let mut flushed = vec![];
+
+        for write in writes {
+            match write {
+                JournalWrite::Record(block_number, entry) => {
+                    let mut cursor = WriteTransaction::dup_cursor(&txn, &TendermintJournalTable);
+                    let index = cursor
+                        .set_key(&block_number)
+                        .and_then(|_| cursor.last_duplicate())
+                        .map_or(0, |last| last.index + 1);
+                    cursor.append_dup(&block_number, &IndexedValue::new(index, entry));
+                }
+                JournalWrite::Clear(block_number) => {
+                    txn.remove(&TendermintJournalTable, &block_number);
+                }
+                JournalWrite::Prune(block_number) => {
+                    let first_retained = block_number
+                        .saturating_sub(Self::RETAINED_BATCHES * Policy::blocks_per_batch());
+
+                    let mut cursor = WriteTransaction::dup_cursor(&txn, &TendermintJournalTable);
+                    let mut pos = cursor.first();
+                    while let Some((height, _)) = pos {
+                        if height >= first_retained {
+                            break;
+                        }
+                        cursor.remove_all_dup();
+                        pos = cursor.first();
+                    }
+                }
+                JournalWrite::Flush(sender) => flushed.push(sender),
+            }
+        }
+        txn.commit();
+
+        for sender in flushed {
+            let _ = sender.send(());
+        }
+    }
+}
+
+/// A pending write to the [`TendermintJournal`].
+#[derive(Debug)]
+enum JournalWrite {
+    Record(u32, JournalEntry),
+    Clear(u32),
+    Prune(u32),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Records events to a [`TendermintJournal`] without blocking the caller.
+///
+/// The writes are queued and a background task stores all writes queued in the meantime in a
+/// single transaction, in the order they were queued.
+#[derive(Clone, Debug)]
+pub struct JournalRecorder {
+    sender: mpsc::UnboundedSender<JournalWrite>,
+}
+
+impl JournalRecorder {
+    /// The maximum number of writes stored in a single transaction.
+    const MAX_BATCH_SIZE: usize = 256;
+
+    /// Creates a recorder for the given journal and spawns its writer task, which ends once all
+    /// clones of the recorder were dropped.
+    pub fn new(journal: TendermintJournal) -> Self {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let journal = Arc::new(journal);
+
+        spawn(async move {
+            let mut writes = Vec::with_capacity(Self::MAX_BATCH_SIZE);
+            while receiver.recv_many(&mut writes, Self::MAX_BATCH_SIZE).await > 0 {
+                let journal = Arc::clone(&journal);
+                let writes = mem::take(&mut writes);
+                if let Err(error) = spawn_blocking(move || journal.write(writes)).await {
+                    log::error!(%error, "Failed to write the Tendermint journal");
+                }
+            }
+        });
+
+        Self { sender }
+    }
+
+    fn queue(&self, write: JournalWrite) {
+        // The writer task only ends once all senders were dropped.
+        let _ = self.sender.send(write);
+    }
+
+    /// Appends an event to the journal of the given block height.
+    pub fn record(&self, block_number: u32, event: Event<Blake2sHash>) {
+        let entry = JournalEntry {
+            timestamp: systemtime_to_timestamp(SystemTime::now()),
+            event,
+        };
+        self.queue(JournalWrite::Record(block_number, entry));
+    }
+
+    /// Removes the journal of the given block height, such that a Tendermint instance starting
+    /// from scratch records a journal that can be replayed.
+    pub fn clear(&self, block_number: u32) {
+        self.queue(JournalWrite::Clear(block_number));
+    }
+
+    /// Removes the journals of the macro blocks more than [`TendermintJournal::RETAINED_BATCHES`]
+    /// batches before the given block height.
+    pub fn prune(&self, block_number: u32) {
+        self.queue(JournalWrite::Prune(block_number));
+    }
+
+    /// Waits until all writes queued before were stored.
+    pub async fn flush(&self) {
+        let (sender, receiver) = oneshot::channel();
+        self.queue(JournalWrite::Flush(sender));
+        let _ = receiver.await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimiq_hash::{Blake2sHasher, Hasher};
+    use nimiq_tendermint::Step;
+    use nimiq_test_log::test;
+
+    use super::*;
+
+    #[test(tokio::test)]
+    async fn it_records_and_prunes_journals() {
+        let env = MdbxDatabase::new_volatile(Default::default()).unwrap();
+        let journal = TendermintJournal::new(env.clone());
+        let recorder = JournalRecorder::new(TendermintJournal::new(env));
+        let proposal_hash = Blake2sHasher::new().digest(b"proposal");
+        let first_height = Policy::blocks_per_batch();
+        let last_height =
+            first_height + TendermintJournal::RETAINED_BATCHES * Policy::blocks_per_batch();
+
+        recorder.record(
+            first_height,
+            Event::Timeout {
+                round: 0,
+                step: Step::Propose,
+            },
+        );
+        recorder.record(
+            last_height,
+            Event::Proposed {
+                round: 0,
+                valid_round: None,
+                proposal_hash: proposal_hash.clone(),
+            },
+        );
+        recorder.record(
+            last_height,
+            Event::Votes {
+                round: 0,
+                step: Step::Prevote,
+                votes: vec![(Some(proposal_hash), Policy::SLOTS as usize)],
+            },
+        );
+
+        // Nothing is written before the writer task runs.
+        assert_eq!(journal.last_block_number(), None);
+        recorder.flush().await;
+        assert_eq!(journal.last_block_number(), Some(last_height));
+        let events: Vec<_> = journal
+            .get(last_height)
+            .into_iter()
+            .map(|entry| entry.event)
+            .collect();
+        assert!(matches!(
+            events[..],
+            [Event::Proposed { .. }, Event::Votes { .. }]
+        ));
+
+        recorder.prune(last_height);
+        recorder.flush().await;
+        assert_eq!(journal.get(first_height).len(), 1);
+        recorder.prune(last_height + Policy::blocks_per_batch());
+        recorder.flush().await;
+        assert!(journal.get(first_height).is_empty());
+        assert_eq!(journal.get(last_height).len(), 2);
+
+        recorder.clear(last_height);
+        recorder.flush().await;
+        assert_eq!(journal.last_block_number(), None);
+
+        recorder.record(
+            last_height,
+            Event::Timeout {
+                round: 0,
+                step: Step::Propose,
+            },
+        );
+        recorder.flush().await;
+        assert_eq!(journal.get(last_height).len(), 1);
+    }
+}
@@ -0,0 +1,323 @@
+//! Policies with which the validator selects the transactions of the micro blocks it produces.
+//!
+//! By default, control transactions are included first and the rest of the block is filled with
+//! regular transactions in the order of the mempool. The policies below refine the selection of
+//! the regular transactions and how the block is split between both kinds.
+
+use std::collections::HashSet;
+
+use nimiq_blockchain::Blockchain;
+use nimiq_keys::Address;
+use nimiq_mempool::{mempool::Mempool, mempool_transactions::BlockInclusion};
+use nimiq_transaction::Transaction;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TransactionSelection {
+    /// Regular transactions paying less fee per byte are not included.
+    pub min_fee_per_byte: f64,
+    /// The number of bytes of each block reserved for control transactions. Regular transactions
+    /// are included after the control transactions fitting in this space, and control transactions
+    /// only fill the space left after that. If `None`, control transactions may use the whole block.
+    pub reserved_control_bytes: Option<usize>,
+    /// Regular transactions sent from or to these accounts are included first, regardless of
+    /// deprioritization. Only transactions sent from these accounts skip the fee floor, as anyone
+    /// can send transactions to them.
+    pub own_accounts: HashSet<Address>,
+    /// Regular transactions sent from these accounts are not included.
+    pub excluded_senders: HashSet<Address>,
+    /// Regular transactions sent to these accounts are not included.
+    pub excluded_recipients: HashSet<Address>,
+    /// Regular transactions sent from these accounts are included after all others.
+    pub deprioritized_senders: HashSet<Address>,
+    /// Regular transactions sent to these accounts are included after all others.
+    pub deprioritized_recipients: HashSet<Address>,
+}
+
+impl TransactionSelection {
+    /// Returns how the given regular transaction is included in a block.
+    pub fn inclusion(&self, transaction: &Transaction) -> BlockInclusion {
+        if self.excluded_senders.contains(&transaction.sender)
+            || self.excluded_recipients.contains(&transaction.recipient)
+        {
+            BlockInclusion::Excluded
+        } else if self.own_accounts.contains(&transaction.sender) {
+            BlockInclusion::Prioritized
+        } else if transaction.fee_per_byte() < self.min_fee_per_byte {
+            BlockInclusion::Excluded
+        } else if self.own_accounts.contains(&transaction.recipient) {
+            BlockInclusion::Prioritized
+        } else if self.deprioritized_senders.contains(&transaction.sender)
+            || self
+                .deprioritized_recipients
+                .contains(&transaction.recipient)
+        {
+            BlockInclusion::Deprioritized
+        } else {
+            BlockInclusion::Regular
+        }
+    }
+
+    /// Returns whether any policy refines the selection of the regular transactions.
+    fn has_regular_policies(&self) -> bool {
+        self.min_fee_per_byte > 0.0
+            || !self.own_accounts.is_empty()
+            || !self.excluded_senders.is_empty()
+            || !self.excluded_recipients.is_empty()
+            || !self.deprioritized_senders.is_empty()
+            || !self.deprioritized_recipients.is_empty()
+    }
+
+    /// Takes the transactions of the next block from the mempool, up to `available_bytes`.
+    /// Control transactions come first.
+    pub fn select_transactions(
+        &self,
+        mempool: &Mempool,
+        blockchain: &Blockchain,
+        available_bytes: usize,
+    ) -> Vec<Transaction> {
+        let control_bytes = self
+            .reserved_control_bytes
+            .map_or(available_bytes, |bytes| bytes.min(available_bytes));
+        let (mut transactions, control_size) =
+            mempool.get_control_transactions_for_block_locked(blockchain, control_bytes);
+        let mut available_bytes = available_bytes - control_size;
+
+        // Without policies for the regular transactions, they are taken straight from the fee
+        // ordered heap of the mempool.
+        let (mut regular_transactions, regular_size) = if self.has_regular_policies() {
+            mempool.get_transactions_for_block_by_locked(
+                blockchain,
+                available_bytes,
+                |transaction| self.inclusion(transaction),
+            )
+        } else {
+            mempool.get_transactions_for_block_locked(blockchain, available_bytes)
+        };
+        available_bytes -= regular_size;
+
+        if self.reserved_control_bytes.is_some() {
+            let (mut control_transactions, _) =
+                mempool.get_control_transactions_for_block_locked(blockchain, available_bytes);
+            transactions.append(&mut control_transactions);
+        }
+
+        transactions.append(&mut regular_transactions);
+        transactions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{str::FromStr, sync::Arc};
+
+    use nimiq_block::MicroBlock;
+    use nimiq_keys::{KeyPair, PrivateKey};
+    use nimiq_mempool::config::MempoolConfig;
+    use nimiq_primitives::{coin::Coin, networks::NetworkId, policy::Policy};
+    use nimiq_serde::Serialize;
+    use nimiq_test_log::test;
+    use nimiq_test_utils::{block_production::TemporaryBlockProducer, blockchain::REWARD_KEY};
+    use nimiq_transaction_builder::TransactionBuilder;
+
+    use super::*;
+
+    fn basic_transaction(key_pair: &KeyPair, recipient: Address, fee: u64) -> Transaction {
+        TransactionBuilder::new_basic(
+            key_pair,
+            recipient,
+            Coin::from_u64_unchecked(1),
+            Coin::from_u64_unchecked(fee),
+            1 + Policy::genesis_block_number(),
+            NetworkId::UnitAlbatross,
+        )
+        .unwrap()
+    }
+
+    #[test(tokio::test)]
+    async fn it_selects_transactions_by_policy() {
+        let producer = TemporaryBlockProducer::new();
+        let mempool = Mempool::new(Arc::clone(&producer.blockchain), MempoolConfig::default());
+        let key_pair = KeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap());
+        let recipients: Vec<_> = (1..=5u8).map(|i| Address::from([i; 20])).collect();
+
+        // The fee increases with the recipient, so the mempool orders the transactions backwards.
+        let mut transactions = vec![];
+        for (fee, recipient) in (1..=5).zip(&recipients) {
+            let transaction = TransactionBuilder::new_basic(
+                &key_pair,
+                recipient.clone(),
+                Coin::from_u64_unchecked(1),
+                Coin::from_u64_unchecked(fee * 1000),
+                1 + Policy::genesis_block_number(),
+                NetworkId::UnitAlbatross,
+            )
+            .unwrap();
+            mempool
+                .add_transaction(transaction.clone(), None)
+                .await
+                .unwrap();
+            transactions.push(transaction);
+        }
+
+        let selection = TransactionSelection {
+            min_fee_per_byte: transactions[1].fee_per_byte(),
+            own_accounts: HashSet::from([recipients[1].clone()]),
+            excluded_recipients: HashSet::from([recipients[2].clone()]),
+            deprioritized_recipients: HashSet::from([recipients[4].clone()]),
+            ..Default::default()
+        };
+        let selected = selection.select_transactions(
+            &mempool,
+            &producer.blockchain.read(),
+            MicroBlock::get_available_bytes(0),
+        );
+
+        // The own transaction comes first despite the lower fee, the excluded ones are left out
+        // and the deprioritized one comes last despite the highest fee.
+        assert_eq!(
+            selected,
+            vec![
+                transactions[1].clone(),
+                transactions[3].clone(),
+                transactions[4].clone(),
+            ]
+        );
+        assert_eq!(mempool.num_transactions(), 2);
+
+        producer.next_block_with_txs(vec![], false, selected);
+    }
+
+    #[test]
+    fn it_keeps_the_fee_floor_for_transactions_to_own_accounts() {
+        let own_key_pair = KeyPair::from(PrivateKey::from([1; 32]));
+        let other_key_pair = KeyPair::from(PrivateKey::from([2; 32]));
+        let own_address = Address::from(&own_key_pair);
+        let other_address = Address::from(&other_key_pair);
+
+        let selection = TransactionSelection {
+            min_fee_per_byte: 1.0,
+            own_accounts: HashSet::from([own_address.clone()]),
+            ..Default::default()
+        };
+
+        // Own transactions skip the fee floor, transactions to own accounts don't.
+        assert_eq!(
+            selection.inclusion(&basic_transaction(&own_key_pair, other_address, 0)),
+            BlockInclusion::Prioritized
+        );
+        assert_eq!(
+            selection.inclusion(&basic_transaction(&other_key_pair, own_address.clone(), 0)),
+            BlockInclusion::Excluded
+        );
+        assert_eq!(
+            selection.inclusion(&basic_transaction(&other_key_pair, own_address, 1000)),
+            BlockInclusion::Prioritized
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn it_takes_the_best_transactions_without_policies() {
+        let producer = TemporaryBlockProducer::new();
+        let mempool = Mempool::new(Arc::clone(&producer.blockchain), MempoolConfig::default());
+        let key_pair = KeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap());
+
+        let mut transactions = vec![];
+        for fee in 1..=3u8 {
+            let transaction =
+                basic_transaction(&key_pair, Address::from([fee; 20]), fee as u64 * 1000);
+            mempool
+                .add_transaction(transaction.clone(), None)
+                .await
+                .unwrap();
+            transactions.push(transaction);
+        }
+        let transaction_size = transactions[0].serialized_size() + 1;
+
+        let selected = TransactionSelection::default().select_transactions(
+            &mempool,
+            &producer.blockchain.read(),
+            2 * transaction_size,
+        );
+
+        assert_eq!(
+            selected,
+            vec![transactions[2].clone(), transactions[1].clone()]
+        );
+        assert_eq!(mempool.num_transactions(), 1);
+    }
+
+    #[test(tokio::test)]
+    async fn it_reserves_bytes_for_control_transactions() {
+        let producer = TemporaryBlockProducer::new();
+        let mempool = Mempool::new(Arc::clone(&producer.blockchain), MempoolConfig::default());
+        let key_pair = KeyPair::from(PrivateKey::from_str(REWARD_KEY).unwrap());
+
+        // The first control transaction pays the higher fee.
+        let mut control_transactions = vec![];
+        for i in 1..=2u8 {
+            let transaction = TransactionBuilder::new_create_staker(
+                &key_pair,
+                &KeyPair::from(PrivateKey::from([i; 32])),
+                None,
+                Coin::from_u64_unchecked(Policy::MINIMUM_STAKE),
+                Coin::from_u64_unchecked((3 - i as u64) * 1000),
+                1 + Policy::genesis_block_number(),
+                NetworkId::UnitAlbatross,
+            )
+            .unwrap();
+            mempool
+                .add_transaction(transaction.clone(), None)
+                .await
+                .unwrap();
+            control_transactions.push(transaction);
+        }
+
+        let mut transactions = vec![];
+        for fee in 1..=3u8 {
+            let transaction =
+                basic_transaction(&key_pair, Address::from([fee; 20]), fee as u64 * 1000);
+            mempool
+                .add_transaction(transaction.clone(), None)
+                .await
+                .unwrap();
+            transactions.push(transaction);
+        }
+
+        let control_size = control_transactions[0].serialized_size() + 1;
+        let transaction_size = transactions[0].serialized_size() + 1;
+
+        // Only one control transaction fits in the reserved bytes, and the regular transactions
+        // take the rest of the block.
+        let selection = TransactionSelection {
+            reserved_control_bytes: Some(control_size),
+            ..Default::default()
+        };
+        let selected = selection.select_transactions(
+            &mempool,
+            &producer.blockchain.read(),
+            control_size + 3 * transaction_size,
+        );
+        assert_eq!(
+            selected,
+            vec![
+                control_transactions[0].clone(),
+                transactions[2].clone(),
+                transactions[1].clone(),
+                transactions[0].clone(),
+            ]
+        );
+        assert_eq!(mempool.num_transactions(), 1);
+
+        producer.next_block_with_txs(vec![], false, selected);
+
+        // Control transactions still fill the space left by the regular transactions.
+        let selection = TransactionSelection {
+            reserved_control_bytes: Some(0),
+            ..Default::default()
+        };
+        let selected =
+            selection.select_transactions(&mempool, &producer.blockchain.read(), control_size);
+        assert_eq!(selected, vec![control_transactions[1].clone()]);
+        assert_eq!(mempool.num_transactions(), 0);
+    }
+}
@@ -0,0 +1,245 @@
+//! An index of the duty statistics of all validators, so that delegators can compare the uptime
+//! of the validators they could stake with.
+//!
+//! The statistics of an epoch are accumulated batch by batch while the chain advances and stored
+//! once the epoch is finalized. Since the micro blocks of past epochs are pruned, the index is the
+//! only place where the micro block statistics of past epochs remain available.
+
+use std::{collections::BTreeMap, sync::Arc};
+
+use futures::StreamExt;
+use nimiq_blockchain::Blockchain;
+use nimiq_blockchain_interface::{AbstractBlockchain, BlockchainError, BlockchainEvent};
+use nimiq_database::{
+    declare_table,
+    mdbx::MdbxDatabase,
+    traits::{Database, ReadCursor, ReadTransaction, WriteTransaction},
+};
+use nimiq_database_value_derive::DbSerializable;
+use nimiq_keys::Address;
+use nimiq_primitives::policy::Policy;
+use nimiq_serde::{Deserialize, Serialize};
+use parking_lot::{Mutex, RwLock};
+use tokio::task::spawn_blocking;
+
+use crate::performance::{PerformanceAccumulator, ValidatorPerformance};
+
+/// The statistics of all validators of an epoch.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, DbSerializable)]
+pub struct EpochUptime {
+    pub validators: BTreeMap<Address, ValidatorPerformance>,
+}
+
+declare_table!(UptimeTable, "ValidatorUptime", u32 => EpochUptime);
+
+/// The maximum number of epochs returned by a single query.
+pub const MAX_EPOCHS_PER_QUERY: u32 = 100;
+
+#[derive(Clone)]
+pub struct UptimeIndex {
+    env: MdbxDatabase,
+    blockchain: Arc<RwLock<Blockchain>>,
+    /// The statistics of the epoch that is being indexed, up to its latest macro block.
+    current: Arc<Mutex<Option<PerformanceAccumulator>>>,
+}
+
+impl UptimeIndex {
+    pub fn new(env: MdbxDatabase, blockchain: Arc<RwLock<Blockchain>>) -> Self {
+        env.create_regular_table(&UptimeTable);
+
+        Self {
+            env,
+            blockchain,
+            current: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Returns the last epoch that was stored in the index.
+    pub fn last_indexed_epoch(&self) -> Option<u32> {
+        let txn = self.env.read_transaction();
+        let mut cursor = txn.cursor(&UptimeTable);
+        cursor.last().map(|(epoch_number, _)| epoch_number)
+    }
+
+    /// Returns the statistics of the epochs from `from_epoch` to `to_epoch` (inclusive) that are
+    /// in the index, optionally only those of the given validator. The statistics of the current
+    /// epoch cover the blocks up to its latest macro block.
+    ///
+    /// Epochs before the index was enabled are not included. At most
+    /// [`MAX_EPOCHS_PER_QUERY`] epochs are returned.
+    pub fn get_epochs(
+        &self,
+        from_epoch: u32,
+        to_epoch: u32,
+        validator_address: Option<&Address>,
+    ) -> Vec<(u32, EpochUptime)> {
+        let to_epoch = to_epoch.min(from_epoch.saturating_add(MAX_EPOCHS_PER_QUERY - 1));
+        let filter = |mut uptime: EpochUptime| {
+            if let Some(address) = validator_address {
+                uptime
+                    .validators
+                    .retain(|validator, _| validator == address);
+            }
+            uptime
+        };
+
+        let mut epochs = vec![];
+        let txn = self.env.read_transaction();
+        for epoch_number in from_epoch..=to_epoch {
+            if let Some(uptime) = txn.get(&UptimeTable, &epoch_number) {
+                epochs.push((epoch_number, filter(uptime)));
+            }
+        }
+
+        if let Some(current) = self.current.lock().as_ref() {
+            let epoch_number = current.epoch_number();
+            if (from_epoch..=to_epoch).contains(&epoch_number)
+                && !epochs.iter().any(|(epoch, _)| *epoch == epoch_number)
+            {
+                let uptime = EpochUptime {
+                    validators: current.performances(),
+                };
+                epochs.push((epoch_number, filter(uptime)));
+            }
+        }
+        epochs
+    }
+
+    /// Processes the finalized blocks that were not processed yet and stores the epochs that
+    /// were completed. The blockchain is only locked for one batch at a time.
+    fn update(&self) -> Result<(), BlockchainError> {
+        let macro_head = Policy::last_macro_block(self.blockchain.read().block_number());
+
+        loop {
+            // Work on a copy, such that queries still see the current epoch in the meantime.
+            let current = self.current.lock().clone();
+            let mut accumulator = match current {
+                Some(accumulator) => accumulator,
+                None => {
+                    // Start with the epoch following the last indexed one, or with the current
+                    // epoch if the index is empty.
+                    let epoch_number = match self.last_indexed_epoch() {
+                        Some(epoch_number) => epoch_number + 1,
+                        None => Policy::epoch_at(macro_head + 1),
+                    };
+                    let blockchain = self.blockchain.read();
+                    if Policy::first_block_of(epoch_number)
+                        .is_some_and(|block_number| block_number > blockchain.block_number())
+                    {
+                        return Ok(());
+                    }
+                    let txn = blockchain.read_transaction();
+                    PerformanceAccumulator::new(&blockchain, epoch_number, &txn)?
+                }
+            };
+
+            accumulator.process_finalized(&self.blockchain, macro_head)?;
+            if !accumulator.is_complete() {
+                *self.current.lock() = Some(accumulator);
+                return Ok(());
+            }
+
+            debug!(
+                epoch_number = accumulator.epoch_number(),
+                "Storing validator uptime"
+            );
+            let mut write_txn = self.env.write_transaction();
+            write_txn.put(
+                &UptimeTable,
+                &accumulator.epoch_number(),
+                &EpochUptime {
+                    validators: accumulator.performances(),
+                },
+            );
+            write_txn.commit();
+            *self.current.lock() = None;
+        }
+    }
+
+    /// Runs [`Self::update`] on the blocking thread pool, since it reads the blocks of whole
+    /// epochs from the database.
+    async fn update_blocking(&self) {
+        let index = self.clone();
+        let result = spawn_blocking(move || index.update())
+            .await
+            .expect("Updating the validator uptime index should not panic");
+        if let Err(error) = result {
+            warn!(%error, "Failed to update the validator uptime index");
+        }
+    }
+
+    /// Keeps the index up to date. This future doesn't terminate.
+    pub async fn run(self) {
+        let mut blockchain_events = self.blockchain.read().notifier_as_stream();
+
+        self.update_blocking().await;
+        while let Some(event) = blockchain_events.next().await {
+            // Only finalized blocks are taken into account.
+            if matches!(
+                event,
+                BlockchainEvent::Finalized(_) | BlockchainEvent::EpochFinalized(_)
+            ) {
+                self.update_blocking().await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nimiq_test_log::test;
+    use nimiq_test_utils::block_production::TemporaryBlockProducer;
+
+    use super::*;
+
+    #[test]
+    fn it_indexes_finalized_epochs() {
+        let producer = TemporaryBlockProducer::new();
+        let index = UptimeIndex::new(
+            MdbxDatabase::new_volatile(Default::default()).unwrap(),
+            Arc::clone(&producer.blockchain),
+        );
+
+        // The index starts with the current epoch.
+        producer.next_block(vec![], false);
+        index.update().unwrap();
+        assert_eq!(index.last_indexed_epoch(), None);
+
+        // Fill the first epoch and one batch of the second one.
+        let num_blocks = Policy::blocks_per_epoch() + Policy::blocks_per_batch() - 1;
+        for i in 0..num_blocks {
+            producer.next_block(vec![], i == 1);
+        }
+        index.update().unwrap();
+
+        assert_eq!(index.last_indexed_epoch(), Some(1));
+        let validator_address = producer
+            .blockchain
+            .read()
+            .current_validators()
+            .unwrap()
+            .iter()
+            .next()
+            .unwrap()
+            .address
+            .clone();
+
+        let epochs = index.get_epochs(0, 10, Some(&validator_address));
+        assert_eq!(epochs.len(), 2);
+
+        let (epoch_number, uptime) = &epochs[0];
+        assert_eq!(*epoch_number, 1);
+        let performance = &uptime.validators[&validator_address];
+        assert_eq!(
+            performance.block_number,
+            Policy::election_block_of(1).unwrap()
+        );
+        assert_eq!(performance.skip_blocks_caused, 1);
+        assert_eq!(performance.macro_blocks, Policy::batches_per_epoch() as u32);
+        assert_eq!(performance.macro_blocks_signed, performance.macro_blocks);
+
+        let (epoch_number, uptime) = &epochs[1];
+        assert_eq!(*epoch_number, 2);
+        assert_eq!(uptime.validators[&validator_address].macro_blocks, 1);
+    }
+}
@@ -10,11 +10,14 @@ use std::{
     time::Duration,
 };
 
-use futures::stream::StreamExt;
+use futures::{
+    future,
+    stream::{BoxStream, StreamExt},
+};
 use nimiq_block::{Block, BlockType, EquivocationProof};
-use nimiq_blockchain::{interface::HistoryInterface, BlockProducer, Blockchain};
+use nimiq_blockchain::{interface::HistoryInterface, Blockchain};
 use nimiq_blockchain_interface::{AbstractBlockchain, BlockchainEvent, ForkEvent, PushResult};
-use nimiq_bls::{lazy::LazyPublicKey, KeyPair as BlsKeyPair};
+use nimiq_bls::lazy::LazyPublicKey;
 use nimiq_consensus::{
     messages::{BlockBodyTopic, BlockHeaderMessage, BlockHeaderTopic},
     Consensus, ConsensusEvent, ConsensusProxy,
@@ -25,28 +28,38 @@ use nimiq_database::{
     traits::{Database, ReadTransaction, WriteTransaction},
 };
 use nimiq_hash::{Blake2bHash, Hash};
-use nimiq_keys::{Address, KeyPair as SchnorrKeyPair};
+use nimiq_keys::Address;
 use nimiq_mempool::config::MempoolConfig;
 use nimiq_mempool_task::MempoolTask;
 use nimiq_network_interface::{
     network::{MsgAcceptance, Network, NetworkEvent, SubscribeEvents},
     request::request_handler,
 };
-use nimiq_primitives::{coin::Coin, policy::Policy};
-use nimiq_transaction_builder::TransactionBuilder;
+use nimiq_primitives::{networks::NetworkId, policy::Policy};
 use nimiq_utils::spawn;
 use nimiq_validator_network::{PubsubId, ValidatorNetwork};
 use parking_lot::RwLock;
+use tokio::sync::{mpsc, watch};
 #[cfg(feature = "metrics")]
 use tokio_metrics::TaskMonitor;
-use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::wrappers::{BroadcastStream, UnboundedReceiverStream};
 
 use crate::{
-    aggregation::tendermint::{proposal::RequestProposal, state::MacroState},
+    aggregation::{
+        tendermint::{proposal::RequestProposal, state::MacroState},
+        AggregationConfig, AggregationTelemetry,
+    },
+    equivocation::{EquivocationMonitor, EquivocationRecord, EquivocationStatus, Evidence},
+    failover::{role_changes, FailoverConfig, FailoverRole, FailoverStatus},
     jail::EquivocationProofPool,
+    key_rotation::KeyRotation,
     micro::{ProduceMicroBlock, ProduceMicroBlockEvent},
-    proposal_buffer::{ProposalBuffer, ProposalReceiver},
+    proposal_buffer::{ProposalBuffer, ProposalReceiver, ProposalSender},
     r#macro::{MappedReturn, ProduceMacroBlock, ProposalTopic},
+    signer::ValidatorSigner,
+    signing_history::SigningHistory,
+    tendermint_journal::{JournalRecorder, TendermintJournal},
+    transaction_selection::TransactionSelection,
 };
 
 #[derive(PartialEq)]
@@ -69,24 +82,29 @@ struct InactivityState {
 
 pub struct ValidatorProxy {
     pub validator_address: Arc<RwLock<Address>>,
-    pub signing_key: Arc<RwLock<SchnorrKeyPair>>,
-    pub voting_key: Arc<RwLock<BlsKeyPair>>,
-    pub fee_key: Arc<RwLock<SchnorrKeyPair>>,
+    pub signer: Arc<dyn ValidatorSigner>,
     pub automatic_reactivate: Arc<AtomicBool>,
     pub slot_band: Arc<RwLock<Option<u16>>>,
     pub consensus_state: Arc<RwLock<ConsensusState>>,
+    pub signing_history: Arc<SigningHistory>,
+    /// The status of the active/standby failover, if enabled.
+    pub failover_status: Arc<RwLock<Option<FailoverStatus>>>,
+    pub key_rotation: Arc<KeyRotation>,
+    pub aggregation_telemetry: AggregationTelemetry,
 }
 
 impl Clone for ValidatorProxy {
     fn clone(&self) -> Self {
         Self {
             validator_address: Arc::clone(&self.validator_address),
-            signing_key: Arc::clone(&self.signing_key),
-            voting_key: Arc::clone(&self.voting_key),
-            fee_key: Arc::clone(&self.fee_key),
+            signer: Arc::clone(&self.signer),
             automatic_reactivate: Arc::clone(&self.automatic_reactivate),
             slot_band: Arc::clone(&self.slot_band),
             consensus_state: Arc::clone(&self.consensus_state),
+            signing_history: Arc::clone(&self.signing_history),
+            failover_status: Arc::clone(&self.failover_status),
+            key_rotation: Arc::clone(&self.key_rotation),
+            aggregation_telemetry: self.aggregation_telemetry.clone(),
         }
     }
 }
@@ -105,15 +123,20 @@ where
     env: MdbxDatabase,
 
     validator_address: Arc<RwLock<Address>>,
-    signing_key: Arc<RwLock<SchnorrKeyPair>>,
-    voting_key: Arc<RwLock<BlsKeyPair>>,
-    fee_key: Arc<RwLock<SchnorrKeyPair>>,
+    signer: Arc<dyn ValidatorSigner>,
 
     proposal_receiver: ProposalReceiver<TValidatorNetwork>,
+    /// Whether to subscribe to the Tendermint proposals.
+    proposal_subscription: watch::Sender<bool>,
 
     consensus_event_rx: BroadcastStream<ConsensusEvent>,
     network_event_rx: SubscribeEvents<<TValidatorNetwork::NetworkType as Network>::PeerId>,
     fork_event_rx: BroadcastStream<ForkEvent>,
+    double_vote_tx: mpsc::UnboundedSender<EquivocationProof>,
+    double_vote_rx: UnboundedReceiverStream<EquivocationProof>,
+
+    equivocation_monitor: Option<EquivocationMonitor>,
+    equivocation_rx: Option<BroadcastStream<EquivocationRecord>>,
 
     slot_band: Arc<RwLock<Option<u16>>>,
     consensus_state: Arc<RwLock<ConsensusState>>,
@@ -124,6 +147,19 @@ where
     macro_state: Arc<RwLock<Option<MacroState>>>,
 
     micro_producer: Option<ProduceMicroBlock<TValidatorNetwork>>,
+    signing_history: Arc<SigningHistory>,
+    tendermint_journal: Option<JournalRecorder>,
+
+    failover: Option<BoxStream<'static, FailoverRole>>,
+    failover_status: Arc<RwLock<Option<FailoverStatus>>>,
+    dht_ready: bool,
+
+    key_rotation: Arc<KeyRotation>,
+
+    aggregation_config: AggregationConfig,
+    aggregation_telemetry: AggregationTelemetry,
+
+    transaction_selection: Arc<TransactionSelection>,
 
     pub mempool_task: MempoolTask<TValidatorNetwork::NetworkType>,
 }
@@ -143,9 +179,12 @@ where
         network: Arc<TValidatorNetwork>,
         validator_address: Address,
         automatic_reactivate: bool,
-        signing_key: SchnorrKeyPair,
-        voting_key: BlsKeyPair,
-        fee_key: SchnorrKeyPair,
+        signer: Arc<dyn ValidatorSigner>,
+        failover: Option<FailoverConfig>,
+        journal_tendermint: bool,
+        aggregation_config: AggregationConfig,
+        transaction_selection: TransactionSelection,
+        equivocation_monitor: Option<EquivocationMonitor>,
         mempool_config: MempoolConfig,
     ) -> Self {
         let consensus_event_rx = consensus.subscribe_events();
@@ -154,10 +193,33 @@ where
         let fork_event_rx = BroadcastStream::new(blockchain_rg.fork_notifier.subscribe());
         drop(blockchain_rg);
 
+        let (double_vote_tx, double_vote_rx) = mpsc::unbounded_channel();
+        let equivocation_rx = equivocation_monitor
+            .as_ref()
+            .map(|monitor| BroadcastStream::new(monitor.subscribe()));
+
         let network_event_rx = network.subscribe_events();
 
+        // The proofs that were still pending when the node stopped are included again.
+        let mut equivocation_proofs = EquivocationProofPool::new();
+        if let Some(monitor) = &equivocation_monitor {
+            let blockchain = blockchain.read();
+            for record in monitor.get(None) {
+                if let (EquivocationStatus::Pending, Evidence::Proof(proof)) =
+                    (record.status(), record.evidence)
+                {
+                    if proof.is_valid_at(blockchain.block_number() + 1)
+                        && !blockchain
+                            .history_store
+                            .has_equivocation_proof(proof.locator(), None)
+                    {
+                        equivocation_proofs.insert(proof);
+                    }
+                }
+            }
+        }
         let blockchain_state = ConsensusState {
-            equivocation_proofs: EquivocationProofPool::new(),
+            equivocation_proofs,
         };
 
         env.create_regular_table(&ValidatorTable);
@@ -168,6 +230,11 @@ where
         };
         let macro_state = Arc::new(RwLock::new(macro_state));
 
+        let signing_history = Arc::new(SigningHistory::new(env.clone()));
+        let tendermint_journal =
+            journal_tendermint.then(|| JournalRecorder::new(TendermintJournal::new(env.clone())));
+        let key_rotation = Arc::new(KeyRotation::new(env.clone()));
+
         let (proposal_sender, proposal_receiver) = ProposalBuffer::new(
             Arc::clone(&blockchain),
             Arc::clone(&network),
@@ -178,17 +245,24 @@ where
 
         let automatic_reactivate = Arc::new(AtomicBool::new(automatic_reactivate));
 
+        // With failover enabled, we start on standby until we acquire the lease.
+        let failover_status = Arc::new(RwLock::new(
+            failover
+                .as_ref()
+                .map(|config| FailoverStatus::new(config.node_id.clone())),
+        ));
+        let failover = failover.map(role_changes);
+
         Self::init_network_request_receivers(&consensus.network, &macro_state);
 
-        let network1 = Arc::clone(&network);
-        spawn(async move {
-            network1
-                .subscribe::<ProposalTopic<TValidatorNetwork>>()
-                .await
-                .expect("Failed to subscribe to proposal topic")
-                .for_each(|proposal| async { proposal_sender.send(proposal) })
-                .await
-        });
+        // The monitor observes the proposals regardless of whether we are an active validator.
+        let (proposal_subscription, subscribed) = watch::channel(equivocation_monitor.is_some());
+        spawn(Self::subscribe_proposals(
+            Arc::clone(&network),
+            proposal_sender,
+            equivocation_monitor.clone(),
+            subscribed,
+        ));
 
         Self {
             consensus: consensus.proxy(),
@@ -199,15 +273,19 @@ where
             env,
 
             validator_address: Arc::new(RwLock::new(validator_address)),
-            signing_key: Arc::new(RwLock::new(signing_key)),
-            voting_key: Arc::new(RwLock::new(voting_key)),
-            fee_key: Arc::new(RwLock::new(fee_key)),
+            signer,
 
             proposal_receiver,
+            proposal_subscription,
 
             consensus_event_rx,
             network_event_rx,
             fork_event_rx,
+            double_vote_tx,
+            double_vote_rx: UnboundedReceiverStream::new(double_vote_rx),
+
+            equivocation_monitor,
+            equivocation_rx,
 
             slot_band: Arc::new(RwLock::new(None)),
             consensus_state: Arc::new(RwLock::new(blockchain_state)),
@@ -218,11 +296,77 @@ where
             macro_state: Arc::clone(&macro_state),
 
             micro_producer: None,
+            signing_history,
+            tendermint_journal,
+
+            failover,
+            failover_status,
+            dht_ready: false,
+
+            key_rotation,
+
+            aggregation_config,
+            aggregation_telemetry: AggregationTelemetry::default(),
+
+            transaction_selection: Arc::new(transaction_selection),
 
             mempool_task: mempool,
         }
     }
 
+    /// Subscribes to the Tendermint proposals while `subscribed` is set and passes them to the
+    /// proposal buffer and the equivocation monitor. Ends once the validator is dropped.
+    async fn subscribe_proposals(
+        network: Arc<TValidatorNetwork>,
+        proposal_sender: ProposalSender<TValidatorNetwork>,
+        monitor: Option<EquivocationMonitor>,
+        mut subscribed: watch::Receiver<bool>,
+    ) {
+        while subscribed.wait_for(|subscribed| *subscribed).await.is_ok() {
+            let proposals = network
+                .subscribe::<ProposalTopic<TValidatorNetwork>>()
+                .await
+                .expect("Failed to subscribe to proposal topic");
+            let unsubscribed = async {
+                let _ = subscribed.wait_for(|subscribed| !*subscribed).await;
+            };
+
+            proposals
+                .take_until(unsubscribed)
+                .for_each(|proposal| {
+                    if let Some(monitor) = &monitor {
+                        monitor.observe_proposal(&proposal.0);
+                    }
+                    proposal_sender.send(proposal);
+                    future::ready(())
+                })
+                .await;
+
+            // Still being subscribed means that the network or the validator is gone.
+            if *subscribed.borrow() {
+                return;
+            }
+            if let Err(error) = network
+                .unsubscribe::<ProposalTopic<TValidatorNetwork>>()
+                .await
+            {
+                warn!(%error, "Failed to unsubscribe from proposal topic");
+            }
+        }
+    }
+
+    /// Only active elected validators need the Tendermint proposals, unless the equivocation
+    /// monitor observes them.
+    fn update_proposal_subscription(&self) {
+        let subscribe =
+            self.equivocation_monitor.is_some() || (self.is_elected() && self.is_active());
+        self.proposal_subscription.send_if_modified(|subscribed| {
+            let modified = *subscribed != subscribe;
+            *subscribed = subscribe;
+            modified
+        });
+    }
+
     fn init_network_request_receivers(
         network: &Arc<TValidatorNetwork::NetworkType>,
         macro_state: &Arc<RwLock<Option<MacroState>>>,
@@ -232,6 +376,8 @@ where
     }
 
     fn init(&mut self, head_hash: Option<&Blake2bHash>) {
+        // We might have been restarted after a key rotation became effective.
+        self.switch_keys_if_effective();
         self.init_epoch();
         self.init_block_producer(head_hash);
     }
@@ -308,7 +454,7 @@ where
         self.macro_producer = None;
         self.micro_producer = None;
 
-        if !self.is_elected() || !self.is_synced() {
+        if !self.is_elected() || !self.is_synced() || !self.is_active() {
             return;
         }
 
@@ -324,7 +470,6 @@ where
         let head = blockchain.head();
         let next_block_number = head.block_number() + 1;
         let network_id = head.network();
-        let block_producer = BlockProducer::new(self.signing_key(), self.voting_key());
 
         debug!(
             next_block_number = next_block_number,
@@ -341,13 +486,17 @@ where
                 self.macro_producer = Some(ProduceMacroBlock::new(
                     Arc::clone(&self.blockchain),
                     Arc::clone(&self.network),
-                    block_producer,
+                    Arc::clone(&self.signer),
                     self.validator_slot_band(),
                     active_validators,
                     network_id,
                     next_block_number,
                     self.macro_state.read().clone(),
                     proposal_stream,
+                    self.aggregation_config.tendermint.clone(),
+                    Arc::clone(&self.aggregation_telemetry.tendermint),
+                    self.tendermint_journal.clone(),
+                    self.double_vote_tx.clone(),
                 ));
             }
             BlockType::Micro => {
@@ -363,25 +512,66 @@ where
                 self.micro_producer = Some(ProduceMicroBlock::new(
                     Arc::clone(&self.blockchain),
                     Arc::clone(&self.mempool_task.mempool),
+                    Arc::clone(&self.transaction_selection),
                     Arc::clone(&self.network),
-                    block_producer,
+                    Arc::clone(&self.signer),
+                    Arc::clone(&self.signing_history),
                     self.validator_slot_band(),
                     equivocation_proofs,
                     prev_seed,
                     next_block_number,
                     Self::PRODUCER_TIMEOUT,
                     Self::BLOCK_SEPARATION_TIME,
+                    self.aggregation_config.skip_block.clone(),
+                    Arc::clone(&self.aggregation_telemetry.skip_block),
                 ));
             }
         }
     }
 
+    /// Switches to the keys of a pending key rotation once they are in effect.
+    fn switch_keys_if_effective(&mut self) {
+        let switched = self.key_rotation.switch_if_effective(
+            &self.blockchain.read(),
+            &self.validator_address(),
+            self.signer.as_ref(),
+        );
+
+        // Our validator record must be signed with the new voting key.
+        if switched && self.dht_ready && self.is_active() {
+            self.publish_dht();
+        }
+    }
+
     fn pause(&mut self) {
         *self.slot_band.write() = None;
         self.macro_producer = None;
         self.micro_producer = None;
     }
 
+    fn on_failover_role(&mut self, role: FailoverRole) {
+        let mut failover_status = self.failover_status.write();
+        failover_status
+            .as_mut()
+            .expect("Failover status must exist if failover is enabled")
+            .transition(role);
+        drop(failover_status);
+
+        match role {
+            FailoverRole::Active => {
+                info!("Acquired the validator lease, we are the ACTIVE validator host");
+                self.init(None);
+                if self.dht_ready {
+                    self.publish_dht();
+                }
+            }
+            FailoverRole::Standby => {
+                warn!("Lost the validator lease, we are a STANDBY validator host");
+                self.pause();
+            }
+        }
+    }
+
     fn on_blockchain_event(&mut self, event: BlockchainEvent) {
         match event {
             BlockchainEvent::Extended(ref hash) => self.on_blockchain_extended(hash),
@@ -390,6 +580,7 @@ where
                 self.on_blockchain_extended(hash);
             }
             BlockchainEvent::EpochFinalized(ref hash) => {
+                self.switch_keys_if_effective();
                 self.init_epoch();
                 // The on_blockchain_extended is necessary for the order of events to not matter.
                 self.on_blockchain_extended(hash);
@@ -418,6 +609,14 @@ where
             .equivocation_proofs
             .apply_block(&block);
 
+        // Once a batch is finalized, we will never be asked to sign at its heights again.
+        if block.is_macro() {
+            self.signing_history.prune(block.block_number());
+            if let Some(journal) = &self.tendermint_journal {
+                journal.prune(block.block_number());
+            }
+        }
+
         self.check_reactivate(block.block_number());
         self.init_block_producer(Some(hash));
     }
@@ -576,14 +775,18 @@ where
 
     /// Publish our own validator record to the DHT.
     fn publish_dht(&self) {
-        let key = self.voting_key();
+        let public_key = self.signer.voting_public_key().compress();
+        let signer = Arc::clone(&self.signer);
         let network = Arc::clone(&self.network);
 
         spawn(async move {
-            if let Err(err) = network
-                .set_public_key(&key.public_key.compress(), &key.secret_key)
-                .await
-            {
+            let sign = Box::new(move |data: &[u8]| {
+                signer
+                    .sign_validator_record(data)
+                    .map_err(|error| error!(%error, "Failed to sign our validator record"))
+                    .ok()
+            });
+            if let Err(err) = network.set_public_key(&public_key, sign).await {
                 error!("could not set up DHT record: {:?}", err);
             }
         });
@@ -622,6 +825,15 @@ where
         self.slot_band.read().is_some()
     }
 
+    /// Checks whether we are the active host of this validator, i.e. failover is disabled or we
+    /// hold the lease.
+    fn is_active(&self) -> bool {
+        self.failover_status
+            .read()
+            .as_ref()
+            .map_or(true, |status| status.role == FailoverRole::Active)
+    }
+
     /// Checks whether the validator fulfills the conditions for producing valid blocks.
     /// This includes having consensus, being able to extend the history tree and to enforce transaction validity.
     fn is_synced(&self) -> bool {
@@ -649,17 +861,22 @@ where
             )
     }
 
-    fn reactivate(&self, blockchain: &Blockchain) -> InactivityState {
-        let validity_start_height = blockchain.block_number();
-
-        let reactivate_transaction = TransactionBuilder::new_reactivate_validator(
-            &self.fee_key(),
+    fn reactivate(
+        &self,
+        validity_start_height: u32,
+        network_id: NetworkId,
+    ) -> Option<InactivityState> {
+        let reactivate_transaction = match self.signer.reactivate_transaction(
             self.validator_address(),
-            &self.signing_key(),
-            Coin::ZERO,
             validity_start_height,
-            blockchain.network_id(),
-        );
+            network_id,
+        ) {
+            Ok(transaction) => transaction,
+            Err(error) => {
+                error!(%error, "Failed to create reactivate transaction");
+                return None;
+            }
+        };
         let tx_hash = reactivate_transaction.hash();
 
         let cn = self.consensus.clone();
@@ -674,10 +891,10 @@ where
             }
         });
 
-        InactivityState {
+        Some(InactivityState {
             inactive_tx_hash: tx_hash,
             inactive_tx_validity_window_start: validity_start_height,
-        }
+        })
     }
 
     pub fn validator_slot_band(&self) -> u16 {
@@ -688,27 +905,21 @@ where
         self.validator_address.read().clone()
     }
 
-    pub fn voting_key(&self) -> BlsKeyPair {
-        self.voting_key.read().clone()
-    }
-
-    pub fn signing_key(&self) -> SchnorrKeyPair {
-        self.signing_key.read().clone()
-    }
-
-    pub fn fee_key(&self) -> SchnorrKeyPair {
-        self.fee_key.read().clone()
+    pub fn signer(&self) -> Arc<dyn ValidatorSigner> {
+        Arc::clone(&self.signer)
     }
 
     pub fn proxy(&self) -> ValidatorProxy {
         ValidatorProxy {
             validator_address: Arc::clone(&self.validator_address),
-            signing_key: Arc::clone(&self.signing_key),
-            voting_key: Arc::clone(&self.voting_key),
-            fee_key: Arc::clone(&self.fee_key),
+            signer: Arc::clone(&self.signer),
             automatic_reactivate: Arc::clone(&self.automatic_reactivate),
             slot_band: Arc::clone(&self.slot_band),
             consensus_state: Arc::clone(&self.consensus_state),
+            signing_history: Arc::clone(&self.signing_history),
+            failover_status: Arc::clone(&self.failover_status),
+            key_rotation: Arc::clone(&self.key_rotation),
+            aggregation_telemetry: self.aggregation_telemetry.clone(),
         }
     }
 
@@ -744,6 +955,15 @@ where
             }
         }
 
+        // Process changes of our failover role.
+        while let Some(Poll::Ready(Some(role))) = self
+            .failover
+            .as_mut()
+            .map(|failover| failover.poll_next_unpin(cx))
+        {
+            self.on_failover_role(role);
+        }
+
         // Process blockchain updates.
         while let Poll::Ready(Some(event)) = self.mempool_task.poll_next_unpin(cx) {
             trace!(
@@ -764,8 +984,33 @@ where
             }
         }
 
+        // Process the double votes detected in our Tendermint aggregations.
+        while let Poll::Ready(Some(proof)) = self.double_vote_rx.poll_next_unpin(cx) {
+            if let Some(monitor) = &self.equivocation_monitor {
+                monitor.report(Evidence::Proof(proof.clone()), false);
+            }
+            self.on_equivocation_proof(proof);
+        }
+
+        // Process the equivocation proofs received or detected by the equivocation monitor.
+        while let Some(Poll::Ready(Some(result))) = self
+            .equivocation_rx
+            .as_mut()
+            .map(|equivocation_rx| equivocation_rx.poll_next_unpin(cx))
+        {
+            if let Ok(EquivocationRecord {
+                evidence: Evidence::Proof(proof),
+                ..
+            }) = result
+            {
+                self.on_equivocation_proof(proof);
+            }
+        }
+
+        self.update_proposal_subscription();
+
         // If we are an active validator, participate in block production.
-        if self.is_synced() && self.is_elected() {
+        if self.is_synced() && self.is_elected() && self.is_active() {
             if self.macro_producer.is_some() {
                 self.poll_macro(cx);
             }
@@ -775,7 +1020,8 @@ where
         }
 
         // Once the validator can be active is established, check the validator staking state.
-        if self.is_synced() {
+        // Only the active host sends reactivate transactions.
+        if self.is_synced() && self.is_active() {
             let blockchain = self.blockchain.read();
             match self.get_staking_state(&blockchain) {
                 ValidatorStakingState::Active => {
@@ -794,9 +1040,11 @@ where
                             .unwrap_or(true)
                         && self.automatic_reactivate.load(Ordering::Acquire)
                     {
-                        let inactivity_state = self.reactivate(&blockchain);
+                        let validity_start_height = blockchain.block_number();
+                        let network_id = blockchain.network_id();
+                        // The signer is not asked for the transaction under the blockchain lock.
                         drop(blockchain);
-                        self.validator_state = Some(inactivity_state);
+                        self.validator_state = self.reactivate(validity_start_height, network_id);
                     }
                 }
                 ValidatorStakingState::NoStake | ValidatorStakingState::Unknown => {}
@@ -807,7 +1055,11 @@ where
         while let Poll::Ready(Some(result)) = self.network_event_rx.poll_next_unpin(cx) {
             match result {
                 Ok(NetworkEvent::DhtReady) => {
-                    self.publish_dht();
+                    // A standby host publishes our record once it takes over.
+                    self.dht_ready = true;
+                    if self.is_active() {
+                        self.publish_dht();
+                    }
                 }
                 Ok(_) => {}
                 Err(e) => error!("{}", e),
@@ -817,3 +1069,103 @@ where
         Poll::Pending
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use nimiq_block::ForkProof;
+    use nimiq_genesis::NetworkInfo;
+    use nimiq_network_mock::{MockHub, MockNetwork};
+    use nimiq_test_log::test;
+    use nimiq_test_utils::{
+        block_production::TemporaryBlockProducer,
+        blockchain::{signing_key, voting_key},
+        node::Node,
+    };
+    use nimiq_validator_network::network_impl::ValidatorNetworkImpl;
+
+    use super::*;
+    use crate::signer::LocalSigner;
+
+    fn validator(
+        node: &Node<MockNetwork>,
+        monitor: EquivocationMonitor,
+    ) -> Validator<ValidatorNetworkImpl<MockNetwork>> {
+        let consensus = node.consensus.as_ref().unwrap();
+        Validator::new(
+            node.environment.clone(),
+            consensus,
+            Arc::clone(&node.blockchain),
+            Arc::new(ValidatorNetworkImpl::new(Arc::clone(&consensus.network))),
+            Address::from([1; 20]),
+            false,
+            Arc::new(LocalSigner::new(signing_key(), voting_key(), signing_key())),
+            None,
+            false,
+            Default::default(),
+            Default::default(),
+            Some(monitor),
+            MempoolConfig::default(),
+        )
+    }
+
+    #[test(tokio::test)]
+    async fn it_keeps_pending_equivocation_proofs_across_restarts() {
+        let network_info = NetworkInfo::from_network_id(NetworkId::UnitAlbatross);
+        let node = Node::<MockNetwork>::new_history(
+            1,
+            network_info.genesis_block(),
+            network_info.genesis_accounts(),
+            &mut Some(MockHub::default()),
+            false,
+        )
+        .await;
+
+        // A fork proof for the next block of the same chain.
+        let producer = TemporaryBlockProducer::new();
+        let validator_address = producer
+            .blockchain
+            .read()
+            .current_validators()
+            .unwrap()
+            .iter()
+            .next()
+            .unwrap()
+            .address
+            .clone();
+        let block1 = producer.next_block_no_push(vec![1], false).unwrap_micro();
+        let block2 = producer.next_block_no_push(vec![2], false).unwrap_micro();
+        let proof: EquivocationProof = ForkProof::new(
+            validator_address,
+            block1.header,
+            block1.justification.unwrap().unwrap_micro(),
+            block2.header,
+            block2.justification.unwrap().unwrap_micro(),
+        )
+        .into();
+
+        let monitor = EquivocationMonitor::new(
+            node.environment.clone(),
+            Arc::clone(&node.blockchain),
+            false,
+        );
+        assert!(monitor.report(Evidence::Proof(proof.clone()), false));
+        let validator1 = validator(&node, monitor);
+        drop(validator1);
+
+        // After the restart, the proof is still pending and thus included in the pool.
+        let monitor = EquivocationMonitor::new(
+            node.environment.clone(),
+            Arc::clone(&node.blockchain),
+            false,
+        );
+        let validator2 = validator(&node, monitor);
+        assert_eq!(
+            validator2
+                .consensus_state
+                .read()
+                .equivocation_proofs
+                .get_equivocation_proofs_for_block(usize::MAX),
+            vec![proof]
+        );
+    }
+}
@@ -2,6 +2,7 @@ use std::{sync::Arc, task::Poll, time::Duration};
 
 use futures::{future, StreamExt};
 use nimiq_block::{MultiSignature, SignedSkipBlockInfo, SkipBlockInfo};
+use nimiq_blockchain::Blockchain;
 use nimiq_blockchain_interface::{AbstractBlockchain, BlockchainEvent};
 use nimiq_bls::{AggregateSignature, KeyPair as BlsKeyPair};
 use nimiq_collections::BitSet;
@@ -14,7 +15,7 @@ use nimiq_network_interface::{
     request::{MessageMarker, RequestCommon},
 };
 use nimiq_network_libp2p::Network;
-use nimiq_network_mock::{MockHub, MockNetwork};
+use nimiq_network_mock::{LinkConditions, MockHub, MockNetwork};
 use nimiq_primitives::{networks::NetworkId, policy::Policy};
 use nimiq_test_log::test;
 use nimiq_test_utils::{
@@ -26,6 +27,7 @@ use nimiq_test_utils::{
 use nimiq_time::{sleep, timeout};
 use nimiq_utils::spawn;
 use nimiq_validator::aggregation::skip_block::SignedSkipBlockMessage;
+use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -178,6 +180,114 @@ async fn four_validators_can_do_skip_block() {
     assert!(blockchain.read().block_number() > Policy::genesis_block_number());
 }
 
+/// Waits until the given blockchain reached `block_number`.
+async fn wait_for_block_number(blockchain: &Arc<RwLock<Blockchain>>, block_number: u32) {
+    let blockchain2 = Arc::clone(blockchain);
+    let stop_fut = future::poll_fn(move |_cx| {
+        if blockchain2.read().block_number() < block_number {
+            Poll::Pending
+        } else {
+            Poll::Ready(())
+        }
+    });
+    let events = blockchain.read().notifier_as_stream();
+    events
+        .take_until(stop_fut)
+        .for_each(|_| future::ready(()))
+        .await;
+}
+
+#[test(tokio::test)]
+async fn four_validators_can_create_micro_blocks_with_latency_and_loss() {
+    let mut hub = Some(MockHub::with_seed(42));
+    let env =
+        MdbxDatabase::new_volatile(Default::default()).expect("Could not open a volatile database");
+
+    let validators =
+        build_validators::<MockNetwork>(env, &(17u64..=20u64).collect::<Vec<_>>(), &mut hub, false)
+            .await;
+
+    hub.as_ref()
+        .unwrap()
+        .set_default_link_conditions(LinkConditions {
+            latency: Duration::from_millis(50),
+            jitter: Duration::from_millis(50),
+            drop_rate: 0.01,
+            bandwidth: Some(1_000_000),
+        });
+
+    let blockchain = Arc::clone(&validators.first().unwrap().blockchain);
+
+    for validator in validators {
+        spawn(validator);
+    }
+
+    timeout(
+        Duration::from_secs(120),
+        wait_for_block_number(&blockchain, 30 + Policy::genesis_block_number()),
+    )
+    .await
+    .expect("Validators didn't make progress under the link conditions");
+}
+
+#[test(tokio::test)]
+async fn partitioned_validator_catches_up_after_heal() {
+    let mut hub = Some(MockHub::with_seed(7));
+    let env =
+        MdbxDatabase::new_volatile(Default::default()).expect("Could not open a volatile database");
+
+    let validators =
+        build_validators::<MockNetwork>(env, &(21u64..=24u64).collect::<Vec<_>>(), &mut hub, false)
+            .await;
+
+    // Cut off one validator. The remaining three hold enough slots to keep producing blocks,
+    // using skip blocks for the slots of the partitioned validator.
+    let isolated = validators.last().unwrap();
+    let isolated_address = isolated.consensus.network.address();
+    let isolated_blockchain = Arc::clone(&isolated.blockchain);
+    let blockchain = Arc::clone(&validators.first().unwrap().blockchain);
+    let hub = hub.unwrap();
+    hub.partition(vec![vec![isolated_address]]);
+
+    for validator in validators {
+        spawn(validator);
+    }
+
+    let target = 10 + Policy::genesis_block_number();
+    timeout(
+        Duration::from_secs(120),
+        wait_for_block_number(&blockchain, target),
+    )
+    .await
+    .expect("Majority didn't make progress during the partition");
+    assert!(isolated_blockchain.read().block_number() < target);
+
+    // After healing, the isolated validator must catch up with the majority.
+    hub.heal();
+    let target = blockchain.read().block_number() + 5;
+    timeout(
+        Duration::from_secs(120),
+        wait_for_block_number(&isolated_blockchain, target),
+    )
+    .await
+    .expect("Isolated validator didn't catch up after the partition healed");
+
+    timeout(
+        Duration::from_secs(30),
+        wait_for_block_number(&blockchain, target),
+    )
+    .await
+    .unwrap();
+
+    // Both are on the same chain.
+    let isolated_block = isolated_blockchain
+        .read()
+        .get_block_at(target, false, None)
+        .unwrap();
+    let block = blockchain.read().get_block_at(target, false, None).unwrap();
+    assert_eq!(isolated_block.hash(), block.hash());
+}
+
 fn create_skip_block_update(
     skip_block_info: SkipBlockInfo,
     key_pair: BlsKeyPair,
@@ -293,7 +403,12 @@ async fn validator_can_catch_up() {
     // Manually construct a skip block for the validator
     let vc = create_skip_block_update(
         skip_block_info,
-        validator.voting_key(),
+        validator
+            .signer()
+            .local_keys()
+            .unwrap()
+            .voting_key()
+            .clone(),
         validator.validator_slot_band(),
         &slots,
     );
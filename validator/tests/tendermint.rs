@@ -1,14 +1,21 @@
 use std::sync::Arc;
 
 use nimiq_blockchain_interface::AbstractBlockchain;
+use nimiq_database::mdbx::MdbxDatabase;
 use nimiq_network_libp2p::Network;
 use nimiq_network_mock::MockHub;
 use nimiq_primitives::{networks::NetworkId, policy::Policy};
 use nimiq_tendermint::{ProposalMessage, Protocol, SignedProposalMessage};
 use nimiq_test_log::test;
 use nimiq_test_utils::{block_production::TemporaryBlockProducer, test_network::TestNetwork};
-use nimiq_validator::{aggregation::tendermint::proposal::Header, tendermint::TendermintProtocol};
+use nimiq_validator::{
+    aggregation::tendermint::proposal::Header,
+    signer::LocalSigner,
+    tendermint::TendermintProtocol,
+    tendermint_journal::{JournalRecorder, TendermintJournal},
+};
 use nimiq_validator_network::network_impl::ValidatorNetworkImpl;
+use tokio::sync::mpsc;
 
 #[test(tokio::test)]
 async fn it_verifies_inferior_chain_proposals() {
@@ -120,11 +127,21 @@ async fn it_verifies_inferior_chain_proposals() {
     let interface = TendermintProtocol::new(
         Arc::clone(&blockchain2),
         val_net,
-        temp_producer2.producer.clone(),
+        Arc::new(LocalSigner::new(
+            temp_producer2.producer.signing_key.clone(),
+            temp_producer2.producer.voting_key.clone(),
+            temp_producer2.producer.signing_key.clone(),
+        )),
         current_validators,
         0,
         NetworkId::UnitAlbatross,
         blockchain2.read().head().block_number() + 1,
+        Default::default(),
+        Default::default(),
+        Some(JournalRecorder::new(TendermintJournal::new(
+            MdbxDatabase::new_volatile(Default::default()).unwrap(),
+        ))),
+        mpsc::unbounded_channel().0,
     );
 
     // Make sure the main chain proposal is acceptable.
@@ -52,8 +52,9 @@ use crate::{
     },
     transaction::{
         PlainTransactionDetails, PlainTransactionDetailsArrayType, PlainTransactionDetailsType,
-        PlainTransactionReceipt, PlainTransactionReceiptArrayType, PlainTransactionRecipientData,
-        Transaction, TransactionAnyType, TransactionState,
+        PlainTransactionInclusion, PlainTransactionInclusionType, PlainTransactionReceipt,
+        PlainTransactionReceiptArrayType, PlainTransactionRecipientData, Transaction,
+        TransactionAnyType, TransactionState,
     },
     utils::from_network_id,
 };
@@ -427,17 +428,17 @@ impl Client {
 
     /// Fetches a block by its height (block number).
     ///
-    /// Throws if the client does not have the block.
+    /// If the client does not have the block, it is fetched from the network together with a
+    /// proof that it is part of the chain.
     ///
-    /// Fetching blocks from the network is not yet available.
+    /// Throws if the block is not yet known or on network errors.
     #[wasm_bindgen(js_name = getBlockAt)]
     pub async fn get_block_at(&self, height: u32) -> Result<PlainBlockType, JsError> {
         let block = self
             .inner
             .consensus_proxy()
-            .blockchain
-            .read()
-            .get_block_at(height, false)?;
+            .request_block_at(height, false, 1)
+            .await?;
         Ok(serde_wasm_bindgen::to_value(&PlainBlock::from_block(&block))?.into())
     }
 
@@ -656,6 +657,37 @@ impl Client {
         Ok(serde_wasm_bindgen::to_value(&details)?.into())
     }
 
+    /// Verifies that the transaction with the given hash is included in the blockchain.
+    ///
+    /// The inclusion proof is fetched from the network and verified against the blocks known to
+    /// the client, so this works for any historic transaction. Returns the transaction details and
+    /// whether its inclusion is finalized by a checkpoint or election block.
+    #[wasm_bindgen(js_name = verifyTransactionInclusion)]
+    pub async fn verify_transaction_inclusion(
+        &self,
+        hash: String,
+        min_peers: Option<usize>,
+    ) -> Result<PlainTransactionInclusionType, JsError> {
+        let hash =
+            Blake2bHash::from_str(&hash).map_err(|_| JsError::new("Invalid transaction hash"))?;
+        let (hist_tx, confirmation_level) = self
+            .inner
+            .consensus_proxy()
+            .verify_transaction_inclusion(hash, min_peers.unwrap_or(1))
+            .await?;
+        let transaction = PlainTransactionDetails::try_from_historic_transaction(
+            hist_tx,
+            self.inner.blockchain_head().block_number(),
+        )
+        .ok_or_else(|| JsError::new("Transaction is a penalty inherent"))?;
+
+        let inclusion = PlainTransactionInclusion {
+            transaction,
+            confirmation_level: confirmation_level.into(),
+        };
+        Ok(serde_wasm_bindgen::to_value(&inclusion)?.into())
+    }
+
     /// This function is used to query the network for transaction receipts from and to a
     /// specific address, that have been included in the chain.
     ///
@@ -1211,6 +1211,41 @@ impl PlainTransactionDetails {
     }
 }
 
+/// Describes how final the inclusion of a transaction in the blockchain is, as verified by the client.
+#[cfg(feature = "client")]
+#[derive(Clone, serde::Serialize, serde::Deserialize, Tsify)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConfirmationLevel {
+    /// The transaction is included in a micro block that is not finalized yet.
+    Micro,
+    /// The transaction is included in a batch of the current epoch that is finalized by a checkpoint block.
+    MacroFinalized,
+    /// The transaction is included in an epoch that is finalized by an election block.
+    EpochFinalized,
+}
+
+#[cfg(feature = "client")]
+impl From<nimiq_consensus::consensus::consensus_proxy::ConfirmationLevel> for ConfirmationLevel {
+    fn from(level: nimiq_consensus::consensus::consensus_proxy::ConfirmationLevel) -> Self {
+        use nimiq_consensus::consensus::consensus_proxy::ConfirmationLevel as Level;
+        match level {
+            Level::Micro => ConfirmationLevel::Micro,
+            Level::MacroFinalized => ConfirmationLevel::MacroFinalized,
+            Level::EpochFinalized => ConfirmationLevel::EpochFinalized,
+        }
+    }
+}
+
+/// A transaction whose inclusion in the blockchain has been verified by the client, together with
+/// how final its inclusion is.
+#[cfg(feature = "client")]
+#[derive(serde::Serialize, Tsify)]
+#[serde(rename_all = "camelCase")]
+pub struct PlainTransactionInclusion {
+    pub transaction: PlainTransactionDetails,
+    pub confirmation_level: ConfirmationLevel,
+}
+
 /// JSON-compatible and human-readable format of transaction receipts.
 #[cfg(feature = "client")]
 #[derive(serde::Serialize, serde::Deserialize, Tsify)]
@@ -1246,6 +1281,9 @@ extern "C" {
 
     #[wasm_bindgen(typescript_type = "PlainTransactionReceipt[]")]
     pub type PlainTransactionReceiptArrayType;
+
+    #[wasm_bindgen(typescript_type = "PlainTransactionInclusion")]
+    pub type PlainTransactionInclusionType;
 }
 
 #[cfg(feature = "primitives")]